@@ -0,0 +1,84 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Deterministic synthetic blocks shared by the benchmarks, built with the
+//! `testing` feature's metadata and event builders.
+
+#![allow(dead_code)]
+
+use flamewire_bittensor_indexer::testing::{events, pallet_metadata, EventRecord};
+use flamewire_bittensor_indexer::ChainEvent;
+use parity_scale_codec::Encode;
+use scale_info::TypeInfo;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::{Events, Phase};
+
+/// Events per benchmarked block.
+pub const BLOCK_SIZES: [usize; 2] = [100, 1_000];
+
+pub const PALLET: &str = "SubtensorModule";
+
+#[derive(Encode, TypeInfo)]
+pub enum SubtensorEvent {
+    StakeAdded {
+        hotkey: [u8; 32],
+        netuid: u16,
+        amount: u64,
+    },
+    StakeRemoved {
+        hotkey: [u8; 32],
+        netuid: u16,
+        amount: u64,
+    },
+    AxonServed {
+        netuid: u16,
+        hotkey: [u8; 32],
+    },
+}
+
+/// `size` events cycling through every variant, with fields derived from
+/// their position so every run decodes the same bytes.
+pub fn block(size: usize) -> Events<SubstrateConfig> {
+    let records = (0..size)
+        .map(|i| {
+            let hotkey = [(i % 251) as u8; 32];
+            let netuid = (i % 32) as u16;
+            let amount = i as u64 * 1_000_000_007;
+            let event = match i % 3 {
+                0 => SubtensorEvent::StakeAdded {
+                    hotkey,
+                    netuid,
+                    amount,
+                },
+                1 => SubtensorEvent::StakeRemoved {
+                    hotkey,
+                    netuid,
+                    amount,
+                },
+                _ => SubtensorEvent::AxonServed { netuid, hotkey },
+            };
+            EventRecord::new(Phase::ApplyExtrinsic(i as u32), event)
+        })
+        .collect();
+    events(pallet_metadata::<SubtensorEvent>(PALLET), records)
+}
+
+/// The events of [`block`], with no fields decoded yet.
+pub fn chain_events(block: &Events<SubstrateConfig>) -> Vec<ChainEvent<SubstrateConfig>> {
+    ChainEvent::from_events(block)
+        .map(|event| event.expect("synthetic events decode"))
+        .collect()
+}
@@ -0,0 +1,87 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Field decoding with and without the per-event cache, and serialization of
+//! [`EventEnvelope`]s as the JSON sinks do it.
+
+mod common;
+
+use common::*;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use flamewire_bittensor_indexer::handler::Context;
+use flamewire_bittensor_indexer::types::EventEnvelope;
+use subxt::config::substrate::SubstrateConfig;
+
+fn field_values(c: &mut Criterion) {
+    let mut bench = c.benchmark_group("field_values");
+    for size in BLOCK_SIZES {
+        let block = block(size);
+        bench.throughput(Throughput::Elements(size as u64));
+        // Fresh events each batch, so every call decodes.
+        bench.bench_with_input(BenchmarkId::new("uncached", size), &block, |b, block| {
+            b.iter_batched(
+                || chain_events(block),
+                |events| {
+                    for event in &events {
+                        event.field_values().unwrap();
+                    }
+                    events
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        // The same events throughout, so only the first call decodes.
+        let events = chain_events(&block);
+        bench.bench_with_input(BenchmarkId::new("cached", size), &events, |b, events| {
+            b.iter(|| {
+                for event in events {
+                    event.field_values().unwrap();
+                }
+            })
+        });
+    }
+    bench.finish();
+}
+
+fn envelope_json(c: &mut Criterion) {
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+    let mut bench = c.benchmark_group("envelope_json");
+    for size in BLOCK_SIZES {
+        let block = block(size);
+        bench.throughput(Throughput::Elements(size as u64));
+        bench.bench_with_input(BenchmarkId::from_parameter(size), &block, |b, block| {
+            b.iter_batched(
+                || chain_events(block),
+                |events| {
+                    let mut buf = Vec::new();
+                    for event in &events {
+                        serde_json::to_writer(&mut buf, &EventEnvelope::from_event(event, &ctx))
+                            .unwrap();
+                        buf.push(b'\n');
+                    }
+                    buf
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    bench.finish();
+}
+
+criterion_group!(benches, field_values, envelope_json);
+criterion_main!(benches);
@@ -0,0 +1,95 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Sequential against parallel [`HandlerGroup`] dispatch of a whole block.
+
+mod common;
+
+use async_trait::async_trait;
+use common::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use flamewire_bittensor_indexer::filters::subtensor;
+use flamewire_bittensor_indexer::handler::{Context, EventFilter, Handler};
+use flamewire_bittensor_indexer::handler_group::HandlerGroup;
+use flamewire_bittensor_indexer::{ChainEvent, IndexerError};
+use std::hint::black_box;
+use subxt::config::substrate::SubstrateConfig;
+
+/// Handlers per group, each behind a different filter as in a typical indexer.
+const FILTERS: [EventFilter; 4] = [
+    subtensor::STAKE_ADDED,
+    subtensor::STAKE_REMOVED,
+    subtensor::AXON_SERVED,
+    EventFilter::all(),
+];
+
+struct CountingHandler {
+    filter: EventFilter,
+}
+
+#[async_trait]
+impl Handler<SubstrateConfig> for CountingHandler {
+    fn event_filter(&self) -> EventFilter {
+        self.filter
+    }
+
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<SubstrateConfig>,
+        _ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        black_box(event.index());
+        Ok(())
+    }
+}
+
+fn group(mut group: HandlerGroup<SubstrateConfig>) -> HandlerGroup<SubstrateConfig> {
+    for filter in FILTERS {
+        group = group.add(CountingHandler { filter });
+    }
+    group
+}
+
+fn dispatch(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+    let mut bench = c.benchmark_group("handler_group_dispatch");
+    for size in BLOCK_SIZES {
+        let events = chain_events(&block(size));
+        bench.throughput(Throughput::Elements(size as u64));
+        for (name, handlers) in [
+            ("sequential", group(HandlerGroup::new())),
+            ("parallel", group(HandlerGroup::parallel())),
+        ] {
+            bench.bench_with_input(BenchmarkId::new(name, size), &events, |b, events| {
+                let (handlers, ctx) = (&handlers, &ctx);
+                b.to_async(&runtime).iter(|| async move {
+                    for event in events {
+                        handlers.handle_event(event, ctx).await.unwrap();
+                    }
+                })
+            });
+        }
+    }
+    bench.finish();
+}
+
+criterion_group!(benches, dispatch);
+criterion_main!(benches);
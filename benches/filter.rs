@@ -0,0 +1,57 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! [`EventFilter::matches_event`] throughput over a block, for filters from
+//! a bare name comparison up to a decoded field condition. Fields are decoded
+//! on the first iteration and cached afterwards, as they are for every handler
+//! after the first within a block.
+
+mod common;
+
+use common::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use flamewire_bittensor_indexer::filters::{balances, subtensor};
+use flamewire_bittensor_indexer::handler::EventFilter;
+use std::hint::black_box;
+
+fn matches(c: &mut Criterion) {
+    let filters = [
+        ("all", EventFilter::all()),
+        ("other_pallet", balances::TRANSFER),
+        ("pallet", subtensor::pallet()),
+        ("event", subtensor::STAKE_ADDED),
+        ("field", subtensor::STAKE_ADDED.with_field_u16("netuid", 7)),
+    ];
+    let mut bench = c.benchmark_group("event_filter_matches");
+    for size in BLOCK_SIZES {
+        let events = chain_events(&block(size));
+        bench.throughput(Throughput::Elements(size as u64));
+        for (name, filter) in filters {
+            bench.bench_with_input(BenchmarkId::new(name, size), &events, |b, events| {
+                b.iter(|| {
+                    events
+                        .iter()
+                        .filter(|event| black_box(filter).matches_event(event))
+                        .count()
+                })
+            });
+        }
+    }
+    bench.finish();
+}
+
+criterion_group!(benches, matches);
+criterion_main!(benches);
@@ -0,0 +1,33 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    grpc();
+}
+
+/// Generate the `EventStream` service from `proto/indexer.proto`, with the
+/// vendored `protoc` unless `PROTOC` points at another one.
+#[cfg(feature = "grpc")]
+fn grpc() {
+    if std::env::var_os("PROTOC").is_none() {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+        std::env::set_var("PROTOC", protoc);
+    }
+    tonic_build::configure()
+        .compile_protos(&["proto/indexer.proto"], &["proto"])
+        .expect("compile proto/indexer.proto");
+}
@@ -15,8 +15,9 @@
  */
 
 use flamewire_bittensor_indexer::prelude::{
-    async_trait, AccountId32, ChainEvent, Context, Decode, DecodeAsType, EventFilter, Handler,
-    HandlerGroup, IndexerBuilder, IndexerError, StaticEvent, SubstrateConfig, WebSocketUrl,
+    async_trait, filters, AccountId32, ChainEvent, Context, Decode, DecodeAsType, DisplayBittensor,
+    EventFilter, Handler, HandlerGroup, IndexerBuilder, IndexerError, Rao, StaticEvent,
+    SubstrateConfig, WebSocketUrl,
 };
 use tracing::info;
 
@@ -39,7 +40,7 @@ struct TransferExtractor;
 #[async_trait]
 impl Handler<SubstrateConfig> for TransferExtractor {
     fn event_filter(&self) -> EventFilter {
-        EventFilter::event("Balances", "Transfer")
+        filters::balances::TRANSFER
     }
 
     async fn handle_event(
@@ -68,9 +69,9 @@ impl Handler<SubstrateConfig> for TransferPrinter {
         if let Some(transfer) = ctx.get_pipeline_data::<TransferEvent>("transfer") {
             info!(
                 block = ctx.block_number,
-                from = %transfer.from,
-                to = %transfer.to,
-                amount = transfer.amount,
+                from = %DisplayBittensor(&transfer.from),
+                to = %DisplayBittensor(&transfer.to),
+                amount = %Rao::from(transfer.amount),
                 "Transfer event"
             );
         }
@@ -33,6 +33,7 @@ impl Handler<SubstrateConfig> for PrintHandler {
             block = ctx.block_number,
             pallet = event.pallet_name(),
             event = event.variant_name(),
+            index = event.index(),
             "Event"
         );
         Ok(())
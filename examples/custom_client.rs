@@ -0,0 +1,77 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Index through a client that reconnects on its own when the node drops the connection.
+//!
+//! `cargo run --example custom_client --features reconnecting-rpc-client`
+
+use flamewire_bittensor_indexer::prelude::{
+    async_trait, ChainEvent, Context, Handler, IndexerBuilder, IndexerError, SubstrateConfig,
+};
+use std::time::Duration;
+use subxt::backend::rpc::reconnecting_rpc_client::{ExponentialBackoff, RpcClient as Reconnecting};
+use subxt::backend::rpc::RpcClient;
+use subxt::OnlineClient;
+use tracing::info;
+
+const NODE_URL: &str = "wss://archive.chain.opentensor.ai:443";
+
+struct PrintHandler;
+
+#[async_trait]
+impl Handler<SubstrateConfig> for PrintHandler {
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<SubstrateConfig>,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        info!(
+            block = ctx.block_number,
+            pallet = event.pallet_name(),
+            event = event.variant_name(),
+            "Event"
+        );
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt().compact().init();
+
+    let reconnecting = Reconnecting::builder()
+        .retry_policy(
+            ExponentialBackoff::from_millis(100)
+                .max_delay(Duration::from_secs(10))
+                .take(10),
+        )
+        .build(NODE_URL.to_string())
+        .await?;
+    let rpc = RpcClient::new(reconnecting);
+    let client = OnlineClient::<SubstrateConfig>::from_rpc_client(rpc.clone()).await?;
+
+    // The run loop's own RPC calls go through the same connection.
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .rpc_client(rpc)
+        .start_from_block(1017)
+        .end_at_block(1133)
+        .add_handler(PrintHandler)
+        .build_with_client(client)
+        .await?;
+
+    indexer.run().await?;
+    Ok(())
+}
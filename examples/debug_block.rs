@@ -0,0 +1,72 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Run the handlers over one block, to step through them in a debugger.
+//!
+//! `cargo run --example debug_block -- 3872114`
+
+use flamewire_bittensor_indexer::prelude::{
+    async_trait, ChainEvent, Context, Handler, IndexerBuilder, IndexerError, SubstrateConfig,
+    WebSocketUrl,
+};
+use tracing::info;
+
+struct PrintHandler;
+
+#[async_trait]
+impl Handler<SubstrateConfig> for PrintHandler {
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<SubstrateConfig>,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        info!(
+            block = ctx.block_number,
+            index = event.index(),
+            pallet = event.pallet_name(),
+            event = event.variant_name(),
+            "Event"
+        );
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt().compact().init();
+
+    let block: u64 = std::env::args()
+        .nth(1)
+        .ok_or("usage: debug_block <block number>")?
+        .parse()?;
+
+    let indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .connect(WebSocketUrl::parse(
+            "wss://archive.chain.opentensor.ai:443",
+        )?)
+        .add_handler(PrintHandler)
+        .build()
+        .await?;
+
+    // No checkpoint is written, so this can run next to a live indexer's store.
+    for (handler, result) in indexer.process_block(block).await? {
+        match result {
+            Ok(()) => info!(handler = %handler, "ok"),
+            Err(e) => info!(handler = %handler, error = %e, "failed"),
+        }
+    }
+    Ok(())
+}
@@ -18,7 +18,9 @@ use flamewire_bittensor_indexer::prelude::{
     async_trait, ChainEvent, Context, Handler, HandlerGroup, IndexerBuilder, IndexerError,
     SubstrateConfig, WebSocketUrl,
 };
-use flamewire_bittensor_indexer::{retry_with_backoff, CircuitBreaker, RetryConfig};
+use flamewire_bittensor_indexer::{
+    retry_with_backoff, CircuitBreaker, CircuitBreakerConfig, RetryConfig,
+};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -40,11 +42,11 @@ impl Handler<SubstrateConfig> for FailingHandler {
             "Handler {} attempt {} on block {}",
             self.id, attempt, ctx.block_number
         );
-        Err(IndexerError::HandlerFailed {
-            handler: self.id.into(),
-            block: ctx.block_number,
-            source: Box::new(std::io::Error::other("fail")),
-        })
+        Err(IndexerError::handler_failed(
+            self.id,
+            ctx.block_number,
+            std::io::Error::other("fail"),
+        ))
     }
 
     async fn handle_error(&self, error: &IndexerError, _ctx: &Context<SubstrateConfig>) {
@@ -53,7 +55,6 @@ impl Handler<SubstrateConfig> for FailingHandler {
 }
 
 struct DatabaseSaver {
-    circuit_breaker: Arc<CircuitBreaker>,
     failure_count: Arc<AtomicUsize>,
 }
 
@@ -64,24 +65,15 @@ impl Handler<SubstrateConfig> for DatabaseSaver {
         _event: &ChainEvent<SubstrateConfig>,
         ctx: &Context<SubstrateConfig>,
     ) -> Result<(), IndexerError> {
-        if self.circuit_breaker.is_open() {
-            println!(
-                "\u{1F6D1} Database circuit breaker OPEN - skipping save for block {}",
-                ctx.block_number
-            );
-            return Ok(());
-        }
-
         let attempt = self.failure_count.fetch_add(1, Ordering::SeqCst);
         if attempt % 4 == 0 || attempt % 4 == 1 {
-            self.circuit_breaker.record_failure();
-            return Err(IndexerError::HandlerFailed {
-                handler: "DatabaseSaver".into(),
-                block: ctx.block_number,
-                source: Box::new(std::io::Error::other("Database connection timeout")),
-            });
+            return Err(IndexerError::handler_failed(
+                "DatabaseSaver",
+                ctx.block_number,
+                std::io::Error::other("Database connection timeout"),
+            )
+            .retryable(true));
         } else {
-            self.circuit_breaker.record_success();
             println!(
                 "\u{2705} Transfer saved to database (block {})",
                 ctx.block_number
@@ -102,7 +94,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let counter = Arc::new(AtomicUsize::new(0));
     let db_failures = Arc::new(AtomicUsize::new(0));
-    let db_breaker = Arc::new(CircuitBreaker::new(2, Duration::from_secs(10)));
 
     // Tolerant mode: errors are logged but processing continues
     let tolerant = HandlerGroup::new()
@@ -114,10 +105,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             id: "B",
             count: counter.clone(),
         })
-        .add(DatabaseSaver {
-            circuit_breaker: db_breaker.clone(),
-            failure_count: db_failures.clone(),
-        });
+        // Two consecutive failures open the saver's breaker; further events are
+        // skipped until the cooldown elapses.
+        .add_with_breaker(
+            DatabaseSaver {
+                failure_count: db_failures.clone(),
+            },
+            CircuitBreakerConfig {
+                threshold: 2,
+                cooldown: Duration::from_secs(10),
+                ..Default::default()
+            },
+        );
+    let db_breaker = tolerant.breakers()[0].clone();
 
     // Strict mode: first error aborts the remaining handlers
     let strict = HandlerGroup::new()
@@ -157,5 +157,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await?;
 
     indexer.run().await?;
+    println!(
+        "DatabaseSaver breaker is {:?}, skipped {} events",
+        db_breaker.state(),
+        db_breaker.skipped()
+    );
     Ok(())
 }
@@ -15,8 +15,9 @@
  */
 
 use flamewire_bittensor_indexer::prelude::{
-    async_trait, AccountId32, ChainEvent, Context, Decode, DecodeAsType, EventFilter, Handler,
-    HandlerGroup, IndexerBuilder, IndexerError, StaticEvent, SubstrateConfig, WebSocketUrl,
+    async_trait, filters, AccountId32, ChainEvent, Context, Decode, DecodeAsType, DisplayBittensor,
+    EventFilter, Handler, HandlerGroup, IndexerBuilder, IndexerError, Rao, StaticEvent,
+    SubstrateConfig, WebSocketUrl,
 };
 
 #[allow(dead_code)]
@@ -37,7 +38,7 @@ struct TransferExtractor;
 #[async_trait]
 impl Handler<SubstrateConfig> for TransferExtractor {
     fn event_filter(&self) -> EventFilter {
-        EventFilter::event("Balances", "Transfer")
+        filters::balances::TRANSFER
     }
 
     async fn handle_event(
@@ -61,11 +62,13 @@ impl Handler<SubstrateConfig> for DatabaseSaver {
         _event: &ChainEvent<SubstrateConfig>,
         ctx: &Context<SubstrateConfig>,
     ) -> Result<(), IndexerError> {
-        if ctx
-            .peek_pipeline_data::<TransferEvent>("transfer")
-            .is_some()
-        {
-            println!("Saving transfer to database");
+        if let Some(transfer) = ctx.peek_pipeline_data::<TransferEvent>("transfer") {
+            println!(
+                "Saving transfer of {} {} -> {} to database",
+                Rao::from(transfer.amount),
+                DisplayBittensor(&transfer.from),
+                DisplayBittensor(&transfer.to)
+            );
         }
         Ok(())
     }
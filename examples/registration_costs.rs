@@ -0,0 +1,111 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Store every registration with the subnet's difficulty and burn at its
+//! block in a SQLite table.
+//!
+//! Run with `cargo run --example registration_costs --features
+//! bittensor-events,sqlite`, then query `SELECT * FROM registrations` in
+//! `registrations.db`.
+
+use flamewire_bittensor_indexer::prelude::{
+    async_trait, ChainEvent, Context, Handler, HandlerGroup, IndexerBuilder, IndexerError,
+    SubstrateConfig, WebSocketUrl,
+};
+use flamewire_bittensor_indexer::presets::registrations::{
+    RegistrationCosts, RegistrationRecord, PIPELINE_KEY,
+};
+use flamewire_bittensor_indexer::ss58::encode_bittensor;
+use flamewire_bittensor_indexer::SqliteUrl;
+use sqlx::SqlitePool;
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS registrations (
+    block_number BIGINT NOT NULL,
+    event_index INTEGER NOT NULL,
+    netuid INTEGER NOT NULL,
+    uid INTEGER NOT NULL,
+    hotkey TEXT NOT NULL,
+    difficulty BIGINT NOT NULL,
+    burn BIGINT NOT NULL,
+    PRIMARY KEY (block_number, event_index)
+)";
+
+/// Writes the records `RegistrationCosts` left in the pipeline data.
+struct RegistrationWriter {
+    pool: SqlitePool,
+}
+
+#[async_trait]
+impl Handler<SubstrateConfig> for RegistrationWriter {
+    async fn handle_block(
+        &self,
+        ctx: &Context<SubstrateConfig>,
+        _events: &[ChainEvent<SubstrateConfig>],
+    ) -> Result<(), IndexerError> {
+        let Some(records) = ctx.get_pipeline_data::<Vec<RegistrationRecord>>(PIPELINE_KEY) else {
+            return Ok(());
+        };
+        let mut tx = self.pool.begin().await?;
+        for record in records {
+            sqlx::query(
+                "INSERT INTO registrations
+                    (block_number, event_index, netuid, uid, hotkey, difficulty, burn)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)
+                 ON CONFLICT (block_number, event_index) DO NOTHING",
+            )
+            .bind(record.block_number as i64)
+            .bind(record.event_index as i32)
+            .bind(record.netuid as i32)
+            .bind(record.uid as i32)
+            .bind(encode_bittensor(&record.hotkey))
+            .bind(record.difficulty as i64)
+            .bind(record.burn.get() as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .compact()
+        .init();
+
+    let url = SqliteUrl::parse("sqlite://registrations.db?mode=rwc")?;
+    let pool = SqlitePool::connect_with(url.connect_options()?).await?;
+    sqlx::query(CREATE_TABLE).execute(&pool).await?;
+
+    let pipeline = HandlerGroup::new()
+        .add(RegistrationCosts::new())
+        .add(RegistrationWriter { pool });
+
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .connect(WebSocketUrl::parse(
+            "wss://archive.chain.opentensor.ai:443",
+        )?)
+        .start_from_block(4_000_000)
+        .end_at_block(4_000_100)
+        .add_handler(pipeline)
+        .build()
+        .await?;
+
+    indexer.run().await?;
+    Ok(())
+}
@@ -0,0 +1,105 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Matching on the runtime's outer event enum instead of pallet and variant names.
+//!
+//! In an application the enums below come from
+//! `#[subxt::subxt(runtime_metadata_path = "metadata.scale")]`; they are written
+//! out here, trimmed to the Balances pallet, so the example builds on its own.
+
+use flamewire_bittensor_indexer::prelude::{
+    async_trait, AccountId32, ChainEvent, Context, DecodeAsType, Handler, IndexerBuilder,
+    IndexerError, Rao, RootEvent, SubstrateConfig, WebSocketUrl,
+};
+use subxt::ext::subxt_core;
+use subxt::Metadata;
+use tracing::info;
+
+#[derive(Debug, DecodeAsType)]
+enum BalancesEvent {
+    Transfer {
+        from: AccountId32,
+        to: AccountId32,
+        amount: u128,
+    },
+}
+
+#[derive(Debug)]
+enum RuntimeEvent {
+    Balances(BalancesEvent),
+    Other(String),
+}
+
+impl RootEvent for RuntimeEvent {
+    fn root_event(
+        mut pallet_bytes: &[u8],
+        pallet_name: &str,
+        pallet_event_ty: u32,
+        metadata: &Metadata,
+    ) -> Result<Self, subxt_core::Error> {
+        if pallet_name == "Balances" {
+            return Ok(RuntimeEvent::Balances(BalancesEvent::decode_as_type(
+                &mut pallet_bytes,
+                pallet_event_ty,
+                metadata.types(),
+            )?));
+        }
+        Ok(RuntimeEvent::Other(pallet_name.to_string()))
+    }
+}
+
+struct TransferHandler;
+
+#[async_trait]
+impl Handler<SubstrateConfig> for TransferHandler {
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<SubstrateConfig>,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        // Other Balances events (deposits, withdrawals, ...) are not in the
+        // trimmed enum above and fail to decode, so skip those.
+        let Ok(root) = event.as_root_event::<RuntimeEvent>() else {
+            return Ok(());
+        };
+        if let RuntimeEvent::Balances(BalancesEvent::Transfer { from, to, amount }) = root {
+            let amount = Rao::from(amount);
+            info!(block = ctx.block_number, %from, %to, %amount, "Transfer");
+        }
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .compact()
+        .init();
+
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .connect(WebSocketUrl::parse(
+            "wss://archive.chain.opentensor.ai:443",
+        )?)
+        .start_from_block(1017)
+        .end_at_block(1133)
+        .add_handler(TransferHandler)
+        .build()
+        .await?;
+
+    indexer.run().await?;
+    Ok(())
+}
@@ -0,0 +1,80 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Log how many weights each validator sets per block.
+//!
+//! `set_weights` emits no event carrying the weights, so this handler decodes
+//! the extrinsics themselves. Run with
+//! `cargo run --example set_weights --features bittensor-events`.
+
+use flamewire_bittensor_indexer::calls::subtensor::SetWeightsCall;
+use flamewire_bittensor_indexer::prelude::{
+    async_trait, ChainExtrinsic, Context, DisplayBittensor, Handler, IndexerBuilder, IndexerError,
+    SubstrateConfig, WebSocketUrl,
+};
+use tracing::info;
+
+struct WeightCounts;
+
+#[async_trait]
+impl Handler<SubstrateConfig> for WeightCounts {
+    async fn handle_extrinsic(
+        &self,
+        extrinsic: &ChainExtrinsic,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        let Some(call) = extrinsic
+            .as_call::<SetWeightsCall>()
+            .map_err(|e| IndexerError::handler_failed("set_weights", ctx.block_number, e))?
+        else {
+            return Ok(());
+        };
+        let validator = extrinsic
+            .signer()
+            .map(|signer| DisplayBittensor(signer).to_string())
+            .unwrap_or_else(|| "<unsigned>".into());
+        info!(
+            block = ctx.block_number,
+            %validator,
+            netuid = call.netuid,
+            weights = call.weights.len(),
+            "set_weights"
+        );
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .compact()
+        .init();
+
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .connect(WebSocketUrl::parse(
+            "wss://archive.chain.opentensor.ai:443",
+        )?)
+        .start_from_block(4_000_000)
+        .end_at_block(4_000_100)
+        .correlate_extrinsics(true)
+        .add_handler(WeightCounts)
+        .build()
+        .await?;
+
+    indexer.run().await?;
+    Ok(())
+}
@@ -0,0 +1,80 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Enrich each `StakeAdded` event with the hotkey's total stake after it.
+//!
+//! Run with `cargo run --example stake_totals --features bittensor-events`.
+//! `TotalHotkeyStake` predates dynamic TAO, so the example indexes blocks from
+//! before that upgrade.
+
+use flamewire_bittensor_indexer::events::subtensor::StakeAdded;
+use flamewire_bittensor_indexer::prelude::{
+    async_trait, filters, ChainEvent, Context, DisplayBittensor, EventFilter, Handler,
+    IndexerBuilder, IndexerError, Rao, SubstrateConfig, WebSocketUrl,
+};
+use flamewire_bittensor_indexer::subtensor_storage;
+use tracing::info;
+
+struct StakeTotals;
+
+#[async_trait]
+impl Handler<SubstrateConfig> for StakeTotals {
+    fn event_filter(&self) -> EventFilter {
+        filters::subtensor::STAKE_ADDED
+    }
+
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<SubstrateConfig>,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        let Some(stake) = StakeAdded::from_event(event)
+            .map_err(|e| IndexerError::handler_failed("stake_totals", ctx.block_number, e))?
+        else {
+            return Ok(());
+        };
+        let total = subtensor_storage::total_hotkey_stake(ctx, stake.hotkey()).await?;
+        info!(
+            block = ctx.block_number,
+            hotkey = %DisplayBittensor(stake.hotkey()),
+            added = %Rao::from(stake.amount()),
+            total = %Rao::from(total),
+            "StakeAdded"
+        );
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .compact()
+        .init();
+
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .connect(WebSocketUrl::parse(
+            "wss://archive.chain.opentensor.ai:443",
+        )?)
+        .start_from_block(4_000_000)
+        .end_at_block(4_000_100)
+        .add_handler(StakeTotals)
+        .build()
+        .await?;
+
+    indexer.run().await?;
+    Ok(())
+}
@@ -0,0 +1,50 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Index every TAO transfer into a SQLite table.
+//!
+//! Run with `cargo run --example transfers --features sqlite`, then query
+//! `SELECT * FROM transfers` in `transfers.db`.
+
+use flamewire_bittensor_indexer::prelude::{IndexerBuilder, SubstrateConfig, WebSocketUrl};
+use flamewire_bittensor_indexer::presets::transfers::TransferIndexer;
+use flamewire_bittensor_indexer::SqliteUrl;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .compact()
+        .init();
+
+    let url = SqliteUrl::parse("sqlite://transfers.db?mode=rwc")?;
+    let pool = sqlx::SqlitePool::connect_with(url.connect_options()?).await?;
+    let transfers = TransferIndexer::sqlite(pool);
+    transfers.create_schema().await?;
+
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .connect(WebSocketUrl::parse(
+            "wss://archive.chain.opentensor.ai:443",
+        )?)
+        .start_from_block(1017)
+        .end_at_block(1133)
+        .add_handler(transfers)
+        .build()
+        .await?;
+
+    indexer.run().await?;
+    Ok(())
+}
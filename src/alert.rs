@@ -0,0 +1,542 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Post chat alerts for selected events to Slack or Discord webhooks, with the
+//! `webhook` feature.
+
+use crate::error::IndexerError;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::ss58::encode_bittensor;
+use crate::types::{ChainEvent, FieldValueExt};
+use crate::units::Rao;
+use crate::webhook::{parse_http_url, DEFAULT_TIMEOUT};
+use async_trait::async_trait;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, Url};
+use scale_value::{Primitive, Value, ValueDef};
+use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use subxt::events::StaticEvent;
+use subxt::Config;
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+
+const HANDLER_NAME: &str = "AlertHandler";
+
+/// Alerts sent per [`DEFAULT_RATE_WINDOW`] unless set with
+/// [`AlertHandler::rate_limit`].
+pub const DEFAULT_MAX_ALERTS: u32 = 10;
+
+/// Window of the default rate limit.
+pub const DEFAULT_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Messages waiting to be posted unless set with
+/// [`AlertHandler::queue_capacity`].
+pub const DEFAULT_QUEUE_CAPACITY: usize = 100;
+
+type PredicateFn<C> = dyn Fn(&ChainEvent<C>) -> bool + Send + Sync;
+type MessageFn<C> = dyn Fn(&ChainEvent<C>, &Context<C>) -> Option<String> + Send + Sync;
+
+/// Payload shape expected by the webhook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertFormat {
+    /// Slack incoming webhooks: `{"text": message}`.
+    Slack,
+    /// Discord webhooks: `{"content": message}`.
+    Discord,
+}
+
+impl AlertFormat {
+    fn body(self, message: &str) -> String {
+        match self {
+            Self::Slack => json!({ "text": message }),
+            Self::Discord => json!({ "content": message }),
+        }
+        .to_string()
+    }
+}
+
+/// Which events an [`AlertHandler`] alerts on, and the message sent.
+pub struct AlertRule<C: Config> {
+    filter: EventFilter,
+    predicate: Option<Box<PredicateFn<C>>>,
+    message: Box<MessageFn<C>>,
+}
+
+impl<C: Config> AlertRule<C> {
+    /// Alert on every event passing `filter` with `template`, in which
+    /// placeholders are replaced by:
+    ///
+    /// - `{pallet}`, `{event}`, `{block}` and `{index}`: the event's pallet,
+    ///   variant, block number and index in the block
+    /// - `{name}` or `{0}`: the field called `name` or at position `0`. Account
+    ///   ids are shown as SS58 addresses, strings without quotes and other
+    ///   values as [`Value`] displays them
+    /// - `{name:tao}`: an integer field in rao, shown in TAO
+    ///
+    /// `{{` and `}}` stand for literal braces. Placeholders that do not match a
+    /// field are left as they are, so typos show up in the alert.
+    pub fn new(filter: EventFilter, template: impl Into<String>) -> Self {
+        let template = template.into();
+        Self::custom(filter, move |event, ctx| {
+            Some(render(&template, event, ctx))
+        })
+    }
+
+    /// Alert on events passing `filter` with the message built by `message`.
+    /// Returning `None` skips the event.
+    pub fn custom<F>(filter: EventFilter, message: F) -> Self
+    where
+        F: Fn(&ChainEvent<C>, &Context<C>) -> Option<String> + Send + Sync + 'static,
+    {
+        Self {
+            filter,
+            predicate: None,
+            message: Box::new(message),
+        }
+    }
+
+    /// Alert on `E` events with the message built by `message` from the
+    /// decoded event. Returning `None` skips the event. Events failing to
+    /// decode are logged and skipped.
+    pub fn typed<E, F>(message: F) -> Self
+    where
+        E: StaticEvent + 'static,
+        F: Fn(E, &Context<C>) -> Option<String> + Send + Sync + 'static,
+    {
+        Self::custom(
+            EventFilter::event(E::PALLET, E::EVENT),
+            move |event, ctx| match event.as_event::<E>() {
+                Ok(decoded) => decoded.and_then(|decoded| message(decoded, ctx)),
+                Err(error) => {
+                    warn!(
+                        target: "indexer",
+                        handler = HANDLER_NAME,
+                        block = ctx.block_number,
+                        pallet = E::PALLET,
+                        event = E::EVENT,
+                        %error,
+                        "skipping alert for an event that failed to decode"
+                    );
+                    None
+                }
+            },
+        )
+    }
+
+    /// Only alert on events for which `predicate` holds, e.g. transfers above
+    /// a threshold.
+    pub fn when<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&ChainEvent<C>) -> bool + Send + Sync + 'static,
+    {
+        self.predicate = Some(Box::new(predicate));
+        self
+    }
+
+    fn message(&self, event: &ChainEvent<C>, ctx: &Context<C>) -> Option<String> {
+        if !self.filter.matches_event(event) {
+            return None;
+        }
+        if !self
+            .predicate
+            .as_ref()
+            .is_none_or(|predicate| predicate(event))
+        {
+            return None;
+        }
+        (self.message)(event, ctx)
+    }
+}
+
+/// Work for the task posting an [`AlertHandler`]'s messages.
+enum Outgoing {
+    Message(String),
+    /// Answered once every message queued before it was posted.
+    Flush(oneshot::Sender<()>),
+}
+
+/// What the task posting an [`AlertHandler`]'s messages needs.
+struct Poster {
+    client: Client,
+    url: Url,
+    format: AlertFormat,
+    timeout: Duration,
+    sent: Arc<AtomicU64>,
+    failed: Arc<AtomicU64>,
+}
+
+impl Poster {
+    async fn run(self, mut queue: mpsc::Receiver<Outgoing>) {
+        while let Some(outgoing) = queue.recv().await {
+            match outgoing {
+                Outgoing::Message(message) => self.post(&message).await,
+                Outgoing::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+
+    async fn post(&self, message: &str) {
+        let res = self
+            .client
+            .post(self.url.clone())
+            .timeout(self.timeout)
+            .header(CONTENT_TYPE, "application/json")
+            .body(self.format.body(message))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match res {
+            Ok(_) => {
+                self.sent.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(error) => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                // The URL of a chat webhook is its credential, keep it out of logs.
+                let error = error.without_url();
+                warn!(target: "indexer", handler = HANDLER_NAME, %error, "failed to send alert");
+            }
+        }
+    }
+}
+
+/// Alerts sent and suppressed in the current rate limit window.
+struct RateWindow {
+    started: Instant,
+    sent: u32,
+    suppressed: u64,
+}
+
+/// Handler posting a chat message to a Slack or Discord webhook for each event
+/// matching one of its [`AlertRule`]s.
+///
+/// At most `max_alerts` messages are sent per window, [`DEFAULT_MAX_ALERTS`]
+/// per minute by default. Alerts over the limit are counted and reported in a
+/// single "Suppressed N alerts" message once the window is over, or when the
+/// indexer stops. The window is checked as blocks arrive.
+///
+/// Alerting never affects indexing: each message is posted once, and a message
+/// that fails to send is logged and counted in [`failed`](Self::failed), while
+/// the handler itself always succeeds. [`Handler::handle_block`] only queues
+/// messages, which a background task posts in order, so a slow webhook never
+/// delays a block. Messages arriving while [`queue_capacity`](Self::queue_capacity)
+/// of them wait are dropped and counted in [`dropped`](Self::dropped).
+/// [`Handler::on_shutdown`] waits for the queue to empty.
+///
+/// ```no_run
+/// # use flamewire_bittensor_indexer::{EventFilter, IndexerError};
+/// use flamewire_bittensor_indexer::alert::{AlertFormat, AlertHandler, AlertRule};
+/// use flamewire_bittensor_indexer::units::Rao;
+/// use subxt::config::substrate::SubstrateConfig;
+///
+/// # fn main() -> Result<(), IndexerError> {
+/// let threshold = Rao::from_tao(10_000).get();
+/// let alerts = AlertHandler::<SubstrateConfig>::new(
+///     "https://hooks.slack.com/services/T000/B000/XXXX",
+///     AlertFormat::Slack,
+/// )?
+/// .rule(
+///     AlertRule::new(
+///         EventFilter::event("Balances", "Transfer"),
+///         "{amount:tao} TAO sent from {from} to {to} in block {block}",
+///     )
+///     .when(move |event| {
+///         let amount = event.field("amount").ok().flatten();
+///         amount.and_then(|amount| amount.as_u128()) > Some(threshold)
+///     }),
+/// )
+/// .rule(AlertRule::new(
+///     EventFilter::event("SubtensorModule", "NetworkAdded"),
+///     "Subnet {0} added in block {block}",
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+pub struct AlertHandler<C: Config> {
+    client: Client,
+    url: Url,
+    format: AlertFormat,
+    rules: Vec<AlertRule<C>>,
+    timeout: Duration,
+    max_alerts: u32,
+    window: Duration,
+    limiter: Mutex<RateWindow>,
+    queue_capacity: usize,
+    /// Started with the first message.
+    queue: OnceLock<mpsc::Sender<Outgoing>>,
+    sent: Arc<AtomicU64>,
+    failed: Arc<AtomicU64>,
+    dropped: AtomicU64,
+    suppressed: AtomicU64,
+}
+
+impl<C: Config> AlertHandler<C> {
+    /// Post to `url`, which must be an `http` or `https` URL, in `format`.
+    pub fn new(url: impl AsRef<str>, format: AlertFormat) -> Result<Self, IndexerError> {
+        Ok(Self {
+            client: Client::new(),
+            url: parse_http_url("alert_url", url.as_ref())?,
+            format,
+            rules: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            max_alerts: DEFAULT_MAX_ALERTS,
+            window: DEFAULT_RATE_WINDOW,
+            limiter: Mutex::new(RateWindow {
+                started: Instant::now(),
+                sent: 0,
+                suppressed: 0,
+            }),
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            queue: OnceLock::new(),
+            sent: Arc::new(AtomicU64::new(0)),
+            failed: Arc::new(AtomicU64::new(0)),
+            dropped: AtomicU64::new(0),
+            suppressed: AtomicU64::new(0),
+        })
+    }
+
+    /// Also alert on events matching `rule`. An event matching several rules
+    /// sends one message per rule.
+    pub fn rule(mut self, rule: AlertRule<C>) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Send at most `max_alerts` messages, suppression summaries included,
+    /// per `window`.
+    pub fn rate_limit(mut self, max_alerts: u32, window: Duration) -> Self {
+        self.max_alerts = max_alerts.max(1);
+        self.window = window;
+        self
+    }
+
+    /// Give up on a message after `timeout`, [`DEFAULT_TIMEOUT`] by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Keep at most `capacity` messages waiting to be posted,
+    /// [`DEFAULT_QUEUE_CAPACITY`] by default.
+    pub fn queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity.max(1);
+        self
+    }
+
+    /// Messages delivered so far, suppression summaries included.
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Messages that failed to send.
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Messages dropped because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Alerts suppressed by the rate limit so far.
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed.load(Ordering::Relaxed)
+    }
+
+    /// Start a new window if the current one is over, returning how many
+    /// alerts the old one suppressed. The summary counts against the new window.
+    fn roll_window(&self) -> Option<u64> {
+        let mut window = self.limiter.lock().unwrap();
+        if window.started.elapsed() < self.window {
+            return None;
+        }
+        let suppressed = window.suppressed;
+        *window = RateWindow {
+            started: Instant::now(),
+            sent: u32::from(suppressed > 0),
+            suppressed: 0,
+        };
+        (suppressed > 0).then_some(suppressed)
+    }
+
+    /// Whether another alert fits in the current window, counting it as
+    /// suppressed if not.
+    fn admit(&self) -> bool {
+        let mut window = self.limiter.lock().unwrap();
+        if window.sent < self.max_alerts {
+            window.sent += 1;
+            return true;
+        }
+        window.suppressed += 1;
+        self.suppressed.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    fn send_summary(&self, suppressed: u64) {
+        self.enqueue(format!(
+            "Suppressed {suppressed} alerts over the limit of {} per {:?}",
+            self.max_alerts, self.window
+        ));
+    }
+
+    /// Queue `message` for the posting task, started on first use, or drop it
+    /// if the queue is full.
+    fn enqueue(&self, message: String) {
+        let queue = self.queue.get_or_init(|| {
+            let (tx, rx) = mpsc::channel(self.queue_capacity);
+            let poster = Poster {
+                client: self.client.clone(),
+                url: self.url.clone(),
+                format: self.format,
+                timeout: self.timeout,
+                sent: self.sent.clone(),
+                failed: self.failed.clone(),
+            };
+            tokio::spawn(poster.run(rx));
+            tx
+        });
+        if queue.try_send(Outgoing::Message(message)).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            warn!(target: "indexer", handler = HANDLER_NAME, "alert queue is full, dropping alert");
+        }
+    }
+
+    /// Wait until every message queued so far was posted or failed.
+    pub async fn flush(&self) {
+        let Some(queue) = self.queue.get() else {
+            return;
+        };
+        let (done, posted) = oneshot::channel();
+        if queue.send(Outgoing::Flush(done)).await.is_ok() {
+            let _ = posted.await;
+        }
+    }
+}
+
+#[async_trait]
+impl<C: Config> Handler<C> for AlertHandler<C> {
+    fn name(&self) -> &str {
+        HANDLER_NAME
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        if let Some(suppressed) = self.roll_window() {
+            self.send_summary(suppressed);
+        }
+        for event in events {
+            for rule in &self.rules {
+                let Some(message) = rule.message(event, ctx) else {
+                    continue;
+                };
+                if self.admit() {
+                    self.enqueue(message);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        let suppressed = std::mem::take(&mut self.limiter.lock().unwrap().suppressed);
+        if suppressed > 0 {
+            self.send_summary(suppressed);
+        }
+        self.flush().await;
+        Ok(())
+    }
+}
+
+/// `template` with its placeholders replaced, see [`AlertRule::new`].
+fn render<C: Config>(template: &str, event: &ChainEvent<C>, ctx: &Context<C>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let end = match tail.find('}') {
+            Some(end) if tail.starts_with('{') => end,
+            _ => {
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+                continue;
+            }
+        };
+        match placeholder(&tail[1..end], event, ctx) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&tail[..=end]),
+        }
+        rest = &tail[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn placeholder<C: Config>(
+    placeholder: &str,
+    event: &ChainEvent<C>,
+    ctx: &Context<C>,
+) -> Option<String> {
+    let (name, format) = match placeholder.split_once(':') {
+        Some((name, format)) => (name, Some(format)),
+        None => (placeholder, None),
+    };
+    match (name, format) {
+        ("pallet", None) => return Some(event.pallet_name().to_string()),
+        ("event", None) => return Some(event.variant_name().to_string()),
+        ("block", None) => return Some(ctx.block_number.to_string()),
+        ("index", None) => return Some(event.index().to_string()),
+        _ => {}
+    }
+    let value = match name.parse::<usize>() {
+        Ok(index) => event.field_at(index),
+        Err(_) => event.field(name),
+    }
+    .ok()??;
+    match format {
+        None => Some(display(&value)),
+        Some("tao") => value.as_u128().map(tao),
+        Some(_) => None,
+    }
+}
+
+fn display(value: &Value<u32>) -> String {
+    if let Some(account) = value.as_account_id() {
+        return encode_bittensor(&account);
+    }
+    match &value.value {
+        ValueDef::Primitive(Primitive::String(s)) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+/// `rao` in TAO without trailing zeros, e.g. `12500.5`.
+fn tao(rao: u128) -> String {
+    let tao = Rao::new(rao).to_tao_string(9);
+    tao.trim_end_matches('0').trim_end_matches('.').to_string()
+}
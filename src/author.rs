@@ -0,0 +1,188 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Attribution of blocks to the validator that authored them. Enabled with
+//! [`IndexerBuilder::resolve_block_author`](crate::builder::IndexerBuilder::resolve_block_author).
+//!
+//! A block's pre-runtime digest carries the slot it was authored in; with AURA
+//! the author is the validator at `slot % validators`, with BABE the digest
+//! names the validator's index directly. The validator set is
+//! `Session.Validators` as of the parent block.
+
+use crate::error::IndexerError;
+use parity_scale_codec::{Decode, Encode};
+use scale_value::Value;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use subxt::config::substrate::{BlakeTwo256, DigestItem, SubstrateHeader};
+use subxt::config::HashFor;
+use subxt::storage::Storage;
+use subxt::utils::AccountId32;
+use subxt::{Config, OnlineClient};
+
+pub const AURA_ENGINE_ID: [u8; 4] = *b"aura";
+pub const BABE_ENGINE_ID: [u8; 4] = *b"BABE";
+
+/// The slot claim in a block's pre-runtime digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreRuntime {
+    Aura { slot: u64 },
+    Babe { authority_index: u32, slot: u64 },
+}
+
+impl PreRuntime {
+    /// The first AURA or BABE pre-runtime entry in `logs`.
+    pub fn from_digest(logs: &[DigestItem]) -> Option<Self> {
+        logs.iter().find_map(|item| match item {
+            DigestItem::PreRuntime(engine, data) => Self::decode(*engine, data),
+            _ => None,
+        })
+    }
+
+    /// Decode the pre-runtime data of consensus engine `engine`; `None` for
+    /// other engines and malformed data.
+    pub fn decode(engine: [u8; 4], mut data: &[u8]) -> Option<Self> {
+        match engine {
+            AURA_ENGINE_ID => u64::decode(&mut data).ok().map(|slot| Self::Aura { slot }),
+            // Each BABE pre-digest variant (primary, secondary plain, secondary
+            // VRF) starts with the authority index and the slot.
+            BABE_ENGINE_ID => match <(u8, u32, u64)>::decode(&mut data).ok()? {
+                (1..=3, authority_index, slot) => Some(Self::Babe {
+                    authority_index,
+                    slot,
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn slot(&self) -> u64 {
+        match *self {
+            Self::Aura { slot } | Self::Babe { slot, .. } => slot,
+        }
+    }
+
+    /// The author among `validators`, the set active when the block was authored.
+    pub fn author<'a>(&self, validators: &'a [AccountId32]) -> Option<&'a AccountId32> {
+        let index = match *self {
+            Self::Aura { slot } => slot.checked_rem(validators.len() as u64)? as usize,
+            Self::Babe {
+                authority_index, ..
+            } => authority_index as usize,
+        };
+        validators.get(index)
+    }
+}
+
+/// Parent hash and pre-runtime slot claim of a block header. Assumes the
+/// standard Substrate header layout, which Bittensor uses.
+pub(crate) fn read_header<C: Config>(
+    header: &C::Header,
+) -> Result<(HashFor<C>, Option<PreRuntime>), IndexerError> {
+    let encoded = header.encode();
+    let header = SubstrateHeader::<u64, BlakeTwo256>::decode(&mut encoded.as_slice())
+        .map_err(subxt::Error::from)?;
+    let parent_hash = <HashFor<C> as Decode>::decode(&mut header.parent_hash.as_bytes())
+        .map_err(subxt::Error::from)?;
+    Ok((parent_hash, PreRuntime::from_digest(&header.digest.logs)))
+}
+
+/// `Session.Validators` at the block `storage` is pinned to.
+pub(crate) async fn session_validators<C: Config>(
+    storage: &Storage<C, OnlineClient<C>>,
+) -> Result<Vec<AccountId32>, IndexerError> {
+    let address = subxt::dynamic::storage("Session", "Validators", Vec::<Value>::new());
+    let validators = storage
+        .fetch(&address)
+        .await
+        .map_err(|e| IndexerError::storage_query_failed("Session", "Validators", e))?;
+    match validators {
+        Some(value) => value
+            .as_type::<Vec<AccountId32>>()
+            .map_err(|e| IndexerError::storage_query_failed("Session", "Validators", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Resolves block authors, caching the validator set while blocks arrive
+/// consecutively within one session.
+#[derive(Default)]
+pub struct AuthorResolver {
+    cache: Mutex<Option<CachedValidators>>,
+}
+
+struct CachedValidators {
+    /// Last block resolved with `validators`.
+    block: u64,
+    validators: Arc<[AccountId32]>,
+}
+
+impl AuthorResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The author of block `number` given its slot claim. `load_validators`
+    /// reads the validator set the block was authored under; it is only called
+    /// for the first block, after a gap or reorg, and after
+    /// [`new_session`](Self::new_session).
+    pub async fn resolve<F, Fut>(
+        &self,
+        number: u64,
+        pre_runtime: Option<PreRuntime>,
+        load_validators: F,
+    ) -> Result<Option<AccountId32>, IndexerError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<AccountId32>, IndexerError>>,
+    {
+        let Some(pre_runtime) = pre_runtime else {
+            return Ok(None);
+        };
+        let cached = match self.lock().as_mut() {
+            Some(cache) if cache.block + 1 == number => {
+                cache.block = number;
+                Some(cache.validators.clone())
+            }
+            _ => None,
+        };
+        let validators = match cached {
+            Some(validators) => validators,
+            None => {
+                let validators: Arc<[AccountId32]> = load_validators().await?.into();
+                *self.lock() = Some(CachedValidators {
+                    block: number,
+                    validators: validators.clone(),
+                });
+                validators
+            }
+        };
+        Ok(pre_runtime.author(&validators).cloned())
+    }
+
+    /// Drop the cached validator set. Called for blocks emitting
+    /// `Session.NewSession`, whose successors are authored by the new set.
+    pub fn new_session(&self) {
+        *self.lock() = None;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<CachedValidators>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,158 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Fan indexed events out to live subscribers, with the `grpc` or `http-api`
+//! feature.
+//!
+//! [`EventBroadcast`] is a handler publishing every event of a block to a
+//! bounded broadcast channel. The gRPC `EventStream` service and the `/ws`
+//! endpoint of the status server subscribe to the same channel.
+
+use crate::error::IndexerError;
+use crate::filters;
+use crate::handler::{Context, Handler};
+use crate::types::ChainEvent;
+use async_trait::async_trait;
+use serde::{Serialize, Serializer};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use subxt::Config;
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber unless set with [`EventBroadcast::new`].
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// An indexed event as sent to subscribers. Serializes like
+/// [`EventEnvelope`](crate::types::EventEnvelope), plus the `netuid`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LiveEvent {
+    pub block_number: u64,
+    #[serde(serialize_with = "hex")]
+    pub block_hash: Vec<u8>,
+    pub index: u32,
+    pub pallet: String,
+    pub variant: String,
+    /// Fields as produced by [`ChainEvent::to_json`], `None` if they failed to decode.
+    pub fields_json: Option<serde_json::Value>,
+    /// The event's `netuid` field, or for `SubtensorModule` events its first
+    /// field, when that holds a `u16`.
+    pub netuid: Option<u16>,
+}
+
+impl LiveEvent {
+    pub fn from_event<C: Config>(event: &ChainEvent<C>, ctx: &Context<C>) -> Self {
+        let netuid = match event.field("netuid").ok().flatten() {
+            Some(value) => Some(value),
+            None if event.pallet_name() == filters::subtensor::PALLET => {
+                event.field_at(0).ok().flatten()
+            }
+            None => None,
+        }
+        .and_then(|value| value.as_u128())
+        .and_then(|n| u16::try_from(n).ok());
+        Self {
+            block_number: ctx.block_number,
+            block_hash: ctx.block_hash.as_ref().to_vec(),
+            index: event.index(),
+            pallet: event.pallet_name().to_string(),
+            variant: event.variant_name().to_string(),
+            fields_json: event.to_json().ok(),
+            netuid,
+        }
+    }
+}
+
+fn hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut text = String::with_capacity(2 + bytes.len() * 2);
+    text.push_str("0x");
+    for byte in bytes {
+        let _ = write!(text, "{byte:02x}");
+    }
+    serializer.serialize_str(&text)
+}
+
+/// Handler publishing every event to the subscribers of a bounded broadcast
+/// channel.
+///
+/// Each subscriber lags behind by at most `capacity` events; past that it
+/// misses the oldest ones, which subscribers report through
+/// [`record_dropped`](Self::record_dropped), so a slow subscriber never holds
+/// up indexing. Nothing is buffered while there are no subscribers.
+#[derive(Clone)]
+pub struct EventBroadcast {
+    sender: broadcast::Sender<Arc<LiveEvent>>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventBroadcast {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity.max(1)).0,
+            dropped: Arc::default(),
+        }
+    }
+
+    /// Receive the events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<LiveEvent>> {
+        self.sender.subscribe()
+    }
+
+    /// Number of connected subscribers.
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Events missed by subscribers that fell behind, summed over all of them.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// A subscriber missed `events` events.
+    pub fn record_dropped(&self, events: u64) {
+        self.dropped.fetch_add(events, Ordering::Relaxed);
+    }
+}
+
+impl Default for EventBroadcast {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[async_trait]
+impl<C: Config> Handler<C> for EventBroadcast {
+    fn name(&self) -> &str {
+        "EventBroadcast"
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        if self.sender.receiver_count() == 0 {
+            return Ok(());
+        }
+        for event in events {
+            // Fails only once the last subscriber is gone.
+            let _ = self
+                .sender
+                .send(Arc::new(LiveEvent::from_event(event, ctx)));
+        }
+        Ok(())
+    }
+}
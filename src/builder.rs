@@ -15,26 +15,95 @@
  */
 
 use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
+use subxt::backend::rpc::RpcClient;
 use subxt::Config;
 use subxt::OnlineClient;
 
-use crate::config::IndexerConfig;
+#[cfg(any(feature = "grpc", feature = "http-api"))]
+use crate::broadcast::EventBroadcast;
+use crate::config::{FinalityMode, IndexerConfig, StartFrom, TlsMode};
 use crate::error::IndexerError;
-use crate::handler::Handler;
+#[cfg(feature = "grpc")]
+use crate::grpc::{EventStreamService, GrpcServer};
+use crate::handler::{EventPattern, Handler};
+use crate::heartbeat::HeartbeatFile;
 use crate::indexer::Indexer;
+#[cfg(feature = "metrics")]
+use crate::metrics::{IndexerMetrics, MetricsServer};
+use crate::replay::ReplaySource;
+use crate::reporter::{ErrorReporter, LogReporter, DEFAULT_REPORT_INTERVAL};
+use crate::retry::{CircuitBreakerConfig, RetryConfig, RetryPredicate};
+use crate::rpc;
+use crate::source::{offline_client, BlockSource};
+#[cfg(feature = "statsd")]
+use crate::statsd::StatsdMetrics;
 use crate::storage::init::init_store;
+use crate::storage::{CheckpointStore, StorageConfig};
 use crate::types::BlockNumber;
-use crate::validated_types::WebSocketUrl;
+use crate::validated_types::{BlockRange, PostgresUrl, SqliteUrl, WebSocketUrl};
+
+/// Node URL of a replay, custom block source or RPC client without one, which is
+/// never connected to.
+const OFFLINE_NODE_URL: &str = "ws://offline.invalid";
 
 /// Convenient builder for creating an [`Indexer`].
 pub struct IndexerBuilder<C: Config> {
-    node_url: Option<WebSocketUrl>,
-    database_url: Option<String>,
-    start_block: Option<BlockNumber>,
+    node_urls: Vec<WebSocketUrl>,
+    storage: Option<StorageConfig>,
+    start_from: Option<StartFrom>,
     end_block: Option<BlockNumber>,
+    strict_range: Option<bool>,
+    range_tolerance: Option<BlockNumber>,
+    end_after: Option<Duration>,
+    end_at_time: Option<SystemTime>,
     max_blocks_per_minute: Option<u32>,
+    finality: Option<FinalityMode>,
+    checkpoint_interval: Option<u64>,
+    max_reorg_depth: Option<u32>,
+    handler_error_budget: Option<u64>,
+    error_reporter: Option<Arc<dyn ErrorReporter>>,
+    error_report_interval: Option<u64>,
+    event_allowlist: Vec<EventPattern>,
+    correlate_extrinsics: Option<bool>,
+    resolve_block_author: Option<bool>,
+    track_fees: Option<bool>,
+    abort_on_panic: Option<bool>,
+    skip_undecodable_events: Option<bool>,
+    stale_after: Option<Duration>,
+    record_to: Option<PathBuf>,
+    replay_from: Option<PathBuf>,
+    block_source: Option<Arc<dyn BlockSource<C>>>,
+    rpc_client: Option<RpcClient>,
+    heartbeat: Option<(PathBuf, Duration)>,
+    heartbeat_requires_progress: bool,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<IndexerMetrics>>,
+    #[cfg(feature = "metrics")]
+    metrics_addr: Option<String>,
+    #[cfg(feature = "statsd")]
+    statsd: Option<Arc<StatsdMetrics>>,
+    #[cfg(feature = "grpc")]
+    grpc_addr: Option<String>,
+    #[cfg(feature = "http-api")]
+    status_addr: Option<String>,
+    storage_retry: Option<RetryConfig>,
+    storage_circuit_breaker: Option<CircuitBreakerConfig>,
+    retry_config: Option<RetryConfig>,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+    retryable_errors: Option<RetryPredicate>,
+    rpc_timeout: Option<Duration>,
+    secondary: Option<WebSocketUrl>,
+    hedge_after: Option<Duration>,
+    tls_mode: Option<TlsMode>,
+    queue_depth: Option<usize>,
+    store: Option<Box<dyn CheckpointStore>>,
     handlers: Vec<Box<dyn Handler<C>>>,
+    named_handlers: Vec<(String, Box<dyn Handler<C>>)>,
+    enabled_handlers: Option<Vec<String>>,
     _marker: PhantomData<C>,
 }
 
@@ -54,37 +123,247 @@ where
     /// Create a new builder with default options.
     pub fn new() -> Self {
         Self {
-            node_url: None,
-            database_url: None,
-            start_block: None,
+            node_urls: Vec::new(),
+            storage: None,
+            start_from: None,
             end_block: None,
+            strict_range: None,
+            range_tolerance: None,
+            end_after: None,
+            end_at_time: None,
             max_blocks_per_minute: None,
+            finality: None,
+            checkpoint_interval: None,
+            max_reorg_depth: None,
+            handler_error_budget: None,
+            error_reporter: None,
+            error_report_interval: None,
+            event_allowlist: Vec::new(),
+            correlate_extrinsics: None,
+            resolve_block_author: None,
+            track_fees: None,
+            abort_on_panic: None,
+            skip_undecodable_events: None,
+            stale_after: None,
+            record_to: None,
+            replay_from: None,
+            block_source: None,
+            rpc_client: None,
+            heartbeat: None,
+            heartbeat_requires_progress: false,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+            #[cfg(feature = "metrics")]
+            metrics_addr: None,
+            #[cfg(feature = "statsd")]
+            statsd: None,
+            #[cfg(feature = "grpc")]
+            grpc_addr: None,
+            #[cfg(feature = "http-api")]
+            status_addr: None,
+            storage_retry: None,
+            storage_circuit_breaker: None,
+            retry_config: None,
+            circuit_breaker: None,
+            retryable_errors: None,
+            rpc_timeout: None,
+            secondary: None,
+            hedge_after: None,
+            tls_mode: None,
+            queue_depth: None,
+            store: None,
             handlers: Vec::new(),
+            named_handlers: Vec::new(),
+            enabled_handlers: None,
             _marker: PhantomData,
         }
     }
 
-    /// Connect to the given websocket URL.
+    /// Connect to the given websocket URL, replacing the primary node.
+    ///
+    /// Credentials set with [`WebSocketUrl::with_basic_auth`] or
+    /// [`WebSocketUrl::with_bearer_token`] on any node URL are sent to every node.
     pub fn connect(mut self, url: WebSocketUrl) -> Self {
-        self.node_url = Some(url);
+        match self.node_urls.first_mut() {
+            Some(first) => *first = url,
+            None => self.node_urls.push(url),
+        }
         self
     }
 
-    /// Use a PostgreSQL store.
-    pub fn with_postgres(mut self, url: impl Into<String>) -> Self {
-        self.database_url = Some(url.into());
+    /// Add a node after those already configured. The second node is the one slow
+    /// read-only calls are hedged to, see [`hedge_after`](Self::hedge_after); later
+    /// nodes are not used.
+    pub fn add_node_url(mut self, url: WebSocketUrl) -> Self {
+        self.node_urls.push(url);
         self
     }
 
+    /// Use a PostgreSQL store.
+    pub fn with_postgres(self, url: PostgresUrl) -> Self {
+        self.with_storage(StorageConfig::Postgres { url })
+    }
+
     /// Use a SQLite store.
-    pub fn with_sqlite(mut self, url: impl Into<String>) -> Self {
-        self.database_url = Some(url.into());
+    pub fn with_sqlite(self, url: SqliteUrl) -> Self {
+        self.with_storage(StorageConfig::Sqlite { url })
+    }
+
+    /// Store checkpoints as JSON at `path` instead of `database/checkpoint.json`.
+    pub fn with_json(self, path: impl Into<PathBuf>) -> Self {
+        self.with_storage(StorageConfig::json(path))
+    }
+
+    /// Use the checkpoint backend described by `storage`.
+    pub fn with_storage(mut self, storage: StorageConfig) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Parse `url` and use it as a PostgreSQL store.
+    ///
+    /// Fails if `url` is not a valid `postgres://` or `postgresql://` URL.
+    pub fn try_with_postgres(mut self, url: &str) -> Result<Self, IndexerError> {
+        self.storage = Some(StorageConfig::parse_for("postgres", url)?);
+        Ok(self)
+    }
+
+    /// Parse `url` and use it as a SQLite store.
+    ///
+    /// Fails if `url` is not a valid `sqlite:` URL.
+    pub fn try_with_sqlite(mut self, url: &str) -> Result<Self, IndexerError> {
+        self.storage = Some(StorageConfig::parse_for("sqlite", url)?);
+        Ok(self)
+    }
+
+    /// Use an already constructed checkpoint store instead of one derived from the
+    /// database URL.
+    ///
+    /// Pass an `Arc<S>` to keep a handle to the store for your own use.
+    pub fn with_store(mut self, store: impl CheckpointStore + 'static) -> Self {
+        self.store = Some(Box::new(store));
+        self
+    }
+
+    /// Retry policy for checkpoint store operations.
+    ///
+    /// The store is always wrapped in a [`RetryingStore`](crate::storage::RetryingStore);
+    /// this overrides its defaults.
+    pub fn storage_retry(mut self, config: RetryConfig) -> Self {
+        self.storage_retry = Some(config);
+        self
+    }
+
+    /// Open the storage circuit breaker after `threshold` consecutive failures, for `cooldown`.
+    pub fn storage_circuit_breaker(mut self, threshold: usize, cooldown: Duration) -> Self {
+        self.storage_circuit_breaker = Some(CircuitBreakerConfig {
+            threshold,
+            cooldown,
+            ..CircuitBreakerConfig::storage()
+        });
+        self
+    }
+
+    /// Full storage circuit breaker settings, including half-open probing.
+    pub fn storage_circuit_breaker_config(mut self, config: CircuitBreakerConfig) -> Self {
+        self.storage_circuit_breaker = Some(config);
+        self
+    }
+
+    /// Retry policy for node RPC calls.
+    pub fn retry_config(mut self, config: RetryConfig) -> Self {
+        self.retry_config = Some(config);
+        self
+    }
+
+    /// Open the RPC circuit breaker after `threshold` consecutive failures, for `cooldown`.
+    pub fn circuit_breaker(mut self, threshold: usize, cooldown: Duration) -> Self {
+        self.circuit_breaker = Some(CircuitBreakerConfig {
+            threshold,
+            cooldown,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Full RPC circuit breaker settings, including half-open probing.
+    pub fn circuit_breaker_config(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Limit each attempt of a node RPC call to `timeout`; timed-out attempts are retried.
+    pub fn rpc_timeout(mut self, timeout: Duration) -> Self {
+        self.rpc_timeout = Some(timeout);
+        self
+    }
+
+    /// Hedge read-only RPC calls to the second node added with
+    /// [`add_node_url`](Self::add_node_url) when the primary node has not answered
+    /// after `delay`; whichever node answers first wins.
+    ///
+    /// Only block hash, header and finalized head lookups are hedged. How often the
+    /// secondary won is reported by [`Indexer::hedge_counters`](crate::indexer::Indexer::hedge_counters).
+    pub fn hedge_after(mut self, delay: Duration) -> Self {
+        self.hedge_after = Some(delay);
+        self
+    }
+
+    /// Deprecated: add `secondary` with [`add_node_url`](Self::add_node_url) and call
+    /// [`hedge_after`](Self::hedge_after) instead.
+    ///
+    /// Fails the build if a second node is added as well.
+    pub fn hedge_to(mut self, secondary: WebSocketUrl, hedge_after: Duration) -> Self {
+        self.secondary = Some(secondary);
+        self.hedge_after = Some(hedge_after);
+        self
+    }
+
+    /// Accept any certificate from `wss://` nodes, including self-signed and expired
+    /// ones.
+    ///
+    /// Certificates are verified by default. Disabling verification leaves the
+    /// connection open to interception; use it only against test nodes.
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.tls_mode = Some(if accept {
+            TlsMode::Insecure
+        } else {
+            TlsMode::Secure
+        });
+        self
+    }
+
+    /// Buffer at most `depth` fetched blocks ahead of the handlers (default 16).
+    ///
+    /// When the handlers fall behind, fetching waits for them instead of holding
+    /// more blocks in memory. The current depth is reported by
+    /// [`Indexer::queue`](crate::indexer::Indexer::queue) and
+    /// [`HealthStatus::queue_depth`](crate::health::HealthStatus::queue_depth).
+    pub fn queue_depth(mut self, depth: usize) -> Self {
+        self.queue_depth = Some(depth);
+        self
+    }
+
+    /// Decide which errors are retried, for both RPC calls and checkpoint storage.
+    ///
+    /// Overrides the built-in [`is_retryable_error`](crate::retry::is_retryable_error)
+    /// classification and any predicate set on the individual retry configs.
+    pub fn retryable_errors(
+        mut self,
+        predicate: impl Fn(&IndexerError) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retryable_errors = Some(Arc::new(predicate));
         self
     }
 
     /// Start indexing from the specified block.
-    pub fn start_from_block(mut self, block: BlockNumber) -> Self {
-        self.start_block = Some(block);
+    pub fn start_from_block(self, block: BlockNumber) -> Self {
+        self.start_from(StartFrom::Block(block))
+    }
+
+    /// Choose where indexing starts; resuming from the checkpoint by default.
+    pub fn start_from(mut self, start: StartFrom) -> Self {
+        self.start_from = Some(start);
         self
     }
 
@@ -94,12 +373,255 @@ where
         self
     }
 
+    /// Index the blocks in `range`: start from its first block and end at its last.
+    pub fn range(mut self, range: BlockRange) -> Self {
+        self.start_from = Some(StartFrom::Block(range.start()));
+        self.end_block = range.end();
+        self
+    }
+
+    /// Fail at startup with [`IndexerError::InvalidConfig`] when the start block lies more
+    /// than [`range_tolerance`](Self::range_tolerance) blocks past the chain head. Such a
+    /// start is only logged as a warning otherwise.
+    pub fn strict_range(mut self, strict: bool) -> Self {
+        self.strict_range = Some(strict);
+        self
+    }
+
+    /// Allow the start block to lie up to `blocks` past the chain head (default 0).
+    pub fn range_tolerance(mut self, blocks: BlockNumber) -> Self {
+        self.range_tolerance = Some(blocks);
+        self
+    }
+
+    /// Stop once the run has lasted `limit`, finishing the current block and writing the
+    /// checkpoint first. Combines with [`end_at_block`](Self::end_at_block); whichever is
+    /// reached first ends the run.
+    pub fn end_after(mut self, limit: Duration) -> Self {
+        self.end_after = Some(limit);
+        self
+    }
+
+    /// Stop at `time`, finishing the current block and writing the checkpoint first.
+    pub fn end_at_time(mut self, time: SystemTime) -> Self {
+        self.end_at_time = Some(time);
+        self
+    }
+
     /// Set a maximum number of blocks to process per minute.
     pub fn max_blocks_per_minute(mut self, value: u32) -> Self {
         self.max_blocks_per_minute = Some(value);
         self
     }
 
+    /// Follow finalized blocks (the default) or best blocks.
+    pub fn finality(mut self, mode: FinalityMode) -> Self {
+        self.finality = Some(mode);
+        self
+    }
+
+    /// Write the checkpoint every `interval` handled blocks instead of after each one.
+    ///
+    /// Fewer writes speed up backfills; after a restart up to `interval - 1` blocks are
+    /// handled again.
+    pub fn checkpoint_interval(mut self, interval: u64) -> Self {
+        self.checkpoint_interval = Some(interval);
+        self
+    }
+
+    /// Most blocks unwound when following best blocks and the chain reorganizes
+    /// (default 64). Handlers learn about unwound blocks through
+    /// [`Handler::on_reorg`]; deeper reorgs stop the indexer with
+    /// [`IndexerError::ReorgTooDeep`].
+    pub fn max_reorg_depth(mut self, depth: u32) -> Self {
+        self.max_reorg_depth = Some(depth);
+        self
+    }
+
+    /// Stop the run with [`IndexerError::ErrorBudgetExhausted`] once handlers have failed
+    /// more than `budget` times. Can be changed while running through
+    /// [`Indexer::settings_handle`].
+    pub fn handler_error_budget(mut self, budget: u64) -> Self {
+        self.handler_error_budget = Some(budget);
+        self
+    }
+
+    /// Report handler failures, including those absorbed by non-strict
+    /// [`HandlerGroup`](crate::handler_group::HandlerGroup)s, retry give-ups and circuit
+    /// breaker openings to `reporter`. They are logged with [`LogReporter`] by default.
+    pub fn error_reporter(mut self, reporter: impl ErrorReporter + 'static) -> Self {
+        self.error_reporter = Some(Arc::new(reporter));
+        self
+    }
+
+    /// Report failures of the same handler at most once every `blocks` blocks,
+    /// [`DEFAULT_REPORT_INTERVAL`](crate::reporter::DEFAULT_REPORT_INTERVAL) by default.
+    pub fn error_report_interval(mut self, blocks: u64) -> Self {
+        self.error_report_interval = Some(blocks);
+        self
+    }
+
+    /// Dispatch only events matching one of `patterns`, to every handler. All events are
+    /// dispatched when no patterns are given.
+    pub fn event_allowlist(mut self, patterns: impl IntoIterator<Item = EventPattern>) -> Self {
+        self.event_allowlist = patterns.into_iter().collect();
+        self
+    }
+
+    /// Fetch the extrinsics of every block so handlers can attribute events to the
+    /// extrinsic, call and signer behind them with
+    /// [`Context::extrinsic_info`](crate::handler::Context::extrinsic_info), and to
+    /// call [`Handler::handle_extrinsic`](crate::handler::Handler::handle_extrinsic).
+    /// Off by default, as it costs one extra RPC call per block.
+    pub fn correlate_extrinsics(mut self, correlate: bool) -> Self {
+        self.correlate_extrinsics = Some(correlate);
+        self
+    }
+
+    /// Resolve which validator authored each block, exposed to handlers as
+    /// [`Context::block_author`](crate::handler::Context::block_author). The author
+    /// is found from the slot in the header's pre-runtime digest (AURA or BABE) and
+    /// `Session.Validators`, which is read once per session rather than per block.
+    /// Off by default.
+    pub fn resolve_block_author(mut self, resolve: bool) -> Self {
+        self.resolve_block_author = Some(resolve);
+        self
+    }
+
+    /// Attach the fee and tip each extrinsic paid, from the block's
+    /// `TransactionPayment.TransactionFeePaid` events, exposed to handlers as
+    /// [`Context::extrinsic_fee`](crate::handler::Context::extrinsic_fee). Needs
+    /// [`correlate_extrinsics`](Self::correlate_extrinsics) and no extra RPC call. On
+    /// runtimes without that event the fees are simply `None`. Off by default.
+    pub fn track_fees(mut self, track: bool) -> Self {
+        self.track_fees = Some(track);
+        self
+    }
+
+    /// Fail fast on handler panics. By default a panic in a handler hook is caught
+    /// and turned into an [`IndexerError::HandlerFailed`] carrying the panic
+    /// message, which is reported and counted against the
+    /// [`handler_error_budget`](Self::handler_error_budget) like any other handler
+    /// error. With `abort_on_panic(true)` the panic unwinds through the indexer
+    /// task instead, stopping it.
+    pub fn abort_on_panic(mut self, abort: bool) -> Self {
+        self.abort_on_panic = Some(abort);
+        self
+    }
+
+    /// Keep going when an event of a block fails to decode. By default the block
+    /// fails with [`IndexerError::EventDecodingFailed`], which stops the indexer.
+    /// With `skip_undecodable_events(true)` the error is passed to every handler's
+    /// [`handle_error`](crate::handler::Handler::handle_error), reported and counted
+    /// like a handler error, and the events decoded before it are dispatched. The
+    /// events after it are lost, as an undecodable event hides where the next one
+    /// starts.
+    pub fn skip_undecodable_events(mut self, skip: bool) -> Self {
+        self.skip_undecodable_events = Some(skip);
+        self
+    }
+
+    /// Report the indexer as stale once the checkpoint has not advanced for `threshold`.
+    pub fn stale_after(mut self, threshold: Duration) -> Self {
+        self.stale_after = Some(threshold);
+        self
+    }
+
+    /// Record every fetched block to the file at `path`, see
+    /// [`Indexer::record_to`].
+    pub fn record_to(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_to = Some(path.into());
+        self
+    }
+
+    /// Replay the recording at `path` instead of fetching blocks from a node,
+    /// see [`ReplaySource`]. The start and end blocks apply as usual, and no
+    /// node URL is needed.
+    pub fn replay_from(mut self, path: impl Into<PathBuf>) -> Self {
+        self.replay_from = Some(path.into());
+        self
+    }
+
+    /// Fetch blocks from `source` instead of connecting to the node, e.g. a
+    /// [`MockBlockSource`](crate::testing::MockBlockSource) in tests. No node
+    /// URL is needed; the client is set up with the source's runtime.
+    pub fn block_source(mut self, source: impl BlockSource<C> + 'static) -> Self {
+        self.block_source = Some(Arc::new(source));
+        self
+    }
+
+    /// Make node RPC calls through `rpc`, e.g. a reconnecting client or one
+    /// with custom headers, instead of connecting to the node URL. The node
+    /// URL is then optional.
+    pub fn rpc_client(mut self, rpc: RpcClient) -> Self {
+        self.rpc_client = Some(rpc);
+        self
+    }
+
+    /// Touch the file at `path` every `interval` and after each processed block,
+    /// for liveness probes that check its modification time. The run loop
+    /// touches it itself, so it goes stale while the loop is stuck or a circuit
+    /// breaker is open. See [`HeartbeatFile`].
+    pub fn heartbeat_file(mut self, path: impl Into<PathBuf>, interval: Duration) -> Self {
+        self.heartbeat = Some((path.into(), interval));
+        self
+    }
+
+    /// Only touch the [`heartbeat_file`](Self::heartbeat_file) when the processed
+    /// block number advanced since the last touch.
+    pub fn heartbeat_requires_progress(mut self, requires_progress: bool) -> Self {
+        self.heartbeat_requires_progress = requires_progress;
+        self
+    }
+
+    /// Report to `metrics` instead of a fresh [`IndexerMetrics`], e.g. to share its
+    /// registry with [`SubnetMetrics`](crate::presets::subnet_metrics::SubnetMetrics).
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, metrics: Arc<IndexerMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Serve Prometheus metrics on `GET /metrics` at `addr`, e.g. `"0.0.0.0:9185"`.
+    /// Port 0 picks a free port, see [`Indexer::metrics_addr`]. The endpoint stops
+    /// when the indexer is dropped.
+    #[cfg(feature = "metrics")]
+    pub fn serve_metrics(mut self, addr: impl Into<String>) -> Self {
+        self.metrics_addr = Some(addr.into());
+        self
+    }
+
+    /// Send metrics to StatsD through `statsd` instead of Prometheus, see
+    /// [`StatsdMetrics`]. Cannot be combined with [`metrics`](Self::metrics) or
+    /// [`serve_metrics`](Self::serve_metrics).
+    #[cfg(feature = "statsd")]
+    pub fn statsd(mut self, statsd: Arc<StatsdMetrics>) -> Self {
+        self.statsd = Some(statsd);
+        self
+    }
+
+    /// Stream indexed events to gRPC subscribers at `addr`, e.g. `"0.0.0.0:50051"`,
+    /// through an [`EventStreamService`] over an [`EventBroadcast`] added as a
+    /// handler. Port 0 picks a free
+    /// port, see [`Indexer::grpc_addr`]. The server stops when the indexer is
+    /// dropped.
+    #[cfg(feature = "grpc")]
+    pub fn serve_grpc(mut self, addr: impl Into<String>) -> Self {
+        self.grpc_addr = Some(addr.into());
+        self
+    }
+
+    /// Serve `GET /status`, `GET /checkpoint`, the `/ws` event feed and, with
+    /// metrics enabled, `GET /metrics` at `addr`, e.g. `"0.0.0.0:8080"`; see
+    /// [`http_api`](crate::http_api). Port 0 picks a free port, see
+    /// [`Indexer::status_addr`]. The server shuts down gracefully when the
+    /// indexer is dropped.
+    #[cfg(feature = "http-api")]
+    pub fn serve_status(mut self, addr: impl Into<String>) -> Self {
+        self.status_addr = Some(addr.into());
+        self
+    }
+
     /// Add a handler to the indexer.
     pub fn add_handler(mut self, handler: impl Handler<C> + 'static) -> Self {
         self.handlers.push(Box::new(handler));
@@ -112,33 +634,418 @@ where
         self
     }
 
+    /// Register a handler under `name`, so that a config file can enable it by name.
+    ///
+    /// Registered handlers run after those added with [`add_handler`](Self::add_handler).
+    /// When [`IndexerConfig::handlers`] is set, only the listed ones are added, in the
+    /// listed order.
+    pub fn register_handler(
+        mut self,
+        name: impl Into<String>,
+        handler: impl Handler<C> + 'static,
+    ) -> Self {
+        self.named_handlers.push((name.into(), Box::new(handler)));
+        self
+    }
+
+    /// Start from a complete [`IndexerConfig`], e.g. one loaded with
+    /// `IndexerConfig::from_file`. Builder methods called afterwards override its values.
+    pub fn from_config(mut config: IndexerConfig) -> Result<Self, IndexerError> {
+        config.validate()?;
+        config.normalize()?;
+        let mut builder = Self::new();
+        builder.node_urls = config
+            .node_urls
+            .iter()
+            .map(|url| WebSocketUrl::parse(url).map(|url| url.with_auth(config.node_auth.clone())))
+            .collect::<Result<_, _>>()?;
+        builder.storage = Some(config.resolved_storage()?);
+        builder.start_from = Some(config.start_from);
+        builder.end_block = config.end_block;
+        builder.strict_range = Some(config.strict_range);
+        builder.range_tolerance = Some(config.range_tolerance);
+        builder.end_after = config.end_after;
+        builder.end_at_time = config.end_at_time;
+        builder.max_blocks_per_minute = config.max_blocks_per_minute;
+        builder.finality = Some(config.finality);
+        builder.checkpoint_interval = Some(config.checkpoint_interval);
+        builder.max_reorg_depth = Some(config.max_reorg_depth);
+        builder.handler_error_budget = config.handler_error_budget;
+        builder.event_allowlist = config.event_allowlist;
+        builder.correlate_extrinsics = Some(config.correlate_extrinsics);
+        builder.resolve_block_author = Some(config.resolve_block_author);
+        builder.track_fees = Some(config.track_fees);
+        builder.abort_on_panic = Some(config.abort_on_panic);
+        builder.skip_undecodable_events = Some(config.skip_undecodable_events);
+        builder.retry_config = Some(config.retry);
+        builder.circuit_breaker = Some(config.circuit_breaker);
+        builder.rpc_timeout = config.rpc_timeout;
+        builder.hedge_after = config.hedge_after;
+        builder.tls_mode = Some(config.tls_mode);
+        builder.queue_depth = Some(config.queue_depth);
+        builder.storage_retry = Some(config.storage_retry);
+        builder.storage_circuit_breaker = Some(config.storage_circuit_breaker);
+        builder.enabled_handlers = config.handlers;
+        Ok(builder)
+    }
+
+    /// Check the configuration and enabled handler names without connecting to the
+    /// node or the checkpoint store.
+    pub fn validate(&self) -> Result<(), IndexerError> {
+        self.config()?;
+        #[cfg(feature = "metrics")]
+        self.metrics_addr()?;
+        #[cfg(all(feature = "metrics", feature = "statsd"))]
+        self.check_metrics_backend()?;
+        self.check_heartbeat()?;
+        #[cfg(feature = "grpc")]
+        self.grpc_addr()?;
+        #[cfg(feature = "http-api")]
+        self.status_addr()?;
+        self.check_handler_names()
+    }
+
     /// Build the indexer.
     pub async fn build(self) -> Result<Indexer<C>, IndexerError> {
-        let node_url = self
-            .node_url
-            .ok_or_else(|| IndexerError::invalid_config("node_url", "missing"))?;
+        self.assemble(None).await
+    }
+
+    /// Build the indexer around `client` instead of connecting one. Nothing is
+    /// connected to until [`Indexer::run`], which still makes its node RPC calls
+    /// through [`rpc_client`](Self::rpc_client) or the node URL.
+    pub async fn build_with_client(
+        self,
+        client: OnlineClient<C>,
+    ) -> Result<Indexer<C>, IndexerError> {
+        self.assemble(Some(client)).await
+    }
+
+    async fn assemble(self, client: Option<OnlineClient<C>>) -> Result<Indexer<C>, IndexerError> {
+        let config = self.config()?;
+        #[cfg(feature = "metrics")]
+        let metrics_addr = self.metrics_addr()?;
+        #[cfg(all(feature = "metrics", feature = "statsd"))]
+        self.check_metrics_backend()?;
+        self.check_heartbeat()?;
+        #[cfg(feature = "grpc")]
+        let grpc_addr = self.grpc_addr()?;
+        #[cfg(feature = "http-api")]
+        let status_addr = self.status_addr()?;
+        self.check_handler_names()?;
+
+        let replay = self
+            .replay_from
+            .map(ReplaySource::open)
+            .transpose()?
+            .map(Arc::new);
+        let client = match (client, &replay, &self.block_source) {
+            (Some(client), _, _) => client,
+            (None, Some(replay), _) => replay.client::<C>()?,
+            (None, None, Some(source)) => offline_client(source.as_ref()).await?,
+            (None, None, None) => {
+                let rpc = match &self.rpc_client {
+                    Some(rpc) => rpc.clone(),
+                    None => {
+                        rpc::connect(
+                            config.node_url(),
+                            config.node_auth.as_ref(),
+                            config.tls_mode,
+                        )
+                        .await?
+                    }
+                };
+                OnlineClient::<C>::from_rpc_client(rpc).await.map_err(|e| {
+                    IndexerError::ConnectionFailed {
+                        url: config.node_url().to_string(),
+                        source: Box::new(e),
+                    }
+                })?
+            }
+        };
+        let store = match self.store {
+            Some(store) => store,
+            None => init_store(self.storage.unwrap_or_default()).await?,
+        };
+
+        let mut named = self.named_handlers;
+        let named: Vec<_> = match self.enabled_handlers {
+            Some(enabled) => enabled
+                .iter()
+                .filter_map(|name| {
+                    let index = named.iter().position(|(n, _)| n == name)?;
+                    Some(named.remove(index).1)
+                })
+                .collect(),
+            None => named.into_iter().map(|(_, handler)| handler).collect(),
+        };
+
+        let mut indexer = Indexer::new(client, store, config).await?;
+        indexer.stale_after = self.stale_after;
+        indexer.replay = replay;
+        indexer.source = self.block_source;
+        indexer.rpc_client = self.rpc_client;
+        if let Some(path) = self.record_to {
+            indexer.record_to(path)?;
+        }
+        indexer.heartbeat = self.heartbeat.map(|(path, interval)| {
+            HeartbeatFile::new(path, interval).requires_progress(self.heartbeat_requires_progress)
+        });
+        if self.error_reporter.is_some() || self.error_report_interval.is_some() {
+            indexer.set_error_reporter(
+                self.error_reporter.unwrap_or_else(|| Arc::new(LogReporter)),
+                self.error_report_interval
+                    .unwrap_or(DEFAULT_REPORT_INTERVAL),
+            );
+        }
+        #[cfg(feature = "metrics")]
+        {
+            let metrics = match (self.metrics, metrics_addr) {
+                (Some(metrics), _) => Some(metrics),
+                (None, Some(_)) => Some(Arc::new(IndexerMetrics::new()?)),
+                (None, None) => None,
+            };
+            if let Some(metrics) = metrics {
+                if let Some(addr) = metrics_addr {
+                    indexer.metrics_server =
+                        Some(MetricsServer::bind(addr, metrics.clone()).await?);
+                }
+                indexer.set_metrics(metrics);
+            }
+        }
+        #[cfg(feature = "statsd")]
+        if let Some(statsd) = self.statsd {
+            indexer.set_recorder(statsd);
+        }
+        for h in self.handlers.into_iter().chain(named) {
+            indexer.add_dyn_handler(h)?;
+        }
+        #[cfg(any(feature = "grpc", feature = "http-api"))]
+        {
+            // The gRPC stream and the `/ws` feed share one broadcast handler.
+            let events = EventBroadcast::default();
+            let mut live = false;
+            #[cfg(feature = "grpc")]
+            if let Some(addr) = grpc_addr {
+                let service = EventStreamService::new(events.clone());
+                indexer.grpc_server = Some(GrpcServer::bind(addr, service).await?);
+                live = true;
+            }
+            #[cfg(feature = "http-api")]
+            if let Some(addr) = status_addr {
+                indexer.serve_status(addr, events.clone()).await?;
+                live = true;
+            }
+            if live {
+                indexer.add_handler(events)?;
+            }
+        }
+
+        Ok(indexer)
+    }
 
-        let client = OnlineClient::<C>::from_insecure_url(node_url.as_str()).await?;
-        let store = init_store(self.database_url.clone()).await?;
+    /// The validated configuration the indexer will be built with.
+    fn config(&self) -> Result<IndexerConfig, IndexerError> {
+        let offline = self.replay_from.is_some() || self.block_source.is_some();
+        if self.node_urls.is_empty() && !offline && self.rpc_client.is_none() {
+            return Err(IndexerError::invalid_config("node_url", "missing"));
+        }
+        if self.replay_from.is_some() && self.block_source.is_some() {
+            return Err(IndexerError::invalid_config(
+                "block_source",
+                "cannot be combined with replay_from",
+            ));
+        }
+        if self.replay_from.is_some() && self.record_to.is_some() {
+            return Err(IndexerError::invalid_config(
+                "record_to",
+                "cannot record while replaying a recording",
+            ));
+        }
 
-        let mut cfg_builder = IndexerConfig::builder().node_url(node_url.as_str());
-        if let Some(ref db) = self.database_url {
-            cfg_builder = cfg_builder.with_postgres(db);
+        let mut cfg_builder = IndexerConfig::builder();
+        for url in &self.node_urls {
+            cfg_builder = cfg_builder.add_node_url(url.as_str());
+        }
+        if self.node_urls.is_empty() {
+            // Never connected to: the replay, block source or RPC client stands in
+            // for the node.
+            cfg_builder = cfg_builder.add_node_url(OFFLINE_NODE_URL);
+        }
+        let auth = self
+            .node_urls
+            .iter()
+            .chain(&self.secondary)
+            .find_map(WebSocketUrl::auth);
+        if let Some(auth) = auth {
+            cfg_builder = cfg_builder.node_auth(auth.clone());
+        }
+        if let Some(mode) = self.tls_mode {
+            cfg_builder = cfg_builder.tls_mode(mode);
         }
-        if let Some(block) = self.start_block {
-            cfg_builder = cfg_builder.start_from_block(block);
+        if let Some(storage) = &self.storage {
+            cfg_builder = cfg_builder.storage(storage.clone());
+        }
+        if let Some(start) = self.start_from {
+            cfg_builder = cfg_builder.start_from(start);
         }
         if let Some(block) = self.end_block {
             cfg_builder = cfg_builder.end_at_block(block);
         }
-        let config = cfg_builder.build()?;
+        if let Some(strict) = self.strict_range {
+            cfg_builder = cfg_builder.strict_range(strict);
+        }
+        if let Some(blocks) = self.range_tolerance {
+            cfg_builder = cfg_builder.range_tolerance(blocks);
+        }
+        if let Some(limit) = self.end_after {
+            cfg_builder = cfg_builder.end_after(limit);
+        }
+        if let Some(time) = self.end_at_time {
+            cfg_builder = cfg_builder.end_at_time(time);
+        }
+        if let Some(retry) = &self.retry_config {
+            cfg_builder = cfg_builder.retry_config(retry.clone());
+        }
+        if let Some(breaker) = &self.circuit_breaker {
+            cfg_builder = cfg_builder.circuit_breaker_config(breaker.clone());
+        }
+        if let Some(timeout) = self.rpc_timeout {
+            cfg_builder = cfg_builder.rpc_timeout(timeout);
+        }
+        if let Some(secondary) = &self.secondary {
+            cfg_builder = cfg_builder.secondary_node_url(secondary.as_str());
+        }
+        if let Some(delay) = self.hedge_after {
+            cfg_builder = cfg_builder.hedge_after(delay);
+        }
+        if let Some(value) = self.max_blocks_per_minute {
+            cfg_builder = cfg_builder.max_blocks_per_minute(value);
+        }
+        if let Some(mode) = self.finality {
+            cfg_builder = cfg_builder.finality(mode);
+        }
+        if let Some(interval) = self.checkpoint_interval {
+            cfg_builder = cfg_builder.checkpoint_interval(interval);
+        }
+        if let Some(depth) = self.max_reorg_depth {
+            cfg_builder = cfg_builder.max_reorg_depth(depth);
+        }
+        if let Some(budget) = self.handler_error_budget {
+            cfg_builder = cfg_builder.handler_error_budget(budget);
+        }
+        cfg_builder = cfg_builder.event_allowlist(self.event_allowlist.clone());
+        if let Some(correlate) = self.correlate_extrinsics {
+            cfg_builder = cfg_builder.correlate_extrinsics(correlate);
+        }
+        if let Some(resolve) = self.resolve_block_author {
+            cfg_builder = cfg_builder.resolve_block_author(resolve);
+        }
+        if let Some(track) = self.track_fees {
+            cfg_builder = cfg_builder.track_fees(track);
+        }
+        if let Some(abort) = self.abort_on_panic {
+            cfg_builder = cfg_builder.abort_on_panic(abort);
+        }
+        if let Some(skip) = self.skip_undecodable_events {
+            cfg_builder = cfg_builder.skip_undecodable_events(skip);
+        }
+        if let Some(depth) = self.queue_depth {
+            cfg_builder = cfg_builder.queue_depth(depth);
+        }
+        if let Some(retry) = &self.storage_retry {
+            cfg_builder = cfg_builder.storage_retry(retry.clone());
+        }
+        if let Some(breaker) = &self.storage_circuit_breaker {
+            cfg_builder = cfg_builder.storage_circuit_breaker_config(breaker.clone());
+        }
+        let mut config = cfg_builder.build()?;
+        if let Some(predicate) = &self.retryable_errors {
+            config.retry.retry_predicate = Some(predicate.clone());
+            config.storage_retry.retry_predicate = Some(predicate.clone());
+        }
+        config.handlers = self.enabled_handlers.clone();
+        Ok(config)
+    }
 
-        let mut indexer = Indexer::new(client, store, config).await?;
-        indexer.max_blocks_per_minute = self.max_blocks_per_minute;
-        for h in self.handlers {
-            indexer.add_dyn_handler(h)?;
+    /// The parsed [`serve_metrics`](Self::serve_metrics) address.
+    #[cfg(feature = "metrics")]
+    fn metrics_addr(&self) -> Result<Option<std::net::SocketAddr>, IndexerError> {
+        self.metrics_addr
+            .as_deref()
+            .map(|addr| {
+                addr.parse().map_err(|e| {
+                    IndexerError::invalid_config("metrics_addr", format!("`{addr}`: {e}"))
+                })
+            })
+            .transpose()
+    }
+
+    fn check_heartbeat(&self) -> Result<(), IndexerError> {
+        match &self.heartbeat {
+            Some((_, interval)) if interval.is_zero() => Err(IndexerError::invalid_config(
+                "heartbeat_interval",
+                "must be greater than zero",
+            )),
+            _ => Ok(()),
         }
+    }
 
-        Ok(indexer)
+    /// Prometheus and StatsD are alternative metrics backends.
+    #[cfg(all(feature = "metrics", feature = "statsd"))]
+    fn check_metrics_backend(&self) -> Result<(), IndexerError> {
+        if self.statsd.is_some() && (self.metrics.is_some() || self.metrics_addr.is_some()) {
+            return Err(IndexerError::invalid_config(
+                "statsd",
+                "cannot be combined with Prometheus metrics",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The parsed [`serve_grpc`](Self::serve_grpc) address.
+    #[cfg(feature = "grpc")]
+    fn grpc_addr(&self) -> Result<Option<std::net::SocketAddr>, IndexerError> {
+        self.grpc_addr
+            .as_deref()
+            .map(|addr| {
+                addr.parse().map_err(|e| {
+                    IndexerError::invalid_config("grpc_addr", format!("`{addr}`: {e}"))
+                })
+            })
+            .transpose()
+    }
+
+    /// The parsed [`serve_status`](Self::serve_status) address.
+    #[cfg(feature = "http-api")]
+    fn status_addr(&self) -> Result<Option<std::net::SocketAddr>, IndexerError> {
+        self.status_addr
+            .as_deref()
+            .map(|addr| {
+                addr.parse().map_err(|e| {
+                    IndexerError::invalid_config("status_addr", format!("`{addr}`: {e}"))
+                })
+            })
+            .transpose()
+    }
+
+    fn check_handler_names(&self) -> Result<(), IndexerError> {
+        let Some(enabled) = &self.enabled_handlers else {
+            return Ok(());
+        };
+        for (i, name) in enabled.iter().enumerate() {
+            if !self.named_handlers.iter().any(|(n, _)| n == name) {
+                return Err(IndexerError::invalid_config(
+                    "handlers",
+                    format!("unknown handler `{name}`"),
+                ));
+            }
+            if enabled[..i].contains(name) {
+                return Err(IndexerError::invalid_config(
+                    "handlers",
+                    format!("handler `{name}` is listed twice"),
+                ));
+            }
+        }
+        Ok(())
     }
 }
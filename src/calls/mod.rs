@@ -0,0 +1,26 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Typed definitions of common Bittensor calls, behind the `bittensor-events`
+//! feature, for decoding with
+//! [`ChainExtrinsic::as_call`](crate::extrinsics::ChainExtrinsic::as_call).
+//!
+//! Calls such as `set_weights` emit no event carrying their arguments, so the
+//! extrinsic itself has to be decoded. Arguments are matched by name through
+//! the runtime metadata, so one struct covers runtimes that reordered them or
+//! changed their integer widths.
+
+pub mod subtensor;
@@ -0,0 +1,73 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Calls of the `SubtensorModule` pallet.
+
+use scale_decode::DecodeAsType;
+use subxt::blocks::StaticExtrinsic;
+use subxt::utils::AccountId32;
+
+pub use crate::filters::subtensor::PALLET;
+
+/// A validator setting its weights on a subnet. `dests` are neuron uids and
+/// `weights` their u16-normalized weights, position by position.
+#[derive(Debug, Clone, PartialEq, Eq, DecodeAsType)]
+pub struct SetWeightsCall {
+    pub netuid: u16,
+    pub dests: Vec<u16>,
+    pub weights: Vec<u16>,
+    pub version_key: u64,
+}
+
+impl StaticExtrinsic for SetWeightsCall {
+    const PALLET: &'static str = PALLET;
+    const CALL: &'static str = "set_weights";
+}
+
+/// A hotkey announcing its axon endpoint on a subnet. `ip` holds an IPv4
+/// address in its low 32 bits when `ip_type` is 4.
+#[derive(Debug, Clone, PartialEq, Eq, DecodeAsType)]
+pub struct ServeAxonCall {
+    pub netuid: u16,
+    pub version: u32,
+    pub ip: u128,
+    pub port: u16,
+    pub ip_type: u8,
+    pub protocol: u8,
+    pub placeholder1: u8,
+    pub placeholder2: u8,
+}
+
+impl StaticExtrinsic for ServeAxonCall {
+    const PALLET: &'static str = PALLET;
+    const CALL: &'static str = "serve_axon";
+}
+
+/// A proof-of-work registration of `hotkey`, owned by `coldkey`, on a subnet.
+#[derive(Debug, Clone, PartialEq, Eq, DecodeAsType)]
+pub struct RegisterCall {
+    pub netuid: u16,
+    pub block_number: u64,
+    pub nonce: u64,
+    pub work: Vec<u8>,
+    pub hotkey: AccountId32,
+    pub coldkey: AccountId32,
+}
+
+impl StaticExtrinsic for RegisterCall {
+    const PALLET: &'static str = PALLET;
+    const CALL: &'static str = "register";
+}
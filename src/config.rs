@@ -15,14 +15,351 @@
  */
 
 use crate::error::IndexerError;
+use crate::handler::EventPattern;
+use crate::queue::DEFAULT_QUEUE_DEPTH;
+use crate::redact::redact_url;
+use crate::reorg::DEFAULT_MAX_REORG_DEPTH;
+use crate::retry::{CircuitBreakerConfig, JitterMode, RetryConfig};
+use crate::storage::{CheckpointStore, StorageConfig};
 use crate::types::BlockNumber;
+use crate::validated_types::{BlockRange, NodeAuth, WebSocketUrl};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+/// Which blocks the indexer follows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FinalityMode {
+    /// Only finalized blocks; never reorganized.
+    #[default]
+    Finalized,
+    /// Best (head) blocks as soon as they are imported. Lower latency, but blocks may
+    /// later be reorganized out; handlers are told through
+    /// [`Handler::on_reorg`](crate::handler::Handler::on_reorg) and the new blocks are
+    /// handled again, up to `max_reorg_depth` blocks deep.
+    Best,
+}
+
+/// How the certificates of `wss://` nodes are checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsMode {
+    /// Verify certificates against the platform's trusted roots.
+    #[default]
+    Secure,
+    /// Accept any certificate, including self-signed and expired ones. The connection
+    /// is still encrypted but the node is not authenticated; only for testing.
+    Insecure,
+}
+
+/// Starting points for common deployments; see [`IndexerConfig::preset`].
+///
+/// In config files this is written as `preset = "archive-backfill"`, `"live-tail"` or
+/// `"development"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    /// Long backfills against an archive node: patient retries with jitter, generous RPC
+    /// timeouts, a deep queue and a checkpoint every 100 blocks.
+    ArchiveBackfill,
+    /// Following the chain head with low latency: best blocks, quick retries, a shallow
+    /// queue and a checkpoint after every block.
+    LiveTail,
+    /// Local experiments: a single attempt per call and at most one block per second.
+    Development,
+}
+
+/// Where the indexer starts.
+///
+/// In config files this is written as `"genesis"`, `"checkpoint"`, `"latest"` or a block
+/// number.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StartFromRepr", into = "StartFromRepr")]
+pub enum StartFrom {
+    /// Block 0.
+    Genesis,
+    /// The stored checkpoint, or block 0 when there is none.
+    #[default]
+    Checkpoint,
+    /// The given block, ignoring any checkpoint.
+    Block(BlockNumber),
+    /// The chain head (finalized or best, per [`FinalityMode`]) when the run starts.
+    Latest,
+}
+
+impl StartFrom {
+    /// The first block to index, given the checkpoint `store` and the current head
+    /// `latest`.
+    pub async fn resolve(
+        &self,
+        store: &dyn CheckpointStore,
+        latest: BlockNumber,
+    ) -> Result<BlockNumber, IndexerError> {
+        Ok(match *self {
+            Self::Genesis => 0,
+            Self::Checkpoint => store.load_checkpoint().await?.unwrap_or(0),
+            Self::Block(block) => block,
+            Self::Latest => latest,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+enum StartFromRepr {
+    Block(BlockNumber),
+    Named(NamedStart),
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum NamedStart {
+    Genesis,
+    Checkpoint,
+    Latest,
+}
+
+impl From<StartFromRepr> for StartFrom {
+    fn from(repr: StartFromRepr) -> Self {
+        match repr {
+            StartFromRepr::Block(block) => Self::Block(block),
+            StartFromRepr::Named(NamedStart::Genesis) => Self::Genesis,
+            StartFromRepr::Named(NamedStart::Checkpoint) => Self::Checkpoint,
+            StartFromRepr::Named(NamedStart::Latest) => Self::Latest,
+        }
+    }
+}
+
+impl From<StartFrom> for StartFromRepr {
+    fn from(start: StartFrom) -> Self {
+        match start {
+            StartFrom::Genesis => Self::Named(NamedStart::Genesis),
+            StartFrom::Checkpoint => Self::Named(NamedStart::Checkpoint),
+            StartFrom::Block(block) => Self::Block(block),
+            StartFrom::Latest => Self::Named(NamedStart::Latest),
+        }
+    }
+}
 
 /// Configuration for the [`Indexer`](crate::indexer::Indexer).
+///
+/// Can be loaded from TOML or YAML with [`IndexerConfig::from_file`] (feature
+/// `config-file`). Missing keys take their default value, unknown keys are rejected, and
+/// durations are written as strings such as `"500ms"` or `"1m"`.
+///
+/// The `Debug` output masks credentials in the node and database URLs with
+/// [`redact_url`].
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct IndexerConfig {
-    pub node_url: String,
+    /// Websocket URLs of the nodes to index from. The indexer connects to the first;
+    /// slow read-only calls are hedged to the second once `hedge_after` is set. Later
+    /// entries are not used. Config files may also give a single `node_url`.
+    #[serde(alias = "node_url", deserialize_with = "one_or_many")]
+    pub node_urls: Vec<String>,
+    /// Credentials sent in the handshake of every node connection, for providers that
+    /// require an `Authorization` header.
+    pub node_auth: Option<NodeAuth>,
+    /// How the certificates of `wss://` nodes are checked.
+    pub tls_mode: TlsMode,
+    /// Where checkpoints are stored. JSON at `database/checkpoint.json` by default.
+    pub storage: StorageConfig,
+    /// Deprecated: set `storage` instead.
+    ///
+    /// A Postgres or SQLite URL here selects that backend in place of `storage`. Built
+    /// and loaded configurations keep it in step with `storage`, so existing readers
+    /// still find the URL.
     pub database_url: Option<String>,
-    pub start_block: Option<BlockNumber>,
+    /// Where to start. Config files may also give a block number as `start_block`.
+    #[serde(alias = "start_block")]
+    pub start_from: StartFrom,
     pub end_block: Option<BlockNumber>,
+    /// Fail at startup, instead of warning, when `start_from` is a block more than
+    /// `range_tolerance` blocks past the chain head. See [`validate_range`].
+    pub strict_range: bool,
+    /// How far past the chain head a configured start block may lie.
+    pub range_tolerance: BlockNumber,
+    /// Stop once the run has lasted this long, after finishing the current block.
+    #[serde(with = "crate::serde_duration::option")]
+    pub end_after: Option<Duration>,
+    /// Stop at this time, after finishing the current block. Written as an RFC 3339
+    /// timestamp in config files.
+    #[serde(with = "crate::serde_time::option")]
+    pub end_at_time: Option<SystemTime>,
+    /// Retry policy for node RPC calls.
+    pub retry: RetryConfig,
+    /// Circuit breaker guarding node RPC calls.
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Time limit for each attempt of a node RPC call. Unlimited when unset.
+    #[serde(with = "crate::serde_duration::option")]
+    pub rpc_timeout: Option<Duration>,
+    /// Deprecated: add the node as the second entry of `node_urls` instead.
+    ///
+    /// Given alone, the URL is moved into `node_urls`. Built and loaded configurations
+    /// keep it in step with `node_urls[1]`, so existing readers still find it.
+    pub secondary_node_url: Option<String>,
+    /// How long to wait for the primary node before hedging to the second entry of
+    /// `node_urls`. Hedging is off when unset.
+    #[serde(with = "crate::serde_duration::option")]
+    pub hedge_after: Option<Duration>,
+    /// Retry policy for checkpoint loads and writes.
+    pub storage_retry: RetryConfig,
+    /// Circuit breaker guarding checkpoint loads and writes.
+    pub storage_circuit_breaker: CircuitBreakerConfig,
+    /// Number of fetched blocks buffered ahead of the handlers.
+    pub queue_depth: usize,
+    /// Maximum number of blocks processed per minute. Unlimited when unset.
+    pub max_blocks_per_minute: Option<u32>,
+    /// Which blocks to follow.
+    pub finality: FinalityMode,
+    /// Write the checkpoint every `checkpoint_interval` handled blocks (and when the run
+    /// ends). Blocks handled since the last checkpoint are handled again after a restart.
+    pub checkpoint_interval: u64,
+    /// Most blocks unwound on a reorg of best blocks. Deeper reorgs stop the indexer
+    /// with [`IndexerError::ReorgTooDeep`].
+    pub max_reorg_depth: u32,
+    /// Stop with [`IndexerError::ErrorBudgetExhausted`] once handlers have failed more
+    /// than this many times in a run. Handler errors only reach
+    /// [`Handler::handle_error`](crate::handler::Handler::handle_error) when unset.
+    pub handler_error_budget: Option<u64>,
+    /// Names of the handlers registered with
+    /// [`IndexerBuilder::register_handler`](crate::builder::IndexerBuilder::register_handler)
+    /// to enable, in order. All registered handlers are enabled when unset.
+    pub handlers: Option<Vec<String>>,
+    /// Events dispatched to handlers, as patterns such as `"Balances.Transfer"` or
+    /// `"SubtensorModule.*"`. Other events are dropped before any handler sees them,
+    /// including handlers using [`EventFilter::all`](crate::handler::EventFilter::all).
+    /// Every event is dispatched when empty.
+    pub event_allowlist: Vec<EventPattern>,
+    /// Fetch each block's extrinsics so handlers can look up the extrinsic behind an
+    /// event with [`Context::extrinsic_info`](crate::handler::Context::extrinsic_info)
+    /// and receive them in [`Handler::handle_extrinsic`](crate::handler::Handler::handle_extrinsic).
+    /// Costs one extra RPC call per block.
+    pub correlate_extrinsics: bool,
+    /// Resolve the validator that authored each block, see
+    /// [`Context::block_author`](crate::handler::Context::block_author).
+    pub resolve_block_author: bool,
+    /// Attach the fee and tip of each extrinsic from the block's
+    /// `TransactionPayment.TransactionFeePaid` events, see
+    /// [`Context::extrinsic_fee`](crate::handler::Context::extrinsic_fee).
+    /// Requires `correlate_extrinsics`.
+    pub track_fees: bool,
+    /// Let a panicking handler unwind through the indexer instead of turning the
+    /// panic into an [`IndexerError::HandlerFailed`].
+    pub abort_on_panic: bool,
+    /// Dispatch the events of a block decoded before one that fails to decode,
+    /// instead of failing the block with [`IndexerError::EventDecodingFailed`].
+    pub skip_undecodable_events: bool,
+}
+
+impl fmt::Debug for IndexerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexerConfig")
+            .field(
+                "node_urls",
+                &self
+                    .node_urls
+                    .iter()
+                    .map(|url| redact_url(url))
+                    .collect::<Vec<_>>(),
+            )
+            .field("node_auth", &self.node_auth)
+            .field("tls_mode", &self.tls_mode)
+            .field("storage", &self.storage)
+            .field(
+                "database_url",
+                &self.database_url.as_deref().map(redact_url),
+            )
+            .field("start_from", &self.start_from)
+            .field("end_block", &self.end_block)
+            .field("strict_range", &self.strict_range)
+            .field("range_tolerance", &self.range_tolerance)
+            .field("end_after", &self.end_after)
+            .field("end_at_time", &self.end_at_time)
+            .field("retry", &self.retry)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("rpc_timeout", &self.rpc_timeout)
+            .field(
+                "secondary_node_url",
+                &self.secondary_node_url.as_deref().map(redact_url),
+            )
+            .field("hedge_after", &self.hedge_after)
+            .field("storage_retry", &self.storage_retry)
+            .field("storage_circuit_breaker", &self.storage_circuit_breaker)
+            .field("queue_depth", &self.queue_depth)
+            .field("max_blocks_per_minute", &self.max_blocks_per_minute)
+            .field("finality", &self.finality)
+            .field("checkpoint_interval", &self.checkpoint_interval)
+            .field("max_reorg_depth", &self.max_reorg_depth)
+            .field("handler_error_budget", &self.handler_error_budget)
+            .field("handlers", &self.handlers)
+            .field("event_allowlist", &self.event_allowlist)
+            .field("correlate_extrinsics", &self.correlate_extrinsics)
+            .field("resolve_block_author", &self.resolve_block_author)
+            .field("track_fees", &self.track_fees)
+            .field("abort_on_panic", &self.abort_on_panic)
+            .field("skip_undecodable_events", &self.skip_undecodable_events)
+            .finish()
+    }
+}
+
+/// Overlay `overrides` on `base`, merging nested tables key by key.
+#[cfg(feature = "config-file")]
+fn overlay(base: &mut serde_json::Value, overrides: serde_json::Value) {
+    use serde_json::Value;
+
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            // An alias in the overrides replaces the field it stands for, and a storage
+            // table replaces the preset's rather than merging into it.
+            for (alias, field) in [
+                ("node_url", "node_urls"),
+                ("start_block", "start_from"),
+                ("storage", "storage"),
+            ] {
+                if overrides.contains_key(alias) {
+                    base.remove(field);
+                }
+            }
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => overlay(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// Accept either a single string or a list of strings.
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(url) => vec![url],
+        OneOrMany::Many(urls) => urls,
+    })
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        IndexerConfigBuilder::new().into_config()
+    }
 }
 
 impl IndexerConfig {
@@ -31,47 +368,345 @@ impl IndexerConfig {
         IndexerConfigBuilder::new()
     }
 
+    /// A builder pre-populated for a common deployment, to be completed with the node
+    /// URL and any other settings.
+    ///
+    /// | | `ArchiveBackfill` | `LiveTail` | `Development` |
+    /// |---|---|---|---|
+    /// | finality | finalized | best | finalized |
+    /// | retry attempts | 10 | 3 | 1 |
+    /// | retry delays | 1s to 60s, full jitter | 100ms to 2s, equal jitter | default |
+    /// | RPC timeout | 30s | 5s | none |
+    /// | checkpoint interval | 100 | 1 | 1 |
+    /// | queue depth | 64 | 4 | 16 |
+    /// | blocks per minute | unlimited | unlimited | 60 |
+    pub fn preset(preset: Preset) -> IndexerConfigBuilder {
+        let builder = IndexerConfigBuilder::new();
+        match preset {
+            Preset::ArchiveBackfill => builder
+                .finality(FinalityMode::Finalized)
+                .retry_config(RetryConfig {
+                    max_retries: 10,
+                    initial_delay: Duration::from_secs(1),
+                    max_delay: Duration::from_secs(60),
+                    jitter: JitterMode::Full,
+                    ..RetryConfig::default()
+                })
+                .rpc_timeout(Duration::from_secs(30))
+                .checkpoint_interval(100)
+                .queue_depth(64),
+            Preset::LiveTail => builder
+                .finality(FinalityMode::Best)
+                .retry_config(RetryConfig {
+                    max_retries: 3,
+                    initial_delay: Duration::from_millis(100),
+                    max_delay: Duration::from_secs(2),
+                    jitter: JitterMode::Equal,
+                    ..RetryConfig::default()
+                })
+                .rpc_timeout(Duration::from_secs(5))
+                .checkpoint_interval(1)
+                .queue_depth(4),
+            Preset::Development => builder
+                .finality(FinalityMode::Finalized)
+                .retry_config(RetryConfig {
+                    max_retries: 1,
+                    ..RetryConfig::default()
+                })
+                .checkpoint_interval(1)
+                .queue_depth(DEFAULT_QUEUE_DEPTH)
+                .max_blocks_per_minute(60),
+        }
+    }
+
+    /// Load a configuration from a `.toml`, `.yaml` or `.yml` file and validate it.
+    ///
+    /// Unknown keys are rejected with an error naming the key. A top-level `preset` key
+    /// starts from that [`Preset`]; the other keys override its values, and nested
+    /// tables such as `[retry]` override only the keys they list.
+    #[cfg(feature = "config-file")]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, IndexerError> {
+        let path = path.as_ref();
+        let file_error = |message: String| IndexerError::ConfigFile {
+            path: path.display().to_string(),
+            message,
+        };
+        let text = std::fs::read_to_string(path).map_err(|e| file_error(e.to_string()))?;
+        let parse = |text: &str| -> Result<serde_json::Value, String> {
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("toml") => toml::from_str(text).map_err(|e| e.to_string()),
+                Some("yaml" | "yml") => serde_yaml::from_str(text).map_err(|e| e.to_string()),
+                _ => Err("unsupported extension, expected .toml, .yaml or .yml".into()),
+            }
+        };
+        let mut value = parse(&text).map_err(file_error)?;
+        let preset = value.as_object_mut().and_then(|keys| keys.remove("preset"));
+        let is_toml = path.extension().is_some_and(|ext| ext == "toml");
+        let mut config: Self = match preset {
+            // Without a preset, parse the text directly so errors keep their line numbers.
+            None if is_toml => toml::from_str(&text).map_err(|e| file_error(e.to_string()))?,
+            None => serde_yaml::from_str(&text).map_err(|e| file_error(e.to_string()))?,
+            Some(preset) => {
+                let preset: Preset =
+                    serde_json::from_value(preset).map_err(|e| file_error(e.to_string()))?;
+                let mut base = serde_json::to_value(Self::preset(preset).into_config())
+                    .map_err(|e| file_error(e.to_string()))?;
+                overlay(&mut base, value);
+                serde_json::from_value(base).map_err(|e| file_error(e.to_string()))?
+            }
+        };
+        config.validate()?;
+        config.normalize()?;
+        Ok(config)
+    }
+
+    /// Whether `event_allowlist` lets `pallet`'s `event` through to the handlers.
+    pub fn allows_event(&self, pallet: &str, event: &str) -> bool {
+        self.event_allowlist.is_empty()
+            || self
+                .event_allowlist
+                .iter()
+                .any(|pattern| pattern.matches(pallet, event))
+    }
+
+    /// URL of the primary node, the first entry of `node_urls`.
+    pub fn node_url(&self) -> &str {
+        self.node_urls.first().map_or("", String::as_str)
+    }
+
+    /// URL of the node slow read-only calls are hedged to: the second entry of
+    /// `node_urls`, or the deprecated `secondary_node_url`.
+    pub fn secondary_node_url(&self) -> Option<&str> {
+        self.node_urls
+            .get(1)
+            .or(self.secondary_node_url.as_ref())
+            .map(String::as_str)
+    }
+
     /// Validate this configuration.
+    ///
+    /// Errors in the primary node URL are reported against `node_url`, errors in later
+    /// entries against `node_urls[i]`.
     pub fn validate(&self) -> Result<(), IndexerError> {
-        if self.node_url.trim().is_empty() {
-            return Err(IndexerError::invalid_config("node_url", "cannot be empty"));
+        if self.node_urls.is_empty() {
+            return Err(IndexerError::invalid_config("node_urls", "cannot be empty"));
+        }
+
+        for (i, url) in self.node_urls.iter().enumerate() {
+            let field = match i {
+                0 => "node_url".to_string(),
+                i => format!("node_urls[{i}]"),
+            };
+            if url.trim().is_empty() {
+                return Err(IndexerError::invalid_config(field, "cannot be empty"));
+            }
+            WebSocketUrl::parse_field(url, &field)?;
+        }
+
+        if let Some(url) = &self.secondary_node_url {
+            let secondary = WebSocketUrl::parse_field(url, "secondary_node_url")?;
+            if let Some(second) = self.node_urls.get(1) {
+                if WebSocketUrl::parse(second)?.as_str() != secondary.as_str() {
+                    return Err(IndexerError::invalid_config(
+                        "secondary_node_url",
+                        "conflicts with node_urls[1]; set only node_urls",
+                    ));
+                }
+            }
+        }
+
+        if self.hedge_after.is_some() && self.secondary_node_url().is_none() {
+            return Err(IndexerError::invalid_config(
+                "hedge_after",
+                "requires a second entry in node_urls",
+            ));
+        }
+
+        if self.track_fees && !self.correlate_extrinsics {
+            return Err(IndexerError::invalid_config(
+                "track_fees",
+                "requires correlate_extrinsics",
+            ));
+        }
+
+        self.resolved_storage()?.validate()?;
+
+        self.retry.validate()?;
+        self.storage_retry.validate()?;
+
+        if self.max_blocks_per_minute == Some(0) {
+            return Err(IndexerError::invalid_config(
+                "max_blocks_per_minute",
+                "must be at least 1",
+            ));
+        }
+
+        if self.checkpoint_interval == 0 {
+            return Err(IndexerError::invalid_config(
+                "checkpoint_interval",
+                "must be at least 1",
+            ));
+        }
+
+        if self.end_after == Some(Duration::ZERO) {
+            return Err(IndexerError::invalid_config(
+                "end_after",
+                "must be greater than 0",
+            ));
+        }
+
+        if self.max_reorg_depth == 0 {
+            return Err(IndexerError::invalid_config(
+                "max_reorg_depth",
+                "must be at least 1",
+            ));
+        }
+
+        if self.queue_depth == 0 {
+            return Err(IndexerError::invalid_config(
+                "queue_depth",
+                "must be greater than 0",
+            ));
+        }
+
+        if let StartFrom::Block(start) = self.start_from {
+            BlockRange::new(start, self.end_block)?;
+        }
+
+        Ok(())
+    }
+
+    /// The blocks to index when starting from a fixed block; `None` for the other
+    /// [`StartFrom`] choices, whose first block is only known at run time.
+    pub fn range(&self) -> Option<BlockRange> {
+        match self.start_from {
+            StartFrom::Block(start) => BlockRange::new(start, self.end_block).ok(),
+            _ => None,
         }
+    }
 
-        if !self.node_url.starts_with("ws://") && !self.node_url.starts_with("wss://") {
+    /// The checkpoint backend to open: `storage`, unless the deprecated `database_url`
+    /// selects another one.
+    ///
+    /// Fails if `database_url` and a non-default `storage` disagree.
+    pub fn resolved_storage(&self) -> Result<StorageConfig, IndexerError> {
+        let Some(url) = &self.database_url else {
+            return Ok(self.storage.clone());
+        };
+        let from_url = StorageConfig::parse(url)?;
+        if self.storage != StorageConfig::default() && self.storage != from_url {
             return Err(IndexerError::invalid_config(
-                "node_url",
-                "must start with ws:// or wss://",
+                "database_url",
+                "conflicts with storage; set only storage",
             ));
         }
+        Ok(from_url)
+    }
 
-        if let Some(db) = &self.database_url {
-            if db.trim().is_empty() {
-                return Err(IndexerError::invalid_config(
-                    "database_url",
-                    "cannot be empty",
-                ));
+    /// Fill in what validation assumed: node URLs in the canonical form of
+    /// [`WebSocketUrl`], without repeats and with the secondary node second and
+    /// mirrored in `secondary_node_url`, and the resolved backend in `storage` with its
+    /// URL mirrored in `database_url`.
+    ///
+    /// Fails on `node_urls` if `hedge_after` is set and every entry names the
+    /// primary node once canonical.
+    pub(crate) fn normalize(&mut self) -> Result<(), IndexerError> {
+        if let Some(url) = self.secondary_node_url.take() {
+            if self.node_urls.len() < 2 {
+                self.node_urls.push(url);
             }
         }
+        for url in &mut self.node_urls {
+            *url = WebSocketUrl::parse(url)?.as_str().to_string();
+        }
+        let mut seen = HashSet::new();
+        self.node_urls.retain(|url| seen.insert(url.clone()));
+        if self.hedge_after.is_some() && self.node_urls.len() < 2 {
+            return Err(IndexerError::invalid_config(
+                "node_urls",
+                "repeats the primary node; hedge_after requires a different second node",
+            ));
+        }
+        self.secondary_node_url = self.node_urls.get(1).cloned();
+        self.storage = self.resolved_storage()?;
+        self.database_url = self.storage.database_url();
+        Ok(())
+    }
+}
 
-        if let (Some(start), Some(end)) = (self.start_block, self.end_block) {
-            if end < start {
-                return Err(IndexerError::invalid_config(
-                    "end_block",
-                    "must be greater than or equal to start_block",
-                ));
+/// Check the configured block range against the chain, once `head` and the stored
+/// `checkpoint` are known.
+///
+/// A start block more than `range_tolerance` blocks past `head` would leave the indexer
+/// waiting for blocks that may be years away: it fails with
+/// [`IndexerError::InvalidConfig`] on `start_from` if `strict_range` is set, and is
+/// logged as a warning otherwise. When resuming from the checkpoint, an `end_block`
+/// below the checkpoint always fails, on `end_block`.
+pub fn validate_range(
+    head: BlockNumber,
+    checkpoint: Option<BlockNumber>,
+    config: &IndexerConfig,
+) -> Result<(), IndexerError> {
+    if let StartFrom::Block(start) = config.start_from {
+        if start > head.saturating_add(config.range_tolerance) {
+            let message = format!(
+                "start block {start} is {} blocks past the chain head {head}",
+                start - head
+            );
+            if config.strict_range {
+                return Err(IndexerError::invalid_config("start_from", message));
             }
+            warn!(target: "indexer", start, head, "{message}; waiting for the chain to reach it");
         }
+    }
 
-        Ok(())
+    if let (StartFrom::Checkpoint, Some(end), Some(checkpoint)) =
+        (config.start_from, config.end_block, checkpoint)
+    {
+        if end < checkpoint {
+            return Err(IndexerError::invalid_config(
+                "end_block",
+                format!("end block {end} is below the checkpoint at block {checkpoint}"),
+            ));
+        }
     }
+
+    Ok(())
 }
 
 /// Builder pattern for [`IndexerConfig`].
 pub struct IndexerConfigBuilder {
-    node_url: String,
+    node_urls: Vec<String>,
+    node_auth: Option<NodeAuth>,
+    tls_mode: TlsMode,
+    storage: StorageConfig,
     database_url: Option<String>,
-    start_block: Option<BlockNumber>,
+    database_backend: Option<&'static str>,
+    start_from: StartFrom,
     end_block: Option<BlockNumber>,
+    strict_range: bool,
+    range_tolerance: BlockNumber,
+    end_after: Option<Duration>,
+    end_at_time: Option<SystemTime>,
+    retry: RetryConfig,
+    circuit_breaker: CircuitBreakerConfig,
+    rpc_timeout: Option<Duration>,
+    secondary_node_url: Option<String>,
+    hedge_after: Option<Duration>,
+    storage_retry: RetryConfig,
+    storage_circuit_breaker: CircuitBreakerConfig,
+    queue_depth: usize,
+    max_blocks_per_minute: Option<u32>,
+    finality: FinalityMode,
+    checkpoint_interval: u64,
+    max_reorg_depth: u32,
+    handler_error_budget: Option<u64>,
+    event_allowlist: Vec<EventPattern>,
+    correlate_extrinsics: bool,
+    resolve_block_author: bool,
+    track_fees: bool,
+    abort_on_panic: bool,
+    skip_undecodable_events: bool,
 }
 
 impl Default for IndexerConfigBuilder {
@@ -84,34 +719,99 @@ impl IndexerConfigBuilder {
     /// Create a new builder with default values.
     pub fn new() -> Self {
         Self {
-            node_url: String::new(),
+            node_urls: Vec::new(),
+            node_auth: None,
+            tls_mode: TlsMode::default(),
+            storage: StorageConfig::default(),
             database_url: None,
-            start_block: None,
+            database_backend: None,
+            start_from: StartFrom::default(),
             end_block: None,
+            strict_range: false,
+            range_tolerance: 0,
+            end_after: None,
+            end_at_time: None,
+            retry: RetryConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            rpc_timeout: None,
+            secondary_node_url: None,
+            hedge_after: None,
+            storage_retry: RetryConfig::default(),
+            storage_circuit_breaker: CircuitBreakerConfig::storage(),
+            queue_depth: DEFAULT_QUEUE_DEPTH,
+            max_blocks_per_minute: None,
+            finality: FinalityMode::default(),
+            checkpoint_interval: 1,
+            max_reorg_depth: DEFAULT_MAX_REORG_DEPTH,
+            handler_error_budget: None,
+            event_allowlist: Vec::new(),
+            correlate_extrinsics: false,
+            resolve_block_author: false,
+            track_fees: false,
+            abort_on_panic: false,
+            skip_undecodable_events: false,
         }
     }
 
-    /// Set the node URL.
+    /// Set the primary node URL, replacing the first entry of the node list.
     pub fn node_url(mut self, url: impl Into<String>) -> Self {
-        self.node_url = url.into();
+        match self.node_urls.first_mut() {
+            Some(first) => *first = url.into(),
+            None => self.node_urls.push(url.into()),
+        }
         self
     }
 
-    /// Configure a PostgreSQL backend.
-    pub fn with_postgres(mut self, url: impl Into<String>) -> Self {
-        self.database_url = Some(url.into());
+    /// Append a node URL to the node list. The second is the node slow read-only
+    /// calls are hedged to, see [`hedge_after`](Self::hedge_after).
+    pub fn add_node_url(mut self, url: impl Into<String>) -> Self {
+        self.node_urls.push(url.into());
         self
     }
 
+    /// Configure a PostgreSQL backend.
+    ///
+    /// [`build`](Self::build) rejects URLs that do not use the `postgres` scheme.
+    pub fn with_postgres(self, url: impl Into<String>) -> Self {
+        self.database(url, Some("postgres"))
+    }
+
     /// Configure a SQLite backend.
-    pub fn with_sqlite(mut self, url: impl Into<String>) -> Self {
+    ///
+    /// [`build`](Self::build) rejects URLs that do not use the `sqlite` scheme.
+    pub fn with_sqlite(self, url: impl Into<String>) -> Self {
+        self.database(url, Some("sqlite"))
+    }
+
+    /// Store checkpoints as JSON at `path`.
+    pub fn with_json(self, path: impl Into<PathBuf>) -> Self {
+        self.storage(StorageConfig::json(path))
+    }
+
+    /// Choose the checkpoint backend, replacing any database URL set before.
+    pub fn storage(mut self, storage: StorageConfig) -> Self {
+        self.storage = storage;
+        self.database_url = None;
+        self.database_backend = None;
+        self
+    }
+
+    /// Set the database URL, optionally requiring it to select `backend`.
+    fn database(mut self, url: impl Into<String>, backend: Option<&'static str>) -> Self {
+        self.storage = StorageConfig::default();
         self.database_url = Some(url.into());
+        self.database_backend = backend;
         self
     }
 
     /// Start indexing from the specified block.
-    pub fn start_from_block(mut self, block: BlockNumber) -> Self {
-        self.start_block = Some(block);
+    pub fn start_from_block(self, block: BlockNumber) -> Self {
+        self.start_from(StartFrom::Block(block))
+    }
+
+    /// Choose where indexing starts.
+    pub fn start_from(mut self, start: StartFrom) -> Self {
+        self.start_from = start;
         self
     }
 
@@ -121,15 +821,227 @@ impl IndexerConfigBuilder {
         self
     }
 
+    /// Index the blocks in `range`: start from its first block and end at its last.
+    pub fn range(mut self, range: BlockRange) -> Self {
+        self.start_from = StartFrom::Block(range.start());
+        self.end_block = range.end();
+        self
+    }
+
+    /// Fail at startup when the start block lies more than `range_tolerance` blocks
+    /// past the chain head, instead of only warning.
+    pub fn strict_range(mut self, strict: bool) -> Self {
+        self.strict_range = strict;
+        self
+    }
+
+    /// Allow the start block to lie up to `blocks` past the chain head.
+    pub fn range_tolerance(mut self, blocks: BlockNumber) -> Self {
+        self.range_tolerance = blocks;
+        self
+    }
+
+    /// Stop once the run has lasted `limit`, after finishing the current block.
+    pub fn end_after(mut self, limit: Duration) -> Self {
+        self.end_after = Some(limit);
+        self
+    }
+
+    /// Stop at `time`, after finishing the current block.
+    pub fn end_at_time(mut self, time: SystemTime) -> Self {
+        self.end_at_time = Some(time);
+        self
+    }
+
+    /// Set the retry policy for node RPC calls.
+    pub fn retry_config(mut self, config: RetryConfig) -> Self {
+        self.retry = config;
+        self
+    }
+
+    /// Open the RPC circuit breaker after `threshold` consecutive failures, for `cooldown`.
+    pub fn circuit_breaker(mut self, threshold: usize, cooldown: Duration) -> Self {
+        self.circuit_breaker.threshold = threshold;
+        self.circuit_breaker.cooldown = cooldown;
+        self
+    }
+
+    /// Set the full RPC circuit breaker settings.
+    pub fn circuit_breaker_config(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = config;
+        self
+    }
+
+    /// Limit each attempt of a node RPC call to `timeout`.
+    pub fn rpc_timeout(mut self, timeout: Duration) -> Self {
+        self.rpc_timeout = Some(timeout);
+        self
+    }
+
+    /// Send `auth` in the handshake of every node connection.
+    pub fn node_auth(mut self, auth: NodeAuth) -> Self {
+        self.node_auth = Some(auth);
+        self
+    }
+
+    /// Choose how the certificates of `wss://` nodes are checked.
+    pub fn tls_mode(mut self, mode: TlsMode) -> Self {
+        self.tls_mode = mode;
+        self
+    }
+
+    /// Deprecated: add the node with [`add_node_url`](Self::add_node_url) instead.
+    ///
+    /// Set the node that slow read-only RPC calls are hedged to.
+    pub fn secondary_node_url(mut self, url: impl Into<String>) -> Self {
+        self.secondary_node_url = Some(url.into());
+        self
+    }
+
+    /// Hedge read-only RPC calls to the second node after `delay` without an answer.
+    pub fn hedge_after(mut self, delay: Duration) -> Self {
+        self.hedge_after = Some(delay);
+        self
+    }
+
+    /// Set the retry policy for checkpoint store operations.
+    pub fn storage_retry(mut self, config: RetryConfig) -> Self {
+        self.storage_retry = config;
+        self
+    }
+
+    /// Open the storage circuit breaker after `threshold` consecutive failures, for `cooldown`.
+    pub fn storage_circuit_breaker(mut self, threshold: usize, cooldown: Duration) -> Self {
+        self.storage_circuit_breaker.threshold = threshold;
+        self.storage_circuit_breaker.cooldown = cooldown;
+        self
+    }
+
+    /// Set the full storage circuit breaker settings.
+    pub fn storage_circuit_breaker_config(mut self, config: CircuitBreakerConfig) -> Self {
+        self.storage_circuit_breaker = config;
+        self
+    }
+
+    /// Buffer at most `depth` fetched blocks ahead of the handlers.
+    pub fn queue_depth(mut self, depth: usize) -> Self {
+        self.queue_depth = depth;
+        self
+    }
+
+    /// Process at most `value` blocks per minute.
+    pub fn max_blocks_per_minute(mut self, value: u32) -> Self {
+        self.max_blocks_per_minute = Some(value);
+        self
+    }
+
+    /// Follow finalized or best blocks.
+    pub fn finality(mut self, mode: FinalityMode) -> Self {
+        self.finality = mode;
+        self
+    }
+
+    /// Write the checkpoint every `interval` handled blocks instead of after each one.
+    pub fn checkpoint_interval(mut self, interval: u64) -> Self {
+        self.checkpoint_interval = interval;
+        self
+    }
+
+    /// Stop with [`IndexerError::ReorgTooDeep`] instead of unwinding more than `depth`
+    /// blocks on a reorg (default 64).
+    pub fn max_reorg_depth(mut self, depth: u32) -> Self {
+        self.max_reorg_depth = depth;
+        self
+    }
+
+    /// Stop once handlers have failed more than `budget` times in a run.
+    pub fn handler_error_budget(mut self, budget: u64) -> Self {
+        self.handler_error_budget = Some(budget);
+        self
+    }
+
+    /// Dispatch only events matching one of `patterns`.
+    pub fn event_allowlist(mut self, patterns: impl IntoIterator<Item = EventPattern>) -> Self {
+        self.event_allowlist = patterns.into_iter().collect();
+        self
+    }
+
+    /// Fetch each block's extrinsics and expose them to handlers.
+    pub fn correlate_extrinsics(mut self, correlate: bool) -> Self {
+        self.correlate_extrinsics = correlate;
+        self
+    }
+
+    /// Resolve the author of each block from its digest and the session validators.
+    pub fn resolve_block_author(mut self, resolve: bool) -> Self {
+        self.resolve_block_author = resolve;
+        self
+    }
+
+    /// Attach each extrinsic's fee and tip; needs
+    /// [`correlate_extrinsics`](Self::correlate_extrinsics).
+    pub fn track_fees(mut self, track: bool) -> Self {
+        self.track_fees = track;
+        self
+    }
+
+    /// Let handler panics unwind instead of converting them to errors.
+    pub fn abort_on_panic(mut self, abort: bool) -> Self {
+        self.abort_on_panic = abort;
+        self
+    }
+
+    /// Dispatch what decodes of a block with an undecodable event.
+    pub fn skip_undecodable_events(mut self, skip: bool) -> Self {
+        self.skip_undecodable_events = skip;
+        self
+    }
+
     /// Build the configuration and validate it.
     pub fn build(self) -> Result<IndexerConfig, IndexerError> {
-        let config = IndexerConfig {
-            node_url: self.node_url,
-            database_url: self.database_url,
-            start_block: self.start_block,
-            end_block: self.end_block,
-        };
+        let backend = self.database_backend;
+        let mut config = self.into_config();
         config.validate()?;
+        if let (Some(backend), Some(url)) = (backend, &config.database_url) {
+            StorageConfig::parse_for(backend, url)?;
+        }
+        config.normalize()?;
         Ok(config)
     }
+
+    fn into_config(self) -> IndexerConfig {
+        IndexerConfig {
+            node_urls: self.node_urls,
+            node_auth: self.node_auth,
+            tls_mode: self.tls_mode,
+            storage: self.storage,
+            database_url: self.database_url,
+            start_from: self.start_from,
+            end_block: self.end_block,
+            strict_range: self.strict_range,
+            range_tolerance: self.range_tolerance,
+            end_after: self.end_after,
+            end_at_time: self.end_at_time,
+            retry: self.retry,
+            circuit_breaker: self.circuit_breaker,
+            rpc_timeout: self.rpc_timeout,
+            secondary_node_url: self.secondary_node_url,
+            hedge_after: self.hedge_after,
+            storage_retry: self.storage_retry,
+            storage_circuit_breaker: self.storage_circuit_breaker,
+            queue_depth: self.queue_depth,
+            max_blocks_per_minute: self.max_blocks_per_minute,
+            finality: self.finality,
+            checkpoint_interval: self.checkpoint_interval,
+            max_reorg_depth: self.max_reorg_depth,
+            handler_error_budget: self.handler_error_budget,
+            handlers: None,
+            event_allowlist: self.event_allowlist,
+            correlate_extrinsics: self.correlate_extrinsics,
+            resolve_block_author: self.resolve_block_author,
+            track_fees: self.track_fees,
+            abort_on_panic: self.abort_on_panic,
+            skip_undecodable_events: self.skip_undecodable_events,
+        }
+    }
 }
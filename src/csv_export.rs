@@ -0,0 +1,345 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Write events to rotating CSV files, with the `csv-export` feature.
+
+use crate::error::IndexerError;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::types::ChainEvent;
+use async_trait::async_trait;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use subxt::events::StaticEvent;
+use subxt::Config;
+use tracing::warn;
+
+const HANDLER_NAME: &str = "CsvExportHandler";
+
+/// Bytes read at a time while looking for the last complete row.
+const TAIL_CHUNK: u64 = 8 * 1024;
+
+/// Columns of [`CsvSchema::envelope`].
+pub const ENVELOPE_HEADERS: [&str; 5] =
+    ["block_number", "event_index", "pallet", "event", "fields"];
+
+type RowFn<C> =
+    dyn Fn(&ChainEvent<C>, &Context<C>) -> Result<Option<Vec<String>>, IndexerError> + Send + Sync;
+
+/// How a [`CsvExportHandler`] turns events into rows, and the file name prefix.
+pub struct CsvSchema<C: Config> {
+    name: String,
+    headers: Vec<String>,
+    filter: EventFilter,
+    row: Box<RowFn<C>>,
+}
+
+impl<C: Config> CsvSchema<C> {
+    /// One row per event passing `filter`, built by `row`. Returning `None` skips
+    /// the event.
+    pub fn custom<F>(name: impl Into<String>, headers: &[&str], filter: EventFilter, row: F) -> Self
+    where
+        F: Fn(&ChainEvent<C>, &Context<C>) -> Result<Option<Vec<String>>, IndexerError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self {
+            name: name.into(),
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            filter,
+            row: Box::new(row),
+        }
+    }
+
+    /// One row per `E` event, built by `extract` from the decoded event.
+    pub fn typed<E, F>(name: impl Into<String>, headers: &[&str], extract: F) -> Self
+    where
+        E: StaticEvent + 'static,
+        F: Fn(E, &Context<C>) -> Vec<String> + Send + Sync + 'static,
+    {
+        Self::custom(
+            name,
+            headers,
+            EventFilter::event(E::PALLET, E::EVENT),
+            move |event, ctx| {
+                let decoded =
+                    event
+                        .as_event::<E>()
+                        .map_err(|source| IndexerError::EventDecodingFailed {
+                            pallet: E::PALLET.into(),
+                            event: E::EVENT.into(),
+                            block: ctx.block_number,
+                            source,
+                        })?;
+                Ok(decoded.map(|decoded| extract(decoded, ctx)))
+            },
+        )
+    }
+
+    /// The [`ENVELOPE_HEADERS`] columns for every event passing `filter`, with
+    /// the fields as produced by [`ChainEvent::to_json`], empty if they fail
+    /// to decode.
+    pub fn envelope(name: impl Into<String>, filter: EventFilter) -> Self {
+        Self::custom(name, &ENVELOPE_HEADERS, filter, |event, ctx| {
+            let fields = event
+                .to_json()
+                .map(|fields| fields.to_string())
+                .unwrap_or_default();
+            Ok(Some(vec![
+                ctx.block_number.to_string(),
+                event.index().to_string(),
+                event.pallet_name().to_string(),
+                event.variant_name().to_string(),
+                fields,
+            ]))
+        })
+    }
+}
+
+/// When a [`CsvExportHandler`] starts a new file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    /// One file per range of this many blocks, aligned to multiples of it, named
+    /// `{name}_{start}_{end}.csv` with `end` exclusive, e.g.
+    /// `transfers_0001000000_0001100000.csv`.
+    Blocks(u64),
+    /// A new file once the current one reaches this many bytes, named
+    /// `{name}_{first block}.csv`. A block is never split across files.
+    Bytes(u64),
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self::Blocks(100_000)
+    }
+}
+
+struct Output {
+    file: File,
+    /// File name, as recorded in the marker.
+    name: String,
+    /// First block after the file's range, with [`Rotation::Blocks`].
+    range_end: u64,
+    len: u64,
+}
+
+/// Handler appending one CSV row per matching event to files in a directory.
+///
+/// The rows of a block are encoded in memory and appended with a single write
+/// from [`Handler::handle_block`], so each block is flushed on its own. After
+/// each block the length of the file is recorded in a hidden `.{name}.offset`
+/// marker next to it, and a block cut short by a crash is removed down to that
+/// length when the file is opened again. Rows are not
+/// deduplicated: blocks the indexer processes again after a restart, because
+/// they were past its last checkpoint, are written again.
+pub struct CsvExportHandler<C: Config> {
+    dir: PathBuf,
+    schema: CsvSchema<C>,
+    rotation: Rotation,
+    output: Mutex<Option<Output>>,
+}
+
+impl<C: Config> CsvExportHandler<C> {
+    /// Write the rows of `schema` to files in `dir`, created if missing.
+    pub fn new(dir: impl Into<PathBuf>, schema: CsvSchema<C>) -> Result<Self, IndexerError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            schema,
+            rotation: Rotation::default(),
+            output: Mutex::new(None),
+        })
+    }
+
+    /// Rotate files according to `rotation`, every 100 000 blocks by default.
+    pub fn rotate(mut self, rotation: Rotation) -> Self {
+        self.rotation = match rotation {
+            Rotation::Blocks(blocks) => Rotation::Blocks(blocks.max(1)),
+            Rotation::Bytes(bytes) => Rotation::Bytes(bytes.max(1)),
+        };
+        self
+    }
+
+    /// File the rows of `block` go to when a new one is started.
+    fn path_for(&self, block: u64) -> (PathBuf, u64) {
+        let name = &self.schema.name;
+        match self.rotation {
+            Rotation::Blocks(blocks) => {
+                let start = block / blocks * blocks;
+                let end = start.saturating_add(blocks);
+                (
+                    self.dir.join(format!("{name}_{start:010}_{end:010}.csv")),
+                    end,
+                )
+            }
+            Rotation::Bytes(_) => (self.dir.join(format!("{name}_{block:010}.csv")), u64::MAX),
+        }
+    }
+
+    /// Marker recording the file being appended to and its length after the
+    /// last complete block.
+    fn marker_path(&self) -> PathBuf {
+        self.dir.join(format!(".{}.offset", self.schema.name))
+    }
+
+    fn needs_rotation(&self, output: &Output, block: u64) -> bool {
+        match self.rotation {
+            Rotation::Blocks(_) => block >= output.range_end,
+            Rotation::Bytes(bytes) => output.len >= bytes,
+        }
+    }
+
+    fn encode(&self, rows: &[Vec<String>], headers: bool) -> Result<Vec<u8>, csv::Error> {
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(Vec::new());
+        if headers {
+            writer.write_record(&self.schema.headers)?;
+        }
+        for row in rows {
+            writer.write_record(row)?;
+        }
+        writer.into_inner().map_err(|e| e.into_error().into())
+    }
+
+    fn append(&self, block: u64, rows: &[Vec<String>]) -> Result<(), IndexerError> {
+        let mut output = self.output.lock().unwrap();
+        let current = match output.take() {
+            Some(current) if !self.needs_rotation(&current, block) => current,
+            _ => {
+                let (path, range_end) = self.path_for(block);
+                let (file, len) = open(&path, &self.marker_path())?;
+                Output {
+                    file,
+                    name: file_name(&path),
+                    range_end,
+                    len,
+                }
+            }
+        };
+        let current = output.insert(current);
+        let bytes = self
+            .encode(rows, current.len == 0)
+            .map_err(|e| IndexerError::handler_failed(HANDLER_NAME, block, e))?;
+        let written = current.file.write_all(&bytes).and_then(|()| {
+            let len = current.len + bytes.len() as u64;
+            fs::write(self.marker_path(), format!("{} {len}\n", current.name))?;
+            Ok(len)
+        });
+        match written {
+            Ok(len) => {
+                current.len = len;
+                Ok(())
+            }
+            Err(e) => {
+                // Reopen next time, which removes whatever part of the block was written.
+                *output = None;
+                Err(e.into())
+            }
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Open `path` for appending, dropping a trailing partial block. Returns the
+/// file and its length.
+fn open(path: &Path, marker: &Path) -> io::Result<(File, u64)> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+    let len = file.metadata()?.len();
+    let complete = match recorded_len(marker, path) {
+        Some(recorded) if recorded <= len => recorded,
+        _ => complete_len(&mut file, len)?,
+    };
+    if complete < len {
+        warn!(
+            target: "indexer",
+            path = %path.display(),
+            dropped_bytes = len - complete,
+            "removing partial CSV block"
+        );
+        file.set_len(complete)?;
+    }
+    Ok((file, complete))
+}
+
+/// Length of `path` after its last complete block, when `marker` records it.
+fn recorded_len(marker: &Path, path: &Path) -> Option<u64> {
+    let contents = fs::read_to_string(marker).ok()?;
+    let (name, len) = contents.trim_end().rsplit_once(' ')?;
+    (name == file_name(path))
+        .then(|| len.parse().ok())
+        .flatten()
+}
+
+/// Length of `file` up to and including its last newline, for files the
+/// marker does not cover, such as ones written before it existed.
+fn complete_len(file: &mut File, len: u64) -> io::Result<u64> {
+    let mut end = len;
+    let mut buf = vec![0u8; TAIL_CHUNK as usize];
+    while end > 0 {
+        let start = end.saturating_sub(TAIL_CHUNK);
+        let chunk = &mut buf[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        if let Some(pos) = chunk.iter().rposition(|&b| b == b'\n') {
+            return Ok(start + pos as u64 + 1);
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+#[async_trait]
+impl<C: Config> Handler<C> for CsvExportHandler<C> {
+    fn name(&self) -> &str {
+        HANDLER_NAME
+    }
+
+    fn event_filter(&self) -> EventFilter {
+        self.schema.filter
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        let mut rows = Vec::new();
+        for event in events {
+            if self.schema.filter.matches_event(event) {
+                if let Some(row) = (self.schema.row)(event, ctx)? {
+                    rows.push(row);
+                }
+            }
+        }
+        if rows.is_empty() {
+            return Ok(());
+        }
+        self.append(ctx.block_number, &rows)
+    }
+}
@@ -14,11 +14,17 @@
  * limitations under the License.
  */
 
+use crate::redact::{redact_url, redact_urls_in};
+use crate::retry::BreakerDomain;
 #[cfg(feature = "json-storage")]
 use serde_json;
 use std::error::Error as StdError;
+use std::time::Duration;
 use thiserror::Error;
 
+/// JSON-RPC error code Substrate nodes and most RPC providers use for "limit exceeded".
+const RPC_LIMIT_EXCEEDED: i32 = -32005;
+
 #[derive(Error, Debug)]
 pub enum IndexerError {
     #[error("Subxt error: {0}")]
@@ -30,11 +36,15 @@ pub enum IndexerError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[cfg(feature = "metrics")]
+    #[error("Metrics error: {0}")]
+    Metrics(#[from] prometheus::Error),
+
     #[cfg(feature = "json-storage")]
     #[error("Serde JSON error: {0}")]
     SerdeJson(#[from] serde_json::Error),
 
-    #[error("Connection to {url} failed: {source}")]
+    #[error("Connection to {} failed: {source}", redact_url(url))]
     ConnectionFailed {
         url: String,
         #[source]
@@ -44,18 +54,36 @@ pub enum IndexerError {
     #[error("Block {block} not found")]
     BlockNotFound { block: u64 },
 
-    #[error("Handler {handler} failed at block {block}: {source}")]
+    #[error("Block {hash} not found")]
+    BlockHashNotFound { hash: String },
+
+    #[error(
+        "Handler {handler} failed at block {block}{}: {source}",
+        event_index.map(|index| format!(", event {index}")).unwrap_or_default()
+    )]
     HandlerFailed {
         handler: String,
         block: u64,
+        /// Position in the block of the event being handled, if the failure was in
+        /// [`Handler::handle_event`](crate::handler::Handler::handle_event). Filled in
+        /// by the indexer when the handler leaves it unset.
+        event_index: Option<u32>,
         #[source]
         source: Box<dyn StdError + Send + Sync>,
+        /// Whether the failure is transient and the handler may be retried.
+        retryable: bool,
     },
 
     #[error("Invalid config for `{field}`: {message}")]
     InvalidConfig { field: String, message: String },
 
-    #[error("Checkpoint {operation} failed using {backend}: {source}")]
+    #[error("Failed to load config file {path}: {message}")]
+    ConfigFile { path: String, message: String },
+
+    #[error(
+        "Checkpoint {operation} failed using {backend}: {}",
+        redact_urls_in(&source.to_string())
+    )]
     CheckpointError {
         operation: String,
         backend: String,
@@ -63,6 +91,48 @@ pub enum IndexerError {
         source: Box<dyn StdError + Send + Sync>,
     },
 
+    #[error(
+        "Storage backend for `{scheme}` URLs requires the `{feature}` cargo feature (enabled backends: {})",
+        enabled_backends_list()
+    )]
+    StorageBackendUnavailable {
+        scheme: String,
+        feature: &'static str,
+    },
+
+    #[error(
+        "Unsupported database URL scheme `{scheme}`: expected postgres://, postgresql:// or sqlite://{}",
+        scheme_hint(scheme)
+    )]
+    UnsupportedStorageScheme { scheme: String },
+
+    /// An operation was rejected without being tried because the breaker guarding
+    /// `domain` is open. Never retried, and never counted as a failure by a breaker.
+    #[error("{domain} circuit breaker is open")]
+    CircuitOpen { domain: BreakerDomain },
+
+    #[error("Refusing to replace checkpoint at block {existing} with older block {incoming}")]
+    CheckpointConflict { existing: u64, incoming: u64 },
+
+    #[error("Reorg of {depth} blocks exceeds max_reorg_depth of {max}")]
+    ReorgTooDeep { depth: u32, max: u32 },
+
+    #[error("Handlers failed {errors} times, exceeding the error budget of {budget}")]
+    ErrorBudgetExhausted { errors: u64, budget: u64 },
+
+    #[error("{operation} timed out after {duration:?}")]
+    Timeout {
+        operation: String,
+        duration: Duration,
+    },
+
+    #[error("Rate limited by the RPC provider (retry after: {retry_after:?})")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        #[source]
+        source: Box<subxt::Error>,
+    },
+
     #[error("Metadata update failed: {source}")]
     MetadataUpdateFailed {
         #[source]
@@ -77,6 +147,14 @@ pub enum IndexerError {
         #[source]
         source: Box<subxt::Error>,
     },
+
+    #[error("Storage query {pallet}.{item} failed: {source}")]
+    StorageQueryFailed {
+        pallet: String,
+        item: String,
+        #[source]
+        source: Box<subxt::Error>,
+    },
 }
 
 impl IndexerError {
@@ -86,17 +164,149 @@ impl IndexerError {
             message: message.into(),
         }
     }
+
+    pub fn storage_query_failed(
+        pallet: impl Into<String>,
+        item: impl Into<String>,
+        source: subxt::Error,
+    ) -> Self {
+        Self::StorageQueryFailed {
+            pallet: pallet.into(),
+            item: item.into(),
+            source: Box::new(source),
+        }
+    }
+
+    /// A non-retryable [`IndexerError::HandlerFailed`]; chain
+    /// [`retryable`](Self::retryable) to mark it transient.
+    pub fn handler_failed(
+        handler: impl Into<String>,
+        block: u64,
+        source: impl Into<Box<dyn StdError + Send + Sync>>,
+    ) -> Self {
+        Self::HandlerFailed {
+            handler: handler.into(),
+            block,
+            event_index: None,
+            source: source.into(),
+            retryable: false,
+        }
+    }
+
+    /// Record the position of the failing event on a [`IndexerError::HandlerFailed`]
+    /// that does not name one yet. Other variants are returned unchanged.
+    pub fn with_event_index(mut self, index: u32) -> Self {
+        if let Self::HandlerFailed { event_index, .. } = &mut self {
+            event_index.get_or_insert(index);
+        }
+        self
+    }
+
+    /// Mark a [`IndexerError::HandlerFailed`] as (non-)retryable. Other variants are
+    /// returned unchanged; their retryability is decided by
+    /// [`is_retryable_error`](crate::retry::is_retryable_error).
+    pub fn retryable(mut self, value: bool) -> Self {
+        if let Self::HandlerFailed { retryable, .. } = &mut self {
+            *retryable = value;
+        }
+        self
+    }
+
+    /// Messages of the errors underneath this one, outermost first.
+    pub fn sources(&self) -> Vec<String> {
+        let mut sources = Vec::new();
+        let mut current = self.source();
+        while let Some(err) = current {
+            sources.push(err.to_string());
+            current = err.source();
+        }
+        sources
+    }
+
+    /// Recognise a rate-limit response from the node or RPC provider, keeping `err` as
+    /// the source: a subscription limit, or a JSON-RPC error with the "limit exceeded"
+    /// code, whose `data` may carry the provider's retry-after hint.
+    pub fn rate_limited_from(err: Box<subxt::Error>) -> Result<Self, Box<subxt::Error>> {
+        match rate_limit(&err) {
+            Some(retry_after) => Ok(Self::RateLimited {
+                retry_after,
+                source: err,
+            }),
+            None => Err(err),
+        }
+    }
+}
+
+/// The retry-after hint of a rate-limit error, `Some(None)` for one without a hint, and
+/// `None` for other errors.
+fn rate_limit(err: &subxt::Error) -> Option<Option<Duration>> {
+    use jsonrpsee::core::ClientError as JsonrpseeError;
+    use subxt::error::RpcError;
+    use subxt::ext::subxt_rpcs::Error as ClientError;
+
+    let (code, data) = match err {
+        subxt::Error::Rpc(RpcError::LimitReached) => return Some(None),
+        subxt::Error::Rpc(RpcError::ClientError(ClientError::User(e))) => {
+            (e.code, e.data.as_ref().map(|data| data.get()))
+        }
+        // Custom clients may pass jsonrpsee's errors on unconverted.
+        subxt::Error::Rpc(RpcError::ClientError(ClientError::Client(e))) => {
+            match e.downcast_ref::<JsonrpseeError>()? {
+                JsonrpseeError::Call(e) => (e.code(), e.data().map(|data| data.get())),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    (code == RPC_LIMIT_EXCEEDED)
+        .then(|| data.and_then(|data| parse_retry_after(&data.to_ascii_lowercase())))
+}
+
+/// Find a `retry_after` / `retry-after` / `retryAfter` value in lowercased error data.
+/// Plain numbers are seconds; an `ms` suffix means milliseconds.
+fn parse_retry_after(message: &str) -> Option<Duration> {
+    let start = ["retry_after", "retry-after", "retryafter"]
+        .iter()
+        .find_map(|key| message.find(key).map(|i| i + key.len()))?;
+    let rest = message[start..].trim_start_matches(['"', '\\', '\'', ':', '=', ' ']);
+    let end = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(rest.len());
+    let value: f64 = rest[..end].parse().ok()?;
+    let secs = if rest[end..].trim_start().starts_with("ms") {
+        value / 1000.0
+    } else {
+        value
+    };
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+fn enabled_backends_list() -> String {
+    let backends = crate::storage::enabled_backends();
+    if backends.is_empty() {
+        "none".into()
+    } else {
+        backends.join(", ")
+    }
+}
+
+fn scheme_hint(scheme: &str) -> &'static str {
+    match scheme {
+        "http" | "https" => " (http(s) URLs point at a service, not a database)",
+        "ws" | "wss" => " (this looks like a node URL)",
+        _ => "",
+    }
 }
 
 impl From<subxt::Error> for IndexerError {
     fn from(err: subxt::Error) -> Self {
-        Self::Subxt(Box::new(err))
+        Self::from(Box::new(err))
     }
 }
 
 impl From<Box<subxt::Error>> for IndexerError {
     fn from(err: Box<subxt::Error>) -> Self {
-        Self::Subxt(err)
+        Self::rate_limited_from(err).unwrap_or_else(Self::Subxt)
     }
 }
 
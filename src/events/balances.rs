@@ -0,0 +1,37 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Events of the `Balances` pallet. Bittensor balances are `u64` amounts of RAO.
+
+use parity_scale_codec::{Decode, Encode};
+use scale_decode::DecodeAsType;
+use subxt::events::StaticEvent;
+use subxt::utils::AccountId32;
+
+pub use crate::filters::balances::{PALLET, TRANSFER};
+
+/// Funds were transferred between accounts.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, DecodeAsType)]
+pub struct Transfer {
+    pub from: AccountId32,
+    pub to: AccountId32,
+    pub amount: u64,
+}
+
+impl StaticEvent for Transfer {
+    const PALLET: &'static str = PALLET;
+    const EVENT: &'static str = "Transfer";
+}
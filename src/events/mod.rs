@@ -0,0 +1,25 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Typed definitions of common Bittensor events, behind the `bittensor-events`
+//! feature.
+//!
+//! Where a field layout changed across runtime upgrades the event comes in
+//! versioned forms (`StakeAddedV1`, `StakeAddedV2`) plus an enum whose
+//! `from_event` tries each layout, newest first.
+
+pub mod balances;
+pub mod subtensor;
@@ -0,0 +1,194 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Events of the `SubtensorModule` pallet.
+//!
+//! Layouts follow the runtime's fields, positional unless the runtime names
+//! them; `V1` is the layout before dynamic TAO and `V2` the one introduced
+//! with it.
+
+use crate::types::ChainEvent;
+use parity_scale_codec::{Decode, Encode};
+use scale_decode::DecodeAsType;
+use subxt::events::StaticEvent;
+use subxt::utils::AccountId32;
+use subxt::Config;
+
+pub use crate::filters::subtensor::{
+    AXON_SERVED, NEURON_REGISTERED, PALLET, STAKE_ADDED, STAKE_REMOVED,
+};
+
+/// A neuron was registered on a subnet: `(netuid, uid, hotkey)`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, DecodeAsType)]
+pub struct NeuronRegistered(pub u16, pub u16, pub AccountId32);
+
+impl StaticEvent for NeuronRegistered {
+    const PALLET: &'static str = PALLET;
+    const EVENT: &'static str = "NeuronRegistered";
+}
+
+/// A hotkey served its axon on a subnet: `(netuid, hotkey)`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, DecodeAsType)]
+pub struct AxonServed(pub u16, pub AccountId32);
+
+impl StaticEvent for AxonServed {
+    const PALLET: &'static str = PALLET;
+    const EVENT: &'static str = "AxonServed";
+}
+
+/// A coldkey moved one of its hotkeys to a new hotkey.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, DecodeAsType)]
+pub struct HotkeySwapped {
+    pub coldkey: AccountId32,
+    pub old_hotkey: AccountId32,
+    pub new_hotkey: AccountId32,
+}
+
+impl StaticEvent for HotkeySwapped {
+    const PALLET: &'static str = PALLET;
+    const EVENT: &'static str = "HotkeySwapped";
+}
+
+/// Everything owned by `old_coldkey`, its hotkeys included, moved to
+/// `new_coldkey`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, DecodeAsType)]
+pub struct ColdkeySwapped {
+    pub old_coldkey: AccountId32,
+    pub new_coldkey: AccountId32,
+}
+
+impl StaticEvent for ColdkeySwapped {
+    const PALLET: &'static str = PALLET;
+    const EVENT: &'static str = "ColdkeySwapped";
+}
+
+/// Stake added before dynamic TAO: `(hotkey, amount)`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, DecodeAsType)]
+pub struct StakeAddedV1(pub AccountId32, pub u64);
+
+impl StaticEvent for StakeAddedV1 {
+    const PALLET: &'static str = PALLET;
+    const EVENT: &'static str = "StakeAdded";
+}
+
+/// Stake added since dynamic TAO:
+/// `(coldkey, hotkey, tao_amount, alpha_amount, netuid, fee)`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, DecodeAsType)]
+pub struct StakeAddedV2(
+    pub AccountId32,
+    pub AccountId32,
+    pub u64,
+    pub u64,
+    pub u16,
+    pub u64,
+);
+
+impl StaticEvent for StakeAddedV2 {
+    const PALLET: &'static str = PALLET;
+    const EVENT: &'static str = "StakeAdded";
+}
+
+/// Stake removed before dynamic TAO: `(hotkey, amount)`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, DecodeAsType)]
+pub struct StakeRemovedV1(pub AccountId32, pub u64);
+
+impl StaticEvent for StakeRemovedV1 {
+    const PALLET: &'static str = PALLET;
+    const EVENT: &'static str = "StakeRemoved";
+}
+
+/// Stake removed since dynamic TAO:
+/// `(coldkey, hotkey, tao_amount, alpha_amount, netuid, fee)`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, DecodeAsType)]
+pub struct StakeRemovedV2(
+    pub AccountId32,
+    pub AccountId32,
+    pub u64,
+    pub u64,
+    pub u16,
+    pub u64,
+);
+
+impl StaticEvent for StakeRemovedV2 {
+    const PALLET: &'static str = PALLET;
+    const EVENT: &'static str = "StakeRemoved";
+}
+
+/// `StakeAdded` in whichever layout the event's runtime used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StakeAdded {
+    V1(StakeAddedV1),
+    V2(StakeAddedV2),
+}
+
+impl StakeAdded {
+    /// `None` if `event` is not a `StakeAdded` event, an error if it matches
+    /// neither layout.
+    pub fn from_event<C: Config>(event: &ChainEvent<C>) -> Result<Option<Self>, Box<subxt::Error>> {
+        match event.as_event::<StakeAddedV2>() {
+            Ok(v2) => Ok(v2.map(Self::V2)),
+            Err(_) => Ok(event.as_event::<StakeAddedV1>()?.map(Self::V1)),
+        }
+    }
+
+    pub fn hotkey(&self) -> &AccountId32 {
+        match self {
+            Self::V1(event) => &event.0,
+            Self::V2(event) => &event.1,
+        }
+    }
+
+    /// Staked amount in RAO.
+    pub fn amount(&self) -> u64 {
+        match self {
+            Self::V1(event) => event.1,
+            Self::V2(event) => event.2,
+        }
+    }
+}
+
+/// `StakeRemoved` in whichever layout the event's runtime used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StakeRemoved {
+    V1(StakeRemovedV1),
+    V2(StakeRemovedV2),
+}
+
+impl StakeRemoved {
+    /// `None` if `event` is not a `StakeRemoved` event, an error if it matches
+    /// neither layout.
+    pub fn from_event<C: Config>(event: &ChainEvent<C>) -> Result<Option<Self>, Box<subxt::Error>> {
+        match event.as_event::<StakeRemovedV2>() {
+            Ok(v2) => Ok(v2.map(Self::V2)),
+            Err(_) => Ok(event.as_event::<StakeRemovedV1>()?.map(Self::V1)),
+        }
+    }
+
+    pub fn hotkey(&self) -> &AccountId32 {
+        match self {
+            Self::V1(event) => &event.0,
+            Self::V2(event) => &event.1,
+        }
+    }
+
+    /// Unstaked amount in RAO.
+    pub fn amount(&self) -> u64 {
+        match self {
+            Self::V1(event) => event.1,
+            Self::V2(event) => event.2,
+        }
+    }
+}
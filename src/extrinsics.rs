@@ -0,0 +1,317 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Extrinsics of a block, for attributing events to the call and signer that
+//! caused them and for decoding calls that emit no events of their own. Enabled
+//! with [`IndexerBuilder::correlate_extrinsics`](crate::builder::IndexerBuilder::correlate_extrinsics).
+
+use crate::error::IndexerError;
+use crate::types::ChainEvent;
+use scale_decode::{DecodeAsFields, Field};
+use std::fmt;
+use subxt::blocks::{Block, StaticExtrinsic};
+use subxt::ext::subxt_core;
+use subxt::utils::AccountId32;
+use subxt::{Config, Metadata, OnlineClient};
+
+/// An extrinsic of a block, as seen by handlers through
+/// [`Context::extrinsic_info`](crate::handler::Context::extrinsic_info).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtrinsicInfo {
+    pub pallet: String,
+    pub call: String,
+    /// `None` for unsigned extrinsics and inherents such as `Timestamp.set`, and
+    /// for signed ones whose address is not an account id.
+    pub signer: Option<AccountId32>,
+    /// From the block's `System.ExtrinsicSuccess` and `System.ExtrinsicFailed`
+    /// events; `None` if neither was seen for this extrinsic.
+    pub success: Option<bool>,
+    /// Fee actually paid, tip included, from the block's
+    /// `TransactionPayment.TransactionFeePaid` event; `None` without
+    /// [`track_fees`](crate::builder::IndexerBuilder::track_fees), for extrinsics
+    /// that paid no fee and on runtimes without that event.
+    pub fee: Option<u128>,
+    /// Tip part of [`fee`](Self::fee).
+    pub tip: Option<u128>,
+}
+
+impl ExtrinsicInfo {
+    pub fn new(pallet: impl Into<String>, call: impl Into<String>) -> Self {
+        Self {
+            pallet: pallet.into(),
+            call: call.into(),
+            signer: None,
+            success: None,
+            fee: None,
+            tip: None,
+        }
+    }
+
+    pub fn with_signer(mut self, signer: AccountId32) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+}
+
+/// The extrinsics of one block, by index.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockExtrinsics {
+    extrinsics: Vec<ExtrinsicInfo>,
+}
+
+impl BlockExtrinsics {
+    /// `extrinsics` in block order, so the extrinsic at position `i` is the one
+    /// events with `Phase::ApplyExtrinsic(i)` belong to.
+    pub fn new(extrinsics: Vec<ExtrinsicInfo>) -> Self {
+        Self { extrinsics }
+    }
+
+    pub fn get(&self, index: u32) -> Option<&ExtrinsicInfo> {
+        self.extrinsics.get(index as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.extrinsics.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extrinsics.is_empty()
+    }
+
+    /// Record each extrinsic's outcome from the block's
+    /// `System.ExtrinsicSuccess`/`System.ExtrinsicFailed` events.
+    pub fn apply_outcomes<C: Config>(&mut self, events: &[ChainEvent<C>]) {
+        for event in events {
+            if event.pallet_name() != "System" {
+                continue;
+            }
+            let success = match event.variant_name() {
+                "ExtrinsicSuccess" => true,
+                "ExtrinsicFailed" => false,
+                _ => continue,
+            };
+            if let Some(info) = event
+                .extrinsic_index()
+                .and_then(|index| self.extrinsics.get_mut(index as usize))
+            {
+                info.success = Some(success);
+            }
+        }
+    }
+
+    /// Record each extrinsic's fee and tip from the block's
+    /// `TransactionPayment.TransactionFeePaid` events. Extrinsics without such an
+    /// event keep `None`.
+    pub fn apply_fees<C: Config>(&mut self, events: &[ChainEvent<C>]) {
+        for event in events {
+            if event.pallet_name() != "TransactionPayment"
+                || event.variant_name() != "TransactionFeePaid"
+            {
+                continue;
+            }
+            let Some(info) = event
+                .extrinsic_index()
+                .and_then(|index| self.extrinsics.get_mut(index as usize))
+            else {
+                continue;
+            };
+            let amount = |name: &str| event.field(name).ok().flatten()?.as_u128();
+            info.fee = amount("actual_fee");
+            info.tip = amount("tip");
+        }
+    }
+
+    /// Summaries of `extrinsics`, which must be in block order. Outcomes are
+    /// filled in later by [`apply_outcomes`](Self::apply_outcomes).
+    pub fn from_extrinsics(extrinsics: &[ChainExtrinsic]) -> Self {
+        Self::new(extrinsics.iter().map(ChainExtrinsic::info).collect())
+    }
+}
+
+/// An extrinsic of the block being indexed, passed to
+/// [`Handler::handle_extrinsic`](crate::handler::Handler::handle_extrinsic).
+///
+/// Keeps the SCALE-encoded call arguments, which [`as_call`](Self::as_call)
+/// decodes into a typed call such as `calls::subtensor::SetWeightsCall` (with
+/// the `bittensor-events` feature).
+#[derive(Clone)]
+pub struct ChainExtrinsic {
+    index: u32,
+    pallet: String,
+    call: String,
+    signed: bool,
+    signer: Option<AccountId32>,
+    field_bytes: Vec<u8>,
+    metadata: Metadata,
+}
+
+impl fmt::Debug for ChainExtrinsic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChainExtrinsic")
+            .field("index", &self.index)
+            .field("pallet", &self.pallet)
+            .field("call", &self.call)
+            .field("signed", &self.signed)
+            .field("field_bytes", &self.field_bytes.len())
+            .finish()
+    }
+}
+
+impl ChainExtrinsic {
+    /// Decode a block's SCALE-encoded extrinsics, given in block order, against
+    /// the runtime `metadata` they were created for.
+    pub fn decode_all<C: Config>(
+        extrinsics: Vec<Vec<u8>>,
+        metadata: Metadata,
+    ) -> Result<Vec<Self>, IndexerError> {
+        let decoded =
+            subxt_core::blocks::Extrinsics::<C>::decode_from(extrinsics, metadata.clone())
+                .map_err(|e| subxt::Error::from(subxt_core::Error::from(e)))?;
+        decoded
+            .iter()
+            .map(|extrinsic| {
+                Ok(Self {
+                    index: extrinsic.index(),
+                    pallet: extrinsic
+                        .pallet_name()
+                        .map_err(subxt::Error::from)?
+                        .to_string(),
+                    call: extrinsic
+                        .variant_name()
+                        .map_err(subxt::Error::from)?
+                        .to_string(),
+                    signed: extrinsic.is_signed(),
+                    signer: extrinsic.address_bytes().and_then(signer_from_address),
+                    field_bytes: extrinsic.field_bytes().to_vec(),
+                    metadata: metadata.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Fetch and decode the extrinsics of `block`.
+    pub(crate) async fn fetch<C: Config>(
+        block: &Block<C, OnlineClient<C>>,
+        metadata: Metadata,
+    ) -> Result<Vec<Self>, IndexerError> {
+        let mut extrinsics = Vec::new();
+        for extrinsic in block.extrinsics().await?.iter() {
+            extrinsics.push(Self {
+                index: extrinsic.index(),
+                pallet: extrinsic.pallet_name()?.to_string(),
+                call: extrinsic.variant_name()?.to_string(),
+                signed: extrinsic.is_signed(),
+                signer: extrinsic.address_bytes().and_then(signer_from_address),
+                field_bytes: extrinsic.field_bytes().to_vec(),
+                metadata: metadata.clone(),
+            });
+        }
+        Ok(extrinsics)
+    }
+
+    /// Position in the block, matching `Phase::ApplyExtrinsic` of its events.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn pallet_name(&self) -> &str {
+        &self.pallet
+    }
+
+    pub fn call_name(&self) -> &str {
+        &self.call
+    }
+
+    /// Whether the extrinsic carries a signature. Inherents such as
+    /// `Timestamp.set` and other bare extrinsics are unsigned.
+    pub fn is_signed(&self) -> bool {
+        self.signed
+    }
+
+    /// See [`ExtrinsicInfo::signer`].
+    pub fn signer(&self) -> Option<&AccountId32> {
+        self.signer.as_ref()
+    }
+
+    /// The SCALE-encoded call arguments.
+    pub fn field_bytes(&self) -> &[u8] {
+        &self.field_bytes
+    }
+
+    /// Decode the call as `T` if it is `T`'s pallet and call, `None` otherwise.
+    /// Arguments are decoded through the runtime metadata and matched by name,
+    /// so `T` keeps decoding across runtimes that reorder arguments or change
+    /// their integer widths or compact encoding.
+    pub fn as_call<T: StaticExtrinsic + DecodeAsFields>(
+        &self,
+    ) -> Result<Option<T>, Box<subxt::Error>> {
+        if !T::is_extrinsic(&self.pallet, &self.call) {
+            return Ok(None);
+        }
+        let variant = self
+            .metadata
+            .pallet_by_name(&self.pallet)
+            .and_then(|pallet| pallet.call_variant_by_name(&self.call))
+            .ok_or_else(|| {
+                Box::new(subxt::Error::Other(format!(
+                    "call {}.{} not found in metadata",
+                    self.pallet, self.call
+                )))
+            })?;
+        let mut fields = variant
+            .fields
+            .iter()
+            .map(|field| Field::new(field.ty.id, field.name.as_deref()));
+        T::decode_as_fields(
+            &mut &self.field_bytes[..],
+            &mut fields,
+            self.metadata.types(),
+        )
+        .map(Some)
+        .map_err(|e| {
+            Box::new(subxt::Error::Other(format!(
+                "failed to decode call {}.{}: {e}",
+                self.pallet, self.call
+            )))
+        })
+    }
+
+    /// The summary kept in [`BlockExtrinsics`] for
+    /// [`Context::extrinsic_info`](crate::handler::Context::extrinsic_info).
+    pub fn info(&self) -> ExtrinsicInfo {
+        ExtrinsicInfo {
+            pallet: self.pallet.clone(),
+            call: self.call.clone(),
+            signer: self.signer.clone(),
+            success: None,
+            fee: None,
+            tip: None,
+        }
+    }
+}
+
+/// The account id in a SCALE-encoded signer address: either a bare
+/// `AccountId32` or `MultiAddress::Id`, which is prefixed with variant index 0.
+pub fn signer_from_address(address: &[u8]) -> Option<AccountId32> {
+    let account = match address {
+        [0, account @ ..] if account.len() == 32 => account,
+        account if account.len() == 32 => account,
+        _ => return None,
+    };
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(account);
+    Some(AccountId32(bytes))
+}
@@ -0,0 +1,55 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Ready-made [`EventFilter`](crate::handler::EventFilter)s for well-known
+//! Bittensor pallets and events, so pallet and event names are not retyped in
+//! every handler.
+//!
+//! ```
+//! # use flamewire_bittensor_indexer::prelude::*;
+//! fn event_filter() -> EventFilter {
+//!     filters::balances::TRANSFER
+//! }
+//! ```
+
+pub mod subtensor {
+    use crate::handler::EventFilter;
+
+    pub const PALLET: &str = "SubtensorModule";
+
+    /// Every `SubtensorModule` event.
+    pub const fn pallet() -> EventFilter {
+        EventFilter::pallet(PALLET)
+    }
+
+    pub const NEURON_REGISTERED: EventFilter = EventFilter::event(PALLET, "NeuronRegistered");
+    pub const AXON_SERVED: EventFilter = EventFilter::event(PALLET, "AxonServed");
+    pub const STAKE_ADDED: EventFilter = EventFilter::event(PALLET, "StakeAdded");
+    pub const STAKE_REMOVED: EventFilter = EventFilter::event(PALLET, "StakeRemoved");
+}
+
+pub mod balances {
+    use crate::handler::EventFilter;
+
+    pub const PALLET: &str = "Balances";
+
+    /// Every `Balances` event.
+    pub const fn pallet() -> EventFilter {
+        EventFilter::pallet(PALLET)
+    }
+
+    pub const TRANSFER: EventFilter = EventFilter::event(PALLET, "Transfer");
+}
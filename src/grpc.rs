@@ -0,0 +1,163 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Stream live events to gRPC subscribers, with the `grpc` feature.
+//!
+//! [`EventStreamService`] implements the `EventStream` service of
+//! `proto/indexer.proto` on top of an [`EventBroadcast`], streaming its events
+//! to subscribers, each with its own filter.
+//! [`IndexerBuilder::serve_grpc`](crate::builder::IndexerBuilder::serve_grpc)
+//! sets both up; [`GrpcServer`] serves a service set up by hand.
+
+use crate::broadcast::{EventBroadcast, LiveEvent};
+use crate::error::IndexerError;
+use futures::Stream;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+/// Code generated from `proto/indexer.proto`.
+pub mod proto {
+    tonic::include_proto!("flamewire.indexer.v1");
+}
+
+use proto::event_stream_server::{EventStream, EventStreamServer};
+use proto::{EventEnvelope, FilterRequest};
+
+/// The `EventStream` service, streaming the events of an [`EventBroadcast`].
+///
+/// A subscriber that falls behind misses its oldest events, counted by
+/// [`EventBroadcast::dropped`], and keeps streaming.
+#[derive(Clone)]
+pub struct EventStreamService {
+    events: EventBroadcast,
+}
+
+impl EventStreamService {
+    /// Stream the events published to `events`, which must be added to the
+    /// indexer as a handler.
+    pub fn new(events: EventBroadcast) -> Self {
+        Self { events }
+    }
+}
+
+impl From<&LiveEvent> for EventEnvelope {
+    fn from(event: &LiveEvent) -> Self {
+        Self {
+            block_number: event.block_number,
+            block_hash: event.block_hash.clone(),
+            index: event.index,
+            pallet: event.pallet.clone(),
+            variant: event.variant.clone(),
+            fields_json: event.fields_json.as_ref().map(|fields| fields.to_string()),
+            netuid: event.netuid.map(u32::from),
+        }
+    }
+}
+
+/// Whether `event` passes `filter`, see `FilterRequest` in the proto file.
+fn matches(filter: &FilterRequest, event: &LiveEvent) -> bool {
+    let named = (filter.pallets.is_empty() && filter.events.is_empty())
+        || filter.pallets.contains(&event.pallet)
+        || filter.events.iter().any(|name| {
+            name.split_once('.') == Some((event.pallet.as_str(), event.variant.as_str()))
+        });
+    named
+        && filter
+            .netuid
+            .is_none_or(|netuid| event.netuid.map(u32::from) == Some(netuid))
+}
+
+type EnvelopeStream = Pin<Box<dyn Stream<Item = Result<EventEnvelope, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl EventStream for EventStreamService {
+    type SubscribeStream = EnvelopeStream;
+
+    async fn subscribe(
+        &self,
+        request: Request<FilterRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let filter = request.into_inner();
+        let receiver = self.events.subscribe();
+        let events = self.events.clone();
+        let stream = futures::stream::unfold(receiver, move |mut receiver| {
+            let filter = filter.clone();
+            let events = events.clone();
+            async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) if matches(&filter, &event) => {
+                            return Some((Ok(EventEnvelope::from(&*event)), receiver));
+                        }
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(skipped)) => {
+                            events.record_dropped(skipped);
+                            warn!(
+                                target: "indexer",
+                                skipped,
+                                "gRPC subscriber fell behind, dropping events"
+                            );
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Running gRPC server, stopped when dropped.
+pub struct GrpcServer {
+    local_addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl GrpcServer {
+    /// Listen on `addr` and serve `service` as `EventStream`.
+    pub async fn bind(addr: SocketAddr, service: EventStreamService) -> Result<Self, IndexerError> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        info!(target: "indexer", %local_addr, "serving gRPC event stream");
+        let task = tokio::spawn(async move {
+            let result = tonic::transport::Server::builder()
+                .add_service(EventStreamServer::new(service))
+                .serve_with_incoming(TcpListenerStream::new(listener))
+                .await;
+            if let Err(e) = result {
+                warn!(target: "indexer", "gRPC server stopped: {e}");
+            }
+        });
+        Ok(Self { local_addr, task })
+    }
+
+    /// Address the server listens on, useful when bound to port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for GrpcServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -15,18 +15,54 @@
  */
 
 use crate::error::IndexerError;
-use crate::types::ChainEvent;
+use crate::extrinsics::{BlockExtrinsics, ChainExtrinsic, ExtrinsicInfo};
+#[cfg(feature = "metrics")]
+use crate::metrics::IndexerMetrics;
+use crate::recorder::MetricsRecorder;
+use crate::reporter::ErrorReporter;
+use crate::types::{BlockNumber, ChainEvent, EventId};
 use async_trait::async_trait;
+use futures::FutureExt;
+use parity_scale_codec::Decode;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use subxt::config::HashFor;
-use subxt::Config;
+use subxt::storage::Storage;
+use subxt::utils::AccountId32;
+use subxt::{Config, OnlineClient};
 
 pub struct Context<C: Config> {
     pub block_number: u64,
     pub block_hash: HashFor<C>,
     pipeline: Mutex<HashMap<String, Box<dyn Any + Send + Sync>>>,
+    extrinsics: Option<Arc<BlockExtrinsics>>,
+    storage: Option<Storage<C, OnlineClient<C>>>,
+    block_author: Option<AccountId32>,
+    timestamp: Option<u64>,
+    error_reporter: Option<Arc<dyn ErrorReporter>>,
+    /// The indexer's metrics backend, whichever it is.
+    recorder: Option<Arc<dyn MetricsRecorder>>,
+    abort_on_panic: bool,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<IndexerMetrics>>,
+}
+
+impl<C: Config> fmt::Debug for Context<C> {
+    /// Shows the names of the pipeline entries but not their values, which
+    /// need not implement `Debug`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("block_number", &self.block_number)
+            .field("block_hash", &self.block_hash)
+            .field("pipeline_keys", &self.pipeline_keys())
+            .finish()
+    }
 }
 
 impl<C: Config> Context<C> {
@@ -35,9 +71,144 @@ impl<C: Config> Context<C> {
             block_number,
             block_hash,
             pipeline: Mutex::new(HashMap::new()),
+            extrinsics: None,
+            storage: None,
+            block_author: None,
+            timestamp: None,
+            error_reporter: None,
+            recorder: None,
+            abort_on_panic: false,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 
+    /// Build a context field by field, as handler tests do.
+    pub fn builder() -> ContextBuilder<C> {
+        ContextBuilder::default()
+    }
+
+    /// Attach the indexer's error reporter, see [`error_reporter`](Self::error_reporter).
+    pub fn with_error_reporter(mut self, reporter: Arc<dyn ErrorReporter>) -> Self {
+        self.error_reporter = Some(reporter);
+        self
+    }
+
+    /// Where errors that handlers recover from should be reported, set by the
+    /// indexer for every block. Non-strict [`HandlerGroup`](crate::handler_group::HandlerGroup)s
+    /// report the member failures they absorb here.
+    pub fn error_reporter(&self) -> Option<&Arc<dyn ErrorReporter>> {
+        self.error_reporter.as_ref()
+    }
+
+    /// Attach the indexer's metrics, see [`metrics`](Self::metrics).
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: Arc<IndexerMetrics>) -> Self {
+        self.recorder = Some(metrics.clone());
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Attach the indexer's metrics backend, see [`recorder`](Self::recorder).
+    pub(crate) fn with_recorder(mut self, recorder: Arc<dyn MetricsRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Where handler groups and sinks record their metrics: Prometheus, StatsD
+    /// or nothing, depending on the builder.
+    pub(crate) fn recorder(&self) -> Option<&Arc<dyn MetricsRecorder>> {
+        self.recorder.as_ref()
+    }
+
+    /// Let handler panics unwind instead of catching them, see
+    /// [`abort_on_panic`](crate::builder::IndexerBuilder::abort_on_panic).
+    pub(crate) fn with_abort_on_panic(mut self, abort: bool) -> Self {
+        self.abort_on_panic = abort;
+        self
+    }
+
+    /// Whether handler groups let their members' panics unwind.
+    pub(crate) fn aborts_on_panic(&self) -> bool {
+        self.abort_on_panic
+    }
+
+    /// The indexer's Prometheus metrics, when it was built with
+    /// [`metrics`](crate::builder::IndexerBuilder::metrics) or
+    /// [`serve_metrics`](crate::builder::IndexerBuilder::serve_metrics).
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Option<&Arc<IndexerMetrics>> {
+        self.metrics.as_ref()
+    }
+
+    /// Record the validator that authored this block, see
+    /// [`block_author`](Self::block_author).
+    pub fn with_block_author(mut self, author: AccountId32) -> Self {
+        self.block_author = Some(author);
+        self
+    }
+
+    /// The validator hotkey that authored this block. Always `None` unless the
+    /// indexer was built with
+    /// [`resolve_block_author`](crate::builder::IndexerBuilder::resolve_block_author),
+    /// and `None` for blocks without an AURA or BABE slot claim.
+    pub fn block_author(&self) -> Option<&AccountId32> {
+        self.block_author.as_ref()
+    }
+
+    /// Record when this block was produced, see [`timestamp`](Self::timestamp).
+    pub fn with_timestamp(mut self, millis: u64) -> Self {
+        self.timestamp = Some(millis);
+        self
+    }
+
+    /// When this block was produced, in milliseconds since the Unix epoch, as
+    /// read from `Timestamp.Now` at the block. `None` on chains without a
+    /// `Timestamp` pallet and for replayed blocks.
+    pub fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
+    /// Attach storage access pinned to this block, see [`storage`](Self::storage).
+    pub fn with_storage(mut self, storage: Storage<C, OnlineClient<C>>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Storage at this context's block, for reading chain state as of the events
+    /// being handled. Set by the indexer for every block; `None` for contexts
+    /// built by hand, as in tests.
+    pub fn storage(&self) -> Option<&Storage<C, OnlineClient<C>>> {
+        self.storage.as_ref()
+    }
+
+    /// Attach the block's extrinsics, see [`extrinsic_info`](Self::extrinsic_info).
+    pub fn with_extrinsics(mut self, extrinsics: Arc<BlockExtrinsics>) -> Self {
+        self.extrinsics = Some(extrinsics);
+        self
+    }
+
+    /// The extrinsic at `index`, typically
+    /// [`ChainEvent::extrinsic_index`](crate::types::ChainEvent::extrinsic_index).
+    /// Always `None` unless the indexer was built with
+    /// [`correlate_extrinsics`](crate::builder::IndexerBuilder::correlate_extrinsics).
+    pub fn extrinsic_info(&self, index: Option<u32>) -> Option<&ExtrinsicInfo> {
+        self.extrinsics.as_ref()?.get(index?)
+    }
+
+    /// Fee paid by the extrinsic at `index`, tip included. Always `None` unless the
+    /// indexer was built with
+    /// [`track_fees`](crate::builder::IndexerBuilder::track_fees), see
+    /// [`ExtrinsicInfo::fee`].
+    pub fn extrinsic_fee(&self, index: Option<u32>) -> Option<u128> {
+        self.extrinsic_info(index)?.fee
+    }
+
+    /// Stable identifier of `event` within this block, see [`EventId`].
+    pub fn event_id(&self, event: &ChainEvent<C>) -> EventId {
+        event.event_id(&self.block_hash)
+    }
+
     /// Store data for use by subsequent handlers in a pipeline
     pub fn set_pipeline_data<T: Send + Sync + 'static>(&self, key: &str, data: T) {
         let mut map = self.pipeline.lock().unwrap();
@@ -55,11 +226,137 @@ impl<C: Config> Context<C> {
         let map = self.pipeline.lock().unwrap();
         map.get(key)?.downcast_ref::<T>().cloned()
     }
+
+    /// Keys of the pipeline data currently stored, sorted.
+    pub fn pipeline_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .pipeline
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        keys.sort();
+        keys
+    }
 }
 
+/// Builder of a [`Context`], see [`Context::builder`]. Block `0` with a zero hash
+/// and nothing attached unless set.
+pub struct ContextBuilder<C: Config> {
+    block_number: u64,
+    block_hash: Option<HashFor<C>>,
+    pipeline: HashMap<String, Box<dyn Any + Send + Sync>>,
+    extrinsics: Option<Arc<BlockExtrinsics>>,
+    storage: Option<Storage<C, OnlineClient<C>>>,
+    block_author: Option<AccountId32>,
+    timestamp: Option<u64>,
+    error_reporter: Option<Arc<dyn ErrorReporter>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<IndexerMetrics>>,
+}
+
+impl<C: Config> Default for ContextBuilder<C> {
+    fn default() -> Self {
+        Self {
+            block_number: 0,
+            block_hash: None,
+            pipeline: HashMap::new(),
+            extrinsics: None,
+            storage: None,
+            block_author: None,
+            timestamp: None,
+            error_reporter: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+}
+
+impl<C: Config> ContextBuilder<C> {
+    /// Number of the block, `0` unless set.
+    pub fn block_number(mut self, number: u64) -> Self {
+        self.block_number = number;
+        self
+    }
+
+    /// Hash of the block, all zeroes unless set.
+    pub fn block_hash(mut self, hash: HashFor<C>) -> Self {
+        self.block_hash = Some(hash);
+        self
+    }
+
+    /// Store `data` under `key`, as a previous handler would with
+    /// [`Context::set_pipeline_data`].
+    pub fn pipeline_data<T: Send + Sync + 'static>(mut self, key: &str, data: T) -> Self {
+        self.pipeline.insert(key.to_string(), Box::new(data));
+        self
+    }
+
+    /// See [`Context::with_extrinsics`].
+    pub fn extrinsics(mut self, extrinsics: Arc<BlockExtrinsics>) -> Self {
+        self.extrinsics = Some(extrinsics);
+        self
+    }
+
+    /// See [`Context::with_storage`].
+    pub fn storage(mut self, storage: Storage<C, OnlineClient<C>>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// See [`Context::with_block_author`].
+    pub fn block_author(mut self, author: AccountId32) -> Self {
+        self.block_author = Some(author);
+        self
+    }
+
+    /// See [`Context::with_timestamp`].
+    pub fn timestamp(mut self, millis: u64) -> Self {
+        self.timestamp = Some(millis);
+        self
+    }
+
+    /// See [`Context::with_error_reporter`].
+    pub fn error_reporter(mut self, reporter: Arc<dyn ErrorReporter>) -> Self {
+        self.error_reporter = Some(reporter);
+        self
+    }
+
+    /// See [`Context::with_metrics`].
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, metrics: Arc<IndexerMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// The context, with the pipeline entries stored. Anything not set is left
+    /// as [`Context::new`] leaves it.
+    pub fn build(self) -> Context<C> {
+        let block_hash = self.block_hash.unwrap_or_else(|| {
+            HashFor::<C>::decode(&mut &[0u8; 64][..]).expect("hashes decode from zero bytes")
+        });
+        let mut ctx = Context::new(self.block_number, block_hash);
+        ctx.pipeline = Mutex::new(self.pipeline);
+        ctx.extrinsics = self.extrinsics;
+        ctx.storage = self.storage;
+        ctx.block_author = self.block_author;
+        ctx.timestamp = self.timestamp;
+        ctx.error_reporter = self.error_reporter;
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = self.metrics {
+            ctx = ctx.with_metrics(metrics);
+        }
+        ctx
+    }
+}
+
+#[derive(Clone, Copy)]
 pub struct EventFilter {
     pub pallet: Option<&'static str>,
     pub event: Option<&'static str>,
+    /// Decoded field that must hold the given value, see [`EventFilter::with_field_u16`].
+    field: Option<(&'static str, u16)>,
 }
 
 impl EventFilter {
@@ -67,6 +364,7 @@ impl EventFilter {
         Self {
             pallet: None,
             event: None,
+            field: None,
         }
     }
 
@@ -74,6 +372,7 @@ impl EventFilter {
         Self {
             pallet: Some(pallet),
             event: None,
+            field: None,
         }
     }
 
@@ -81,9 +380,21 @@ impl EventFilter {
         Self {
             pallet: Some(pallet),
             event: Some(event),
+            field: None,
         }
     }
 
+    /// Additionally require the named field to equal `value`, such as
+    /// `filters::subtensor::pallet().with_field_u16("netuid", 18)`. Events
+    /// without that field do not match.
+    pub const fn with_field_u16(self, name: &'static str, value: u16) -> Self {
+        Self {
+            field: Some((name, value)),
+            ..self
+        }
+    }
+
+    /// Match on pallet and event name only, ignoring any field condition.
     pub fn matches(&self, pallet: &str, event: &str) -> bool {
         match (self.pallet, self.event) {
             (Some(p), Some(e)) => p == pallet && e == event,
@@ -92,11 +403,137 @@ impl EventFilter {
             _ => false,
         }
     }
+
+    /// Match a decoded event, including any field condition. The event's
+    /// fields are only decoded once its pallet and event names match.
+    pub fn matches_event<C: Config>(&self, event: &ChainEvent<C>) -> bool {
+        self.matches_with(event.pallet_name(), event.variant_name(), |name| {
+            event.field(name).ok().flatten()?.as_u128()
+        })
+    }
+
+    /// [`matches_event`](Self::matches_event) with `field` looking up an
+    /// integer field by name. `field` is not called unless the names match.
+    pub fn matches_with(
+        &self,
+        pallet: &str,
+        event: &str,
+        field: impl FnOnce(&str) -> Option<u128>,
+    ) -> bool {
+        if !self.matches(pallet, event) {
+            return false;
+        }
+        match self.field {
+            Some((name, value)) => field(name) == Some(u128::from(value)),
+            None => true,
+        }
+    }
+}
+
+/// Event selector written as a string, used by
+/// [`IndexerConfig::event_allowlist`](crate::config::IndexerConfig::event_allowlist).
+///
+/// `"Balances.Transfer"` selects one event and `"SubtensorModule.*"` every event of a
+/// pallet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct EventPattern {
+    pallet: String,
+    /// `None` matches every event of the pallet.
+    event: Option<String>,
+}
+
+impl EventPattern {
+    /// Parse a `Pallet.Event` or `Pallet.*` pattern.
+    pub fn parse(pattern: &str) -> Result<Self, IndexerError> {
+        let invalid = |reason: &str| {
+            IndexerError::invalid_config(
+                "event_allowlist",
+                format!("invalid pattern `{pattern}`: {reason}"),
+            )
+        };
+        let (pallet, event) = pattern
+            .split_once('.')
+            .ok_or_else(|| invalid("expected `Pallet.Event` or `Pallet.*`"))?;
+        if !is_identifier(pallet) {
+            return Err(invalid("pallet must be a name"));
+        }
+        let event = match event {
+            "*" => None,
+            event if is_identifier(event) => Some(event.to_string()),
+            _ => return Err(invalid("event must be a name or `*`")),
+        };
+        Ok(Self {
+            pallet: pallet.to_string(),
+            event,
+        })
+    }
+
+    pub fn pallet(&self) -> &str {
+        &self.pallet
+    }
+
+    /// The selected event, or `None` for every event of the pallet.
+    pub fn event(&self) -> Option<&str> {
+        self.event.as_deref()
+    }
+
+    pub fn matches(&self, pallet: &str, event: &str) -> bool {
+        self.pallet == pallet && self.event.as_deref().is_none_or(|e| e == event)
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl FromStr for EventPattern {
+    type Err = IndexerError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        Self::parse(pattern)
+    }
 }
 
+impl TryFrom<String> for EventPattern {
+    type Error = IndexerError;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Self::parse(&pattern)
+    }
+}
+
+impl From<EventPattern> for String {
+    fn from(pattern: EventPattern) -> Self {
+        pattern.to_string()
+    }
+}
+
+impl fmt::Display for EventPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}",
+            self.pallet,
+            self.event.as_deref().unwrap_or("*")
+        )
+    }
+}
+
+/// Receives the blocks, extrinsics and events the indexer processes.
+///
+/// A panic in a hook is caught and handled like an error returned by it, unless
+/// [`abort_on_panic`](crate::builder::IndexerBuilder::abort_on_panic) is set.
+/// Handlers need not be `UnwindSafe`, so state they keep between calls, such as
+/// behind a `Mutex`, may be left half-updated by a panic.
 #[allow(unused_variables)]
 #[async_trait]
 pub trait Handler<C: Config>: Send + Sync {
+    /// Name of the handler in tracing spans, the type name by default.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
     fn event_filter(&self) -> EventFilter {
         EventFilter::all()
     }
@@ -117,5 +554,74 @@ pub trait Handler<C: Config>: Send + Sync {
         Ok(())
     }
 
+    /// Called for every extrinsic of the block, in block order, after
+    /// [`handle_block`](Self::handle_block) and before the block's events. Only
+    /// called when the indexer was built with
+    /// [`correlate_extrinsics`](crate::builder::IndexerBuilder::correlate_extrinsics);
+    /// [`event_filter`](Self::event_filter) does not apply.
+    async fn handle_extrinsic(
+        &self,
+        extrinsic: &ChainExtrinsic,
+        ctx: &Context<C>,
+    ) -> Result<(), IndexerError> {
+        Ok(())
+    }
+
     async fn handle_error(&self, error: &IndexerError, ctx: &Context<C>) {}
+
+    /// Called when blocks `from..=to`, already handled, are no longer on the best chain
+    /// (only with [`FinalityMode::Best`](crate::config::FinalityMode::Best)). The blocks
+    /// of the new chain are handled afterwards. An error stops the indexer.
+    async fn on_reorg(&self, from: u64, to: u64) -> Result<(), IndexerError> {
+        Ok(())
+    }
+
+    /// Called before the indexer stores a checkpoint at `block`, every block up to
+    /// which has been handled. Returns the last block whose output the handler has
+    /// made durable, or `None` if none is yet; the checkpoint is held back to the
+    /// lowest block any handler returns, so blocks still buffered in memory are
+    /// handled again after a crash. `block` itself by default, for handlers that
+    /// write each block as it is handled. An error stops the indexer.
+    async fn on_checkpoint(&self, block: BlockNumber) -> Result<Option<BlockNumber>, IndexerError> {
+        Ok(Some(block))
+    }
+
+    /// Called when [`Indexer::run`](crate::indexer::Indexer::run) stops, with or
+    /// without an error, before the final checkpoint is stored, to flush anything the
+    /// handler buffers. The handler must keep working if `run` is called again. An
+    /// error fails the run, once every other handler has been shut down and the
+    /// checkpoint stored.
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        Ok(())
+    }
+}
+
+/// Run a handler hook, turning a panic into [`IndexerError::HandlerFailed`] naming
+/// `handler` and `block`, unless `abort` is set. The hook is asserted to be
+/// `UnwindSafe`, as handlers are not required to be.
+pub(crate) async fn catch_panic<T>(
+    handler: &str,
+    block: BlockNumber,
+    abort: bool,
+    hook: impl Future<Output = Result<T, IndexerError>>,
+) -> Result<T, IndexerError> {
+    if abort {
+        return hook.await;
+    }
+    match AssertUnwindSafe(hook).catch_unwind().await {
+        Ok(res) => res,
+        Err(payload) => {
+            let message = format!("panicked: {}", panic_message(payload.as_ref()));
+            Err(IndexerError::handler_failed(handler, block, message))
+        }
+    }
+}
+
+/// The message of a caught panic, for the usual `&str` and `String` payloads.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload")
 }
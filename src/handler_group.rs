@@ -15,16 +15,32 @@
  */
 
 use crate::error::IndexerError;
-use crate::handler::{Context, EventFilter, Handler};
-use crate::types::ChainEvent;
+use crate::extrinsics::ChainExtrinsic;
+use crate::handler::{catch_panic, Context, EventFilter, Handler};
+use crate::reporter::ErrorContext;
+use crate::retry::{
+    BreakerDomain, BreakerState, BreakerStats, CircuitBreaker, CircuitBreakerConfig,
+};
+use crate::types::{BlockNumber, ChainEvent};
 use async_trait::async_trait;
 use futures::future::join_all;
+use std::future::Future;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use subxt::Config;
+use tracing::warn;
+
+/// Log a skipped-event warning on the first skip and then once every this many skips.
+const SKIP_WARN_INTERVAL: u64 = 100;
 
 /// A group of handlers that can be added as a single unit.
+///
+/// A member's panic is caught and handled like an error it returned, unless
+/// [`abort_on_panic`](crate::builder::IndexerBuilder::abort_on_panic) is set.
 pub struct HandlerGroup<C: Config> {
     handlers: Vec<Box<dyn Handler<C>>>,
+    breakers: Vec<Arc<HandlerBreaker>>,
     strict: bool,
     parallel: bool,
 }
@@ -40,6 +56,7 @@ impl<C: Config> HandlerGroup<C> {
     pub fn new() -> Self {
         Self {
             handlers: Vec::new(),
+            breakers: Vec::new(),
             strict: false,
             parallel: false,
         }
@@ -49,6 +66,7 @@ impl<C: Config> HandlerGroup<C> {
     pub fn parallel() -> Self {
         Self {
             handlers: Vec::new(),
+            breakers: Vec::new(),
             strict: false,
             parallel: true,
         }
@@ -61,6 +79,24 @@ impl<C: Config> HandlerGroup<C> {
         self
     }
 
+    /// Add a handler guarded by its own circuit breaker, see [`BreakerHandler`].
+    pub fn add_with_breaker(
+        mut self,
+        handler: impl Handler<C> + 'static,
+        config: CircuitBreakerConfig,
+    ) -> Self {
+        let handler = BreakerHandler::new(handler, &config);
+        self.breakers.push(handler.breaker());
+        self.handlers.push(Box::new(handler));
+        self
+    }
+
+    /// Breakers of the handlers added with [`add_with_breaker`](Self::add_with_breaker),
+    /// in insertion order.
+    pub fn breakers(&self) -> &[Arc<HandlerBreaker>] {
+        &self.breakers
+    }
+
     /// Enable strict mode which aborts execution on the first handler error
     pub fn strict(mut self) -> Self {
         self.strict = true;
@@ -105,32 +141,22 @@ where
                 .handlers
                 .iter()
                 .enumerate()
-                .filter(|(_, h)| {
-                    h.event_filter()
-                        .matches(event.pallet_name(), event.variant_name())
+                .filter(|(_, h)| h.event_filter().matches_event(event))
+                .map(|(i, h)| async move {
+                    (i, guarded(h.name(), ctx, h.handle_event(event, ctx)).await)
                 })
-                .map(|(i, h)| async move { (i, h.handle_event(event, ctx).await) })
                 .collect();
             let results = join_all(futures).await;
             for (i, res) in results {
                 if let Err(e) = res {
-                    let h = &self.handlers[i];
-                    h.handle_error(&e, ctx).await;
-                    if self.strict {
-                        return Err(e);
-                    }
+                    member_failed(self.handlers[i].as_ref(), e, ctx, self.strict).await?;
                 }
             }
         } else {
             for h in &self.handlers {
-                if h.event_filter()
-                    .matches(event.pallet_name(), event.variant_name())
-                {
-                    if let Err(e) = h.handle_event(event, ctx).await {
-                        h.handle_error(&e, ctx).await;
-                        if self.strict {
-                            return Err(e);
-                        }
+                if h.event_filter().matches_event(event) {
+                    if let Err(e) = guarded(h.name(), ctx, h.handle_event(event, ctx)).await {
+                        member_failed(h.as_ref(), e, ctx, self.strict).await?;
                     }
                 }
             }
@@ -148,25 +174,53 @@ where
                 .handlers
                 .iter()
                 .enumerate()
-                .map(|(i, h)| async move { (i, h.handle_block(ctx, events).await) })
+                .map(|(i, h)| async move {
+                    (i, guarded(h.name(), ctx, h.handle_block(ctx, events)).await)
+                })
                 .collect();
             let results = join_all(futures).await;
             for (i, res) in results {
                 if let Err(e) = res {
-                    let h = &self.handlers[i];
-                    h.handle_error(&e, ctx).await;
-                    if self.strict {
-                        return Err(e);
-                    }
+                    member_failed(self.handlers[i].as_ref(), e, ctx, self.strict).await?;
                 }
             }
         } else {
             for h in &self.handlers {
-                if let Err(e) = h.handle_block(ctx, events).await {
-                    h.handle_error(&e, ctx).await;
-                    if self.strict {
-                        return Err(e);
-                    }
+                if let Err(e) = guarded(h.name(), ctx, h.handle_block(ctx, events)).await {
+                    member_failed(h.as_ref(), e, ctx, self.strict).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_extrinsic(
+        &self,
+        extrinsic: &ChainExtrinsic,
+        ctx: &Context<C>,
+    ) -> Result<(), IndexerError> {
+        if self.parallel {
+            let futures: Vec<_> = self
+                .handlers
+                .iter()
+                .enumerate()
+                .map(|(i, h)| async move {
+                    (
+                        i,
+                        guarded(h.name(), ctx, h.handle_extrinsic(extrinsic, ctx)).await,
+                    )
+                })
+                .collect();
+            let results = join_all(futures).await;
+            for (i, res) in results {
+                if let Err(e) = res {
+                    member_failed(self.handlers[i].as_ref(), e, ctx, self.strict).await?;
+                }
+            }
+        } else {
+            for h in &self.handlers {
+                if let Err(e) = guarded(h.name(), ctx, h.handle_extrinsic(extrinsic, ctx)).await {
+                    member_failed(h.as_ref(), e, ctx, self.strict).await?;
                 }
             }
         }
@@ -175,8 +229,39 @@ where
 
     async fn handle_error(&self, error: &IndexerError, ctx: &Context<C>) {
         for h in &self.handlers {
-            h.handle_error(error, ctx).await;
+            error_hook(h.as_ref(), error, ctx).await;
+        }
+    }
+
+    /// Unwinds every member, then returns the first error.
+    async fn on_reorg(&self, from: u64, to: u64) -> Result<(), IndexerError> {
+        let mut error = None;
+        for h in &self.handlers {
+            if let Err(e) = h.on_reorg(from, to).await {
+                error.get_or_insert(e);
+            }
+        }
+        error.map_or(Ok(()), Err)
+    }
+
+    /// The lowest block any member has made durable.
+    async fn on_checkpoint(&self, block: BlockNumber) -> Result<Option<BlockNumber>, IndexerError> {
+        let mut durable = Some(block);
+        for h in &self.handlers {
+            durable = durable.min(h.on_checkpoint(block).await?);
         }
+        Ok(durable)
+    }
+
+    /// Shuts down every member, then returns the first error.
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        let mut error = None;
+        for h in &self.handlers {
+            if let Err(e) = h.on_shutdown().await {
+                error.get_or_insert(e);
+            }
+        }
+        error.map_or(Ok(()), Err)
     }
 }
 
@@ -193,6 +278,10 @@ where
     H: Handler<C> + 'static,
     F: Fn(&ChainEvent<C>) -> bool + Send + Sync + 'static,
 {
+    fn name(&self) -> &str {
+        self.handler.name()
+    }
+
     fn event_filter(&self) -> EventFilter {
         self.handler.event_filter()
     }
@@ -217,7 +306,223 @@ where
         self.handler.handle_block(ctx, events).await
     }
 
+    async fn handle_extrinsic(
+        &self,
+        extrinsic: &ChainExtrinsic,
+        ctx: &Context<C>,
+    ) -> Result<(), IndexerError> {
+        self.handler.handle_extrinsic(extrinsic, ctx).await
+    }
+
+    async fn handle_error(&self, error: &IndexerError, ctx: &Context<C>) {
+        self.handler.handle_error(error, ctx).await;
+    }
+
+    async fn on_reorg(&self, from: u64, to: u64) -> Result<(), IndexerError> {
+        self.handler.on_reorg(from, to).await
+    }
+
+    async fn on_checkpoint(&self, block: BlockNumber) -> Result<Option<BlockNumber>, IndexerError> {
+        self.handler.on_checkpoint(block).await
+    }
+
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        self.handler.on_shutdown().await
+    }
+}
+
+/// Circuit breaker state shared between a [`BreakerHandler`] and its observers.
+pub struct HandlerBreaker {
+    name: &'static str,
+    breaker: CircuitBreaker,
+    skipped: AtomicU64,
+}
+
+impl HandlerBreaker {
+    /// Type name of the wrapped handler.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.breaker.state()
+    }
+
+    pub fn stats(&self) -> BreakerStats {
+        self.breaker.stats()
+    }
+
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// Events skipped because the breaker was open.
+    pub fn skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+}
+
+/// Handler wrapper that stops calling `handle_event` after repeated failures.
+///
+/// Failures of the inner handler's `handle_event` are recorded on a [`CircuitBreaker`];
+/// once it opens, events are skipped (and counted) until the cooldown elapses, and a
+/// successful probe closes it again. Skipped events return `Ok(())`, so an open breaker
+/// never aborts a strict [`HandlerGroup`]. `handle_block` and `handle_extrinsic` are skipped
+/// while the breaker is open but do not affect it.
+pub struct BreakerHandler<H> {
+    handler: H,
+    breaker: Arc<HandlerBreaker>,
+}
+
+impl<H> BreakerHandler<H> {
+    pub fn new(handler: H, config: &CircuitBreakerConfig) -> Self {
+        let name = std::any::type_name::<H>();
+        Self {
+            handler,
+            breaker: Arc::new(HandlerBreaker {
+                name,
+                breaker: CircuitBreaker::from_config(config).with_domain(BreakerDomain::Handler),
+                skipped: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Shared handle to the breaker, for health reporting.
+    pub fn breaker(&self) -> Arc<HandlerBreaker> {
+        self.breaker.clone()
+    }
+
+    fn skip(&self, block: u64) {
+        let skipped = self.breaker.skipped.fetch_add(1, Ordering::Relaxed) + 1;
+        if skipped == 1 || skipped % SKIP_WARN_INTERVAL == 0 {
+            warn!(
+                target: "indexer",
+                handler = self.breaker.name,
+                block,
+                skipped,
+                "handler circuit breaker open, skipping events"
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl<C, H> Handler<C> for BreakerHandler<H>
+where
+    C: Config + Send + Sync + 'static,
+    H: Handler<C> + 'static,
+{
+    fn name(&self) -> &str {
+        self.handler.name()
+    }
+
+    fn event_filter(&self) -> EventFilter {
+        self.handler.event_filter()
+    }
+
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<C>,
+        ctx: &Context<C>,
+    ) -> Result<(), IndexerError> {
+        if !self.breaker.breaker.try_acquire() {
+            self.skip(ctx.block_number);
+            return Ok(());
+        }
+        let res = self.handler.handle_event(event, ctx).await;
+        match &res {
+            Ok(()) => self.breaker.breaker.record_success(),
+            Err(_) => self.breaker.breaker.record_failure(),
+        }
+        res
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        if self.breaker.breaker.is_open() {
+            return Ok(());
+        }
+        self.handler.handle_block(ctx, events).await
+    }
+
+    async fn handle_extrinsic(
+        &self,
+        extrinsic: &ChainExtrinsic,
+        ctx: &Context<C>,
+    ) -> Result<(), IndexerError> {
+        if self.breaker.breaker.is_open() {
+            return Ok(());
+        }
+        self.handler.handle_extrinsic(extrinsic, ctx).await
+    }
+
     async fn handle_error(&self, error: &IndexerError, ctx: &Context<C>) {
         self.handler.handle_error(error, ctx).await;
     }
+
+    async fn on_reorg(&self, from: u64, to: u64) -> Result<(), IndexerError> {
+        self.handler.on_reorg(from, to).await
+    }
+
+    async fn on_checkpoint(&self, block: BlockNumber) -> Result<Option<BlockNumber>, IndexerError> {
+        self.handler.on_checkpoint(block).await
+    }
+
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        self.handler.on_shutdown().await
+    }
+}
+
+/// Run a member's hook, turning a panic into an error of that member, as the indexer
+/// does for its own handlers, so the other members still run.
+async fn guarded<C: Config>(
+    handler: &str,
+    ctx: &Context<C>,
+    hook: impl Future<Output = Result<(), IndexerError>>,
+) -> Result<(), IndexerError> {
+    catch_panic(handler, ctx.block_number, ctx.aborts_on_panic(), hook).await
+}
+
+/// Pass a member's failure to its `handle_error`, then return it if the group is
+/// strict, or count and report it as absorbed otherwise.
+async fn member_failed<C: Config>(
+    handler: &dyn Handler<C>,
+    error: IndexerError,
+    ctx: &Context<C>,
+    strict: bool,
+) -> Result<(), IndexerError> {
+    error_hook(handler, &error, ctx).await;
+    if strict {
+        return Err(error);
+    }
+    record_absorbed(ctx, handler.name(), &error);
+    Ok(())
+}
+
+/// Run a member's `handle_error` under [`guarded`], absorbing a panic in it.
+async fn error_hook<C: Config>(handler: &dyn Handler<C>, error: &IndexerError, ctx: &Context<C>) {
+    let hook = async {
+        handler.handle_error(error, ctx).await;
+        Ok(())
+    };
+    if let Err(panic) = guarded(handler.name(), ctx, hook).await {
+        record_absorbed(ctx, handler.name(), &panic);
+    }
+}
+
+/// Count and report a member failure that a non-strict group absorbs, which the
+/// indexer never sees.
+fn record_absorbed<C: Config>(ctx: &Context<C>, handler: &str, error: &IndexerError) {
+    if let Some(recorder) = ctx.recorder() {
+        recorder.record_handler_error(error);
+    }
+    if let Some(reporter) = ctx.error_reporter() {
+        reporter.report(
+            error,
+            Some(&ErrorContext::handler(handler, ctx.block_number)),
+        );
+    }
 }
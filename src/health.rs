@@ -0,0 +1,65 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::queue::QueueGauge;
+use crate::retry::BreakerStats;
+use crate::storage::Checkpoint;
+use std::time::Duration;
+
+/// Point-in-time view of the indexer's progress, suitable for health checks.
+#[derive(Clone, Debug)]
+pub struct HealthStatus {
+    pub checkpoint: Option<Checkpoint>,
+    /// Time since the checkpoint was last advanced, if the backend records it.
+    pub staleness: Option<Duration>,
+    /// Whether `staleness` exceeds the threshold set with
+    /// [`IndexerBuilder::stale_after`](crate::builder::IndexerBuilder::stale_after).
+    pub stale: bool,
+    /// Counters of the breaker guarding node RPC calls.
+    pub rpc_breaker: BreakerStats,
+    /// Counters of the breaker guarding checkpoint storage.
+    pub storage_breaker: BreakerStats,
+    /// Blocks fetched but not yet handled. Stays at `queue_capacity` while the
+    /// handlers are the bottleneck.
+    pub queue_depth: usize,
+    /// Configured queue depth.
+    pub queue_capacity: usize,
+}
+
+impl HealthStatus {
+    pub(crate) fn new(
+        checkpoint: Option<Checkpoint>,
+        stale_after: Option<Duration>,
+        rpc_breaker: BreakerStats,
+        storage_breaker: BreakerStats,
+        queue: &QueueGauge,
+    ) -> Self {
+        let staleness = checkpoint.as_ref().and_then(Checkpoint::staleness);
+        let stale = matches!(
+            (staleness, stale_after),
+            (Some(staleness), Some(threshold)) if staleness > threshold
+        );
+        Self {
+            checkpoint,
+            staleness,
+            stale,
+            rpc_breaker,
+            storage_breaker,
+            queue_depth: queue.depth(),
+            queue_capacity: queue.capacity(),
+        }
+    }
+}
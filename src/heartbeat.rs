@@ -0,0 +1,112 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Liveness file for orchestrators that probe a file's modification time.
+
+use crate::types::BlockNumber;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+/// File touched by the run loop to signal it is alive, see
+/// [`IndexerBuilder::heartbeat_file`](crate::builder::IndexerBuilder::heartbeat_file).
+///
+/// [`Indexer::run`](crate::indexer::Indexer::run) calls [`on_block`](Self::on_block)
+/// after each processed block and [`on_interval`](Self::on_interval) every
+/// [`interval`](Self::interval), from the loop that runs the handlers, so the
+/// file stops being touched when that loop stalls. A Kubernetes liveness probe
+/// can then restart the pod once the file is older than a threshold:
+///
+/// ```yaml
+/// livenessProbe:
+///   exec:
+///     command: ["sh", "-c", "test $(( $(date +%s) - $(stat -c %Y /tmp/alive) )) -lt 120"]
+/// ```
+#[derive(Clone, Debug)]
+pub struct HeartbeatFile {
+    path: PathBuf,
+    interval: Duration,
+    requires_progress: bool,
+    /// Highest block processed.
+    last_block: Option<BlockNumber>,
+    /// Highest block processed at the last touch.
+    touched_block: Option<BlockNumber>,
+}
+
+impl HeartbeatFile {
+    /// Touch `path` every `interval` and after each processed block.
+    pub fn new(path: impl Into<PathBuf>, interval: Duration) -> Self {
+        Self {
+            path: path.into(),
+            interval,
+            requires_progress: false,
+            last_block: None,
+            touched_block: None,
+        }
+    }
+
+    /// Only touch the file once the processed block number has advanced since
+    /// the last touch, so it also goes stale while the indexer waits on a stuck
+    /// node. Pick a probe threshold well above the block time.
+    pub fn requires_progress(mut self, requires_progress: bool) -> Self {
+        self.requires_progress = requires_progress;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Block `number` went through the handlers.
+    pub fn on_block(&mut self, number: BlockNumber) {
+        self.last_block = self.last_block.max(Some(number));
+        self.beat();
+    }
+
+    /// The interval elapsed. `healthy` is false while a circuit breaker is open,
+    /// which skips the touch.
+    pub fn on_interval(&mut self, healthy: bool) {
+        if healthy {
+            self.beat();
+        }
+    }
+
+    /// Create the file or update its modification time, now.
+    pub fn touch(&self) -> std::io::Result<()> {
+        File::options()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .set_modified(SystemTime::now())
+    }
+
+    fn beat(&mut self) {
+        if self.requires_progress && self.last_block <= self.touched_block {
+            return;
+        }
+        match self.touch() {
+            Ok(()) => self.touched_block = self.last_block,
+            Err(e) => {
+                warn!(target: "indexer", path = %self.path.display(), "failed to touch heartbeat file: {e}")
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Hedged requests across a primary and a secondary node.
+//!
+//! A hedged call is sent to the primary first. If it has not answered after
+//! `hedge_after`, the same call is also sent to the secondary and the first
+//! successful answer wins; the other request is dropped, which cancels it. Only
+//! read-only calls should be hedged, since both endpoints may end up executing them.
+
+use crate::error::IndexerError;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+/// Counters describing how often hedging kicked in and paid off.
+#[derive(Debug, Default)]
+pub struct HedgeCounters {
+    calls: AtomicU64,
+    hedges: AtomicU64,
+    hedge_wins: AtomicU64,
+}
+
+impl HedgeCounters {
+    /// Calls made through the hedged client.
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Calls that were also sent to the secondary endpoint.
+    pub fn hedges(&self) -> u64 {
+        self.hedges.load(Ordering::Relaxed)
+    }
+
+    /// Hedged calls answered by the secondary endpoint.
+    pub fn hedge_wins(&self) -> u64 {
+        self.hedge_wins.load(Ordering::Relaxed)
+    }
+}
+
+/// Client wrapper that hedges calls from a primary endpoint to a secondary one.
+///
+/// `T` is the per-endpoint client (e.g. `LegacyRpcMethods<C>`) and is cloned into
+/// every call.
+pub struct HedgedRpc<T> {
+    primary: T,
+    secondary: Option<(T, Duration)>,
+    counters: Arc<HedgeCounters>,
+}
+
+impl<T: Clone> HedgedRpc<T> {
+    /// Send every call to `primary` only.
+    pub fn new(primary: T) -> Self {
+        Self {
+            primary,
+            secondary: None,
+            counters: Arc::default(),
+        }
+    }
+
+    /// Also send a call to `secondary` when `primary` has not answered after `hedge_after`.
+    pub fn with_secondary(mut self, secondary: T, hedge_after: Duration) -> Self {
+        self.secondary = Some((secondary, hedge_after));
+        self
+    }
+
+    /// Record into shared counters instead of private ones.
+    pub fn with_counters(mut self, counters: Arc<HedgeCounters>) -> Self {
+        self.counters = counters;
+        self
+    }
+
+    /// Client for the primary endpoint, for calls that must not be hedged.
+    pub fn primary(&self) -> &T {
+        &self.primary
+    }
+
+    /// How often calls were hedged and won by the secondary.
+    pub fn counters(&self) -> &HedgeCounters {
+        &self.counters
+    }
+
+    /// Run `op` against the primary, hedging to the secondary if it is slow.
+    ///
+    /// The first successful answer is returned. If one endpoint fails the other is
+    /// awaited; if both fail the primary's error is returned.
+    pub async fn call<F, Fut, R>(&self, op: F) -> Result<R, IndexerError>
+    where
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = Result<R, IndexerError>>,
+    {
+        self.counters.calls.fetch_add(1, Ordering::Relaxed);
+        let primary = op(self.primary.clone());
+        let Some((secondary, hedge_after)) = &self.secondary else {
+            return primary.await;
+        };
+
+        tokio::pin!(primary);
+        tokio::select! {
+            res = &mut primary => return res,
+            _ = tokio::time::sleep(*hedge_after) => {}
+        }
+
+        debug!(target: "indexer", "no answer after {hedge_after:?}, hedging to secondary node");
+        self.counters.hedges.fetch_add(1, Ordering::Relaxed);
+        let hedge = op(secondary.clone());
+        tokio::pin!(hedge);
+        tokio::select! {
+            res = &mut primary => match res {
+                Ok(value) => Ok(value),
+                Err(primary_err) => match hedge.await {
+                    Ok(value) => {
+                        self.counters.hedge_wins.fetch_add(1, Ordering::Relaxed);
+                        Ok(value)
+                    }
+                    Err(_) => Err(primary_err),
+                },
+            },
+            res = &mut hedge => match res {
+                Ok(value) => {
+                    self.counters.hedge_wins.fetch_add(1, Ordering::Relaxed);
+                    Ok(value)
+                }
+                Err(_) => primary.await,
+            },
+        }
+    }
+}
@@ -0,0 +1,363 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! HTTP status endpoints, with the `http-api` feature.
+//!
+//! [`IndexerBuilder::serve_status`](crate::builder::IndexerBuilder::serve_status)
+//! starts a [`StatusServer`] answering:
+//!
+//! - `GET /status`: the [`HealthStatus`] as JSON, with a 503 once stale
+//! - `GET /checkpoint`: the last stored checkpoint, 404 before there is one
+//! - `GET /metrics`: the Prometheus metrics, with the `metrics` feature and
+//!   metrics enabled on the builder
+//! - `/ws`: a WebSocket feed of the indexed events, see [`WsFilter`]
+//!
+//! The endpoints read shared counters and the checkpoint last stored by the
+//! indexer rather than calling the node or the store, so they answer while the
+//! indexer catches up or a breaker is open.
+
+use crate::broadcast::{EventBroadcast, LiveEvent};
+use crate::error::IndexerError;
+use crate::health::HealthStatus;
+#[cfg(feature = "metrics")]
+use crate::metrics::IndexerMetrics;
+use crate::queue::QueueGauge;
+use crate::retry::{BreakerState, BreakerStats, CircuitBreaker};
+use crate::storage::Checkpoint;
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// What the endpoints report, shared with the indexer.
+#[derive(Clone)]
+pub struct StatusSource {
+    checkpoint: watch::Receiver<Option<Checkpoint>>,
+    rpc_breaker: Arc<CircuitBreaker>,
+    storage_breaker: Arc<CircuitBreaker>,
+    queue: Arc<QueueGauge>,
+    stale_after: Option<Duration>,
+    events: Option<EventBroadcast>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<IndexerMetrics>>,
+}
+
+impl StatusSource {
+    /// Report the latest value of `checkpoint` and the current state of the
+    /// breakers and queue.
+    pub fn new(
+        checkpoint: watch::Receiver<Option<Checkpoint>>,
+        rpc_breaker: Arc<CircuitBreaker>,
+        storage_breaker: Arc<CircuitBreaker>,
+        queue: Arc<QueueGauge>,
+    ) -> Self {
+        Self {
+            checkpoint,
+            rpc_breaker,
+            storage_breaker,
+            queue,
+            stale_after: None,
+            events: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// Report stale once the checkpoint is older than `threshold`.
+    pub fn stale_after(mut self, threshold: Option<Duration>) -> Self {
+        self.stale_after = threshold;
+        self
+    }
+
+    /// Stream the events of `events` at `/ws`, which is a 404 otherwise.
+    pub fn events(mut self, events: EventBroadcast) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Serve `metrics` at `GET /metrics`.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, metrics: Option<Arc<IndexerMetrics>>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    fn health(&self) -> HealthStatus {
+        HealthStatus::new(
+            self.checkpoint.borrow().clone(),
+            self.stale_after,
+            self.rpc_breaker.stats(),
+            self.storage_breaker.stats(),
+            &self.queue,
+        )
+    }
+}
+
+/// Running status server. Dropping it stops accepting connections and lets
+/// requests in flight finish.
+pub struct StatusServer {
+    local_addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl StatusServer {
+    /// Listen on `addr` and answer from `source`.
+    pub async fn bind(addr: SocketAddr, source: StatusSource) -> Result<Self, IndexerError> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let router = Router::new()
+            .route("/status", get(status))
+            .route("/checkpoint", get(checkpoint))
+            .route("/ws", get(ws));
+        #[cfg(feature = "metrics")]
+        let router = router.route("/metrics", get(metrics));
+        let router = router.with_state(source);
+        let (shutdown, stopped) = oneshot::channel();
+        info!(target: "indexer", %local_addr, "serving status endpoints");
+        let task = tokio::spawn(async move {
+            let result = axum::serve(listener, router)
+                .with_graceful_shutdown(async {
+                    let _ = stopped.await;
+                })
+                .await;
+            if let Err(e) = result {
+                warn!(target: "indexer", "status server stopped: {e}");
+            }
+        });
+        Ok(Self {
+            local_addr,
+            shutdown: Some(shutdown),
+            task,
+        })
+    }
+
+    /// Address the server listens on, useful when bound to port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting connections and wait for requests in flight to finish.
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        let _ = (&mut self.task).await;
+    }
+}
+
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+async fn status(State(source): State<StatusSource>) -> Response {
+    let status = source.health();
+    let code = if status.stale {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (code, Json(status_json(&status))).into_response()
+}
+
+async fn checkpoint(State(source): State<StatusSource>) -> Response {
+    match source.checkpoint.borrow().as_ref() {
+        Some(checkpoint) => Json(json!({
+            "block": checkpoint.block,
+            "updated_at": checkpoint.updated_at.map(timestamp),
+        }))
+        .into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "no checkpoint stored yet" })),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(feature = "metrics")]
+async fn metrics(State(source): State<StatusSource>) -> Response {
+    let Some(metrics) = &source.metrics else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match metrics.render() {
+        Ok(body) => (
+            [(
+                axum::http::header::CONTENT_TYPE,
+                "text/plain; version=0.0.4; charset=utf-8",
+            )],
+            body,
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Filter sent by a `/ws` client as a text message, e.g.
+/// `{"pallet": "Balances", "event": "Transfer"}`. Every field is optional and
+/// an empty filter passes every event.
+///
+/// The server answers `{"subscribed": <filter>}` and then sends each matching
+/// event as a JSON [`LiveEvent`], or `{"error": "..."}` for a message it cannot
+/// parse. A new filter replaces the previous one; nothing is sent before the
+/// first. A client that falls more than the broadcast capacity behind is
+/// disconnected with a policy close frame, and the events it missed are
+/// counted by [`EventBroadcast::dropped`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WsFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pallet: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub netuid: Option<u16>,
+}
+
+impl WsFilter {
+    pub fn matches(&self, event: &LiveEvent) -> bool {
+        self.pallet
+            .as_ref()
+            .is_none_or(|pallet| *pallet == event.pallet)
+            && self
+                .event
+                .as_ref()
+                .is_none_or(|name| *name == event.variant)
+            && self
+                .netuid
+                .is_none_or(|netuid| event.netuid == Some(netuid))
+    }
+}
+
+async fn ws(upgrade: WebSocketUpgrade, State(source): State<StatusSource>) -> Response {
+    match source.events {
+        Some(events) => upgrade.on_upgrade(move |socket| feed(socket, events)),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Serve one `/ws` client until it disconnects or falls behind.
+async fn feed(mut socket: WebSocket, events: EventBroadcast) {
+    let mut receiver = events.subscribe();
+    let mut filter: Option<WsFilter> = None;
+    loop {
+        let reply = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<WsFilter>(&text) {
+                    Ok(new) => {
+                        let reply = json!({ "subscribed": new });
+                        filter = Some(new);
+                        reply
+                    }
+                    Err(e) => json!({ "error": format!("invalid filter: {e}") }),
+                },
+                Some(Ok(Message::Close(_))) | None => return,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    debug!(target: "indexer", "WebSocket client failed: {e}");
+                    return;
+                }
+            },
+            event = receiver.recv() => match event {
+                Ok(event) if filter.as_ref().is_some_and(|filter| filter.matches(&event)) => {
+                    match serde_json::to_value(&*event) {
+                        Ok(event) => event,
+                        Err(_) => continue,
+                    }
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    events.record_dropped(skipped);
+                    warn!(target: "indexer", skipped, "disconnecting slow WebSocket client");
+                    let close = CloseFrame {
+                        code: close_code::POLICY,
+                        reason: "client fell behind".into(),
+                    };
+                    let _ = socket.send(Message::Close(Some(close))).await;
+                    return;
+                }
+                Err(RecvError::Closed) => return,
+            },
+        };
+        if socket
+            .send(Message::Text(reply.to_string().into()))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn status_json(status: &HealthStatus) -> Value {
+    json!({
+        "checkpoint": status.checkpoint.as_ref().map(|checkpoint| checkpoint.block),
+        "checkpoint_updated_at": status
+            .checkpoint
+            .as_ref()
+            .and_then(|checkpoint| checkpoint.updated_at)
+            .map(timestamp),
+        "staleness": status.staleness.map(duration),
+        "stale": status.stale,
+        "rpc_breaker": breaker_json(&status.rpc_breaker),
+        "storage_breaker": breaker_json(&status.storage_breaker),
+        "queue_depth": status.queue_depth,
+        "queue_capacity": status.queue_capacity,
+    })
+}
+
+fn breaker_json(stats: &BreakerStats) -> Value {
+    let state = match stats.state {
+        BreakerState::Closed => "closed",
+        BreakerState::Open { .. } => "open",
+        BreakerState::HalfOpen => "half_open",
+    };
+    json!({
+        "state": state,
+        "opens": stats.opens,
+        "failures": stats.failures,
+        "successes": stats.successes,
+        "consecutive_failures": stats.consecutive_failures,
+        "last_opened_at": stats.last_opened_at.map(timestamp),
+        "time_open": duration(stats.time_open),
+    })
+}
+
+fn timestamp(time: SystemTime) -> String {
+    humantime::format_rfc3339_millis(time).to_string()
+}
+
+/// `duration` in the `"1m 30s 250ms"` form used for durations in config files.
+fn duration(duration: Duration) -> String {
+    let millis = Duration::from_millis(duration.as_millis() as u64);
+    humantime::format_duration(millis).to_string()
+}
@@ -14,33 +14,103 @@
  * limitations under the License.
  */
 
-use crate::config::IndexerConfig;
+use crate::author::{self, AuthorResolver};
+use crate::config::{validate_range, FinalityMode, IndexerConfig};
 use crate::error::IndexerError;
-use crate::handler::{Context, Handler};
-use crate::retry::{retry_with_backoff, CircuitBreaker, RetryConfig};
-use crate::storage::CheckpointStore;
+use crate::extrinsics::{BlockExtrinsics, ChainExtrinsic};
+#[cfg(feature = "grpc")]
+use crate::grpc::GrpcServer;
+use crate::handler::{catch_panic, Context, Handler};
+use crate::health::HealthStatus;
+use crate::heartbeat::HeartbeatFile;
+use crate::hedge::{HedgeCounters, HedgedRpc};
+#[cfg(feature = "http-api")]
+use crate::http_api::{StatusServer, StatusSource};
+#[cfg(feature = "metrics")]
+use crate::metrics::{IndexerMetrics, MetricsServer};
+use crate::queue::{self, QueueGauge, QueueSender};
+#[cfg(any(feature = "metrics", feature = "statsd"))]
+use crate::recorder::watch_breaker;
+use crate::recorder::{MetricsRecorder, NoopRecorder};
+use crate::redact::redact_url;
+use crate::reorg::{Reorg, ReorgTracker};
+use crate::replay::{Recorded, RecordedBlock, RecordingWriter, ReplaySource};
+use crate::reporter::{
+    report_breaker_opens, ErrorContext, ErrorReporter, GiveUpReporter, LogReporter,
+    ThrottledReporter, DEFAULT_REPORT_INTERVAL,
+};
+use crate::retry::{
+    retry_span, retry_with_backoff_observed, with_timeout_and_backoff_observed, BreakerState,
+    CircuitBreaker, RetryConfig, RetryCounters, RetryObserver,
+};
+use crate::rpc;
+use crate::run::{RunLimits, RunSummary, StopReason};
+use crate::settings::{RuntimeSettings, SettingsHandle};
+use crate::source::{BlockSource, NodeSource, SourceBlock};
+#[cfg(feature = "http-api")]
+use crate::storage::Checkpoint;
+use crate::storage::{CheckpointStore, RetryingStore};
 use crate::types::{BlockNumber, ChainEvent};
+use futures::StreamExt;
+use parity_scale_codec::{Decode, Encode};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use subxt::backend::BackendExt;
+use subxt::backend::rpc::RpcClient;
 use subxt::config::HashFor;
 use subxt::config::Header;
 use subxt::events::Events;
-use subxt::{
-    backend::{legacy::LegacyRpcMethods, rpc::RpcClient},
-    client::RuntimeVersion,
-    Config, OnlineClient,
-};
-use tracing::warn;
+use subxt::utils::AccountId32;
+use subxt::{backend::legacy::LegacyRpcMethods, Config, OnlineClient};
+use tracing::{debug, error, field, info, info_span, warn, Instrument, Span};
 
 pub struct Indexer<C: Config> {
-    retry_config: RetryConfig,
-    circuit_breaker: CircuitBreaker,
+    rpc_retry: RetryConfig,
+    rpc_breaker: Arc<CircuitBreaker>,
+    rpc_retries: RetryCounters,
+    hedge_counters: Arc<HedgeCounters>,
     client: OnlineClient<C>,
     handlers: Vec<Arc<dyn Handler<C>>>,
-    store: Box<dyn CheckpointStore>,
+    /// Checkpoint store wrapped with the storage retry policy and breaker.
+    store: RetryingStore<Box<dyn CheckpointStore>>,
+    /// Blocks fetched ahead of the handlers.
+    queue: Arc<QueueGauge>,
     config: IndexerConfig,
-    pub(crate) max_blocks_per_minute: Option<u32>,
+    /// Settings re-read before every block.
+    settings: SettingsHandle,
+    /// Handler failures in the current run, counted against the error budget.
+    handler_errors: AtomicU64,
+    /// Validator set cache for [`IndexerConfig::resolve_block_author`].
+    authors: AuthorResolver,
+    /// Throttled reporter of handler failures and retry give-ups.
+    error_reporter: Arc<dyn ErrorReporter>,
+    /// Metrics backend chosen on the builder.
+    recorder: Arc<dyn MetricsRecorder>,
+    pub(crate) stale_after: Option<Duration>,
+    /// Liveness file touched by the dispatch loop.
+    pub(crate) heartbeat: Option<HeartbeatFile>,
+    /// Recording every fetched block is written to.
+    recording: Option<RecordingWriter>,
+    /// Recording replayed in place of the node.
+    pub(crate) replay: Option<Arc<ReplaySource>>,
+    /// Source used instead of connecting to the node for each run.
+    pub(crate) source: Option<Arc<dyn BlockSource<C>>>,
+    /// Client for the node RPC calls of each run, instead of connecting to the node URL.
+    pub(crate) rpc_client: Option<RpcClient>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<IndexerMetrics>>,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics_server: Option<MetricsServer>,
+    #[cfg(feature = "grpc")]
+    pub(crate) grpc_server: Option<GrpcServer>,
+    /// Last stored checkpoint, for the status server.
+    #[cfg(feature = "http-api")]
+    checkpoint: tokio::sync::watch::Sender<Option<Checkpoint>>,
+    #[cfg(feature = "http-api")]
+    status_server: Option<StatusServer>,
 }
 
 impl<C> Indexer<C>
@@ -52,17 +122,152 @@ where
         store: Box<dyn CheckpointStore>,
         config: IndexerConfig,
     ) -> Result<Self, IndexerError> {
+        let mut store = RetryingStore::new(
+            store,
+            config.storage_retry.clone(),
+            CircuitBreaker::from_config(&config.storage_circuit_breaker),
+        );
+        let error_reporter: Arc<dyn ErrorReporter> = Arc::new(ThrottledReporter::new(
+            Arc::new(LogReporter),
+            DEFAULT_REPORT_INTERVAL,
+        ));
+        store.set_observer(Arc::new(GiveUpReporter::new(
+            error_reporter.clone(),
+            "storage",
+        )));
+        let rpc_breaker = Arc::new(CircuitBreaker::from_config(&config.circuit_breaker));
+        log_breaker_transitions(&rpc_breaker, config.node_url());
+        log_breaker_transitions(store.circuit_breaker(), config.node_url());
         Ok(Self {
-            retry_config: RetryConfig::default(),
-            circuit_breaker: CircuitBreaker::new(3, std::time::Duration::from_secs(60)),
+            rpc_retry: config.retry.clone(),
+            rpc_breaker,
+            rpc_retries: RetryCounters::default(),
+            hedge_counters: Arc::default(),
             client,
             handlers: Vec::new(),
             store,
+            queue: Arc::new(QueueGauge::new(config.queue_depth)),
+            settings: SettingsHandle::new(RuntimeSettings::from(&config)),
+            handler_errors: AtomicU64::new(0),
+            authors: AuthorResolver::new(),
+            error_reporter,
+            recorder: Arc::new(NoopRecorder),
             config,
-            max_blocks_per_minute: None,
+            stale_after: None,
+            heartbeat: None,
+            recording: None,
+            replay: None,
+            source: None,
+            rpc_client: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+            #[cfg(feature = "metrics")]
+            metrics_server: None,
+            #[cfg(feature = "grpc")]
+            grpc_server: None,
+            #[cfg(feature = "http-api")]
+            checkpoint: tokio::sync::watch::Sender::new(None),
+            #[cfg(feature = "http-api")]
+            status_server: None,
         })
     }
 
+    /// Report handler failures, retry give-ups and the opening of either breaker
+    /// to `reporter` instead of the log, at most once per handler every
+    /// `interval` blocks.
+    pub(crate) fn set_error_reporter(&mut self, reporter: Arc<dyn ErrorReporter>, interval: u64) {
+        let reporter: Arc<dyn ErrorReporter> = Arc::new(ThrottledReporter::new(reporter, interval));
+        self.store
+            .set_observer(Arc::new(GiveUpReporter::new(reporter.clone(), "storage")));
+        report_breaker_opens(&self.rpc_breaker, reporter.clone());
+        report_breaker_opens(self.store.circuit_breaker(), reporter.clone());
+        self.error_reporter = reporter;
+    }
+
+    /// Report metrics to `recorder` from now on, including the state of both
+    /// breakers.
+    #[cfg(any(feature = "metrics", feature = "statsd"))]
+    pub(crate) fn set_recorder(&mut self, recorder: Arc<dyn MetricsRecorder>) {
+        watch_breaker(recorder.clone(), &self.rpc_breaker);
+        watch_breaker(recorder.clone(), self.store.circuit_breaker());
+        self.recorder = recorder;
+    }
+
+    /// Report to `metrics` from now on, including the state of both breakers.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn set_metrics(&mut self, metrics: Arc<IndexerMetrics>) {
+        self.set_recorder(metrics.clone());
+        self.metrics = Some(metrics);
+    }
+
+    /// Prometheus metrics, if enabled on the builder.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Option<&Arc<IndexerMetrics>> {
+        self.metrics.as_ref()
+    }
+
+    /// Address of the `/metrics` endpoint, if one was started with
+    /// [`IndexerBuilder::serve_metrics`](crate::builder::IndexerBuilder::serve_metrics).
+    #[cfg(feature = "metrics")]
+    pub fn metrics_addr(&self) -> Option<std::net::SocketAddr> {
+        self.metrics_server.as_ref().map(MetricsServer::local_addr)
+    }
+
+    /// Address of the gRPC event stream, if one was started with
+    /// [`IndexerBuilder::serve_grpc`](crate::builder::IndexerBuilder::serve_grpc).
+    #[cfg(feature = "grpc")]
+    pub fn grpc_addr(&self) -> Option<std::net::SocketAddr> {
+        self.grpc_server.as_ref().map(GrpcServer::local_addr)
+    }
+
+    /// Start the status server on `addr`, seeded with the stored checkpoint,
+    /// with `/ws` streaming `events`.
+    #[cfg(feature = "http-api")]
+    pub(crate) async fn serve_status(
+        &mut self,
+        addr: std::net::SocketAddr,
+        events: crate::broadcast::EventBroadcast,
+    ) -> Result<(), IndexerError> {
+        self.checkpoint
+            .send_replace(self.store.load_checkpoint_info().await?);
+        let source = StatusSource::new(
+            self.checkpoint.subscribe(),
+            self.rpc_breaker.clone(),
+            self.store.shared_circuit_breaker(),
+            self.queue.clone(),
+        )
+        .stale_after(self.stale_after)
+        .events(events);
+        #[cfg(feature = "metrics")]
+        let source = source.metrics(self.metrics.clone());
+        self.status_server = Some(StatusServer::bind(addr, source).await?);
+        Ok(())
+    }
+
+    /// Address of the status endpoints, if started with
+    /// [`IndexerBuilder::serve_status`](crate::builder::IndexerBuilder::serve_status).
+    #[cfg(feature = "http-api")]
+    pub fn status_addr(&self) -> Option<std::net::SocketAddr> {
+        self.status_server.as_ref().map(StatusServer::local_addr)
+    }
+
+    /// Record every block fetched from now on to the file at `path`, replacing
+    /// it, for replays with
+    /// [`IndexerBuilder::replay_from`](crate::builder::IndexerBuilder::replay_from).
+    /// Each block is stored with its header, its raw event bytes and the
+    /// metadata of its runtime.
+    pub fn record_to(&mut self, path: impl Into<PathBuf>) -> Result<(), IndexerError> {
+        if self.replay.is_some() {
+            return Err(IndexerError::invalid_config(
+                "record_to",
+                "cannot record while replaying a recording",
+            ));
+        }
+        let genesis_hash = self.client.genesis_hash().encode();
+        self.recording = Some(RecordingWriter::create(path, &genesis_hash)?);
+        Ok(())
+    }
+
     pub fn add_handler(&mut self, handler: impl Handler<C> + 'static) -> Result<(), IndexerError> {
         self.handlers.push(Arc::new(handler));
         Ok(())
@@ -81,24 +286,117 @@ where
         Ok(())
     }
 
-    async fn with_circuit_breaker<F, Fut, T>(&self, op: F) -> Result<T, IndexerError>
+    /// Handle for changing the throttle, checkpoint interval and handler error budget
+    /// while the indexer runs. Changes apply from the next block.
+    pub fn settings_handle(&self) -> SettingsHandle {
+        self.settings.clone()
+    }
+
+    /// Time since the checkpoint was last advanced, if the storage backend records it.
+    pub async fn checkpoint_staleness(&self) -> Result<Option<Duration>, IndexerError> {
+        Ok(self
+            .store
+            .load_checkpoint_info()
+            .await?
+            .and_then(|checkpoint| checkpoint.staleness()))
+    }
+
+    /// Breaker guarding node RPC calls.
+    pub fn rpc_breaker(&self) -> &CircuitBreaker {
+        &self.rpc_breaker
+    }
+
+    /// Retry counters for node RPC calls.
+    pub fn rpc_retries(&self) -> &RetryCounters {
+        &self.rpc_retries
+    }
+
+    /// How often read-only RPC calls were hedged to the secondary node, and won.
+    pub fn hedge_counters(&self) -> &HedgeCounters {
+        &self.hedge_counters
+    }
+
+    /// Breaker guarding checkpoint loads and writes.
+    pub fn storage_breaker(&self) -> &CircuitBreaker {
+        self.store.circuit_breaker()
+    }
+
+    /// Blocks fetched but not yet handled; see [`IndexerBuilder::queue_depth`](crate::builder::IndexerBuilder::queue_depth).
+    pub fn queue(&self) -> &QueueGauge {
+        &self.queue
+    }
+
+    /// Current checkpoint and staleness of the indexer.
+    pub async fn health(&self) -> Result<HealthStatus, IndexerError> {
+        let checkpoint = self.store.load_checkpoint_info().await?;
+        Ok(HealthStatus::new(
+            checkpoint,
+            self.stale_after,
+            self.rpc_breaker.stats(),
+            self.storage_breaker().stats(),
+            &self.queue,
+        ))
+    }
+
+    /// Run a node RPC call under the RPC retry policy and breaker, inside a
+    /// [`retry_span`] naming the operation and block. Final failures are logged at
+    /// `error` with their source chain.
+    async fn with_circuit_breaker<F, Fut, T>(
+        &self,
+        operation: &'static str,
+        block: Option<BlockNumber>,
+        op: F,
+    ) -> Result<T, IndexerError>
     where
         F: FnMut() -> Fut,
         Fut: std::future::Future<Output = Result<T, IndexerError>>,
     {
-        if self.circuit_breaker.is_open() {
-            return Err(IndexerError::ConnectionFailed {
-                url: self.config.node_url.clone(),
-                source: Box::new(subxt::Error::Other("circuit open".into())),
+        let span = retry_span(operation, block);
+        let res = self.call_rpc(op).instrument(span.clone()).await;
+        if let Err(e) = &res {
+            span.in_scope(|| {
+                error!(target: "indexer", error = %e, sources = ?e.sources(), "{operation} failed")
             });
         }
-        let inner = op;
-        let res = retry_with_backoff(inner, &self.retry_config, &self.circuit_breaker).await;
+        res
+    }
+
+    async fn call_rpc<F, Fut, T>(&self, op: F) -> Result<T, IndexerError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, IndexerError>>,
+    {
+        if self.rpc_breaker.is_open() {
+            return Err(self.rpc_breaker.open_error());
+        }
+        let observer = RpcRetryObserver {
+            counters: &self.rpc_retries,
+            reporter: self.error_reporter.as_ref(),
+            domain: self.rpc_breaker.domain(),
+            recorder: self.recorder.as_ref(),
+        };
+        let res = match self.config.rpc_timeout {
+            Some(timeout) => {
+                with_timeout_and_backoff_observed(
+                    op,
+                    timeout,
+                    &self.rpc_retry,
+                    &self.rpc_breaker,
+                    &observer,
+                )
+                .await
+            }
+            None => {
+                retry_with_backoff_observed(op, &self.rpc_retry, &self.rpc_breaker, &observer).await
+            }
+        };
         match &res {
-            Ok(_) => self.circuit_breaker.record_success(),
+            Ok(_) => self.rpc_breaker.record_success(),
+            // Rejected by the breaker itself, not a failure of the node.
+            Err(IndexerError::CircuitOpen { .. }) => {}
             Err(e) => {
-                if crate::retry::is_retryable_error(e) {
-                    self.circuit_breaker.record_failure();
+                if self.rpc_retry.is_retryable(e) {
+                    self.rpc_breaker.record_failure();
                 }
             }
         }
@@ -107,218 +405,1054 @@ where
 
     async fn update_metadata(
         &self,
-        rpc: &LegacyRpcMethods<C>,
+        source: &dyn BlockSource<C>,
+        number: BlockNumber,
         hash: HashFor<C>,
     ) -> Result<(), IndexerError> {
         let version = self
-            .with_circuit_breaker(|| async {
-                rpc.state_get_runtime_version(Some(hash))
-                    .await
-                    .map_err(|e| IndexerError::MetadataUpdateFailed {
-                        source: Box::new(subxt::Error::from(e)),
-                    })
+            .with_circuit_breaker("state_get_runtime_version", Some(number), || {
+                source.runtime_version(hash)
             })
             .await?;
 
         let current = self.client.runtime_version();
         if version.spec_version != current.spec_version {
-            use subxt::metadata::types::SUPPORTED_METADATA_VERSIONS;
-            let backend = self.client.backend();
-            let mut metadata = None;
-            for v in SUPPORTED_METADATA_VERSIONS {
-                match backend.metadata_at_version(v, hash).await {
-                    Ok(m) => {
-                        metadata = Some(m);
+            let span = info_span!(
+                target: "indexer",
+                "update_metadata",
+                block = number,
+                from_spec_version = current.spec_version,
+                to_spec_version = version.spec_version
+            );
+            let metadata = self
+                .with_circuit_breaker("metadata", Some(number), || source.metadata(hash))
+                .instrument(span.clone())
+                .await?;
+            self.client.set_metadata(metadata);
+            self.client.set_runtime_version(version);
+            span.in_scope(|| info!(target: "indexer", "metadata updated"));
+        }
+        Ok(())
+    }
+
+    /// Write the checkpoint inside a `checkpoint` span, with an event once stored.
+    async fn store_checkpoint(&self, number: BlockNumber) -> Result<(), IndexerError> {
+        let span = info_span!(target: "indexer", "checkpoint", block = number);
+        self.store
+            .store_checkpoint(number)
+            .instrument(span.clone())
+            .await?;
+        span.in_scope(|| debug!(target: "indexer", "checkpoint stored"));
+        #[cfg(feature = "http-api")]
+        self.checkpoint.send_replace(Some(Checkpoint {
+            block: number,
+            updated_at: Some(std::time::SystemTime::now()),
+        }));
+        Ok(())
+    }
+
+    /// Store a checkpoint at `number`, held back to the last block every handler has
+    /// made durable, see [`Handler::on_checkpoint`]. Returns whether `number` itself
+    /// was stored.
+    async fn checkpoint(&self, number: BlockNumber) -> Result<bool, IndexerError> {
+        let mut durable = Some(number);
+        for handler in &self.handlers {
+            let hook = handler.on_checkpoint(number);
+            durable = durable.min(self.guard(handler.as_ref(), number, hook).await?);
+        }
+        if let Some(block) = durable {
+            self.store_checkpoint(block).await?;
+        }
+        Ok(durable == Some(number))
+    }
+
+    /// Index blocks until `end_block`, `end_after` or `end_at_time` is reached, or the
+    /// node closes the block subscription.
+    ///
+    /// A time limit stops the run after the block being handled; blocks already fetched
+    /// but not yet handled are left for the next run.
+    pub async fn run(&mut self) -> Result<RunSummary, IndexerError> {
+        let started = tokio::time::Instant::now();
+        self.handler_errors.store(0, Ordering::SeqCst);
+        let limits = RunLimits::new(
+            self.config.end_block,
+            self.config.end_after,
+            self.config.end_at_time,
+        );
+        let feed = match &self.replay {
+            Some(replay) => Feed::Replay(replay.clone()),
+            None => Feed::Source(self.block_source().await?),
+        };
+
+        let (tx, mut rx) = queue::bounded(self.queue.clone());
+        let this = &*self;
+        // A fetch error is passed on as a value, so the dispatch stage still drains
+        // the queue, which closes with `tx`, and shuts the handlers down.
+        let fetch = async {
+            let fetched = match &feed {
+                Feed::Source(source) => this.fetch_blocks(source.as_ref(), tx, &limits).await,
+                Feed::Replay(replay) => this.replay_blocks(replay, tx, &limits).await,
+            };
+            Ok::<_, IndexerError>(fetched)
+        };
+        let dispatch = async move {
+            let mut unsaved = None;
+            let mut since_checkpoint = 0;
+            let mut blocks_processed: u64 = 0;
+            let mut last_block = None;
+            let mut stopped = None;
+            // Touched from this loop rather than a task of its own, so the file
+            // goes stale when the handlers stop being fed.
+            let mut heartbeat = this.heartbeat.clone().map(|heartbeat| {
+                let mut ticker = tokio::time::interval(heartbeat.interval());
+                ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                (heartbeat, ticker)
+            });
+            let dispatched = async {
+                loop {
+                    let item = match &mut heartbeat {
+                        Some((heartbeat, ticker)) => tokio::select! {
+                            item = rx.recv() => item,
+                            _ = ticker.tick() => {
+                                heartbeat.on_interval(
+                                    !this.rpc_breaker.is_open()
+                                        && !this.store.circuit_breaker().is_open(),
+                                );
+                                continue;
+                            }
+                        },
+                        None => rx.recv().await,
+                    };
+                    let Some(item) = item else {
+                        break;
+                    };
+                    let number = match item {
+                        Fetched::Block(block) => {
+                            let number = block.number;
+                            this.dispatch_block(block).await?;
+                            blocks_processed += 1;
+                            last_block = Some(number);
+                            if let Some((heartbeat, _)) = &mut heartbeat {
+                                heartbeat.on_block(number);
+                            }
+                            number
+                        }
+                        Fetched::Reorg(reorg) => {
+                            // Resume before the unwound blocks if stopped now, even
+                            // when a handler fails to unwind.
+                            let number = reorg.from.saturating_sub(1);
+                            unsaved = Some(number);
+                            let mut error = None;
+                            for handler in &this.handlers {
+                                if let Err(e) = handler.on_reorg(reorg.from, reorg.to).await {
+                                    error.get_or_insert(e);
+                                }
+                            }
+                            error.map_or(Ok(()), Err)?;
+                            number
+                        }
+                    };
+                    unsaved = Some(number);
+                    since_checkpoint += 1;
+                    if since_checkpoint >= this.settings.get().checkpoint_interval.max(1) {
+                        if this.checkpoint(number).await? {
+                            unsaved = None;
+                        }
+                        since_checkpoint = 0;
+                    }
+                    if limits.time_is_up() {
+                        stopped = Some(StopReason::TimeLimit);
                         break;
                     }
-                    Err(_) => continue,
                 }
+                Ok::<_, IndexerError>(())
             }
-            let metadata = match metadata {
-                Some(m) => m,
-                None => {
-                    self.with_circuit_breaker(|| async {
-                        backend.legacy_metadata(hash).await.map_err(|e| {
-                            IndexerError::MetadataUpdateFailed {
-                                source: Box::new(e),
-                            }
-                        })
-                    })
-                    .await?
+            .await;
+            // Closing the queue stops the fetch stage.
+            drop(rx);
+            // Every handler is shut down and the checkpoint written, on failure too,
+            // and the first error is returned afterwards.
+            let mut error = dispatched.err();
+            for handler in &this.handlers {
+                if let Err(e) = handler.on_shutdown().await {
+                    warn!(target: "indexer", handler = handler.name(), error = %e, "handler shutdown failed");
+                    error.get_or_insert(e);
                 }
-            };
-            self.client.set_metadata(metadata);
-            self.client.set_runtime_version(RuntimeVersion {
-                spec_version: version.spec_version,
-                transaction_version: version.transaction_version,
-            });
-        }
-        Ok(())
+            }
+            if let Some(number) = unsaved {
+                if let Err(e) = this.checkpoint(number).await {
+                    error.get_or_insert(e);
+                }
+            }
+            match error {
+                Some(e) => Err(e),
+                None => Ok((blocks_processed, last_block, stopped)),
+            }
+        };
+        let (fetched, (blocks_processed, last_block, dispatch_stopped)) =
+            tokio::try_join!(fetch, dispatch)?;
+        let fetch_stopped = fetched?;
+        let stop_reason = dispatch_stopped
+            .or(fetch_stopped)
+            .unwrap_or(StopReason::SubscriptionClosed);
+        info!(
+            target: "indexer",
+            blocks_processed,
+            ?last_block,
+            ?stop_reason,
+            "run finished"
+        );
+        Ok(RunSummary {
+            blocks_processed,
+            last_block,
+            elapsed: started.elapsed(),
+            stop_reason,
+        })
+    }
+
+    /// Run every handler over block `number` as [`run`](Self::run) does, to debug them
+    /// on one block. The block is fetched from the node, or the recording when
+    /// replaying, and no checkpoint is written.
+    ///
+    /// Returns each handler's name with the first error it returned for the block.
+    /// Fetching or decoding the block failing, or the handler error budget running
+    /// out, fails the call.
+    pub async fn process_block(
+        &self,
+        number: BlockNumber,
+    ) -> Result<Vec<(String, Result<(), IndexerError>)>, IndexerError> {
+        let block = match &self.replay {
+            Some(replay) => self.recorded_block(
+                replay,
+                |block| block.number == number,
+                || IndexerError::BlockNotFound { block: number },
+            )?,
+            None => {
+                let source = self.block_source().await?;
+                let hash = self.block_hash(source.as_ref(), number).await?;
+                self.fetch_block(source.as_ref(), number, hash).await?
+            }
+        };
+        self.process_one(block).await
+    }
+
+    /// Like [`process_block`](Self::process_block), for the block with hash `hash`,
+    /// which need not be on the canonical chain. An unknown hash fails with
+    /// [`IndexerError::BlockHashNotFound`].
+    pub async fn process_block_at(
+        &self,
+        hash: HashFor<C>,
+    ) -> Result<Vec<(String, Result<(), IndexerError>)>, IndexerError> {
+        let block = match &self.replay {
+            Some(replay) => {
+                let encoded = hash.encode();
+                self.recorded_block(
+                    replay,
+                    |block| block.hash == encoded,
+                    || IndexerError::BlockHashNotFound {
+                        hash: format!("{hash:?}"),
+                    },
+                )?
+            }
+            None => {
+                let source = self.block_source().await?;
+                let header = self
+                    .with_circuit_breaker("chain_get_header", None, || source.header(Some(hash)))
+                    .await?
+                    .ok_or_else(|| IndexerError::BlockHashNotFound {
+                        hash: format!("{hash:?}"),
+                    })?;
+                self.fetch_block(source.as_ref(), header.number().into(), hash)
+                    .await?
+            }
+        };
+        self.process_one(block).await
     }
 
-    pub async fn run(&mut self) -> Result<(), IndexerError> {
-        let rpc_client = self
-            .with_circuit_breaker(|| async {
-                RpcClient::from_insecure_url(&self.config.node_url)
-                    .await
-                    .map_err(|e| IndexerError::ConnectionFailed {
-                        url: self.config.node_url.clone(),
-                        source: Box::new(subxt::Error::from(e)),
-                    })
+    /// The recorded block matching `find`, or the error of `missing` when there is none.
+    fn recorded_block(
+        &self,
+        replay: &ReplaySource,
+        find: impl Fn(&RecordedBlock) -> bool,
+        missing: impl FnOnce() -> IndexerError,
+    ) -> Result<FetchedBlock<C>, IndexerError> {
+        // The last match is the block that stayed on the chain after reorgs.
+        let block = replay
+            .entries()
+            .iter()
+            .rev()
+            .find_map(|entry| match entry {
+                Recorded::Block(block) if find(block) => Some(block),
+                _ => None,
             })
+            .ok_or_else(missing)?;
+        self.replayed_block(replay, block)
+    }
+
+    async fn process_one(
+        &self,
+        block: FetchedBlock<C>,
+    ) -> Result<Vec<(String, Result<(), IndexerError>)>, IndexerError> {
+        let (_, failures) = self
+            .process_events(
+                block.number,
+                block.hash,
+                &block.events,
+                block.extrinsics,
+                block.author,
+                block.timestamp,
+            )
             .await?;
-        let rpc = LegacyRpcMethods::<C>::new(rpc_client);
+        Ok(self
+            .handlers
+            .iter()
+            .zip(failures)
+            .map(|(handler, failure)| (handler.name().to_string(), failure.map_or(Ok(()), Err)))
+            .collect())
+    }
 
-        let mut current_block = match self.config.start_block {
-            Some(n) => n,
-            None => self
-                .with_circuit_breaker(|| async { self.store.load_checkpoint().await })
+    /// The source set on the builder, or the node.
+    async fn block_source(&self) -> Result<Arc<dyn BlockSource<C>>, IndexerError> {
+        match &self.source {
+            Some(source) => Ok(source.clone()),
+            None => Ok(Arc::new(self.connect_node().await?)),
+        }
+    }
+
+    /// Connect to the primary node, unless given a client for it, and to the secondary
+    /// one when hedging.
+    async fn connect_node(&self) -> Result<NodeSource<C>, IndexerError> {
+        let rpc_client = match &self.rpc_client {
+            Some(rpc_client) => rpc_client.clone(),
+            None => {
+                self.with_circuit_breaker("connect", None, || {
+                    rpc::connect(
+                        self.config.node_url(),
+                        self.config.node_auth.as_ref(),
+                        self.config.tls_mode,
+                    )
+                })
                 .await?
-                .unwrap_or(0),
+            }
         };
-        let end_block = self.config.end_block;
+        let mut rpc = HedgedRpc::new(LegacyRpcMethods::<C>::new(rpc_client))
+            .with_counters(self.hedge_counters.clone());
+        if let (Some(url), Some(hedge_after)) =
+            (self.config.secondary_node_url(), self.config.hedge_after)
+        {
+            match rpc::connect(url, self.config.node_auth.as_ref(), self.config.tls_mode).await {
+                Ok(client) => {
+                    rpc = rpc.with_secondary(LegacyRpcMethods::new(client), hedge_after);
+                }
+                Err(e) => {
+                    let url = redact_url(url);
+                    warn!(target: "indexer", %url, "secondary node unavailable, hedging disabled: {e}")
+                }
+            }
+        }
+        Ok(NodeSource::new(self.client.clone(), rpc))
+    }
 
-        let finalized_hash = self
-            .with_circuit_breaker(|| async {
-                rpc.chain_get_finalized_head()
-                    .await
-                    .map_err(|e| IndexerError::from(subxt::Error::from(e)))
-            })
-            .await?;
-        let finalized_header = self
-            .with_circuit_breaker(|| async {
-                rpc.chain_get_header(Some(finalized_hash))
-                    .await
-                    .map_err(|e| IndexerError::from(subxt::Error::from(e)))
-            })
+    /// Fetch stage: resolve and fetch every block from the checkpoint onwards and
+    /// hand it to the dispatch stage. Waits whenever the queue is full.
+    ///
+    /// When following best blocks, reorgs of already fetched blocks are detected and
+    /// passed on before the blocks of the new chain.
+    ///
+    /// Returns why it stopped, or `None` if the dispatch stage stopped first.
+    async fn fetch_blocks(
+        &self,
+        source: &dyn BlockSource<C>,
+        tx: QueueSender<Fetched<C>>,
+        limits: &RunLimits,
+    ) -> Result<Option<StopReason>, IndexerError> {
+        // Without a hash, `header` returns the best block.
+        let head_hash = match self.config.finality {
+            FinalityMode::Finalized => Some(
+                self.with_circuit_breaker("chain_get_finalized_head", None, || {
+                    source.finalized_head()
+                })
+                .await?,
+            ),
+            FinalityMode::Best => None,
+        };
+        let head_header = self
+            .with_circuit_breaker("chain_get_header", None, || source.header(head_hash))
             .await?
             .ok_or(IndexerError::BlockNotFound { block: 0 })?;
-        let latest_number = finalized_header.number().into();
+        let latest_number = head_header.number().into();
+        self.recorder.observe_head(latest_number);
+        let checkpoint = self.store.load_checkpoint().await?;
+        validate_range(latest_number, checkpoint, &self.config)?;
+
+        let mut current_block = self
+            .config
+            .start_from
+            .resolve(&self.store, latest_number)
+            .await?;
+        info!(
+            target: "indexer",
+            start_from = ?self.config.start_from,
+            block = current_block,
+            "resolved starting block"
+        );
+
+        let mut reorgs = (self.config.finality == FinalityMode::Best)
+            .then(|| ReorgTracker::new(self.config.max_reorg_depth));
 
         while current_block <= latest_number {
-            if let Some(end) = end_block {
-                if current_block > end {
-                    return Ok(());
-                }
+            if let Some(reason) = limits.check(current_block) {
+                return Ok(Some(reason));
             }
-            let hash = self
-                .with_circuit_breaker(|| async {
-                    rpc.chain_get_block_hash(Some(current_block.into()))
-                        .await
-                        .map_err(|e| IndexerError::from(subxt::Error::from(e)))
-                })
+            let hash = self.block_hash(source, current_block).await?;
+            if !self
+                .queue_block(source, &tx, &mut reorgs, current_block, hash)
                 .await?
-                .ok_or(IndexerError::BlockNotFound {
-                    block: current_block,
-                })?;
-
-            self.process_block(&rpc, current_block, hash).await?;
+            {
+                // The dispatch stage stopped; its error or reason is reported by `run`.
+                return Ok(None);
+            }
             current_block += 1;
         }
 
-        let updater = self.client.updater();
-        tokio::spawn(async move {
-            if let Err(e) = updater.perform_runtime_updates().await {
-                warn!(target: "indexer", "runtime updater exited: {:?}", e);
+        let mut sub = match self.config.finality {
+            FinalityMode::Finalized => source.subscribe_finalized().await?,
+            FinalityMode::Best => source.subscribe_best().await?,
+        };
+        loop {
+            let next = match limits.deadline() {
+                Some(deadline) => match tokio::time::timeout_at(deadline, sub.next()).await {
+                    Ok(next) => next,
+                    Err(_) => return Ok(Some(StopReason::TimeLimit)),
+                },
+                None => sub.next().await,
+            };
+            let Some(block) = next else {
+                return Ok(Some(StopReason::SubscriptionClosed));
+            };
+            let (number, block_hash) = block?;
+            self.recorder.observe_head(number);
+
+            if let Some(tracker) = &mut reorgs {
+                if let Some(reorg) = tracker.check(|n| self.canonical_hash(source, n)).await? {
+                    warn!(
+                        target: "indexer",
+                        from = reorg.from,
+                        to = reorg.to,
+                        "chain reorganized, unwinding {} blocks",
+                        reorg.depth()
+                    );
+                    if let Some(recording) = &self.recording {
+                        recording.reorg(reorg)?;
+                    }
+                    if tx.send(Fetched::Reorg(reorg)).await.is_err() {
+                        return Ok(None);
+                    }
+                    current_block = reorg.from;
+                }
             }
-        });
 
-        let mut sub = self.client.blocks().subscribe_finalized().await?;
-        while let Some(block) = sub.next().await {
-            let block = block?;
-            let number = block.header().number().into();
+            // Fill in blocks the subscription skipped, and the new chain after a reorg.
+            for n in current_block..=number {
+                if let Some(reason) = limits.check(n) {
+                    return Ok(Some(reason));
+                }
+                let hash = if n == number {
+                    block_hash
+                } else {
+                    self.block_hash(source, n).await?
+                };
+                if !self.queue_block(source, &tx, &mut reorgs, n, hash).await? {
+                    return Ok(None);
+                }
+                current_block = n + 1;
+            }
 
-            if number < current_block {
-                continue;
+            if let Some(reason) = limits.check(current_block) {
+                return Ok(Some(reason));
             }
+        }
+    }
 
-            self.process_block(&rpc, number, block.hash()).await?;
-            current_block = number + 1;
+    /// Hash of block `number` on the node's chain.
+    async fn block_hash(
+        &self,
+        source: &dyn BlockSource<C>,
+        number: BlockNumber,
+    ) -> Result<HashFor<C>, IndexerError> {
+        self.canonical_hash(source, number)
+            .await?
+            .ok_or(IndexerError::BlockNotFound { block: number })
+    }
 
-            if let Some(end) = end_block {
-                if number >= end {
-                    break;
-                }
-            }
+    async fn canonical_hash(
+        &self,
+        source: &dyn BlockSource<C>,
+        number: BlockNumber,
+    ) -> Result<Option<HashFor<C>>, IndexerError> {
+        self.with_circuit_breaker("chain_get_block_hash", Some(number), || {
+            source.block_hash(number)
+        })
+        .await
+    }
+
+    /// Fetch block `number` and queue it for the handlers. Returns `false` once the
+    /// dispatch stage has stopped.
+    async fn queue_block(
+        &self,
+        source: &dyn BlockSource<C>,
+        tx: &QueueSender<Fetched<C>>,
+        reorgs: &mut Option<ReorgTracker<HashFor<C>>>,
+        number: BlockNumber,
+        hash: HashFor<C>,
+    ) -> Result<bool, IndexerError> {
+        let block = self.fetch_block(source, number, hash).await?;
+        if let Some(tracker) = reorgs {
+            tracker.push(number, hash);
         }
+        Ok(tx.send(Fetched::Block(block)).await.is_ok())
+    }
 
-        Ok(())
+    async fn fetch_block(
+        &self,
+        source: &dyn BlockSource<C>,
+        number: BlockNumber,
+        hash: HashFor<C>,
+    ) -> Result<FetchedBlock<C>, IndexerError> {
+        self.update_metadata(source, number, hash).await?;
+        let block = self
+            .with_circuit_breaker("block_at", Some(number), || {
+                source.block_at(hash, self.config.correlate_extrinsics)
+            })
+            .await?;
+        let metadata = self.client.metadata();
+        if let Some(recording) = &self.recording {
+            self.record_block(recording, source, number, hash, &block)
+                .await?;
+        }
+        let events = Events::decode_from(block.events, metadata.clone());
+        let extrinsics = block
+            .extrinsics
+            .map(|extrinsics| ChainExtrinsic::decode_all::<C>(extrinsics, metadata))
+            .transpose()?;
+        let author = if self.config.resolve_block_author {
+            self.resolve_author(number, &block.header, &events).await?
+        } else {
+            None
+        };
+        Ok(FetchedBlock {
+            number,
+            hash,
+            events,
+            extrinsics,
+            author,
+            timestamp: block.timestamp,
+        })
     }
 
-    async fn process_block(
+    /// Append block `number` to `recording`, preceded by the metadata of its
+    /// runtime when that changed.
+    async fn record_block(
         &self,
-        rpc: &LegacyRpcMethods<C>,
+        recording: &RecordingWriter,
+        source: &dyn BlockSource<C>,
         number: BlockNumber,
         hash: HashFor<C>,
+        block: &SourceBlock<C>,
     ) -> Result<(), IndexerError> {
+        let version = self.client.runtime_version();
+        if recording.spec_version() != Some(version.spec_version) {
+            let metadata = self
+                .with_circuit_breaker("state_get_metadata", Some(number), || {
+                    source.raw_metadata(hash)
+                })
+                .await?;
+            recording.runtime(version.spec_version, version.transaction_version, metadata)?;
+        }
+        recording.block(
+            number,
+            &hash.encode(),
+            &block.header.encode(),
+            &block.events,
+        )
+    }
+
+    /// Fetch stage of a replay: pass on the recorded blocks from the start block
+    /// onwards, switching metadata where the recorded runtime changes.
+    async fn replay_blocks(
+        &self,
+        replay: &ReplaySource,
+        tx: QueueSender<Fetched<C>>,
+        limits: &RunLimits,
+    ) -> Result<Option<StopReason>, IndexerError> {
+        let Some(latest_number) = replay.last_block() else {
+            return Ok(Some(StopReason::EndOfRecording));
+        };
+        self.recorder.observe_head(latest_number);
+        let checkpoint = self.store.load_checkpoint().await?;
+        validate_range(latest_number, checkpoint, &self.config)?;
+        let start = self
+            .config
+            .start_from
+            .resolve(&self.store, latest_number)
+            .await?;
+        info!(
+            target: "indexer",
+            path = %replay.path().display(),
+            block = start,
+            "replaying recorded blocks"
+        );
+
+        for entry in replay.entries() {
+            let fetched = match entry {
+                Recorded::Block(block) if block.number < start => continue,
+                Recorded::Block(block) => {
+                    if let Some(reason) = limits.check(block.number) {
+                        return Ok(Some(reason));
+                    }
+                    Fetched::Block(self.replayed_block(replay, block)?)
+                }
+                Recorded::Reorg(reorg) if reorg.to < start => continue,
+                Recorded::Reorg(reorg) => Fetched::Reorg(*reorg),
+            };
+            if tx.send(fetched).await.is_err() {
+                return Ok(None);
+            }
+        }
+        Ok(Some(StopReason::EndOfRecording))
+    }
+
+    /// Decode a recorded block with the metadata of its runtime.
+    fn replayed_block(
+        &self,
+        replay: &ReplaySource,
+        block: &RecordedBlock,
+    ) -> Result<FetchedBlock<C>, IndexerError> {
+        if self.client.runtime_version().spec_version != block.spec_version {
+            let (metadata, version) = replay.runtime(block.spec_version).ok_or_else(|| {
+                IndexerError::invalid_config(
+                    "replay_from",
+                    format!(
+                        "no metadata recorded for spec version {}",
+                        block.spec_version
+                    ),
+                )
+            })?;
+            self.client.set_metadata(metadata);
+            self.client.set_runtime_version(version);
+        }
+        let hash = HashFor::<C>::decode(&mut &block.hash[..]).map_err(|e| {
+            IndexerError::invalid_config(
+                "replay_from",
+                format!("hash of block {}: {e}", block.number),
+            )
+        })?;
+        Ok(FetchedBlock {
+            number: block.number,
+            hash,
+            events: Events::decode_from(block.events.clone(), self.client.metadata()),
+            extrinsics: None,
+            author: None,
+            timestamp: None,
+        })
+    }
+
+    async fn resolve_author(
+        &self,
+        number: BlockNumber,
+        header: &C::Header,
+        events: &Events<C>,
+    ) -> Result<Option<AccountId32>, IndexerError> {
+        let (parent_hash, pre_runtime) = author::read_header::<C>(header)?;
+        let storage = self.client.storage().at(parent_hash);
+        let author = self
+            .authors
+            .resolve(number, pre_runtime, || author::session_validators(&storage))
+            .await?;
+        let new_session = events
+            .iter()
+            .flatten()
+            .any(|event| event.pallet_name() == "Session" && event.variant_name() == "NewSession");
+        if new_session {
+            self.authors.new_session();
+        }
+        Ok(author)
+    }
+
+    /// Dispatch stage: run the handlers over a fetched block. Checkpointing is left to
+    /// the caller, which batches it according to `checkpoint_interval`.
+    async fn dispatch_block(&self, block: FetchedBlock<C>) -> Result<(), IndexerError> {
         let block_start = Instant::now();
-        self.update_metadata(rpc, hash).await?;
-        let block = self.client.blocks().at(hash).await?;
-        let events = block.events().await?;
-        self.process_events(number, hash, &events).await?;
-        self.with_circuit_breaker(|| async { self.store.store_checkpoint(number).await })
+        let number = block.number;
+        let (dispatched, _) = self
+            .process_events(
+                number,
+                block.hash,
+                &block.events,
+                block.extrinsics,
+                block.author,
+                block.timestamp,
+            )
             .await?;
         tracing::debug!("Finished processing block {}, all events consumed.", number);
+        self.recorder
+            .observe_block(number, dispatched, block_start.elapsed());
 
-        let elapsed = block_start.elapsed();
-        if let Some(min_dur) = self
-            .max_blocks_per_minute
-            .map(|bpm| Duration::from_secs_f64(60.0 / bpm as f64))
-        {
-            if elapsed < min_dur {
-                let to_wait = min_dur - elapsed;
-                tracing::debug!("Throttling: sleeping {:?} to respect rate limits", to_wait);
-                tokio::time::sleep(to_wait).await;
-            }
+        let settings = self.settings.get();
+        if let Some(to_wait) = settings.throttle_delay(block_start.elapsed()) {
+            tracing::debug!("Throttling: sleeping {:?} to respect rate limits", to_wait);
+            tokio::time::sleep(to_wait).await;
         }
 
         Ok(())
     }
 
+    /// Run the handlers over a block inside an `index_block` span, with a `handler`
+    /// child span per handler. Returns the number of events dispatched and the first
+    /// error of each handler.
     async fn process_events(
         &self,
         block_number: BlockNumber,
         block_hash: HashFor<C>,
         events: &Events<C>,
-    ) -> Result<(), IndexerError> {
-        let ctx = Context::new(block_number, block_hash);
+        extrinsics: Option<Vec<ChainExtrinsic>>,
+        author: Option<AccountId32>,
+        timestamp: Option<u64>,
+    ) -> Result<(usize, Vec<Option<IndexerError>>), IndexerError> {
+        self.run_handlers(
+            block_number,
+            block_hash,
+            events,
+            extrinsics,
+            author,
+            timestamp,
+        )
+        .instrument(block_span(block_number, block_hash))
+        .await
+    }
 
-        let mut decoded = Vec::new();
-        for (index, evt_result) in events.iter().enumerate() {
-            let evt = match evt_result {
-                Ok(evt) => evt,
+    async fn run_handlers(
+        &self,
+        block_number: BlockNumber,
+        block_hash: HashFor<C>,
+        events: &Events<C>,
+        extrinsics: Option<Vec<ChainExtrinsic>>,
+        author: Option<AccountId32>,
+        timestamp: Option<u64>,
+    ) -> Result<(usize, Vec<Option<IndexerError>>), IndexerError> {
+        let mut all = Vec::new();
+        let mut undecodable = None;
+        for evt_result in ChainEvent::from_events(events) {
+            match evt_result {
+                Ok(event) => all.push(event),
                 Err(e) => {
-                    return Err(IndexerError::EventDecodingFailed {
+                    let error = IndexerError::EventDecodingFailed {
                         pallet: "<unknown>".into(),
                         event: "<unknown>".into(),
                         block: block_number,
                         source: Box::new(e),
-                    });
+                    };
+                    if !self.config.skip_undecodable_events {
+                        return Err(error);
+                    }
+                    // Where the next event starts is unknown past this one.
+                    undecodable = Some(error);
+                    break;
                 }
-            };
-            decoded.push(ChainEvent::new(evt, index as u32));
+            }
         }
 
-        for handler in &self.handlers {
-            if let Err(e) = handler.handle_block(&ctx, &decoded).await {
-                handler.handle_error(&e, &ctx).await;
+        let mut ctx = Context::new(block_number, block_hash)
+            .with_storage(self.client.storage().at(block_hash))
+            .with_error_reporter(self.error_reporter.clone())
+            .with_recorder(self.recorder.clone())
+            .with_abort_on_panic(self.config.abort_on_panic);
+        if let Some(author) = author {
+            ctx = ctx.with_block_author(author);
+        }
+        if let Some(timestamp) = timestamp {
+            ctx = ctx.with_timestamp(timestamp);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            ctx = ctx.with_metrics(metrics.clone());
+        }
+        if let Some(extrinsics) = &extrinsics {
+            let mut info = BlockExtrinsics::from_extrinsics(extrinsics);
+            // Outcomes and fees come from `System` and `TransactionPayment` events,
+            // which the allowlist may drop.
+            info.apply_outcomes(&all);
+            if self.config.track_fees {
+                info.apply_fees(&all);
             }
+            ctx = ctx.with_extrinsics(Arc::new(info));
         }
 
-        for chain_event in &decoded {
-            let pallet = chain_event.pallet_name().to_string();
-            let variant = chain_event.variant_name().to_string();
+        let decoded: Vec<_> = all
+            .into_iter()
+            .filter(|event| {
+                self.config
+                    .allows_event(event.pallet_name(), event.variant_name())
+            })
+            .collect();
+        Span::current().record("events", decoded.len());
 
-            for handler in &self.handlers {
+        let handlers: Vec<_> = self
+            .handlers
+            .iter()
+            .map(|handler| {
                 let filter = handler.event_filter();
-                if filter.matches(&pallet, &variant) {
-                    if let Err(e) = handler.handle_event(chain_event, &ctx).await {
-                        handler.handle_error(&e, &ctx).await;
+                let matched = decoded.iter().filter(|e| filter.matches_event(e)).count();
+                let span = handler_span(handler.name(), matched);
+                (handler, filter, span)
+            })
+            .collect();
+
+        if let Some(error) = &undecodable {
+            warn!(target: "indexer", block = block_number, "{error}, skipping the rest of the block");
+            let mut panics = Vec::new();
+            for (handler, _, span) in &handlers {
+                if let Some(panic) = self.error_hook(handler.as_ref(), span, &ctx, error).await {
+                    panics.push((handler, panic));
+                }
+            }
+            // A failure of the block, reported and counted once whatever the
+            // number of handlers.
+            let report = ErrorContext::decode(block_number);
+            self.error_reporter.report(error, Some(&report));
+            self.count_handler_error(error)?;
+            for (handler, panic) in &panics {
+                self.report_handler_error(handler.as_ref(), block_number, panic)?;
+            }
+        }
+
+        // First error of each handler, for `process_block`.
+        let mut failures: Vec<Option<IndexerError>> = handlers.iter().map(|_| None).collect();
+
+        for (i, (handler, _, span)) in handlers.iter().enumerate() {
+            let hook = handler.handle_block(&ctx, &decoded);
+            let res = self
+                .guard(
+                    handler.as_ref(),
+                    block_number,
+                    hook.instrument(span.clone()),
+                )
+                .await;
+            if let Err(e) = res {
+                self.handler_failed(handler.as_ref(), span, &ctx, &e)
+                    .await?;
+                failures[i].get_or_insert(e);
+            }
+        }
+
+        for extrinsic in extrinsics.iter().flatten() {
+            for (i, (handler, _, span)) in handlers.iter().enumerate() {
+                let hook = handler.handle_extrinsic(extrinsic, &ctx);
+                let res = self
+                    .guard(
+                        handler.as_ref(),
+                        block_number,
+                        hook.instrument(span.clone()),
+                    )
+                    .await;
+                if let Err(e) = res {
+                    self.handler_failed(handler.as_ref(), span, &ctx, &e)
+                        .await?;
+                    failures[i].get_or_insert(e);
+                }
+            }
+        }
+
+        for chain_event in &decoded {
+            for (i, (handler, filter, span)) in handlers.iter().enumerate() {
+                if filter.matches_event(chain_event) {
+                    let hook = handler.handle_event(chain_event, &ctx);
+                    let res = self
+                        .guard(
+                            handler.as_ref(),
+                            block_number,
+                            hook.instrument(span.clone()),
+                        )
+                        .await;
+                    if let Err(e) = res {
+                        let e = e.with_event_index(chain_event.index());
+                        self.handler_failed(handler.as_ref(), span, &ctx, &e)
+                            .await?;
+                        failures[i].get_or_insert(e);
                     }
                 }
             }
         }
 
+        Ok((decoded.len(), failures))
+    }
+
+    /// Record a handler failure on its span, let the handler see it, report it
+    /// and count it. A panic in [`Handler::handle_error`] is reported and counted
+    /// as a failure of its own.
+    async fn handler_failed(
+        &self,
+        handler: &dyn Handler<C>,
+        span: &Span,
+        ctx: &Context<C>,
+        error: &IndexerError,
+    ) -> Result<(), IndexerError> {
+        record_span_error(span, error);
+        let panicked = self.error_hook(handler, span, ctx, error).await;
+        for error in std::iter::once(error).chain(&panicked) {
+            self.report_handler_error(handler, ctx.block_number, error)?;
+        }
         Ok(())
     }
+
+    /// Pass `error` to [`Handler::handle_error`], returning a panic in it as
+    /// [`IndexerError::HandlerFailed`].
+    async fn error_hook(
+        &self,
+        handler: &dyn Handler<C>,
+        span: &Span,
+        ctx: &Context<C>,
+        error: &IndexerError,
+    ) -> Option<IndexerError> {
+        let hook = async {
+            handler.handle_error(error, ctx).await;
+            Ok(())
+        };
+        self.guard(handler, ctx.block_number, hook.instrument(span.clone()))
+            .await
+            .err()
+    }
+
+    /// Report a failure of `handler` on `block` and count it.
+    fn report_handler_error(
+        &self,
+        handler: &dyn Handler<C>,
+        block: BlockNumber,
+        error: &IndexerError,
+    ) -> Result<(), IndexerError> {
+        let report = ErrorContext::handler(handler.name(), block);
+        self.error_reporter.report(error, Some(&report));
+        self.count_handler_error(error)
+    }
+
+    /// Run a handler hook, turning a panic into [`IndexerError::HandlerFailed`]
+    /// with the panic message unless
+    /// [`abort_on_panic`](crate::builder::IndexerBuilder::abort_on_panic) is set.
+    ///
+    /// Handlers are not required to be `UnwindSafe`, so the hook is asserted to be.
+    /// State a handler shares between calls may be left half-updated by the panic
+    /// and is seen as such by its later calls.
+    async fn guard<T>(
+        &self,
+        handler: &dyn Handler<C>,
+        block: BlockNumber,
+        hook: impl Future<Output = Result<T, IndexerError>>,
+    ) -> Result<T, IndexerError> {
+        catch_panic(handler.name(), block, self.config.abort_on_panic, hook).await
+    }
+
+    /// Count a handler failure, failing once the error budget is exceeded.
+    fn count_handler_error(&self, error: &IndexerError) -> Result<(), IndexerError> {
+        self.recorder.record_handler_error(error);
+        let errors = self.handler_errors.fetch_add(1, Ordering::SeqCst) + 1;
+        match self.settings.get().handler_error_budget {
+            Some(budget) if errors > budget => {
+                Err(IndexerError::ErrorBudgetExhausted { errors, budget })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Item passed from the fetch stage to the dispatch stage.
+enum Fetched<C: Config> {
+    Block(FetchedBlock<C>),
+    /// Blocks already passed on that are no longer on the best chain.
+    Reorg(Reorg),
+}
+
+/// Where the fetch stage of a run gets its blocks from.
+enum Feed<C: Config> {
+    Source(Arc<dyn BlockSource<C>>),
+    Replay(Arc<ReplaySource>),
+}
+
+/// A block whose events have been fetched and are waiting for the handlers.
+struct FetchedBlock<C: Config> {
+    number: BlockNumber,
+    hash: HashFor<C>,
+    events: Events<C>,
+    /// Present when extrinsic correlation is enabled.
+    extrinsics: Option<Vec<ChainExtrinsic>>,
+    /// Present when author resolution is enabled and the block has a slot claim.
+    author: Option<AccountId32>,
+    /// `Timestamp.Now` at the block, in milliseconds.
+    timestamp: Option<u64>,
+}
+
+/// Span the indexer runs the handlers of block `block` in. `events` is filled in
+/// with the number of events dispatched once they are decoded.
+pub fn block_span(block: BlockNumber, hash: impl fmt::Debug) -> Span {
+    info_span!(
+        target: "indexer",
+        "index_block",
+        block,
+        hash = ?hash,
+        events = field::Empty
+    )
+}
+
+/// Child span of [`block_span`] for one handler, whose filter `matched_events`
+/// events of the block pass. See [`record_span_error`] for its other fields.
+pub fn handler_span(handler: &str, matched_events: usize) -> Span {
+    info_span!(
+        target: "indexer",
+        "handler",
+        handler,
+        matched_events,
+        error = field::Empty,
+        "otel.status_code" = field::Empty
+    )
+}
+
+/// Record `error` on a [`handler_span`], and mark it failed for OpenTelemetry layers
+/// through `otel.status_code`.
+pub fn record_span_error(span: &Span, error: &IndexerError) {
+    span.record("error", field::display(error));
+    span.record("otel.status_code", "ERROR");
+}
+
+/// Forwards RPC retry notifications to the counters and the metrics backend,
+/// and give-ups to the error reporter too.
+struct RpcRetryObserver<'a> {
+    counters: &'a RetryCounters,
+    reporter: &'a dyn ErrorReporter,
+    domain: &'static str,
+    recorder: &'a dyn MetricsRecorder,
+}
+
+impl RetryObserver for RpcRetryObserver<'_> {
+    fn on_retry(&self, attempt: usize, delay: Duration, error: &IndexerError) {
+        self.counters.on_retry(attempt, delay, error);
+        self.recorder.on_retry(attempt, delay, error);
+    }
+
+    fn on_give_up(&self, attempts: usize, error: &IndexerError) {
+        self.counters.on_give_up(attempts, error);
+        let report = ErrorContext::retry_give_up(self.domain, attempts);
+        self.reporter.report(error, Some(&report));
+        self.recorder.on_give_up(attempts, error);
+    }
+}
+
+/// Log every state change of `breaker`, tagged with the node the indexer talks to.
+fn log_breaker_transitions(breaker: &CircuitBreaker, node_url: &str) {
+    let domain = breaker.domain();
+    let node_url = redact_url(node_url);
+    breaker.on_state_change(move |from, to| match to {
+        BreakerState::Open { .. } => {
+            warn!(target: "indexer", %node_url, "{domain} circuit breaker opened (was {from:?})")
+        }
+        _ => info!(target: "indexer", %node_url, "{domain} circuit breaker {from:?} -> {to:?}"),
+    });
 }
@@ -0,0 +1,256 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Write events as newline-delimited JSON, to stdout or to files.
+
+use crate::error::IndexerError;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::types::{ChainEvent, EventEnvelope};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use subxt::config::HashFor;
+use subxt::Config;
+
+/// Record written after a block's events by
+/// [`JsonLinesHandler::with_block_records`].
+#[derive(Serialize)]
+struct BlockRecord<'a, H> {
+    block_number: u64,
+    block_hash: &'a H,
+    /// Events of the block that passed the filter.
+    events: usize,
+}
+
+/// Files written by [`JsonLinesHandler::file`].
+struct Files {
+    path: PathBuf,
+    append: bool,
+    rotate_blocks: Option<u64>,
+    /// Open file and the block range it covers, `0` without rotation.
+    current: Option<(u64, File)>,
+}
+
+impl Files {
+    fn writer(&mut self, block: u64) -> Result<&mut File, IndexerError> {
+        let range = self.rotate_blocks.map_or(0, |blocks| block / blocks);
+        let (_, file) = match self.current.take() {
+            Some(current) if current.0 == range => self.current.insert(current),
+            _ => {
+                let file = self.open(range)?;
+                self.current.insert((range, file))
+            }
+        };
+        Ok(file)
+    }
+
+    fn open(&self, range: u64) -> Result<File, IndexerError> {
+        let path = match self.rotate_blocks {
+            Some(blocks) => rotated_path(&self.path, range * blocks, blocks),
+            None => self.path.clone(),
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(self.append)
+            .truncate(!self.append)
+            .open(path)?;
+        Ok(file)
+    }
+}
+
+/// `events.ndjson` becomes `events_{start}_{end}.ndjson`, `end` exclusive.
+fn rotated_path(path: &Path, start: u64, blocks: u64) -> PathBuf {
+    let end = start.saturating_add(blocks);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}_{start:010}_{end:010}.{}", ext.to_string_lossy()),
+        None => format!("{stem}_{start:010}_{end:010}"),
+    };
+    path.with_file_name(name)
+}
+
+enum Output {
+    Writer(Box<dyn Write + Send>),
+    Files(Files),
+}
+
+/// Handler writing one [`EventEnvelope`] per line for every event passing its
+/// filter, for piping into tools such as `jq`.
+///
+/// A block's lines are written and flushed together once the block is
+/// handled. With [`with_block_records`](Self::with_block_records) every block
+/// is followed by a `{"block_number", "block_hash", "events"}` line, also for
+/// blocks without matching events.
+pub struct JsonLinesHandler {
+    filter: EventFilter,
+    pretty: bool,
+    block_records: bool,
+    output: Mutex<Output>,
+}
+
+impl JsonLinesHandler {
+    /// Write to standard output.
+    pub fn stdout() -> Self {
+        Self::writer(io::stdout())
+    }
+
+    /// Write to `writer`, flushed after every block.
+    pub fn writer(writer: impl Write + Send + 'static) -> Self {
+        Self::with_output(Output::Writer(Box::new(writer)))
+    }
+
+    /// Append to the file at `path`, created with its directory when missing.
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self::with_output(Output::Files(Files {
+            path: path.into(),
+            append: true,
+            rotate_blocks: None,
+            current: None,
+        }))
+    }
+
+    fn with_output(output: Output) -> Self {
+        Self {
+            filter: EventFilter::all(),
+            pretty: false,
+            block_records: false,
+            output: Mutex::new(output),
+        }
+    }
+
+    /// Only write events passing `filter`.
+    pub fn with_filter(mut self, filter: EventFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Indent each record over several lines. Tools reading a JSON stream such
+    /// as `jq` still accept the output, line-based ones do not.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Write a record after every block.
+    pub fn with_block_records(mut self) -> Self {
+        self.block_records = true;
+        self
+    }
+
+    /// Append to existing files, the default, or truncate them when opened.
+    /// Has no effect unless writing to files.
+    pub fn append(mut self, append: bool) -> Self {
+        if let Output::Files(files) = self.output.get_mut().unwrap() {
+            files.append = append;
+        }
+        self
+    }
+
+    /// Start a new file every `blocks` blocks, aligned to multiples of
+    /// `blocks`: `events.ndjson` is written as
+    /// `events_0001000000_0001010000.ndjson` and so on, `end` exclusive. Has
+    /// no effect unless writing to files.
+    pub fn rotate_blocks(mut self, blocks: u64) -> Self {
+        if let Output::Files(files) = self.output.get_mut().unwrap() {
+            files.rotate_blocks = Some(blocks.max(1));
+        }
+        self
+    }
+
+    fn push(&self, buf: &mut Vec<u8>, record: &impl Serialize) -> Result<(), IndexerError> {
+        if self.pretty {
+            serde_json::to_writer_pretty(&mut *buf, record)?;
+        } else {
+            serde_json::to_writer(&mut *buf, record)?;
+        }
+        buf.push(b'\n');
+        Ok(())
+    }
+
+    fn write(&self, block: u64, buf: &[u8]) -> Result<(), IndexerError> {
+        let mut output = self.output.lock().unwrap();
+        let writer: &mut dyn Write = match &mut *output {
+            Output::Writer(writer) => writer,
+            Output::Files(files) => files.writer(block)?,
+        };
+        writer.write_all(buf)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<C> Handler<C> for JsonLinesHandler
+where
+    C: Config,
+    HashFor<C>: Serialize,
+{
+    fn name(&self) -> &str {
+        "JsonLinesHandler"
+    }
+
+    fn event_filter(&self) -> EventFilter {
+        self.filter
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        let mut buf = Vec::new();
+        let mut matched = 0;
+        for event in events.iter().filter(|e| self.filter.matches_event(e)) {
+            self.push(&mut buf, &EventEnvelope::from_event(event, ctx))?;
+            matched += 1;
+        }
+        if self.block_records {
+            let record = BlockRecord {
+                block_number: ctx.block_number,
+                block_hash: &ctx.block_hash,
+                events: matched,
+            };
+            self.push(&mut buf, &record)?;
+        }
+        if buf.is_empty() {
+            return Ok(());
+        }
+        self.write(ctx.block_number, &buf)
+    }
+
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        let mut output = self.output.lock().unwrap();
+        match &mut *output {
+            Output::Writer(writer) => writer.flush()?,
+            Output::Files(files) => {
+                if let Some((_, file)) = files.current.take() {
+                    file.sync_all()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -14,25 +14,131 @@
  * limitations under the License.
  */
 
+#[cfg(feature = "webhook")]
+pub mod alert;
+pub mod author;
+#[cfg(any(feature = "grpc", feature = "http-api"))]
+pub mod broadcast;
 pub mod builder;
+#[cfg(feature = "bittensor-events")]
+pub mod calls;
 pub mod config;
+#[cfg(feature = "csv-export")]
+pub mod csv_export;
 pub mod error;
+#[cfg(feature = "bittensor-events")]
+pub mod events;
+pub mod extrinsics;
+pub mod filters;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod handler;
 pub mod handler_group;
+pub mod health;
+pub mod heartbeat;
+pub mod hedge;
+#[cfg(feature = "http-api")]
+pub mod http_api;
 pub mod indexer;
+#[cfg(feature = "json-storage")]
+pub mod json_lines;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "nats")]
+pub mod nats;
+#[cfg(feature = "parquet")]
+pub mod parquet_export;
 pub mod prelude;
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "bittensor-events"))]
+pub mod presets;
+pub mod queue;
+mod recorder;
+pub mod redact;
+pub mod reorg;
+pub mod replay;
+pub mod reporter;
 pub mod retry;
+pub mod rpc;
+pub mod run;
+mod serde_duration;
+mod serde_time;
+pub mod settings;
+pub mod source;
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+pub mod sql_sink;
+pub mod ss58;
+#[cfg(feature = "statsd")]
+pub mod statsd;
 pub mod storage;
+#[cfg(feature = "bittensor-events")]
+pub mod subtensor_storage;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod types;
+pub mod units;
 pub mod validated_types;
+#[cfg(feature = "webhook")]
+pub mod webhook;
 
+#[cfg(feature = "webhook")]
+pub use crate::alert::{AlertFormat, AlertHandler, AlertRule};
+pub use crate::author::{AuthorResolver, PreRuntime};
+#[cfg(any(feature = "grpc", feature = "http-api"))]
+pub use crate::broadcast::{EventBroadcast, LiveEvent};
 pub use crate::builder::IndexerBuilder;
-pub use crate::config::IndexerConfig;
+pub use crate::config::{FinalityMode, IndexerConfig, Preset, StartFrom, TlsMode};
+#[cfg(feature = "csv-export")]
+pub use crate::csv_export::{CsvExportHandler, CsvSchema};
 pub use crate::error::IndexerError;
-pub use crate::handler::{Context, EventFilter, Handler};
-pub use crate::handler_group::HandlerGroup;
-pub use crate::indexer::Indexer;
-pub use crate::retry::{retry_with_backoff, CircuitBreaker, RetryConfig};
-pub use crate::storage::CheckpointStore;
-pub use crate::types::{BlockNumber, ChainEvent};
-pub use crate::validated_types::{PostgresUrl, SqliteUrl, WebSocketUrl};
+pub use crate::extrinsics::{BlockExtrinsics, ChainExtrinsic, ExtrinsicInfo};
+#[cfg(feature = "grpc")]
+pub use crate::grpc::{EventStreamService, GrpcServer};
+pub use crate::handler::{Context, ContextBuilder, EventFilter, EventPattern, Handler};
+pub use crate::handler_group::{BreakerHandler, HandlerBreaker, HandlerGroup};
+pub use crate::health::HealthStatus;
+pub use crate::heartbeat::HeartbeatFile;
+pub use crate::hedge::{HedgeCounters, HedgedRpc};
+#[cfg(feature = "http-api")]
+pub use crate::http_api::{StatusServer, StatusSource, WsFilter};
+pub use crate::indexer::{block_span, handler_span, record_span_error, Indexer};
+#[cfg(feature = "json-storage")]
+pub use crate::json_lines::JsonLinesHandler;
+#[cfg(feature = "metrics")]
+pub use crate::metrics::{IndexerMetrics, MetricsServer};
+#[cfg(feature = "nats")]
+pub use crate::nats::NatsSinkHandler;
+#[cfg(feature = "parquet")]
+pub use crate::parquet_export::{ParquetExportHandler, ParquetSchema, ToArrow};
+pub use crate::queue::QueueGauge;
+pub use crate::redact::{redact_url, set_redaction_enabled};
+pub use crate::reorg::{Reorg, ReorgTracker};
+pub use crate::replay::{RecordingWriter, ReplaySource};
+#[cfg(feature = "sentry")]
+pub use crate::reporter::SentryReporter;
+pub use crate::reporter::{
+    ErrorContext, ErrorReporter, GiveUpReporter, LogReporter, ReportKind, ThrottledReporter,
+};
+pub use crate::retry::{
+    retry_span, retry_with_backoff, retry_with_backoff_observed, with_timeout_and_backoff,
+    BreakerDomain, BreakerState, BreakerStats, CircuitBreaker, CircuitBreakerConfig, JitterMode,
+    RetryConfig, RetryCounters, RetryObserver, RetryPredicate,
+};
+pub use crate::run::{RunLimits, RunSummary, StopReason};
+pub use crate::settings::{RuntimeSettings, SettingsHandle};
+pub use crate::source::{BlockSource, NodeSource, SourceBlock};
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+pub use crate::sql_sink::{SqlPool, SqlSinkHandler, SqlValue};
+#[cfg(feature = "statsd")]
+pub use crate::statsd::StatsdMetrics;
+pub use crate::storage::{CheckpointStore, MemoryStore, RetryingStore, StorageConfig};
+#[cfg(feature = "testing")]
+pub use crate::testing::MockBlockSource;
+#[cfg(feature = "json-storage")]
+pub use crate::types::EventEnvelope;
+pub use crate::types::{BlockNumber, ChainEvent, EventId, EventSummary, FieldValueExt};
+pub use crate::units::Rao;
+pub use crate::validated_types::{
+    BlockRange, NodeAuth, PostgresUrl, PostgresUrlBuilder, SqliteMode, SqliteUrl, WebSocketUrl,
+};
+#[cfg(feature = "webhook")]
+pub use crate::webhook::WebhookHandler;
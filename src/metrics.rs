@@ -0,0 +1,341 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Prometheus metrics of the indexer, with the `metrics` feature.
+//!
+//! [`IndexerMetrics`] owns a [`Registry`] holding the indexer's series. The
+//! indexer updates them as it processes blocks, retries RPC calls and trips
+//! breakers, and [`HandlerGroup`](crate::handler_group::HandlerGroup) counts
+//! the member failures it absorbs. Other Prometheus collectors, such as
+//! [`SubnetMetrics`](crate::presets::subnet_metrics::SubnetMetrics) with the
+//! `bittensor-events` feature, can be registered in the same registry so one
+//! scrape covers everything.
+//!
+//! [`IndexerBuilder::serve_metrics`](crate::builder::IndexerBuilder::serve_metrics)
+//! serves the registry at `GET /metrics`.
+
+use crate::error::IndexerError;
+pub use crate::recorder::UNKNOWN_HANDLER;
+use crate::recorder::{breaker_value, gauge_value, handler_label, MetricsRecorder};
+use crate::retry::{BreakerState, CircuitBreaker, RetryObserver};
+use crate::types::BlockNumber;
+use prometheus::core::Collector;
+use prometheus::{
+    Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+/// Upper bounds, in seconds, of the block processing duration buckets.
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Largest request head the metrics server reads.
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+/// Pause after a failed accept, e.g. when out of file descriptors.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// The indexer's Prometheus series:
+///
+/// - `indexer_blocks_processed_total` and `indexer_events_processed_total`
+/// - `indexer_handler_errors_total{handler}`, labeled with the handler named
+///   by [`IndexerError::HandlerFailed`], or [`UNKNOWN_HANDLER`]
+/// - `indexer_block_processing_seconds`, a histogram of the time spent in the
+///   handlers per block
+/// - `indexer_chain_head`, `indexer_last_processed_block` and
+///   `indexer_chain_lag_blocks`, the distance between them
+/// - `indexer_breaker_state{breaker}`: 0 closed, 1 half-open, 2 open
+/// - `indexer_rpc_retries_total` and `indexer_rpc_retry_give_ups_total`
+/// - `indexer_sink_events_total{sink,outcome}`: events sinks such as the
+///   `webhook` delivered (`sent`) or dropped (`failed`)
+pub struct IndexerMetrics {
+    registry: Registry,
+    blocks_processed: IntCounter,
+    events_processed: IntCounter,
+    handler_errors: IntCounterVec,
+    block_duration: Histogram,
+    chain_head: IntGauge,
+    last_processed: IntGauge,
+    lag: IntGauge,
+    breaker_state: IntGaugeVec,
+    retries: IntCounter,
+    give_ups: IntCounter,
+    sink_events: IntCounterVec,
+}
+
+impl IndexerMetrics {
+    /// Metrics in a registry of their own.
+    pub fn new() -> Result<Self, IndexerError> {
+        Self::with_registry(Registry::new())
+    }
+
+    /// Metrics registered in `registry`, which may already hold other series.
+    /// Fails if it holds any of the indexer's.
+    pub fn with_registry(registry: Registry) -> Result<Self, IndexerError> {
+        let metrics = Self {
+            blocks_processed: IntCounter::new(
+                "indexer_blocks_processed_total",
+                "Blocks whose handlers have run",
+            )?,
+            events_processed: IntCounter::new(
+                "indexer_events_processed_total",
+                "Events dispatched to handlers",
+            )?,
+            handler_errors: IntCounterVec::new(
+                Opts::new(
+                    "indexer_handler_errors_total",
+                    "Handler failures, by handler",
+                ),
+                &["handler"],
+            )?,
+            block_duration: Histogram::with_opts(
+                HistogramOpts::new(
+                    "indexer_block_processing_seconds",
+                    "Time spent running the handlers over one block",
+                )
+                .buckets(DURATION_BUCKETS.to_vec()),
+            )?,
+            chain_head: IntGauge::new("indexer_chain_head", "Latest block seen on the chain")?,
+            last_processed: IntGauge::new(
+                "indexer_last_processed_block",
+                "Latest block whose handlers have run",
+            )?,
+            lag: IntGauge::new(
+                "indexer_chain_lag_blocks",
+                "Blocks between the chain head and the last processed block",
+            )?,
+            breaker_state: IntGaugeVec::new(
+                Opts::new(
+                    "indexer_breaker_state",
+                    "Circuit breaker state: 0 closed, 1 half-open, 2 open",
+                ),
+                &["breaker"],
+            )?,
+            retries: IntCounter::new("indexer_rpc_retries_total", "RPC calls retried")?,
+            give_ups: IntCounter::new(
+                "indexer_rpc_retry_give_ups_total",
+                "RPC calls that failed after their retries",
+            )?,
+            sink_events: IntCounterVec::new(
+                Opts::new(
+                    "indexer_sink_events_total",
+                    "Events delivered or dropped by sinks, by sink and outcome",
+                ),
+                &["sink", "outcome"],
+            )?,
+            registry,
+        };
+        let collectors: [Box<dyn Collector>; 11] = [
+            Box::new(metrics.blocks_processed.clone()),
+            Box::new(metrics.events_processed.clone()),
+            Box::new(metrics.handler_errors.clone()),
+            Box::new(metrics.block_duration.clone()),
+            Box::new(metrics.chain_head.clone()),
+            Box::new(metrics.last_processed.clone()),
+            Box::new(metrics.lag.clone()),
+            Box::new(metrics.breaker_state.clone()),
+            Box::new(metrics.retries.clone()),
+            Box::new(metrics.give_ups.clone()),
+            Box::new(metrics.sink_events.clone()),
+        ];
+        for collector in collectors {
+            metrics.registry.register(collector)?;
+        }
+        Ok(metrics)
+    }
+
+    /// Registry holding the series, for registering more collectors.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Every series of the registry in the Prometheus text format.
+    pub fn render(&self) -> Result<String, IndexerError> {
+        Ok(TextEncoder::new().encode_to_string(&self.registry.gather())?)
+    }
+
+    /// Block `number`, with `events` events, went through the handlers in
+    /// `elapsed`.
+    pub fn observe_block(&self, number: BlockNumber, events: usize, elapsed: Duration) {
+        self.blocks_processed.inc();
+        self.events_processed.inc_by(events as u64);
+        self.block_duration.observe(elapsed.as_secs_f64());
+        self.last_processed.set(gauge_value(number));
+        self.update_lag();
+    }
+
+    /// The chain reached block `number`.
+    pub fn observe_head(&self, number: BlockNumber) {
+        if gauge_value(number) > self.chain_head.get() {
+            self.chain_head.set(gauge_value(number));
+            self.update_lag();
+        }
+    }
+
+    /// A handler failed with `error`.
+    pub fn record_handler_error(&self, error: &IndexerError) {
+        self.handler_errors
+            .with_label_values(&[handler_label(error)])
+            .inc();
+    }
+
+    /// `sink` delivered `events` events, or dropped them if not `delivered`.
+    pub fn record_sink_delivery(&self, sink: &str, events: usize, delivered: bool) {
+        let outcome = if delivered { "sent" } else { "failed" };
+        self.sink_events
+            .with_label_values(&[sink, outcome])
+            .inc_by(events as u64);
+    }
+
+    /// Track the state of `breaker`, labeled with its
+    /// [`domain`](CircuitBreaker::domain).
+    pub fn watch_breaker(&self, breaker: &CircuitBreaker) {
+        let gauge = self.breaker_state.with_label_values(&[breaker.domain()]);
+        gauge.set(breaker_value(&breaker.state()));
+        breaker.on_state_change(move |_, to| gauge.set(breaker_value(&to)));
+    }
+
+    fn update_lag(&self) {
+        let head = self.chain_head.get();
+        if head > 0 {
+            self.lag.set((head - self.last_processed.get()).max(0));
+        }
+    }
+}
+
+impl RetryObserver for IndexerMetrics {
+    fn on_retry(&self, _attempt: usize, _delay: Duration, _error: &IndexerError) {
+        self.retries.inc();
+    }
+
+    fn on_give_up(&self, _attempts: usize, _error: &IndexerError) {
+        self.give_ups.inc();
+    }
+}
+
+impl MetricsRecorder for IndexerMetrics {
+    fn observe_block(&self, number: BlockNumber, events: usize, elapsed: Duration) {
+        IndexerMetrics::observe_block(self, number, events, elapsed);
+    }
+
+    fn observe_head(&self, number: BlockNumber) {
+        IndexerMetrics::observe_head(self, number);
+    }
+
+    fn record_handler_error(&self, error: &IndexerError) {
+        IndexerMetrics::record_handler_error(self, error);
+    }
+
+    fn record_sink_delivery(&self, sink: &str, events: usize, delivered: bool) {
+        IndexerMetrics::record_sink_delivery(self, sink, events, delivered);
+    }
+
+    fn record_breaker_state(&self, domain: &'static str, state: &BreakerState) {
+        self.breaker_state
+            .with_label_values(&[domain])
+            .set(breaker_value(state));
+    }
+}
+
+/// Background task serving `GET /metrics`; stopped when dropped.
+pub struct MetricsServer {
+    local_addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl MetricsServer {
+    /// Listen on `addr` and answer `GET /metrics` with `metrics` rendered in the
+    /// Prometheus text format. Other paths get a 404.
+    pub async fn bind(
+        addr: SocketAddr,
+        metrics: Arc<IndexerMetrics>,
+    ) -> Result<Self, IndexerError> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        info!(target: "indexer", %local_addr, "serving metrics");
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        debug!(target: "indexer", "metrics listener failed to accept: {e}");
+                        tokio::time::sleep(ACCEPT_BACKOFF).await;
+                        continue;
+                    }
+                };
+                let metrics = metrics.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = respond(stream, &metrics).await {
+                        debug!(target: "indexer", "metrics request failed: {e}");
+                    }
+                });
+            }
+        });
+        Ok(Self { local_addr, task })
+    }
+
+    /// Address the server listens on, useful when bound to port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Answer one HTTP/1.1 request and close the connection.
+async fn respond(mut stream: TcpStream, metrics: &IndexerMetrics) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 || head.len() + n > MAX_REQUEST_HEAD {
+            return Ok(());
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    let request_line = head.split(|&b| b == b'\r').next().unwrap_or_default();
+    let mut parts = request_line.split(|&b| b == b' ');
+    let (method, path) = (parts.next(), parts.next());
+    let path = path.map(|path| path.split(|&b| b == b'?').next().unwrap_or_default());
+
+    let (status, body) = match (method, path) {
+        (Some(b"GET"), Some(b"/metrics")) => match metrics.render() {
+            Ok(body) => ("200 OK", body),
+            Err(e) => ("500 Internal Server Error", format!("{e}\n")),
+        },
+        (Some(b"GET"), _) => ("404 Not Found", "not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "method not allowed\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,342 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Publish events to NATS JetStream, with the `nats` feature.
+
+use crate::error::IndexerError;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::recorder::MetricsRecorder;
+use crate::types::{BlockNumber, ChainEvent, EventEnvelope};
+use async_nats::jetstream;
+use async_nats::{Client, ConnectOptions, Event};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use subxt::config::HashFor;
+use subxt::Config;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+const HANDLER_NAME: &str = "NatsSinkHandler";
+
+/// What [`NatsSinkHandler`] does with a message JetStream did not acknowledge.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnUnreachable {
+    /// Fail the handler with a retryable [`IndexerError::HandlerFailed`].
+    #[default]
+    Fail,
+    /// Keep up to this many messages and publish them, in order, before the next
+    /// one. The handler fails once the buffer is full.
+    Buffer(usize),
+}
+
+/// Replace the characters NATS does not allow in a subject token (whitespace,
+/// `.`, `*` and `>`) with `_`. An empty name becomes `_`.
+pub fn sanitize_token(name: &str) -> String {
+    if name.is_empty() {
+        return "_".to_string();
+    }
+    name.chars()
+        .map(|c| match c {
+            '.' | '*' | '>' => '_',
+            c if c.is_whitespace() || c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Handler publishing every event that passes its filter to JetStream as a JSON
+/// [`EventEnvelope`], on the subject `{prefix}.{pallet}.{event}`.
+///
+/// Each publish waits for the stream's acknowledgement. The client reconnects on
+/// its own; connection events are logged, and a message that is not
+/// acknowledged is handled according to [`OnUnreachable`]. Buffered messages
+/// are published when the indexer stops, see [`Handler::on_shutdown`], or by
+/// calling [`flush`](Self::flush).
+///
+/// Buffered messages live in memory only, so [`Handler::on_checkpoint`] holds
+/// the indexer's checkpoint back to the block before the oldest of them, and
+/// their blocks are handled again after a crash. Delivery is therefore at
+/// least once, and consumers can detect duplicates by the envelope's
+/// [`EventId`](crate::types::EventId).
+///
+/// The stream itself is not created: configure one capturing `{prefix}.>`.
+///
+/// ```no_run
+/// # use flamewire_bittensor_indexer::{EventFilter, IndexerError};
+/// use flamewire_bittensor_indexer::nats::{NatsSinkHandler, OnUnreachable};
+///
+/// # async fn example() -> Result<(), IndexerError> {
+/// let sink = NatsSinkHandler::new("nats://localhost:4222", "bittensor.events")
+///     .await?
+///     .with_filter(EventFilter::pallet("SubtensorModule"))
+///     .on_unreachable(OnUnreachable::Buffer(10_000));
+/// # Ok(())
+/// # }
+/// ```
+pub struct NatsSinkHandler {
+    client: Client,
+    jetstream: jetstream::Context,
+    prefix: String,
+    filter: EventFilter,
+    on_unreachable: OnUnreachable,
+    /// Unacknowledged messages with their block, oldest first. Held across
+    /// publishes to keep their order.
+    pending: Mutex<VecDeque<(u64, String, Vec<u8>)>>,
+    /// Block of the newest event, for errors raised outside a block.
+    last_block: AtomicU64,
+    published: AtomicU64,
+    failed: AtomicU64,
+    /// The indexer's metrics, taken from the first [`Context`] providing them.
+    metrics: OnceLock<Arc<dyn MetricsRecorder>>,
+}
+
+impl NatsSinkHandler {
+    /// Connect to the NATS server at `url` and publish under `subject_prefix`, e.g.
+    /// `"bittensor.events"`.
+    pub async fn new(
+        url: impl AsRef<str>,
+        subject_prefix: impl Into<String>,
+    ) -> Result<Self, IndexerError> {
+        let subject_prefix = subject_prefix.into();
+        validate_prefix(&subject_prefix)?;
+        let client = ConnectOptions::new()
+            .event_callback(|event| async move {
+                match event {
+                    Event::Connected => info!(target: "indexer", "nats connected"),
+                    event => warn!(target: "indexer", %event, "nats connection event"),
+                }
+            })
+            .connect(url.as_ref())
+            .await
+            .map_err(|e| IndexerError::handler_failed(HANDLER_NAME, 0, e).retryable(true))?;
+        Self::with_client(client, subject_prefix)
+    }
+
+    /// Publish with an already connected `client`, e.g. one built with custom
+    /// [`ConnectOptions`].
+    pub fn with_client(
+        client: Client,
+        subject_prefix: impl Into<String>,
+    ) -> Result<Self, IndexerError> {
+        let prefix = subject_prefix.into();
+        validate_prefix(&prefix)?;
+        Ok(Self {
+            jetstream: jetstream::new(client.clone()),
+            client,
+            prefix,
+            filter: EventFilter::all(),
+            on_unreachable: OnUnreachable::default(),
+            pending: Mutex::new(VecDeque::new()),
+            last_block: AtomicU64::new(0),
+            published: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            metrics: OnceLock::new(),
+        })
+    }
+
+    /// Only publish events passing `filter`. Every event by default.
+    pub fn with_filter(mut self, filter: EventFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// What to do when JetStream does not acknowledge a message,
+    /// [`OnUnreachable::Fail`] by default.
+    pub fn on_unreachable(mut self, on_unreachable: OnUnreachable) -> Self {
+        self.on_unreachable = on_unreachable;
+        self
+    }
+
+    /// Wait up to `timeout` for each acknowledgement.
+    pub fn with_ack_timeout(mut self, timeout: Duration) -> Self {
+        self.jetstream.set_timeout(timeout);
+        self
+    }
+
+    /// Subject the events `pallet.event` are published on.
+    pub fn subject(&self, pallet: &str, event: &str) -> String {
+        format!(
+            "{}.{}.{}",
+            self.prefix,
+            sanitize_token(pallet),
+            sanitize_token(event)
+        )
+    }
+
+    /// Messages acknowledged so far.
+    pub fn published(&self) -> u64 {
+        self.published.load(Ordering::Relaxed)
+    }
+
+    /// Messages dropped because they could not be published or buffered.
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Messages waiting in the [`OnUnreachable::Buffer`].
+    pub async fn buffered(&self) -> usize {
+        self.pending.lock().await.len()
+    }
+
+    /// Publish the buffered messages and flush the client.
+    pub async fn flush(&self) -> Result<(), IndexerError> {
+        let mut pending = self.pending.lock().await;
+        let block = self.last_block.load(Ordering::Relaxed);
+        self.drain(&mut pending)
+            .await
+            .map_err(|e| IndexerError::handler_failed(HANDLER_NAME, block, e).retryable(true))?;
+        self.client
+            .flush()
+            .await
+            .map_err(|e| IndexerError::handler_failed(HANDLER_NAME, block, e).retryable(true))
+    }
+
+    async fn send(
+        &self,
+        subject: String,
+        payload: Vec<u8>,
+        block: u64,
+    ) -> Result<(), IndexerError> {
+        let mut pending = self.pending.lock().await;
+        let res = match self.drain(&mut pending).await {
+            Ok(()) => self.publish(&subject, payload.clone()).await,
+            Err(e) => Err(e),
+        };
+        match res {
+            Ok(()) => Ok(()),
+            Err(e) => match self.on_unreachable {
+                OnUnreachable::Buffer(max) if pending.len() < max => {
+                    if pending.is_empty() {
+                        warn!(target: "indexer", error = %e, "nats unreachable, buffering messages");
+                    }
+                    pending.push_back((block, subject, payload));
+                    Ok(())
+                }
+                _ => {
+                    self.record(false);
+                    Err(IndexerError::handler_failed(HANDLER_NAME, block, e).retryable(true))
+                }
+            },
+        }
+    }
+
+    /// Publish the buffered messages, oldest first, stopping at the first failure.
+    async fn drain(
+        &self,
+        pending: &mut VecDeque<(u64, String, Vec<u8>)>,
+    ) -> Result<(), jetstream::context::PublishError> {
+        while let Some((_, subject, payload)) = pending.front() {
+            self.publish(subject, payload.clone()).await?;
+            pending.pop_front();
+        }
+        Ok(())
+    }
+
+    async fn publish(
+        &self,
+        subject: &str,
+        payload: Vec<u8>,
+    ) -> Result<(), jetstream::context::PublishError> {
+        self.jetstream
+            .publish(subject.to_string(), payload.into())
+            .await?
+            .await?;
+        self.record(true);
+        Ok(())
+    }
+
+    fn record(&self, published: bool) {
+        let counter = if published {
+            &self.published
+        } else {
+            &self.failed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(metrics) = self.metrics.get() {
+            metrics.record_sink_delivery("nats", 1, published);
+        }
+    }
+
+    fn attach_metrics<C: Config>(&self, ctx: &Context<C>) {
+        if let Some(recorder) = ctx.recorder() {
+            self.metrics.get_or_init(|| recorder.clone());
+        }
+    }
+}
+
+fn validate_prefix(prefix: &str) -> Result<(), IndexerError> {
+    let valid = !prefix.is_empty()
+        && prefix.split('.').all(|token| {
+            !token.is_empty()
+                && token
+                    .chars()
+                    .all(|c| !matches!(c, '*' | '>') && !c.is_whitespace())
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(IndexerError::invalid_config(
+            "subject_prefix",
+            format!("`{prefix}` is not a NATS subject without wildcards"),
+        ))
+    }
+}
+
+#[async_trait]
+impl<C> Handler<C> for NatsSinkHandler
+where
+    C: Config,
+    HashFor<C>: Serialize,
+{
+    fn name(&self) -> &str {
+        HANDLER_NAME
+    }
+
+    fn event_filter(&self) -> EventFilter {
+        self.filter
+    }
+
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<C>,
+        ctx: &Context<C>,
+    ) -> Result<(), IndexerError> {
+        self.attach_metrics(ctx);
+        self.last_block.store(ctx.block_number, Ordering::Relaxed);
+        let subject = self.subject(event.pallet_name(), event.variant_name());
+        let payload = serde_json::to_vec(&EventEnvelope::from_event(event, ctx))?;
+        self.send(subject, payload, ctx.block_number).await
+    }
+
+    async fn handle_error(&self, error: &IndexerError, _ctx: &Context<C>) {
+        warn!(target: "indexer", %error, subject_prefix = %self.prefix, "nats publish failed");
+    }
+
+    async fn on_checkpoint(&self, block: BlockNumber) -> Result<Option<BlockNumber>, IndexerError> {
+        Ok(match self.pending.lock().await.front() {
+            Some((first, _, _)) => first.checked_sub(1),
+            None => Some(block),
+        })
+    }
+
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        self.flush().await
+    }
+}
@@ -0,0 +1,366 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Write events to Parquet files for analytics, with the `parquet` feature.
+
+use crate::error::IndexerError;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::types::{BlockNumber, ChainEvent};
+use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt32Array, UInt64Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use subxt::events::StaticEvent;
+use subxt::Config;
+use tracing::warn;
+
+const HANDLER_NAME: &str = "ParquetExportHandler";
+
+/// Rows that can be written as Arrow record batches.
+pub trait ToArrow: Sized {
+    /// Schema of every batch.
+    fn schema() -> SchemaRef;
+
+    /// `rows` as one batch with [`schema`](Self::schema).
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, ArrowError>;
+}
+
+/// Row of [`ParquetSchema::envelope`]: the event's position and names, with its
+/// fields as produced by [`ChainEvent::to_json`], `None` if they fail to decode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvelopeRow {
+    pub block_number: u64,
+    pub event_index: u32,
+    pub pallet: String,
+    pub event: String,
+    pub fields: Option<String>,
+}
+
+impl ToArrow for EnvelopeRow {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("block_number", DataType::UInt64, false),
+            Field::new("event_index", DataType::UInt32, false),
+            Field::new("pallet", DataType::Utf8, false),
+            Field::new("event", DataType::Utf8, false),
+            Field::new("fields", DataType::Utf8, true),
+        ]))
+    }
+
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, ArrowError> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|row| row.block_number),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                rows.iter().map(|row| row.event_index),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| row.pallet.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| row.event.as_str()),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.fields.as_deref()),
+            )),
+        ];
+        RecordBatch::try_new(Self::schema(), columns)
+    }
+}
+
+type RowFn<C, R> =
+    dyn Fn(&ChainEvent<C>, &Context<C>) -> Result<Option<R>, IndexerError> + Send + Sync;
+
+/// How a [`ParquetExportHandler`] turns events into rows, and the file name prefix.
+pub struct ParquetSchema<C: Config, R> {
+    name: String,
+    filter: EventFilter,
+    row: Box<RowFn<C, R>>,
+}
+
+impl<C: Config, R: ToArrow> ParquetSchema<C, R> {
+    /// One row per event passing `filter`, built by `row`. Returning `None` skips
+    /// the event.
+    pub fn custom<F>(name: impl Into<String>, filter: EventFilter, row: F) -> Self
+    where
+        F: Fn(&ChainEvent<C>, &Context<C>) -> Result<Option<R>, IndexerError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self {
+            name: name.into(),
+            filter,
+            row: Box::new(row),
+        }
+    }
+
+    /// One row per `E` event, built by `extract` from the decoded event.
+    pub fn typed<E, F>(name: impl Into<String>, extract: F) -> Self
+    where
+        E: StaticEvent + 'static,
+        F: Fn(E, &Context<C>) -> R + Send + Sync + 'static,
+    {
+        Self::custom(
+            name,
+            EventFilter::event(E::PALLET, E::EVENT),
+            move |event, ctx| {
+                let decoded =
+                    event
+                        .as_event::<E>()
+                        .map_err(|source| IndexerError::EventDecodingFailed {
+                            pallet: E::PALLET.into(),
+                            event: E::EVENT.into(),
+                            block: ctx.block_number,
+                            source,
+                        })?;
+                Ok(decoded.map(|decoded| extract(decoded, ctx)))
+            },
+        )
+    }
+}
+
+impl<C: Config> ParquetSchema<C, EnvelopeRow> {
+    /// An [`EnvelopeRow`] for every event passing `filter`.
+    pub fn envelope(name: impl Into<String>, filter: EventFilter) -> Self {
+        Self::custom(name, filter, |event, ctx| {
+            Ok(Some(EnvelopeRow {
+                block_number: ctx.block_number,
+                event_index: event.index(),
+                pallet: event.pallet_name().to_string(),
+                event: event.variant_name().to_string(),
+                fields: event.to_json().ok().map(|fields| fields.to_string()),
+            }))
+        })
+    }
+}
+
+/// File being written, under a temporary name until closed.
+struct OpenFile {
+    writer: ArrowWriter<File>,
+    tmp_path: PathBuf,
+    path: PathBuf,
+}
+
+struct State<R> {
+    /// Rows of the row group being collected.
+    rows: Vec<R>,
+    /// Row group and file range of the buffered rows.
+    group: Option<(u64, u64)>,
+    file: Option<(u64, OpenFile)>,
+    /// First block with rows not yet in a complete file.
+    first_pending: Option<u64>,
+}
+
+/// Handler writing one Parquet row per matching event.
+///
+/// Rows are buffered and written as one row group per `row_group_blocks`
+/// blocks, into one file per `file_blocks` blocks, both aligned to multiples
+/// of their size. Files are named `{name}_{start}_{end}.parquet` with `end`
+/// exclusive, e.g. `transfers_0001000000_0001100000.parquet`, and are written
+/// under a `.tmp` name until complete. When the indexer stops, including at
+/// its `end_block`, [`Handler::on_shutdown`] writes the buffered rows and
+/// completes the open file. Until a file is complete its rows are not durable,
+/// so [`Handler::on_checkpoint`] holds the indexer's checkpoint back to the
+/// block before its first row, and a crash resumes from there, rewriting the
+/// incomplete file. Blocks written again after a restart go to a new
+/// file with a `_1`, `_2`, ... suffix rather than replacing a complete one.
+pub struct ParquetExportHandler<C: Config, R: ToArrow> {
+    dir: PathBuf,
+    schema: ParquetSchema<C, R>,
+    row_group_blocks: u64,
+    file_blocks: u64,
+    compression: Compression,
+    state: Mutex<State<R>>,
+}
+
+impl<C: Config, R: ToArrow> ParquetExportHandler<C, R> {
+    /// Write the rows of `schema` to files in `dir`, created if missing.
+    pub fn new(dir: impl Into<PathBuf>, schema: ParquetSchema<C, R>) -> Result<Self, IndexerError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            schema,
+            row_group_blocks: 1_000,
+            file_blocks: 100_000,
+            compression: Compression::ZSTD(ZstdLevel::default()),
+            state: Mutex::new(State {
+                rows: Vec::new(),
+                group: None,
+                file: None,
+                first_pending: None,
+            }),
+        })
+    }
+
+    /// Write a row group every `blocks` blocks, 1 000 by default.
+    pub fn row_group_blocks(mut self, blocks: u64) -> Self {
+        self.row_group_blocks = blocks.max(1);
+        self
+    }
+
+    /// Start a new file every `blocks` blocks, 100 000 by default.
+    pub fn file_blocks(mut self, blocks: u64) -> Self {
+        self.file_blocks = blocks.max(1);
+        self
+    }
+
+    /// Compress columns with `compression`, zstd at its default level by default.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Write the buffered rows and complete the open file.
+    pub fn flush(&self) -> Result<(), IndexerError> {
+        let mut state = self.state.lock().unwrap();
+        self.write_group(&mut state)?;
+        self.close_file(&mut state)
+    }
+
+    fn add_block(&self, block: u64, rows: Vec<R>) -> Result<(), IndexerError> {
+        let group = (block / self.row_group_blocks, block / self.file_blocks);
+        let mut state = self.state.lock().unwrap();
+        if state.group.is_some_and(|buffered| buffered != group) {
+            self.write_group(&mut state)?;
+        }
+        if state
+            .file
+            .as_ref()
+            .is_some_and(|(range, _)| *range != group.1)
+        {
+            self.close_file(&mut state)?;
+        }
+        if !rows.is_empty() {
+            state.rows.extend(rows);
+            state.group = Some(group);
+            state.first_pending.get_or_insert(block);
+        }
+        Ok(())
+    }
+
+    /// Write the buffered rows as a row group of the file for their range.
+    fn write_group(&self, state: &mut State<R>) -> Result<(), IndexerError> {
+        let Some((_, range)) = state.group.take() else {
+            return Ok(());
+        };
+        let rows = std::mem::take(&mut state.rows);
+        let first_block = range * self.file_blocks;
+        let failed = |e| IndexerError::handler_failed(HANDLER_NAME, first_block, e);
+        let batch = R::to_record_batch(&rows)
+            .map_err(|e| IndexerError::handler_failed(HANDLER_NAME, first_block, e))?;
+        if state.file.is_none() {
+            state.file = Some((range, self.open_file(range)?));
+        }
+        let Some((_, file)) = &mut state.file else {
+            return Ok(());
+        };
+        file.writer.write(&batch).map_err(failed)?;
+        file.writer.flush().map_err(failed)
+    }
+
+    fn close_file(&self, state: &mut State<R>) -> Result<(), IndexerError> {
+        let Some((range, file)) = state.file.take() else {
+            return Ok(());
+        };
+        file.writer
+            .close()
+            .map_err(|e| IndexerError::handler_failed(HANDLER_NAME, range * self.file_blocks, e))?;
+        fs::rename(&file.tmp_path, &file.path)?;
+        state.first_pending = None;
+        Ok(())
+    }
+
+    fn open_file(&self, range: u64) -> Result<OpenFile, IndexerError> {
+        let start = range * self.file_blocks;
+        let end = start.saturating_add(self.file_blocks);
+        let stem = format!("{}_{start:010}_{end:010}", self.schema.name);
+        let mut path = self.dir.join(format!("{stem}.parquet"));
+        let mut part = 0;
+        while path.exists() {
+            part += 1;
+            path = self.dir.join(format!("{stem}_{part}.parquet"));
+        }
+        if part > 0 {
+            warn!(
+                target: "indexer",
+                path = %path.display(),
+                "blocks already exported, writing a new part"
+            );
+        }
+        let tmp_path = path.with_extension("parquet.tmp");
+        let props = WriterProperties::builder()
+            .set_compression(self.compression)
+            .build();
+        let writer = ArrowWriter::try_new(File::create(&tmp_path)?, R::schema(), Some(props))
+            .map_err(|e| IndexerError::handler_failed(HANDLER_NAME, start, e))?;
+        Ok(OpenFile {
+            writer,
+            tmp_path,
+            path,
+        })
+    }
+}
+
+#[async_trait]
+impl<C, R> Handler<C> for ParquetExportHandler<C, R>
+where
+    C: Config,
+    R: ToArrow + Send + 'static,
+{
+    fn name(&self) -> &str {
+        HANDLER_NAME
+    }
+
+    fn event_filter(&self) -> EventFilter {
+        self.schema.filter
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        let mut rows = Vec::new();
+        for event in events {
+            if self.schema.filter.matches_event(event) {
+                if let Some(row) = (self.schema.row)(event, ctx)? {
+                    rows.push(row);
+                }
+            }
+        }
+        self.add_block(ctx.block_number, rows)
+    }
+
+    async fn on_checkpoint(&self, block: BlockNumber) -> Result<Option<BlockNumber>, IndexerError> {
+        let state = self.state.lock().unwrap();
+        Ok(match state.first_pending {
+            Some(first) => first.checked_sub(1),
+            None => Some(block),
+        })
+    }
+
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        self.flush()
+    }
+}
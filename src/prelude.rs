@@ -15,19 +15,30 @@
  */
 
 pub use crate::builder::IndexerBuilder;
-pub use crate::config::IndexerConfig;
+pub use crate::config::{FinalityMode, IndexerConfig, Preset, StartFrom, TlsMode};
 pub use crate::error::IndexerError;
+pub use crate::extrinsics::ChainExtrinsic;
+pub use crate::filters;
 pub use crate::handler::{Context, EventFilter, Handler};
 pub use crate::handler_group::HandlerGroup;
 pub use crate::indexer::Indexer;
+pub use crate::run::{RunSummary, StopReason};
+pub use crate::ss58::{self, DisplayBittensor};
 pub use crate::storage::CheckpointStore;
-pub use crate::types::{BlockNumber, ChainEvent};
-pub use crate::validated_types::{PostgresUrl, SqliteUrl, WebSocketUrl};
+#[cfg(feature = "json-storage")]
+pub use crate::types::EventEnvelope;
+pub use crate::types::{BlockNumber, ChainEvent, EventId, EventSummary, FieldValueExt};
+pub use crate::units::Rao;
+pub use crate::validated_types::{BlockRange, PostgresUrl, SqliteUrl, WebSocketUrl};
 
 pub use async_trait::async_trait;
 pub use parity_scale_codec::Decode;
 pub use scale_decode::DecodeAsType;
-pub use subxt::{config::substrate::SubstrateConfig, events::StaticEvent, utils::AccountId32};
+pub use subxt::{
+    config::substrate::SubstrateConfig,
+    events::{Phase, RootEvent, StaticEvent},
+    utils::AccountId32,
+};
 
 pub use parity_scale_codec;
 pub use scale_decode;
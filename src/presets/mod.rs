@@ -0,0 +1,29 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Ready-made pipelines for common indexing tasks. `transfers` needs the
+//! `postgres` or `sqlite` feature, `ownership` and `registrations` the
+//! `bittensor-events` one (`ownership` also a database feature for its SQL
+//! store), and `subnet_metrics` both `bittensor-events` and `metrics`.
+
+#[cfg(feature = "bittensor-events")]
+pub mod ownership;
+#[cfg(feature = "bittensor-events")]
+pub mod registrations;
+#[cfg(all(feature = "bittensor-events", feature = "metrics"))]
+pub mod subnet_metrics;
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+pub mod transfers;
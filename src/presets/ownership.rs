@@ -0,0 +1,549 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Track which coldkey owns each hotkey, block by block.
+//!
+//! [`OwnershipTracker`] follows the `SubtensorModule` events that change
+//! ownership and records each change in an [`OwnershipStore`], keyed by hotkey
+//! and the block it took effect. The history answers "who owned this hotkey at
+//! block N", which other handlers reach through [`OwnershipLookup`].
+
+use crate::error::IndexerError;
+use crate::events::subtensor::{ColdkeySwapped, HotkeySwapped, NeuronRegistered};
+use crate::filters;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::subtensor_storage;
+use crate::types::ChainEvent;
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use subxt::utils::AccountId32;
+use subxt::Config;
+
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+use crate::ss58::{encode_bittensor, parse_ss58};
+
+const HANDLER_NAME: &str = "OwnershipTracker";
+
+/// Pipeline data key under which [`OwnershipTracker`] publishes its
+/// [`OwnershipLookup`] for the handlers after it.
+pub const PIPELINE_KEY: &str = "ownership";
+
+/// The coldkey owning a hotkey and the block that ownership started at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ownership {
+    pub coldkey: AccountId32,
+    pub since_block: u64,
+}
+
+/// Ownership history backing an [`OwnershipTracker`].
+///
+/// Changes are keyed by `(hotkey, block)`: writing the same key again replaces
+/// the earlier value, so re-processing a block is harmless.
+#[async_trait]
+pub trait OwnershipStore: Send + Sync {
+    /// Record that from `block` on `hotkey` is owned by `coldkey`, or by no one
+    /// when `coldkey` is `None`.
+    async fn set_owner(
+        &self,
+        hotkey: &AccountId32,
+        coldkey: Option<&AccountId32>,
+        block: u64,
+    ) -> Result<(), IndexerError>;
+
+    /// Owner of `hotkey` once `block` was applied.
+    async fn owner_at(
+        &self,
+        hotkey: &AccountId32,
+        block: u64,
+    ) -> Result<Option<Ownership>, IndexerError>;
+
+    /// Hotkeys owned by `coldkey` once `block` was applied, in no particular
+    /// order.
+    async fn hotkeys_at(
+        &self,
+        coldkey: &AccountId32,
+        block: u64,
+    ) -> Result<Vec<AccountId32>, IndexerError>;
+
+    /// Forget every change recorded at `from_block` or later.
+    async fn rollback(&self, from_block: u64) -> Result<(), IndexerError>;
+}
+
+#[async_trait]
+impl<T: OwnershipStore + ?Sized> OwnershipStore for Arc<T> {
+    async fn set_owner(
+        &self,
+        hotkey: &AccountId32,
+        coldkey: Option<&AccountId32>,
+        block: u64,
+    ) -> Result<(), IndexerError> {
+        (**self).set_owner(hotkey, coldkey, block).await
+    }
+
+    async fn owner_at(
+        &self,
+        hotkey: &AccountId32,
+        block: u64,
+    ) -> Result<Option<Ownership>, IndexerError> {
+        (**self).owner_at(hotkey, block).await
+    }
+
+    async fn hotkeys_at(
+        &self,
+        coldkey: &AccountId32,
+        block: u64,
+    ) -> Result<Vec<AccountId32>, IndexerError> {
+        (**self).hotkeys_at(coldkey, block).await
+    }
+
+    async fn rollback(&self, from_block: u64) -> Result<(), IndexerError> {
+        (**self).rollback(from_block).await
+    }
+}
+
+/// In-memory [`OwnershipStore`], lost on restart.
+#[derive(Debug, Default)]
+pub struct MemoryOwnershipStore {
+    history: Mutex<HashMap<AccountId32, BTreeMap<u64, Option<AccountId32>>>>,
+}
+
+impl MemoryOwnershipStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn latest(changes: &BTreeMap<u64, Option<AccountId32>>, block: u64) -> Option<Ownership> {
+    let (since_block, coldkey) = changes.range(..=block).next_back()?;
+    Some(Ownership {
+        coldkey: coldkey.clone()?,
+        since_block: *since_block,
+    })
+}
+
+#[async_trait]
+impl OwnershipStore for MemoryOwnershipStore {
+    async fn set_owner(
+        &self,
+        hotkey: &AccountId32,
+        coldkey: Option<&AccountId32>,
+        block: u64,
+    ) -> Result<(), IndexerError> {
+        let mut history = self.history.lock().unwrap();
+        history
+            .entry(hotkey.clone())
+            .or_default()
+            .insert(block, coldkey.cloned());
+        Ok(())
+    }
+
+    async fn owner_at(
+        &self,
+        hotkey: &AccountId32,
+        block: u64,
+    ) -> Result<Option<Ownership>, IndexerError> {
+        let history = self.history.lock().unwrap();
+        Ok(history
+            .get(hotkey)
+            .and_then(|changes| latest(changes, block)))
+    }
+
+    async fn hotkeys_at(
+        &self,
+        coldkey: &AccountId32,
+        block: u64,
+    ) -> Result<Vec<AccountId32>, IndexerError> {
+        let history = self.history.lock().unwrap();
+        Ok(history
+            .iter()
+            .filter(|(_, changes)| {
+                latest(changes, block).is_some_and(|owner| &owner.coldkey == coldkey)
+            })
+            .map(|(hotkey, _)| hotkey.clone())
+            .collect())
+    }
+
+    async fn rollback(&self, from_block: u64) -> Result<(), IndexerError> {
+        let mut history = self.history.lock().unwrap();
+        history.retain(|_, changes| {
+            changes.split_off(&from_block);
+            !changes.is_empty()
+        });
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS hotkey_owners (
+    hotkey TEXT NOT NULL,
+    since_block BIGINT NOT NULL,
+    coldkey TEXT,
+    PRIMARY KEY (hotkey, since_block)
+)";
+
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+enum Pool {
+    #[cfg(feature = "postgres")]
+    Postgres(sqlx::PgPool),
+    #[cfg(feature = "sqlite")]
+    Sqlite(sqlx::SqlitePool),
+}
+
+/// [`OwnershipStore`] in a `hotkey_owners` table, one row per change. Keys are
+/// Bittensor SS58; a released hotkey has a `NULL` coldkey.
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+pub struct SqlOwnershipStore {
+    pool: Pool,
+}
+
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+impl SqlOwnershipStore {
+    #[cfg(feature = "postgres")]
+    pub fn postgres(pool: sqlx::PgPool) -> Self {
+        Self {
+            pool: Pool::Postgres(pool),
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(pool: sqlx::SqlitePool) -> Self {
+        Self {
+            pool: Pool::Sqlite(pool),
+        }
+    }
+
+    /// Create the `hotkey_owners` table if it does not exist yet.
+    pub async fn create_schema(&self) -> Result<(), IndexerError> {
+        match &self.pool {
+            #[cfg(feature = "postgres")]
+            Pool::Postgres(pool) => {
+                sqlx::query(CREATE_TABLE).execute(pool).await?;
+            }
+            #[cfg(feature = "sqlite")]
+            Pool::Sqlite(pool) => {
+                sqlx::query(CREATE_TABLE).execute(pool).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+#[async_trait]
+impl OwnershipStore for SqlOwnershipStore {
+    async fn set_owner(
+        &self,
+        hotkey: &AccountId32,
+        coldkey: Option<&AccountId32>,
+        block: u64,
+    ) -> Result<(), IndexerError> {
+        let hotkey = encode_bittensor(hotkey);
+        let coldkey = coldkey.map(encode_bittensor);
+        match &self.pool {
+            #[cfg(feature = "postgres")]
+            Pool::Postgres(pool) => {
+                sqlx::query(
+                    "INSERT INTO hotkey_owners (hotkey, since_block, coldkey)
+                     VALUES ($1, $2, $3)
+                     ON CONFLICT (hotkey, since_block) DO UPDATE SET coldkey = excluded.coldkey",
+                )
+                .bind(&hotkey)
+                .bind(block as i64)
+                .bind(&coldkey)
+                .execute(pool)
+                .await?;
+            }
+            #[cfg(feature = "sqlite")]
+            Pool::Sqlite(pool) => {
+                sqlx::query(
+                    "INSERT INTO hotkey_owners (hotkey, since_block, coldkey)
+                     VALUES (?, ?, ?)
+                     ON CONFLICT (hotkey, since_block) DO UPDATE SET coldkey = excluded.coldkey",
+                )
+                .bind(&hotkey)
+                .bind(block as i64)
+                .bind(&coldkey)
+                .execute(pool)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn owner_at(
+        &self,
+        hotkey: &AccountId32,
+        block: u64,
+    ) -> Result<Option<Ownership>, IndexerError> {
+        let hotkey = encode_bittensor(hotkey);
+        let row: Option<(Option<String>, i64)> = match &self.pool {
+            #[cfg(feature = "postgres")]
+            Pool::Postgres(pool) => {
+                sqlx::query_as(
+                    "SELECT coldkey, since_block FROM hotkey_owners
+                     WHERE hotkey = $1 AND since_block <= $2
+                     ORDER BY since_block DESC LIMIT 1",
+                )
+                .bind(&hotkey)
+                .bind(block as i64)
+                .fetch_optional(pool)
+                .await?
+            }
+            #[cfg(feature = "sqlite")]
+            Pool::Sqlite(pool) => {
+                sqlx::query_as(
+                    "SELECT coldkey, since_block FROM hotkey_owners
+                     WHERE hotkey = ? AND since_block <= ?
+                     ORDER BY since_block DESC LIMIT 1",
+                )
+                .bind(&hotkey)
+                .bind(block as i64)
+                .fetch_optional(pool)
+                .await?
+            }
+        };
+        match row {
+            Some((Some(coldkey), since_block)) => Ok(Some(Ownership {
+                coldkey: parse_ss58(&coldkey)?,
+                since_block: since_block as u64,
+            })),
+            _ => Ok(None),
+        }
+    }
+
+    async fn hotkeys_at(
+        &self,
+        coldkey: &AccountId32,
+        block: u64,
+    ) -> Result<Vec<AccountId32>, IndexerError> {
+        let coldkey = encode_bittensor(coldkey);
+        let rows: Vec<(String,)> = match &self.pool {
+            #[cfg(feature = "postgres")]
+            Pool::Postgres(pool) => {
+                sqlx::query_as(
+                    "SELECT o.hotkey FROM hotkey_owners o
+                     WHERE o.coldkey = $1 AND o.since_block = (
+                         SELECT MAX(since_block) FROM hotkey_owners
+                         WHERE hotkey = o.hotkey AND since_block <= $2
+                     )",
+                )
+                .bind(&coldkey)
+                .bind(block as i64)
+                .fetch_all(pool)
+                .await?
+            }
+            #[cfg(feature = "sqlite")]
+            Pool::Sqlite(pool) => {
+                sqlx::query_as(
+                    "SELECT o.hotkey FROM hotkey_owners o
+                     WHERE o.coldkey = ?1 AND o.since_block = (
+                         SELECT MAX(since_block) FROM hotkey_owners
+                         WHERE hotkey = o.hotkey AND since_block <= ?2
+                     )",
+                )
+                .bind(&coldkey)
+                .bind(block as i64)
+                .fetch_all(pool)
+                .await?
+            }
+        };
+        rows.iter().map(|(hotkey,)| parse_ss58(hotkey)).collect()
+    }
+
+    async fn rollback(&self, from_block: u64) -> Result<(), IndexerError> {
+        match &self.pool {
+            #[cfg(feature = "postgres")]
+            Pool::Postgres(pool) => {
+                sqlx::query("DELETE FROM hotkey_owners WHERE since_block >= $1")
+                    .bind(from_block as i64)
+                    .execute(pool)
+                    .await?;
+            }
+            #[cfg(feature = "sqlite")]
+            Pool::Sqlite(pool) => {
+                sqlx::query("DELETE FROM hotkey_owners WHERE since_block >= ?")
+                    .bind(from_block as i64)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read-only handle on the ownership history of an [`OwnershipTracker`].
+///
+/// Get one from [`OwnershipTracker::lookup_handle`] when building the
+/// pipeline, or from the context of any block the tracker already handled:
+///
+/// ```no_run
+/// # use flamewire_bittensor_indexer::prelude::*;
+/// # use flamewire_bittensor_indexer::presets::ownership::{self, OwnershipLookup};
+/// # async fn owner_of(
+/// #     ctx: &Context<SubstrateConfig>,
+/// #     hotkey: &AccountId32,
+/// # ) -> Result<(), IndexerError> {
+/// let owners = ctx
+///     .peek_pipeline_data::<OwnershipLookup>(ownership::PIPELINE_KEY)
+///     .expect("OwnershipTracker runs before this handler");
+/// let owner = owners.lookup(hotkey, ctx.block_number).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct OwnershipLookup {
+    store: Arc<dyn OwnershipStore>,
+}
+
+impl OwnershipLookup {
+    /// Owner of `hotkey` once `at_block` was applied.
+    pub async fn lookup(
+        &self,
+        hotkey: &AccountId32,
+        at_block: u64,
+    ) -> Result<Option<Ownership>, IndexerError> {
+        self.store.owner_at(hotkey, at_block).await
+    }
+
+    /// Hotkeys owned by `coldkey` once `at_block` was applied.
+    pub async fn hotkeys_of(
+        &self,
+        coldkey: &AccountId32,
+        at_block: u64,
+    ) -> Result<Vec<AccountId32>, IndexerError> {
+        self.store.hotkeys_at(coldkey, at_block).await
+    }
+}
+
+/// Handler keeping an [`OwnershipStore`] in step with the chain.
+///
+/// - `NeuronRegistered` for a hotkey not yet known reads its owner from
+///   `SubtensorModule.Owner` at the block.
+/// - `HotkeySwapped` hands the new hotkey to the coldkey and releases the old
+///   one.
+/// - `ColdkeySwapped` moves every hotkey of the old coldkey to the new one.
+///
+/// A change that matches the recorded owner is skipped, so restarts and
+/// re-processed blocks add no rows. [`Handler::on_reorg`] drops the changes
+/// of the abandoned blocks. Every block, the tracker publishes an
+/// [`OwnershipLookup`] under [`PIPELINE_KEY`] for the handlers after it.
+pub struct OwnershipTracker {
+    store: Arc<dyn OwnershipStore>,
+    seed_from_storage: bool,
+    seeded: AtomicBool,
+}
+
+impl OwnershipTracker {
+    pub fn new(store: impl OwnershipStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+            seed_from_storage: false,
+            seeded: AtomicBool::new(false),
+        }
+    }
+
+    /// Load the whole `SubtensorModule.Owner` map at the first handled block,
+    /// for indexers that do not start from genesis. Off by default.
+    pub fn seed_from_storage(mut self, seed: bool) -> Self {
+        self.seed_from_storage = seed;
+        self
+    }
+
+    pub fn lookup_handle(&self) -> OwnershipLookup {
+        OwnershipLookup {
+            store: self.store.clone(),
+        }
+    }
+
+    async fn record(
+        &self,
+        hotkey: &AccountId32,
+        coldkey: Option<&AccountId32>,
+        block: u64,
+    ) -> Result<(), IndexerError> {
+        let current = self.store.owner_at(hotkey, block).await?;
+        if current.as_ref().map(|owner| &owner.coldkey) == coldkey {
+            return Ok(());
+        }
+        self.store.set_owner(hotkey, coldkey, block).await
+    }
+
+    async fn seed<C: Config>(&self, ctx: &Context<C>) -> Result<(), IndexerError> {
+        for (hotkey, coldkey) in subtensor_storage::owners(ctx).await? {
+            self.record(&hotkey, Some(&coldkey), ctx.block_number)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn apply<C: Config>(
+        &self,
+        ctx: &Context<C>,
+        event: &ChainEvent<C>,
+    ) -> Result<(), IndexerError> {
+        let block = ctx.block_number;
+        if let Some(NeuronRegistered(_, _, hotkey)) = event.as_event::<NeuronRegistered>()? {
+            if self.store.owner_at(&hotkey, block).await?.is_none() {
+                if let Some(coldkey) = subtensor_storage::owner(ctx, &hotkey).await? {
+                    self.record(&hotkey, Some(&coldkey), block).await?;
+                }
+            }
+        } else if let Some(swap) = event.as_event::<HotkeySwapped>()? {
+            self.record(&swap.old_hotkey, None, block).await?;
+            self.record(&swap.new_hotkey, Some(&swap.coldkey), block)
+                .await?;
+        } else if let Some(swap) = event.as_event::<ColdkeySwapped>()? {
+            for hotkey in self.store.hotkeys_at(&swap.old_coldkey, block).await? {
+                self.record(&hotkey, Some(&swap.new_coldkey), block).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<C: Config> Handler<C> for OwnershipTracker {
+    fn event_filter(&self) -> EventFilter {
+        filters::subtensor::pallet()
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        ctx.set_pipeline_data(PIPELINE_KEY, self.lookup_handle());
+        if self.seed_from_storage && !self.seeded.load(Ordering::Acquire) {
+            self.seed(ctx)
+                .await
+                .map_err(|e| IndexerError::handler_failed(HANDLER_NAME, ctx.block_number, e))?;
+            self.seeded.store(true, Ordering::Release);
+        }
+        for event in events {
+            self.apply(ctx, event).await.map_err(|e| {
+                IndexerError::handler_failed(HANDLER_NAME, ctx.block_number, e)
+                    .with_event_index(event.index())
+            })?;
+        }
+        Ok(())
+    }
+
+    async fn on_reorg(&self, from: u64, _to: u64) -> Result<(), IndexerError> {
+        self.store.rollback(from).await
+    }
+}
@@ -0,0 +1,162 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Enrich `NeuronRegistered` with what registering cost on the subnet.
+//!
+//! [`RegistrationCosts`] reads `Difficulty(netuid)` and `Burn(netuid)` for
+//! every registration and publishes the block's [`RegistrationRecord`]s as
+//! pipeline data under [`PIPELINE_KEY`], for a later handler to store.
+
+use crate::error::IndexerError;
+use crate::events::subtensor::{NeuronRegistered, NEURON_REGISTERED};
+use crate::handler::{Context, EventFilter, Handler};
+use crate::subtensor_storage;
+use crate::types::ChainEvent;
+use crate::units::Rao;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use subxt::utils::AccountId32;
+use subxt::Config;
+
+const HANDLER_NAME: &str = "RegistrationCosts";
+
+/// Pipeline data key of the `Vec<RegistrationRecord>` published each block.
+pub const PIPELINE_KEY: &str = "registrations";
+
+/// Registration cost of a subnet at one block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegistrationCost {
+    pub difficulty: u64,
+    pub burn: Rao,
+}
+
+/// A `NeuronRegistered` event with the subnet's cost at its block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegistrationRecord {
+    pub block_number: u64,
+    pub event_index: u32,
+    pub netuid: u16,
+    pub uid: u16,
+    pub hotkey: AccountId32,
+    pub difficulty: u64,
+    pub burn: Rao,
+}
+
+/// Where [`RegistrationCosts`] reads a subnet's registration cost.
+#[async_trait]
+pub trait RegistrationCostSource<C: Config>: Send + Sync {
+    async fn cost(&self, ctx: &Context<C>, netuid: u16) -> Result<RegistrationCost, IndexerError>;
+}
+
+#[async_trait]
+impl<C: Config, T: RegistrationCostSource<C> + ?Sized> RegistrationCostSource<C> for Arc<T> {
+    async fn cost(&self, ctx: &Context<C>, netuid: u16) -> Result<RegistrationCost, IndexerError> {
+        (**self).cost(ctx, netuid).await
+    }
+}
+
+/// Reads the cost from chain storage through [`Context::storage`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChainStorage;
+
+#[async_trait]
+impl<C: Config> RegistrationCostSource<C> for ChainStorage {
+    async fn cost(&self, ctx: &Context<C>, netuid: u16) -> Result<RegistrationCost, IndexerError> {
+        Ok(RegistrationCost {
+            difficulty: subtensor_storage::difficulty(ctx, netuid).await?,
+            burn: subtensor_storage::burn(ctx, netuid).await?.into(),
+        })
+    }
+}
+
+/// Handler pairing each `NeuronRegistered` with [`RegistrationCost`].
+///
+/// The cost is read once per subnet and block, from the state after the
+/// block. Every block gets a record list in the pipeline data, empty when
+/// nothing registered, so later handlers can take it with
+/// `ctx.get_pipeline_data::<Vec<RegistrationRecord>>(PIPELINE_KEY)`.
+pub struct RegistrationCosts<S = ChainStorage> {
+    source: S,
+}
+
+impl RegistrationCosts {
+    pub fn new() -> Self {
+        Self {
+            source: ChainStorage,
+        }
+    }
+}
+
+impl Default for RegistrationCosts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> RegistrationCosts<S> {
+    /// Read costs from `source` instead of chain storage.
+    pub fn with_source(source: S) -> Self {
+        Self { source }
+    }
+}
+
+#[async_trait]
+impl<C: Config, S: RegistrationCostSource<C>> Handler<C> for RegistrationCosts<S> {
+    fn event_filter(&self) -> EventFilter {
+        NEURON_REGISTERED
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        let mut costs = HashMap::new();
+        let mut records = Vec::new();
+        for event in events {
+            let failed = |e| {
+                IndexerError::handler_failed(HANDLER_NAME, ctx.block_number, e)
+                    .with_event_index(event.index())
+            };
+            let registered = event
+                .as_event::<NeuronRegistered>()
+                .map_err(|e| failed(IndexerError::from(e)))?;
+            let Some(NeuronRegistered(netuid, uid, hotkey)) = registered else {
+                continue;
+            };
+            let cost = match costs.get(&netuid) {
+                Some(cost) => *cost,
+                None => {
+                    let cost = self.source.cost(ctx, netuid).await.map_err(failed)?;
+                    costs.insert(netuid, cost);
+                    cost
+                }
+            };
+            records.push(RegistrationRecord {
+                block_number: ctx.block_number,
+                event_index: event.index(),
+                netuid,
+                uid,
+                hotkey,
+                difficulty: cost.difficulty,
+                burn: cost.burn,
+            });
+        }
+        ctx.set_pipeline_data(PIPELINE_KEY, records);
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Per-subnet Prometheus counters for `SubtensorModule` activity.
+//!
+//! [`SubnetMetrics`] registers its counters in a [`prometheus::Registry`] and
+//! labels them by `netuid`. Netuids above [`SubnetMetrics::max_netuid`] share
+//! the `other` label, so the number of series stays bounded whatever the chain
+//! does.
+
+use crate::error::IndexerError;
+use crate::events::subtensor::{AxonServed, NeuronRegistered, StakeAdded, StakeRemoved};
+use crate::filters;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::types::ChainEvent;
+use async_trait::async_trait;
+use prometheus::{IntCounterVec, Opts, Registry};
+use std::collections::HashSet;
+use subxt::Config;
+
+const HANDLER_NAME: &str = "SubnetMetrics";
+
+/// Default for [`SubnetMetrics::max_netuid`].
+pub const DEFAULT_MAX_NETUID: u16 = 1024;
+
+/// Label of the netuids above [`SubnetMetrics::max_netuid`].
+pub const OTHER_NETUID: &str = "other";
+
+/// Handler counting subnet activity into Prometheus counters:
+///
+/// - `bittensor_subnet_registrations_total{netuid}` from `NeuronRegistered`
+/// - `bittensor_subnet_axons_served_total{netuid}` from `AxonServed`
+/// - `bittensor_subnet_stake_added_rao_total{netuid}` and
+///   `bittensor_subnet_stake_removed_rao_total{netuid}` from `StakeAdded` and
+///   `StakeRemoved`
+/// - `bittensor_subnet_events_total{netuid, event}` for the events passed to
+///   [`track_event`](Self::track_event), such as the runtime's emission events
+///
+/// Stake events from before dynamic TAO carry no netuid and are not counted.
+/// Registrations per block are the rate of the registrations counter over the
+/// block time.
+///
+/// ```no_run
+/// # use flamewire_bittensor_indexer::prelude::*;
+/// # use flamewire_bittensor_indexer::presets::subnet_metrics::SubnetMetrics;
+/// # fn build() -> Result<(), IndexerError> {
+/// let registry = prometheus::Registry::new();
+/// let metrics = SubnetMetrics::register(&registry)?.track_event("EmissionValuesSet");
+/// # Ok(())
+/// # }
+/// ```
+pub struct SubnetMetrics {
+    registrations: IntCounterVec,
+    axons_served: IntCounterVec,
+    stake_added: IntCounterVec,
+    stake_removed: IntCounterVec,
+    events: IntCounterVec,
+    tracked: HashSet<String>,
+    max_netuid: u16,
+}
+
+impl SubnetMetrics {
+    /// Create the counters and register them in `registry`. Fails if the
+    /// registry already holds counters with the same names.
+    pub fn register(registry: &Registry) -> Result<Self, IndexerError> {
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let counter = IntCounterVec::new(Opts::new(name, help), labels)?;
+            registry.register(Box::new(counter.clone()))?;
+            Ok::<_, IndexerError>(counter)
+        };
+        Ok(Self {
+            registrations: counter(
+                "bittensor_subnet_registrations_total",
+                "Neurons registered, by subnet",
+                &["netuid"],
+            )?,
+            axons_served: counter(
+                "bittensor_subnet_axons_served_total",
+                "Axons served, by subnet",
+                &["netuid"],
+            )?,
+            stake_added: counter(
+                "bittensor_subnet_stake_added_rao_total",
+                "Stake added in RAO, by subnet",
+                &["netuid"],
+            )?,
+            stake_removed: counter(
+                "bittensor_subnet_stake_removed_rao_total",
+                "Stake removed in RAO, by subnet",
+                &["netuid"],
+            )?,
+            events: counter(
+                "bittensor_subnet_events_total",
+                "Tracked SubtensorModule events, by subnet and event",
+                &["netuid", "event"],
+            )?,
+            tracked: HashSet::new(),
+            max_netuid: DEFAULT_MAX_NETUID,
+        })
+    }
+
+    /// Also count `SubtensorModule.<event>` in `bittensor_subnet_events_total`.
+    /// The netuid is read from a field named `netuid`, or else from the first
+    /// field; events where neither holds a `u16` are not counted.
+    pub fn track_event(mut self, event: impl Into<String>) -> Self {
+        self.tracked.insert(event.into());
+        self
+    }
+
+    /// Highest netuid given its own label; higher ones are labeled
+    /// [`OTHER_NETUID`].
+    pub fn max_netuid(mut self, max_netuid: u16) -> Self {
+        self.max_netuid = max_netuid;
+        self
+    }
+
+    fn label(&self, netuid: u16) -> String {
+        if netuid > self.max_netuid {
+            OTHER_NETUID.to_string()
+        } else {
+            netuid.to_string()
+        }
+    }
+
+    fn observe<C: Config>(&self, event: &ChainEvent<C>) -> Result<(), Box<subxt::Error>> {
+        if let Some(NeuronRegistered(netuid, _, _)) = event.as_event::<NeuronRegistered>()? {
+            self.registrations
+                .with_label_values(&[self.label(netuid).as_str()])
+                .inc();
+        } else if let Some(AxonServed(netuid, _)) = event.as_event::<AxonServed>()? {
+            self.axons_served
+                .with_label_values(&[self.label(netuid).as_str()])
+                .inc();
+        } else if let Some(StakeAdded::V2(stake)) = StakeAdded::from_event(event)? {
+            self.stake_added
+                .with_label_values(&[self.label(stake.4).as_str()])
+                .inc_by(stake.2);
+        } else if let Some(StakeRemoved::V2(stake)) = StakeRemoved::from_event(event)? {
+            self.stake_removed
+                .with_label_values(&[self.label(stake.4).as_str()])
+                .inc_by(stake.2);
+        }
+
+        if self.tracked.contains(event.variant_name()) {
+            let netuid = match event.field("netuid")? {
+                Some(value) => Some(value),
+                None => event.field_at(0)?,
+            }
+            .and_then(|value| value.as_u128())
+            .and_then(|n| u16::try_from(n).ok());
+            if let Some(netuid) = netuid {
+                self.events
+                    .with_label_values(&[self.label(netuid).as_str(), event.variant_name()])
+                    .inc();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<C: Config> Handler<C> for SubnetMetrics {
+    fn event_filter(&self) -> EventFilter {
+        filters::subtensor::pallet()
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        for event in events {
+            if event.pallet_name() != filters::subtensor::PALLET {
+                continue;
+            }
+            self.observe(event).map_err(|e| {
+                IndexerError::handler_failed(HANDLER_NAME, ctx.block_number, *e)
+                    .with_event_index(event.index())
+            })?;
+        }
+        Ok(())
+    }
+}
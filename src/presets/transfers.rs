@@ -0,0 +1,213 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Index every `Balances.Transfer` into a `transfers` table.
+
+use crate::error::IndexerError;
+use crate::filters;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::ss58::encode_bittensor;
+use crate::types::ChainEvent;
+use crate::units::Rao;
+use async_trait::async_trait;
+use scale_decode::DecodeAsType;
+use subxt::events::StaticEvent;
+use subxt::utils::AccountId32;
+use subxt::Config;
+
+const HANDLER_NAME: &str = "TransferIndexer";
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS transfers (
+    block_number BIGINT NOT NULL,
+    event_index INTEGER NOT NULL,
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    timestamp BIGINT,
+    PRIMARY KEY (block_number, event_index)
+)";
+
+#[derive(DecodeAsType)]
+struct Transfer {
+    from: AccountId32,
+    to: AccountId32,
+    amount: u64,
+}
+
+impl StaticEvent for Transfer {
+    const PALLET: &'static str = filters::balances::PALLET;
+    const EVENT: &'static str = "Transfer";
+}
+
+/// One row of the `transfers` table. Addresses are Bittensor SS58; the
+/// `amount` column holds the RAO integer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferRow {
+    pub block_number: u64,
+    pub event_index: u32,
+    pub from: String,
+    pub to: String,
+    pub amount: Rao,
+    /// Block timestamp in milliseconds, see [`Context::timestamp`].
+    pub timestamp: Option<u64>,
+}
+
+enum Pool {
+    #[cfg(feature = "postgres")]
+    Postgres(sqlx::PgPool),
+    #[cfg(feature = "sqlite")]
+    Sqlite(sqlx::SqlitePool),
+}
+
+/// Handler writing `Balances.Transfer` events to the `transfers` table.
+///
+/// The transfers of a block are inserted in one transaction from
+/// [`Handler::handle_block`]. Rows are keyed by block number and event index,
+/// so re-processing a block does not duplicate them.
+pub struct TransferIndexer {
+    pool: Pool,
+}
+
+impl TransferIndexer {
+    #[cfg(feature = "postgres")]
+    pub fn postgres(pool: sqlx::PgPool) -> Self {
+        Self {
+            pool: Pool::Postgres(pool),
+        }
+    }
+
+    /// ```no_run
+    /// # use flamewire_bittensor_indexer::prelude::*;
+    /// # use flamewire_bittensor_indexer::presets::transfers::TransferIndexer;
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let pool = sqlx::SqlitePool::connect("sqlite://transfers.db?mode=rwc").await?;
+    /// let transfers = TransferIndexer::sqlite(pool);
+    /// transfers.create_schema().await?;
+    ///
+    /// let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+    ///     .connect(WebSocketUrl::parse("wss://archive.chain.opentensor.ai:443")?)
+    ///     .add_handler(transfers)
+    ///     .build()
+    ///     .await?;
+    /// indexer.run().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(pool: sqlx::SqlitePool) -> Self {
+        Self {
+            pool: Pool::Sqlite(pool),
+        }
+    }
+
+    /// Create the `transfers` table if it does not exist yet.
+    pub async fn create_schema(&self) -> Result<(), IndexerError> {
+        match &self.pool {
+            #[cfg(feature = "postgres")]
+            Pool::Postgres(pool) => {
+                sqlx::query(CREATE_TABLE).execute(pool).await?;
+            }
+            #[cfg(feature = "sqlite")]
+            Pool::Sqlite(pool) => {
+                sqlx::query(CREATE_TABLE).execute(pool).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn insert(&self, rows: &[TransferRow]) -> Result<(), IndexerError> {
+        match &self.pool {
+            #[cfg(feature = "postgres")]
+            Pool::Postgres(pool) => {
+                let mut tx = pool.begin().await?;
+                for row in rows {
+                    sqlx::query(
+                        "INSERT INTO transfers
+                            (block_number, event_index, from_address, to_address, amount, timestamp)
+                         VALUES ($1, $2, $3, $4, $5, $6)
+                         ON CONFLICT (block_number, event_index) DO NOTHING",
+                    )
+                    .bind(row.block_number as i64)
+                    .bind(row.event_index as i32)
+                    .bind(&row.from)
+                    .bind(&row.to)
+                    .bind(row.amount.get() as i64)
+                    .bind(row.timestamp.map(|t| t as i64))
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                tx.commit().await?;
+            }
+            #[cfg(feature = "sqlite")]
+            Pool::Sqlite(pool) => {
+                let mut tx = pool.begin().await?;
+                for row in rows {
+                    sqlx::query(
+                        "INSERT INTO transfers
+                            (block_number, event_index, from_address, to_address, amount, timestamp)
+                         VALUES (?, ?, ?, ?, ?, ?)
+                         ON CONFLICT (block_number, event_index) DO NOTHING",
+                    )
+                    .bind(row.block_number as i64)
+                    .bind(row.event_index as i32)
+                    .bind(&row.from)
+                    .bind(&row.to)
+                    .bind(row.amount.get() as i64)
+                    .bind(row.timestamp.map(|t| t as i64))
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                tx.commit().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<C: Config> Handler<C> for TransferIndexer {
+    fn event_filter(&self) -> EventFilter {
+        filters::balances::TRANSFER
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        let mut rows = Vec::new();
+        for event in events {
+            let transfer = event.as_event::<Transfer>().map_err(|e| {
+                IndexerError::handler_failed(HANDLER_NAME, ctx.block_number, *e)
+                    .with_event_index(event.index())
+            })?;
+            if let Some(transfer) = transfer {
+                rows.push(TransferRow {
+                    block_number: ctx.block_number,
+                    event_index: event.index(),
+                    from: encode_bittensor(&transfer.from),
+                    to: encode_bittensor(&transfer.to),
+                    amount: transfer.amount.into(),
+                    timestamp: ctx.timestamp(),
+                });
+            }
+        }
+        if rows.is_empty() {
+            return Ok(());
+        }
+        self.insert(&rows).await
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Bounded hand-off between block fetching and handler dispatch.
+//!
+//! The indexer fetches blocks ahead of the handlers through a channel of fixed
+//! capacity. Once it is full the fetch stage waits, so slow handlers slow down RPC
+//! calls instead of letting fetched blocks pile up in memory.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Default number of fetched blocks buffered ahead of the handlers.
+pub const DEFAULT_QUEUE_DEPTH: usize = 16;
+
+/// Live view of a block queue: how many blocks are waiting for the handlers.
+#[derive(Debug)]
+pub struct QueueGauge {
+    capacity: usize,
+    depth: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl QueueGauge {
+    /// Gauge for a queue holding at most `capacity` items.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            depth: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    /// Maximum number of buffered items.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Items currently buffered. Equal to [`capacity`](Self::capacity) while the
+    /// consumer is the bottleneck.
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::SeqCst)
+    }
+
+    /// Highest depth observed since the gauge was created.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+
+    fn push(&self) {
+        let depth = self.depth.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(depth, Ordering::SeqCst);
+    }
+
+    fn pop(&self) {
+        self.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Create a bounded queue whose capacity and depth are tracked by `gauge`.
+///
+/// # Panics
+///
+/// Panics if the gauge capacity is `0`.
+pub fn bounded<T>(gauge: Arc<QueueGauge>) -> (QueueSender<T>, QueueReceiver<T>) {
+    let (tx, rx) = mpsc::channel(gauge.capacity);
+    (
+        QueueSender {
+            tx,
+            gauge: gauge.clone(),
+        },
+        QueueReceiver { rx, gauge },
+    )
+}
+
+/// Producing half of a [`bounded`] queue.
+pub struct QueueSender<T> {
+    tx: mpsc::Sender<T>,
+    gauge: Arc<QueueGauge>,
+}
+
+impl<T> QueueSender<T> {
+    /// Wait for a free slot and enqueue `item`.
+    ///
+    /// Returns the item back if the receiver has been dropped.
+    pub async fn send(&self, item: T) -> Result<(), T> {
+        match self.tx.reserve().await {
+            Ok(permit) => {
+                // Count the item before it becomes visible to the receiver.
+                self.gauge.push();
+                permit.send(item);
+                Ok(())
+            }
+            Err(_) => Err(item),
+        }
+    }
+}
+
+/// Consuming half of a [`bounded`] queue.
+pub struct QueueReceiver<T> {
+    rx: mpsc::Receiver<T>,
+    gauge: Arc<QueueGauge>,
+}
+
+impl<T> QueueReceiver<T> {
+    /// Take the next item, or `None` once every sender is gone and the queue is empty.
+    pub async fn recv(&mut self) -> Option<T> {
+        let item = self.rx.recv().await?;
+        self.gauge.pop();
+        Some(item)
+    }
+}
+
+impl<T> Drop for QueueReceiver<T> {
+    fn drop(&mut self) {
+        // Items left behind are discarded; keep the gauge in step.
+        self.rx.close();
+        while self.rx.try_recv().is_ok() {
+            self.gauge.pop();
+        }
+    }
+}
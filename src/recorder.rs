@@ -0,0 +1,99 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The metrics facade the indexer, handler groups and sinks report through, so
+//! the Prometheus and StatsD backends are interchangeable.
+
+// Without a backend only the no-op recorder is used.
+#![cfg_attr(
+    not(any(feature = "metrics", feature = "statsd")),
+    allow(dead_code, unused_imports)
+)]
+
+use crate::error::IndexerError;
+use crate::retry::{BreakerState, CircuitBreaker, RetryObserver};
+use crate::types::BlockNumber;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Label of handler errors that do not name their handler.
+pub const UNKNOWN_HANDLER: &str = "unknown";
+
+/// Backend receiving the indexer's metrics. Retries and give-ups of RPC calls
+/// arrive through [`RetryObserver`].
+pub(crate) trait MetricsRecorder: RetryObserver {
+    /// Block `number`, with `events` events, went through the handlers in
+    /// `elapsed`.
+    fn observe_block(&self, number: BlockNumber, events: usize, elapsed: Duration);
+
+    /// The chain reached block `number`.
+    fn observe_head(&self, number: BlockNumber);
+
+    /// A handler failed with `error`.
+    fn record_handler_error(&self, error: &IndexerError);
+
+    /// `sink` delivered `events` events, or dropped them if not `delivered`.
+    fn record_sink_delivery(&self, sink: &str, events: usize, delivered: bool);
+
+    /// The breaker guarding `domain` moved to `state`.
+    fn record_breaker_state(&self, domain: &'static str, state: &BreakerState);
+}
+
+/// Recorder used when no metrics backend is configured.
+pub(crate) struct NoopRecorder;
+
+impl RetryObserver for NoopRecorder {}
+
+impl MetricsRecorder for NoopRecorder {
+    fn observe_block(&self, _number: BlockNumber, _events: usize, _elapsed: Duration) {}
+
+    fn observe_head(&self, _number: BlockNumber) {}
+
+    fn record_handler_error(&self, _error: &IndexerError) {}
+
+    fn record_sink_delivery(&self, _sink: &str, _events: usize, _delivered: bool) {}
+
+    fn record_breaker_state(&self, _domain: &'static str, _state: &BreakerState) {}
+}
+
+/// Record the current state of `breaker` and every later transition.
+pub(crate) fn watch_breaker(recorder: Arc<dyn MetricsRecorder>, breaker: &CircuitBreaker) {
+    let domain = breaker.domain();
+    recorder.record_breaker_state(domain, &breaker.state());
+    breaker.on_state_change(move |_, to| recorder.record_breaker_state(domain, &to));
+}
+
+/// Name of the handler that failed with `error`, or [`UNKNOWN_HANDLER`].
+pub(crate) fn handler_label(error: &IndexerError) -> &str {
+    match error {
+        IndexerError::HandlerFailed { handler, .. } => handler.as_str(),
+        _ => UNKNOWN_HANDLER,
+    }
+}
+
+/// Breaker state as a gauge value: 0 closed, 1 half-open, 2 open.
+pub(crate) fn breaker_value(state: &BreakerState) -> i64 {
+    match state {
+        BreakerState::Closed => 0,
+        BreakerState::HalfOpen => 1,
+        BreakerState::Open { .. } => 2,
+    }
+}
+
+/// Block number as a gauge value.
+pub(crate) fn gauge_value(number: BlockNumber) -> i64 {
+    i64::try_from(number).unwrap_or(i64::MAX)
+}
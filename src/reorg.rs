@@ -0,0 +1,135 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Detection of reorganized blocks when following best blocks.
+//!
+//! [`ReorgTracker`] remembers the hashes of the last `max_reorg_depth + 1` indexed
+//! blocks. When the node's chain no longer contains the tip, it walks back to the most
+//! recent common ancestor and reports the indexed blocks above it as a [`Reorg`].
+
+use crate::error::IndexerError;
+use crate::types::BlockNumber;
+use std::collections::VecDeque;
+use std::future::Future;
+
+/// Default for [`IndexerConfig::max_reorg_depth`](crate::config::IndexerConfig::max_reorg_depth).
+pub const DEFAULT_MAX_REORG_DEPTH: u32 = 64;
+
+/// Indexed blocks `from..=to` that are no longer on the node's chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reorg {
+    pub from: BlockNumber,
+    pub to: BlockNumber,
+}
+
+impl Reorg {
+    /// Number of unwound blocks.
+    pub fn depth(&self) -> u64 {
+        self.to - self.from + 1
+    }
+}
+
+/// Hashes of the most recently indexed blocks.
+pub struct ReorgTracker<H> {
+    max_depth: u32,
+    recent: VecDeque<(BlockNumber, H)>,
+}
+
+impl<H: Copy + PartialEq> ReorgTracker<H> {
+    pub fn new(max_depth: u32) -> Self {
+        Self {
+            max_depth,
+            recent: VecDeque::new(),
+        }
+    }
+
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    /// The most recently indexed block.
+    pub fn tip(&self) -> Option<BlockNumber> {
+        self.recent.back().map(|(number, _)| *number)
+    }
+
+    /// Hash recorded for `number`, if it is still in the history.
+    pub fn hash_of(&self, number: BlockNumber) -> Option<H> {
+        self.recent
+            .iter()
+            .find(|(n, _)| *n == number)
+            .map(|(_, hash)| *hash)
+    }
+
+    /// Record `hash` as indexed block `number`, forgetting any recorded block at or
+    /// above it and blocks too old to be unwound.
+    pub fn push(&mut self, number: BlockNumber, hash: H) {
+        while self.tip().is_some_and(|tip| tip >= number) {
+            self.recent.pop_back();
+        }
+        self.recent.push_back((number, hash));
+        while self.recent.len() > self.max_depth as usize + 1 {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Compare the history with the node's chain, where `canonical(n)` returns the hash
+    /// the node currently has at height `n`.
+    ///
+    /// Returns `None` while the tip is still canonical. Otherwise the unwound blocks are
+    /// dropped from the history and returned. When no recorded block is canonical and
+    /// the history holds at most `max_depth` blocks, as after starting from the latest
+    /// block, all of them are unwound. Fails with [`IndexerError::ReorgTooDeep`] when
+    /// more than `max_depth` blocks would have to be unwound; `depth` is then a lower
+    /// bound.
+    pub async fn check<F, Fut>(&mut self, mut canonical: F) -> Result<Option<Reorg>, IndexerError>
+    where
+        F: FnMut(BlockNumber) -> Fut,
+        Fut: Future<Output = Result<Option<H>, IndexerError>>,
+    {
+        let Some(tip) = self.tip() else {
+            return Ok(None);
+        };
+        let mut depth = 0;
+        for &(number, hash) in self.recent.iter().rev() {
+            depth = tip - number;
+            if depth > u64::from(self.max_depth) {
+                break;
+            }
+            if canonical(number).await? == Some(hash) {
+                if depth == 0 {
+                    return Ok(None);
+                }
+                self.recent.retain(|(n, _)| *n <= number);
+                return Ok(Some(Reorg {
+                    from: number + 1,
+                    to: tip,
+                }));
+            }
+            depth += 1;
+        }
+        if depth > u64::from(self.max_depth) {
+            return Err(IndexerError::ReorgTooDeep {
+                depth: u32::try_from(depth).unwrap_or(u32::MAX),
+                max: self.max_depth,
+            });
+        }
+        // The history ran out within `max_depth`: the blocks before it were never
+        // recorded, so unwind everything that was.
+        let from = self.recent.front().map_or(tip, |(number, _)| *number);
+        self.recent.clear();
+        Ok(Some(Reorg { from, to: tip }))
+    }
+}
@@ -0,0 +1,389 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Record fetched blocks to a file and replay them without a node.
+//!
+//! A recording holds, for each block, its hash, SCALE-encoded header and event
+//! bytes, plus the metadata of every runtime version it spans. Write one with
+//! [`Indexer::record_to`](crate::indexer::Indexer::record_to) and run the
+//! handlers over it again with
+//! [`IndexerBuilder::replay_from`](crate::builder::IndexerBuilder::replay_from).
+
+use crate::error::IndexerError;
+use crate::reorg::Reorg;
+use crate::types::BlockNumber;
+use parity_scale_codec::{Decode, Encode, Input};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use subxt::client::RuntimeVersion;
+use subxt::config::HashFor;
+use subxt::{Config, Metadata, OnlineClient};
+
+/// First bytes of every recording.
+const MAGIC: [u8; 4] = *b"BIRC";
+
+/// Version of the entry layout below.
+const FORMAT_VERSION: u32 = 1;
+
+/// One record of a recording file, written back to back after [`MAGIC`].
+#[derive(Encode, Decode)]
+enum Entry {
+    /// Always first.
+    Start {
+        format: u32,
+        genesis_hash: Vec<u8>,
+    },
+    /// The blocks that follow were produced by this runtime.
+    Runtime {
+        spec_version: u32,
+        transaction_version: u32,
+        /// SCALE-encoded `RuntimeMetadataPrefixed`, as returned by `state_getMetadata`.
+        metadata: Vec<u8>,
+    },
+    Block {
+        number: BlockNumber,
+        hash: Vec<u8>,
+        header: Vec<u8>,
+        events: Vec<u8>,
+    },
+    Reorg {
+        from: BlockNumber,
+        to: BlockNumber,
+    },
+}
+
+/// Writer of a recording file. Entries are flushed as they are written, so a
+/// recording cut short by a crash still replays up to its last block.
+pub struct RecordingWriter {
+    path: PathBuf,
+    state: Mutex<WriterState>,
+}
+
+struct WriterState {
+    file: BufWriter<File>,
+    /// Runtime of the blocks written last.
+    spec_version: Option<u32>,
+}
+
+impl RecordingWriter {
+    /// Create the recording at `path` for the chain with `genesis_hash`,
+    /// replacing any file there.
+    pub fn create(path: impl Into<PathBuf>, genesis_hash: &[u8]) -> Result<Self, IndexerError> {
+        let path = path.into();
+        let mut file = BufWriter::new(File::create(&path)?);
+        file.write_all(&MAGIC)?;
+        let writer = Self {
+            path,
+            state: Mutex::new(WriterState {
+                file,
+                spec_version: None,
+            }),
+        };
+        writer.write(&Entry::Start {
+            format: FORMAT_VERSION,
+            genesis_hash: genesis_hash.to_vec(),
+        })?;
+        Ok(writer)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Runtime version of the blocks written last, `None` before the first
+    /// [`runtime`](Self::runtime).
+    pub fn spec_version(&self) -> Option<u32> {
+        self.state.lock().unwrap().spec_version
+    }
+
+    /// Blocks written from now on were produced by the runtime with
+    /// `metadata`, the SCALE-encoded `RuntimeMetadataPrefixed`.
+    pub fn runtime(
+        &self,
+        spec_version: u32,
+        transaction_version: u32,
+        metadata: Vec<u8>,
+    ) -> Result<(), IndexerError> {
+        self.write(&Entry::Runtime {
+            spec_version,
+            transaction_version,
+            metadata,
+        })?;
+        self.state.lock().unwrap().spec_version = Some(spec_version);
+        Ok(())
+    }
+
+    /// Record block `number` with its SCALE-encoded hash and header and the
+    /// raw bytes of its `System.Events` storage value.
+    pub fn block(
+        &self,
+        number: BlockNumber,
+        hash: &[u8],
+        header: &[u8],
+        events: &[u8],
+    ) -> Result<(), IndexerError> {
+        if self.spec_version().is_none() {
+            return Err(IndexerError::invalid_config(
+                "record_to",
+                "a runtime must be recorded before the first block",
+            ));
+        }
+        self.write(&Entry::Block {
+            number,
+            hash: hash.to_vec(),
+            header: header.to_vec(),
+            events: events.to_vec(),
+        })
+    }
+
+    /// Record that blocks already written were unwound by `reorg`.
+    pub fn reorg(&self, reorg: Reorg) -> Result<(), IndexerError> {
+        self.write(&Entry::Reorg {
+            from: reorg.from,
+            to: reorg.to,
+        })
+    }
+
+    fn write(&self, entry: &Entry) -> Result<(), IndexerError> {
+        let mut state = self.state.lock().unwrap();
+        state.file.write_all(&entry.encode())?;
+        state.file.flush()?;
+        Ok(())
+    }
+}
+
+/// Input over a recording that tells a truncated entry from a corrupt one.
+struct Tracked<'a> {
+    bytes: &'a [u8],
+    /// Whether a read went past the end of `bytes`.
+    exhausted: bool,
+}
+
+impl<'a> Tracked<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            exhausted: false,
+        }
+    }
+}
+
+impl Input for Tracked<'_> {
+    /// Unknown, so that decoding reads up to the end of a truncated entry
+    /// instead of rejecting its length prefix up front.
+    fn remaining_len(&mut self) -> Result<Option<usize>, parity_scale_codec::Error> {
+        Ok(None)
+    }
+
+    fn read(&mut self, into: &mut [u8]) -> Result<(), parity_scale_codec::Error> {
+        if into.len() > self.bytes.len() {
+            self.exhausted = true;
+            return Err("not enough data to fill buffer".into());
+        }
+        let (head, rest) = self.bytes.split_at(into.len());
+        into.copy_from_slice(head);
+        self.bytes = rest;
+        Ok(())
+    }
+}
+
+/// A recorded block, as read back.
+#[derive(Clone, Debug)]
+pub(crate) struct RecordedBlock {
+    pub(crate) number: BlockNumber,
+    pub(crate) hash: Vec<u8>,
+    pub(crate) spec_version: u32,
+    pub(crate) events: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Recorded {
+    Block(RecordedBlock),
+    Reorg(Reorg),
+}
+
+#[derive(Clone, Debug)]
+struct RecordedRuntime {
+    transaction_version: u32,
+    metadata: Metadata,
+}
+
+/// A recording read into memory, replayed by the indexer in place of a node.
+///
+/// Replayed blocks go through the same dispatch pipeline as live ones, from
+/// the start block to the end block configured on the builder. Extrinsics,
+/// block authors and timestamps are not recorded, and storage queries from
+/// handlers fail since there is no node to answer them.
+#[derive(Clone, Debug)]
+pub struct ReplaySource {
+    path: PathBuf,
+    genesis_hash: Vec<u8>,
+    runtimes: BTreeMap<u32, RecordedRuntime>,
+    entries: Vec<Recorded>,
+}
+
+impl ReplaySource {
+    /// Read the recording at `path`. A truncated last entry, left by a
+    /// recording that was cut short, is ignored. Any other entry that fails to
+    /// decode is reported as corrupt.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, IndexerError> {
+        let path = path.into();
+        let bytes = std::fs::read(&path)?;
+        let invalid = |message: &str| {
+            IndexerError::Io(std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: {message}", path.display()),
+            ))
+        };
+        let mut input = bytes
+            .strip_prefix(&MAGIC[..])
+            .ok_or_else(|| invalid("not a block recording"))?;
+        let genesis_hash = match Entry::decode(&mut input) {
+            Ok(Entry::Start {
+                format: FORMAT_VERSION,
+                genesis_hash,
+            }) => genesis_hash,
+            Ok(Entry::Start { format, .. }) => {
+                return Err(invalid(&format!("unsupported format version {format}")))
+            }
+            _ => return Err(invalid("missing start entry")),
+        };
+
+        let mut runtimes = BTreeMap::new();
+        let mut entries = Vec::new();
+        let mut spec = None;
+        while !input.is_empty() {
+            let offset = bytes.len() - input.len();
+            let mut tracked = Tracked::new(input);
+            let entry = match Entry::decode(&mut tracked) {
+                Ok(entry) => entry,
+                Err(_) if tracked.exhausted => break,
+                Err(e) => return Err(invalid(&format!("corrupt entry at offset {offset}: {e}"))),
+            };
+            input = tracked.bytes;
+            match entry {
+                Entry::Start { .. } => return Err(invalid("unexpected start entry")),
+                Entry::Runtime {
+                    spec_version,
+                    transaction_version,
+                    metadata,
+                } => {
+                    let metadata = Metadata::decode(&mut &metadata[..]).map_err(|e| {
+                        invalid(&format!("metadata of spec version {spec_version}: {e}"))
+                    })?;
+                    runtimes.insert(
+                        spec_version,
+                        RecordedRuntime {
+                            transaction_version,
+                            metadata,
+                        },
+                    );
+                    spec = Some(spec_version);
+                }
+                Entry::Block {
+                    number,
+                    hash,
+                    events,
+                    ..
+                } => {
+                    let spec_version =
+                        spec.ok_or_else(|| invalid("block recorded before any runtime"))?;
+                    entries.push(Recorded::Block(RecordedBlock {
+                        number,
+                        hash,
+                        spec_version,
+                        events,
+                    }));
+                }
+                Entry::Reorg { from, to } => entries.push(Recorded::Reorg(Reorg { from, to })),
+            }
+        }
+        Ok(Self {
+            path,
+            genesis_hash,
+            runtimes,
+            entries,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of recorded blocks.
+    pub fn blocks(&self) -> usize {
+        self.recorded_blocks().count()
+    }
+
+    /// Lowest recorded block.
+    pub fn first_block(&self) -> Option<BlockNumber> {
+        self.recorded_blocks().map(|block| block.number).min()
+    }
+
+    /// Highest recorded block, the chain head of a replay.
+    pub fn last_block(&self) -> Option<BlockNumber> {
+        self.recorded_blocks().map(|block| block.number).max()
+    }
+
+    pub(crate) fn entries(&self) -> &[Recorded] {
+        &self.entries
+    }
+
+    /// Metadata and runtime version of the blocks recorded under `spec_version`.
+    pub(crate) fn runtime(&self, spec_version: u32) -> Option<(Metadata, RuntimeVersion)> {
+        self.runtimes.get(&spec_version).map(|runtime| {
+            (
+                runtime.metadata.clone(),
+                RuntimeVersion {
+                    spec_version,
+                    transaction_version: runtime.transaction_version,
+                },
+            )
+        })
+    }
+
+    /// A client set up with the first recorded runtime that never reaches a node.
+    pub(crate) fn client<C: Config>(&self) -> Result<OnlineClient<C>, IndexerError> {
+        let genesis_hash = HashFor::<C>::decode(&mut &self.genesis_hash[..]).map_err(|e| {
+            IndexerError::invalid_config("replay_from", format!("genesis hash: {e}"))
+        })?;
+        let spec_version = self
+            .recorded_blocks()
+            .next()
+            .map(|block| block.spec_version)
+            .or_else(|| self.runtimes.keys().next().copied())
+            .ok_or_else(|| {
+                IndexerError::invalid_config("replay_from", "the recording has no runtime")
+            })?;
+        let (metadata, version) = self.runtime(spec_version).expect("recorded runtime");
+        Ok(OnlineClient::from_rpc_client_with(
+            genesis_hash,
+            version,
+            metadata,
+            crate::rpc::offline_client(),
+        )?)
+    }
+
+    fn recorded_blocks(&self) -> impl Iterator<Item = &RecordedBlock> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Recorded::Block(block) => Some(block),
+            Recorded::Reorg(_) => None,
+        })
+    }
+}
@@ -0,0 +1,289 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Forward handler failures, retry give-ups and circuit breaker openings to an
+//! error tracker such as Sentry.
+
+use crate::error::IndexerError;
+use crate::retry::{BreakerState, CircuitBreaker, RetryObserver};
+use crate::types::BlockNumber;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::error;
+
+/// Blocks within which repeated reports from the same handler are dropped,
+/// unless set with [`IndexerBuilder::error_report_interval`](crate::builder::IndexerBuilder::error_report_interval).
+pub const DEFAULT_REPORT_INTERVAL: u64 = 100;
+
+/// What kind of failure a report is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReportKind {
+    /// A handler returned an error, counted by the indexer or absorbed by a
+    /// non-strict [`HandlerGroup`](crate::handler_group::HandlerGroup).
+    Handler,
+    /// An operation failed for good after exhausting its retries.
+    RetryGiveUp,
+    /// A circuit breaker opened.
+    BreakerOpen,
+    /// An event failed to decode and the rest of its block was skipped, with
+    /// [`skip_undecodable_events`](crate::builder::IndexerBuilder::skip_undecodable_events).
+    Decode,
+}
+
+impl ReportKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Handler => "handler",
+            Self::RetryGiveUp => "retry_give_up",
+            Self::BreakerOpen => "breaker_open",
+            Self::Decode => "decode",
+        }
+    }
+}
+
+impl fmt::Display for ReportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where a reported error happened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorContext {
+    pub kind: ReportKind,
+    /// Block being handled, when known.
+    pub block: Option<BlockNumber>,
+    /// Name of the failing handler, for [`ReportKind::Handler`].
+    pub handler: Option<String>,
+    /// Operations the breaker or retry policy guards, e.g. `rpc` or `storage`.
+    pub domain: Option<&'static str>,
+    /// Attempts made before giving up, for [`ReportKind::RetryGiveUp`].
+    pub attempt: Option<usize>,
+}
+
+impl ErrorContext {
+    /// Handler `handler` failed on `block`.
+    pub fn handler(handler: impl Into<String>, block: BlockNumber) -> Self {
+        Self {
+            kind: ReportKind::Handler,
+            block: Some(block),
+            handler: Some(handler.into()),
+            domain: None,
+            attempt: None,
+        }
+    }
+
+    /// An operation of `domain` failed after `attempts` attempts.
+    pub fn retry_give_up(domain: &'static str, attempts: usize) -> Self {
+        Self {
+            kind: ReportKind::RetryGiveUp,
+            block: None,
+            handler: None,
+            domain: Some(domain),
+            attempt: Some(attempts),
+        }
+    }
+
+    /// An event of `block` failed to decode.
+    pub fn decode(block: BlockNumber) -> Self {
+        Self {
+            kind: ReportKind::Decode,
+            block: Some(block),
+            handler: None,
+            domain: None,
+            attempt: None,
+        }
+    }
+
+    /// The breaker guarding `domain` opened.
+    pub fn breaker_open(domain: &'static str) -> Self {
+        Self {
+            kind: ReportKind::BreakerOpen,
+            block: None,
+            handler: None,
+            domain: Some(domain),
+            attempt: None,
+        }
+    }
+}
+
+/// Receives the errors the indexer recovers from, which would otherwise only
+/// show up in logs and metrics.
+///
+/// Register one with
+/// [`IndexerBuilder::error_reporter`](crate::builder::IndexerBuilder::error_reporter).
+/// `report` is called synchronously from the indexer and its breakers, so it
+/// should hand the error off rather than block.
+pub trait ErrorReporter: Send + Sync {
+    fn report(&self, error: &IndexerError, ctx: Option<&ErrorContext>);
+}
+
+impl<R: ErrorReporter + ?Sized> ErrorReporter for Arc<R> {
+    fn report(&self, error: &IndexerError, ctx: Option<&ErrorContext>) {
+        (**self).report(error, ctx);
+    }
+}
+
+/// [`ErrorReporter`] logging each report at error level, the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogReporter;
+
+impl ErrorReporter for LogReporter {
+    fn report(&self, error: &IndexerError, ctx: Option<&ErrorContext>) {
+        let Some(ctx) = ctx else {
+            error!(target: "indexer", %error, "reported error");
+            return;
+        };
+        error!(
+            target: "indexer",
+            kind = ctx.kind.as_str(),
+            block = ctx.block,
+            handler = ctx.handler.as_deref(),
+            domain = ctx.domain,
+            attempt = ctx.attempt,
+            %error,
+            "reported error"
+        );
+    }
+}
+
+/// [`ErrorReporter`] passing reports on to another one, but at most one per
+/// kind and handler (or domain) every `interval` blocks.
+///
+/// Reports without a block are always passed on. A report for an earlier block
+/// than the last one passed on, as after a restart or reorg, is passed on too.
+pub struct ThrottledReporter {
+    inner: Arc<dyn ErrorReporter>,
+    interval: u64,
+    last: Mutex<HashMap<(ReportKind, String), BlockNumber>>,
+    suppressed: AtomicU64,
+}
+
+impl ThrottledReporter {
+    pub fn new(inner: Arc<dyn ErrorReporter>, interval: u64) -> Self {
+        Self {
+            inner,
+            interval,
+            last: Mutex::new(HashMap::new()),
+            suppressed: AtomicU64::new(0),
+        }
+    }
+
+    /// Reports dropped as duplicates so far.
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed.load(Ordering::Relaxed)
+    }
+
+    fn is_duplicate(&self, ctx: &ErrorContext) -> bool {
+        let Some(block) = ctx.block else {
+            return false;
+        };
+        let source = ctx.handler.as_deref().or(ctx.domain).unwrap_or_default();
+        let mut last = self.last.lock().unwrap();
+        let key = (ctx.kind, source.to_string());
+        if let Some(&reported) = last.get(&key) {
+            if block >= reported && block - reported < self.interval {
+                return true;
+            }
+        }
+        last.insert(key, block);
+        false
+    }
+}
+
+impl ErrorReporter for ThrottledReporter {
+    fn report(&self, error: &IndexerError, ctx: Option<&ErrorContext>) {
+        if ctx.is_some_and(|ctx| self.is_duplicate(ctx)) {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.inner.report(error, ctx);
+    }
+}
+
+/// [`RetryObserver`] reporting operations of `domain` that give up.
+pub struct GiveUpReporter {
+    reporter: Arc<dyn ErrorReporter>,
+    domain: &'static str,
+}
+
+impl GiveUpReporter {
+    pub fn new(reporter: Arc<dyn ErrorReporter>, domain: &'static str) -> Self {
+        Self { reporter, domain }
+    }
+}
+
+impl RetryObserver for GiveUpReporter {
+    fn on_give_up(&self, attempts: usize, error: &IndexerError) {
+        let ctx = ErrorContext::retry_give_up(self.domain, attempts);
+        self.reporter.report(error, Some(&ctx));
+    }
+}
+
+/// Report to `reporter` every time `breaker` opens, with the error the breaker
+/// rejects operations with.
+pub fn report_breaker_opens(breaker: &CircuitBreaker, reporter: Arc<dyn ErrorReporter>) {
+    let domain = breaker.domain();
+    breaker.on_state_change(move |_, to| {
+        if let BreakerState::Open { .. } = to {
+            let error = CircuitBreaker::circuit_open_error(domain.into());
+            reporter.report(&error, Some(&ErrorContext::breaker_open(domain)));
+        }
+    });
+}
+
+/// [`ErrorReporter`] sending each report to Sentry through the current hub,
+/// with the `sentry` feature.
+///
+/// The context becomes the `kind`, `block`, `handler`, `domain` and `attempt`
+/// tags, and reports are grouped by kind and handler or domain rather than by
+/// message, so that failures on different blocks form one issue. Initialize
+/// Sentry with `sentry::init` as usual; this reporter does nothing without a
+/// client.
+#[cfg(feature = "sentry")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SentryReporter;
+
+#[cfg(feature = "sentry")]
+impl ErrorReporter for SentryReporter {
+    fn report(&self, error: &IndexerError, ctx: Option<&ErrorContext>) {
+        sentry::with_scope(
+            |scope| {
+                let Some(ctx) = ctx else {
+                    return;
+                };
+                scope.set_tag("kind", ctx.kind);
+                if let Some(block) = ctx.block {
+                    scope.set_tag("block", block);
+                }
+                if let Some(handler) = &ctx.handler {
+                    scope.set_tag("handler", handler);
+                }
+                if let Some(domain) = ctx.domain {
+                    scope.set_tag("domain", domain);
+                }
+                if let Some(attempt) = ctx.attempt {
+                    scope.set_tag("attempt", attempt);
+                }
+                let source = ctx.handler.as_deref().or(ctx.domain).unwrap_or_default();
+                scope.set_fingerprint(Some(&["indexer", ctx.kind.as_str(), source][..]));
+            },
+            || sentry::capture_error(error),
+        );
+    }
+}
@@ -14,20 +14,110 @@
  * limitations under the License.
  */
 
+use std::collections::hash_map::RandomState;
+use std::fmt;
 use std::future::Future;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Mutex;
-use std::time::{Duration, Instant};
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::time::sleep;
 
 use crate::error::IndexerError;
-use tracing::warn;
+use crate::types::BlockNumber;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, field, info_span, warn, Span};
 
+/// How much randomness to apply to each backoff delay.
+///
+/// Semantics follow the AWS "Exponential Backoff And Jitter" article: `Full` sleeps a
+/// random duration in `[0, delay]`, `Equal` sleeps `delay / 2` plus a random duration in
+/// `[0, delay / 2]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JitterMode {
+    #[default]
+    None,
+    Full,
+    Equal,
+}
+
+impl JitterMode {
+    /// Apply this jitter mode to `delay` given a random `fraction` in `[0, 1]`.
+    pub fn apply(self, delay: Duration, fraction: f64) -> Duration {
+        let fraction = if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+        match self {
+            JitterMode::None => delay,
+            JitterMode::Full => delay.mul_f64(fraction),
+            JitterMode::Equal => {
+                let half = delay / 2;
+                half + (delay - half).mul_f64(fraction)
+            }
+        }
+    }
+}
+
+/// Source of randomness for [`JitterMode`], returning values in `[0, 1]`.
+///
+/// Any `Fn() -> f64` closure can be used, which makes jitter deterministic in tests.
+pub trait JitterSource: Send + Sync {
+    fn next_fraction(&self) -> f64;
+}
+
+impl<F: Fn() -> f64 + Send + Sync> JitterSource for F {
+    fn next_fraction(&self) -> f64 {
+        self()
+    }
+}
+
+/// Default [`JitterSource`] backed by the standard library's randomly keyed hasher.
+#[derive(Default)]
+pub struct RandomJitter {
+    counter: AtomicU64,
+}
+
+impl JitterSource for RandomJitter {
+    fn next_fraction(&self) -> f64 {
+        let n = self.counter.fetch_add(1, Ordering::Relaxed);
+        let bits = RandomState::new().hash_one(n) >> 11;
+        bits as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Decides whether a failed operation should be retried.
+pub type RetryPredicate = Arc<dyn Fn(&IndexerError) -> bool + Send + Sync>;
+
+/// Retry policy used by [`retry_with_backoff`].
+///
+/// (De)serializable for config files, with durations written as strings such as
+/// `"500ms"`. The jitter source and retry predicate can only be set in code; two
+/// configs only compare equal if they share the same ones.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct RetryConfig {
+    /// Total number of attempts, including the first. `0` is treated as `1`.
     pub max_retries: usize,
+    #[serde(with = "crate::serde_duration")]
     pub initial_delay: Duration,
+    /// Longest wait between attempts, including one a rate-limited provider asks for.
+    #[serde(with = "crate::serde_duration")]
     pub max_delay: Duration,
     pub backoff_multiplier: f32,
+    pub jitter: JitterMode,
+    /// Randomness used for `jitter`; defaults to [`RandomJitter`] when unset.
+    #[serde(skip)]
+    pub jitter_source: Option<Arc<dyn JitterSource>>,
+    /// Overrides [`is_retryable_error`] when set.
+    #[serde(skip)]
+    pub retry_predicate: Option<RetryPredicate>,
+    /// Give up instead of sleeping when the next attempt would start after this much time
+    /// has passed since the first one.
+    #[serde(with = "crate::serde_duration::option")]
+    pub max_elapsed: Option<Duration>,
 }
 
 impl Default for RetryConfig {
@@ -37,46 +127,455 @@ impl Default for RetryConfig {
             initial_delay: Duration::from_millis(500),
             max_delay: Duration::from_secs(10),
             backoff_multiplier: 2.0,
+            jitter: JitterMode::None,
+            jitter_source: None,
+            retry_predicate: None,
+            max_elapsed: None,
+        }
+    }
+}
+
+impl fmt::Debug for RetryConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryConfig")
+            .field("max_retries", &self.max_retries)
+            .field("initial_delay", &self.initial_delay)
+            .field("max_delay", &self.max_delay)
+            .field("backoff_multiplier", &self.backoff_multiplier)
+            .field("jitter", &self.jitter)
+            .field("custom_jitter_source", &self.jitter_source.is_some())
+            .field("custom_retry_predicate", &self.retry_predicate.is_some())
+            .field("max_elapsed", &self.max_elapsed)
+            .finish()
+    }
+}
+
+impl PartialEq for RetryConfig {
+    fn eq(&self, other: &Self) -> bool {
+        fn same<T: ?Sized>(a: &Option<Arc<T>>, b: &Option<Arc<T>>) -> bool {
+            match (a, b) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+        }
+        self.max_retries == other.max_retries
+            && self.initial_delay == other.initial_delay
+            && self.max_delay == other.max_delay
+            && self.backoff_multiplier == other.backoff_multiplier
+            && self.jitter == other.jitter
+            && same(&self.jitter_source, &other.jitter_source)
+            && same(&self.retry_predicate, &other.retry_predicate)
+            && self.max_elapsed == other.max_elapsed
+    }
+}
+
+impl RetryConfig {
+    /// Retry only errors for which `predicate` returns `true`, instead of using
+    /// [`is_retryable_error`].
+    pub fn retry_if(mut self, predicate: RetryPredicate) -> Self {
+        self.retry_predicate = Some(predicate);
+        self
+    }
+
+    /// Reject values that would make backoff misbehave.
+    pub fn validate(&self) -> Result<(), IndexerError> {
+        if !self.backoff_multiplier.is_finite() || self.backoff_multiplier < 1.0 {
+            return Err(IndexerError::invalid_config(
+                "backoff_multiplier",
+                "must be a finite number of at least 1.0",
+            ));
+        }
+        if self.max_delay.is_zero() {
+            return Err(IndexerError::invalid_config(
+                "max_delay",
+                "must be greater than zero",
+            ));
+        }
+        if self.initial_delay > self.max_delay {
+            return Err(IndexerError::invalid_config(
+                "initial_delay",
+                "must not exceed max_delay",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether `err` should be retried under this configuration.
+    pub fn is_retryable(&self, err: &IndexerError) -> bool {
+        match &self.retry_predicate {
+            Some(predicate) => predicate(err),
+            None => is_retryable_error(err),
+        }
+    }
+
+    /// Delay to sleep for the given backoff step, capped at `max_delay` and jittered.
+    pub fn jittered_delay(&self, delay: Duration) -> Duration {
+        let delay = delay.min(self.max_delay);
+        let fraction = match (self.jitter, &self.jitter_source) {
+            (JitterMode::None, _) => return delay,
+            (_, Some(source)) => source.next_fraction(),
+            (_, None) => RandomJitter::default().next_fraction(),
+        };
+        self.jitter.apply(delay, fraction)
+    }
+}
+
+/// Settings for a [`CircuitBreaker`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the breaker.
+    pub threshold: usize,
+    /// How long the breaker stays open.
+    #[serde(with = "crate::serde_duration")]
+    pub cooldown: Duration,
+    /// Operations let through while half-open before the breaker decides to close or re-open.
+    pub half_open_probes: usize,
+    /// Factor applied to the cooldown each time a probe fails. `1.0` keeps it constant.
+    pub cooldown_multiplier: f32,
+    /// Upper bound for the cooldown once it has been increased by `cooldown_multiplier`.
+    #[serde(with = "crate::serde_duration")]
+    pub max_cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 3,
+            cooldown: Duration::from_secs(60),
+            half_open_probes: 1,
+            cooldown_multiplier: 1.0,
+            max_cooldown: Duration::from_secs(600),
         }
     }
 }
 
+impl CircuitBreakerConfig {
+    /// Defaults for the breaker guarding checkpoint storage.
+    pub fn storage() -> Self {
+        Self {
+            threshold: 5,
+            cooldown: Duration::from_secs(30),
+            ..Default::default()
+        }
+    }
+}
+
+/// State of a [`CircuitBreaker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Operations flow normally.
+    Closed,
+    /// Operations are rejected until the cooldown elapses at `until`.
+    Open { until: Instant },
+    /// The cooldown elapsed; a limited number of probe operations are allowed through.
+    HalfOpen,
+}
+
+/// Snapshot of a [`CircuitBreaker`]'s counters, see [`CircuitBreaker::stats`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BreakerStats {
+    pub state: BreakerState,
+    /// Times the breaker has opened.
+    pub opens: u64,
+    /// Failures recorded, including those while half-open.
+    pub failures: u64,
+    /// Successes recorded.
+    pub successes: u64,
+    /// Failures recorded since the last success.
+    pub consecutive_failures: usize,
+    pub last_opened_at: Option<SystemTime>,
+    /// Total time spent open, including the current open period.
+    pub time_open: Duration,
+}
+
+struct BreakerInner {
+    state: BreakerState,
+    cooldown: Duration,
+    probes_started: usize,
+    opened_at: Option<Instant>,
+    last_opened_at: Option<SystemTime>,
+    time_open: Duration,
+}
+
+type StateListener = Box<dyn Fn(BreakerState, BreakerState, SystemTime) + Send + Sync>;
+
+/// What a [`CircuitBreaker`] guards, named in [`IndexerError::CircuitOpen`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BreakerDomain {
+    /// Node RPC calls.
+    Rpc,
+    /// Checkpoint loads and writes.
+    Storage,
+    /// Events of a handler added with
+    /// [`HandlerGroup::add_with_breaker`](crate::handler_group::HandlerGroup::add_with_breaker).
+    Handler,
+    /// Webhook deliveries.
+    Webhook,
+    /// Any other operations, by name.
+    Other(&'static str),
+}
+
+impl BreakerDomain {
+    /// The name used in logs and metric labels.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rpc => "rpc",
+            Self::Storage => "storage",
+            Self::Handler => "handler",
+            Self::Webhook => "webhook",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl From<&'static str> for BreakerDomain {
+    fn from(name: &'static str) -> Self {
+        match name {
+            "rpc" => Self::Rpc,
+            "storage" => Self::Storage,
+            "handler" => Self::Handler,
+            "webhook" => Self::Webhook,
+            name => Self::Other(name),
+        }
+    }
+}
+
+impl fmt::Display for BreakerDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 pub struct CircuitBreaker {
     failures: AtomicUsize,
+    total_opens: AtomicU64,
+    total_failures: AtomicU64,
+    total_successes: AtomicU64,
     threshold: usize,
-    cooldown: Duration,
-    open_until: Mutex<Option<Instant>>,
+    base_cooldown: Duration,
+    half_open_probes: usize,
+    cooldown_multiplier: f32,
+    max_cooldown: Duration,
+    inner: Mutex<BreakerInner>,
+    listeners: RwLock<Vec<StateListener>>,
+    domain: BreakerDomain,
 }
 
 impl CircuitBreaker {
     pub fn new(threshold: usize, cooldown: Duration) -> Self {
-        Self {
-            failures: AtomicUsize::new(0),
+        Self::from_config(&CircuitBreakerConfig {
             threshold,
             cooldown,
-            open_until: Mutex::new(None),
+            ..Default::default()
+        })
+    }
+
+    /// What this breaker guards, named in the [`IndexerError::CircuitOpen`] errors
+    /// it rejects operations with.
+    pub fn with_domain(mut self, domain: impl Into<BreakerDomain>) -> Self {
+        self.domain = domain.into();
+        self
+    }
+
+    pub fn domain(&self) -> &'static str {
+        self.domain.as_str()
+    }
+
+    /// Error returned for operations rejected by this breaker.
+    pub(crate) fn open_error(&self) -> IndexerError {
+        Self::circuit_open_error(self.domain)
+    }
+
+    /// [`open_error`](Self::open_error) of a breaker guarding `domain`.
+    pub(crate) fn circuit_open_error(domain: BreakerDomain) -> IndexerError {
+        IndexerError::CircuitOpen { domain }
+    }
+
+    pub fn from_config(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            failures: AtomicUsize::new(0),
+            total_opens: AtomicU64::new(0),
+            total_failures: AtomicU64::new(0),
+            total_successes: AtomicU64::new(0),
+            threshold: config.threshold,
+            base_cooldown: config.cooldown,
+            half_open_probes: config.half_open_probes.max(1),
+            cooldown_multiplier: config.cooldown_multiplier.max(1.0),
+            max_cooldown: config.max_cooldown.max(config.cooldown),
+            inner: Mutex::new(BreakerInner {
+                state: BreakerState::Closed,
+                cooldown: config.cooldown,
+                probes_started: 0,
+                opened_at: None,
+                last_opened_at: None,
+                time_open: Duration::ZERO,
+            }),
+            listeners: RwLock::new(Vec::new()),
+            domain: BreakerDomain::Rpc,
         }
     }
 
-    pub fn is_open(&self) -> bool {
-        if let Some(until) = *self.open_until.lock().unwrap() {
-            if Instant::now() < until {
-                return true;
+    /// Call `listener` with the old and new state on every transition.
+    ///
+    /// Listeners run synchronously on the thread that caused the transition, after the
+    /// breaker's internal lock has been released.
+    pub fn on_state_change(
+        &self,
+        listener: impl Fn(BreakerState, BreakerState) + Send + Sync + 'static,
+    ) {
+        self.on_state_change_at(move |from, to, _| listener(from, to));
+    }
+
+    /// Like [`on_state_change`](Self::on_state_change), also passing the time of the transition.
+    pub fn on_state_change_at(
+        &self,
+        listener: impl Fn(BreakerState, BreakerState, SystemTime) + Send + Sync + 'static,
+    ) {
+        self.listeners.write().unwrap().push(Box::new(listener));
+    }
+
+    /// Run `f` on the locked state and notify listeners of any resulting transitions.
+    ///
+    /// An open breaker whose cooldown has elapsed is moved to half-open first.
+    fn with_state<R>(&self, f: impl FnOnce(&Self, &mut BreakerInner) -> R) -> R {
+        let mut transitions = Vec::new();
+        let result = {
+            let mut inner = self.inner.lock().unwrap();
+            if let BreakerState::Open { until } = inner.state {
+                if Instant::now() >= until {
+                    Self::leave_open(&mut inner);
+                    inner.state = BreakerState::HalfOpen;
+                    inner.probes_started = 0;
+                    transitions.push((BreakerState::Open { until }, BreakerState::HalfOpen));
+                }
+            }
+            let before = inner.state;
+            let result = f(self, &mut inner);
+            if inner.state != before {
+                transitions.push((before, inner.state));
+            }
+            result
+        };
+        if !transitions.is_empty() {
+            let now = SystemTime::now();
+            let listeners = self.listeners.read().unwrap();
+            for (from, to) in transitions {
+                for listener in listeners.iter() {
+                    listener(from, to, now);
+                }
             }
         }
-        false
+        result
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.with_state(|_, inner| inner.state)
+    }
+
+    pub fn is_half_open(&self) -> bool {
+        self.state() == BreakerState::HalfOpen
+    }
+
+    /// Time left until an open breaker moves to half-open, `None` unless open.
+    pub fn remaining_cooldown(&self) -> Option<Duration> {
+        match self.state() {
+            BreakerState::Open { until } => Some(until.saturating_duration_since(Instant::now())),
+            _ => None,
+        }
+    }
+
+    /// Failures recorded since the last success.
+    pub fn failure_count(&self) -> usize {
+        self.failures.load(Ordering::Relaxed)
+    }
+
+    pub fn stats(&self) -> BreakerStats {
+        self.with_state(|this, inner| BreakerStats {
+            state: inner.state,
+            opens: this.total_opens.load(Ordering::Relaxed),
+            failures: this.total_failures.load(Ordering::Relaxed),
+            successes: this.total_successes.load(Ordering::Relaxed),
+            consecutive_failures: this.failures.load(Ordering::Relaxed),
+            last_opened_at: inner.last_opened_at,
+            time_open: inner.time_open + inner.opened_at.map_or(Duration::ZERO, |at| at.elapsed()),
+        })
+    }
+
+    /// Whether operations are currently rejected: the breaker is open, or half-open with
+    /// every probe already handed out.
+    pub fn is_open(&self) -> bool {
+        self.with_state(|this, inner| match inner.state {
+            BreakerState::Closed => false,
+            BreakerState::Open { .. } => true,
+            BreakerState::HalfOpen => inner.probes_started >= this.half_open_probes,
+        })
+    }
+
+    /// Ask to run one operation. While half-open this consumes one probe.
+    pub fn try_acquire(&self) -> bool {
+        self.with_state(|this, inner| match inner.state {
+            BreakerState::Closed => true,
+            BreakerState::Open { .. } => false,
+            BreakerState::HalfOpen => {
+                if inner.probes_started < this.half_open_probes {
+                    inner.probes_started += 1;
+                    true
+                } else {
+                    false
+                }
+            }
+        })
     }
 
     pub fn record_success(&self) {
+        self.total_successes.fetch_add(1, Ordering::Relaxed);
         self.failures.store(0, Ordering::Relaxed);
-        *self.open_until.lock().unwrap() = None;
+        self.with_state(|this, inner| {
+            Self::leave_open(inner);
+            inner.state = BreakerState::Closed;
+            inner.cooldown = this.base_cooldown;
+            inner.probes_started = 0;
+        });
     }
 
     pub fn record_failure(&self) {
+        self.total_failures.fetch_add(1, Ordering::Relaxed);
         let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
-        if failures >= self.threshold {
-            *self.open_until.lock().unwrap() = Some(Instant::now() + self.cooldown);
-            self.failures.store(0, Ordering::Relaxed);
+        self.with_state(|this, inner| match inner.state {
+            BreakerState::Closed => {
+                if failures >= this.threshold {
+                    let cooldown = inner.cooldown;
+                    this.open(inner, cooldown);
+                }
+            }
+            BreakerState::HalfOpen => {
+                let next = Duration::try_from_secs_f32(
+                    inner.cooldown.as_secs_f32() * this.cooldown_multiplier,
+                )
+                .unwrap_or(this.max_cooldown);
+                this.open(inner, next.min(this.max_cooldown));
+            }
+            BreakerState::Open { .. } => {}
+        });
+    }
+
+    fn open(&self, inner: &mut BreakerInner, cooldown: Duration) {
+        self.total_opens.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        inner.state = BreakerState::Open {
+            until: now + cooldown,
+        };
+        inner.cooldown = cooldown;
+        inner.probes_started = 0;
+        inner.opened_at = Some(now);
+        inner.last_opened_at = Some(SystemTime::now());
+    }
+
+    fn leave_open(inner: &mut BreakerInner) {
+        if let Some(at) = inner.opened_at.take() {
+            inner.time_open += at.elapsed();
         }
     }
 }
@@ -85,50 +584,282 @@ fn is_retryable_subxt_error(err: &subxt::Error) -> bool {
     if err.is_rpc_limit_reached() {
         return false;
     }
-    if let subxt::Error::Rpc(subxt::error::RpcError::ClientError(_)) = err {
-        return false;
+    match err {
+        subxt::Error::Rpc(subxt::error::RpcError::ClientError(_)) => false,
+        subxt::Error::Rpc(_) | subxt::Error::Other(_) => true,
+        subxt::Error::Io(e) => is_transient_io_error(e),
+        _ => false,
     }
-    true
 }
 
+/// Whether an I/O error looks like a dropped or stalled connection rather than, say, a
+/// missing file or a permission problem.
+fn is_transient_io_error(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        err.kind(),
+        ErrorKind::TimedOut
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe
+            | ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+/// Whether a database error is likely to go away on its own: dropped connections, pool
+/// exhaustion, serialization conflicts and locked SQLite databases.
+fn is_transient_sqlx_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::Protocol(_)
+        | sqlx::Error::PoolTimedOut
+        | sqlx::Error::WorkerCrashed => true,
+        sqlx::Error::Database(db) => db.code().is_some_and(|code| {
+            // PostgreSQL SQLSTATEs: connection exceptions, operator intervention,
+            // serialization failure, deadlock and too many connections.
+            code.starts_with("08")
+                || code.starts_with("57P")
+                || matches!(code.as_ref(), "40001" | "40P01" | "53300")
+                // SQLite (extended) result codes for SQLITE_BUSY and SQLITE_LOCKED.
+                || (code.len() < 5
+                    && code.parse::<u32>().is_ok_and(|n| matches!(n & 0xff, 5 | 6)))
+        }),
+        _ => false,
+    }
+}
+
+/// Built-in retry classification.
+///
+/// Only failures that are expected to go away on their own are retried: node connection
+/// and transport errors (including those of storage queries), timeouts, rate limiting,
+/// metadata updates, transient database errors and connection-level I/O errors (including
+/// those wrapped in `CheckpointError`), and `HandlerFailed` errors constructed as
+/// retryable. Everything else, notably other handler failures, decoding errors and corrupt
+/// checkpoint files, fails immediately. Use
+/// [`RetryConfig::retry_if`] to retry additional errors.
 pub fn is_retryable_error(err: &IndexerError) -> bool {
     match err {
-        IndexerError::BlockNotFound { .. } | IndexerError::InvalidConfig { .. } => false,
         IndexerError::Subxt(e)
         | IndexerError::ConnectionFailed { source: e, .. }
-        | IndexerError::MetadataUpdateFailed { source: e } => is_retryable_subxt_error(e.as_ref()),
-        _ => true,
+        | IndexerError::MetadataUpdateFailed { source: e }
+        | IndexerError::StorageQueryFailed { source: e, .. } => {
+            is_retryable_subxt_error(e.as_ref())
+        }
+        IndexerError::RateLimited { .. } | IndexerError::Timeout { .. } => true,
+        IndexerError::HandlerFailed { retryable, .. } => *retryable,
+        IndexerError::Database(e) => is_transient_sqlx_error(e),
+        IndexerError::Io(e) => is_transient_io_error(e),
+        IndexerError::CheckpointError { source, .. } => {
+            if let Some(e) = source.downcast_ref::<sqlx::Error>() {
+                is_transient_sqlx_error(e)
+            } else if let Some(e) = source.downcast_ref::<std::io::Error>() {
+                is_transient_io_error(e)
+            } else {
+                false
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Receives notifications from [`retry_with_backoff_observed`].
+///
+/// Attempts are counted from 1. Both methods default to doing nothing.
+pub trait RetryObserver: Send + Sync {
+    /// Attempt `attempt` failed with `error`; the next one starts after `delay`.
+    fn on_retry(&self, _attempt: usize, _delay: Duration, _error: &IndexerError) {}
+
+    /// The operation failed for good after `attempts` attempts.
+    fn on_give_up(&self, _attempts: usize, _error: &IndexerError) {}
+}
+
+/// [`RetryObserver`] that ignores every notification.
+pub struct NoopRetryObserver;
+
+impl RetryObserver for NoopRetryObserver {}
+
+/// [`RetryObserver`] that logs retries and counts them.
+#[derive(Debug, Default)]
+pub struct RetryCounters {
+    retries: AtomicU64,
+    give_ups: AtomicU64,
+}
+
+impl RetryCounters {
+    /// Number of retries scheduled so far.
+    pub fn retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// Number of operations that failed after exhausting or aborting their retries.
+    pub fn give_ups(&self) -> u64 {
+        self.give_ups.load(Ordering::Relaxed)
     }
 }
 
+impl RetryObserver for RetryCounters {
+    fn on_retry(&self, attempt: usize, delay: Duration, error: &IndexerError) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+        debug!(target: "indexer", attempt, ?delay, %error, "scheduling retry");
+    }
+
+    fn on_give_up(&self, attempts: usize, error: &IndexerError) {
+        self.give_ups.fetch_add(1, Ordering::Relaxed);
+        debug!(target: "indexer", attempts, %error, "giving up after retries");
+    }
+}
+
+/// Span for one retried operation, e.g. `chain_get_block_hash` for `block`.
+///
+/// Instrument the retry future with it: the retry loop fills in the `attempt` field,
+/// so retry warnings carry the operation, block and attempt number.
+pub fn retry_span(operation: &'static str, block: Option<BlockNumber>) -> Span {
+    let span = info_span!(
+        target: "indexer",
+        "retry",
+        operation,
+        block = field::Empty,
+        attempt = field::Empty
+    );
+    if let Some(block) = block {
+        span.record("block", block);
+    }
+    span
+}
+
 pub async fn retry_with_backoff<F, Fut, T>(
+    op: F,
+    config: &RetryConfig,
+    circuit_breaker: &CircuitBreaker,
+) -> Result<T, IndexerError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, IndexerError>>,
+{
+    retry_with_backoff_observed(op, config, circuit_breaker, &NoopRetryObserver).await
+}
+
+/// [`retry_with_backoff`] reporting each retry and the final failure to `observer`.
+pub async fn retry_with_backoff_observed<F, Fut, T>(
     mut op: F,
     config: &RetryConfig,
     circuit_breaker: &CircuitBreaker,
+    observer: &dyn RetryObserver,
 ) -> Result<T, IndexerError>
 where
     F: FnMut() -> Fut,
     Fut: Future<Output = Result<T, IndexerError>>,
 {
+    let max_attempts = config.max_retries.max(1);
+    let started = tokio::time::Instant::now();
     let mut delay = config.initial_delay;
-    for attempt in 0..config.max_retries {
-        if circuit_breaker.is_open() {
-            return Err(IndexerError::Subxt(Box::new(subxt::Error::Other(
-                "circuit open".into(),
-            ))));
+    let mut attempt = 0;
+    let mut last_error = None;
+    loop {
+        if !circuit_breaker.try_acquire() {
+            // Once an attempt has failed, that failure is what the caller should see
+            // and count against the breaker, not the rejection.
+            return Err(match last_error {
+                Some(e) => {
+                    observer.on_give_up(attempt, &e);
+                    e
+                }
+                None => circuit_breaker.open_error(),
+            });
         }
+        attempt += 1;
+        Span::current().record("attempt", attempt);
         match op().await {
             Ok(val) => return Ok(val),
             Err(e) => {
-                if !is_retryable_error(&e) || attempt + 1 == config.max_retries {
+                if !config.is_retryable(&e) || attempt >= max_attempts {
+                    observer.on_give_up(attempt, &e);
+                    return Err(e);
+                }
+                let wait = match &e {
+                    // Honor the provider's hint instead of our own schedule, up to
+                    // the longest delay we would wait anyway.
+                    IndexerError::RateLimited {
+                        retry_after: Some(after),
+                        ..
+                    } => (*after).min(config.max_delay),
+                    _ => config.jittered_delay(delay),
+                };
+                if config
+                    .max_elapsed
+                    .is_some_and(|budget| started.elapsed() + wait > budget)
+                {
+                    observer.on_give_up(attempt, &e);
                     return Err(e);
                 }
-                warn!(target: "indexer", "retrying in {:?} after error", delay);
-                sleep(delay).await;
+                warn!(target: "indexer", error = %e, "retrying in {:?} after error", wait);
+                observer.on_retry(attempt, wait, &e);
+                sleep(wait).await;
                 let next = (delay.as_millis() as f32 * config.backoff_multiplier) as u64;
                 delay = Duration::from_millis(next).min(config.max_delay);
+                last_error = Some(e);
             }
         }
     }
-    unreachable!()
+}
+
+/// [`retry_with_backoff`] with every attempt limited to `per_attempt_timeout`.
+///
+/// An attempt that does not finish in time fails with a retryable
+/// [`IndexerError::Timeout`] whose `operation` is the breaker's domain.
+pub async fn with_timeout_and_backoff<F, Fut, T>(
+    op: F,
+    per_attempt_timeout: Duration,
+    config: &RetryConfig,
+    circuit_breaker: &CircuitBreaker,
+) -> Result<T, IndexerError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, IndexerError>>,
+{
+    with_timeout_and_backoff_observed(
+        op,
+        per_attempt_timeout,
+        config,
+        circuit_breaker,
+        &NoopRetryObserver,
+    )
+    .await
+}
+
+/// [`with_timeout_and_backoff`] reporting retries to `observer`.
+pub async fn with_timeout_and_backoff_observed<F, Fut, T>(
+    mut op: F,
+    per_attempt_timeout: Duration,
+    config: &RetryConfig,
+    circuit_breaker: &CircuitBreaker,
+    observer: &dyn RetryObserver,
+) -> Result<T, IndexerError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, IndexerError>>,
+{
+    let operation = circuit_breaker.domain();
+    retry_with_backoff_observed(
+        || {
+            let attempt = tokio::time::timeout(per_attempt_timeout, op());
+            async move {
+                attempt.await.unwrap_or_else(|_| {
+                    Err(IndexerError::Timeout {
+                        operation: operation.to_string(),
+                        duration: per_attempt_timeout,
+                    })
+                })
+            }
+        },
+        config,
+        circuit_breaker,
+        observer,
+    )
+    .await
 }
@@ -0,0 +1,276 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Websocket connections to the node.
+//!
+//! [`connect_with`] picks one of the constructors of a [`ClientFactory`] from the URL
+//! scheme and the [`TlsMode`]. The indexer connects through [`WsClientFactory`].
+
+use crate::config::TlsMode;
+use crate::error::IndexerError;
+use crate::redact::redact_url;
+use crate::validated_types::{NodeAuth, WebSocketUrl};
+use async_trait::async_trait;
+use jsonrpsee::ws_client::{CustomCertStore, HeaderMap, HeaderValue, WsClientBuilder};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use std::net::IpAddr;
+use std::sync::Arc;
+use subxt::backend::rpc::{RawRpcFuture, RawRpcSubscription, RawValue, RpcClient, RpcClientT};
+use subxt::ext::subxt_rpcs::Error as ClientError;
+use tracing::warn;
+
+/// Constructors for node clients, one per transport.
+#[async_trait]
+pub trait ClientFactory: Send + Sync {
+    type Client: Send;
+
+    /// Connect to a `wss://` URL, verifying the node's certificate.
+    async fn secure(
+        &self,
+        url: &str,
+        auth: Option<&NodeAuth>,
+    ) -> Result<Self::Client, subxt::Error>;
+
+    /// Connect to a `wss://` URL, accepting any certificate.
+    async fn accept_invalid_certs(
+        &self,
+        url: &str,
+        auth: Option<&NodeAuth>,
+    ) -> Result<Self::Client, subxt::Error>;
+
+    /// Connect to a `ws://` URL, without TLS.
+    async fn plaintext(
+        &self,
+        url: &str,
+        auth: Option<&NodeAuth>,
+    ) -> Result<Self::Client, subxt::Error>;
+}
+
+/// Connect to `url` with the constructor of `factory` matching its scheme and `tls`.
+///
+/// Logs a warning when certificate verification is disabled, and when a `ws://` URL
+/// points anywhere but the local machine.
+pub async fn connect_with<F: ClientFactory>(
+    factory: &F,
+    url: &str,
+    auth: Option<&NodeAuth>,
+    tls: TlsMode,
+) -> Result<F::Client, IndexerError> {
+    let parsed = WebSocketUrl::parse(url)?;
+    let result = match (parsed.is_secure(), tls) {
+        (true, TlsMode::Secure) => factory.secure(url, auth).await,
+        (true, TlsMode::Insecure) => {
+            let redacted = redact_url(url);
+            warn!(target: "indexer", url = %redacted, "certificate verification is disabled for this node");
+            factory.accept_invalid_certs(url, auth).await
+        }
+        (false, _) => {
+            if !is_local(parsed.host()) {
+                let redacted = redact_url(url);
+                warn!(target: "indexer", url = %redacted, "connecting to a remote node without TLS");
+            }
+            factory.plaintext(url, auth).await
+        }
+    };
+    result.map_err(|e| IndexerError::ConnectionFailed {
+        url: url.to_string(),
+        source: Box::new(e),
+    })
+}
+
+/// Connect to `url` through [`WsClientFactory`].
+pub(crate) async fn connect(
+    url: &str,
+    auth: Option<&NodeAuth>,
+    tls: TlsMode,
+) -> Result<RpcClient, IndexerError> {
+    connect_with(&WsClientFactory, url, auth, tls).await
+}
+
+fn is_local(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// [`ClientFactory`] building jsonrpsee websocket clients.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WsClientFactory;
+
+#[async_trait]
+impl ClientFactory for WsClientFactory {
+    type Client = RpcClient;
+
+    async fn secure(&self, url: &str, auth: Option<&NodeAuth>) -> Result<RpcClient, subxt::Error> {
+        match auth {
+            None => RpcClient::from_url(url).await.map_err(subxt::Error::from),
+            Some(auth) => ws_client(WsClientBuilder::default(), url, Some(auth)).await,
+        }
+    }
+
+    async fn accept_invalid_certs(
+        &self,
+        url: &str,
+        auth: Option<&NodeAuth>,
+    ) -> Result<RpcClient, subxt::Error> {
+        let builder = WsClientBuilder::default().with_custom_cert_store(accept_any_cert()?);
+        ws_client(builder, url, auth).await
+    }
+
+    async fn plaintext(
+        &self,
+        url: &str,
+        auth: Option<&NodeAuth>,
+    ) -> Result<RpcClient, subxt::Error> {
+        match auth {
+            None => RpcClient::from_insecure_url(url)
+                .await
+                .map_err(subxt::Error::from),
+            Some(auth) => ws_client(WsClientBuilder::default(), url, Some(auth)).await,
+        }
+    }
+}
+
+async fn ws_client(
+    builder: WsClientBuilder,
+    url: &str,
+    auth: Option<&NodeAuth>,
+) -> Result<RpcClient, subxt::Error> {
+    let mut builder = builder.max_buffer_capacity_per_subscription(4096);
+    if let Some(auth) = auth {
+        builder = builder.set_headers(auth_headers(auth)?);
+    }
+    let client = builder
+        .build(url)
+        .await
+        .map_err(|e| ClientError::Client(Box::new(e)))?;
+    Ok(RpcClient::new(client))
+}
+
+/// Client for replays and custom block sources, failing every call since there is
+/// no node behind it.
+pub(crate) fn offline_client() -> RpcClient {
+    RpcClient::new(Offline)
+}
+
+struct Offline;
+
+impl Offline {
+    fn error(method: &str) -> ClientError {
+        ClientError::Client(Box::new(std::io::Error::other(format!(
+            "`{method}` needs a node, but the indexer runs without one"
+        ))))
+    }
+}
+
+impl RpcClientT for Offline {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        _params: Option<Box<RawValue>>,
+    ) -> RawRpcFuture<'a, Box<RawValue>> {
+        Box::pin(async move { Err(Self::error(method)) })
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        _params: Option<Box<RawValue>>,
+        _unsub: &'a str,
+    ) -> RawRpcFuture<'a, RawRpcSubscription> {
+        Box::pin(async move { Err(Self::error(sub)) })
+    }
+}
+
+/// Headers sent with the websocket handshake for `auth`.
+///
+/// The `Authorization` value is marked sensitive so it is left out of debug output.
+fn auth_headers(auth: &NodeAuth) -> Result<HeaderMap, subxt::Error> {
+    let mut value = HeaderValue::from_str(&auth.header_value()).map_err(|_| {
+        subxt::Error::Other("node credentials contain characters not allowed in a header".into())
+    })?;
+    value.set_sensitive(true);
+    let mut headers = HeaderMap::new();
+    headers.insert("authorization", value);
+    Ok(headers)
+}
+
+/// TLS configuration that accepts any server certificate. Handshake signatures are
+/// still checked, so the connection is encrypted to whoever holds the presented key.
+fn accept_any_cert() -> Result<CustomCertStore, subxt::Error> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| subxt::Error::Other(e.to_string()))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+        .with_no_client_auth();
+    Ok(config)
+}
+
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
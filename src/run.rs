@@ -0,0 +1,100 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Limits on how long [`Indexer::run`](crate::indexer::Indexer::run) keeps going, and
+//! the summary it returns when it stops.
+
+use crate::types::BlockNumber;
+use std::time::{Duration, SystemTime};
+use tokio::time::Instant;
+
+/// Why a run stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// The next block was past `end_block`.
+    EndBlock,
+    /// `end_after` elapsed or `end_at_time` was reached.
+    TimeLimit,
+    /// The node closed the block subscription.
+    SubscriptionClosed,
+    /// Every block of the replayed recording was handled.
+    EndOfRecording,
+}
+
+/// What a completed run did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunSummary {
+    /// Blocks handled by this run.
+    pub blocks_processed: u64,
+    /// Last block handled by this run, if any.
+    pub last_block: Option<BlockNumber>,
+    /// Wall-clock duration of the run.
+    pub elapsed: Duration,
+    pub stop_reason: StopReason,
+}
+
+/// Block and time bounds of a run, fixed when the run starts.
+///
+/// Whichever bound is reached first stops the run.
+#[derive(Clone, Copy, Debug)]
+pub struct RunLimits {
+    end_block: Option<BlockNumber>,
+    deadline: Option<Instant>,
+}
+
+impl RunLimits {
+    /// Limits for a run starting now. `end_at_time` in the past stops the run before its
+    /// first block.
+    pub fn new(
+        end_block: Option<BlockNumber>,
+        end_after: Option<Duration>,
+        end_at_time: Option<SystemTime>,
+    ) -> Self {
+        let now = Instant::now();
+        let after = end_after.map(|limit| now + limit);
+        let at = end_at_time.map(|at| {
+            now + at
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO)
+        });
+        Self {
+            end_block,
+            deadline: after.into_iter().chain(at).min(),
+        }
+    }
+
+    /// When the time limit runs out, if there is one.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Whether the time limit has run out.
+    pub fn time_is_up(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// The reason to stop instead of handling block `next`, if any.
+    pub fn check(&self, next: BlockNumber) -> Option<StopReason> {
+        if self.end_block.is_some_and(|end| next > end) {
+            Some(StopReason::EndBlock)
+        } else if self.time_is_up() {
+            Some(StopReason::TimeLimit)
+        } else {
+            None
+        }
+    }
+}
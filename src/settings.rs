@@ -0,0 +1,209 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Settings that can be changed while the indexer runs.
+//!
+//! [`Indexer::settings_handle`](crate::indexer::Indexer::settings_handle) returns a
+//! [`SettingsHandle`]; the run loop re-reads the settings before every block, so a
+//! change takes effect from the next block without restarting the subscription.
+
+use crate::config::IndexerConfig;
+use crate::error::IndexerError;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::info;
+
+/// The subset of [`IndexerConfig`] that is safe to change at runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuntimeSettings {
+    /// Maximum number of blocks processed per minute. Unlimited when unset.
+    pub max_blocks_per_minute: Option<u32>,
+    /// Write the checkpoint every `checkpoint_interval` handled blocks.
+    pub checkpoint_interval: u64,
+    /// Stop with [`IndexerError::ErrorBudgetExhausted`] once handlers have failed more
+    /// than this many times in a run. Unlimited when unset.
+    pub handler_error_budget: Option<u64>,
+}
+
+impl From<&IndexerConfig> for RuntimeSettings {
+    fn from(config: &IndexerConfig) -> Self {
+        Self {
+            max_blocks_per_minute: config.max_blocks_per_minute,
+            checkpoint_interval: config.checkpoint_interval,
+            handler_error_budget: config.handler_error_budget,
+        }
+    }
+}
+
+impl RuntimeSettings {
+    /// Reject values [`IndexerConfig::validate`] would reject.
+    pub fn validate(&self) -> Result<(), IndexerError> {
+        if self.max_blocks_per_minute == Some(0) {
+            return Err(IndexerError::invalid_config(
+                "max_blocks_per_minute",
+                "must be at least 1",
+            ));
+        }
+        if self.checkpoint_interval == 0 {
+            return Err(IndexerError::invalid_config(
+                "checkpoint_interval",
+                "must be at least 1",
+            ));
+        }
+        Ok(())
+    }
+
+    /// How long to wait after a block that took `elapsed` to respect
+    /// `max_blocks_per_minute`, if at all.
+    pub fn throttle_delay(&self, elapsed: Duration) -> Option<Duration> {
+        let min = Duration::from_secs_f64(60.0 / f64::from(self.max_blocks_per_minute?));
+        min.checked_sub(elapsed).filter(|wait| !wait.is_zero())
+    }
+
+    /// Names of the settings that differ between `self` and `other`.
+    fn changes(&self, other: &Self) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.max_blocks_per_minute != other.max_blocks_per_minute {
+            changed.push("max_blocks_per_minute");
+        }
+        if self.checkpoint_interval != other.checkpoint_interval {
+            changed.push("checkpoint_interval");
+        }
+        if self.handler_error_budget != other.handler_error_budget {
+            changed.push("handler_error_budget");
+        }
+        changed
+    }
+}
+
+/// Shared, cloneable handle for reading and changing [`RuntimeSettings`].
+#[derive(Clone, Debug)]
+pub struct SettingsHandle {
+    tx: Arc<watch::Sender<RuntimeSettings>>,
+}
+
+impl SettingsHandle {
+    /// A handle starting from `settings`. The indexer makes its own from the
+    /// builder's settings, see
+    /// [`Indexer::settings_handle`](crate::indexer::Indexer::settings_handle).
+    pub fn new(settings: RuntimeSettings) -> Self {
+        Self {
+            tx: Arc::new(watch::Sender::new(settings)),
+        }
+    }
+
+    /// The current settings.
+    pub fn get(&self) -> RuntimeSettings {
+        self.tx.borrow().clone()
+    }
+
+    /// Receiver notified whenever the settings change.
+    pub fn subscribe(&self) -> watch::Receiver<RuntimeSettings> {
+        self.tx.subscribe()
+    }
+
+    /// Replace all settings at once, logging the ones that changed.
+    ///
+    /// Invalid settings are rejected and leave the current ones in place. Returns the
+    /// names of the changed settings.
+    pub fn apply(&self, settings: RuntimeSettings) -> Result<Vec<&'static str>, IndexerError> {
+        settings.validate()?;
+        let changed = self.get().changes(&settings);
+        if !changed.is_empty() {
+            info!(target: "indexer", ?changed, new = ?settings, "runtime settings changed");
+            self.tx.send_replace(settings);
+        }
+        Ok(changed)
+    }
+
+    /// Process at most `value` blocks per minute, or without limit when `None`.
+    pub fn set_max_blocks_per_minute(&self, value: Option<u32>) -> Result<(), IndexerError> {
+        self.apply(RuntimeSettings {
+            max_blocks_per_minute: value,
+            ..self.get()
+        })?;
+        Ok(())
+    }
+
+    /// Write the checkpoint every `interval` handled blocks.
+    pub fn set_checkpoint_interval(&self, interval: u64) -> Result<(), IndexerError> {
+        self.apply(RuntimeSettings {
+            checkpoint_interval: interval,
+            ..self.get()
+        })?;
+        Ok(())
+    }
+
+    /// Allow handlers to fail at most `budget` times per run, or without limit when
+    /// `None`.
+    pub fn set_handler_error_budget(&self, budget: Option<u64>) -> Result<(), IndexerError> {
+        self.apply(RuntimeSettings {
+            handler_error_budget: budget,
+            ..self.get()
+        })?;
+        Ok(())
+    }
+
+    /// Re-read the config file at `path` whenever it changes and apply its runtime
+    /// settings. The file is checked every `poll_interval`.
+    ///
+    /// Other settings in the file are ignored until the next restart. Files that fail
+    /// to load or validate are logged and skipped. The returned task runs until aborted.
+    /// A zero `poll_interval` is rejected with [`IndexerError::InvalidConfig`].
+    #[cfg(feature = "hot-reload")]
+    pub fn watch_file(
+        &self,
+        path: impl Into<std::path::PathBuf>,
+        poll_interval: Duration,
+    ) -> Result<tokio::task::JoinHandle<()>, IndexerError> {
+        if poll_interval.is_zero() {
+            return Err(IndexerError::invalid_config(
+                "poll_interval",
+                "must be greater than zero",
+            ));
+        }
+        let handle = self.clone();
+        let path = path.into();
+        let modified = |path: &std::path::Path| {
+            std::fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .ok()
+        };
+        let mut last = modified(&path);
+        Ok(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(poll_interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let current = modified(&path);
+                if current == last {
+                    continue;
+                }
+                last = current;
+                let result = IndexerConfig::from_file(&path)
+                    .and_then(|config| handle.apply(RuntimeSettings::from(&config)));
+                if let Err(e) = result {
+                    tracing::warn!(
+                        target: "indexer",
+                        path = %path.display(),
+                        "ignoring config reload: {e}"
+                    );
+                }
+            }
+        }))
+    }
+}
@@ -0,0 +1,296 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Where the indexer gets blocks from.
+//!
+//! [`Indexer::run`](crate::indexer::Indexer::run) makes every chain call through a
+//! [`BlockSource`]. By default that is a [`NodeSource`] connected when the run
+//! starts; [`IndexerBuilder::block_source`](crate::builder::IndexerBuilder::block_source)
+//! swaps in another one, such as the scripted
+//! [`MockBlockSource`](crate::testing::MockBlockSource) of the `testing` feature.
+//!
+//! Retries, circuit breaking and checkpointing stay in the indexer, around each call.
+
+use crate::error::IndexerError;
+use crate::hedge::HedgedRpc;
+use crate::rpc;
+use crate::types::BlockNumber;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use parity_scale_codec::Decode;
+use scale_value::Value;
+use subxt::backend::legacy::LegacyRpcMethods;
+use subxt::backend::BackendExt;
+use subxt::blocks::Block;
+use subxt::client::RuntimeVersion;
+use subxt::config::{HashFor, Header};
+use subxt::storage::Storage;
+use subxt::{Config, Metadata, OnlineClient};
+use tracing::warn;
+
+/// A block as returned by [`BlockSource::block_at`].
+pub struct SourceBlock<C: Config> {
+    pub header: C::Header,
+    /// SCALE-encoded `System.Events` of the block.
+    pub events: Vec<u8>,
+    /// SCALE-encoded extrinsics, when asked for.
+    pub extrinsics: Option<Vec<Vec<u8>>>,
+    /// `Timestamp.Now` at the block, in milliseconds since the Unix epoch;
+    /// `None` when the runtime has no `Timestamp` pallet.
+    pub timestamp: Option<u64>,
+}
+
+/// A new block announced by a subscription: its number and hash.
+pub type NewBlock<C> = (BlockNumber, HashFor<C>);
+
+/// Blocks announced by [`BlockSource::subscribe_finalized`] or
+/// [`BlockSource::subscribe_best`]. The run stops with
+/// [`StopReason::SubscriptionClosed`](crate::run::StopReason::SubscriptionClosed)
+/// when it ends.
+pub type BlockStream<C> = BoxStream<'static, Result<NewBlock<C>, IndexerError>>;
+
+/// The chain calls the run loop makes: catching up by number, following new
+/// blocks, and fetching their events and metadata.
+#[async_trait]
+pub trait BlockSource<C: Config>: Send + Sync {
+    async fn genesis_hash(&self) -> Result<HashFor<C>, IndexerError>;
+
+    /// Hash of the latest finalized block.
+    async fn finalized_head(&self) -> Result<HashFor<C>, IndexerError>;
+
+    /// Header of block `hash`, or of the best block when `None`.
+    async fn header(&self, hash: Option<HashFor<C>>) -> Result<Option<C::Header>, IndexerError>;
+
+    /// Hash of block `number` on the canonical chain, `None` past the head.
+    async fn block_hash(&self, number: BlockNumber) -> Result<Option<HashFor<C>>, IndexerError>;
+
+    /// Header and events of block `hash`, and its extrinsics if `extrinsics`.
+    async fn block_at(
+        &self,
+        hash: HashFor<C>,
+        extrinsics: bool,
+    ) -> Result<SourceBlock<C>, IndexerError>;
+
+    /// Version of the runtime that produced block `hash`.
+    async fn runtime_version(&self, hash: HashFor<C>) -> Result<RuntimeVersion, IndexerError>;
+
+    /// SCALE-encoded `RuntimeMetadataPrefixed` at block `hash`, as returned by
+    /// `state_getMetadata`. Stored in recordings.
+    async fn raw_metadata(&self, hash: HashFor<C>) -> Result<Vec<u8>, IndexerError>;
+
+    /// Metadata used to decode the events of block `hash`. Decodes
+    /// [`raw_metadata`](Self::raw_metadata) by default.
+    async fn metadata(&self, hash: HashFor<C>) -> Result<Metadata, IndexerError> {
+        let bytes = self.raw_metadata(hash).await?;
+        Ok(Metadata::decode(&mut &bytes[..]).map_err(subxt::Error::from)?)
+    }
+
+    async fn subscribe_finalized(&self) -> Result<BlockStream<C>, IndexerError>;
+
+    async fn subscribe_best(&self) -> Result<BlockStream<C>, IndexerError>;
+}
+
+/// [`BlockSource`] backed by a node: legacy RPC methods, hedged to a
+/// secondary node when configured, and the subxt client for blocks and
+/// subscriptions.
+pub struct NodeSource<C: Config> {
+    client: OnlineClient<C>,
+    rpc: HedgedRpc<LegacyRpcMethods<C>>,
+}
+
+impl<C: Config> NodeSource<C> {
+    pub fn new(client: OnlineClient<C>, rpc: HedgedRpc<LegacyRpcMethods<C>>) -> Self {
+        Self { client, rpc }
+    }
+
+    /// Keep the client's metadata current while following the chain.
+    fn spawn_runtime_updater(&self) {
+        let updater = self.client.updater();
+        tokio::spawn(async move {
+            if let Err(e) = updater.perform_runtime_updates().await {
+                warn!(target: "indexer", "runtime updater exited: {:?}", e);
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl<C> BlockSource<C> for NodeSource<C>
+where
+    C: Config + Send + Sync + 'static,
+{
+    async fn genesis_hash(&self) -> Result<HashFor<C>, IndexerError> {
+        Ok(self.client.genesis_hash())
+    }
+
+    async fn finalized_head(&self) -> Result<HashFor<C>, IndexerError> {
+        self.rpc
+            .call(|rpc| async move {
+                rpc.chain_get_finalized_head()
+                    .await
+                    .map_err(|e| IndexerError::from(subxt::Error::from(e)))
+            })
+            .await
+    }
+
+    async fn header(&self, hash: Option<HashFor<C>>) -> Result<Option<C::Header>, IndexerError> {
+        self.rpc
+            .call(|rpc| async move {
+                rpc.chain_get_header(hash)
+                    .await
+                    .map_err(|e| IndexerError::from(subxt::Error::from(e)))
+            })
+            .await
+    }
+
+    async fn block_hash(&self, number: BlockNumber) -> Result<Option<HashFor<C>>, IndexerError> {
+        self.rpc
+            .call(|rpc| async move {
+                rpc.chain_get_block_hash(Some(number.into()))
+                    .await
+                    .map_err(|e| IndexerError::from(subxt::Error::from(e)))
+            })
+            .await
+    }
+
+    async fn block_at(
+        &self,
+        hash: HashFor<C>,
+        extrinsics: bool,
+    ) -> Result<SourceBlock<C>, IndexerError> {
+        let block = self.client.blocks().at(hash).await?;
+        let events = block.events().await?;
+        let extrinsics = if extrinsics {
+            let extrinsics = block.extrinsics().await?;
+            Some(extrinsics.iter().map(|e| e.bytes().to_vec()).collect())
+        } else {
+            None
+        };
+        let timestamp = if self.client.metadata().pallet_by_name("Timestamp").is_some() {
+            block_timestamp(&block.storage()).await?
+        } else {
+            None
+        };
+        Ok(SourceBlock {
+            header: block.header().clone(),
+            events: events.bytes().to_vec(),
+            extrinsics,
+            timestamp,
+        })
+    }
+
+    async fn runtime_version(&self, hash: HashFor<C>) -> Result<RuntimeVersion, IndexerError> {
+        let version = self
+            .rpc
+            .primary()
+            .state_get_runtime_version(Some(hash))
+            .await
+            .map_err(|e| IndexerError::MetadataUpdateFailed {
+                source: Box::new(subxt::Error::from(e)),
+            })?;
+        Ok(RuntimeVersion {
+            spec_version: version.spec_version,
+            transaction_version: version.transaction_version,
+        })
+    }
+
+    async fn raw_metadata(&self, hash: HashFor<C>) -> Result<Vec<u8>, IndexerError> {
+        let metadata = self
+            .rpc
+            .primary()
+            .state_get_metadata(Some(hash))
+            .await
+            .map_err(|e| IndexerError::from(subxt::Error::from(e)))?;
+        Ok(metadata.into_raw())
+    }
+
+    /// The newest supported metadata version, falling back to `state_getMetadata`.
+    async fn metadata(&self, hash: HashFor<C>) -> Result<Metadata, IndexerError> {
+        use subxt::metadata::types::SUPPORTED_METADATA_VERSIONS;
+        let backend = self.client.backend();
+        for v in SUPPORTED_METADATA_VERSIONS {
+            if let Ok(metadata) = backend.metadata_at_version(v, hash).await {
+                return Ok(metadata);
+            }
+        }
+        backend
+            .legacy_metadata(hash)
+            .await
+            .map_err(|e| IndexerError::MetadataUpdateFailed {
+                source: Box::new(e),
+            })
+    }
+
+    async fn subscribe_finalized(&self) -> Result<BlockStream<C>, IndexerError> {
+        self.spawn_runtime_updater();
+        let sub = self.client.blocks().subscribe_finalized().await?;
+        Ok(announced(sub))
+    }
+
+    async fn subscribe_best(&self) -> Result<BlockStream<C>, IndexerError> {
+        self.spawn_runtime_updater();
+        let sub = self.client.blocks().subscribe_best().await?;
+        Ok(announced(sub))
+    }
+}
+
+/// Number and hash of each block of a subxt block subscription.
+fn announced<C, S>(sub: S) -> BlockStream<C>
+where
+    C: Config + Send + Sync + 'static,
+    S: Stream<Item = Result<Block<C, OnlineClient<C>>, subxt::Error>> + Send + 'static,
+{
+    sub.map(|block| {
+        let block = block?;
+        Ok((block.header().number().into(), block.hash()))
+    })
+    .boxed()
+}
+
+/// A client for `source` that never reaches a node, set up with the runtime at
+/// its finalized head.
+pub(crate) async fn offline_client<C>(
+    source: &dyn BlockSource<C>,
+) -> Result<OnlineClient<C>, IndexerError>
+where
+    C: Config + Send + Sync + 'static,
+{
+    let genesis_hash = source.genesis_hash().await?;
+    let head = source.finalized_head().await?;
+    let version = source.runtime_version(head).await?;
+    let metadata = source.metadata(head).await?;
+    Ok(OnlineClient::from_rpc_client_with(
+        genesis_hash,
+        version,
+        metadata,
+        rpc::offline_client(),
+    )?)
+}
+
+/// `Timestamp.Now` at the block `storage` is pinned to.
+async fn block_timestamp<C: Config>(
+    storage: &Storage<C, OnlineClient<C>>,
+) -> Result<Option<u64>, IndexerError> {
+    let address = subxt::dynamic::storage("Timestamp", "Now", Vec::<Value>::new());
+    let now = storage
+        .fetch(&address)
+        .await
+        .map_err(|e| IndexerError::storage_query_failed("Timestamp", "Now", e))?;
+    now.map(|value| value.as_type::<u64>())
+        .transpose()
+        .map_err(|e| IndexerError::storage_query_failed("Timestamp", "Now", e))
+}
@@ -0,0 +1,272 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Insert one row per event into an existing table with [`SqlSinkHandler`].
+
+use crate::error::IndexerError;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::types::ChainEvent;
+use async_trait::async_trait;
+use sqlx::query::Query;
+use sqlx::{Database, Encode, Type};
+use subxt::Config;
+use tracing::warn;
+
+const HANDLER_NAME: &str = "SqlSinkHandler";
+
+/// A value bound to a statement parameter.
+///
+/// NULLs are typed, e.g. `SqlValue::Int(None)`, as Postgres checks the
+/// parameter type against the column. Unsigned integers above `i64::MAX` have
+/// no column type in either database and must be converted by the mapper.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SqlValue {
+    Bool(Option<bool>),
+    Int(Option<i64>),
+    Float(Option<f64>),
+    Text(Option<String>),
+    Bytes(Option<Vec<u8>>),
+}
+
+macro_rules! sql_value_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for SqlValue {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(Some(value.into()))
+                }
+            }
+
+            impl From<Option<$ty>> for SqlValue {
+                fn from(value: Option<$ty>) -> Self {
+                    Self::$variant(value.map(Into::into))
+                }
+            }
+        )*
+    };
+}
+
+sql_value_from! {
+    bool => Bool,
+    i8 => Int,
+    i16 => Int,
+    i32 => Int,
+    i64 => Int,
+    u8 => Int,
+    u16 => Int,
+    u32 => Int,
+    f32 => Float,
+    f64 => Float,
+    String => Text,
+    &str => Text,
+    Vec<u8> => Bytes,
+    &[u8] => Bytes,
+}
+
+/// Pool a [`SqlSinkHandler`] writes to.
+#[derive(Clone, Debug)]
+pub enum SqlPool {
+    #[cfg(feature = "postgres")]
+    Postgres(sqlx::PgPool),
+    #[cfg(feature = "sqlite")]
+    Sqlite(sqlx::SqlitePool),
+}
+
+#[cfg(feature = "postgres")]
+impl From<sqlx::PgPool> for SqlPool {
+    fn from(pool: sqlx::PgPool) -> Self {
+        Self::Postgres(pool)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<sqlx::SqlitePool> for SqlPool {
+    fn from(pool: sqlx::SqlitePool) -> Self {
+        Self::Sqlite(pool)
+    }
+}
+
+type Mapper<C> = dyn Fn(&ChainEvent<C>, &Context<C>) -> Result<Option<Vec<SqlValue>>, IndexerError>
+    + Send
+    + Sync;
+
+/// Handler running one statement per event, with the bind values returned by
+/// a mapper closure.
+///
+/// The mapper runs for every event passing the filter, all events by default,
+/// and returns `None` to skip one. The statements of a block run in one
+/// transaction, so a failing statement rolls back the whole block. A mapper
+/// error skips the event with a warning, or fails the block in
+/// [`strict`](Self::strict) mode before anything is written.
+///
+/// Parameters are written `$1`, `$2`, ... which both Postgres and SQLite
+/// accept:
+///
+/// ```no_run
+/// # use flamewire_bittensor_indexer::prelude::*;
+/// # use flamewire_bittensor_indexer::sql_sink::{SqlSinkHandler, SqlValue};
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let pool = sqlx::SqlitePool::connect("sqlite://events.db?mode=rwc").await?;
+/// let events = SqlSinkHandler::<SubstrateConfig>::new(
+///     pool,
+///     "INSERT INTO events (block, idx, name) VALUES ($1, $2, $3)",
+///     |event, ctx| {
+///         Ok(Some(vec![
+///             SqlValue::Int(Some(ctx.block_number as i64)),
+///             event.index().into(),
+///             format!("{}.{}", event.pallet_name(), event.variant_name()).into(),
+///         ]))
+///     },
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct SqlSinkHandler<C: Config> {
+    pool: SqlPool,
+    sql: String,
+    filter: EventFilter,
+    mapper: Box<Mapper<C>>,
+    strict: bool,
+}
+
+impl<C: Config> SqlSinkHandler<C> {
+    pub fn new<F>(pool: impl Into<SqlPool>, sql: impl Into<String>, mapper: F) -> Self
+    where
+        F: Fn(&ChainEvent<C>, &Context<C>) -> Result<Option<Vec<SqlValue>>, IndexerError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self {
+            pool: pool.into(),
+            sql: sql.into(),
+            filter: EventFilter::all(),
+            mapper: Box::new(mapper),
+            strict: false,
+        }
+    }
+
+    /// Only map events passing `filter`.
+    pub fn with_filter(mut self, filter: EventFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Fail the block on the first mapper error instead of skipping the event.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    fn map_events(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<Vec<Vec<SqlValue>>, IndexerError> {
+        let mut rows = Vec::new();
+        for event in events {
+            if !self.filter.matches_event(event) {
+                continue;
+            }
+            match (self.mapper)(event, ctx) {
+                Ok(Some(row)) => rows.push(row),
+                Ok(None) => {}
+                Err(e) if self.strict => return Err(e),
+                Err(e) => warn!(
+                    target: "indexer",
+                    block = ctx.block_number,
+                    event_index = event.index(),
+                    error = %e,
+                    "skipping event the SQL sink could not map"
+                ),
+            }
+        }
+        Ok(rows)
+    }
+
+    async fn insert(&self, rows: Vec<Vec<SqlValue>>) -> Result<(), IndexerError> {
+        match &self.pool {
+            #[cfg(feature = "postgres")]
+            SqlPool::Postgres(pool) => {
+                let mut tx = pool.begin().await?;
+                for row in rows {
+                    bind_values(sqlx::query(&self.sql), row)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+            }
+            #[cfg(feature = "sqlite")]
+            SqlPool::Sqlite(pool) => {
+                let mut tx = pool.begin().await?;
+                for row in rows {
+                    bind_values(sqlx::query(&self.sql), row)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                tx.commit().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn bind_values<'q, DB>(
+    mut query: Query<'q, DB, DB::Arguments<'q>>,
+    values: Vec<SqlValue>,
+) -> Query<'q, DB, DB::Arguments<'q>>
+where
+    DB: Database,
+    Option<bool>: Encode<'q, DB> + Type<DB>,
+    Option<i64>: Encode<'q, DB> + Type<DB>,
+    Option<f64>: Encode<'q, DB> + Type<DB>,
+    Option<String>: Encode<'q, DB> + Type<DB>,
+    Option<Vec<u8>>: Encode<'q, DB> + Type<DB>,
+{
+    for value in values {
+        query = match value {
+            SqlValue::Bool(value) => query.bind(value),
+            SqlValue::Int(value) => query.bind(value),
+            SqlValue::Float(value) => query.bind(value),
+            SqlValue::Text(value) => query.bind(value),
+            SqlValue::Bytes(value) => query.bind(value),
+        };
+    }
+    query
+}
+
+#[async_trait]
+impl<C: Config> Handler<C> for SqlSinkHandler<C> {
+    fn name(&self) -> &str {
+        HANDLER_NAME
+    }
+
+    fn event_filter(&self) -> EventFilter {
+        self.filter
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        let rows = self.map_events(ctx, events)?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+        self.insert(rows).await
+    }
+}
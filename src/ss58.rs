@@ -0,0 +1,85 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! SS58 formatting of account ids for the Bittensor network.
+//!
+//! Bittensor addresses use the generic Substrate prefix 42 and so start with `5`.
+
+use crate::error::IndexerError;
+use blake2::{Blake2b512, Digest};
+use std::fmt;
+use subxt::utils::AccountId32;
+
+/// SS58 network prefix of Bittensor addresses.
+pub const BITTENSOR_PREFIX: u8 = 42;
+
+const CHECKSUM_PREIMAGE: &[u8] = b"SS58PRE";
+const CHECKSUM_LEN: usize = 2;
+/// Prefix byte, account id and checksum.
+const ADDRESS_LEN: usize = 1 + 32 + CHECKSUM_LEN;
+
+/// The account's Bittensor address.
+pub fn encode_bittensor(account: &AccountId32) -> String {
+    let mut bytes = Vec::with_capacity(ADDRESS_LEN);
+    bytes.push(BITTENSOR_PREFIX);
+    bytes.extend_from_slice(&account.0);
+    let checksum = checksum(&bytes);
+    bytes.extend_from_slice(&checksum[..CHECKSUM_LEN]);
+    bs58::encode(bytes).into_string()
+}
+
+/// Parse a Bittensor address, rejecting other networks' prefixes and bad checksums.
+pub fn parse_ss58(address: &str) -> Result<AccountId32, IndexerError> {
+    let invalid = |message: String| IndexerError::invalid_config("address", message);
+    let bytes = bs58::decode(address)
+        .into_vec()
+        .map_err(|e| invalid(format!("`{address}` is not base58: {e}")))?;
+    if bytes.len() != ADDRESS_LEN {
+        return Err(invalid(format!(
+            "`{address}` is not a 32-byte account address"
+        )));
+    }
+    let (payload, check) = bytes.split_at(ADDRESS_LEN - CHECKSUM_LEN);
+    if checksum(payload)[..CHECKSUM_LEN] != *check {
+        return Err(invalid(format!("`{address}` has an invalid checksum")));
+    }
+    if payload[0] != BITTENSOR_PREFIX {
+        return Err(invalid(format!(
+            "`{address}` has SS58 prefix {}, expected {BITTENSOR_PREFIX}",
+            payload[0]
+        )));
+    }
+    let mut account = [0u8; 32];
+    account.copy_from_slice(&payload[1..]);
+    Ok(AccountId32(account))
+}
+
+fn checksum(payload: &[u8]) -> [u8; 64] {
+    let mut hasher = Blake2b512::new();
+    hasher.update(CHECKSUM_PREIMAGE);
+    hasher.update(payload);
+    hasher.finalize().into()
+}
+
+/// Displays an account as its Bittensor address, e.g. in `tracing` fields:
+/// `info!(hotkey = %DisplayBittensor(&hotkey))`.
+pub struct DisplayBittensor<'a>(pub &'a AccountId32);
+
+impl fmt::Display for DisplayBittensor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encode_bittensor(self.0))
+    }
+}
@@ -0,0 +1,352 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Push the indexer's metrics to StatsD or DogStatsD over UDP, with the
+//! `statsd` feature.
+//!
+//! [`StatsdMetrics`] is an alternative to the Prometheus
+//! [`IndexerMetrics`](crate::metrics::IndexerMetrics) for stacks that do not
+//! scrape. It receives the same updates, aggregates them in memory and sends
+//! them on a timer, see
+//! [`IndexerBuilder::statsd`](crate::builder::IndexerBuilder::statsd).
+
+use crate::error::IndexerError;
+use crate::recorder::{breaker_value, gauge_value, handler_label, watch_breaker, MetricsRecorder};
+use crate::retry::{BreakerState, CircuitBreaker, RetryObserver};
+use crate::types::BlockNumber;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+/// Prefix of the metric names unless set with [`StatsdMetrics::with_prefix`].
+pub const DEFAULT_PREFIX: &str = "indexer";
+
+/// How often metrics are sent unless set with [`StatsdMetrics::flush_interval`].
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Largest datagram sent, to stay below the usual MTU.
+const MAX_PACKET: usize = 1432;
+
+/// Tags of one series, as `(key, value)` pairs.
+type Tags = Vec<(&'static str, String)>;
+
+/// Updates received since the last flush.
+#[derive(Default)]
+struct Pending {
+    counters: BTreeMap<(&'static str, Tags), u64>,
+    gauges: BTreeMap<(&'static str, Tags), i64>,
+    /// Timings in milliseconds.
+    timings: Vec<(&'static str, f64)>,
+}
+
+/// StatsD metrics backend, sending every [`flush_interval`](Self::flush_interval)
+/// once [started](Self::start).
+///
+/// With the default `indexer` prefix, it sends:
+///
+/// - `indexer.blocks_processed` and `indexer.events_processed` counters
+/// - `indexer.handler_errors` counter, tagged with the failing `handler`
+/// - `indexer.block_processing_time`, the time spent in the handlers per block
+///   in milliseconds
+/// - `indexer.chain_head`, `indexer.last_processed_block` and
+///   `indexer.chain_lag_blocks` gauges
+/// - `indexer.breaker_state` gauge, tagged with the `breaker`: 0 closed,
+///   1 half-open, 2 open
+/// - `indexer.rpc_retries` and `indexer.rpc_retry_give_ups` counters
+/// - `indexer.sink_events` counter, tagged with the `sink` and `outcome`
+///
+/// Counters are sent as the increase since the previous flush and gauges only
+/// when set since then. Tags use the DogStatsD `|#key:value` extension; plain
+/// StatsD servers need a backend that understands it or no tags. Sending is
+/// best effort: a failed send is logged at debug level and its data dropped.
+///
+/// ```no_run
+/// # use flamewire_bittensor_indexer::IndexerError;
+/// use flamewire_bittensor_indexer::statsd::StatsdMetrics;
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), IndexerError> {
+/// let statsd = StatsdMetrics::new("127.0.0.1:8125")?
+///     .with_prefix("bittensor.indexer")
+///     .with_tag("env", "prod")
+///     .flush_interval(Duration::from_secs(5))
+///     .start();
+/// # Ok(())
+/// # }
+/// ```
+pub struct StatsdMetrics {
+    socket: UdpSocket,
+    target: SocketAddr,
+    prefix: String,
+    /// Constant tags, rendered as `key:value` pairs.
+    tags: Vec<String>,
+    flush_interval: Duration,
+    pending: Mutex<Pending>,
+    head: AtomicU64,
+    last_processed: AtomicU64,
+}
+
+impl StatsdMetrics {
+    /// Send to the StatsD server at `addr`, e.g. `"127.0.0.1:8125"`, from an
+    /// ephemeral local port.
+    pub fn new(addr: impl ToSocketAddrs) -> Result<Self, IndexerError> {
+        let target = addr.to_socket_addrs()?.next().ok_or_else(|| {
+            IndexerError::invalid_config("statsd_addr", "address resolved to nothing")
+        })?;
+        let local: SocketAddr = match target {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            target,
+            prefix: DEFAULT_PREFIX.to_string(),
+            tags: Vec::new(),
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            pending: Mutex::new(Pending::default()),
+            head: AtomicU64::new(0),
+            last_processed: AtomicU64::new(0),
+        })
+    }
+
+    /// Prefix the metric names with `prefix` and a dot, [`DEFAULT_PREFIX`] by
+    /// default. An empty prefix sends the bare names.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Tag every metric with `key:value`, e.g. the environment or host.
+    pub fn with_tag(mut self, key: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.tags.push(format!(
+            "{}:{}",
+            sanitize(key.as_ref()),
+            sanitize(value.as_ref())
+        ));
+        self
+    }
+
+    /// Send the aggregated metrics every `interval`, [`DEFAULT_FLUSH_INTERVAL`]
+    /// by default.
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval.max(Duration::from_millis(1));
+        self
+    }
+
+    /// Flush every [`flush_interval`](Self::flush_interval) from a background
+    /// task until the metrics are dropped, which flushes them one last time.
+    /// Must be called within a Tokio runtime.
+    pub fn start(self) -> Arc<Self> {
+        let metrics = Arc::new(self);
+        let weak = Arc::downgrade(&metrics);
+        let mut ticker = tokio::time::interval(metrics.flush_interval);
+        tokio::spawn(async move {
+            // The first tick completes at once.
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(metrics) = weak.upgrade() else {
+                    break;
+                };
+                if let Err(e) = metrics.flush() {
+                    debug!(target: "indexer", "failed to send statsd metrics: {e}");
+                }
+            }
+        });
+        metrics
+    }
+
+    /// Address metrics are sent to.
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// Send everything received since the last flush now.
+    pub fn flush(&self) -> Result<(), IndexerError> {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        let mut lines = Vec::new();
+        for ((name, tags), value) in &pending.counters {
+            lines.push(self.line(name, &value.to_string(), "c", tags));
+        }
+        for ((name, tags), value) in &pending.gauges {
+            lines.push(self.line(name, &value.to_string(), "g", tags));
+        }
+        for (name, millis) in &pending.timings {
+            lines.push(self.line(name, &millis.to_string(), "ms", &[]));
+        }
+        let mut packet = String::new();
+        for line in lines {
+            if !packet.is_empty() && packet.len() + 1 + line.len() > MAX_PACKET {
+                self.send(&packet)?;
+                packet.clear();
+            }
+            if !packet.is_empty() {
+                packet.push('\n');
+            }
+            packet.push_str(&line);
+        }
+        if !packet.is_empty() {
+            self.send(&packet)?;
+        }
+        Ok(())
+    }
+
+    fn send(&self, packet: &str) -> Result<(), IndexerError> {
+        self.socket.send_to(packet.as_bytes(), self.target)?;
+        Ok(())
+    }
+
+    /// `prefix.name:value|kind|#tags`
+    fn line(&self, name: &str, value: &str, kind: &str, tags: &[(&'static str, String)]) -> String {
+        let mut line = String::new();
+        if !self.prefix.is_empty() {
+            line.push_str(&self.prefix);
+            line.push('.');
+        }
+        let _ = write!(line, "{name}:{value}|{kind}");
+        let tags = self
+            .tags
+            .iter()
+            .cloned()
+            .chain(tags.iter().map(|(key, value)| format!("{key}:{value}")));
+        for (i, tag) in tags.enumerate() {
+            line.push_str(if i == 0 { "|#" } else { "," });
+            line.push_str(&tag);
+        }
+        line
+    }
+
+    /// Record that block `number`, with `events` events, went through the
+    /// handlers in `elapsed`.
+    pub fn observe_block(&self, number: BlockNumber, events: usize, elapsed: Duration) {
+        self.count("blocks_processed", Vec::new(), 1);
+        self.count("events_processed", Vec::new(), events as u64);
+        self.pending
+            .lock()
+            .unwrap()
+            .timings
+            .push(("block_processing_time", elapsed.as_micros() as f64 / 1000.0));
+        self.last_processed.store(number, Ordering::Relaxed);
+        self.gauge("last_processed_block", Vec::new(), gauge_value(number));
+        self.update_lag();
+    }
+
+    /// Record that the chain reached block `number`; lower numbers are ignored.
+    pub fn observe_head(&self, number: BlockNumber) {
+        if self.head.fetch_max(number, Ordering::Relaxed) < number {
+            self.gauge("chain_head", Vec::new(), gauge_value(number));
+            self.update_lag();
+        }
+    }
+
+    /// Count a handler error, tagged with the handler named by
+    /// [`IndexerError::HandlerFailed`].
+    pub fn record_handler_error(&self, error: &IndexerError) {
+        let handler = sanitize(handler_label(error));
+        self.count("handler_errors", vec![("handler", handler)], 1);
+    }
+
+    /// Count `events` events `sink` delivered, or dropped if not `delivered`.
+    pub fn record_sink_delivery(&self, sink: &str, events: usize, delivered: bool) {
+        let outcome = if delivered { "sent" } else { "failed" };
+        let tags = vec![("sink", sanitize(sink)), ("outcome", outcome.to_string())];
+        self.count("sink_events", tags, events as u64);
+    }
+
+    /// Report the state of `breaker` now and on every transition.
+    pub fn watch_breaker(self: &Arc<Self>, breaker: &CircuitBreaker) {
+        watch_breaker(self.clone(), breaker);
+    }
+
+    fn count(&self, name: &'static str, tags: Tags, by: u64) {
+        *self
+            .pending
+            .lock()
+            .unwrap()
+            .counters
+            .entry((name, tags))
+            .or_default() += by;
+    }
+
+    fn gauge(&self, name: &'static str, tags: Tags, value: i64) {
+        self.pending
+            .lock()
+            .unwrap()
+            .gauges
+            .insert((name, tags), value);
+    }
+
+    fn update_lag(&self) {
+        let head = self.head.load(Ordering::Relaxed);
+        if head > 0 {
+            let lag = head.saturating_sub(self.last_processed.load(Ordering::Relaxed));
+            self.gauge("chain_lag_blocks", Vec::new(), gauge_value(lag));
+        }
+    }
+}
+
+impl Drop for StatsdMetrics {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            debug!(target: "indexer", "failed to send statsd metrics: {e}");
+        }
+    }
+}
+
+impl RetryObserver for StatsdMetrics {
+    fn on_retry(&self, _attempt: usize, _delay: Duration, _error: &IndexerError) {
+        self.count("rpc_retries", Vec::new(), 1);
+    }
+
+    fn on_give_up(&self, _attempts: usize, _error: &IndexerError) {
+        self.count("rpc_retry_give_ups", Vec::new(), 1);
+    }
+}
+
+impl MetricsRecorder for StatsdMetrics {
+    fn observe_block(&self, number: BlockNumber, events: usize, elapsed: Duration) {
+        StatsdMetrics::observe_block(self, number, events, elapsed);
+    }
+
+    fn observe_head(&self, number: BlockNumber) {
+        StatsdMetrics::observe_head(self, number);
+    }
+
+    fn record_handler_error(&self, error: &IndexerError) {
+        StatsdMetrics::record_handler_error(self, error);
+    }
+
+    fn record_sink_delivery(&self, sink: &str, events: usize, delivered: bool) {
+        StatsdMetrics::record_sink_delivery(self, sink, events, delivered);
+    }
+
+    fn record_breaker_state(&self, domain: &'static str, state: &BreakerState) {
+        let tags = vec![("breaker", domain.to_string())];
+        self.gauge("breaker_state", tags, breaker_value(state));
+    }
+}
+
+/// `value` with the characters StatsD uses as separators replaced, e.g. the
+/// `::` of handler names taken from type names.
+fn sanitize(value: &str) -> String {
+    value.replace([':', '|', ',', '#', '@', '\n'], "_")
+}
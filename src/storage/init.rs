@@ -21,63 +21,110 @@ use crate::storage::json::JsonStore;
 use crate::storage::postgres::PostgreSQLStore;
 #[cfg(feature = "sqlite")]
 use crate::storage::sqlite::SQLiteStore;
-use crate::storage::CheckpointStore;
-use std::path::Path;
+use crate::storage::{Checkpoint, CheckpointStore, MemoryStore, StorageConfig};
 
-pub async fn init_store(
-    database_url: Option<String>,
-) -> Result<Box<dyn CheckpointStore>, IndexerError> {
-    if let Some(url) = database_url {
-        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+/// Open the checkpoint store described by `storage`.
+///
+/// Fails with [`IndexerError::StorageBackendUnavailable`] if the backend's cargo feature
+/// is not enabled.
+pub async fn init_store(storage: StorageConfig) -> Result<Box<dyn CheckpointStore>, IndexerError> {
+    match storage {
+        StorageConfig::Postgres { url } => {
             #[cfg(feature = "postgres")]
             {
-                let store = PostgreSQLStore::new(&url).await?;
-                return Ok(Box::new(store));
+                let store = PostgreSQLStore::new(url.as_str()).await?;
+                Ok(Box::new(store))
             }
             #[cfg(not(feature = "postgres"))]
             {
-                return Err(IndexerError::invalid_config(
-                    "database_url",
-                    "postgres feature disabled",
-                ));
+                Err(IndexerError::StorageBackendUnavailable {
+                    scheme: url.as_str().split(':').next().unwrap_or_default().into(),
+                    feature: "postgres",
+                })
             }
-        } else if url.starts_with("sqlite://") {
+        }
+        StorageConfig::Sqlite { url } => {
             #[cfg(feature = "sqlite")]
             {
-                let path = url.trim_start_matches("sqlite://");
-                let store = SQLiteStore::new(path).await?;
-                return Ok(Box::new(store));
+                let store = SQLiteStore::from_url(&url).await?;
+                Ok(Box::new(store))
             }
             #[cfg(not(feature = "sqlite"))]
             {
-                return Err(IndexerError::invalid_config(
-                    "database_url",
-                    "sqlite feature disabled",
-                ));
+                let _ = url;
+                Err(IndexerError::StorageBackendUnavailable {
+                    scheme: "sqlite".into(),
+                    feature: "sqlite",
+                })
+            }
+        }
+        StorageConfig::Json { path } => {
+            #[cfg(feature = "json-storage")]
+            {
+                if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                    if !dir.exists() {
+                        tokio::fs::create_dir_all(dir).await?;
+                    }
+                }
+                Ok(Box::new(JsonStore::new(path)))
+            }
+            #[cfg(not(feature = "json-storage"))]
+            {
+                let _ = path;
+                Err(IndexerError::invalid_config(
+                    "storage",
+                    "No storage backend enabled",
+                ))
             }
-        } else {
-            return Err(IndexerError::invalid_config(
-                "database_url",
-                "Unsupported database URL",
-            ));
         }
+        StorageConfig::Memory => Ok(Box::new(MemoryStore::new())),
     }
+}
+
+/// Read the current checkpoint from `store` in a backend-independent form.
+pub async fn export_checkpoint(
+    store: &dyn CheckpointStore,
+) -> Result<Option<Checkpoint>, IndexerError> {
+    store.load_checkpoint_info().await
+}
 
-    // Default to JSON storage
-    #[cfg(feature = "json-storage")]
-    {
-        let base_dir = Path::new("database");
-        if !base_dir.exists() {
-            tokio::fs::create_dir_all(base_dir).await?;
+/// Write `checkpoint` into `store`.
+///
+/// If the store already holds a checkpoint newer than `checkpoint`, this fails with
+/// [`IndexerError::CheckpointConflict`] unless `overwrite` is set.
+pub async fn import_checkpoint(
+    store: &dyn CheckpointStore,
+    checkpoint: &Checkpoint,
+    overwrite: bool,
+) -> Result<(), IndexerError> {
+    if !overwrite {
+        if let Some(existing) = store.load_checkpoint().await? {
+            if existing > checkpoint.block {
+                return Err(IndexerError::CheckpointConflict {
+                    existing,
+                    incoming: checkpoint.block,
+                });
+            }
         }
-        let json_path = base_dir.join("checkpoint.json");
-        let store = JsonStore::new(json_path);
-        Ok(Box::new(store))
     }
+    store.store_checkpoint(checkpoint.block).await
+}
 
-    #[cfg(not(feature = "json-storage"))]
-    Err(IndexerError::invalid_config(
-        "storage",
-        "No storage backend enabled",
-    ))
+/// Copy the checkpoint from one storage backend to another.
+///
+/// Both URLs are resolved with [`StorageConfig::from_database_url`], so `None` selects the
+/// default JSON store.
+/// Returns the migrated checkpoint, or `None` if the source had nothing to migrate.
+pub async fn migrate_checkpoint(
+    from_url: Option<String>,
+    to_url: Option<String>,
+    overwrite: bool,
+) -> Result<Option<Checkpoint>, IndexerError> {
+    let from = init_store(StorageConfig::from_database_url(from_url.as_deref())?).await?;
+    let Some(checkpoint) = export_checkpoint(from.as_ref()).await? else {
+        return Ok(None);
+    };
+    let to = init_store(StorageConfig::from_database_url(to_url.as_deref())?).await?;
+    import_checkpoint(to.as_ref(), &checkpoint, overwrite).await?;
+    Ok(Some(checkpoint))
 }
@@ -15,16 +15,20 @@
  */
 
 use crate::error::IndexerError;
-use crate::storage::CheckpointStore;
+use crate::storage::{Checkpoint, CheckpointStore};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 #[derive(Serialize, Deserialize)]
 struct JsonCheckpoint {
     last_block: u64,
+    /// ISO-8601 timestamp of the last write. Absent in files written by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated_at: Option<String>,
 }
 
 pub struct JsonStore {
@@ -39,11 +43,8 @@ impl JsonStore {
         }
         Self { path }
     }
-}
 
-#[async_trait]
-impl CheckpointStore for JsonStore {
-    async fn load_checkpoint(&self) -> Result<Option<u64>, IndexerError> {
+    fn read(&self) -> Result<Option<JsonCheckpoint>, IndexerError> {
         if !self.path.exists() {
             return Ok(None);
         }
@@ -52,12 +53,41 @@ impl CheckpointStore for JsonStore {
             backend: "json".into(),
             source: Box::new(e),
         })?;
-        let checkpoint: JsonCheckpoint = serde_json::from_str(&data)?;
-        Ok(Some(checkpoint.last_block))
+        Ok(Some(serde_json::from_str(&data)?))
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for JsonStore {
+    async fn load_checkpoint(&self) -> Result<Option<u64>, IndexerError> {
+        Ok(self.read()?.map(|checkpoint| checkpoint.last_block))
+    }
+
+    async fn load_checkpoint_info(&self) -> Result<Option<Checkpoint>, IndexerError> {
+        let Some(stored) = self.read()? else {
+            return Ok(None);
+        };
+        let updated_at = stored
+            .updated_at
+            .as_deref()
+            .map(humantime::parse_rfc3339)
+            .transpose()
+            .map_err(|e| IndexerError::CheckpointError {
+                operation: "load_checkpoint".into(),
+                backend: "json".into(),
+                source: Box::new(e),
+            })?;
+        Ok(Some(Checkpoint {
+            block: stored.last_block,
+            updated_at,
+        }))
     }
 
     async fn store_checkpoint(&self, block: u64) -> Result<(), IndexerError> {
-        let checkpoint = JsonCheckpoint { last_block: block };
+        let checkpoint = JsonCheckpoint {
+            last_block: block,
+            updated_at: Some(humantime::format_rfc3339_millis(SystemTime::now()).to_string()),
+        };
         let json = serde_json::to_string_pretty(&checkpoint)?;
         let mut file = fs::File::create(&self.path).map_err(|e| IndexerError::CheckpointError {
             operation: "store_checkpoint".into(),
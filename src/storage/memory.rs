@@ -0,0 +1,52 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::error::IndexerError;
+use crate::storage::{Checkpoint, CheckpointStore};
+use async_trait::async_trait;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Checkpoint store that keeps the checkpoint in memory, so every run starts afresh.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    checkpoint: Mutex<Option<Checkpoint>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for MemoryStore {
+    async fn load_checkpoint(&self) -> Result<Option<u64>, IndexerError> {
+        Ok(self.load_checkpoint_info().await?.map(|c| c.block))
+    }
+
+    async fn load_checkpoint_info(&self) -> Result<Option<Checkpoint>, IndexerError> {
+        Ok(self.checkpoint.lock().unwrap().clone())
+    }
+
+    async fn store_checkpoint(&self, block: u64) -> Result<(), IndexerError> {
+        *self.checkpoint.lock().unwrap() = Some(Checkpoint {
+            block,
+            updated_at: Some(SystemTime::now()),
+        });
+        Ok(())
+    }
+}
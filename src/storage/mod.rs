@@ -15,9 +15,17 @@
  */
 
 use crate::error::IndexerError;
+use crate::types::BlockNumber;
+use crate::validated_types::{PostgresUrl, SqliteUrl};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 pub mod init;
+pub mod memory;
+pub mod retrying;
 
 #[cfg(feature = "json-storage")]
 pub mod json;
@@ -28,8 +36,241 @@ pub mod postgres;
 #[cfg(feature = "sqlite")]
 pub mod sqlite;
 
+pub use memory::MemoryStore;
+pub use retrying::RetryingStore;
+
+/// Names of the storage backends compiled into this build.
+pub fn enabled_backends() -> Vec<&'static str> {
+    [
+        cfg!(feature = "json-storage").then_some("json"),
+        cfg!(feature = "postgres").then_some("postgres"),
+        cfg!(feature = "sqlite").then_some("sqlite"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Location of the default JSON checkpoint file.
+pub const DEFAULT_JSON_PATH: &str = "database/checkpoint.json";
+
+/// Checkpoint backend to open, together with its validated location.
+///
+/// In config files this is the `[storage]` table, with a `backend` key of `json`,
+/// `postgres`, `sqlite` or `memory`:
+///
+/// ```toml
+/// [storage]
+/// backend = "json"
+/// path = "data/checkpoint.json"
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "lowercase", deny_unknown_fields)]
+pub enum StorageConfig {
+    /// JSON file at `path`; its directory is created if missing.
+    Json {
+        path: PathBuf,
+    },
+    Postgres {
+        url: PostgresUrl,
+    },
+    Sqlite {
+        url: SqliteUrl,
+    },
+    /// Keep the checkpoint in memory, so every run starts afresh.
+    Memory,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self::json(DEFAULT_JSON_PATH)
+    }
+}
+
+impl StorageConfig {
+    /// JSON storage at `path`.
+    pub fn json(path: impl Into<PathBuf>) -> Self {
+        Self::Json { path: path.into() }
+    }
+
+    /// Parse a database URL, choosing the backend from its scheme.
+    pub fn parse(url: &str) -> Result<Self, IndexerError> {
+        if url.trim().is_empty() {
+            return Err(IndexerError::invalid_config(
+                "database_url",
+                "cannot be empty",
+            ));
+        }
+        match url.split_once(':').map(|(scheme, _)| scheme) {
+            Some("postgres" | "postgresql") => Ok(Self::Postgres {
+                url: PostgresUrl::parse(url)?,
+            }),
+            Some("sqlite") => Ok(Self::Sqlite {
+                url: SqliteUrl::parse(url)?,
+            }),
+            scheme => Err(IndexerError::UnsupportedStorageScheme {
+                scheme: scheme.unwrap_or_default().to_string(),
+            }),
+        }
+    }
+
+    /// Parse a database URL for `backend`, rejecting URLs that select another backend.
+    pub fn parse_for(backend: &'static str, url: &str) -> Result<Self, IndexerError> {
+        let spec = Self::parse(url)?;
+        if spec.backend() != backend {
+            return Err(IndexerError::invalid_config(
+                "database_url",
+                format!(
+                    "expected a {backend} URL, got a {} URL; use with_{} instead",
+                    spec.backend(),
+                    spec.backend()
+                ),
+            ));
+        }
+        Ok(spec)
+    }
+
+    /// Resolve an optional database URL; `None` selects the default JSON store.
+    pub fn from_database_url(url: Option<&str>) -> Result<Self, IndexerError> {
+        url.map_or_else(|| Ok(Self::default()), Self::parse)
+    }
+
+    /// The database URL selecting this backend; `None` for the JSON and memory stores,
+    /// which have no URL form.
+    pub fn database_url(&self) -> Option<String> {
+        match self {
+            Self::Postgres { url } => Some(url.as_str().to_string()),
+            Self::Sqlite { url } => Some(url.to_url()),
+            Self::Json { .. } | Self::Memory => None,
+        }
+    }
+
+    /// Name of the backend, as listed by [`enabled_backends`].
+    pub fn backend(&self) -> &'static str {
+        match self {
+            Self::Postgres { .. } => "postgres",
+            Self::Sqlite { .. } => "sqlite",
+            Self::Json { .. } => "json",
+            Self::Memory => "memory",
+        }
+    }
+
+    /// Check that the store can be opened where configured.
+    ///
+    /// For JSON storage the checkpoint file's directory must exist or be creatable: its
+    /// nearest existing ancestor has to be a writable directory.
+    pub fn validate(&self) -> Result<(), IndexerError> {
+        let Self::Json { path } = self else {
+            return Ok(());
+        };
+        if path.as_os_str().is_empty() {
+            return Err(IndexerError::invalid_config(
+                "storage.path",
+                "cannot be empty",
+            ));
+        }
+        let parent = path.parent().unwrap_or(Path::new(""));
+        let existing = parent
+            .ancestors()
+            .find(|dir| dir.as_os_str().is_empty() || dir.exists())
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let unusable = |reason: String| {
+            IndexerError::invalid_config(
+                "storage.path",
+                format!("cannot create `{}`: {reason}", parent.display()),
+            )
+        };
+        let metadata = std::fs::metadata(existing).map_err(|e| unusable(e.to_string()))?;
+        if !metadata.is_dir() {
+            return Err(unusable(format!(
+                "`{}` is not a directory",
+                existing.display()
+            )));
+        }
+        if metadata.permissions().readonly() {
+            return Err(unusable(format!("`{}` is read-only", existing.display())));
+        }
+        Ok(())
+    }
+}
+
+/// Backend-independent snapshot of the indexer's progress.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub block: BlockNumber,
+    /// When the checkpoint was last written, if the backend records it.
+    #[serde(default)]
+    pub updated_at: Option<SystemTime>,
+}
+
+impl Checkpoint {
+    pub fn new(block: BlockNumber) -> Self {
+        Self {
+            block,
+            updated_at: None,
+        }
+    }
+
+    /// Time elapsed since the checkpoint was last written.
+    pub fn staleness(&self) -> Option<Duration> {
+        self.updated_at
+            .map(|at| SystemTime::now().duration_since(at).unwrap_or_default())
+    }
+}
+
 #[async_trait]
 pub trait CheckpointStore: Send + Sync {
     async fn load_checkpoint(&self) -> Result<Option<u64>, IndexerError>;
     async fn store_checkpoint(&self, block: u64) -> Result<(), IndexerError>;
+
+    /// Load the checkpoint together with any metadata the backend records.
+    async fn load_checkpoint_info(&self) -> Result<Option<Checkpoint>, IndexerError> {
+        Ok(self.load_checkpoint().await?.map(Checkpoint::new))
+    }
+}
+
+#[async_trait]
+impl<T: CheckpointStore + ?Sized> CheckpointStore for Box<T> {
+    async fn load_checkpoint(&self) -> Result<Option<u64>, IndexerError> {
+        (**self).load_checkpoint().await
+    }
+
+    async fn store_checkpoint(&self, block: u64) -> Result<(), IndexerError> {
+        (**self).store_checkpoint(block).await
+    }
+
+    async fn load_checkpoint_info(&self) -> Result<Option<Checkpoint>, IndexerError> {
+        (**self).load_checkpoint_info().await
+    }
+}
+
+#[async_trait]
+impl<T: CheckpointStore + ?Sized> CheckpointStore for Arc<T> {
+    async fn load_checkpoint(&self) -> Result<Option<u64>, IndexerError> {
+        (**self).load_checkpoint().await
+    }
+
+    async fn store_checkpoint(&self, block: u64) -> Result<(), IndexerError> {
+        (**self).store_checkpoint(block).await
+    }
+
+    async fn load_checkpoint_info(&self) -> Result<Option<Checkpoint>, IndexerError> {
+        (**self).load_checkpoint_info().await
+    }
+}
+
+#[async_trait]
+impl<T: CheckpointStore + ?Sized> CheckpointStore for &T {
+    async fn load_checkpoint(&self) -> Result<Option<u64>, IndexerError> {
+        (**self).load_checkpoint().await
+    }
+
+    async fn store_checkpoint(&self, block: u64) -> Result<(), IndexerError> {
+        (**self).store_checkpoint(block).await
+    }
+
+    async fn load_checkpoint_info(&self) -> Result<Option<Checkpoint>, IndexerError> {
+        (**self).load_checkpoint_info().await
+    }
 }
@@ -15,12 +15,17 @@
  */
 
 use crate::error::IndexerError;
-use crate::storage::CheckpointStore;
+use crate::storage::{Checkpoint, CheckpointStore};
 use async_trait::async_trait;
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use futures::stream::{BoxStream, StreamExt};
+use sqlx::postgres::{PgListener, PgPoolOptions};
+use sqlx::PgPool;
+use std::time::{Duration, UNIX_EPOCH};
+use tracing::warn;
 
 pub struct PostgreSQLStore {
     pool: PgPool,
+    notify_channel: Option<String>,
 }
 
 impl PostgreSQLStore {
@@ -38,7 +43,8 @@ impl PostgreSQLStore {
         sqlx::query(
             "CREATE TABLE IF NOT EXISTS indexer_checkpoint (
                 id TEXT PRIMARY KEY,
-                last_block BIGINT NOT NULL
+                last_block BIGINT NOT NULL,
+                updated_at TIMESTAMPTZ
             )",
         )
         .execute(&pool)
@@ -49,7 +55,81 @@ impl PostgreSQLStore {
             source: Box::new(e),
         })?;
 
-        Ok(Self { pool })
+        // Tables created by older versions lack `updated_at`; existing rows stay NULL
+        // until the next checkpoint write.
+        sqlx::query(
+            "ALTER TABLE indexer_checkpoint ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| IndexerError::CheckpointError {
+            operation: "migrate".into(),
+            backend: "postgres".into(),
+            source: Box::new(e),
+        })?;
+
+        Ok(Self {
+            pool,
+            notify_channel: None,
+        })
+    }
+
+    /// Issue `NOTIFY <channel>, '<block>'` after every successful checkpoint write.
+    ///
+    /// Notification failures are logged and never fail the checkpoint write itself.
+    pub fn with_notifications(mut self, channel: impl Into<String>) -> Self {
+        self.notify_channel = Some(channel.into());
+        self
+    }
+
+    async fn notify(&self, channel: &str, block: u64) {
+        let res = sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(channel)
+            .bind(block.to_string())
+            .execute(&self.pool)
+            .await;
+        if let Err(e) = res {
+            warn!(target: "indexer", "failed to notify {} of checkpoint {}: {}", channel, block, e);
+        }
+    }
+}
+
+/// Consumer side of [`PostgreSQLStore::with_notifications`].
+pub struct PostgresCheckpointListener;
+
+impl PostgresCheckpointListener {
+    /// Listen on `channel` and yield each checkpointed block number.
+    ///
+    /// Payloads that are not block numbers are ignored.
+    pub async fn subscribe(
+        database_url: &str,
+        channel: &str,
+    ) -> Result<BoxStream<'static, u64>, IndexerError> {
+        let map_err = |operation: &str, e: sqlx::Error| IndexerError::CheckpointError {
+            operation: operation.into(),
+            backend: "postgres".into(),
+            source: Box::new(e),
+        };
+        let mut listener = PgListener::connect(database_url)
+            .await
+            .map_err(|e| map_err("connect", e))?;
+        listener
+            .listen(channel)
+            .await
+            .map_err(|e| map_err("listen", e))?;
+
+        let stream = listener
+            .into_stream()
+            .filter_map(|notification| async move {
+                match notification {
+                    Ok(notification) => notification.payload().parse::<u64>().ok(),
+                    Err(e) => {
+                        warn!(target: "indexer", "checkpoint listener error: {}", e);
+                        None
+                    }
+                }
+            });
+        Ok(stream.boxed())
     }
 }
 
@@ -70,11 +150,31 @@ impl CheckpointStore for PostgreSQLStore {
         Ok(row.map(|v| v as u64))
     }
 
+    async fn load_checkpoint_info(&self) -> Result<Option<Checkpoint>, IndexerError> {
+        let row: Option<(i64, Option<i64>)> = sqlx::query_as(
+            "SELECT last_block, CAST(EXTRACT(EPOCH FROM updated_at) * 1000 AS BIGINT)
+             FROM indexer_checkpoint WHERE id = $1",
+        )
+        .bind("bittensor")
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| IndexerError::CheckpointError {
+            operation: "load_checkpoint".into(),
+            backend: "postgres".into(),
+            source: Box::new(e),
+        })?;
+
+        Ok(row.map(|(block, updated_at)| Checkpoint {
+            block: block as u64,
+            updated_at: updated_at.map(|ms| UNIX_EPOCH + Duration::from_millis(ms as u64)),
+        }))
+    }
+
     async fn store_checkpoint(&self, block: u64) -> Result<(), IndexerError> {
         sqlx::query(
-            "INSERT INTO indexer_checkpoint (id, last_block) 
-             VALUES ($1, $2) 
-             ON CONFLICT (id) DO UPDATE SET last_block = EXCLUDED.last_block",
+            "INSERT INTO indexer_checkpoint (id, last_block, updated_at) 
+             VALUES ($1, $2, NOW()) 
+             ON CONFLICT (id) DO UPDATE SET last_block = EXCLUDED.last_block, updated_at = EXCLUDED.updated_at",
         )
         .bind("bittensor")
         .bind(block as i64)
@@ -86,6 +186,10 @@ impl CheckpointStore for PostgreSQLStore {
             source: Box::new(e),
         })?;
 
+        if let Some(channel) = &self.notify_channel {
+            self.notify(channel, block).await;
+        }
+
         Ok(())
     }
 }
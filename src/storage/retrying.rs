@@ -0,0 +1,123 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::error::IndexerError;
+use crate::retry::{
+    retry_with_backoff_observed, BreakerDomain, CircuitBreaker, CircuitBreakerConfig,
+    NoopRetryObserver, RetryConfig, RetryObserver,
+};
+use crate::storage::{Checkpoint, CheckpointStore};
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::Arc;
+
+/// [`CheckpointStore`] decorator that retries transient failures of the inner store.
+///
+/// The store owns its own [`CircuitBreaker`], so storage outages never trip the breaker
+/// guarding node RPC calls.
+pub struct RetryingStore<S> {
+    inner: S,
+    retry_config: RetryConfig,
+    circuit_breaker: Arc<CircuitBreaker>,
+    observer: Option<Arc<dyn RetryObserver>>,
+}
+
+impl<S: CheckpointStore> RetryingStore<S> {
+    pub fn new(inner: S, retry_config: RetryConfig, circuit_breaker: CircuitBreaker) -> Self {
+        Self {
+            inner,
+            retry_config,
+            circuit_breaker: Arc::new(circuit_breaker.with_domain(BreakerDomain::Storage)),
+            observer: None,
+        }
+    }
+
+    /// Wrap `inner` with the default storage retry policy.
+    pub fn with_defaults(inner: S) -> Self {
+        Self::new(
+            inner,
+            RetryConfig::default(),
+            CircuitBreaker::from_config(&CircuitBreakerConfig::storage()),
+        )
+    }
+
+    /// Replace the retry policy, keeping the circuit breaker.
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuit_breaker
+    }
+
+    /// The breaker, for watching it from another task.
+    pub(crate) fn shared_circuit_breaker(&self) -> Arc<CircuitBreaker> {
+        self.circuit_breaker.clone()
+    }
+
+    /// Report retries and give-ups to `observer`.
+    pub(crate) fn set_observer(&mut self, observer: Arc<dyn RetryObserver>) {
+        self.observer = Some(observer);
+    }
+
+    async fn retry<F, Fut, T>(&self, op: F) -> Result<T, IndexerError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, IndexerError>>,
+    {
+        if self.circuit_breaker.is_open() {
+            return Err(self.circuit_breaker.open_error());
+        }
+        let observer: &dyn RetryObserver = match &self.observer {
+            Some(observer) => observer.as_ref(),
+            None => &NoopRetryObserver,
+        };
+        let res =
+            retry_with_backoff_observed(op, &self.retry_config, &self.circuit_breaker, observer)
+                .await;
+        match &res {
+            Ok(_) => self.circuit_breaker.record_success(),
+            // Rejected by the breaker itself, not a failure of the store.
+            Err(IndexerError::CircuitOpen { .. }) => {}
+            Err(e) => {
+                if self.retry_config.is_retryable(e) {
+                    self.circuit_breaker.record_failure();
+                }
+            }
+        }
+        res
+    }
+}
+
+#[async_trait]
+impl<S: CheckpointStore> CheckpointStore for RetryingStore<S> {
+    async fn load_checkpoint(&self) -> Result<Option<u64>, IndexerError> {
+        self.retry(|| self.inner.load_checkpoint()).await
+    }
+
+    async fn store_checkpoint(&self, block: u64) -> Result<(), IndexerError> {
+        self.retry(|| self.inner.store_checkpoint(block)).await
+    }
+
+    async fn load_checkpoint_info(&self) -> Result<Option<Checkpoint>, IndexerError> {
+        self.retry(|| self.inner.load_checkpoint_info()).await
+    }
+}
@@ -15,34 +15,58 @@
  */
 
 use crate::error::IndexerError;
-use crate::storage::CheckpointStore;
+use crate::storage::{Checkpoint, CheckpointStore};
+use crate::validated_types::{SqliteMode, SqliteUrl};
 use async_trait::async_trait;
 use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub struct SQLiteStore {
     pool: SqlitePool,
 }
 
 impl SQLiteStore {
-    pub async fn new(path: &str) -> Result<Self, IndexerError> {
-        if let Some(parent) = std::path::Path::new(path).parent() {
-            tokio::fs::create_dir_all(parent).await?;
+    /// Open a store from a SQLite URL (`sqlite://path`, `sqlite::memory:`) or a bare file path.
+    pub async fn new(url: &str) -> Result<Self, IndexerError> {
+        let url = if url.starts_with("sqlite:") {
+            SqliteUrl::parse(url)?
+        } else {
+            SqliteUrl::parse(&format!("sqlite://{url}"))?
+        };
+        Self::from_url(&url).await
+    }
+
+    pub async fn from_url(url: &SqliteUrl) -> Result<Self, IndexerError> {
+        let mut options = SqlitePoolOptions::new().max_connections(1);
+        if url.is_memory() {
+            // An in-memory database is dropped with its last connection, so keep one open
+            // for the lifetime of the pool.
+            options = options
+                .min_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None);
+        } else {
+            url.ensure_parent_dir()?;
         }
 
-        let pool = SqlitePoolOptions::new()
-            .max_connections(1)
-            .connect(path)
+        let pool = options
+            .connect_with(url.connect_options()?)
             .await
             .map_err(|e| IndexerError::CheckpointError {
                 operation: "connect".into(),
                 backend: "sqlite".into(),
                 source: Box::new(e),
             })?;
+        if url.mode() == SqliteMode::ReadOnly {
+            // Nothing can be created or migrated; the schema comes from an earlier run.
+            return Ok(Self { pool });
+        }
 
         sqlx::query(
             "CREATE TABLE IF NOT EXISTS indexer_checkpoint (
                 id TEXT PRIMARY KEY,
-                last_block BIGINT NOT NULL
+                last_block BIGINT NOT NULL,
+                updated_at INTEGER
             )",
         )
         .execute(&pool)
@@ -53,6 +77,29 @@ impl SQLiteStore {
             source: Box::new(e),
         })?;
 
+        // Tables created by older versions lack `updated_at`; SQLite has no
+        // `ADD COLUMN IF NOT EXISTS`, so check the schema first.
+        let has_updated_at: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('indexer_checkpoint') WHERE name = 'updated_at'",
+        )
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| IndexerError::CheckpointError {
+            operation: "migrate".into(),
+            backend: "sqlite".into(),
+            source: Box::new(e),
+        })?;
+        if has_updated_at == 0 {
+            sqlx::query("ALTER TABLE indexer_checkpoint ADD COLUMN updated_at INTEGER")
+                .execute(&pool)
+                .await
+                .map_err(|e| IndexerError::CheckpointError {
+                    operation: "migrate".into(),
+                    backend: "sqlite".into(),
+                    source: Box::new(e),
+                })?;
+        }
+
         Ok(Self { pool })
     }
 }
@@ -74,14 +121,37 @@ impl CheckpointStore for SQLiteStore {
         Ok(row.map(|v| v as u64))
     }
 
+    async fn load_checkpoint_info(&self) -> Result<Option<Checkpoint>, IndexerError> {
+        let row: Option<(i64, Option<i64>)> =
+            sqlx::query_as("SELECT last_block, updated_at FROM indexer_checkpoint WHERE id = ?")
+                .bind("bittensor")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| IndexerError::CheckpointError {
+                    operation: "load_checkpoint".into(),
+                    backend: "sqlite".into(),
+                    source: Box::new(e),
+                })?;
+
+        Ok(row.map(|(block, updated_at)| Checkpoint {
+            block: block as u64,
+            updated_at: updated_at.map(|ms| UNIX_EPOCH + Duration::from_millis(ms as u64)),
+        }))
+    }
+
     async fn store_checkpoint(&self, block: u64) -> Result<(), IndexerError> {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
         sqlx::query(
-            "INSERT INTO indexer_checkpoint (id, last_block) 
-             VALUES (?, ?) 
-             ON CONFLICT(id) DO UPDATE SET last_block = excluded.last_block",
+            "INSERT INTO indexer_checkpoint (id, last_block, updated_at) 
+             VALUES (?, ?, ?) 
+             ON CONFLICT(id) DO UPDATE SET last_block = excluded.last_block, updated_at = excluded.updated_at",
         )
         .bind("bittensor")
         .bind(block as i64)
+        .bind(now_ms)
         .execute(&self.pool)
         .await
         .map_err(|e| IndexerError::CheckpointError {
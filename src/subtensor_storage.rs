@@ -0,0 +1,176 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Typed reads of `SubtensorModule` storage at the block being indexed, built
+//! as dynamic queries so no runtime codegen is needed.
+//!
+//! Every helper reads through [`Context::storage`], which the indexer attaches
+//! to each block's context. Errors name the storage item that failed, and an
+//! item missing from the runtime's metadata is reported the same way.
+
+use crate::error::IndexerError;
+use crate::filters::subtensor::PALLET;
+use crate::handler::Context;
+use scale_value::Value;
+use subxt::storage::{DynamicAddress, Storage};
+use subxt::utils::AccountId32;
+use subxt::{Config, OnlineClient};
+
+/// `TotalHotkeyStake`: total stake delegated to a hotkey, keyed by hotkey.
+pub const TOTAL_HOTKEY_STAKE: &str = "TotalHotkeyStake";
+/// `SubnetworkN`: number of registered neurons, keyed by netuid.
+pub const SUBNETWORK_N: &str = "SubnetworkN";
+/// `Owner`: coldkey owning a hotkey, keyed by hotkey.
+pub const OWNER: &str = "Owner";
+/// `Difficulty`: proof-of-work registration difficulty, keyed by netuid.
+pub const DIFFICULTY: &str = "Difficulty";
+/// `Burn`: TAO burned to register, in rao, keyed by netuid.
+pub const BURN: &str = "Burn";
+
+/// Address of `SubtensorModule.TotalHotkeyStake(hotkey)`.
+pub fn total_hotkey_stake_address(hotkey: &AccountId32) -> DynamicAddress<Vec<Value>> {
+    subxt::dynamic::storage(
+        PALLET,
+        TOTAL_HOTKEY_STAKE,
+        vec![Value::from_bytes(hotkey.0)],
+    )
+}
+
+/// Address of `SubtensorModule.SubnetworkN(netuid)`.
+pub fn subnet_n_address(netuid: u16) -> DynamicAddress<Vec<Value>> {
+    subxt::dynamic::storage(PALLET, SUBNETWORK_N, vec![Value::u128(netuid.into())])
+}
+
+/// Address of `SubtensorModule.Difficulty(netuid)`.
+pub fn difficulty_address(netuid: u16) -> DynamicAddress<Vec<Value>> {
+    subxt::dynamic::storage(PALLET, DIFFICULTY, vec![Value::u128(netuid.into())])
+}
+
+/// Address of `SubtensorModule.Burn(netuid)`.
+pub fn burn_address(netuid: u16) -> DynamicAddress<Vec<Value>> {
+    subxt::dynamic::storage(PALLET, BURN, vec![Value::u128(netuid.into())])
+}
+
+/// Address of `SubtensorModule.Owner(hotkey)`.
+pub fn owner_address(hotkey: &AccountId32) -> DynamicAddress<Vec<Value>> {
+    subxt::dynamic::storage(PALLET, OWNER, vec![Value::from_bytes(hotkey.0)])
+}
+
+/// Total stake on `hotkey` in rao at the context's block. Unset entries read
+/// as zero.
+pub async fn total_hotkey_stake<C: Config>(
+    ctx: &Context<C>,
+    hotkey: &AccountId32,
+) -> Result<u64, IndexerError> {
+    let value = fetch_u128(ctx, TOTAL_HOTKEY_STAKE, total_hotkey_stake_address(hotkey)).await?;
+    narrow(TOTAL_HOTKEY_STAKE, value)
+}
+
+/// Number of neurons registered on `netuid` at the context's block.
+pub async fn subnet_n<C: Config>(ctx: &Context<C>, netuid: u16) -> Result<u16, IndexerError> {
+    let value = fetch_u128(ctx, SUBNETWORK_N, subnet_n_address(netuid)).await?;
+    narrow(SUBNETWORK_N, value)
+}
+
+/// Registration difficulty of `netuid` at the context's block.
+pub async fn difficulty<C: Config>(ctx: &Context<C>, netuid: u16) -> Result<u64, IndexerError> {
+    let value = fetch_u128(ctx, DIFFICULTY, difficulty_address(netuid)).await?;
+    narrow(DIFFICULTY, value)
+}
+
+/// Registration burn of `netuid` in rao at the context's block.
+pub async fn burn<C: Config>(ctx: &Context<C>, netuid: u16) -> Result<u64, IndexerError> {
+    let value = fetch_u128(ctx, BURN, burn_address(netuid)).await?;
+    narrow(BURN, value)
+}
+
+/// Coldkey owning `hotkey` at the context's block, `None` if the hotkey was
+/// never associated with one.
+pub async fn owner<C: Config>(
+    ctx: &Context<C>,
+    hotkey: &AccountId32,
+) -> Result<Option<AccountId32>, IndexerError> {
+    let storage = attached(ctx, OWNER)?;
+    let thunk = storage
+        .fetch(&owner_address(hotkey))
+        .await
+        .map_err(|e| IndexerError::storage_query_failed(PALLET, OWNER, e))?;
+    thunk
+        .map(|thunk| thunk.as_type::<AccountId32>())
+        .transpose()
+        .map_err(|e| IndexerError::storage_query_failed(PALLET, OWNER, e))
+}
+
+/// Every `(hotkey, coldkey)` pair of the `Owner` map at the context's block.
+///
+/// This walks the whole map, so it is meant for one-off seeding rather than
+/// per-block use.
+pub async fn owners<C: Config>(
+    ctx: &Context<C>,
+) -> Result<Vec<(AccountId32, AccountId32)>, IndexerError> {
+    let storage = attached(ctx, OWNER)?;
+    let failed = |e| IndexerError::storage_query_failed(PALLET, OWNER, e);
+    let mut pairs = storage
+        .iter(subxt::dynamic::storage(PALLET, OWNER, Vec::<Value>::new()))
+        .await
+        .map_err(failed)?;
+    let mut owners = Vec::new();
+    while let Some(pair) = pairs.next().await {
+        let pair = pair.map_err(failed)?;
+        // `Blake2_128Concat` keys end with the raw 32-byte hotkey.
+        let hotkey = pair
+            .key_bytes
+            .len()
+            .checked_sub(32)
+            .and_then(|start| <[u8; 32]>::try_from(&pair.key_bytes[start..]).ok())
+            .map(AccountId32)
+            .ok_or_else(|| query_failed(OWNER, "storage key too short for a hotkey"))?;
+        let coldkey = pair.value.as_type::<AccountId32>().map_err(failed)?;
+        owners.push((hotkey, coldkey));
+    }
+    Ok(owners)
+}
+
+fn attached<'a, C: Config>(
+    ctx: &'a Context<C>,
+    item: &str,
+) -> Result<&'a Storage<C, OnlineClient<C>>, IndexerError> {
+    ctx.storage()
+        .ok_or_else(|| query_failed(item, "no storage access attached to this context"))
+}
+
+async fn fetch_u128<C: Config>(
+    ctx: &Context<C>,
+    item: &str,
+    address: DynamicAddress<Vec<Value>>,
+) -> Result<u128, IndexerError> {
+    let value = attached(ctx, item)?
+        .fetch_or_default(&address)
+        .await
+        .and_then(|thunk| thunk.to_value())
+        .map_err(|e| IndexerError::storage_query_failed(PALLET, item, e))?;
+    value
+        .as_u128()
+        .ok_or_else(|| query_failed(item, format!("expected an unsigned integer, got {value}")))
+}
+
+fn narrow<T: TryFrom<u128>>(item: &str, value: u128) -> Result<T, IndexerError> {
+    T::try_from(value).map_err(|_| query_failed(item, format!("value {value} out of range")))
+}
+
+fn query_failed(item: &str, message: impl Into<String>) -> IndexerError {
+    IndexerError::storage_query_failed(PALLET, item, subxt::Error::Other(message.into()))
+}
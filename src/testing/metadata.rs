@@ -0,0 +1,237 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Metadata of a minimal runtime with one pallet, and the SCALE bytes of its
+//! events and extrinsics.
+
+use frame_metadata::{
+    v15::{
+        CustomMetadata, ExtrinsicMetadata, OuterEnums, PalletCallMetadata, PalletEventMetadata,
+        PalletMetadata, PalletStorageMetadata, RuntimeMetadataV15,
+    },
+    RuntimeMetadataPrefixed,
+};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::{meta_type, MetaType, TypeInfo};
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::{Events, Phase, RootEvent};
+use subxt::Metadata;
+
+/// A `frame_system::EventRecord` of `event`, emitted by the runtime's only pallet.
+#[derive(Encode)]
+pub struct EventRecord<E: Encode> {
+    phase: Phase,
+    event: TestAllEvents<E>,
+    topics: Vec<subxt::config::HashFor<SubstrateConfig>>,
+}
+
+impl<E: Encode> EventRecord<E> {
+    /// `event` emitted during `phase`, without topics.
+    pub fn new(phase: Phase, event: E) -> Self {
+        Self {
+            phase,
+            event: TestAllEvents::Test(event),
+            topics: Vec::new(),
+        }
+    }
+
+    pub fn with_topics(mut self, topics: Vec<subxt::config::HashFor<SubstrateConfig>>) -> Self {
+        self.topics = topics;
+        self
+    }
+}
+
+/// The runtime's event enum, with its only pallet at index 0.
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq)]
+pub enum TestAllEvents<E> {
+    Test(E),
+}
+
+/// Mirrors the `RootEvent` impl generated by `#[subxt::subxt]` for the only pallet.
+impl<E: Decode> RootEvent for TestAllEvents<E> {
+    fn root_event(
+        mut pallet_bytes: &[u8],
+        _pallet_name: &str,
+        _pallet_event_ty: u32,
+        _metadata: &Metadata,
+    ) -> Result<Self, subxt::ext::subxt_core::Error> {
+        Ok(TestAllEvents::Test(E::decode(&mut pallet_bytes)?))
+    }
+}
+
+/// Metadata with a single pallet called `Test` whose events are `E`.
+pub fn test_metadata<E: TypeInfo + 'static>() -> Metadata {
+    pallet_metadata::<E>("Test")
+}
+
+/// Metadata with a single pallet called `pallet` whose events are `E`.
+pub fn pallet_metadata<E: TypeInfo + 'static>(pallet: &'static str) -> Metadata {
+    pallet_metadata_with_storage::<E>(pallet, None)
+}
+
+/// Like [`pallet_metadata`], with the pallet's storage entries.
+pub fn pallet_metadata_with_storage<E: TypeInfo + 'static>(
+    pallet: &'static str,
+    storage: Option<PalletStorageMetadata>,
+) -> Metadata {
+    to_metadata(pallet_runtime_metadata::<E>(pallet, storage))
+}
+
+/// [`pallet_metadata`] SCALE-encoded, as `state_getMetadata` returns it.
+pub fn pallet_metadata_bytes<E: TypeInfo + 'static>(pallet: &'static str) -> Vec<u8> {
+    pallet_runtime_metadata::<E>(pallet, None).encode()
+}
+
+fn pallet_runtime_metadata<E: TypeInfo + 'static>(
+    pallet: &'static str,
+    storage: Option<PalletStorageMetadata>,
+) -> RuntimeMetadataPrefixed {
+    #[derive(TypeInfo)]
+    struct ExtrinsicType<Call> {
+        call: Call,
+    }
+    #[derive(TypeInfo)]
+    enum RuntimeCall {
+        PalletName(Pallet),
+    }
+    #[derive(TypeInfo)]
+    enum Pallet {
+        SomeCall,
+    }
+
+    let pallets = vec![PalletMetadata {
+        name: pallet,
+        storage,
+        calls: None,
+        event: Some(PalletEventMetadata {
+            ty: meta_type::<E>(),
+        }),
+        constants: vec![],
+        error: None,
+        index: 0,
+        docs: vec![],
+    }];
+
+    let extrinsic = ExtrinsicMetadata {
+        version: 0,
+        signed_extensions: vec![],
+        address_ty: meta_type::<()>(),
+        call_ty: meta_type::<RuntimeCall>(),
+        signature_ty: meta_type::<()>(),
+        extra_ty: meta_type::<()>(),
+    };
+
+    runtime_metadata(pallets, extrinsic, meta_type::<TestAllEvents<E>>())
+}
+
+/// `MultiAddress::Id`, the only address variant signed test extrinsics use.
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq)]
+pub enum TestAddress {
+    Id([u8; 32]),
+}
+
+/// The runtime's call enum of [`call_metadata`].
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq)]
+pub enum TestAllCalls<Call> {
+    Test(Call),
+}
+
+/// Metadata with a single pallet called `pallet` at `index` whose calls are `Call`.
+/// Extrinsics are version 4, signed with a [`TestAddress`] and a 64 byte signature
+/// and no transaction extensions, see [`unsigned_extrinsic`] and [`signed_extrinsic`].
+pub fn call_metadata<Call: TypeInfo + 'static>(pallet: &'static str, index: u8) -> Metadata {
+    let pallets = vec![PalletMetadata {
+        name: pallet,
+        storage: None,
+        calls: Some(PalletCallMetadata {
+            ty: meta_type::<Call>(),
+        }),
+        event: None,
+        constants: vec![],
+        error: None,
+        index,
+        docs: vec![],
+    }];
+
+    let extrinsic = ExtrinsicMetadata {
+        version: 4,
+        signed_extensions: vec![],
+        address_ty: meta_type::<TestAddress>(),
+        call_ty: meta_type::<TestAllCalls<Call>>(),
+        signature_ty: meta_type::<[u8; 64]>(),
+        extra_ty: meta_type::<()>(),
+    };
+
+    to_metadata(runtime_metadata(pallets, extrinsic, meta_type::<()>()))
+}
+
+fn runtime_metadata(
+    pallets: Vec<PalletMetadata>,
+    extrinsic: ExtrinsicMetadata,
+    event_enum_ty: MetaType,
+) -> RuntimeMetadataPrefixed {
+    let meta = RuntimeMetadataV15::new(
+        pallets,
+        extrinsic,
+        meta_type::<()>(),
+        vec![],
+        OuterEnums {
+            call_enum_ty: meta_type::<()>(),
+            event_enum_ty,
+            error_enum_ty: meta_type::<()>(),
+        },
+        CustomMetadata {
+            map: Default::default(),
+        },
+    );
+    meta.into()
+}
+
+fn to_metadata(runtime_metadata: RuntimeMetadataPrefixed) -> Metadata {
+    let metadata: subxt_metadata::Metadata = runtime_metadata.try_into().unwrap();
+    Metadata::from(metadata)
+}
+
+/// A version 4 unsigned (bare) extrinsic carrying `call`, which starts with
+/// the pallet and call indices.
+pub fn unsigned_extrinsic(call: &[u8]) -> Vec<u8> {
+    let mut body = vec![0x04];
+    body.extend_from_slice(call);
+    body.encode()
+}
+
+/// A version 4 extrinsic carrying `call`, signed by `signer` with a dummy
+/// signature, matching [`call_metadata`].
+pub fn signed_extrinsic(signer: [u8; 32], call: &[u8]) -> Vec<u8> {
+    let mut body = vec![0x84];
+    TestAddress::Id(signer).encode_to(&mut body);
+    body.extend_from_slice(&[0u8; 64]);
+    body.extend_from_slice(call);
+    body.encode()
+}
+
+/// `System.Events` of a block with `records`, decoded with `metadata`.
+pub fn events<E: Encode>(
+    metadata: Metadata,
+    records: Vec<EventRecord<E>>,
+) -> Events<SubstrateConfig> {
+    Events::decode_from(event_bytes(records), metadata)
+}
+
+/// `records` SCALE-encoded as the `System.Events` storage value.
+pub fn event_bytes<E: Encode>(records: Vec<EventRecord<E>>) -> Vec<u8> {
+    records.encode()
+}
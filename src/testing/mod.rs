@@ -0,0 +1,334 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Helpers for testing handlers without a node, behind the `testing` feature.
+//!
+//! [`TestChain`] builds blocks of typed events for a minimal runtime, and
+//! [`run_handlers`] runs handlers over them through the indexer's own run loop,
+//! fed by a [`MockBlockSource`]. Each handler's calls are logged for assertions:
+//!
+//! ```rust,ignore
+//! #[derive(Encode, Decode, TypeInfo)]
+//! enum BalancesEvent {
+//!     Transfer { from: [u8; 32], to: [u8; 32], amount: u128 },
+//! }
+//!
+//! let mut chain = TestChain::<BalancesEvent>::new("Balances");
+//! chain.add_block(vec![EventRecord::new(
+//!     Phase::ApplyExtrinsic(1),
+//!     BalancesEvent::Transfer { from: [1; 32], to: [2; 32], amount: 5 },
+//! )]);
+//!
+//! let run = run_handlers(&chain, vec![Box::new(TransferHandler::default())]).await;
+//! run.result?;
+//! assert_eq!(run.handler(0).events(), vec![(1, "Balances.Transfer".to_string())]);
+//! ```
+
+mod metadata;
+mod source;
+
+pub use metadata::{
+    call_metadata, event_bytes, events, pallet_metadata, pallet_metadata_bytes,
+    pallet_metadata_with_storage, signed_extrinsic, test_metadata, unsigned_extrinsic, EventRecord,
+    TestAddress, TestAllCalls, TestAllEvents,
+};
+pub use source::MockBlockSource;
+
+use crate::builder::IndexerBuilder;
+use crate::error::IndexerError;
+use crate::extrinsics::ChainExtrinsic;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::run::RunSummary;
+use crate::storage::MemoryStore;
+use crate::types::{BlockNumber, ChainEvent};
+use async_trait::async_trait;
+use parity_scale_codec::Encode;
+use scale_info::TypeInfo;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use subxt::config::substrate::SubstrateConfig;
+use subxt::Metadata;
+
+/// Blocks of events of `E`, the event enum of the runtime's only pallet.
+///
+/// Block `0` has no events; [`add_block`](Self::add_block) appends blocks from `1`.
+pub struct TestChain<E> {
+    pallet: &'static str,
+    blocks: Vec<Vec<u8>>,
+    _event: PhantomData<fn() -> E>,
+}
+
+impl<E: Encode + TypeInfo + 'static> TestChain<E> {
+    /// A chain whose pallet is called `pallet`, without blocks yet.
+    pub fn new(pallet: &'static str) -> Self {
+        Self {
+            pallet,
+            blocks: Vec::new(),
+            _event: PhantomData,
+        }
+    }
+
+    /// Append a block emitting `records`, and return its number.
+    pub fn add_block(&mut self, records: Vec<EventRecord<E>>) -> BlockNumber {
+        self.blocks.push(event_bytes(records));
+        self.last_block()
+    }
+
+    /// Number of the last block, `0` before any was added.
+    pub fn last_block(&self) -> BlockNumber {
+        self.blocks.len() as BlockNumber
+    }
+
+    /// Metadata of the chain's runtime.
+    pub fn metadata(&self) -> Metadata {
+        pallet_metadata::<E>(self.pallet)
+    }
+
+    /// A source serving the chain's blocks, all of them finalized.
+    pub fn source(&self) -> MockBlockSource<SubstrateConfig> {
+        let blocks = self.blocks.clone();
+        MockBlockSource::new(pallet_metadata_bytes::<E>(self.pallet))
+            .with_blocks(1..=self.last_block(), |n| blocks[n as usize - 1].clone())
+            .finalized(self.last_block())
+    }
+}
+
+/// Run `handlers` over blocks `1..` of `chain`, as
+/// [`Indexer::run`](crate::indexer::Indexer::run) would with a node serving them.
+/// The chain needs at least one block. Storage queries from handlers fail.
+pub async fn run_handlers<E: Encode + TypeInfo + 'static>(
+    chain: &TestChain<E>,
+    handlers: Vec<Box<dyn Handler<SubstrateConfig>>>,
+) -> TestRun {
+    run_handlers_with(chain, IndexerBuilder::new(), handlers).await
+}
+
+/// Like [`run_handlers`], with the other settings of `builder`, such as an
+/// event allowlist or an error budget. The block source, store and block range
+/// are set here.
+pub async fn run_handlers_with<E: Encode + TypeInfo + 'static>(
+    chain: &TestChain<E>,
+    builder: IndexerBuilder<SubstrateConfig>,
+    handlers: Vec<Box<dyn Handler<SubstrateConfig>>>,
+) -> TestRun {
+    let mut builder = builder
+        .block_source(chain.source())
+        .with_store(MemoryStore::new())
+        .start_from_block(1)
+        .end_at_block(chain.last_block());
+    let mut logs = Vec::new();
+    for handler in handlers {
+        let log = Arc::new(Mutex::new(HandlerLog {
+            name: handler.name().to_string(),
+            calls: Vec::new(),
+        }));
+        builder = builder.add_handler(Logged {
+            inner: handler,
+            log: log.clone(),
+        });
+        logs.push(log);
+    }
+    let result = match builder.build().await {
+        Ok(mut indexer) => indexer.run().await,
+        Err(e) => Err(e),
+    };
+    TestRun {
+        result,
+        handlers: logs
+            .into_iter()
+            .map(|log| std::mem::take(&mut *log.lock().unwrap_or_else(|e| e.into_inner())))
+            .collect(),
+    }
+}
+
+/// Outcome of [`run_handlers`].
+pub struct TestRun {
+    /// What [`Indexer::run`](crate::indexer::Indexer::run) returned.
+    pub result: Result<RunSummary, IndexerError>,
+    handlers: Vec<HandlerLog>,
+}
+
+impl TestRun {
+    /// Log of the handler at `index` in the list given to [`run_handlers`].
+    ///
+    /// # Panics
+    ///
+    /// If there is no handler at `index`.
+    pub fn handler(&self, index: usize) -> &HandlerLog {
+        &self.handlers[index]
+    }
+
+    /// Logs of all handlers, in the order given to [`run_handlers`].
+    pub fn handlers(&self) -> &[HandlerLog] {
+        &self.handlers
+    }
+}
+
+/// The calls one handler received, in order.
+#[derive(Clone, Debug, Default)]
+pub struct HandlerLog {
+    pub name: String,
+    pub calls: Vec<HandlerCall>,
+}
+
+impl HandlerLog {
+    /// Blocks passed to `handle_block`.
+    pub fn blocks(&self) -> Vec<BlockNumber> {
+        self.calls
+            .iter()
+            .filter_map(|call| match call {
+                HandlerCall::Block(block) => Some(*block),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Block and `Pallet.Variant` of each event passed to `handle_event`.
+    pub fn events(&self) -> Vec<(BlockNumber, String)> {
+        self.calls
+            .iter()
+            .filter_map(|call| match call {
+                HandlerCall::Event {
+                    block,
+                    pallet,
+                    variant,
+                    ..
+                } => Some((*block, format!("{pallet}.{variant}"))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Errors passed to `handle_error`.
+    pub fn errors(&self) -> Vec<&str> {
+        self.calls
+            .iter()
+            .filter_map(|call| match call {
+                HandlerCall::Error { error, .. } => Some(error.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A call made to a handler during [`run_handlers`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HandlerCall {
+    Block(BlockNumber),
+    Extrinsic {
+        block: BlockNumber,
+        index: u32,
+    },
+    Event {
+        block: BlockNumber,
+        index: u32,
+        pallet: String,
+        variant: String,
+    },
+    Error {
+        block: BlockNumber,
+        error: String,
+    },
+    Reorg {
+        from: BlockNumber,
+        to: BlockNumber,
+    },
+    Shutdown,
+}
+
+/// Logs the calls to `inner` before passing them on.
+struct Logged {
+    inner: Box<dyn Handler<SubstrateConfig>>,
+    log: Arc<Mutex<HandlerLog>>,
+}
+
+impl Logged {
+    fn push(&self, call: HandlerCall) {
+        self.log
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .calls
+            .push(call);
+    }
+}
+
+#[async_trait]
+impl Handler<SubstrateConfig> for Logged {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn event_filter(&self) -> EventFilter {
+        self.inner.event_filter()
+    }
+
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<SubstrateConfig>,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        self.push(HandlerCall::Event {
+            block: ctx.block_number,
+            index: event.index(),
+            pallet: event.pallet_name().to_string(),
+            variant: event.variant_name().to_string(),
+        });
+        self.inner.handle_event(event, ctx).await
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<SubstrateConfig>,
+        events: &[ChainEvent<SubstrateConfig>],
+    ) -> Result<(), IndexerError> {
+        self.push(HandlerCall::Block(ctx.block_number));
+        self.inner.handle_block(ctx, events).await
+    }
+
+    async fn handle_extrinsic(
+        &self,
+        extrinsic: &ChainExtrinsic,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        self.push(HandlerCall::Extrinsic {
+            block: ctx.block_number,
+            index: extrinsic.index(),
+        });
+        self.inner.handle_extrinsic(extrinsic, ctx).await
+    }
+
+    async fn handle_error(&self, error: &IndexerError, ctx: &Context<SubstrateConfig>) {
+        self.push(HandlerCall::Error {
+            block: ctx.block_number,
+            error: error.to_string(),
+        });
+        self.inner.handle_error(error, ctx).await
+    }
+
+    async fn on_reorg(&self, from: u64, to: u64) -> Result<(), IndexerError> {
+        self.push(HandlerCall::Reorg { from, to });
+        self.inner.on_reorg(from, to).await
+    }
+
+    async fn on_checkpoint(&self, block: BlockNumber) -> Result<Option<BlockNumber>, IndexerError> {
+        self.inner.on_checkpoint(block).await
+    }
+
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        self.push(HandlerCall::Shutdown);
+        self.inner.on_shutdown().await
+    }
+}
@@ -0,0 +1,336 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! [`MockBlockSource`], standing in for a node so the run loop can be driven
+//! without one:
+//!
+//! ```rust,ignore
+//! let source = MockBlockSource::<SubstrateConfig>::new(metadata)
+//!     .with_blocks(1..=8, |n| events_of(n))
+//!     .finalized(5)
+//!     .with_subscription([6, 8]);
+//! let mut indexer = IndexerBuilder::new()
+//!     .block_source(source.clone())
+//!     .with_store(MemoryStore::new())
+//!     .build()
+//!     .await?;
+//! indexer.run().await?;
+//! assert_eq!(source.calls("subscribe_finalized"), 1);
+//! ```
+
+use crate::error::IndexerError;
+use crate::source::{BlockSource, BlockStream, SourceBlock};
+use crate::types::BlockNumber;
+use async_trait::async_trait;
+use futures::StreamExt;
+use parity_scale_codec::{Decode, Encode};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex, MutexGuard};
+use subxt::client::RuntimeVersion;
+use subxt::config::substrate::{BlakeTwo256, Digest, SubstrateHeader};
+use subxt::config::HashFor;
+use subxt::utils::H256;
+use subxt::Config;
+
+/// Marks the hashes a [`MockBlockSource`] hands out.
+const HASH_MARKER: u8 = 0xb5;
+
+/// A scripted [`BlockSource`].
+///
+/// Block `0` exists from the start, without events. Blocks are numbered
+/// consecutively, and block `n` has a hash derived from `n`. Each subscription
+/// call takes the next script from [`with_subscription`](Self::with_subscription),
+/// announces its blocks and ends; without one left it ends right away. The best
+/// block is the finalized head.
+///
+/// Clones share their script, so a test can keep one to look at
+/// [`calls`](Self::calls) after handing the other to the builder.
+pub struct MockBlockSource<C: Config> {
+    state: Arc<Mutex<MockState>>,
+    _config: std::marker::PhantomData<fn() -> C>,
+}
+
+#[derive(Default)]
+struct MockState {
+    /// SCALE-encoded events of each block.
+    blocks: Vec<Vec<u8>>,
+    /// Extrinsics of each block that has any.
+    extrinsics: HashMap<BlockNumber, Vec<Vec<u8>>>,
+    /// `Timestamp.Now` of each block that has one, in milliseconds.
+    timestamps: HashMap<BlockNumber, u64>,
+    /// Runtimes by the first block they apply to.
+    runtimes: BTreeMap<BlockNumber, (RuntimeVersion, Vec<u8>)>,
+    finalized: BlockNumber,
+    subscriptions: VecDeque<Vec<BlockNumber>>,
+    failures: HashMap<&'static str, VecDeque<IndexerError>>,
+    calls: HashMap<&'static str, usize>,
+}
+
+impl<C: Config> Clone for MockBlockSource<C> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            _config: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<C: Config> MockBlockSource<C> {
+    /// A chain of only block `0`, running spec version 1 with `metadata`, a
+    /// SCALE-encoded `RuntimeMetadataPrefixed`.
+    pub fn new(metadata: Vec<u8>) -> Self {
+        let mut state = MockState {
+            blocks: vec![Vec::<()>::new().encode()],
+            ..Default::default()
+        };
+        let version = RuntimeVersion {
+            spec_version: 1,
+            transaction_version: 1,
+        };
+        state.runtimes.insert(0, (version, metadata));
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            _config: std::marker::PhantomData,
+        }
+    }
+
+    /// Append a block with SCALE-encoded `System.Events` `events`.
+    pub fn with_block(self, events: Vec<u8>) -> Self {
+        self.state().blocks.push(events);
+        self
+    }
+
+    /// Append blocks `numbers`, with the events `events` returns for each.
+    ///
+    /// # Panics
+    ///
+    /// If `numbers` does not start right after the last block.
+    pub fn with_blocks(
+        self,
+        numbers: RangeInclusive<BlockNumber>,
+        mut events: impl FnMut(BlockNumber) -> Vec<u8>,
+    ) -> Self {
+        {
+            let mut state = self.state();
+            assert_eq!(
+                *numbers.start(),
+                state.blocks.len() as BlockNumber,
+                "mock blocks must be consecutive"
+            );
+            state.blocks.extend(numbers.map(&mut events));
+        }
+        self
+    }
+
+    /// Give block `number` the SCALE-encoded extrinsics `extrinsics`.
+    pub fn with_extrinsics(self, number: BlockNumber, extrinsics: Vec<Vec<u8>>) -> Self {
+        self.state().extrinsics.insert(number, extrinsics);
+        self
+    }
+
+    /// Give block `number` the timestamp `millis`, reported as `Timestamp.Now`.
+    pub fn with_timestamp(self, number: BlockNumber, millis: u64) -> Self {
+        self.state().timestamps.insert(number, millis);
+        self
+    }
+
+    /// Run `spec_version` with `metadata` from block `from` on.
+    pub fn with_runtime_upgrade(
+        self,
+        from: BlockNumber,
+        spec_version: u32,
+        metadata: Vec<u8>,
+    ) -> Self {
+        let version = RuntimeVersion {
+            spec_version,
+            transaction_version: 1,
+        };
+        self.state().runtimes.insert(from, (version, metadata));
+        self
+    }
+
+    /// Report block `number` as the finalized head.
+    pub fn finalized(self, number: BlockNumber) -> Self {
+        self.state().finalized = number;
+        self
+    }
+
+    /// Script the next subscription to announce `numbers`, then end.
+    pub fn with_subscription(self, numbers: impl IntoIterator<Item = BlockNumber>) -> Self {
+        self.state()
+            .subscriptions
+            .push_back(numbers.into_iter().collect());
+        self
+    }
+
+    /// Fail the next call of `method`, named as in [`BlockSource`], with `error`.
+    /// Failures of the same method are returned in the order they were added.
+    pub fn fail_next(self, method: &'static str, error: IndexerError) -> Self {
+        self.state()
+            .failures
+            .entry(method)
+            .or_default()
+            .push_back(error);
+        self
+    }
+
+    /// How many times `method` was called, including failed calls.
+    pub fn calls(&self, method: &str) -> usize {
+        self.state().calls.get(method).copied().unwrap_or(0)
+    }
+
+    /// The hash of block `number`.
+    pub fn hash(number: BlockNumber) -> HashFor<C> {
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&number.to_le_bytes());
+        bytes[31] = HASH_MARKER;
+        HashFor::<C>::decode(&mut &bytes[..]).expect("config hashes are 32 bytes")
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Count a call of `method` and return its scripted failure, if any.
+    fn call(&self, method: &'static str) -> Result<MutexGuard<'_, MockState>, IndexerError> {
+        let mut state = self.state();
+        *state.calls.entry(method).or_default() += 1;
+        match state.failures.get_mut(method).and_then(VecDeque::pop_front) {
+            Some(error) => Err(error),
+            None => Ok(state),
+        }
+    }
+
+    /// Number of the mock block `hash`.
+    fn number(state: &MockState, hash: HashFor<C>) -> Result<BlockNumber, IndexerError> {
+        let bytes = hash.encode();
+        if bytes.len() != 32 || bytes[31] != HASH_MARKER {
+            return Err(IndexerError::BlockHashNotFound {
+                hash: format!("{hash:?}"),
+            });
+        }
+        let number = u64::from_le_bytes(bytes[..8].try_into().expect("32-byte hash"));
+        if number >= state.blocks.len() as BlockNumber {
+            return Err(IndexerError::BlockNotFound { block: number });
+        }
+        Ok(number)
+    }
+
+    fn header_of(number: BlockNumber) -> C::Header {
+        let parent_hash = match number.checked_sub(1) {
+            Some(parent) => H256::from_slice(&Self::hash(parent).encode()),
+            None => H256::zero(),
+        };
+        let header = SubstrateHeader::<u64, BlakeTwo256> {
+            parent_hash,
+            number,
+            state_root: H256::zero(),
+            extrinsics_root: H256::zero(),
+            digest: Digest::default(),
+        };
+        C::Header::decode(&mut header.encode().as_slice())
+            .expect("config uses the standard Substrate header")
+    }
+
+    fn runtime(state: &MockState, number: BlockNumber) -> &(RuntimeVersion, Vec<u8>) {
+        state
+            .runtimes
+            .range(..=number)
+            .next_back()
+            .map(|(_, runtime)| runtime)
+            .expect("block 0 has a runtime")
+    }
+
+    fn subscribe(&self, method: &'static str) -> Result<BlockStream<C>, IndexerError> {
+        let numbers = self.call(method)?.subscriptions.pop_front();
+        let announced = numbers
+            .unwrap_or_default()
+            .into_iter()
+            .map(|number| Ok((number, Self::hash(number))));
+        Ok(futures::stream::iter(announced).boxed())
+    }
+}
+
+#[async_trait]
+impl<C> BlockSource<C> for MockBlockSource<C>
+where
+    C: Config + Send + Sync + 'static,
+{
+    async fn genesis_hash(&self) -> Result<HashFor<C>, IndexerError> {
+        drop(self.call("genesis_hash")?);
+        Ok(Self::hash(0))
+    }
+
+    async fn finalized_head(&self) -> Result<HashFor<C>, IndexerError> {
+        let state = self.call("finalized_head")?;
+        Ok(Self::hash(state.finalized))
+    }
+
+    async fn header(&self, hash: Option<HashFor<C>>) -> Result<Option<C::Header>, IndexerError> {
+        let state = self.call("header")?;
+        let number = match hash {
+            Some(hash) => match Self::number(&state, hash) {
+                Ok(number) => number,
+                Err(_) => return Ok(None),
+            },
+            None => state.finalized,
+        };
+        Ok(Some(Self::header_of(number)))
+    }
+
+    async fn block_hash(&self, number: BlockNumber) -> Result<Option<HashFor<C>>, IndexerError> {
+        let state = self.call("block_hash")?;
+        Ok((number < state.blocks.len() as BlockNumber).then(|| Self::hash(number)))
+    }
+
+    async fn block_at(
+        &self,
+        hash: HashFor<C>,
+        extrinsics: bool,
+    ) -> Result<SourceBlock<C>, IndexerError> {
+        let state = self.call("block_at")?;
+        let number = Self::number(&state, hash)?;
+        let extrinsics =
+            extrinsics.then(|| state.extrinsics.get(&number).cloned().unwrap_or_default());
+        Ok(SourceBlock {
+            header: Self::header_of(number),
+            events: state.blocks[number as usize].clone(),
+            extrinsics,
+            timestamp: state.timestamps.get(&number).copied(),
+        })
+    }
+
+    async fn runtime_version(&self, hash: HashFor<C>) -> Result<RuntimeVersion, IndexerError> {
+        let state = self.call("runtime_version")?;
+        let number = Self::number(&state, hash)?;
+        Ok(Self::runtime(&state, number).0.clone())
+    }
+
+    async fn raw_metadata(&self, hash: HashFor<C>) -> Result<Vec<u8>, IndexerError> {
+        let state = self.call("raw_metadata")?;
+        let number = Self::number(&state, hash)?;
+        Ok(Self::runtime(&state, number).1.clone())
+    }
+
+    async fn subscribe_finalized(&self) -> Result<BlockStream<C>, IndexerError> {
+        self.subscribe("subscribe_finalized")
+    }
+
+    async fn subscribe_best(&self) -> Result<BlockStream<C>, IndexerError> {
+        self.subscribe("subscribe_best")
+    }
+}
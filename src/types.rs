@@ -14,20 +14,98 @@
  * limitations under the License.
  */
 
-use scale_value::Composite;
-use subxt::events::EventDetails;
+use crate::error::IndexerError;
+#[cfg(feature = "json-storage")]
+use crate::handler::Context;
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+use parity_scale_codec::Encode;
+use scale_value::{Composite, Value, ValueDef};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use subxt::config::HashFor;
+use subxt::events::{EventDetails, Events, Phase, RootEvent};
+use subxt::utils::AccountId32;
 use subxt::Config;
 
 pub type BlockNumber = u64;
 
+/// An event of a block together with its position in the block.
+///
+/// `Debug` prints the event's name, position and encoded field size, so it can be
+/// logged directly:
+///
+/// ```
+/// # use flamewire_bittensor_indexer::prelude::*;
+/// fn trace(event: &ChainEvent<SubstrateConfig>, ctx: &Context<SubstrateConfig>) {
+///     println!("{event:?} in {ctx:?}");
+/// }
+/// ```
 pub struct ChainEvent<C: Config> {
     inner: EventDetails<C>,
-    pub index: u32,
+    index: u32,
+    fields: OnceLock<Composite<u32>>,
 }
 
 impl<C: Config> ChainEvent<C> {
     pub fn new(inner: EventDetails<C>, index: u32) -> Self {
-        Self { inner, index }
+        Self {
+            inner,
+            index,
+            fields: OnceLock::new(),
+        }
+    }
+
+    /// Every event of a block, in order, each with its position.
+    pub fn from_events(events: &Events<C>) -> impl Iterator<Item = Result<Self, subxt::Error>> {
+        events
+            .iter()
+            .enumerate()
+            .map(|(index, event)| event.map(|event| Self::new(event, index as u32)))
+    }
+
+    /// Position of the event among all events of its block, starting at 0.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// A cloneable copy of the event's identity and field bytes, for keeping
+    /// beyond the handler call.
+    pub fn to_owned_summary(&self) -> EventSummary {
+        EventSummary {
+            pallet: self.pallet_name().to_string(),
+            variant: self.variant_name().to_string(),
+            index: self.index,
+            phase: self.phase(),
+            field_bytes: self.inner.field_bytes().to_vec(),
+        }
+    }
+
+    /// Stable identifier of the event within the block `block_hash`, see [`EventId`].
+    pub fn event_id(&self, block_hash: &HashFor<C>) -> EventId {
+        EventId::new(block_hash, self.index)
+    }
+
+    /// Phase of block execution the event was emitted in, e.g.
+    /// [`Phase::Initialization`] for events raised by `on_initialize` hooks.
+    pub fn phase(&self) -> Phase {
+        self.inner.phase()
+    }
+
+    /// Index of the extrinsic that emitted the event, `None` for events of
+    /// block initialization and finalization.
+    pub fn extrinsic_index(&self) -> Option<u32> {
+        match self.phase() {
+            Phase::ApplyExtrinsic(index) => Some(index),
+            Phase::Initialization | Phase::Finalization => None,
+        }
+    }
+
+    /// Topics attached to the event, empty for most pallets.
+    pub fn topics(&self) -> &[HashFor<C>] {
+        self.inner.topics()
     }
 
     pub fn pallet_name(&self) -> &str {
@@ -44,7 +122,334 @@ impl<C: Config> ChainEvent<C> {
         self.inner.as_event::<T>().map_err(Box::new)
     }
 
+    /// Decode the event as the runtime's outer event enum, such as the
+    /// `RuntimeEvent` generated by `#[subxt::subxt]`.
+    pub fn as_root_event<E: RootEvent>(&self) -> Result<E, Box<subxt::Error>> {
+        self.inner.as_root_event::<E>().map_err(Box::new)
+    }
+
     pub fn field_values(&self) -> Result<Composite<u32>, Box<subxt::Error>> {
-        self.inner.field_values().map_err(Box::new)
+        self.fields().cloned()
+    }
+
+    /// The field called `name`, or `None` if there is no such field or the
+    /// fields are positional.
+    pub fn field(&self, name: &str) -> Result<Option<Value<u32>>, Box<subxt::Error>> {
+        Ok(match self.fields()? {
+            Composite::Named(fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.clone()),
+            Composite::Unnamed(_) => None,
+        })
+    }
+
+    /// The field at position `index`, for named and positional fields alike.
+    pub fn field_at(&self, index: usize) -> Result<Option<Value<u32>>, Box<subxt::Error>> {
+        Ok(self.fields()?.values().nth(index).cloned())
+    }
+
+    /// Fields decoded on first use and kept for later calls. A failed decode
+    /// is not cached.
+    fn fields(&self) -> Result<&Composite<u32>, Box<subxt::Error>> {
+        if let Some(fields) = self.fields.get() {
+            return Ok(fields);
+        }
+        let fields = self.inner.field_values().map_err(Box::new)?;
+        Ok(self.fields.get_or_init(|| fields))
+    }
+}
+
+impl<C: Config> fmt::Debug for ChainEvent<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChainEvent")
+            .field("pallet", &self.pallet_name())
+            .field("variant", &self.variant_name())
+            .field("index", &self.index)
+            .field("field_bytes", &self.inner.field_bytes().len())
+            .finish()
+    }
+}
+
+/// Owned summary of a [`ChainEvent`], see [`ChainEvent::to_owned_summary`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventSummary {
+    pub pallet: String,
+    pub variant: String,
+    pub index: u32,
+    pub phase: Phase,
+    /// SCALE-encoded fields of the event.
+    pub field_bytes: Vec<u8>,
+}
+
+/// Identifier of an event that is unique across blocks and stable across
+/// restarts, suitable as a deduplication or message key.
+///
+/// It is the 32-byte BLAKE2b digest of the SCALE-encoded block hash followed by
+/// the event index as a little-endian `u32`. This derivation is part of the
+/// public contract and will not change between crate versions. It is displayed
+/// and serialized as `0x`-prefixed lowercase hex.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct EventId([u8; 32]);
+
+impl EventId {
+    pub fn new<H: Encode>(block_hash: &H, index: u32) -> Self {
+        let mut hasher = Blake2b::<U32>::new();
+        hasher.update(block_hash.encode());
+        hasher.update(index.to_le_bytes());
+        Self(hasher.finalize().into())
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("0x")?;
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EventId({self})")
+    }
+}
+
+impl FromStr for EventId {
+    type Err = IndexerError;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || IndexerError::invalid_config("event_id", "expected 0x followed by 64 hex digits");
+        let digits = id.strip_prefix("0x").ok_or_else(invalid)?;
+        if digits.len() != 64 || !digits.is_ascii() {
+            return Err(invalid());
+        }
+        let mut bytes = [0u8; 32];
+        for (byte, pair) in bytes.iter_mut().zip(digits.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl TryFrom<String> for EventId {
+    type Error = IndexerError;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        id.parse()
+    }
+}
+
+impl From<EventId> for String {
+    fn from(id: EventId) -> Self {
+        id.to_string()
+    }
+}
+
+/// Extraction helpers for field values beyond the `as_u128`, `as_i128`,
+/// `as_bool` and `as_str` that [`Value`] already provides.
+pub trait FieldValueExt {
+    /// The value as an account id: 32 bytes, possibly wrapped in newtypes.
+    fn as_account_id(&self) -> Option<AccountId32>;
+}
+
+impl<T> FieldValueExt for Value<T> {
+    fn as_account_id(&self) -> Option<AccountId32> {
+        let ValueDef::Composite(Composite::Unnamed(values)) = &self.value else {
+            return None;
+        };
+        if let [inner] = values.as_slice() {
+            return inner.as_account_id();
+        }
+        let bytes = values
+            .iter()
+            .map(|value| value.as_u128().and_then(|n| u8::try_from(n).ok()))
+            .collect::<Option<Vec<u8>>>()?;
+        <[u8; 32]>::try_from(bytes).ok().map(AccountId32)
+    }
+}
+
+#[cfg(feature = "json-storage")]
+impl<C: Config> ChainEvent<C> {
+    /// Decoded fields as JSON: named fields become an object and positional
+    /// fields an array. Nested sequences of bytes are rendered as `0x` hex and
+    /// integers that do not fit in 64 bits as strings, so no precision is lost.
+    pub fn to_json(&self) -> Result<serde_json::Value, Box<subxt::Error>> {
+        Ok(json::composite(self.fields()?, true))
+    }
+
+    /// [`to_json`](Self::to_json) wrapped in `{pallet, event, index, phase, fields}`.
+    pub fn to_json_with_meta(&self) -> Result<serde_json::Value, Box<subxt::Error>> {
+        Ok(serde_json::json!({
+            "pallet": self.pallet_name(),
+            "event": self.variant_name(),
+            "index": self.index,
+            "phase": json::phase(self.phase()),
+            "fields": self.to_json()?,
+        }))
+    }
+}
+
+/// A self-contained copy of an event and its block, for sinks that run outside
+/// the handler call such as channels, message queues or batch writers.
+#[cfg(feature = "json-storage")]
+#[derive(Serialize)]
+#[serde(bound(serialize = "HashFor<C>: Serialize"))]
+pub struct EventEnvelope<C: Config> {
+    pub block_number: u64,
+    pub block_hash: HashFor<C>,
+    /// Block timestamp in milliseconds, see [`Context::timestamp`].
+    pub timestamp: Option<u64>,
+    pub index: u32,
+    pub pallet: String,
+    pub variant: String,
+    /// Fields as produced by [`ChainEvent::to_json`], `None` if they failed to decode.
+    pub fields_json: Option<serde_json::Value>,
+}
+
+#[cfg(feature = "json-storage")]
+impl<C: Config> EventEnvelope<C> {
+    /// Copy `event` along with the number, hash and timestamp of the block in `ctx`.
+    pub fn from_event(event: &ChainEvent<C>, ctx: &Context<C>) -> Self {
+        Self {
+            block_number: ctx.block_number,
+            block_hash: ctx.block_hash,
+            timestamp: ctx.timestamp(),
+            index: event.index(),
+            pallet: event.pallet_name().to_string(),
+            variant: event.variant_name().to_string(),
+            fields_json: event.to_json().ok(),
+        }
+    }
+
+    /// Replace the timestamp taken from the context.
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// The [`EventId`] of the wrapped event.
+    pub fn id(&self) -> EventId {
+        EventId::new(&self.block_hash, self.index)
+    }
+}
+
+// Implemented by hand because deriving would require `C: Clone + Debug`.
+#[cfg(feature = "json-storage")]
+impl<C: Config> Clone for EventEnvelope<C> {
+    fn clone(&self) -> Self {
+        Self {
+            block_number: self.block_number,
+            block_hash: self.block_hash,
+            timestamp: self.timestamp,
+            index: self.index,
+            pallet: self.pallet.clone(),
+            variant: self.variant.clone(),
+            fields_json: self.fields_json.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "json-storage")]
+impl<C: Config> fmt::Debug for EventEnvelope<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventEnvelope")
+            .field("block_number", &self.block_number)
+            .field("block_hash", &self.block_hash)
+            .field("timestamp", &self.timestamp)
+            .field("index", &self.index)
+            .field("pallet", &self.pallet)
+            .field("variant", &self.variant)
+            .field("fields_json", &self.fields_json)
+            .finish()
+    }
+}
+
+#[cfg(feature = "json-storage")]
+mod json {
+    use scale_value::{Composite, Primitive, Value, ValueDef};
+    use serde_json::{json, Value as Json};
+    use std::fmt::Write;
+    use subxt::events::Phase;
+
+    pub(super) fn phase(phase: Phase) -> Json {
+        match phase {
+            Phase::Initialization => json!("initialization"),
+            Phase::ApplyExtrinsic(index) => json!({ "apply_extrinsic": index }),
+            Phase::Finalization => json!("finalization"),
+        }
+    }
+
+    /// `outer` marks the field list of an event or variant, which stays an
+    /// array even when every field is a single byte.
+    pub(super) fn composite<T>(composite: &Composite<T>, outer: bool) -> Json {
+        match composite {
+            Composite::Named(fields) => Json::Object(
+                fields
+                    .iter()
+                    .map(|(name, field)| (name.clone(), value(field)))
+                    .collect(),
+            ),
+            Composite::Unnamed(values) => match bytes(values) {
+                Some(bytes) if !outer => Json::String(hex(bytes)),
+                _ => Json::Array(values.iter().map(value).collect()),
+            },
+        }
+    }
+
+    fn value<T>(value: &Value<T>) -> Json {
+        match &value.value {
+            ValueDef::Composite(inner) => composite(inner, false),
+            ValueDef::Variant(variant) => json!({
+                "name": variant.name,
+                "values": composite(&variant.values, true),
+            }),
+            ValueDef::BitSequence(bits) => Json::Array(bits.iter().map(Json::Bool).collect()),
+            ValueDef::Primitive(primitive) => match primitive {
+                Primitive::Bool(b) => Json::Bool(*b),
+                Primitive::Char(c) => Json::String(c.to_string()),
+                Primitive::String(s) => Json::String(s.clone()),
+                Primitive::U128(n) => u64::try_from(*n)
+                    .map(Json::from)
+                    .unwrap_or_else(|_| Json::String(n.to_string())),
+                Primitive::I128(n) => i64::try_from(*n)
+                    .map(Json::from)
+                    .unwrap_or_else(|_| Json::String(n.to_string())),
+                // 256-bit integers are stored little-endian, print them big-endian.
+                Primitive::U256(le) | Primitive::I256(le) => {
+                    Json::String(hex(le.iter().rev().copied().collect()))
+                }
+            },
+        }
+    }
+
+    /// The values as bytes if there is at least one and each is an integer below 256.
+    fn bytes<T>(values: &[Value<T>]) -> Option<Vec<u8>> {
+        if values.is_empty() {
+            return None;
+        }
+        values
+            .iter()
+            .map(|value| match value.value {
+                ValueDef::Primitive(Primitive::U128(n)) => u8::try_from(n).ok(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn hex(bytes: Vec<u8>) -> String {
+        bytes.iter().fold(String::from("0x"), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
     }
 }
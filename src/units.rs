@@ -0,0 +1,214 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! TAO amounts. Balances on Bittensor are integers of RAO, the smallest unit;
+//! one TAO is 10⁹ RAO. [`Rao`] converts to and from TAO with integer
+//! arithmetic only, so no amount is ever rounded through a float.
+
+use crate::error::IndexerError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+/// RAO in one TAO.
+pub const RAO_PER_TAO: u128 = 1_000_000_000;
+/// Decimal places of a TAO amount written out in full.
+pub const TAO_DECIMALS: usize = 9;
+
+/// An amount in RAO.
+///
+/// `Display` writes the exact amount in TAO with the `τ` sign and without
+/// trailing zeros, e.g. `τ1.5`. Serde uses the RAO integer as a decimal string,
+/// `"1500000000"`, which JSON consumers cannot truncate to a double.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rao(pub u128);
+
+impl Rao {
+    pub const ZERO: Rao = Rao(0);
+    pub const ONE_TAO: Rao = Rao(RAO_PER_TAO);
+
+    pub const fn new(rao: u128) -> Self {
+        Self(rao)
+    }
+
+    pub const fn get(self) -> u128 {
+        self.0
+    }
+
+    /// Whole TAO, e.g. `Rao::from_tao(3)` is 3 TAO.
+    pub const fn from_tao(tao: u64) -> Self {
+        Self(tao as u128 * RAO_PER_TAO)
+    }
+
+    /// Parse a decimal TAO amount such as `"1"`, `"0.5"` or `"12.000000001"`.
+    /// Digits beyond the ninth decimal place must be zeros, as they would be
+    /// lost; signs, exponents and separators are rejected.
+    pub fn from_tao_str(tao: &str) -> Result<Self, IndexerError> {
+        let invalid =
+            |message: &str| IndexerError::invalid_config("amount", format!("`{tao}` {message}"));
+        let (whole, fraction) = match tao.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (tao, None),
+        };
+        let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if !is_digits(whole) || !fraction.is_none_or(is_digits) {
+            return Err(invalid("is not a decimal TAO amount"));
+        }
+        let fraction = fraction.unwrap_or_default();
+        let (kept, dropped) = fraction.split_at(fraction.len().min(TAO_DECIMALS));
+        if dropped.bytes().any(|b| b != b'0') {
+            return Err(invalid("has more than 9 decimal places"));
+        }
+
+        let too_large = || invalid("does not fit in a u128 of RAO");
+        let whole: u128 = whole.parse().map_err(|_| too_large())?;
+        let mut fraction_rao: u128 = if kept.is_empty() {
+            0
+        } else {
+            kept.parse().map_err(|_| too_large())?
+        };
+        for _ in kept.len()..TAO_DECIMALS {
+            fraction_rao *= 10;
+        }
+        whole
+            .checked_mul(RAO_PER_TAO)
+            .and_then(|rao| rao.checked_add(fraction_rao))
+            .map(Self)
+            .ok_or_else(too_large)
+    }
+
+    /// The amount in TAO with exactly `decimal_places` decimals, rounding half
+    /// up when fewer than 9 are kept: `Rao(1_500_000_000).to_tao_string(2)` is
+    /// `"1.50"` and `Rao(5).to_tao_string(8)` is `"0.00000001"`.
+    pub fn to_tao_string(self, decimal_places: usize) -> String {
+        let mut whole = self.0 / RAO_PER_TAO;
+        let mut fraction = self.0 % RAO_PER_TAO;
+        let kept = decimal_places.min(TAO_DECIMALS);
+        let unit = 10u128.pow((TAO_DECIMALS - kept) as u32);
+        fraction = (fraction + unit / 2) / unit;
+        if fraction == 10u128.pow(kept as u32) {
+            fraction = 0;
+            // Cannot overflow: `whole` is at most u128::MAX / 10⁹.
+            whole += 1;
+        }
+        if decimal_places == 0 {
+            return whole.to_string();
+        }
+        format!(
+            "{whole}.{fraction:0kept$}{:0<pad$}",
+            "",
+            pad = decimal_places - kept
+        )
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl fmt::Display for Rao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tao = self.to_tao_string(TAO_DECIMALS);
+        let tao = tao.trim_end_matches('0').trim_end_matches('.');
+        write!(f, "τ{tao}")
+    }
+}
+
+impl From<u64> for Rao {
+    fn from(rao: u64) -> Self {
+        Self(rao.into())
+    }
+}
+
+impl From<u128> for Rao {
+    fn from(rao: u128) -> Self {
+        Self(rao)
+    }
+}
+
+impl From<Rao> for u128 {
+    fn from(rao: Rao) -> Self {
+        rao.0
+    }
+}
+
+impl Add for Rao {
+    type Output = Rao;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Rao {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Rao {
+    type Output = Rao;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Rao {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Sum for Rao {
+    fn sum<I: Iterator<Item = Rao>>(iter: I) -> Self {
+        iter.fold(Rao::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Rao> for Rao {
+    fn sum<I: Iterator<Item = &'a Rao>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl Serialize for Rao {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rao {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rao = String::deserialize(deserializer)?;
+        rao.parse().map(Self).map_err(|_| {
+            serde::de::Error::custom(format!("`{rao}` is not an integer amount of RAO"))
+        })
+    }
+}
@@ -15,31 +15,220 @@
  */
 
 use crate::error::IndexerError;
+use crate::redact::redact_url;
+use crate::types::BlockNumber;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fmt;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use url::Url;
 
 /// Validated websocket URL.
-#[derive(Clone, Debug)]
-pub struct WebSocketUrl(Url);
+///
+/// `Display` and `Debug` mask credentials; use [`as_str`](WebSocketUrl::as_str) for the
+/// full URL. The serialized form is the URL alone, without credentials set with
+/// [`with_basic_auth`](WebSocketUrl::with_basic_auth) or
+/// [`with_bearer_token`](WebSocketUrl::with_bearer_token).
+///
+/// Parsing normalizes the URL, so spellings such as `WSS://Node:443/` and `wss://node`
+/// compare and hash equal.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct WebSocketUrl {
+    url: Url,
+    auth: Option<NodeAuth>,
+}
+
+/// Credentials sent in the `Authorization` header of the websocket handshake.
+///
+/// `Debug` masks the password and token.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum NodeAuth {
+    /// HTTP basic authentication.
+    Basic { username: String, password: String },
+    /// Bearer token authentication.
+    Bearer { token: String },
+}
+
+impl NodeAuth {
+    /// Value of the `Authorization` header for these credentials.
+    pub fn header_value(&self) -> String {
+        match self {
+            Self::Basic { username, password } => {
+                format!("Basic {}", BASE64.encode(format!("{username}:{password}")))
+            }
+            Self::Bearer { token } => format!("Bearer {token}"),
+        }
+    }
+}
+
+impl fmt::Debug for NodeAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"***")
+                .finish(),
+            Self::Bearer { .. } => f.debug_struct("Bearer").field("token", &"***").finish(),
+        }
+    }
+}
+
+const WS_URL_FORMAT: &str = "expected wss://host[:port][/path] or host[:port][/path]";
 
 impl WebSocketUrl {
     /// Parse and validate a websocket URL (supports both ws:// and wss://).
+    ///
+    /// Input without a scheme, such as `archive.chain.opentensor.ai:443`, is taken to
+    /// be `wss://`. Surrounding whitespace is ignored.
     pub fn parse(input: &str) -> Result<Self, IndexerError> {
-        let url = Url::parse(input)
-            .map_err(|_| IndexerError::invalid_config("node_url", "invalid URL"))?;
+        Self::parse_with_default_scheme(input, "wss")
+    }
+
+    /// [`parse`](Self::parse), assuming `default_scheme` (`ws` or `wss`) for input
+    /// without a scheme.
+    pub fn parse_with_default_scheme(
+        input: &str,
+        default_scheme: &str,
+    ) -> Result<Self, IndexerError> {
+        Self::parse_field_with_default(input, "node_url", default_scheme)
+    }
+
+    /// [`parse`](Self::parse), reporting errors against config field `field`.
+    pub(crate) fn parse_field(input: &str, field: &str) -> Result<Self, IndexerError> {
+        Self::parse_field_with_default(input, field, "wss")
+    }
+
+    fn parse_field_with_default(
+        input: &str,
+        field: &str,
+        default_scheme: &str,
+    ) -> Result<Self, IndexerError> {
+        if !matches!(default_scheme, "ws" | "wss") {
+            return Err(IndexerError::invalid_config(
+                field,
+                format!("default scheme must be ws or wss, not `{default_scheme}`"),
+            ));
+        }
+        let input = input.trim();
+        let with_scheme;
+        let full = if input.contains("://") {
+            input
+        } else {
+            with_scheme = format!("{default_scheme}://{input}");
+            &with_scheme
+        };
+        let url = Url::parse(full).map_err(|e| {
+            IndexerError::invalid_config(
+                field,
+                format!("invalid URL `{}`: {e}; {WS_URL_FORMAT}", redact_url(input)),
+            )
+        })?;
         match url.scheme() {
-            "ws" | "wss" => Ok(Self(url)),
-            _ => Err(IndexerError::invalid_config(
-                "node_url",
-                "must start with ws:// or wss://",
+            "ws" | "wss" => Ok(Self { url, auth: None }),
+            scheme => Err(IndexerError::invalid_config(
+                field,
+                format!(
+                    "`{}` uses {scheme}://, but must start with ws:// or wss://; {WS_URL_FORMAT}",
+                    redact_url(input)
+                ),
             )),
         }
     }
 
-    /// Get the inner string representation.
+    /// Send HTTP basic credentials with the websocket handshake.
+    pub fn with_basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.auth = Some(NodeAuth::Basic {
+            username: username.into(),
+            password: password.into(),
+        });
+        self
+    }
+
+    /// Send `Authorization: Bearer <token>` with the websocket handshake.
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.auth = Some(NodeAuth::Bearer {
+            token: token.into(),
+        });
+        self
+    }
+
+    /// Attach credentials, replacing any set before.
+    pub fn with_auth(mut self, auth: Option<NodeAuth>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Credentials sent with the websocket handshake, if any.
+    pub fn auth(&self) -> Option<&NodeAuth> {
+        self.auth.as_ref()
+    }
+
+    /// The URL in canonical form: lowercase scheme and host, no default port, and no
+    /// trailing `/` when the path is empty. Equal URLs have the same canonical form.
     pub fn as_str(&self) -> &str {
-        self.0.as_str()
+        let url = self.url.as_str();
+        if self.url.path() == "/" && self.url.query().is_none() && self.url.fragment().is_none() {
+            url.strip_suffix('/').unwrap_or(url)
+        } else {
+            url
+        }
+    }
+
+    /// Whether the URL uses TLS (`wss://`).
+    pub fn is_secure(&self) -> bool {
+        self.url.scheme() == "wss"
+    }
+
+    /// Host name or IP literal of the node.
+    pub fn host(&self) -> &str {
+        self.url.host_str().unwrap_or_default()
+    }
+
+    /// Port of the node: the explicit one, or 443 for `wss` and 80 for `ws`.
+    pub fn port(&self) -> u16 {
+        self.url.port_or_known_default().unwrap_or(443)
+    }
+}
+
+impl FromStr for WebSocketUrl {
+    type Err = IndexerError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        Self::parse(url)
+    }
+}
+
+impl TryFrom<&str> for WebSocketUrl {
+    type Error = IndexerError;
+
+    fn try_from(url: &str) -> Result<Self, Self::Error> {
+        Self::parse(url)
+    }
+}
+
+impl TryFrom<String> for WebSocketUrl {
+    type Error = IndexerError;
+
+    fn try_from(url: String) -> Result<Self, Self::Error> {
+        Self::parse(&url)
+    }
+}
+
+impl From<WebSocketUrl> for String {
+    fn from(url: WebSocketUrl) -> Self {
+        url.as_str().to_string()
     }
 }
 
@@ -51,30 +240,258 @@ impl AsRef<str> for WebSocketUrl {
 
 impl fmt::Display for WebSocketUrl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+        f.write_str(&redact_url(self.as_str()))
+    }
+}
+
+impl fmt::Debug for WebSocketUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tuple = f.debug_tuple("WebSocketUrl");
+        tuple.field(&redact_url(self.as_str()));
+        if let Some(auth) = &self.auth {
+            tuple.field(auth);
+        }
+        tuple.finish()
     }
 }
 
 /// Validated PostgreSQL database URL
-#[derive(Clone, Debug)]
+///
+/// `Display` and `Debug` mask credentials; use [`as_str`](PostgresUrl::as_str) for the
+/// full URL.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct PostgresUrl(Url);
 
 impl PostgresUrl {
+    /// Start building a URL from its parts; see [`PostgresUrlBuilder`].
+    pub fn builder() -> PostgresUrlBuilder {
+        PostgresUrlBuilder::default()
+    }
+
     pub fn parse(input: &str) -> Result<Self, IndexerError> {
-        let url = Url::parse(input)
-            .map_err(|_| IndexerError::invalid_config("database_url", "invalid URL"))?;
+        let url = Url::parse(input).map_err(|e| invalid_url("database_url", input, e))?;
         match url.scheme() {
-            "postgres" | "postgresql" => Ok(Self(url)),
-            _ => Err(IndexerError::invalid_config(
+            "postgres" | "postgresql" => {}
+            _ => {
+                return Err(IndexerError::invalid_config(
+                    "database_url",
+                    "must start with postgres:// or postgresql://",
+                ))
+            }
+        }
+        if url.host_str().is_none_or(str::is_empty) {
+            return Err(IndexerError::invalid_config(
                 "database_url",
-                "must start with postgres:// or postgresql://",
-            )),
+                format!("invalid URL `{}`: missing host", redact_url(input)),
+            ));
         }
+        Ok(Self(url))
     }
 
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
+
+    /// The URL with its password and secret query parameters replaced by `***`, as
+    /// shown by `Display` and `Debug`.
+    pub fn redacted(&self) -> String {
+        redact_url(self.as_str())
+    }
+
+    /// Host name or IP literal of the server.
+    pub fn host(&self) -> &str {
+        self.0.host_str().unwrap_or_default()
+    }
+
+    /// Port of the server; 5432 unless the URL names one.
+    pub fn port(&self) -> u16 {
+        self.0.port().unwrap_or(5432)
+    }
+
+    /// Name of the database, if the URL names one.
+    pub fn database(&self) -> Option<Cow<'_, str>> {
+        Some(self.0.path().trim_start_matches('/'))
+            .filter(|db| !db.is_empty())
+            .map(decode)
+    }
+
+    /// User name, if the URL names one.
+    pub fn username(&self) -> Option<Cow<'_, str>> {
+        Some(self.0.username())
+            .filter(|user| !user.is_empty())
+            .map(decode)
+    }
+
+    /// Password, if the URL has one.
+    pub fn password(&self) -> Option<Cow<'_, str>> {
+        self.0.password().map(decode)
+    }
+
+    /// Value of the query parameter `key`, such as `sslmode`.
+    pub fn param(&self, key: &str) -> Option<String> {
+        self.0
+            .query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.into_owned())
+    }
+}
+
+fn decode(component: &str) -> Cow<'_, str> {
+    percent_decode_str(component).decode_utf8_lossy()
+}
+
+/// Builds a [`PostgresUrl`] from separate parts, percent-encoding each one.
+///
+/// ```
+/// # use flamewire_bittensor_indexer::PostgresUrl;
+/// let url = PostgresUrl::builder()
+///     .host("db.internal")
+///     .username("indexer")
+///     .password("p@ss/word")
+///     .database("chain")
+///     .param("sslmode", "require")
+///     .build()?;
+/// assert_eq!(url.password().as_deref(), Some("p@ss/word"));
+/// # Ok::<(), flamewire_bittensor_indexer::IndexerError>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct PostgresUrlBuilder {
+    host: Option<String>,
+    port: Option<u16>,
+    username: Option<String>,
+    password: Option<PasswordSource>,
+    database: Option<String>,
+    params: Vec<(String, String)>,
+}
+
+#[derive(Clone)]
+enum PasswordSource {
+    Value(String),
+    File(PathBuf),
+}
+
+impl PostgresUrlBuilder {
+    /// Host name or IP address of the server. Required.
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Port of the server; 5432 when unset.
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(PasswordSource::Value(password.into()));
+        self
+    }
+
+    /// Read the password from `path` when building, ignoring surrounding whitespace,
+    /// as with the `POSTGRES_PASSWORD_FILE` convention for container secrets.
+    pub fn password_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.password = Some(PasswordSource::File(path.into()));
+        self
+    }
+
+    pub fn database(mut self, database: impl Into<String>) -> Self {
+        self.database = Some(database.into());
+        self
+    }
+
+    /// Add a query parameter such as `sslmode=require`.
+    pub fn param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Assemble and validate the URL.
+    pub fn build(self) -> Result<PostgresUrl, IndexerError> {
+        let host = self
+            .host
+            .filter(|host| !host.is_empty())
+            .ok_or_else(|| IndexerError::invalid_config("database_url", "missing host"))?;
+        let host = if host.contains(':') && !host.starts_with('[') {
+            format!("[{host}]")
+        } else {
+            host
+        };
+        let mut url = Url::parse("postgres://localhost").expect("static URL is valid");
+        url.set_host(Some(&host)).map_err(|e| {
+            IndexerError::invalid_config("database_url", format!("invalid host `{host}`: {e}"))
+        })?;
+        let invalid = |part: &str| {
+            IndexerError::invalid_config("database_url", format!("cannot set the {part}"))
+        };
+        url.set_port(self.port).map_err(|()| invalid("port"))?;
+        if let Some(username) = &self.username {
+            url.set_username(username)
+                .map_err(|()| invalid("user name"))?;
+        }
+        let password = match self.password {
+            Some(PasswordSource::Value(password)) => Some(password),
+            Some(PasswordSource::File(path)) => {
+                let password = std::fs::read_to_string(&path).map_err(|e| {
+                    IndexerError::invalid_config(
+                        "database_url",
+                        format!("cannot read password file `{}`: {e}", path.display()),
+                    )
+                })?;
+                Some(password.trim().to_string())
+            }
+            None => None,
+        };
+        if let Some(password) = &password {
+            url.set_password(Some(password))
+                .map_err(|()| invalid("password"))?;
+        }
+        if let Some(database) = &self.database {
+            url.path_segments_mut()
+                .map_err(|()| invalid("database"))?
+                .push(database);
+        }
+        if !self.params.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.params);
+        }
+        PostgresUrl::parse(url.as_str())
+    }
+}
+
+impl FromStr for PostgresUrl {
+    type Err = IndexerError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        Self::parse(url)
+    }
+}
+
+impl TryFrom<&str> for PostgresUrl {
+    type Error = IndexerError;
+
+    fn try_from(url: &str) -> Result<Self, Self::Error> {
+        Self::parse(url)
+    }
+}
+
+impl TryFrom<String> for PostgresUrl {
+    type Error = IndexerError;
+
+    fn try_from(url: String) -> Result<Self, Self::Error> {
+        Self::parse(&url)
+    }
+}
+
+impl From<PostgresUrl> for String {
+    fn from(url: PostgresUrl) -> Self {
+        url.0.into()
+    }
 }
 
 impl AsRef<str> for PostgresUrl {
@@ -85,33 +502,398 @@ impl AsRef<str> for PostgresUrl {
 
 impl fmt::Display for PostgresUrl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+        f.write_str(&self.redacted())
+    }
+}
+
+impl fmt::Debug for PostgresUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PostgresUrl")
+            .field(&self.redacted())
+            .finish()
     }
 }
 
 /// Validated SQLite database URL
-#[derive(Clone, Debug)]
-pub struct SqliteUrl(PathBuf);
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SqliteUrl {
+    path: PathBuf,
+    query: Option<String>,
+}
+
+const SQLITE_MEMORY: &str = ":memory:";
+
+/// Access mode of a SQLite database, set with the `mode` query parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SqliteMode {
+    /// `mode=ro`: open an existing database read-only.
+    ReadOnly,
+    /// `mode=rw`: open an existing database for reading and writing.
+    ReadWrite,
+    /// `mode=rwc`: like `ReadWrite`, creating the database if it does not exist.
+    ReadWriteCreate,
+}
+
+impl SqliteMode {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ro" => Some(Self::ReadOnly),
+            "rw" => Some(Self::ReadWrite),
+            "rwc" => Some(Self::ReadWriteCreate),
+            _ => None,
+        }
+    }
+}
 
 impl SqliteUrl {
+    /// Parse a SQLite URL.
+    ///
+    /// Accepts `sqlite://path` as well as the sqlx form `sqlite:path`, including the
+    /// in-memory spellings `sqlite://:memory:` and `sqlite::memory:`. Query parameters
+    /// are preserved; `mode` must be one of `ro`, `rw` or `rwc`. The path must name a
+    /// file, so paths ending in `/` or pointing at an existing directory are rejected.
     pub fn parse(input: &str) -> Result<Self, IndexerError> {
-        if !input.starts_with("sqlite://") {
+        let rest = input
+            .strip_prefix("sqlite://")
+            .or_else(|| input.strip_prefix("sqlite:"))
+            .ok_or_else(|| {
+                IndexerError::invalid_config("database_url", "must start with sqlite://")
+            })?;
+        let (path, query) = match rest.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_string())),
+            None => (rest, None),
+        };
+        if path.is_empty() {
             return Err(IndexerError::invalid_config(
                 "database_url",
-                "must start with sqlite://",
+                "missing database path (use sqlite::memory: for an in-memory database)",
             ));
         }
-        let path = input.trim_start_matches("sqlite://");
-        Ok(Self(PathBuf::from(path)))
+        if path != SQLITE_MEMORY && (path.ends_with('/') || Path::new(path).is_dir()) {
+            return Err(IndexerError::invalid_config(
+                "database_url",
+                format!("`{path}` is a directory; the path must name a database file"),
+            ));
+        }
+        let url = Self {
+            path: PathBuf::from(path),
+            query,
+        };
+        for (key, value) in url.params() {
+            check_sqlite_param(key, value)?;
+        }
+        Ok(url)
+    }
+
+    /// URL of a private in-memory database.
+    pub fn memory() -> Self {
+        Self {
+            path: PathBuf::from(SQLITE_MEMORY),
+            query: None,
+        }
     }
 
     pub fn as_path(&self) -> &Path {
-        &self.0
+        &self.path
+    }
+
+    /// Query string following the path, if any.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// Whether this URL refers to an in-memory database.
+    pub fn is_memory(&self) -> bool {
+        self.path.as_os_str() == SQLITE_MEMORY
+    }
+
+    /// Access mode requested by the URL, defaulting to
+    /// [`ReadWriteCreate`](SqliteMode::ReadWriteCreate).
+    pub fn mode(&self) -> SqliteMode {
+        self.mode_param()
+            .and_then(SqliteMode::parse)
+            .unwrap_or(SqliteMode::ReadWriteCreate)
+    }
+
+    fn mode_param(&self) -> Option<&str> {
+        self.params()
+            .find_map(|(key, value)| (key == "mode").then_some(value))
+    }
+
+    /// Query parameters as key/value pairs; a key without `=` has an empty value.
+    fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.query
+            .iter()
+            .flat_map(|q| q.split('&'))
+            .filter(|p| !p.is_empty())
+            .map(|p| p.split_once('=').unwrap_or((p, "")))
+    }
+
+    /// sqlx connection options for this URL, with every query parameter applied.
+    ///
+    /// File databases are created on first use unless the URL sets an explicit `mode`.
+    #[cfg(feature = "sqlite")]
+    pub fn connect_options(&self) -> Result<sqlx::sqlite::SqliteConnectOptions, IndexerError> {
+        use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqliteSynchronous};
+        use std::time::Duration;
+
+        let invalid = |key: &str, e: sqlx::Error| {
+            IndexerError::invalid_config("database_url", format!("invalid `{key}`: {e}"))
+        };
+        let mut options = if self.is_memory() {
+            SqliteConnectOptions::from_str("sqlite::memory:").map_err(|e| invalid("path", e))?
+        } else {
+            SqliteConnectOptions::new()
+                .filename(&self.path)
+                .create_if_missing(self.mode() == SqliteMode::ReadWriteCreate)
+                .read_only(self.mode() == SqliteMode::ReadOnly)
+        };
+        for (key, value) in self.params() {
+            options = match key {
+                "cache" => options.shared_cache(value == "shared"),
+                "busy_timeout" => {
+                    options.busy_timeout(Duration::from_millis(value.parse().map_err(|_| {
+                        IndexerError::invalid_config(
+                            "database_url",
+                            "`busy_timeout` must be a number of milliseconds",
+                        )
+                    })?))
+                }
+                "journal_mode" => options
+                    .journal_mode(SqliteJournalMode::from_str(value).map_err(|e| invalid(key, e))?),
+                "synchronous" => options
+                    .synchronous(SqliteSynchronous::from_str(value).map_err(|e| invalid(key, e))?),
+                _ => options,
+            };
+        }
+        Ok(options)
+    }
+
+    /// Create the directory that will hold the database file.
+    ///
+    /// Does nothing for in-memory databases or when the mode does not allow creating
+    /// the file, since an existing database must already have its directory.
+    pub fn ensure_parent_dir(&self) -> Result<(), IndexerError> {
+        if self.is_memory() || self.mode() != SqliteMode::ReadWriteCreate {
+            return Ok(());
+        }
+        match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => std::fs::create_dir_all(parent)
+                .map_err(|e| IndexerError::CheckpointError {
+                    operation: format!("create directory `{}`", parent.display()),
+                    backend: "sqlite".into(),
+                    source: Box::new(e),
+                }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The URL in `sqlite://path?query` form, as accepted by [`parse`](Self::parse).
+    pub fn to_url(&self) -> String {
+        let mut url = format!("sqlite://{}", self.path.display());
+        if let Some(query) = &self.query {
+            url.push('?');
+            url.push_str(query);
+        }
+        url
+    }
+
+    /// Connection string understood by sqlx.
+    ///
+    /// File databases are created on first use unless the URL sets an explicit `mode`.
+    pub fn to_connection_string(&self) -> String {
+        let mut params: Vec<&str> = self
+            .query
+            .iter()
+            .flat_map(|q| q.split('&'))
+            .filter(|p| !p.is_empty())
+            .collect();
+        if !self.is_memory() && !params.iter().any(|p| p.starts_with("mode=")) {
+            params.push("mode=rwc");
+        }
+        let mut url = format!("sqlite://{}", self.path.display());
+        if !params.is_empty() {
+            url.push('?');
+            url.push_str(&params.join("&"));
+        }
+        url
+    }
+}
+
+impl FromStr for SqliteUrl {
+    type Err = IndexerError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        Self::parse(url)
+    }
+}
+
+impl TryFrom<&str> for SqliteUrl {
+    type Error = IndexerError;
+
+    fn try_from(url: &str) -> Result<Self, Self::Error> {
+        Self::parse(url)
+    }
+}
+
+impl TryFrom<String> for SqliteUrl {
+    type Error = IndexerError;
+
+    fn try_from(url: String) -> Result<Self, Self::Error> {
+        Self::parse(&url)
+    }
+}
+
+impl From<SqliteUrl> for String {
+    fn from(url: SqliteUrl) -> Self {
+        url.to_url()
     }
 }
 
 impl fmt::Display for SqliteUrl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "sqlite://{}", self.0.display())
+        f.write_str(&redact_url(&self.to_url()))
     }
 }
+
+/// Inclusive range of block numbers, open-ended when there is no end block.
+///
+/// Written as `{ start = 100, end = 200 }` in config files; `end` may be left out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "BlockRangeRepr", into = "BlockRangeRepr")]
+pub struct BlockRange {
+    start: BlockNumber,
+    end: Option<BlockNumber>,
+}
+
+impl BlockRange {
+    /// Blocks `start` to `end` inclusive, or from `start` on when `end` is `None`.
+    ///
+    /// Fails on `end_block` if `end` is below `start`.
+    pub fn new(start: BlockNumber, end: Option<BlockNumber>) -> Result<Self, IndexerError> {
+        if end.is_some_and(|end| end < start) {
+            return Err(IndexerError::invalid_config(
+                "end_block",
+                "must be greater than or equal to start_block",
+            ));
+        }
+        Ok(Self { start, end })
+    }
+
+    /// Every block from `start` on.
+    pub fn from_start(start: BlockNumber) -> Self {
+        Self { start, end: None }
+    }
+
+    pub fn start(&self) -> BlockNumber {
+        self.start
+    }
+
+    pub fn end(&self) -> Option<BlockNumber> {
+        self.end
+    }
+
+    /// Number of blocks in the range; `None` when it is open-ended or spans every block
+    /// number.
+    pub fn len(&self) -> Option<u64> {
+        self.end.and_then(|end| (end - self.start).checked_add(1))
+    }
+
+    /// Always `false`: a range holds at least its start block.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Whether `block` lies in the range.
+    pub fn contains(&self, block: BlockNumber) -> bool {
+        block >= self.start && self.end.is_none_or(|end| block <= end)
+    }
+
+    /// The block numbers in the range, in order. Open-ended ranges run up to
+    /// `BlockNumber::MAX`.
+    pub fn iter(&self) -> RangeInclusive<BlockNumber> {
+        self.start..=self.end.unwrap_or(BlockNumber::MAX)
+    }
+}
+
+impl IntoIterator for BlockRange {
+    type Item = BlockNumber;
+    type IntoIter = RangeInclusive<BlockNumber>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Display for BlockRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.end {
+            Some(end) => write!(f, "{}..={end}", self.start),
+            None => write!(f, "{}..", self.start),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BlockRangeRepr {
+    start: BlockNumber,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<BlockNumber>,
+}
+
+impl TryFrom<BlockRangeRepr> for BlockRange {
+    type Error = IndexerError;
+
+    fn try_from(repr: BlockRangeRepr) -> Result<Self, Self::Error> {
+        Self::new(repr.start, repr.end)
+    }
+}
+
+impl From<BlockRange> for BlockRangeRepr {
+    fn from(range: BlockRange) -> Self {
+        Self {
+            start: range.start,
+            end: range.end,
+        }
+    }
+}
+
+const SQLITE_PARAMS: &str = "mode, cache, busy_timeout, journal_mode or synchronous";
+
+/// Reject query parameters that [`SqliteUrl::connect_options`] does not apply.
+fn check_sqlite_param(key: &str, value: &str) -> Result<(), IndexerError> {
+    let lower = value.to_ascii_lowercase();
+    let expected = match key {
+        "mode" if SqliteMode::parse(value).is_none() => "ro, rw or rwc",
+        "cache" if !matches!(value, "shared" | "private") => "shared or private",
+        "busy_timeout" if value.parse::<u64>().is_err() => "a number of milliseconds",
+        "journal_mode"
+            if !matches!(
+                lower.as_str(),
+                "delete" | "truncate" | "persist" | "memory" | "wal" | "off"
+            ) =>
+        {
+            "delete, truncate, persist, memory, wal or off"
+        }
+        "synchronous" if !matches!(lower.as_str(), "off" | "normal" | "full" | "extra") => {
+            "off, normal, full or extra"
+        }
+        "mode" | "cache" | "busy_timeout" | "journal_mode" | "synchronous" => return Ok(()),
+        _ => {
+            return Err(IndexerError::invalid_config(
+                "database_url",
+                format!("unknown SQLite parameter `{key}` (expected {SQLITE_PARAMS})"),
+            ))
+        }
+    };
+    Err(IndexerError::invalid_config(
+        "database_url",
+        format!("unsupported {key} `{value}` (expected {expected})"),
+    ))
+}
+
+fn invalid_url(field: &str, input: &str, err: url::ParseError) -> IndexerError {
+    IndexerError::invalid_config(field, format!("invalid URL `{}`: {err}", redact_url(input)))
+}
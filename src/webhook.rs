@@ -0,0 +1,318 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! POST events to an HTTP endpoint, with the `webhook` feature.
+
+use crate::error::IndexerError;
+use crate::handler::{Context, EventFilter, Handler};
+use crate::recorder::MetricsRecorder;
+use crate::retry::{retry_with_backoff, BreakerDomain, CircuitBreaker, RetryConfig};
+use crate::types::{BlockNumber, ChainEvent, EventEnvelope};
+use async_trait::async_trait;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client, StatusCode, Url};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use subxt::config::HashFor;
+use subxt::Config;
+
+const HANDLER_NAME: &str = "WebhookHandler";
+
+/// Per-request timeout unless set with [`WebhookHandler::with_timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Events waiting for the next batched POST.
+#[derive(Default)]
+struct Batch {
+    envelopes: Vec<serde_json::Value>,
+    started: Option<Instant>,
+    /// Block of the newest buffered event, for errors.
+    block: u64,
+    /// Block of the oldest buffered event.
+    first_block: Option<u64>,
+}
+
+/// Handler POSTing every event that passes its filter to `url` as a JSON
+/// [`EventEnvelope`].
+///
+/// Each event is sent on its own unless [`with_batching`](Self::with_batching)
+/// is set, in which case a JSON array of envelopes is sent once `max_batch`
+/// events are buffered or the oldest of them is `max_age` old. The age is
+/// checked as blocks arrive, so a quiet chain delays the POST until the next
+/// block. The rest is sent when the indexer stops, see
+/// [`Handler::on_shutdown`], or by calling [`flush`](Self::flush).
+///
+/// Buffered events live in memory only, so [`Handler::on_checkpoint`] holds
+/// the indexer's checkpoint back to the block before the oldest of them. After
+/// a crash their blocks are handled again. Delivery is therefore at least once:
+/// events of a block split across two batches may be sent twice, which
+/// receivers can detect by the envelope's [`EventId`](crate::types::EventId).
+///
+/// Connection errors, timeouts, 5xx and 429 responses are retried with the
+/// handler's [`RetryConfig`]. Other 4xx responses fail at once. A delivery
+/// that fails for good drops its events and fails the handler with
+/// [`IndexerError::HandlerFailed`], which counts against the indexer's error
+/// budget. Delivered and failed events are also counted by the indexer's
+/// metrics backend, as `indexer_sink_events_total{sink="webhook"}` with
+/// Prometheus.
+///
+/// ```no_run
+/// # use flamewire_bittensor_indexer::{EventFilter, IndexerError};
+/// use flamewire_bittensor_indexer::webhook::WebhookHandler;
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<(), IndexerError> {
+/// let webhook = WebhookHandler::new("https://example.com/hooks/transfers")?
+///     .with_filter(EventFilter::event("Balances", "Transfer"))
+///     .with_auth_header("Bearer secret")
+///     .with_batching(100, Duration::from_secs(5));
+/// # Ok(())
+/// # }
+/// ```
+pub struct WebhookHandler {
+    client: Client,
+    url: Url,
+    filter: EventFilter,
+    auth_header: Option<String>,
+    timeout: Duration,
+    retry: RetryConfig,
+    /// Never recorded on, so it stays closed: each delivery is bounded by
+    /// `retry` alone.
+    breaker: CircuitBreaker,
+    batching: Option<(usize, Duration)>,
+    batch: Mutex<Batch>,
+    sent: AtomicU64,
+    failed: AtomicU64,
+    /// The indexer's metrics, taken from the first [`Context`] providing them.
+    metrics: OnceLock<Arc<dyn MetricsRecorder>>,
+}
+
+impl WebhookHandler {
+    /// POST to `url`, which must be an `http` or `https` URL.
+    pub fn new(url: impl AsRef<str>) -> Result<Self, IndexerError> {
+        Ok(Self {
+            client: Client::new(),
+            url: parse_http_url("webhook_url", url.as_ref())?,
+            filter: EventFilter::all(),
+            auth_header: None,
+            timeout: DEFAULT_TIMEOUT,
+            retry: RetryConfig::default(),
+            breaker: CircuitBreaker::new(usize::MAX, Duration::ZERO)
+                .with_domain(BreakerDomain::Webhook),
+            batching: None,
+            batch: Mutex::new(Batch::default()),
+            sent: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            metrics: OnceLock::new(),
+        })
+    }
+
+    /// Only POST events passing `filter`. Every event by default.
+    pub fn with_filter(mut self, filter: EventFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Send `value` as the `Authorization` header, e.g. `"Bearer <token>"`.
+    pub fn with_auth_header(mut self, value: impl Into<String>) -> Self {
+        self.auth_header = Some(value.into());
+        self
+    }
+
+    /// Give up on a request after `timeout`, [`DEFAULT_TIMEOUT`] by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Retry failed deliveries with `retry` instead of [`RetryConfig::default`].
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// POST arrays of up to `max_batch` envelopes, sent at the latest once the
+    /// oldest is `max_age` old.
+    pub fn with_batching(mut self, max_batch: usize, max_age: Duration) -> Self {
+        self.batching = Some((max_batch.max(1), max_age));
+        self
+    }
+
+    /// Events delivered so far.
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Events dropped after their delivery failed.
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// POST the buffered events now, if any.
+    pub async fn flush(&self) -> Result<(), IndexerError> {
+        let (envelopes, block) = {
+            let mut batch = self.batch.lock().unwrap();
+            batch.started = None;
+            batch.first_block = None;
+            (std::mem::take(&mut batch.envelopes), batch.block)
+        };
+        if envelopes.is_empty() {
+            return Ok(());
+        }
+        let count = envelopes.len();
+        let body = serde_json::to_vec(&envelopes)?;
+        let res = self.deliver(body, block).await;
+        self.record(count, res.is_ok());
+        res
+    }
+
+    /// Buffer `envelope`, returning whether the batch is due.
+    fn buffer(&self, envelope: serde_json::Value, block: u64, max_batch: usize) -> bool {
+        let mut batch = self.batch.lock().unwrap();
+        batch.envelopes.push(envelope);
+        batch.started.get_or_insert_with(Instant::now);
+        batch.block = block;
+        batch.first_block.get_or_insert(block);
+        batch.envelopes.len() >= max_batch
+    }
+
+    fn batch_expired(&self, max_age: Duration) -> bool {
+        let batch = self.batch.lock().unwrap();
+        batch
+            .started
+            .is_some_and(|started| started.elapsed() >= max_age)
+    }
+
+    async fn deliver(&self, body: Vec<u8>, block: u64) -> Result<(), IndexerError> {
+        retry_with_backoff(
+            || self.post(body.clone(), block),
+            &self.retry,
+            &self.breaker,
+        )
+        .await
+    }
+
+    async fn post(&self, body: Vec<u8>, block: u64) -> Result<(), IndexerError> {
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .timeout(self.timeout)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body);
+        if let Some(auth) = &self.auth_header {
+            request = request.header(AUTHORIZATION, auth);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| IndexerError::handler_failed(HANDLER_NAME, block, e).retryable(true))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        Err(
+            IndexerError::handler_failed(HANDLER_NAME, block, format!("webhook returned {status}"))
+                .retryable(status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS),
+        )
+    }
+
+    fn record(&self, events: usize, delivered: bool) {
+        let counter = if delivered { &self.sent } else { &self.failed };
+        counter.fetch_add(events as u64, Ordering::Relaxed);
+        if let Some(metrics) = self.metrics.get() {
+            metrics.record_sink_delivery("webhook", events, delivered);
+        }
+    }
+
+    fn attach_metrics<C: Config>(&self, ctx: &Context<C>) {
+        if let Some(recorder) = ctx.recorder() {
+            self.metrics.get_or_init(|| recorder.clone());
+        }
+    }
+}
+
+/// Parse `url`, which must be an `http` or `https` URL, reporting errors under
+/// the config key `key`.
+pub(crate) fn parse_http_url(key: &str, url: &str) -> Result<Url, IndexerError> {
+    let url = Url::parse(url).map_err(|e| IndexerError::invalid_config(key, e.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(IndexerError::invalid_config(
+            key,
+            format!("expected http:// or https://, got {}://", url.scheme()),
+        ));
+    }
+    Ok(url)
+}
+
+#[async_trait]
+impl<C> Handler<C> for WebhookHandler
+where
+    C: Config,
+    HashFor<C>: Serialize,
+{
+    fn name(&self) -> &str {
+        HANDLER_NAME
+    }
+
+    fn event_filter(&self) -> EventFilter {
+        self.filter
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<C>,
+        _events: &[ChainEvent<C>],
+    ) -> Result<(), IndexerError> {
+        self.attach_metrics(ctx);
+        match self.batching {
+            Some((_, max_age)) if self.batch_expired(max_age) => self.flush().await,
+            _ => Ok(()),
+        }
+    }
+
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<C>,
+        ctx: &Context<C>,
+    ) -> Result<(), IndexerError> {
+        self.attach_metrics(ctx);
+        let envelope = serde_json::to_value(EventEnvelope::from_event(event, ctx))?;
+        let Some((max_batch, max_age)) = self.batching else {
+            let body = serde_json::to_vec(&envelope)?;
+            let res = self.deliver(body, ctx.block_number).await;
+            self.record(1, res.is_ok());
+            return res;
+        };
+        if self.buffer(envelope, ctx.block_number, max_batch) || self.batch_expired(max_age) {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn on_checkpoint(&self, block: BlockNumber) -> Result<Option<BlockNumber>, IndexerError> {
+        Ok(match self.batch.lock().unwrap().first_block {
+            Some(first) => first.checked_sub(1),
+            None => Some(block),
+        })
+    }
+
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        self.flush().await
+    }
+}
@@ -21,19 +21,24 @@ use flamewire_bittensor_indexer::handler::{Context, EventFilter, Handler};
 use flamewire_bittensor_indexer::storage::CheckpointStore;
 use flamewire_bittensor_indexer::types::ChainEvent;
 use flamewire_bittensor_indexer::IndexerError;
-use frame_metadata::{
-    v15::{
-        CustomMetadata, ExtrinsicMetadata, OuterEnums, PalletEventMetadata, PalletMetadata,
-        RuntimeMetadataV15,
-    },
-    RuntimeMetadataPrefixed,
-};
 use parity_scale_codec::{Decode, Encode};
-use scale_info::{meta_type, TypeInfo};
+use scale_decode::DecodeAsType;
+use scale_info::TypeInfo;
 use std::sync::{Arc, Mutex};
+use subxt::backend::rpc::{RawRpcFuture, RawRpcSubscription, RawValue, RpcClient, RpcClientT};
+use subxt::client::RuntimeVersion;
 use subxt::config::substrate::SubstrateConfig;
-use subxt::events::{Events, Phase};
-use subxt::metadata::Metadata;
+use subxt::events::{Phase, StaticEvent};
+use subxt::ext::subxt_rpcs::Error as ClientError;
+use subxt::utils::H256;
+use subxt::OnlineClient;
+
+// The metadata and event builders ship with the crate, so its own tests use them too.
+pub use flamewire_bittensor_indexer::testing::{
+    call_metadata, event_bytes, events, pallet_metadata, pallet_metadata_bytes,
+    pallet_metadata_with_storage, signed_extrinsic, test_metadata, unsigned_extrinsic, EventRecord,
+    TestAddress, TestAllCalls, TestAllEvents,
+};
 
 // ----------------------- MockCheckpointStore ----------------------------
 pub struct MockCheckpointStore {
@@ -59,7 +64,10 @@ impl CheckpointStore for MockCheckpointStore {
             Err(IndexerError::CheckpointError {
                 operation: "load_checkpoint".into(),
                 backend: "mock".into(),
-                source: Box::new(std::io::Error::other("fail")),
+                source: Box::new(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionRefused,
+                    "fail",
+                )),
             })
         } else {
             Ok(self.checkpoints.lock().unwrap().last().copied())
@@ -71,7 +79,10 @@ impl CheckpointStore for MockCheckpointStore {
             Err(IndexerError::CheckpointError {
                 operation: "store_checkpoint".into(),
                 backend: "mock".into(),
-                source: Box::new(std::io::Error::other("fail")),
+                source: Box::new(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionRefused,
+                    "fail",
+                )),
             })
         } else {
             self.checkpoints.lock().unwrap().push(block);
@@ -124,7 +135,9 @@ impl Handler<SubstrateConfig> for MockHandler {
             Err(IndexerError::HandlerFailed {
                 handler: "mock".into(),
                 block: _ctx.block_number,
+                event_index: None,
                 source: Box::new(std::io::Error::other("fail")),
+                retryable: false,
             })
         } else {
             Ok(())
@@ -155,93 +168,142 @@ pub enum TestEvent {
     B(bool),
 }
 
-#[derive(Encode)]
-pub struct EventRecord<E: Encode> {
-    phase: Phase,
-    event: TestAllEvents<E>,
-    topics: Vec<subxt::config::HashFor<SubstrateConfig>>,
+// ------------------------- Hex Fixtures --------------------------------
+/// Bytes of the hex string `hex`, ignoring surrounding whitespace.
+pub fn decode_hex(hex: &str) -> Vec<u8> {
+    let hex = hex.trim();
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
 }
 
-impl<E: Encode> EventRecord<E> {
-    pub fn new(phase: Phase, event: E) -> Self {
-        Self {
-            phase,
-            event: TestAllEvents::Test(event),
-            topics: Vec::new(),
-        }
-    }
+/// Bytes of the hex file `tests/fixtures/{dir}/{name}.hex`.
+pub fn fixture_hex(dir: &str, name: &str) -> Vec<u8> {
+    let path = format!(
+        "{}/tests/fixtures/{dir}/{name}.hex",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    decode_hex(&std::fs::read_to_string(path).unwrap())
+}
+
+// ------------------------- Sink Fixtures -------------------------------
+/// `Balances` events fed to the sink handlers.
+#[derive(Encode, Decode, TypeInfo)]
+pub enum Balances {
+    Transfer { from: u8, to: u8, amount: u64 },
+    Deposit { who: u8, amount: u64 },
+}
+
+/// A decoded `Balances.Transfer`.
+#[derive(DecodeAsType)]
+pub struct Transfer {
+    pub from: u8,
+    pub to: u8,
+    pub amount: u64,
+}
+
+impl StaticEvent for Transfer {
+    const PALLET: &'static str = "Balances";
+    const EVENT: &'static str = "Transfer";
 }
 
-#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq)]
-pub enum TestAllEvents<E> {
-    Test(E),
+/// A transfer of `amount` from account 1 to account 2.
+pub fn transfer(amount: u64) -> Balances {
+    transfer_to(2, amount)
 }
 
-pub fn test_metadata<E: TypeInfo + 'static>() -> Metadata {
-    #[derive(TypeInfo)]
-    struct ExtrinsicType<Call> {
-        call: Call,
+/// A transfer of `amount` from account 1 to account `to`.
+pub fn transfer_to(to: u8, amount: u64) -> Balances {
+    Balances::Transfer {
+        from: 1,
+        to,
+        amount,
     }
-    #[derive(TypeInfo)]
-    enum RuntimeCall {
-        PalletName(Pallet),
+}
+
+/// Hand `handler` block `block` with `block_events` from the `Balances` pallet,
+/// all emitted by extrinsic 0. Every byte of the block hash is the low byte of
+/// `block`.
+pub async fn try_write_block<E, H>(
+    handler: &H,
+    block: u64,
+    block_events: Vec<E>,
+) -> Result<(), IndexerError>
+where
+    E: Encode + TypeInfo + 'static,
+    H: Handler<SubstrateConfig>,
+{
+    let records = block_events
+        .into_iter()
+        .map(|event| EventRecord::new(Phase::ApplyExtrinsic(0), event))
+        .collect();
+    let evs = events(pallet_metadata::<E>("Balances"), records);
+    let ces: Vec<ChainEvent<SubstrateConfig>> = ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect();
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(block)
+        .block_hash(H256::repeat_byte(block as u8))
+        .build();
+    handler.handle_block(&ctx, &ces).await
+}
+
+/// [`try_write_block`], panicking if the handler fails.
+pub async fn write_block<E, H>(handler: &H, block: u64, block_events: Vec<E>)
+where
+    E: Encode + TypeInfo + 'static,
+    H: Handler<SubstrateConfig>,
+{
+    try_write_block(handler, block, block_events).await.unwrap();
+}
+
+// ------------------------- Node-less Client ----------------------------
+/// RPC client failing every call, counting them.
+#[derive(Clone, Default)]
+pub struct NoNode {
+    pub calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl RpcClientT for NoNode {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        _params: Option<Box<RawValue>>,
+    ) -> RawRpcFuture<'a, Box<RawValue>> {
+        self.calls.lock().unwrap().push(method.to_string());
+        Box::pin(async move {
+            Err(ClientError::Client(Box::new(std::io::Error::other(
+                "no node",
+            ))))
+        })
     }
-    #[derive(TypeInfo)]
-    enum Pallet {
-        SomeCall,
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        _params: Option<Box<RawValue>>,
+        _unsub: &'a str,
+    ) -> RawRpcFuture<'a, RawRpcSubscription> {
+        self.calls.lock().unwrap().push(sub.to_string());
+        Box::pin(async move {
+            Err(ClientError::Client(Box::new(std::io::Error::other(
+                "no node",
+            ))))
+        })
     }
+}
 
-    let pallets = vec![PalletMetadata {
-        name: "Test",
-        storage: None,
-        calls: None,
-        event: Some(PalletEventMetadata {
-            ty: meta_type::<E>(),
-        }),
-        constants: vec![],
-        error: None,
-        index: 0,
-        docs: vec![],
-    }];
-
-    let extrinsic = ExtrinsicMetadata {
-        version: 0,
-        signed_extensions: vec![],
-        address_ty: meta_type::<()>(),
-        call_ty: meta_type::<RuntimeCall>(),
-        signature_ty: meta_type::<()>(),
-        extra_ty: meta_type::<()>(),
-    };
-
-    let meta = RuntimeMetadataV15::new(
-        pallets,
-        extrinsic,
-        meta_type::<()>(),
-        vec![],
-        OuterEnums {
-            call_enum_ty: meta_type::<()>(),
-            event_enum_ty: meta_type::<TestAllEvents<E>>(),
-            error_enum_ty: meta_type::<()>(),
+/// A client with [`test_metadata`] for `E`, backed by `rpc` instead of a node.
+pub fn offline_client<E: TypeInfo + 'static>(rpc: NoNode) -> OnlineClient<SubstrateConfig> {
+    OnlineClient::from_rpc_client_with(
+        Default::default(),
+        RuntimeVersion {
+            spec_version: 1,
+            transaction_version: 1,
         },
-        CustomMetadata {
-            map: Default::default(),
-        },
-    );
-    let runtime_metadata: RuntimeMetadataPrefixed = meta.into();
-    let metadata: subxt_metadata::Metadata = runtime_metadata.try_into().unwrap();
-    Metadata::from(metadata)
-}
-
-pub fn events<E: Decode + Encode>(
-    metadata: Metadata,
-    records: Vec<EventRecord<E>>,
-) -> Events<SubstrateConfig> {
-    let num_events = records.len() as u32;
-    let mut event_bytes = Vec::new();
-    for ev in records {
-        ev.encode_to(&mut event_bytes);
-    }
-    let mut all_event_bytes = parity_scale_codec::Compact(num_events).encode();
-    all_event_bytes.extend(event_bytes);
-    subxt::events::Events::decode_from(all_event_bytes, metadata)
+        test_metadata::<E>(),
+        RpcClient::new(rpc),
+    )
+    .unwrap()
 }
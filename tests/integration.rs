@@ -16,4 +16,13 @@
 
 mod integration {
     mod test_indexer;
+    #[cfg(feature = "nats")]
+    mod test_nats;
+    #[cfg(all(
+        feature = "bittensor-events",
+        any(feature = "postgres", feature = "sqlite")
+    ))]
+    mod test_ownership;
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
+    mod test_transfers;
 }
@@ -24,7 +24,6 @@ use flamewire_bittensor_indexer::{ChainEvent, EventFilter};
 use std::sync::Arc;
 use subxt::config::substrate::SubstrateConfig;
 use subxt::events::Phase;
-use subxt::utils::H256;
 
 async fn process_blocks(
     handler: Arc<MockHandler>,
@@ -49,7 +48,9 @@ async fn process_blocks(
     ];
 
     for (num, evs) in blocks {
-        let ctx = Context::<SubstrateConfig>::new(num, H256::zero());
+        let ctx = Context::<SubstrateConfig>::builder()
+            .block_number(num)
+            .build();
         let mut ces = Vec::new();
         for (i, e) in evs.iter().enumerate() {
             ces.push(ChainEvent::new(e?, i as u32));
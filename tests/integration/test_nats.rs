@@ -0,0 +1,118 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// Needs a NATS server with JetStream, e.g. `nats-server -js`, at `NATS_URL`.
+
+#[path = "../common/mod.rs"]
+mod common;
+use async_nats::jetstream::{self, stream};
+use common::*;
+use flamewire_bittensor_indexer::handler::{Context, Handler};
+use flamewire_bittensor_indexer::nats::{NatsSinkHandler, OnUnreachable};
+use flamewire_bittensor_indexer::{ChainEvent, IndexerError};
+use serde_json::Value;
+use std::time::Duration;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::H256;
+
+fn chain_events() -> Vec<ChainEvent<SubstrateConfig>> {
+    let evs = events(
+        test_metadata::<TestEvent>(),
+        vec![
+            EventRecord::new(Phase::ApplyExtrinsic(0), TestEvent::A(1)),
+            EventRecord::new(Phase::ApplyExtrinsic(1), TestEvent::B(true)),
+        ],
+    );
+    ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn events_are_published_to_jetstream() {
+    let Ok(url) = std::env::var("NATS_URL") else {
+        return;
+    };
+    let client = async_nats::connect(&url).await.unwrap();
+    let js = jetstream::new(client.clone());
+    let stream = js
+        .get_or_create_stream(stream::Config {
+            name: "INDEXER_TEST".into(),
+            subjects: vec!["indexer-test.>".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let sink = NatsSinkHandler::with_client(client, "indexer-test").unwrap();
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(11)
+        .block_hash(H256::repeat_byte(2))
+        .build();
+    for event in &chain_events() {
+        sink.handle_event(event, &ctx).await.unwrap();
+    }
+    sink.on_shutdown().await.unwrap();
+    assert_eq!(sink.published(), 2);
+
+    let message = stream
+        .get_last_raw_message_by_subject("indexer-test.Test.B")
+        .await
+        .unwrap();
+    let envelope: Value = serde_json::from_slice(&message.payload).unwrap();
+    assert_eq!(envelope["block_number"], 11);
+    assert_eq!(envelope["index"], 1);
+    assert_eq!(envelope["variant"], "B");
+}
+
+#[tokio::test]
+async fn unacknowledged_messages_are_buffered_then_fail() {
+    let Ok(url) = std::env::var("NATS_URL") else {
+        return;
+    };
+    let client = async_nats::connect(&url).await.unwrap();
+    // No stream captures these subjects, so JetStream never acknowledges them.
+    let sink = NatsSinkHandler::with_client(client.clone(), "indexer-test-unrouted")
+        .unwrap()
+        .with_ack_timeout(Duration::from_millis(500))
+        .on_unreachable(OnUnreachable::Buffer(1));
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(12)
+        .build();
+    let events = chain_events();
+
+    sink.handle_event(&events[0], &ctx).await.unwrap();
+    assert_eq!(sink.buffered().await, 1);
+    let checkpoint = Handler::<SubstrateConfig>::on_checkpoint(&sink, 12).await;
+    assert_eq!(checkpoint.unwrap(), Some(11));
+    let err = sink.handle_event(&events[1], &ctx).await.unwrap_err();
+    assert!(matches!(
+        err,
+        IndexerError::HandlerFailed {
+            retryable: true,
+            ..
+        }
+    ));
+    assert_eq!(sink.failed(), 1);
+    assert!(sink.on_shutdown().await.is_err());
+
+    let failing = NatsSinkHandler::with_client(client, "indexer-test-unrouted")
+        .unwrap()
+        .with_ack_timeout(Duration::from_millis(500));
+    assert!(failing.handle_event(&events[0], &ctx).await.is_err());
+    assert_eq!(failing.buffered().await, 0);
+}
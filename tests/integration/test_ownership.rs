@@ -0,0 +1,122 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::presets::ownership::{
+    Ownership, OwnershipStore, SqlOwnershipStore,
+};
+use subxt::utils::AccountId32;
+
+fn key(byte: u8) -> AccountId32 {
+    AccountId32([byte; 32])
+}
+
+fn owned(coldkey: u8, since_block: u64) -> Option<Ownership> {
+    Some(Ownership {
+        coldkey: key(coldkey),
+        since_block,
+    })
+}
+
+/// Hotkeys 1 and 2 belong to coldkey 10 from `base`; hotkey 1 moves to 11
+/// at `base + 10` and is released at `base + 20`.
+async fn exercise(store: &SqlOwnershipStore, base: u64) {
+    store
+        .set_owner(&key(1), Some(&key(10)), base)
+        .await
+        .unwrap();
+    store
+        .set_owner(&key(2), Some(&key(10)), base)
+        .await
+        .unwrap();
+    store
+        .set_owner(&key(1), Some(&key(12)), base + 10)
+        .await
+        .unwrap();
+    // Re-processing the block replaces its row.
+    store
+        .set_owner(&key(1), Some(&key(11)), base + 10)
+        .await
+        .unwrap();
+    store.set_owner(&key(1), None, base + 20).await.unwrap();
+
+    assert_eq!(store.owner_at(&key(1), base - 1).await.unwrap(), None);
+    assert_eq!(
+        store.owner_at(&key(1), base + 9).await.unwrap(),
+        owned(10, base)
+    );
+    assert_eq!(
+        store.owner_at(&key(1), base + 15).await.unwrap(),
+        owned(11, base + 10)
+    );
+    assert_eq!(store.owner_at(&key(1), base + 20).await.unwrap(), None);
+
+    let mut hotkeys = store.hotkeys_at(&key(10), base).await.unwrap();
+    hotkeys.sort();
+    assert_eq!(hotkeys, vec![key(1), key(2)]);
+    assert_eq!(
+        store.hotkeys_at(&key(10), base + 10).await.unwrap(),
+        vec![key(2)]
+    );
+    assert_eq!(
+        store.hotkeys_at(&key(11), base + 10).await.unwrap(),
+        vec![key(1)]
+    );
+    assert!(store
+        .hotkeys_at(&key(11), base + 20)
+        .await
+        .unwrap()
+        .is_empty());
+
+    store.rollback(base + 10).await.unwrap();
+    assert_eq!(
+        store.owner_at(&key(1), base + 30).await.unwrap(),
+        owned(10, base)
+    );
+    assert_eq!(
+        store.owner_at(&key(2), base + 30).await.unwrap(),
+        owned(10, base)
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_ownership_history() {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let store = SqlOwnershipStore::sqlite(pool);
+    store.create_schema().await.unwrap();
+    store.create_schema().await.unwrap();
+
+    exercise(&store, 100).await;
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn postgres_ownership_history() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        return;
+    };
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    let store = SqlOwnershipStore::postgres(pool);
+    store.create_schema().await.unwrap();
+    let base = 900_000_000;
+    store.rollback(base).await.unwrap();
+
+    exercise(&store, base).await;
+}
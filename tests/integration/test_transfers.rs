@@ -0,0 +1,170 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::handler::{Context, Handler};
+use flamewire_bittensor_indexer::presets::transfers::TransferIndexer;
+use flamewire_bittensor_indexer::ss58::encode_bittensor;
+use flamewire_bittensor_indexer::ChainEvent;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::AccountId32;
+
+#[derive(Encode, Decode, TypeInfo)]
+enum BalancesEvent {
+    Transfer {
+        from: AccountId32,
+        to: AccountId32,
+        amount: u64,
+    },
+    Deposit {
+        who: AccountId32,
+        amount: u64,
+    },
+}
+
+type Row = (i64, i32, String, String, i64, Option<i64>);
+
+fn alice() -> AccountId32 {
+    AccountId32([1; 32])
+}
+
+fn bob() -> AccountId32 {
+    AccountId32([2; 32])
+}
+
+/// Timestamp of block `number`, one block every 12 seconds.
+fn timestamp(number: u64) -> u64 {
+    number * 12_000
+}
+
+/// Feed two synthetic blocks through `indexer`, the second one twice.
+async fn process_blocks(indexer: &TransferIndexer, first_block: u64) {
+    let blocks = [
+        vec![
+            BalancesEvent::Deposit {
+                who: alice(),
+                amount: 5,
+            },
+            BalancesEvent::Transfer {
+                from: alice(),
+                to: bob(),
+                amount: 1_000_000_000,
+            },
+        ],
+        vec![BalancesEvent::Transfer {
+            from: bob(),
+            to: alice(),
+            amount: 42,
+        }],
+    ];
+    for (offset, block) in blocks.into_iter().enumerate() {
+        let records = block
+            .into_iter()
+            .map(|event| EventRecord::new(Phase::ApplyExtrinsic(1), event))
+            .collect();
+        let evs = events(pallet_metadata::<BalancesEvent>("Balances"), records);
+        let ces: Vec<ChainEvent<SubstrateConfig>> = ChainEvent::from_events(&evs)
+            .map(|event| event.unwrap())
+            .collect();
+        let number = first_block + offset as u64;
+        let ctx = Context::<SubstrateConfig>::builder()
+            .block_number(number)
+            .timestamp(timestamp(number))
+            .build();
+        indexer.handle_block(&ctx, &ces).await.unwrap();
+        if offset == 1 {
+            indexer.handle_block(&ctx, &ces).await.unwrap();
+        }
+    }
+}
+
+fn expected_rows(first_block: u64) -> Vec<Row> {
+    vec![
+        (
+            first_block as i64,
+            1,
+            encode_bittensor(&alice()),
+            encode_bittensor(&bob()),
+            1_000_000_000,
+            Some(timestamp(first_block) as i64),
+        ),
+        (
+            first_block as i64 + 1,
+            0,
+            encode_bittensor(&bob()),
+            encode_bittensor(&alice()),
+            42,
+            Some(timestamp(first_block + 1) as i64),
+        ),
+    ]
+}
+
+const SELECT_ROWS: &str =
+    "SELECT block_number, event_index, from_address, to_address, amount, timestamp
+     FROM transfers WHERE block_number >= $1 ORDER BY block_number, event_index";
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_transfers_are_written() {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let indexer = TransferIndexer::sqlite(pool.clone());
+    indexer.create_schema().await.unwrap();
+    indexer.create_schema().await.unwrap();
+
+    process_blocks(&indexer, 10).await;
+
+    let rows: Vec<Row> = sqlx::query_as(SELECT_ROWS)
+        .bind(10i64)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rows, expected_rows(10));
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn postgres_transfers_are_written() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        return;
+    };
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    let indexer = TransferIndexer::postgres(pool.clone());
+    indexer.create_schema().await.unwrap();
+    let first_block = 900_000_000i64;
+    sqlx::query("DELETE FROM transfers WHERE block_number >= $1")
+        .bind(first_block)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    process_blocks(&indexer, first_block as u64).await;
+
+    let rows: Vec<Row> = sqlx::query_as(SELECT_ROWS)
+        .bind(first_block)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rows, expected_rows(first_block as u64));
+}
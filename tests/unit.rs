@@ -15,11 +15,76 @@
  */
 
 mod unit {
+    #[cfg(feature = "webhook")]
+    mod test_alert;
+    #[cfg(feature = "bittensor-events")]
+    mod test_bittensor_calls;
+    #[cfg(feature = "bittensor-events")]
+    mod test_bittensor_events;
+    mod test_block_author;
+    mod test_block_source;
     mod test_config;
+    #[cfg(feature = "config-file")]
+    mod test_config_file;
+    #[cfg(feature = "csv-export")]
+    mod test_csv_export;
+    mod test_custom_client;
+    mod test_decode_robustness;
     mod test_error;
+    mod test_error_reporter;
     mod test_error_scenarios;
+    mod test_event_allowlist;
+    mod test_event_fields;
+    mod test_event_id;
+    #[cfg(feature = "json-storage")]
+    mod test_event_json;
+    mod test_extrinsics;
+    #[cfg(feature = "grpc")]
+    mod test_grpc;
     mod test_handler;
     mod test_handler_group;
+    mod test_handler_panic;
+    mod test_harness;
+    mod test_heartbeat;
+    mod test_hedge;
+    #[cfg(feature = "http-api")]
+    mod test_http_api;
+    #[cfg(feature = "json-storage")]
+    mod test_json_lines;
+    #[cfg(feature = "metrics")]
+    mod test_metrics;
+    #[cfg(feature = "nats")]
+    mod test_nats;
+    mod test_node_auth;
+    #[cfg(feature = "bittensor-events")]
+    mod test_ownership;
+    #[cfg(feature = "parquet")]
+    mod test_parquet_export;
+    mod test_process_block;
     mod test_property_based;
+    mod test_queue;
+    mod test_redact;
+    #[cfg(feature = "bittensor-events")]
+    mod test_registration_costs;
+    mod test_reorg;
+    mod test_replay;
+    mod test_rpc_connect;
+    mod test_run_limits;
+    mod test_settings;
+    #[cfg(feature = "sqlite")]
+    mod test_sql_sink;
+    mod test_ss58;
+    mod test_start_from;
+    #[cfg(feature = "statsd")]
+    mod test_statsd;
     mod test_storage;
+    #[cfg(all(feature = "bittensor-events", feature = "metrics"))]
+    mod test_subnet_metrics;
+    #[cfg(feature = "bittensor-events")]
+    mod test_subtensor_storage;
+    mod test_tracing;
+    mod test_units;
+    mod test_validated_types;
+    #[cfg(feature = "webhook")]
+    mod test_webhook;
 }
@@ -0,0 +1,273 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::alert::{AlertFormat, AlertHandler, AlertRule};
+use flamewire_bittensor_indexer::handler::{EventFilter, Handler};
+use flamewire_bittensor_indexer::ss58::encode_bittensor;
+use flamewire_bittensor_indexer::units::Rao;
+use flamewire_bittensor_indexer::IndexerError;
+use parity_scale_codec::{Decode, Encode};
+use scale_decode::DecodeAsType;
+use scale_info::TypeInfo;
+use serde_json::{json, Value};
+use std::time::Duration;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::StaticEvent;
+use subxt::utils::AccountId32;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// `Balances` events with full account ids and amounts, which the alert
+/// templates render.
+#[derive(Encode, Decode, TypeInfo)]
+enum AccountBalances {
+    Transfer {
+        from: [u8; 32],
+        to: [u8; 32],
+        amount: u128,
+    },
+    Deposit {
+        who: [u8; 32],
+        amount: u128,
+    },
+}
+
+#[derive(DecodeAsType)]
+struct Deposit {
+    #[allow(dead_code)]
+    who: [u8; 32],
+    amount: u128,
+}
+
+impl StaticEvent for Deposit {
+    const PALLET: &'static str = "Balances";
+    const EVENT: &'static str = "Deposit";
+}
+
+fn transfer(tao: u64) -> AccountBalances {
+    AccountBalances::Transfer {
+        from: [1; 32],
+        to: [2; 32],
+        amount: Rao::from_tao(tao).get(),
+    }
+}
+
+/// Alert on transfers above 10 000 TAO.
+fn large_transfers(template: &str) -> AlertRule<SubstrateConfig> {
+    let threshold = Rao::from_tao(10_000).get();
+    AlertRule::new(EventFilter::event("Balances", "Transfer"), template).when(move |event| {
+        let amount = event.field("amount").ok().flatten();
+        amount.and_then(|amount| amount.as_u128()) > Some(threshold)
+    })
+}
+
+async fn mock_server(status: u16) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(status))
+        .mount(&server)
+        .await;
+    server
+}
+
+async fn bodies(server: &MockServer) -> Vec<Value> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_rejects_non_http_urls() {
+    assert!(matches!(
+        AlertHandler::<SubstrateConfig>::new("ftp://example.com/hook", AlertFormat::Slack),
+        Err(IndexerError::InvalidConfig { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_renders_template_for_events_over_threshold() {
+    let server = mock_server(200).await;
+    let handler = AlertHandler::new(server.uri(), AlertFormat::Slack)
+        .unwrap()
+        .rule(large_transfers(
+            "{amount:tao} TAO from {from} to {to} in {pallet}.{event} #{block}/{index}, {{{missing}}}",
+        ));
+
+    write_block(&handler, 7, vec![transfer(5_000), transfer(12_500)]).await;
+    handler.flush().await;
+
+    let from = encode_bittensor(&AccountId32([1; 32]));
+    let to = encode_bittensor(&AccountId32([2; 32]));
+    assert_eq!(
+        bodies(&server).await,
+        vec![json!({
+            "text": format!(
+                "12500 TAO from {from} to {to} in Balances.Transfer #7/1, {{{{missing}}}}"
+            )
+        })]
+    );
+    assert_eq!(handler.sent(), 1);
+}
+
+#[tokio::test]
+async fn test_typed_rules_post_discord_messages() {
+    let server = mock_server(204).await;
+    let handler = AlertHandler::new(server.uri(), AlertFormat::Discord)
+        .unwrap()
+        .rule(AlertRule::typed(|deposit: Deposit, ctx| {
+            (deposit.amount >= 1_000_000_000).then(|| {
+                format!(
+                    "Deposit of {} TAO in block {}",
+                    Rao::new(deposit.amount).to_tao_string(1),
+                    ctx.block_number
+                )
+            })
+        }));
+
+    write_block(
+        &handler,
+        3,
+        vec![
+            AccountBalances::Deposit {
+                who: [1; 32],
+                amount: 1_500_000_000,
+            },
+            AccountBalances::Deposit {
+                who: [1; 32],
+                amount: 5,
+            },
+            transfer(20_000),
+        ],
+    )
+    .await;
+    handler.flush().await;
+
+    assert_eq!(
+        bodies(&server).await,
+        vec![json!({ "content": "Deposit of 1.5 TAO in block 3" })]
+    );
+}
+
+#[tokio::test]
+async fn test_suppresses_alerts_over_rate_limit() {
+    let server = mock_server(200).await;
+    let handler = AlertHandler::new(server.uri(), AlertFormat::Slack)
+        .unwrap()
+        .rule(large_transfers("{amount:tao} TAO"))
+        .rate_limit(2, Duration::from_millis(200));
+
+    write_block(&handler, 1, (1..=5).map(|n| transfer(10_000 + n)).collect()).await;
+    handler.flush().await;
+    assert_eq!(bodies(&server).await.len(), 2);
+    assert_eq!(handler.suppressed(), 3);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    write_block(&handler, 2, vec![transfer(20_000)]).await;
+    handler.flush().await;
+
+    let texts: Vec<Value> = bodies(&server)
+        .await
+        .into_iter()
+        .map(|body| body["text"].clone())
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            json!("10001 TAO"),
+            json!("10002 TAO"),
+            json!("Suppressed 3 alerts over the limit of 2 per 200ms"),
+            json!("20000 TAO"),
+        ]
+    );
+    assert_eq!(handler.sent(), 4);
+}
+
+#[tokio::test]
+async fn test_shutdown_reports_suppressed_alerts() {
+    let server = mock_server(200).await;
+    let handler = AlertHandler::new(server.uri(), AlertFormat::Slack)
+        .unwrap()
+        .rule(large_transfers("{amount:tao} TAO"))
+        .rate_limit(1, Duration::from_secs(60));
+
+    write_block(&handler, 1, vec![transfer(10_001), transfer(10_002)]).await;
+    Handler::<SubstrateConfig>::on_shutdown(&handler)
+        .await
+        .unwrap();
+    Handler::<SubstrateConfig>::on_shutdown(&handler)
+        .await
+        .unwrap();
+
+    let bodies = bodies(&server).await;
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(
+        bodies[1]["text"],
+        "Suppressed 1 alerts over the limit of 1 per 60s"
+    );
+}
+
+#[tokio::test]
+async fn test_delivery_failures_do_not_fail_the_handler() {
+    let server = mock_server(500).await;
+    let handler = AlertHandler::new(server.uri(), AlertFormat::Slack)
+        .unwrap()
+        .rule(large_transfers("{amount:tao} TAO"));
+
+    // `write_block` unwraps, so reaching the assertions means it succeeded.
+    write_block(&handler, 1, vec![transfer(10_001), transfer(10_002)]).await;
+    handler.flush().await;
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    assert_eq!(handler.sent(), 0);
+    assert_eq!(handler.failed(), 2);
+}
+
+#[tokio::test]
+async fn test_slow_webhook_does_not_delay_blocks() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(200)))
+        .mount(&server)
+        .await;
+    let handler = AlertHandler::new(server.uri(), AlertFormat::Slack)
+        .unwrap()
+        .rule(large_transfers("{amount:tao} TAO"))
+        .queue_capacity(2);
+
+    let started = std::time::Instant::now();
+    write_block(&handler, 1, (1..=5).map(|n| transfer(10_000 + n)).collect()).await;
+    assert!(started.elapsed() < Duration::from_millis(200));
+    // Nothing was taken off the queue yet, so only the first two fit.
+    assert_eq!(handler.dropped(), 3);
+
+    Handler::<SubstrateConfig>::on_shutdown(&handler)
+        .await
+        .unwrap();
+    assert_eq!(handler.sent(), 2);
+    let texts: Vec<Value> = bodies(&server)
+        .await
+        .into_iter()
+        .map(|body| body["text"].clone())
+        .collect();
+    assert_eq!(texts, vec![json!("10001 TAO"), json!("10002 TAO")]);
+}
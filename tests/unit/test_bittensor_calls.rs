@@ -0,0 +1,188 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::calls::subtensor::{RegisterCall, ServeAxonCall, SetWeightsCall};
+use flamewire_bittensor_indexer::extrinsics::ChainExtrinsic;
+use scale_info::TypeInfo;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::utils::AccountId32;
+use subxt::Metadata;
+
+// Whole extrinsics, length prefix included, with the Finney call layouts of
+// `SubtensorModule` at pallet index 7. Signed ones use the envelope of
+// `call_metadata` (a `MultiAddress::Id` of Alice, a zeroed signature and no
+// transaction extensions); Bob is the coldkey.
+fn fixture(name: &str) -> Vec<u8> {
+    fixture_hex("extrinsics", name)
+}
+
+fn alice() -> AccountId32 {
+    "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+        .parse()
+        .unwrap()
+}
+
+fn bob() -> AccountId32 {
+    "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty"
+        .parse()
+        .unwrap()
+}
+
+#[allow(non_camel_case_types, dead_code)]
+#[derive(TypeInfo)]
+enum SubtensorCall {
+    #[codec(index = 0)]
+    set_weights {
+        netuid: u16,
+        dests: Vec<u16>,
+        weights: Vec<u16>,
+        version_key: u64,
+    },
+    #[codec(index = 4)]
+    serve_axon {
+        netuid: u16,
+        version: u32,
+        ip: u128,
+        port: u16,
+        ip_type: u8,
+        protocol: u8,
+        placeholder1: u8,
+        placeholder2: u8,
+    },
+    #[codec(index = 6)]
+    register {
+        netuid: u16,
+        block_number: u64,
+        nonce: u64,
+        work: Vec<u8>,
+        hotkey: AccountId32,
+        coldkey: AccountId32,
+    },
+}
+
+/// A runtime whose `set_weights` takes u32 uids and weights.
+#[allow(non_camel_case_types, dead_code)]
+#[derive(TypeInfo)]
+enum SubtensorCallU32 {
+    #[codec(index = 0)]
+    set_weights {
+        netuid: u16,
+        dests: Vec<u32>,
+        weights: Vec<u32>,
+        version_key: u64,
+    },
+}
+
+fn decode_one(metadata: Metadata, name: &str) -> ChainExtrinsic {
+    let mut decoded =
+        ChainExtrinsic::decode_all::<SubstrateConfig>(vec![fixture(name)], metadata).unwrap();
+    assert_eq!(decoded.len(), 1);
+    decoded.remove(0)
+}
+
+fn subtensor(name: &str) -> ChainExtrinsic {
+    decode_one(call_metadata::<SubtensorCall>("SubtensorModule", 7), name)
+}
+
+fn expected_weights() -> SetWeightsCall {
+    SetWeightsCall {
+        netuid: 1,
+        dests: vec![0, 1, 2],
+        weights: vec![65535, 32768, 0],
+        version_key: 2_001_000,
+    }
+}
+
+#[test]
+fn signed_set_weights_decodes() {
+    let extrinsic = subtensor("set_weights_signed");
+    assert_eq!(extrinsic.pallet_name(), "SubtensorModule");
+    assert_eq!(extrinsic.call_name(), "set_weights");
+    assert!(extrinsic.is_signed());
+    assert_eq!(extrinsic.signer(), Some(&alice()));
+    assert_eq!(
+        extrinsic.as_call::<SetWeightsCall>().unwrap(),
+        Some(expected_weights())
+    );
+    assert_eq!(extrinsic.as_call::<ServeAxonCall>().unwrap(), None);
+    assert_eq!(extrinsic.as_call::<RegisterCall>().unwrap(), None);
+}
+
+#[test]
+fn unsigned_set_weights_decodes() {
+    let extrinsic = subtensor("set_weights_unsigned");
+    assert!(!extrinsic.is_signed());
+    assert_eq!(extrinsic.signer(), None);
+    assert_eq!(
+        extrinsic.as_call::<SetWeightsCall>().unwrap(),
+        Some(expected_weights())
+    );
+}
+
+#[test]
+fn set_weights_decodes_across_argument_widths() {
+    let extrinsic = decode_one(
+        call_metadata::<SubtensorCallU32>("SubtensorModule", 7),
+        "set_weights_u32",
+    );
+    assert_eq!(
+        extrinsic.as_call::<SetWeightsCall>().unwrap(),
+        Some(expected_weights())
+    );
+}
+
+#[test]
+fn serve_axon_decodes() {
+    let call = subtensor("serve_axon_signed")
+        .as_call::<ServeAxonCall>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        call,
+        ServeAxonCall {
+            netuid: 1,
+            version: 7_002_000,
+            ip: u32::from(std::net::Ipv4Addr::new(192, 168, 1, 1)).into(),
+            port: 8091,
+            ip_type: 4,
+            protocol: 4,
+            placeholder1: 0,
+            placeholder2: 0,
+        }
+    );
+}
+
+#[test]
+fn register_decodes() {
+    let call = subtensor("register_signed")
+        .as_call::<RegisterCall>()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        call,
+        RegisterCall {
+            netuid: 1,
+            block_number: 1000,
+            nonce: 42,
+            work: vec![0xab; 32],
+            hotkey: alice(),
+            coldkey: bob(),
+        }
+    );
+}
@@ -0,0 +1,180 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::events::{balances, subtensor};
+use flamewire_bittensor_indexer::filters;
+use flamewire_bittensor_indexer::handler::EventFilter;
+use flamewire_bittensor_indexer::types::ChainEvent;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::{Phase, StaticEvent};
+use subxt::utils::AccountId32;
+
+// Field bytes laid out as the Finney runtime encodes them; Alice is the hotkey
+// and Bob the coldkey throughout.
+fn fixture(name: &str) -> Vec<u8> {
+    fixture_hex("events", name)
+}
+
+fn decode<T: Decode>(name: &str) -> T {
+    let bytes = fixture(name);
+    let mut input = bytes.as_slice();
+    let event = T::decode(&mut input).unwrap();
+    assert!(input.is_empty(), "{name} has trailing bytes");
+    event
+}
+
+fn alice() -> AccountId32 {
+    "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+        .parse()
+        .unwrap()
+}
+
+fn bob() -> AccountId32 {
+    "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty"
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn fixtures_decode() {
+    assert_eq!(
+        decode::<subtensor::StakeAddedV1>("stake_added_v1"),
+        subtensor::StakeAddedV1(alice(), 1_000_000_000)
+    );
+    assert_eq!(
+        decode::<subtensor::StakeAddedV2>("stake_added_v2"),
+        subtensor::StakeAddedV2(bob(), alice(), 1_000_000_000, 987_654_321, 1, 50_000)
+    );
+    assert_eq!(
+        decode::<subtensor::StakeRemovedV1>("stake_removed_v1"),
+        subtensor::StakeRemovedV1(alice(), 500_000_000)
+    );
+    assert_eq!(
+        decode::<subtensor::StakeRemovedV2>("stake_removed_v2"),
+        subtensor::StakeRemovedV2(bob(), alice(), 500_000_000, 493_827_160, 1, 50_000)
+    );
+    assert_eq!(
+        decode::<subtensor::NeuronRegistered>("neuron_registered"),
+        subtensor::NeuronRegistered(1, 42, alice())
+    );
+    assert_eq!(
+        decode::<subtensor::AxonServed>("axon_served"),
+        subtensor::AxonServed(1, alice())
+    );
+    assert_eq!(
+        decode::<balances::Transfer>("balances_transfer"),
+        balances::Transfer {
+            from: bob(),
+            to: alice(),
+            amount: 250_000_000,
+        }
+    );
+}
+
+fn assert_filter_for<E: StaticEvent>(filter: EventFilter) {
+    assert_eq!(filter.pallet, Some(E::PALLET));
+    assert_eq!(filter.event, Some(E::EVENT));
+}
+
+#[test]
+fn filters_match_the_typed_events() {
+    assert_filter_for::<subtensor::StakeAddedV1>(filters::subtensor::STAKE_ADDED);
+    assert_filter_for::<subtensor::StakeAddedV2>(filters::subtensor::STAKE_ADDED);
+    assert_filter_for::<subtensor::StakeRemovedV1>(filters::subtensor::STAKE_REMOVED);
+    assert_filter_for::<subtensor::StakeRemovedV2>(filters::subtensor::STAKE_REMOVED);
+    assert_filter_for::<subtensor::NeuronRegistered>(filters::subtensor::NEURON_REGISTERED);
+    assert_filter_for::<subtensor::AxonServed>(filters::subtensor::AXON_SERVED);
+    assert_filter_for::<balances::Transfer>(filters::balances::TRANSFER);
+
+    let pallet = filters::subtensor::pallet();
+    assert_eq!(pallet.pallet, Some(subtensor::StakeAddedV2::PALLET));
+    assert_eq!(pallet.event, None);
+    assert_eq!(
+        filters::balances::pallet().pallet,
+        Some(balances::Transfer::PALLET)
+    );
+}
+
+/// `SubtensorModule` events before dynamic TAO.
+#[derive(Encode, Decode, TypeInfo)]
+enum SubtensorV1 {
+    StakeAdded(AccountId32, u64),
+    StakeRemoved(AccountId32, u64),
+}
+
+/// `SubtensorModule` events since dynamic TAO.
+#[derive(Encode, Decode, TypeInfo)]
+enum SubtensorV2 {
+    StakeAdded(AccountId32, AccountId32, u64, u64, u16, u64),
+    StakeRemoved(AccountId32, AccountId32, u64, u64, u16, u64),
+    AxonServed(u16, AccountId32),
+}
+
+fn subtensor_events<E: Decode + Encode + TypeInfo + 'static>(
+    pallet_events: Vec<E>,
+) -> Vec<ChainEvent<SubstrateConfig>> {
+    let records = pallet_events
+        .into_iter()
+        .map(|event| EventRecord::new(Phase::ApplyExtrinsic(1), event))
+        .collect();
+    let evs = events(pallet_metadata::<E>("SubtensorModule"), records);
+    ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect()
+}
+
+#[test]
+fn versioned_events_decode_either_layout() {
+    let v1 = subtensor_events(vec![
+        SubtensorV1::StakeAdded(alice(), 7),
+        SubtensorV1::StakeRemoved(alice(), 3),
+    ]);
+    let added = subtensor::StakeAdded::from_event(&v1[0]).unwrap().unwrap();
+    assert_eq!(
+        added,
+        subtensor::StakeAdded::V1(subtensor::StakeAddedV1(alice(), 7))
+    );
+    let removed = subtensor::StakeRemoved::from_event(&v1[1])
+        .unwrap()
+        .unwrap();
+    assert_eq!(removed.hotkey(), &alice());
+    assert_eq!(removed.amount(), 3);
+
+    let v2 = subtensor_events(vec![
+        SubtensorV2::StakeAdded(bob(), alice(), 9, 8, 1, 0),
+        SubtensorV2::StakeRemoved(bob(), alice(), 4, 3, 1, 0),
+        SubtensorV2::AxonServed(1, alice()),
+    ]);
+    let added = subtensor::StakeAdded::from_event(&v2[0]).unwrap().unwrap();
+    assert!(matches!(added, subtensor::StakeAdded::V2(_)));
+    assert_eq!(added.hotkey(), &alice());
+    assert_eq!(added.amount(), 9);
+    let removed = subtensor::StakeRemoved::from_event(&v2[1])
+        .unwrap()
+        .unwrap();
+    assert_eq!(removed.amount(), 4);
+
+    assert!(subtensor::StakeAdded::from_event(&v2[2]).unwrap().is_none());
+    assert_eq!(
+        v2[2].as_event::<subtensor::AxonServed>().unwrap(),
+        Some(subtensor::AxonServed(1, alice()))
+    );
+}
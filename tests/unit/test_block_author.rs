@@ -0,0 +1,169 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::author::{AuthorResolver, PreRuntime};
+use flamewire_bittensor_indexer::config::IndexerConfig;
+use flamewire_bittensor_indexer::handler::Context;
+use flamewire_bittensor_indexer::IndexerError;
+use parity_scale_codec::Encode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use subxt::config::substrate::{DigestItem, SubstrateConfig};
+use subxt::utils::AccountId32;
+
+fn validators() -> Vec<AccountId32> {
+    (1..=3u8).map(|i| AccountId32([i; 32])).collect()
+}
+
+fn aura(slot: u64) -> DigestItem {
+    DigestItem::PreRuntime(*b"aura", slot.encode())
+}
+
+#[test]
+fn aura_slot_selects_validator_round_robin() {
+    let logs = vec![
+        DigestItem::Seal(*b"aura", vec![0; 64]),
+        aura(1_000),
+        DigestItem::Other(vec![1, 2, 3]),
+    ];
+    let pre_runtime = PreRuntime::from_digest(&logs).unwrap();
+    assert_eq!(pre_runtime, PreRuntime::Aura { slot: 1_000 });
+    assert_eq!(pre_runtime.slot(), 1_000);
+    // 1000 % 3 == 1
+    assert_eq!(
+        pre_runtime.author(&validators()),
+        Some(&AccountId32([2; 32]))
+    );
+    assert_eq!(pre_runtime.author(&[]), None);
+}
+
+#[test]
+fn babe_digest_names_the_authority() {
+    // Secondary plain pre-digest: variant, authority index, slot.
+    let data = (2u8, 2u32, 77u64).encode();
+    let logs = vec![DigestItem::PreRuntime(*b"BABE", data)];
+    let pre_runtime = PreRuntime::from_digest(&logs).unwrap();
+    assert_eq!(
+        pre_runtime,
+        PreRuntime::Babe {
+            authority_index: 2,
+            slot: 77
+        }
+    );
+    assert_eq!(
+        pre_runtime.author(&validators()),
+        Some(&AccountId32([3; 32]))
+    );
+
+    let out_of_range = PreRuntime::Babe {
+        authority_index: 3,
+        slot: 77,
+    };
+    assert_eq!(out_of_range.author(&validators()), None);
+}
+
+#[test]
+fn other_engines_and_malformed_digests_are_ignored() {
+    assert_eq!(PreRuntime::from_digest(&[]), None);
+    assert_eq!(
+        PreRuntime::from_digest(&[DigestItem::PreRuntime(*b"pow_", 5u64.encode())]),
+        None
+    );
+    assert_eq!(PreRuntime::decode(*b"aura", &[1, 2]), None);
+    assert_eq!(
+        PreRuntime::decode(*b"BABE", &(9u8, 0u32, 1u64).encode()),
+        None
+    );
+}
+
+async fn resolve(
+    resolver: &AuthorResolver,
+    loads: &AtomicUsize,
+    number: u64,
+    slot: u64,
+) -> Option<AccountId32> {
+    resolver
+        .resolve(number, Some(PreRuntime::Aura { slot }), || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, IndexerError>(validators())
+        })
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn validator_set_is_read_once_per_session() {
+    let resolver = AuthorResolver::new();
+    let loads = AtomicUsize::new(0);
+
+    assert_eq!(
+        resolve(&resolver, &loads, 10, 30).await,
+        Some(AccountId32([1; 32]))
+    );
+    assert_eq!(
+        resolve(&resolver, &loads, 11, 31).await,
+        Some(AccountId32([2; 32]))
+    );
+    assert_eq!(
+        resolve(&resolver, &loads, 12, 32).await,
+        Some(AccountId32([3; 32]))
+    );
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    resolver.new_session();
+    resolve(&resolver, &loads, 13, 33).await;
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+    // A gap or a reorg back to an earlier block reloads the set.
+    resolve(&resolver, &loads, 20, 40).await;
+    resolve(&resolver, &loads, 19, 39).await;
+    assert_eq!(loads.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn blocks_without_a_slot_claim_have_no_author() {
+    let resolver = AuthorResolver::new();
+    let loads = AtomicUsize::new(0);
+    let author = resolver
+        .resolve(1, None, || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, IndexerError>(validators())
+        })
+        .await
+        .unwrap();
+    assert_eq!(author, None);
+    assert_eq!(loads.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn context_exposes_the_author() {
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+    assert_eq!(ctx.block_author(), None);
+    let ctx = ctx.with_block_author(AccountId32([4; 32]));
+    assert_eq!(ctx.block_author(), Some(&AccountId32([4; 32])));
+}
+
+#[test]
+fn author_resolution_is_opt_in() {
+    assert!(!IndexerConfig::default().resolve_block_author);
+    let config = IndexerConfig::builder()
+        .node_url("ws://node")
+        .resolve_block_author(true)
+        .build()
+        .unwrap();
+    assert!(config.resolve_block_author);
+}
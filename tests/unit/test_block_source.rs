@@ -0,0 +1,231 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use async_trait::async_trait;
+use common::*;
+use flamewire_bittensor_indexer::run::StopReason;
+use flamewire_bittensor_indexer::{
+    ChainEvent, Context, EventFilter, Handler, IndexerBuilder, IndexerError, MemoryStore,
+    MockBlockSource, RetryConfig,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+
+fn block_events(number: u64) -> Vec<u8> {
+    event_bytes(vec![EventRecord::new(
+        Phase::Initialization,
+        TestEvent::A(number as u8),
+    )])
+}
+
+/// Blocks 1 to 8 with one `Test.A` event each, finalized up to 5.
+fn chain() -> MockBlockSource<SubstrateConfig> {
+    MockBlockSource::new(pallet_metadata_bytes::<TestEvent>("Test"))
+        .with_blocks(1..=8, block_events)
+        .finalized(5)
+}
+
+fn fast_retries() -> RetryConfig {
+    RetryConfig {
+        max_retries: 3,
+        initial_delay: Duration::from_millis(1),
+        ..Default::default()
+    }
+}
+
+fn handled_blocks(seen: &Mutex<Vec<String>>) -> Vec<u64> {
+    seen.lock()
+        .unwrap()
+        .iter()
+        .filter_map(|seen| seen.strip_prefix("block:")?.parse().ok())
+        .collect()
+}
+
+#[tokio::test]
+async fn catch_up_hands_off_to_subscription_and_fills_gaps() {
+    let source = chain().with_subscription([6, 8]);
+    let handler = MockHandler::new(EventFilter::all());
+    let seen = handler.events.clone();
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(source.clone())
+        .with_store(MemoryStore::new())
+        .add_handler(handler)
+        .build()
+        .await
+        .unwrap();
+    let summary = indexer.run().await.unwrap();
+
+    assert_eq!(summary.stop_reason, StopReason::SubscriptionClosed);
+    assert_eq!(summary.last_block, Some(8));
+    assert_eq!(handled_blocks(&seen), (0..=8).collect::<Vec<_>>());
+    let events = seen.lock().unwrap();
+    assert_eq!(events.iter().filter(|e| *e == "Test.A").count(), 8);
+    // Blocks 0 to 5 while catching up, then 7 to fill the gap before 8.
+    assert_eq!(source.calls("block_hash"), 7);
+    assert_eq!(source.calls("block_at"), 9);
+}
+
+#[tokio::test]
+async fn transient_source_errors_are_retried() {
+    let timeout = || IndexerError::Timeout {
+        operation: "mock".into(),
+        duration: Duration::from_millis(1),
+    };
+    let source = chain()
+        .with_subscription([8])
+        .fail_next("block_hash", timeout())
+        .fail_next("block_hash", timeout())
+        .fail_next("block_at", timeout());
+    let handler = MockHandler::new(EventFilter::all());
+    let seen = handler.events.clone();
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(source.clone())
+        .with_store(MemoryStore::new())
+        .retry_config(fast_retries())
+        .add_handler(handler)
+        .build()
+        .await
+        .unwrap();
+    let summary = indexer.run().await.unwrap();
+
+    assert_eq!(summary.stop_reason, StopReason::SubscriptionClosed);
+    assert_eq!(handled_blocks(&seen), (0..=8).collect::<Vec<_>>());
+    assert_eq!(source.calls("block_at"), 10);
+}
+
+#[tokio::test]
+async fn permanent_source_errors_stop_the_run() {
+    let source = chain().fail_next("block_at", IndexerError::BlockNotFound { block: 0 });
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(source)
+        .with_store(MemoryStore::new())
+        .retry_config(fast_retries())
+        .add_handler(MockHandler::new(EventFilter::all()))
+        .build()
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        indexer.run().await,
+        Err(IndexerError::BlockNotFound { block: 0 })
+    ));
+}
+
+#[tokio::test]
+async fn run_resumes_from_checkpoint_after_subscription_ends() {
+    let source = chain().with_subscription([6]).with_subscription([8]);
+    let handler = MockHandler::new(EventFilter::all());
+    let seen = handler.events.clone();
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(source.clone())
+        .with_store(MemoryStore::new())
+        .add_handler(handler)
+        .build()
+        .await
+        .unwrap();
+
+    let first = indexer.run().await.unwrap();
+    assert_eq!(first.stop_reason, StopReason::SubscriptionClosed);
+    assert_eq!(first.last_block, Some(6));
+    seen.lock().unwrap().clear();
+
+    let second = indexer.run().await.unwrap();
+    assert_eq!(second.stop_reason, StopReason::SubscriptionClosed);
+    assert_eq!(second.last_block, Some(8));
+    // The checkpointed block is handled again, then the run carries on.
+    assert_eq!(handled_blocks(&seen), vec![6, 7, 8]);
+    assert_eq!(source.calls("subscribe_finalized"), 2);
+}
+
+#[tokio::test]
+async fn events_decode_with_the_runtime_of_their_block() {
+    let source = chain().with_runtime_upgrade(4, 2, pallet_metadata_bytes::<TestEvent>("Upgraded"));
+    let handler = MockHandler::new(EventFilter::all());
+    let seen = handler.events.clone();
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(source)
+        .with_store(MemoryStore::new())
+        .end_at_block(5)
+        .add_handler(handler)
+        .build()
+        .await
+        .unwrap();
+    let summary = indexer.run().await.unwrap();
+
+    assert_eq!(summary.stop_reason, StopReason::EndBlock);
+    let events: Vec<_> = seen
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|e| !e.starts_with("block:"))
+        .cloned()
+        .collect();
+    assert_eq!(
+        events,
+        vec!["Test.A", "Test.A", "Test.A", "Upgraded.A", "Upgraded.A"]
+    );
+}
+
+/// Records the timestamp of every block it is given.
+#[derive(Default)]
+struct Timestamps(Arc<Mutex<Vec<Option<u64>>>>);
+
+#[async_trait]
+impl Handler<SubstrateConfig> for Timestamps {
+    async fn handle_block(
+        &self,
+        ctx: &Context<SubstrateConfig>,
+        _events: &[ChainEvent<SubstrateConfig>],
+    ) -> Result<(), IndexerError> {
+        self.0.lock().unwrap().push(ctx.timestamp());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn handlers_see_the_block_timestamp() {
+    let source = chain().with_timestamp(1, 12_000).with_timestamp(2, 24_000);
+    let handler = Timestamps::default();
+    let seen = handler.0.clone();
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(source)
+        .with_store(MemoryStore::new())
+        .end_at_block(3)
+        .add_handler(handler)
+        .build()
+        .await
+        .unwrap();
+    indexer.run().await.unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![None, Some(12_000), Some(24_000), None]
+    );
+}
+
+#[test]
+fn block_source_and_replay_are_exclusive() {
+    let err = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(chain())
+        .replay_from("in.rec")
+        .validate()
+        .unwrap_err();
+    assert!(matches!(err, IndexerError::InvalidConfig { field, .. } if field == "block_source"));
+}
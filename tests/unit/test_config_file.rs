@@ -0,0 +1,371 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::config::IndexerConfig;
+use flamewire_bittensor_indexer::handler::EventFilter;
+use flamewire_bittensor_indexer::retry::JitterMode;
+use flamewire_bittensor_indexer::{
+    FinalityMode, IndexerBuilder, IndexerError, NodeAuth, Preset, StartFrom, StorageConfig,
+};
+use std::time::Duration;
+use subxt::config::substrate::SubstrateConfig;
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+fn assert_full_fixture(cfg: &IndexerConfig) {
+    assert_eq!(cfg.node_url(), "wss://archive.example.org");
+    assert_eq!(cfg.database_url.as_deref(), Some("sqlite://indexer.db"));
+    assert_eq!(cfg.start_from, StartFrom::Block(1000));
+    assert_eq!(cfg.end_block, Some(2000));
+    assert_eq!(cfg.max_blocks_per_minute, Some(600));
+    assert_eq!(cfg.finality, FinalityMode::Best);
+    assert_eq!(cfg.checkpoint_interval, 10);
+    assert_eq!(cfg.rpc_timeout, Some(Duration::from_secs(10)));
+    assert_eq!(cfg.queue_depth, 32);
+    assert_eq!(
+        cfg.handlers,
+        Some(vec!["transfers".to_string(), "stakes".to_string()])
+    );
+
+    assert_eq!(cfg.retry.max_retries, 4);
+    assert_eq!(cfg.retry.initial_delay, Duration::from_millis(250));
+    assert_eq!(cfg.retry.max_delay, Duration::from_secs(5));
+    assert_eq!(cfg.retry.backoff_multiplier, 1.5);
+    assert_eq!(cfg.retry.jitter, JitterMode::Full);
+    assert_eq!(cfg.retry.max_elapsed, Some(Duration::from_secs(60)));
+
+    assert_eq!(cfg.circuit_breaker.threshold, 5);
+    assert_eq!(cfg.circuit_breaker.cooldown, Duration::from_secs(30));
+    assert_eq!(cfg.circuit_breaker.half_open_probes, 2);
+    assert_eq!(cfg.storage_retry.max_retries, 3);
+    assert_eq!(cfg.storage_circuit_breaker.threshold, 10);
+    assert_eq!(
+        cfg.storage_circuit_breaker.cooldown,
+        Duration::from_secs(15)
+    );
+}
+
+#[test]
+fn loads_toml_and_yaml_fixtures() {
+    assert_full_fixture(&IndexerConfig::from_file(fixture("indexer.toml")).unwrap());
+    assert_full_fixture(&IndexerConfig::from_file(fixture("indexer.yaml")).unwrap());
+}
+
+#[test]
+fn unknown_key_is_rejected_by_name() {
+    let err = IndexerConfig::from_file(fixture("unknown_key.toml"))
+        .err()
+        .unwrap();
+    match err {
+        IndexerError::ConfigFile { path, message } => {
+            assert!(path.ends_with("unknown_key.toml"));
+            assert!(message.contains("initial_dealy"), "{message}");
+        }
+        other => panic!("wrong error: {other}"),
+    }
+}
+
+#[test]
+fn unsupported_extension_and_missing_file_are_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let json = dir.path().join("indexer.json");
+    std::fs::write(&json, "{}").unwrap();
+    assert!(matches!(
+        IndexerConfig::from_file(&json),
+        Err(IndexerError::ConfigFile { .. })
+    ));
+    assert!(matches!(
+        IndexerConfig::from_file(dir.path().join("missing.toml")),
+        Err(IndexerError::ConfigFile { .. })
+    ));
+}
+
+#[test]
+fn file_values_are_validated() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("indexer.toml");
+    std::fs::write(&path, "node_url = \"https://node\"\n").unwrap();
+    match IndexerConfig::from_file(&path).err().unwrap() {
+        IndexerError::InvalidConfig { field, .. } => assert_eq!(field, "node_url"),
+        other => panic!("wrong error: {other}"),
+    }
+}
+
+#[test]
+fn start_from_is_written_as_a_name_or_block() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("indexer.toml");
+    for (line, expected) in [
+        ("start_from = \"genesis\"", StartFrom::Genesis),
+        ("start_from = \"checkpoint\"", StartFrom::Checkpoint),
+        ("start_from = \"latest\"", StartFrom::Latest),
+        ("start_from = 1234", StartFrom::Block(1234)),
+        ("start_block = 1234", StartFrom::Block(1234)),
+    ] {
+        std::fs::write(&path, format!("node_url = \"wss://node\"\n{line}\n")).unwrap();
+        let cfg = IndexerConfig::from_file(&path).unwrap();
+        assert_eq!(cfg.start_from, expected, "{line}");
+
+        let text = toml::to_string(&cfg).unwrap();
+        let reparsed: IndexerConfig = toml::from_str(&text).unwrap();
+        assert_eq!(reparsed.start_from, expected);
+    }
+
+    std::fs::write(&path, "node_url = \"wss://node\"\nstart_from = \"tip\"\n").unwrap();
+    assert!(matches!(
+        IndexerConfig::from_file(&path),
+        Err(IndexerError::ConfigFile { .. })
+    ));
+}
+
+#[test]
+fn preset_key_supplies_defaults_for_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let toml = dir.path().join("indexer.toml");
+    std::fs::write(
+        &toml,
+        "preset = \"live-tail\"\nnode_url = \"wss://node\"\nqueue_depth = 8\n\n[retry]\nmax_retries = 5\n",
+    )
+    .unwrap();
+    let yaml = dir.path().join("indexer.yaml");
+    std::fs::write(
+        &yaml,
+        "preset: live-tail\nnode_url: wss://node\nqueue_depth: 8\nretry:\n  max_retries: 5\n",
+    )
+    .unwrap();
+
+    for path in [toml, yaml] {
+        let cfg = IndexerConfig::from_file(&path).unwrap();
+        let mut expected = IndexerConfig::preset(Preset::LiveTail)
+            .node_url("wss://node")
+            .queue_depth(8)
+            .build()
+            .unwrap();
+        expected.retry.max_retries = 5;
+        assert_eq!(cfg, expected, "{}", path.display());
+    }
+}
+
+#[test]
+fn unknown_preset_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("indexer.toml");
+    std::fs::write(&path, "preset = \"turbo\"\nnode_url = \"wss://node\"\n").unwrap();
+    match IndexerConfig::from_file(&path).err().unwrap() {
+        IndexerError::ConfigFile { message, .. } => assert!(message.contains("turbo"), "{message}"),
+        other => panic!("wrong error: {other}"),
+    }
+
+    std::fs::write(
+        &path,
+        "preset = \"development\"\nnode_url = \"wss://node\"\nqueue_dpeth = 1\n",
+    )
+    .unwrap();
+    match IndexerConfig::from_file(&path).err().unwrap() {
+        IndexerError::ConfigFile { message, .. } => {
+            assert!(message.contains("queue_dpeth"), "{message}")
+        }
+        other => panic!("wrong error: {other}"),
+    }
+}
+
+#[test]
+fn node_urls_accept_a_list() {
+    let dir = tempfile::tempdir().unwrap();
+    let toml = dir.path().join("indexer.toml");
+    std::fs::write(
+        &toml,
+        "node_urls = [\"wss://a\", \"wss://b\", \"ws://c\"]\n",
+    )
+    .unwrap();
+    let yaml = dir.path().join("indexer.yaml");
+    std::fs::write(&yaml, "node_urls:\n  - wss://a\n  - wss://b\n  - ws://c\n").unwrap();
+    for path in [toml, yaml] {
+        let cfg = IndexerConfig::from_file(&path).unwrap();
+        assert_eq!(cfg.node_urls, ["wss://a", "wss://b", "ws://c"]);
+        assert_eq!(cfg.node_url(), "wss://a");
+    }
+}
+
+#[test]
+fn storage_table_selects_the_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    let checkpoint = dir.path().join("data").join("checkpoint.json");
+    let path = dir.path().join("indexer.toml");
+    for (table, expected) in [
+        (
+            format!(
+                "backend = \"json\"\npath = {:?}",
+                checkpoint.display().to_string()
+            ),
+            StorageConfig::json(&checkpoint),
+        ),
+        (
+            "backend = \"postgres\"\nurl = \"postgres://user@host/db\"".to_string(),
+            StorageConfig::parse("postgres://user@host/db").unwrap(),
+        ),
+        (
+            "backend = \"sqlite\"\nurl = \"sqlite://indexer.db\"".to_string(),
+            StorageConfig::parse("sqlite://indexer.db").unwrap(),
+        ),
+        ("backend = \"memory\"".to_string(), StorageConfig::Memory),
+    ] {
+        std::fs::write(
+            &path,
+            format!("node_url = \"wss://node\"\n\n[storage]\n{table}\n"),
+        )
+        .unwrap();
+        let cfg = IndexerConfig::from_file(&path).unwrap();
+        assert_eq!(cfg.storage, expected, "{table}");
+        assert_eq!(cfg.database_url, expected.database_url());
+
+        std::fs::write(&path, toml::to_string(&cfg).unwrap()).unwrap();
+        assert_eq!(IndexerConfig::from_file(&path).unwrap(), cfg);
+    }
+
+    std::fs::write(
+        &path,
+        "node_url = \"wss://node\"\n\n[storage]\nbackend = \"postgres\"\nurl = \"mysql://host/db\"\n",
+    )
+    .unwrap();
+    assert!(matches!(
+        IndexerConfig::from_file(&path),
+        Err(IndexerError::ConfigFile { .. })
+    ));
+}
+
+#[test]
+fn time_limits_are_read_from_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("indexer.toml");
+    std::fs::write(
+        &path,
+        "node_url = \"wss://node\"\nend_after = \"30m\"\nend_at_time = \"2030-01-01T03:00:00Z\"\n",
+    )
+    .unwrap();
+    let cfg = IndexerConfig::from_file(&path).unwrap();
+    assert_eq!(cfg.end_after, Some(Duration::from_secs(30 * 60)));
+    assert_eq!(
+        cfg.end_at_time,
+        Some(humantime::parse_rfc3339("2030-01-01T03:00:00Z").unwrap())
+    );
+
+    std::fs::write(&path, toml::to_string(&cfg).unwrap()).unwrap();
+    assert_eq!(IndexerConfig::from_file(&path).unwrap(), cfg);
+}
+
+#[test]
+fn event_allowlist_is_read_from_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("indexer.toml");
+    std::fs::write(
+        &path,
+        "node_url = \"wss://node\"\nevent_allowlist = [\"Balances.Transfer\", \"SubtensorModule.*\"]\n",
+    )
+    .unwrap();
+    let cfg = IndexerConfig::from_file(&path).unwrap();
+    let patterns: Vec<String> = cfg.event_allowlist.iter().map(|p| p.to_string()).collect();
+    assert_eq!(patterns, ["Balances.Transfer", "SubtensorModule.*"]);
+    assert!(cfg.allows_event("SubtensorModule", "StakeAdded"));
+    assert!(!cfg.allows_event("Balances", "Deposit"));
+
+    std::fs::write(&path, toml::to_string(&cfg).unwrap()).unwrap();
+    assert_eq!(IndexerConfig::from_file(&path).unwrap(), cfg);
+
+    std::fs::write(
+        &path,
+        "node_url = \"wss://node\"\nevent_allowlist = [\"Balances\"]\n",
+    )
+    .unwrap();
+    match IndexerConfig::from_file(&path) {
+        Err(IndexerError::ConfigFile { message, .. }) => {
+            assert!(message.contains("invalid pattern `Balances`"), "{message}")
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn database_url_still_selects_the_backend() {
+    let cfg = IndexerConfig::from_file(fixture("indexer.toml")).unwrap();
+    assert_eq!(
+        cfg.storage,
+        StorageConfig::parse("sqlite://indexer.db").unwrap()
+    );
+}
+
+#[test]
+fn config_file_round_trips_into_builder() {
+    let cfg = IndexerConfig::from_file(fixture("indexer.toml")).unwrap();
+    IndexerBuilder::<SubstrateConfig>::from_config(cfg)
+        .unwrap()
+        .register_handler("transfers", MockHandler::new(EventFilter::all()))
+        .register_handler("stakes", MockHandler::new(EventFilter::all()))
+        .validate()
+        .unwrap();
+}
+
+#[test]
+fn unknown_handler_name_fails_validation() {
+    let cfg = IndexerConfig::from_file(fixture("unknown_handler.toml")).unwrap();
+    let err = IndexerBuilder::<SubstrateConfig>::from_config(cfg)
+        .unwrap()
+        .register_handler("transfers", MockHandler::new(EventFilter::all()))
+        .validate()
+        .err()
+        .unwrap();
+    match err {
+        IndexerError::InvalidConfig { field, message } => {
+            assert_eq!(field, "handlers");
+            assert!(message.contains("governance"));
+        }
+        other => panic!("wrong error: {other}"),
+    }
+}
+
+#[test]
+fn node_auth_is_read_from_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("indexer.toml");
+    std::fs::write(
+        &path,
+        "node_url = \"wss://node\"\n[node_auth]\ntype = \"bearer\"\ntoken = \"t0k3n\"\n",
+    )
+    .unwrap();
+    let cfg = IndexerConfig::from_file(&path).unwrap();
+    assert_eq!(
+        cfg.node_auth,
+        Some(NodeAuth::Bearer {
+            token: "t0k3n".into()
+        })
+    );
+
+    std::fs::write(
+        &path,
+        "node_url = \"wss://node\"\n[node_auth]\ntype = \"basic\"\nusername = \"user\"\npassword = \"pass\"\n",
+    )
+    .unwrap();
+    let cfg = IndexerConfig::from_file(&path).unwrap();
+    assert_eq!(
+        cfg.node_auth.map(|auth| auth.header_value()).as_deref(),
+        Some("Basic dXNlcjpwYXNz")
+    );
+}
@@ -0,0 +1,206 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::csv_export::{
+    CsvExportHandler, CsvSchema, Rotation, ENVELOPE_HEADERS,
+};
+use flamewire_bittensor_indexer::handler::EventFilter;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use subxt::config::substrate::SubstrateConfig;
+
+fn transfers_schema() -> CsvSchema<SubstrateConfig> {
+    CsvSchema::typed(
+        "transfers",
+        &["block", "from", "to", "amount"],
+        |t: Transfer, ctx| {
+            vec![
+                ctx.block_number.to_string(),
+                t.from.to_string(),
+                t.to.to_string(),
+                t.amount.to_string(),
+            ]
+        },
+    )
+}
+
+fn read(dir: &Path, name: &str) -> String {
+    fs::read_to_string(dir.join(name)).unwrap()
+}
+
+/// Names of the CSV files in `dir`, leaving out the hidden offset markers.
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    names.sort();
+    names
+}
+
+#[tokio::test]
+async fn test_typed_rows_rotate_by_block_range() {
+    let dir = tempfile::tempdir().unwrap();
+    let handler = CsvExportHandler::new(dir.path(), transfers_schema())
+        .unwrap()
+        .rotate(Rotation::Blocks(100_000));
+
+    write_block(
+        &handler,
+        1_000_000,
+        vec![transfer(5), Balances::Deposit { who: 3, amount: 1 }],
+    )
+    .await;
+    write_block(&handler, 1_099_999, vec![transfer(6), transfer(7)]).await;
+    write_block(&handler, 1_100_000, vec![transfer(8)]).await;
+    // Blocks without matching events create no file.
+    write_block(
+        &handler,
+        1_250_000,
+        vec![Balances::Deposit { who: 3, amount: 1 }],
+    )
+    .await;
+
+    assert_eq!(
+        file_names(dir.path()),
+        vec![
+            "transfers_0001000000_0001100000.csv",
+            "transfers_0001100000_0001200000.csv",
+        ]
+    );
+    assert_eq!(
+        read(dir.path(), "transfers_0001000000_0001100000.csv"),
+        "block,from,to,amount\n1000000,1,2,5\n1099999,1,2,6\n1099999,1,2,7\n"
+    );
+    assert_eq!(
+        read(dir.path(), "transfers_0001100000_0001200000.csv"),
+        "block,from,to,amount\n1100000,1,2,8\n"
+    );
+}
+
+#[tokio::test]
+async fn test_envelope_rows_rotate_by_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let handler = CsvExportHandler::new(
+        dir.path(),
+        CsvSchema::envelope("balances", EventFilter::pallet("Balances")),
+    )
+    .unwrap()
+    .rotate(Rotation::Bytes(200));
+
+    write_block(
+        &handler,
+        10,
+        vec![transfer(5), Balances::Deposit { who: 3, amount: 1 }],
+    )
+    .await;
+    write_block(&handler, 11, vec![transfer(6)]).await;
+    // The first file passed 200 bytes with block 11.
+    write_block(&handler, 12, vec![transfer(7)]).await;
+
+    assert_eq!(
+        file_names(dir.path()),
+        vec!["balances_0000000010.csv", "balances_0000000012.csv"]
+    );
+    let mut reader = csv::Reader::from_path(dir.path().join("balances_0000000010.csv")).unwrap();
+    assert_eq!(reader.headers().unwrap(), ENVELOPE_HEADERS.as_slice());
+    let rows: Vec<(Vec<String>, Value)> = reader
+        .records()
+        .map(|record| {
+            let record = record.unwrap();
+            let fields = serde_json::from_str(&record[4]).unwrap();
+            let columns = record.iter().take(4).map(String::from).collect();
+            (columns, fields)
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            (
+                vec![
+                    "10".into(),
+                    "0".into(),
+                    "Balances".into(),
+                    "Transfer".into()
+                ],
+                json!({"from": 1, "to": 2, "amount": 5}),
+            ),
+            (
+                vec!["10".into(), "1".into(), "Balances".into(), "Deposit".into()],
+                json!({"who": 3, "amount": 1}),
+            ),
+            (
+                vec![
+                    "11".into(),
+                    "0".into(),
+                    "Balances".into(),
+                    "Transfer".into()
+                ],
+                json!({"from": 1, "to": 2, "amount": 6}),
+            ),
+        ]
+    );
+    assert_eq!(
+        read(dir.path(), "balances_0000000012.csv").lines().count(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_partial_row_is_dropped_on_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transfers_0000000000_0000100000.csv");
+    fs::write(&path, "block,from,to,amount\n5,1,2,5\n6,1,2").unwrap();
+
+    let handler = CsvExportHandler::new(dir.path(), transfers_schema()).unwrap();
+    write_block(&handler, 7, vec![transfer(9)]).await;
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "block,from,to,amount\n5,1,2,5\n7,1,2,9\n"
+    );
+}
+
+#[tokio::test]
+async fn test_partial_block_is_dropped_on_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transfers_0000000000_0000100000.csv");
+    let handler = CsvExportHandler::new(dir.path(), transfers_schema()).unwrap();
+    write_block(&handler, 5, vec![transfer(5)]).await;
+    drop(handler);
+    // A crash in the middle of block 6, after its first row.
+    let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(b"6,1,2,6\n6,1,2").unwrap();
+    drop(file);
+
+    let handler = CsvExportHandler::new(dir.path(), transfers_schema()).unwrap();
+    write_block(&handler, 6, vec![transfer(6), transfer(7)]).await;
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "block,from,to,amount\n5,1,2,5\n6,1,2,6\n6,1,2,7\n"
+    );
+    assert_eq!(
+        file_names(dir.path()),
+        vec!["transfers_0000000000_0000100000.csv"]
+    );
+}
@@ -0,0 +1,78 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::{
+    EventFilter, IndexerBuilder, IndexerError, MemoryStore, RetryConfig, WebSocketUrl,
+};
+use subxt::backend::rpc::RpcClient;
+use subxt::config::substrate::SubstrateConfig;
+
+#[tokio::test]
+async fn build_with_client_does_not_connect() {
+    let rpc = NoNode::default();
+    let indexer = IndexerBuilder::<SubstrateConfig>::new()
+        // Nothing listens here; the build must not dial it.
+        .connect(WebSocketUrl::parse("ws://127.0.0.1:1").unwrap())
+        .with_store(MemoryStore::new())
+        .add_handler(MockHandler::new(EventFilter::all()))
+        .build_with_client(offline_client::<TestEvent>(rpc.clone()))
+        .await;
+
+    assert!(indexer.is_ok());
+    assert!(rpc.calls.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn build_with_client_still_validates_the_config() {
+    let err = IndexerBuilder::<SubstrateConfig>::new()
+        .with_store(MemoryStore::new())
+        .build_with_client(offline_client::<TestEvent>(NoNode::default()))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, IndexerError::InvalidConfig { field, .. } if field == "node_url"));
+}
+
+#[test]
+fn rpc_client_makes_the_node_url_optional() {
+    let builder =
+        IndexerBuilder::<SubstrateConfig>::new().rpc_client(RpcClient::new(NoNode::default()));
+    assert!(builder.validate().is_ok());
+}
+
+#[tokio::test]
+async fn runs_use_the_given_rpc_client() {
+    let rpc = NoNode::default();
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .rpc_client(RpcClient::new(rpc.clone()))
+        .with_store(MemoryStore::new())
+        .retry_config(RetryConfig {
+            max_retries: 1,
+            ..Default::default()
+        })
+        .build_with_client(offline_client::<TestEvent>(rpc.clone()))
+        .await
+        .unwrap();
+
+    assert!(indexer.run().await.is_err());
+    let calls = rpc.calls.lock().unwrap();
+    assert_eq!(
+        calls.first().map(String::as_str),
+        Some("chain_getFinalizedHead")
+    );
+}
@@ -0,0 +1,261 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Arbitrary and mutated event bytes through decoding and dispatch: neither may
+//! panic, only fail with typed errors. Inputs that once broke decoding belong in
+//! `tests/fixtures/decode_corpus` as hex.
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::{
+    ChainEvent, EventFilter, IndexerBuilder, IndexerConfig, IndexerError, MemoryStore,
+    MockBlockSource,
+};
+use once_cell::sync::Lazy;
+use parity_scale_codec::{Compact, Encode};
+use proptest::prelude::*;
+use proptest::sample::Index;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::{Events, Phase};
+use subxt::utils::H256;
+use tokio::runtime::Runtime;
+
+static RT: Lazy<Runtime> = Lazy::new(|| Runtime::new().unwrap());
+
+/// Decode `bytes` as a block's events and read the fields of every event that
+/// decodes, as handlers would. Returns the number decoded.
+fn decode_all(bytes: Vec<u8>) -> usize {
+    let events = Events::<SubstrateConfig>::decode_from(bytes, test_metadata::<TestEvent>());
+    let mut decoded = 0;
+    for event in ChainEvent::from_events(&events) {
+        let Ok(event) = event else { break };
+        let _ = event.field_values();
+        let _ = event.field_at(0);
+        #[cfg(feature = "json-storage")]
+        let _ = event.to_json_with_meta();
+        decoded += 1;
+    }
+    decoded
+}
+
+struct Outcome {
+    result: Result<(), IndexerError>,
+    events: Vec<String>,
+    errors: Vec<String>,
+}
+
+/// Run an indexer over block 1 with events `bytes` and a handler of all events.
+fn run(bytes: Vec<u8>, skip_undecodable: bool) -> Outcome {
+    let source =
+        MockBlockSource::<SubstrateConfig>::new(pallet_metadata_bytes::<TestEvent>("Test"))
+            .with_block(bytes)
+            .finalized(1);
+    let handler = MockHandler::new(EventFilter::all());
+    let (events, errors) = (handler.events.clone(), handler.errors.clone());
+    let result = RT.block_on(async {
+        let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+            .block_source(source)
+            .with_store(MemoryStore::new())
+            .start_from_block(1)
+            .end_at_block(1)
+            .skip_undecodable_events(skip_undecodable)
+            .add_handler(handler)
+            .build()
+            .await
+            .unwrap();
+        indexer.run().await.map(|_| ())
+    });
+    let events = events.lock().unwrap().clone();
+    let errors = errors.lock().unwrap().clone();
+    Outcome {
+        result,
+        events,
+        errors,
+    }
+}
+
+fn phase() -> impl Strategy<Value = Phase> {
+    prop_oneof![
+        Just(Phase::Initialization),
+        Just(Phase::Finalization),
+        any::<u32>().prop_map(Phase::ApplyExtrinsic),
+    ]
+}
+
+fn record() -> impl Strategy<Value = EventRecord<TestEvent>> {
+    let event = prop_oneof![
+        any::<u8>().prop_map(TestEvent::A),
+        any::<bool>().prop_map(TestEvent::B),
+    ];
+    let topics = prop::collection::vec(any::<[u8; 32]>().prop_map(H256), 0..3);
+    (phase(), event, topics)
+        .prop_map(|(phase, event, topics)| EventRecord::new(phase, event).with_topics(topics))
+}
+
+/// The events of a valid block, with some bytes overwritten and possibly cut short.
+fn mutated_block() -> impl Strategy<Value = Vec<u8>> {
+    (
+        prop::collection::vec(record(), 0..8),
+        prop::collection::vec((any::<Index>(), any::<u8>()), 0..4),
+        prop::option::of(any::<Index>()),
+    )
+        .prop_map(|(records, writes, cut)| {
+            let mut bytes = event_bytes(records);
+            for (at, byte) in writes {
+                let at = at.index(bytes.len());
+                bytes[at] = byte;
+            }
+            if let Some(cut) = cut {
+                bytes.truncate(cut.index(bytes.len() + 1));
+            }
+            bytes
+        })
+}
+
+fn corpus() -> Vec<(String, Vec<u8>)> {
+    let dir = format!(
+        "{}/tests/fixtures/decode_corpus",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let mut corpus: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            let name = path.file_stem().unwrap().to_str().unwrap();
+            let bytes = fixture_hex("decode_corpus", name);
+            (path.display().to_string(), bytes)
+        })
+        .collect();
+    corpus.sort();
+    corpus
+}
+
+#[test]
+fn prop_valid_blocks_decode_fully() {
+    proptest!(|(records in prop::collection::vec(record(), 0..16))| {
+        let count = records.len();
+        prop_assert_eq!(decode_all(event_bytes(records)), count);
+    });
+}
+
+#[test]
+fn prop_arbitrary_bytes_never_panic() {
+    proptest!(|(bytes in prop::collection::vec(any::<u8>(), 0..256))| {
+        decode_all(bytes);
+    });
+}
+
+#[test]
+fn prop_mutated_blocks_never_panic() {
+    proptest!(|(bytes in mutated_block())| {
+        decode_all(bytes);
+    });
+}
+
+#[test]
+fn prop_dispatch_fails_only_with_decoding_errors() {
+    proptest!(ProptestConfig::with_cases(32), |(bytes in mutated_block())| {
+        let strict = run(bytes.clone(), false);
+        prop_assert!(
+            matches!(strict.result, Ok(()) | Err(IndexerError::EventDecodingFailed { block: 1, .. })),
+            "{:?}",
+            strict.result
+        );
+        let tolerant = run(bytes, true);
+        prop_assert!(tolerant.result.is_ok(), "{:?}", tolerant.result);
+    });
+}
+
+#[test]
+fn corpus_never_panics() {
+    for (name, bytes) in corpus() {
+        decode_all(bytes.clone());
+        let outcome = run(bytes, true);
+        assert!(outcome.result.is_ok(), "{name}: {:?}", outcome.result);
+    }
+}
+
+/// Three events whose third is cut short.
+fn partial_block() -> Vec<u8> {
+    let mut bytes = Compact(3u32).encode();
+    EventRecord::new(Phase::Initialization, TestEvent::A(1)).encode_to(&mut bytes);
+    EventRecord::new(Phase::ApplyExtrinsic(0), TestEvent::B(true)).encode_to(&mut bytes);
+    EventRecord::new(Phase::ApplyExtrinsic(1), TestEvent::A(2)).encode_to(&mut bytes);
+    bytes.truncate(bytes.len() - 2);
+    bytes
+}
+
+#[test]
+fn undecodable_events_fail_the_block_by_default() {
+    let outcome = run(partial_block(), false);
+
+    assert!(matches!(
+        outcome.result,
+        Err(IndexerError::EventDecodingFailed { block: 1, .. })
+    ));
+    assert!(outcome.events.is_empty());
+}
+
+#[test]
+fn skip_undecodable_events_dispatches_the_rest() {
+    let outcome = run(partial_block(), true);
+
+    outcome.result.unwrap();
+    assert_eq!(outcome.events, vec!["block:1", "Test.A", "Test.B"]);
+    assert_eq!(outcome.errors.len(), 1);
+    assert!(
+        outcome.errors[0].starts_with("Failed to decode event <unknown>.<unknown> in block 1"),
+        "{}",
+        outcome.errors[0]
+    );
+}
+
+#[test]
+fn skipped_event_counts_once_against_the_error_budget() {
+    let source =
+        MockBlockSource::<SubstrateConfig>::new(pallet_metadata_bytes::<TestEvent>("Test"))
+            .with_block(partial_block())
+            .finalized(1);
+    let handlers: Vec<_> = (0..3)
+        .map(|_| MockHandler::new(EventFilter::all()))
+        .collect();
+    let errors: Vec<_> = handlers.iter().map(|h| h.errors.clone()).collect();
+    let result = RT.block_on(async {
+        let mut builder = IndexerBuilder::<SubstrateConfig>::new()
+            .block_source(source)
+            .with_store(MemoryStore::new())
+            .start_from_block(1)
+            .end_at_block(1)
+            .skip_undecodable_events(true)
+            .handler_error_budget(1);
+        for handler in handlers {
+            builder = builder.add_handler(handler);
+        }
+        let mut indexer = builder.build().await.unwrap();
+        indexer.run().await.map(|_| ())
+    });
+
+    result.unwrap();
+    for errors in errors {
+        assert_eq!(errors.lock().unwrap().len(), 1);
+    }
+}
+
+#[test]
+fn skip_undecodable_events_defaults_off() {
+    assert!(!IndexerConfig::default().skip_undecodable_events);
+}
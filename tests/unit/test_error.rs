@@ -22,6 +22,11 @@ fn construct_errors() {
     let e = IndexerError::BlockNotFound { block: 1 };
     assert_eq!(format!("{e}"), "Block 1 not found");
 
+    let e = IndexerError::BlockHashNotFound {
+        hash: "0x0101".into(),
+    };
+    assert_eq!(format!("{e}"), "Block 0x0101 not found");
+
     let e = IndexerError::ConnectionFailed {
         url: "wss://node".into(),
         source: Box::new(SubxtError::Other("conn".into())),
@@ -34,7 +39,9 @@ fn construct_errors() {
     let e = IndexerError::HandlerFailed {
         handler: "h".into(),
         block: 1,
+        event_index: None,
         source: Box::new(std::io::Error::other("oops")),
+        retryable: false,
     };
     assert!(format!("{e}").contains("Handler h failed"));
 
@@ -57,4 +64,35 @@ fn construct_errors() {
         source: Box::new(SubxtError::Other("decode".into())),
     };
     assert!(format!("{e}").contains("Failed to decode event"));
+
+    let e = IndexerError::StorageBackendUnavailable {
+        scheme: "postgres".into(),
+        feature: "postgres",
+    };
+    let msg = format!("{e}");
+    assert!(msg.contains("requires the `postgres` cargo feature"));
+    assert!(msg.contains("enabled backends"));
+
+    let e = IndexerError::UnsupportedStorageScheme {
+        scheme: "https".into(),
+    };
+    assert!(format!("{e}").contains("not a database"));
+}
+
+#[test]
+fn handler_failure_names_the_event_index() {
+    let e = IndexerError::handler_failed("h", 7, std::io::Error::other("oops"));
+    assert_eq!(e.to_string(), "Handler h failed at block 7: oops");
+
+    let e = e.with_event_index(3);
+    assert_eq!(e.to_string(), "Handler h failed at block 7, event 3: oops");
+    // An index set by the handler is kept.
+    let e = e.with_event_index(5);
+    assert!(matches!(
+        e,
+        IndexerError::HandlerFailed {
+            event_index: Some(3),
+            ..
+        }
+    ));
 }
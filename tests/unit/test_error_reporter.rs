@@ -0,0 +1,206 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use async_trait::async_trait;
+use flamewire_bittensor_indexer::handler::{Context, Handler};
+use flamewire_bittensor_indexer::handler_group::HandlerGroup;
+use flamewire_bittensor_indexer::reporter::{
+    report_breaker_opens, ErrorContext, ErrorReporter, GiveUpReporter, ReportKind,
+    ThrottledReporter,
+};
+use flamewire_bittensor_indexer::retry::{
+    retry_with_backoff_observed, CircuitBreaker, RetryConfig,
+};
+use flamewire_bittensor_indexer::{ChainEvent, IndexerError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use subxt::config::substrate::SubstrateConfig;
+
+/// Reporter keeping every report as `(error message, context)`.
+#[derive(Default)]
+struct RecordingReporter {
+    reports: Mutex<Vec<(String, Option<ErrorContext>)>>,
+}
+
+impl RecordingReporter {
+    fn reports(&self) -> Vec<(String, Option<ErrorContext>)> {
+        self.reports.lock().unwrap().clone()
+    }
+
+    fn contexts(&self) -> Vec<ErrorContext> {
+        self.reports()
+            .into_iter()
+            .map(|(_, ctx)| ctx.unwrap())
+            .collect()
+    }
+}
+
+impl ErrorReporter for RecordingReporter {
+    fn report(&self, error: &IndexerError, ctx: Option<&ErrorContext>) {
+        self.reports
+            .lock()
+            .unwrap()
+            .push((error.to_string(), ctx.cloned()));
+    }
+}
+
+struct FailingHandler(&'static str);
+
+#[async_trait]
+impl Handler<SubstrateConfig> for FailingHandler {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<SubstrateConfig>,
+        _events: &[ChainEvent<SubstrateConfig>],
+    ) -> Result<(), IndexerError> {
+        Err(IndexerError::handler_failed(
+            self.0,
+            ctx.block_number,
+            "boom",
+        ))
+    }
+}
+
+fn context(block: u64, reporter: &Arc<RecordingReporter>) -> Context<SubstrateConfig> {
+    Context::builder()
+        .block_number(block)
+        .error_reporter(reporter.clone())
+        .build()
+}
+
+#[test]
+fn test_throttles_reports_per_handler() {
+    let recorder = Arc::new(RecordingReporter::default());
+    let reporter = ThrottledReporter::new(recorder.clone(), 10);
+    let error = IndexerError::handler_failed("a", 0, "boom");
+
+    for block in [100, 105, 109, 110, 111] {
+        reporter.report(&error, Some(&ErrorContext::handler("a", block)));
+    }
+    reporter.report(&error, Some(&ErrorContext::handler("b", 105)));
+    // Going back, as after a restart, reports again.
+    reporter.report(&error, Some(&ErrorContext::handler("a", 50)));
+    // Reports without a block are never throttled.
+    reporter.report(&error, Some(&ErrorContext::breaker_open("rpc")));
+    reporter.report(&error, Some(&ErrorContext::breaker_open("rpc")));
+    reporter.report(&error, None);
+
+    let reported: Vec<_> = recorder
+        .reports()
+        .into_iter()
+        .map(|(_, ctx)| ctx.map(|ctx| (ctx.kind, ctx.block, ctx.handler)))
+        .collect();
+    assert_eq!(
+        reported,
+        vec![
+            Some((ReportKind::Handler, Some(100), Some("a".into()))),
+            Some((ReportKind::Handler, Some(110), Some("a".into()))),
+            Some((ReportKind::Handler, Some(105), Some("b".into()))),
+            Some((ReportKind::Handler, Some(50), Some("a".into()))),
+            Some((ReportKind::BreakerOpen, None, None)),
+            Some((ReportKind::BreakerOpen, None, None)),
+            None,
+        ]
+    );
+    assert_eq!(reporter.suppressed(), 3);
+}
+
+#[tokio::test]
+async fn test_group_reports_absorbed_errors() {
+    let reporter = Arc::new(RecordingReporter::default());
+    let group = HandlerGroup::new()
+        .add(FailingHandler("first"))
+        .add(FailingHandler("second"));
+
+    group
+        .handle_block(&context(7, &reporter), &[])
+        .await
+        .unwrap();
+
+    let reports = reporter.reports();
+    assert_eq!(reports.len(), 2);
+    assert!(reports[0].0.contains("boom"));
+    assert_eq!(
+        reporter.contexts(),
+        vec![
+            ErrorContext::handler("first", 7),
+            ErrorContext::handler("second", 7)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_strict_group_leaves_reporting_to_indexer() {
+    let reporter = Arc::new(RecordingReporter::default());
+    let group = HandlerGroup::new().add(FailingHandler("first")).strict();
+
+    assert!(group
+        .handle_block(&context(7, &reporter), &[])
+        .await
+        .is_err());
+    assert!(reporter.reports().is_empty());
+}
+
+#[tokio::test]
+async fn test_reports_retry_give_ups() {
+    let reporter = Arc::new(RecordingReporter::default());
+    let observer = GiveUpReporter::new(reporter.clone(), "storage");
+    let config = RetryConfig {
+        max_retries: 3,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(1),
+        ..Default::default()
+    };
+
+    let res: Result<(), _> = retry_with_backoff_observed(
+        || async {
+            Err(IndexerError::Timeout {
+                operation: "store_checkpoint".into(),
+                duration: Duration::from_secs(1),
+            })
+        },
+        &config,
+        &CircuitBreaker::new(100, Duration::from_secs(1)),
+        &observer,
+    )
+    .await;
+
+    assert!(res.is_err());
+    assert_eq!(
+        reporter.contexts(),
+        vec![ErrorContext::retry_give_up("storage", 3)]
+    );
+}
+
+#[test]
+fn test_reports_breaker_openings() {
+    let reporter = Arc::new(RecordingReporter::default());
+    let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+    report_breaker_opens(&breaker, reporter.clone());
+
+    breaker.record_failure();
+    assert!(reporter.reports().is_empty());
+    breaker.record_failure();
+
+    let reports = reporter.reports();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].0.contains("rpc circuit breaker is open"));
+    assert_eq!(reports[0].1, Some(ErrorContext::breaker_open("rpc")));
+}
@@ -18,9 +18,15 @@
 #[path = "../common/mod.rs"]
 mod common;
 use common::*;
-use flamewire_bittensor_indexer::retry::{retry_with_backoff, CircuitBreaker, RetryConfig};
+use flamewire_bittensor_indexer::retry::{
+    is_retryable_error, retry_with_backoff, retry_with_backoff_observed, with_timeout_and_backoff,
+    BreakerDomain, BreakerState, CircuitBreaker, CircuitBreakerConfig, RetryConfig, RetryCounters,
+    RetryObserver,
+};
+use flamewire_bittensor_indexer::validated_types::WebSocketUrl;
 use flamewire_bittensor_indexer::{
-    ChainEvent, CheckpointStore, Context, EventFilter, Handler, IndexerConfig, IndexerError,
+    ChainEvent, CheckpointStore, Context, EventFilter, Handler, IndexerBuilder, IndexerConfig,
+    IndexerError, RetryingStore,
 };
 use parity_scale_codec::Encode;
 use std::sync::{
@@ -28,9 +34,11 @@ use std::sync::{
     Arc,
 };
 use std::time::Duration;
+use subxt::backend::rpc::RawValue;
 use subxt::config::substrate::SubstrateConfig;
+use subxt::error::RpcError;
 use subxt::events::Phase;
-use subxt::utils::H256;
+use subxt::ext::subxt_rpcs::{Error as ClientError, UserError};
 use subxt::Error as SubxtError;
 
 #[tokio::test]
@@ -40,6 +48,7 @@ async fn retry_recovers_from_connection_drop() {
         initial_delay: Duration::from_millis(1),
         max_delay: Duration::from_millis(2),
         backoff_multiplier: 1.0,
+        ..Default::default()
     };
     let cb = CircuitBreaker::new(3, Duration::from_secs(60));
     let attempts = Arc::new(AtomicUsize::new(0));
@@ -77,6 +86,7 @@ async fn circuit_breaker_opens_after_failures() {
         initial_delay: Duration::from_millis(1),
         max_delay: Duration::from_millis(1),
         backoff_multiplier: 1.0,
+        ..Default::default()
     };
     let cb = CircuitBreaker::new(2, Duration::from_secs(60));
 
@@ -94,12 +104,9 @@ async fn circuit_breaker_opens_after_failures() {
 
     let res = retry_with_backoff(failing_op, &cfg, &cb).await;
     match res {
-        Err(IndexerError::Subxt(ref err)) => {
-            if let SubxtError::Other(msg) = err.as_ref() {
-                assert_eq!(msg, "circuit open");
-            } else {
-                panic!("wrong error: {res:?}");
-            }
+        Err(ref err @ IndexerError::CircuitOpen { domain }) => {
+            assert_eq!(domain, BreakerDomain::Rpc);
+            assert!(!is_retryable_error(err));
         }
         _ => panic!("wrong error: {res:?}"),
     }
@@ -139,6 +146,7 @@ async fn retry_gives_up_after_max_retries() {
         initial_delay: Duration::from_millis(1),
         max_delay: Duration::from_millis(2),
         backoff_multiplier: 1.0,
+        ..Default::default()
     };
     let cb = CircuitBreaker::new(3, Duration::from_secs(60));
     let attempts = Arc::new(AtomicUsize::new(0));
@@ -150,11 +158,10 @@ async fn retry_gives_up_after_max_retries() {
             async move {
                 cnt.fetch_add(1, Ordering::SeqCst);
                 tokio::time::sleep(Duration::from_millis(5)).await;
-                Err(IndexerError::HandlerFailed {
-                    handler: "h".into(),
-                    block: 0,
-                    source: Box::new(std::io::Error::other("slow")),
-                })
+                Err(IndexerError::Io(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "slow",
+                )))
             }
         },
         &cfg,
@@ -171,6 +178,19 @@ async fn invalid_node_url_configuration_error() {
     assert!(res.is_err());
 }
 
+#[tokio::test]
+async fn connection_failure_names_the_node_url() {
+    let url = WebSocketUrl::parse("ws://127.0.0.1:1").unwrap();
+    let res = IndexerBuilder::<SubstrateConfig>::new()
+        .connect(url)
+        .build()
+        .await;
+    match res.err().unwrap() {
+        IndexerError::ConnectionFailed { url, .. } => assert!(url.contains("127.0.0.1:1"), "{url}"),
+        other => panic!("wrong error: {other}"),
+    }
+}
+
 #[cfg(feature = "postgres")]
 #[tokio::test]
 async fn wrong_database_credentials() {
@@ -190,7 +210,9 @@ async fn conflicting_handlers_continue_processing() {
     let mut handler_fail = MockHandler::new(EventFilter::all());
     handler_fail.fail = true;
     let handler_fail = Arc::new(handler_fail);
-    let ctx = Context::<SubstrateConfig>::new(1, H256::zero());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
 
     let chain_events: Vec<ChainEvent<SubstrateConfig>> = evs
         .iter()
@@ -215,3 +237,892 @@ async fn conflicting_handlers_continue_processing() {
     assert!(!handler_fail.errors.lock().unwrap().is_empty());
     assert!(!handler_ok.events.lock().unwrap().is_empty());
 }
+
+struct FlakyStore {
+    inner: MockCheckpointStore,
+    failures_left: AtomicUsize,
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl CheckpointStore for FlakyStore {
+    async fn load_checkpoint(&self) -> Result<Option<u64>, IndexerError> {
+        self.inner.load_checkpoint().await
+    }
+
+    async fn store_checkpoint(&self, block: u64) -> Result<(), IndexerError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let left = self.failures_left.load(Ordering::SeqCst);
+        if left > 0 {
+            self.failures_left.store(left - 1, Ordering::SeqCst);
+            return Err(IndexerError::CheckpointError {
+                operation: "store_checkpoint".into(),
+                backend: "flaky".into(),
+                source: Box::new(std::io::Error::from(std::io::ErrorKind::ConnectionReset)),
+            });
+        }
+        self.inner.store_checkpoint(block).await
+    }
+}
+
+#[tokio::test]
+async fn retrying_store_hides_transient_failures() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let flaky = FlakyStore {
+        inner: MockCheckpointStore::new(),
+        failures_left: AtomicUsize::new(2),
+        calls: calls.clone(),
+    };
+    let cfg = RetryConfig {
+        max_retries: 5,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(2),
+        backoff_multiplier: 1.0,
+        ..Default::default()
+    };
+    let store = RetryingStore::new(flaky, cfg, CircuitBreaker::new(3, Duration::from_secs(60)));
+
+    store.store_checkpoint(10).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(store.load_checkpoint().await.unwrap(), Some(10));
+    assert!(!store.circuit_breaker().is_open());
+}
+
+fn fast_retry(max_retries: usize) -> RetryConfig {
+    RetryConfig {
+        max_retries,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(2),
+        backoff_multiplier: 1.0,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn storage_outage_does_not_block_rpc() {
+    let flaky = FlakyStore {
+        inner: MockCheckpointStore::new(),
+        failures_left: AtomicUsize::new(usize::MAX),
+        calls: Arc::new(AtomicUsize::new(0)),
+    };
+    let store = RetryingStore::new(
+        flaky,
+        fast_retry(1),
+        CircuitBreaker::new(1, Duration::from_secs(60)),
+    );
+    let rpc_breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+
+    assert!(store.store_checkpoint(1).await.is_err());
+    assert!(store.circuit_breaker().is_open());
+    assert!(matches!(
+        store.store_checkpoint(2).await,
+        Err(IndexerError::CircuitOpen {
+            domain: BreakerDomain::Storage
+        })
+    ));
+
+    let res = retry_with_backoff(|| async { Ok(7) }, &fast_retry(3), &rpc_breaker).await;
+    assert_eq!(res.unwrap(), 7);
+    assert!(!rpc_breaker.is_open());
+}
+
+#[tokio::test]
+async fn rpc_outage_does_not_block_storage() {
+    let rpc_breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+    let store = RetryingStore::new(
+        MockCheckpointStore::new(),
+        fast_retry(3),
+        CircuitBreaker::new(1, Duration::from_secs(60)),
+    );
+
+    let failing_rpc = || async {
+        Err::<(), _>(IndexerError::ConnectionFailed {
+            url: "ws://node".into(),
+            source: Box::new(SubxtError::Other("timeout".into())),
+        })
+    };
+    assert!(
+        retry_with_backoff(failing_rpc, &fast_retry(1), &rpc_breaker)
+            .await
+            .is_err()
+    );
+    rpc_breaker.record_failure();
+    assert!(rpc_breaker.is_open());
+
+    store.store_checkpoint(5).await.unwrap();
+    assert_eq!(store.load_checkpoint().await.unwrap(), Some(5));
+    assert!(!store.circuit_breaker().is_open());
+    assert_eq!(store.circuit_breaker().domain(), "storage");
+    assert_eq!(rpc_breaker.domain(), "rpc");
+}
+
+fn half_open_breaker(probes: usize, cooldown_multiplier: f32) -> CircuitBreaker {
+    CircuitBreaker::from_config(&CircuitBreakerConfig {
+        threshold: 2,
+        cooldown: Duration::from_millis(20),
+        half_open_probes: probes,
+        cooldown_multiplier,
+        max_cooldown: Duration::from_secs(1),
+    })
+}
+
+#[tokio::test]
+async fn breaker_half_opens_after_cooldown_and_closes_on_success() {
+    let cb = half_open_breaker(1, 1.0);
+    assert_eq!(cb.state(), BreakerState::Closed);
+    cb.record_failure();
+    assert_eq!(cb.state(), BreakerState::Closed);
+    cb.record_failure();
+    assert!(matches!(cb.state(), BreakerState::Open { .. }));
+    assert!(cb.is_open());
+    assert!(!cb.try_acquire());
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(cb.state(), BreakerState::HalfOpen);
+    assert!(!cb.is_open());
+    assert!(cb.try_acquire());
+    cb.record_success();
+    assert_eq!(cb.state(), BreakerState::Closed);
+    assert!(cb.try_acquire());
+}
+
+#[tokio::test]
+async fn breaker_probe_failure_reopens_with_longer_cooldown() {
+    let cb = half_open_breaker(1, 3.0);
+    cb.record_failure();
+    cb.record_failure();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(cb.try_acquire());
+    cb.record_failure();
+    assert!(matches!(cb.state(), BreakerState::Open { .. }));
+
+    // The original 20ms cooldown has elapsed, the increased 60ms one has not.
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(matches!(cb.state(), BreakerState::Open { .. }));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(cb.state(), BreakerState::HalfOpen);
+}
+
+#[tokio::test]
+async fn breaker_rejects_once_probes_are_exhausted() {
+    let cb = half_open_breaker(2, 1.0);
+    cb.record_failure();
+    cb.record_failure();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    assert!(cb.try_acquire());
+    assert!(cb.try_acquire());
+    assert!(!cb.try_acquire());
+    assert!(cb.is_open());
+    assert_eq!(cb.state(), BreakerState::HalfOpen);
+
+    let res = retry_with_backoff(|| async { Ok(()) }, &fast_retry(1), &cb).await;
+    assert!(res.is_err());
+    cb.record_success();
+    assert!(!cb.is_open());
+}
+
+#[tokio::test]
+async fn rejection_after_a_failed_attempt_returns_the_failure() {
+    let cb = half_open_breaker(1, 1.0);
+    cb.record_failure();
+    cb.record_failure();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    // The probe fails and the retry is rejected, leaving the probe's error to
+    // count against the breaker.
+    let res = retry_with_backoff(
+        || async { Err::<(), _>(transient_error()) },
+        &fast_retry(3),
+        &cb,
+    )
+    .await;
+    assert!(matches!(res, Err(IndexerError::ConnectionFailed { .. })));
+    assert!(matches!(
+        retry_with_backoff(|| async { Ok(()) }, &fast_retry(1), &cb).await,
+        Err(IndexerError::CircuitOpen {
+            domain: BreakerDomain::Rpc
+        })
+    ));
+}
+
+#[tokio::test]
+async fn breaker_notifies_listeners_of_transitions() {
+    let cb = half_open_breaker(1, 1.0);
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorder = seen.clone();
+    let kind = |state: BreakerState| match state {
+        BreakerState::Closed => "closed",
+        BreakerState::Open { .. } => "open",
+        BreakerState::HalfOpen => "half-open",
+    };
+    cb.on_state_change(move |from, to| recorder.lock().unwrap().push((kind(from), kind(to))));
+    let stamps = Arc::new(AtomicUsize::new(0));
+    let counter = stamps.clone();
+    let registered_at = std::time::SystemTime::now();
+    cb.on_state_change_at(move |_, _, at| {
+        assert!(at >= registered_at);
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+
+    cb.record_failure();
+    cb.record_failure();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(cb.try_acquire());
+    cb.record_failure();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(cb.try_acquire());
+    cb.record_success();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            ("closed", "open"),
+            ("open", "half-open"),
+            ("half-open", "open"),
+            ("open", "half-open"),
+            ("half-open", "closed"),
+        ]
+    );
+    assert_eq!(stamps.load(Ordering::SeqCst), 5);
+}
+
+#[derive(Default)]
+struct RecordingObserver {
+    retries: std::sync::Mutex<Vec<(usize, Duration)>>,
+    gave_up: std::sync::Mutex<Option<usize>>,
+}
+
+impl RetryObserver for RecordingObserver {
+    fn on_retry(&self, attempt: usize, delay: Duration, _error: &IndexerError) {
+        self.retries.lock().unwrap().push((attempt, delay));
+    }
+
+    fn on_give_up(&self, attempts: usize, _error: &IndexerError) {
+        *self.gave_up.lock().unwrap() = Some(attempts);
+    }
+}
+
+fn transient_error() -> IndexerError {
+    IndexerError::ConnectionFailed {
+        url: "ws://node".into(),
+        source: Box::new(SubxtError::Other("timeout".into())),
+    }
+}
+
+#[tokio::test]
+async fn observer_sees_each_retry_and_give_up() {
+    let observer = RecordingObserver::default();
+    let cb = CircuitBreaker::new(10, Duration::from_secs(60));
+    let res = retry_with_backoff_observed(
+        || async { Err::<(), _>(transient_error()) },
+        &fast_retry(3),
+        &cb,
+        &observer,
+    )
+    .await;
+    assert!(res.is_err());
+    let attempts: Vec<usize> = observer
+        .retries
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|r| r.0)
+        .collect();
+    assert_eq!(attempts, vec![1, 2]);
+    assert_eq!(*observer.gave_up.lock().unwrap(), Some(3));
+}
+
+#[tokio::test]
+async fn observer_not_told_to_give_up_on_recovery() {
+    let observer = RecordingObserver::default();
+    let cb = CircuitBreaker::new(10, Duration::from_secs(60));
+    let calls = AtomicUsize::new(0);
+    let res = retry_with_backoff_observed(
+        || async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(transient_error())
+            } else {
+                Ok(1)
+            }
+        },
+        &fast_retry(3),
+        &cb,
+        &observer,
+    )
+    .await;
+    assert_eq!(res.unwrap(), 1);
+    assert_eq!(observer.retries.lock().unwrap().len(), 1);
+    assert_eq!(*observer.gave_up.lock().unwrap(), None);
+}
+
+#[tokio::test]
+async fn retry_counters_count_retries_and_give_ups() {
+    let counters = RetryCounters::default();
+    let cb = CircuitBreaker::new(10, Duration::from_secs(60));
+    let _ = retry_with_backoff_observed(
+        || async { Err::<(), _>(transient_error()) },
+        &fast_retry(4),
+        &cb,
+        &counters,
+    )
+    .await;
+    let _ = retry_with_backoff_observed(
+        || async { Err::<(), _>(IndexerError::invalid_config("node_url", "bad")) },
+        &fast_retry(4),
+        &cb,
+        &counters,
+    )
+    .await;
+    assert_eq!(counters.retries(), 3);
+    assert_eq!(counters.give_ups(), 2);
+}
+
+#[tokio::test]
+async fn custom_retry_predicate_overrides_defaults() {
+    let cb = CircuitBreaker::new(10, Duration::from_secs(60));
+    let attempts = AtomicUsize::new(0);
+    let never = fast_retry(5).retry_if(Arc::new(|_| false));
+    let res = retry_with_backoff(
+        || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(transient_error())
+        },
+        &never,
+        &cb,
+    )
+    .await;
+    assert!(res.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+    attempts.store(0, Ordering::SeqCst);
+    let config_errors_too = fast_retry(3).retry_if(Arc::new(
+        |e| matches!(e, IndexerError::InvalidConfig { field, .. } if field == "flaky"),
+    ));
+    let res = retry_with_backoff(
+        || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(IndexerError::invalid_config("flaky", "not yet"))
+        },
+        &config_errors_too,
+        &cb,
+    )
+    .await;
+    assert!(res.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn default_classification_skips_permanent_errors() {
+    assert!(!is_retryable_error(&IndexerError::invalid_config("x", "y")));
+    assert!(!is_retryable_error(&IndexerError::EventDecodingFailed {
+        pallet: "Balances".into(),
+        event: "Transfer".into(),
+        block: 1,
+        source: Box::new(SubxtError::Other("bad bytes".into())),
+    }));
+    assert!(!is_retryable_error(&IndexerError::Database(Box::new(
+        sqlx::Error::RowNotFound
+    ))));
+    assert!(!is_retryable_error(&IndexerError::CheckpointError {
+        operation: "store_checkpoint".into(),
+        backend: "postgres".into(),
+        source: Box::new(sqlx::Error::ColumnNotFound("block".into())),
+    }));
+
+    assert!(is_retryable_error(&transient_error()));
+    assert!(is_retryable_error(&IndexerError::Database(Box::new(
+        sqlx::Error::PoolTimedOut
+    ))));
+    assert!(is_retryable_error(&IndexerError::CheckpointError {
+        operation: "store_checkpoint".into(),
+        backend: "postgres".into(),
+        source: Box::new(sqlx::Error::Io(std::io::Error::other("reset"))),
+    }));
+}
+
+async fn attempts_with(config: &RetryConfig) -> (usize, Result<(), IndexerError>) {
+    let cb = CircuitBreaker::new(10, Duration::from_secs(60));
+    let attempts = AtomicUsize::new(0);
+    let res = retry_with_backoff(
+        || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(transient_error())
+        },
+        config,
+        &cb,
+    )
+    .await;
+    (attempts.load(Ordering::SeqCst), res)
+}
+
+#[tokio::test]
+async fn zero_max_retries_makes_a_single_attempt() {
+    let (attempts, res) = attempts_with(&fast_retry(0)).await;
+    assert_eq!(attempts, 1);
+    assert!(matches!(res, Err(IndexerError::ConnectionFailed { .. })));
+}
+
+#[tokio::test]
+async fn one_max_retry_makes_a_single_attempt() {
+    let (attempts, res) = attempts_with(&fast_retry(1)).await;
+    assert_eq!(attempts, 1);
+    assert!(matches!(res, Err(IndexerError::ConnectionFailed { .. })));
+}
+
+#[tokio::test]
+async fn zero_multiplier_is_rejected_but_does_not_panic() {
+    let cfg = RetryConfig {
+        backoff_multiplier: 0.0,
+        ..fast_retry(3)
+    };
+    match cfg.validate() {
+        Err(IndexerError::InvalidConfig { field, .. }) => assert_eq!(field, "backoff_multiplier"),
+        other => panic!("wrong result: {other:?}"),
+    }
+    let (attempts, res) = attempts_with(&cfg).await;
+    assert_eq!(attempts, 3);
+    assert!(res.is_err());
+}
+
+#[test]
+fn retry_config_validation() {
+    assert!(RetryConfig::default().validate().is_ok());
+    for multiplier in [f32::NAN, -1.0, f32::INFINITY] {
+        let cfg = RetryConfig {
+            backoff_multiplier: multiplier,
+            ..Default::default()
+        };
+        assert!(cfg.validate().is_err(), "{multiplier} accepted");
+    }
+    let cfg = RetryConfig {
+        max_delay: Duration::ZERO,
+        initial_delay: Duration::ZERO,
+        ..Default::default()
+    };
+    match cfg.validate() {
+        Err(IndexerError::InvalidConfig { field, .. }) => assert_eq!(field, "max_delay"),
+        other => panic!("wrong result: {other:?}"),
+    }
+    let res = IndexerConfig::builder()
+        .node_url("ws://node")
+        .retry_config(RetryConfig {
+            backoff_multiplier: f32::NAN,
+            ..Default::default()
+        })
+        .build();
+    assert!(matches!(res, Err(IndexerError::InvalidConfig { .. })));
+}
+
+#[tokio::test(start_paused = true)]
+async fn max_elapsed_budget_stops_retries_early() {
+    let cfg = RetryConfig {
+        max_retries: 10,
+        initial_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(1),
+        backoff_multiplier: 1.0,
+        max_elapsed: Some(Duration::from_millis(2500)),
+        ..Default::default()
+    };
+    let started = tokio::time::Instant::now();
+    let (attempts, res) = attempts_with(&cfg).await;
+    // Attempts at 0s, 1s and 2s; a fourth at 3s would exceed the 2.5s budget.
+    assert_eq!(attempts, 3);
+    assert!(res.is_err());
+    assert_eq!(started.elapsed(), Duration::from_secs(2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn attempt_limit_wins_when_reached_before_budget() {
+    let cfg = RetryConfig {
+        max_retries: 2,
+        initial_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(1),
+        backoff_multiplier: 1.0,
+        max_elapsed: Some(Duration::from_secs(60)),
+        ..Default::default()
+    };
+    let started = tokio::time::Instant::now();
+    let (attempts, _) = attempts_with(&cfg).await;
+    assert_eq!(attempts, 2);
+    assert_eq!(started.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn breaker_stats_follow_scripted_sequence() {
+    let cb = half_open_breaker(1, 1.0);
+    let stats = cb.stats();
+    assert_eq!(stats.opens, 0);
+    assert_eq!(stats.last_opened_at, None);
+    assert_eq!(stats.time_open, Duration::ZERO);
+
+    cb.record_failure();
+    cb.record_success();
+    cb.record_failure();
+    let stats = cb.stats();
+    assert_eq!((stats.failures, stats.successes), (2, 1));
+    assert_eq!(stats.consecutive_failures, 1);
+    assert_eq!(stats.state, BreakerState::Closed);
+
+    cb.record_failure();
+    let stats = cb.stats();
+    assert_eq!(stats.opens, 1);
+    assert!(matches!(stats.state, BreakerState::Open { .. }));
+    assert_eq!(stats.consecutive_failures, 2);
+    assert!(stats.last_opened_at.is_some());
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(cb.try_acquire());
+    cb.record_failure();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(cb.try_acquire());
+    cb.record_success();
+
+    let stats = cb.stats();
+    assert_eq!(stats.opens, 2);
+    assert_eq!(stats.failures, 4);
+    assert_eq!(stats.successes, 2);
+    assert_eq!(stats.state, BreakerState::Closed);
+    assert!(stats.time_open >= Duration::from_millis(40));
+}
+
+#[tokio::test]
+async fn failing_checkpoint_store_leaves_rpc_breaker_closed() {
+    let mut mock = MockCheckpointStore::new();
+    mock.fail_store = true;
+    mock.fail_load = true;
+    let store = RetryingStore::new(
+        mock,
+        fast_retry(2),
+        CircuitBreaker::new(3, Duration::from_secs(60)),
+    );
+    let rpc_breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+
+    for block in 0..3 {
+        assert!(matches!(
+            store.store_checkpoint(block).await,
+            Err(IndexerError::CheckpointError { .. })
+        ));
+    }
+    assert!(matches!(
+        store.load_checkpoint().await,
+        Err(IndexerError::CircuitOpen {
+            domain: BreakerDomain::Storage
+        })
+    ));
+    assert!(!rpc_breaker.is_open());
+    assert_eq!(rpc_breaker.stats().failures, 0);
+
+    let calls = AtomicUsize::new(0);
+    let res = retry_with_backoff(
+        || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, IndexerError>(42)
+        },
+        &fast_retry(3),
+        &rpc_breaker,
+    )
+    .await;
+    assert_eq!(res.unwrap(), 42);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn breaker_accessors_across_transitions() {
+    let cb = half_open_breaker(1, 1.0);
+    assert_eq!(cb.failure_count(), 0);
+    assert_eq!(cb.remaining_cooldown(), None);
+    assert!(!cb.is_half_open());
+
+    cb.record_failure();
+    assert_eq!(cb.failure_count(), 1);
+    assert_eq!(cb.remaining_cooldown(), None);
+
+    cb.record_failure();
+    assert_eq!(cb.failure_count(), 2);
+    let remaining = cb
+        .remaining_cooldown()
+        .expect("open breaker has a cooldown");
+    assert!(remaining <= Duration::from_millis(20) && remaining > Duration::ZERO);
+    match cb.state() {
+        BreakerState::Open { until } => assert!(until > std::time::Instant::now()),
+        other => panic!("expected open, got {other:?}"),
+    }
+    assert!(cb.is_open());
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(cb.is_half_open());
+    assert_eq!(cb.remaining_cooldown(), None);
+    assert_eq!(cb.failure_count(), 2);
+
+    assert!(cb.try_acquire());
+    cb.record_failure();
+    assert_eq!(cb.failure_count(), 3);
+    assert!(!cb.is_half_open());
+    assert!(cb.remaining_cooldown().is_some());
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(cb.try_acquire());
+    cb.record_success();
+    assert_eq!(cb.failure_count(), 0);
+    assert_eq!(cb.state(), BreakerState::Closed);
+    assert_eq!(cb.remaining_cooldown(), None);
+}
+
+#[tokio::test]
+async fn permanent_errors_fail_without_retrying() {
+    let permanent: [fn() -> IndexerError; 4] = [
+        || IndexerError::HandlerFailed {
+            handler: "h".into(),
+            block: 1,
+            event_index: None,
+            source: Box::new(std::io::Error::other("logic bug")),
+            retryable: false,
+        },
+        || IndexerError::Io(std::io::ErrorKind::PermissionDenied.into()),
+        || IndexerError::CheckpointError {
+            operation: "load_checkpoint".into(),
+            backend: "json".into(),
+            source: Box::new(std::io::Error::from(std::io::ErrorKind::NotFound)),
+        },
+        || IndexerError::BlockNotFound { block: 1 },
+    ];
+    let cb = CircuitBreaker::new(10, Duration::from_secs(60));
+    for make_error in permanent {
+        let (attempts, res) = attempts_failing_with(make_error, &fast_retry(3), &cb).await;
+        let message = make_error().to_string();
+        assert_eq!(attempts, 1, "{message} was retried");
+        assert_eq!(res.unwrap_err().to_string(), message);
+    }
+
+    let (attempts, _) = attempts_failing_with(
+        || IndexerError::Io(std::io::ErrorKind::TimedOut.into()),
+        &fast_retry(3),
+        &cb,
+    )
+    .await;
+    assert_eq!(attempts, 3);
+}
+
+async fn attempts_failing_with(
+    make_error: fn() -> IndexerError,
+    config: &RetryConfig,
+    cb: &CircuitBreaker,
+) -> (usize, Result<(), IndexerError>) {
+    let attempts = AtomicUsize::new(0);
+    let res = retry_with_backoff(
+        || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(make_error())
+        },
+        config,
+        cb,
+    )
+    .await;
+    (attempts.load(Ordering::SeqCst), res)
+}
+
+#[cfg(feature = "json-storage")]
+#[test]
+fn corrupt_checkpoint_json_is_not_retried() {
+    let err = serde_json::from_str::<u64>("{").unwrap_err();
+    assert!(!is_retryable_error(&IndexerError::SerdeJson(err)));
+}
+
+/// A JSON-RPC error response with `code` and, if given, `data`.
+fn rpc_error(code: i32, data: Option<&str>) -> SubxtError {
+    SubxtError::Rpc(RpcError::ClientError(ClientError::User(UserError {
+        code,
+        message: "Too many requests".into(),
+        data: data.map(|data| RawValue::from_string(data.into()).unwrap()),
+    })))
+}
+
+#[test]
+fn rate_limit_errors_are_parsed() {
+    let err = IndexerError::from(rpc_error(-32005, Some(r#"{"retry_after":2}"#)));
+    match &err {
+        IndexerError::RateLimited {
+            retry_after,
+            source,
+        } => {
+            assert_eq!(*retry_after, Some(Duration::from_secs(2)));
+            assert!(matches!(**source, SubxtError::Rpc(_)));
+        }
+        other => panic!("wrong error: {other:?}"),
+    }
+
+    let err = IndexerError::from(rpc_error(-32005, Some(r#"{"retryAfter":"1500ms"}"#)));
+    assert!(matches!(
+        err,
+        IndexerError::RateLimited { retry_after: Some(d), .. } if d == Duration::from_millis(1500)
+    ));
+
+    let err = IndexerError::from(rpc_error(-32005, None));
+    assert!(matches!(
+        err,
+        IndexerError::RateLimited {
+            retry_after: None,
+            ..
+        }
+    ));
+    assert!(is_retryable_error(&err));
+
+    let err = IndexerError::from(SubxtError::Rpc(RpcError::LimitReached));
+    assert!(matches!(err, IndexerError::RateLimited { .. }));
+
+    // Only the error code counts, not the wording.
+    let err = IndexerError::from(rpc_error(-32000, Some(r#"{"retry_after":2}"#)));
+    assert!(matches!(err, IndexerError::Subxt(_)));
+    let err = IndexerError::from(SubxtError::Other("429 Too Many Requests".into()));
+    assert!(matches!(err, IndexerError::Subxt(_)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_sleeps_for_the_servers_hint() {
+    let cfg = RetryConfig {
+        max_retries: 4,
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_secs(10),
+        backoff_multiplier: 1.0,
+        ..Default::default()
+    };
+    let cb = CircuitBreaker::new(10, Duration::from_secs(60));
+    let observer = RecordingObserver::default();
+    let calls = AtomicUsize::new(0);
+    let started = tokio::time::Instant::now();
+    let res = retry_with_backoff_observed(
+        || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(IndexerError::from(rpc_error(
+                    -32005,
+                    Some(r#"{"retry_after": 5}"#),
+                ))),
+                1 => Err(IndexerError::from(rpc_error(-32005, None))),
+                // Longer than `max_delay`, which caps it.
+                2 => Err(IndexerError::from(rpc_error(
+                    -32005,
+                    Some(r#"{"retry_after": 60}"#),
+                ))),
+                _ => Ok(()),
+            }
+        },
+        &cfg,
+        &cb,
+        &observer,
+    )
+    .await;
+    assert!(res.is_ok());
+    let delays: Vec<Duration> = observer
+        .retries
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|r| r.1)
+        .collect();
+    assert_eq!(
+        delays,
+        vec![
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+            Duration::from_secs(10)
+        ]
+    );
+    assert_eq!(started.elapsed(), Duration::from_millis(15010));
+}
+
+#[tokio::test(start_paused = true)]
+async fn per_attempt_timeout_retries_hanging_calls() {
+    let cfg = RetryConfig {
+        max_retries: 3,
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(10),
+        backoff_multiplier: 1.0,
+        ..Default::default()
+    };
+    let cb = CircuitBreaker::new(10, Duration::from_secs(60));
+    let attempts = AtomicUsize::new(0);
+    let started = tokio::time::Instant::now();
+    let res: Result<(), _> = with_timeout_and_backoff(
+        || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            std::future::pending()
+        },
+        Duration::from_millis(100),
+        &cfg,
+        &cb,
+    )
+    .await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    match res {
+        Err(IndexerError::Timeout {
+            operation,
+            duration,
+        }) => {
+            assert_eq!(operation, "rpc");
+            assert_eq!(duration, Duration::from_millis(100));
+        }
+        other => panic!("wrong result: {other:?}"),
+    }
+    assert_eq!(started.elapsed(), Duration::from_millis(320));
+}
+
+#[tokio::test(start_paused = true)]
+async fn per_attempt_timeout_passes_fast_results_through() {
+    let cb = CircuitBreaker::new(10, Duration::from_secs(60));
+    let calls = AtomicUsize::new(0);
+    let res = with_timeout_and_backoff(
+        || async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                std::future::pending::<()>().await;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(7)
+        },
+        Duration::from_millis(100),
+        &fast_retry(3),
+        &cb,
+    )
+    .await;
+    assert_eq!(res.unwrap(), 7);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn handler_failures_are_retried_only_when_marked_retryable() {
+    let cb = CircuitBreaker::new(10, Duration::from_secs(60));
+    let (attempts, res) = attempts_failing_with(
+        || {
+            IndexerError::handler_failed("s3", 7, std::io::Error::other("503 Slow Down"))
+                .retryable(true)
+        },
+        &fast_retry(3),
+        &cb,
+    )
+    .await;
+    assert_eq!(attempts, 3);
+    assert!(matches!(
+        res,
+        Err(IndexerError::HandlerFailed {
+            retryable: true,
+            ..
+        })
+    ));
+
+    let (attempts, _) = attempts_failing_with(
+        || IndexerError::handler_failed("s3", 7, "assertion failed"),
+        &fast_retry(3),
+        &cb,
+    )
+    .await;
+    assert_eq!(attempts, 1);
+
+    assert!(!is_retryable_error(
+        &IndexerError::handler_failed("s3", 7, "flip")
+            .retryable(true)
+            .retryable(false)
+    ));
+    assert!(!is_retryable_error(
+        &IndexerError::BlockNotFound { block: 1 }.retryable(true)
+    ));
+}
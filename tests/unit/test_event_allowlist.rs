@@ -0,0 +1,87 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::config::IndexerConfig;
+use flamewire_bittensor_indexer::{EventPattern, IndexerError};
+
+fn allowlist(patterns: &[&str]) -> IndexerConfig {
+    IndexerConfig::builder()
+        .node_url("ws://node")
+        .event_allowlist(patterns.iter().map(|p| p.parse().unwrap()))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn specific_event_pattern() {
+    let pattern = EventPattern::parse("Balances.Transfer").unwrap();
+    assert_eq!(pattern.pallet(), "Balances");
+    assert_eq!(pattern.event(), Some("Transfer"));
+    assert!(pattern.matches("Balances", "Transfer"));
+    assert!(!pattern.matches("Balances", "Deposit"));
+    assert!(!pattern.matches("System", "Transfer"));
+    assert_eq!(pattern.to_string(), "Balances.Transfer");
+}
+
+#[test]
+fn wildcard_pallet_pattern() {
+    let pattern = EventPattern::parse("SubtensorModule.*").unwrap();
+    assert_eq!(pattern.event(), None);
+    assert!(pattern.matches("SubtensorModule", "NeuronRegistered"));
+    assert!(pattern.matches("SubtensorModule", "StakeAdded"));
+    assert!(!pattern.matches("Balances", "Transfer"));
+    assert_eq!(pattern.to_string(), "SubtensorModule.*");
+}
+
+#[test]
+fn invalid_patterns_are_rejected() {
+    for pattern in [
+        "",
+        "Balances",
+        "Balances.",
+        ".Transfer",
+        "*.Transfer",
+        "*",
+        "Balances.Trans*",
+        "Balances.Transfer.Extra",
+        "Bal ances.Transfer",
+    ] {
+        match EventPattern::parse(pattern) {
+            Err(IndexerError::InvalidConfig { field, message }) => {
+                assert_eq!(field, "event_allowlist");
+                assert!(message.contains(pattern), "{message}");
+            }
+            other => panic!("{pattern:?} gave {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn allowlist_filters_events() {
+    let cfg = allowlist(&["Balances.Transfer", "SubtensorModule.*"]);
+    assert!(cfg.allows_event("Balances", "Transfer"));
+    assert!(cfg.allows_event("SubtensorModule", "WeightsSet"));
+    assert!(!cfg.allows_event("Balances", "Deposit"));
+    assert!(!cfg.allows_event("System", "ExtrinsicSuccess"));
+}
+
+#[test]
+fn empty_allowlist_allows_everything() {
+    let cfg = allowlist(&[]);
+    assert!(cfg.event_allowlist.is_empty());
+    assert!(cfg.allows_event("System", "ExtrinsicSuccess"));
+    assert!(cfg.allows_event("Balances", "Transfer"));
+}
@@ -0,0 +1,101 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::types::{ChainEvent, FieldValueExt};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::AccountId32;
+
+#[derive(Encode, Decode, TypeInfo, Clone, Debug)]
+enum FieldEvent {
+    Staked {
+        who: AccountId32,
+        amount: u128,
+        note: String,
+    },
+    Moved(AccountId32, u64),
+}
+
+fn chain_events(records: Vec<EventRecord<FieldEvent>>) -> Vec<ChainEvent<SubstrateConfig>> {
+    let evs = events(test_metadata::<FieldEvent>(), records);
+    ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect()
+}
+
+#[test]
+fn named_fields_by_name_and_position() {
+    let who = AccountId32([1; 32]);
+    let ces = chain_events(vec![EventRecord::new(
+        Phase::Initialization,
+        FieldEvent::Staked {
+            who: who.clone(),
+            amount: 5_000_000_000,
+            note: "hi".into(),
+        },
+    )]);
+    let event = &ces[0];
+
+    let amount = event.field("amount").unwrap().unwrap();
+    assert_eq!(amount.as_u128(), Some(5_000_000_000));
+    let staker = event.field("who").unwrap().unwrap();
+    assert_eq!(staker.as_account_id(), Some(who));
+    assert_eq!(event.field("note").unwrap().unwrap().as_str(), Some("hi"));
+    assert!(event.field("missing").unwrap().is_none());
+
+    assert_eq!(
+        event.field_at(1).unwrap().unwrap().as_u128(),
+        Some(5_000_000_000)
+    );
+    assert!(event.field_at(3).unwrap().is_none());
+}
+
+#[test]
+fn positional_fields_by_index() {
+    let who = AccountId32([9; 32]);
+    let ces = chain_events(vec![EventRecord::new(
+        Phase::Initialization,
+        FieldEvent::Moved(who.clone(), 42),
+    )]);
+    let event = &ces[0];
+
+    assert!(event.field("who").unwrap().is_none());
+    assert_eq!(
+        event.field_at(0).unwrap().unwrap().as_account_id(),
+        Some(who)
+    );
+    let netuid = event.field_at(1).unwrap().unwrap();
+    assert_eq!(netuid.as_u128(), Some(42));
+    assert_eq!(netuid.as_account_id(), None);
+}
+
+#[test]
+fn repeated_access_returns_the_same_fields() {
+    let ces = chain_events(vec![EventRecord::new(
+        Phase::Initialization,
+        FieldEvent::Moved(AccountId32([3; 32]), 7),
+    )]);
+    let event = &ces[0];
+
+    let first = event.field_values().unwrap();
+    assert_eq!(event.field_values().unwrap(), first);
+    assert_eq!(event.field_at(1).unwrap().unwrap().as_u128(), Some(7));
+}
@@ -0,0 +1,89 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::handler::Context;
+use flamewire_bittensor_indexer::types::{ChainEvent, EventId};
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::H256;
+
+// Pinned so that any change to the derivation is caught: ids must stay stable
+// across crate versions.
+#[test]
+fn digests_are_pinned() {
+    assert_eq!(
+        EventId::new(&H256::zero(), 0).to_string(),
+        "0x9f0e444c69f77a49bd0be89db92c38fe713e0963165cca12faf5712d7657120f"
+    );
+    assert_eq!(
+        EventId::new(&H256::zero(), 1).to_string(),
+        "0x5d2532e641a22a8f5e0a42652fe82dc231fd27f8c4ffb9bc927265c92ecadc67"
+    );
+    assert_eq!(
+        EventId::new(&H256::repeat_byte(0xab), 7).to_string(),
+        "0x05c58bfea93615442502bc9940d43feedb20ba8f39405c42fe40c4a6b132c648"
+    );
+}
+
+#[test]
+fn event_and_context_agree() {
+    let block_hash = H256::repeat_byte(0xab);
+    let evs = events(
+        test_metadata::<TestEvent>(),
+        (0..8)
+            .map(|n| EventRecord::new(Phase::Initialization, TestEvent::A(n)))
+            .collect(),
+    );
+    let ces: Vec<ChainEvent<SubstrateConfig>> = ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect();
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .block_hash(block_hash)
+        .build();
+
+    let id = ces[7].event_id(&block_hash);
+    assert_eq!(id, EventId::new(&block_hash, 7));
+    assert_eq!(ctx.event_id(&ces[7]), id);
+    assert_ne!(ces[6].event_id(&block_hash), id);
+    assert_ne!(ces[7].event_id(&H256::zero()), id);
+}
+
+#[test]
+fn parses_its_display_form() {
+    let id = EventId::new(&H256::zero(), 3);
+    assert_eq!(id.to_string().parse::<EventId>().unwrap(), id);
+    assert_eq!(format!("{id:?}"), format!("EventId({id})"));
+
+    assert!("9f0e".parse::<EventId>().is_err());
+    assert!("0x9f0e".parse::<EventId>().is_err());
+    assert!(format!("0x{}", "zz".repeat(32)).parse::<EventId>().is_err());
+}
+
+#[cfg(feature = "json-storage")]
+#[test]
+fn serde_round_trip() {
+    let id = EventId::new(&H256::zero(), 0);
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(
+        json,
+        "\"0x9f0e444c69f77a49bd0be89db92c38fe713e0963165cca12faf5712d7657120f\""
+    );
+    assert_eq!(serde_json::from_str::<EventId>(&json).unwrap(), id);
+}
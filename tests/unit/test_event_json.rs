@@ -0,0 +1,163 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::handler::Context;
+use flamewire_bittensor_indexer::types::{ChainEvent, EventEnvelope};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde_json::json;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::H256;
+
+#[derive(Encode, Decode, TypeInfo, Clone, Debug)]
+enum RichEvent {
+    Transfer {
+        from: [u8; 4],
+        amount: u128,
+        memo: Vec<u8>,
+    },
+    Signed(i128, Option<u64>),
+}
+
+fn chain_events<E: Decode + Encode + TypeInfo + 'static>(
+    records: Vec<EventRecord<E>>,
+) -> Vec<ChainEvent<SubstrateConfig>> {
+    let evs = events(test_metadata::<E>(), records);
+    ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect()
+}
+
+#[test]
+fn test_event_fields() {
+    let ces = chain_events(vec![
+        EventRecord::new(Phase::Initialization, TestEvent::A(1)),
+        EventRecord::new(Phase::ApplyExtrinsic(2), TestEvent::B(true)),
+    ]);
+
+    assert_eq!(ces[0].to_json().unwrap(), json!([1]));
+    assert_eq!(ces[1].to_json().unwrap(), json!([true]));
+}
+
+#[test]
+fn test_event_fields_with_meta() {
+    let ces = chain_events(vec![
+        EventRecord::new(Phase::Initialization, TestEvent::A(1)),
+        EventRecord::new(Phase::ApplyExtrinsic(2), TestEvent::B(true)),
+        EventRecord::new(Phase::Finalization, TestEvent::A(3)),
+    ]);
+
+    assert_eq!(
+        ces[0].to_json_with_meta().unwrap(),
+        json!({
+            "pallet": "Test",
+            "event": "A",
+            "index": 0,
+            "phase": "initialization",
+            "fields": [1],
+        })
+    );
+    assert_eq!(
+        ces[1].to_json_with_meta().unwrap(),
+        json!({
+            "pallet": "Test",
+            "event": "B",
+            "index": 1,
+            "phase": { "apply_extrinsic": 2 },
+            "fields": [true],
+        })
+    );
+    assert_eq!(ces[2].to_json_with_meta().unwrap()["phase"], "finalization");
+}
+
+#[test]
+fn bytes_are_hex_and_big_integers_are_strings() {
+    let ces = chain_events(vec![
+        EventRecord::new(
+            Phase::Initialization,
+            RichEvent::Transfer {
+                from: [0xde, 0xad, 0xbe, 0xef],
+                amount: u128::MAX,
+                memo: b"hi".to_vec(),
+            },
+        ),
+        EventRecord::new(Phase::Initialization, RichEvent::Signed(-5, Some(7))),
+        EventRecord::new(Phase::Initialization, RichEvent::Signed(i128::MIN, None)),
+    ]);
+
+    assert_eq!(
+        ces[0].to_json().unwrap(),
+        json!({
+            "from": "0xdeadbeef",
+            "amount": "340282366920938463463374607431768211455",
+            "memo": "0x6869",
+        })
+    );
+    assert_eq!(
+        ces[1].to_json().unwrap(),
+        json!([-5, { "name": "Some", "values": [7] }])
+    );
+    assert_eq!(
+        ces[2].to_json().unwrap(),
+        json!(["-170141183460469231731687303715884105728", { "name": "None", "values": [] }])
+    );
+}
+
+#[test]
+fn envelope_captures_event_and_block() {
+    let ces = chain_events(vec![
+        EventRecord::new(Phase::Initialization, TestEvent::A(1)),
+        EventRecord::new(Phase::ApplyExtrinsic(0), TestEvent::B(true)),
+    ]);
+    let block_hash = H256::repeat_byte(1);
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(42)
+        .block_hash(block_hash)
+        .timestamp(1_700_000_000_000)
+        .build();
+
+    let envelope = EventEnvelope::from_event(&ces[1], &ctx);
+    assert_eq!(envelope.block_number, 42);
+    assert_eq!(envelope.block_hash, block_hash);
+    assert_eq!(envelope.timestamp, Some(1_700_000_000_000));
+    assert_eq!(envelope.index, 1);
+    assert_eq!(envelope.pallet, "Test");
+    assert_eq!(envelope.variant, "B");
+    assert_eq!(envelope.fields_json, Some(json!([true])));
+    assert_eq!(envelope.id(), ctx.event_id(&ces[1]));
+
+    let copy = envelope.clone();
+    assert_eq!(
+        serde_json::to_value(&copy).unwrap(),
+        json!({
+            "block_number": 42,
+            "block_hash": format!("0x{}", "01".repeat(32)),
+            "timestamp": 1_700_000_000_000u64,
+            "index": 1,
+            "pallet": "Test",
+            "variant": "B",
+            "fields_json": [true],
+        })
+    );
+    assert_eq!(
+        serde_json::to_string(&envelope).unwrap(),
+        serde_json::to_string(&copy).unwrap()
+    );
+}
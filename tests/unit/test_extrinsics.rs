@@ -0,0 +1,323 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::config::IndexerConfig;
+use flamewire_bittensor_indexer::extrinsics::{
+    signer_from_address, BlockExtrinsics, ChainExtrinsic, ExtrinsicInfo,
+};
+use flamewire_bittensor_indexer::handler::Context;
+use flamewire_bittensor_indexer::types::ChainEvent;
+use parity_scale_codec::{Decode, Encode};
+use scale_decode::DecodeAsType;
+use scale_info::TypeInfo;
+use std::sync::Arc;
+use subxt::blocks::StaticExtrinsic;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::AccountId32;
+
+#[derive(Encode, Decode, TypeInfo)]
+enum SystemEvent {
+    ExtrinsicSuccess { weight: u64 },
+    ExtrinsicFailed { error: u8 },
+    NewAccount { account: AccountId32 },
+}
+
+fn signer() -> AccountId32 {
+    AccountId32([7; 32])
+}
+
+/// A block with a timestamp inherent, a successful and a failed `add_stake`,
+/// and a remark without an outcome event.
+fn block() -> (BlockExtrinsics, Vec<ChainEvent<SubstrateConfig>>) {
+    let extrinsics = BlockExtrinsics::new(vec![
+        ExtrinsicInfo::new("Timestamp", "set"),
+        ExtrinsicInfo::new("SubtensorModule", "add_stake").with_signer(signer()),
+        ExtrinsicInfo::new("SubtensorModule", "add_stake").with_signer(signer()),
+        ExtrinsicInfo::new("System", "remark").with_signer(signer()),
+    ]);
+    let evs = events(
+        pallet_metadata::<SystemEvent>("System"),
+        vec![
+            EventRecord::new(
+                Phase::ApplyExtrinsic(0),
+                SystemEvent::ExtrinsicSuccess { weight: 1 },
+            ),
+            EventRecord::new(
+                Phase::ApplyExtrinsic(1),
+                SystemEvent::NewAccount { account: signer() },
+            ),
+            EventRecord::new(
+                Phase::ApplyExtrinsic(1),
+                SystemEvent::ExtrinsicSuccess { weight: 2 },
+            ),
+            EventRecord::new(
+                Phase::ApplyExtrinsic(2),
+                SystemEvent::ExtrinsicFailed { error: 3 },
+            ),
+            EventRecord::new(
+                Phase::Finalization,
+                SystemEvent::ExtrinsicFailed { error: 4 },
+            ),
+        ],
+    );
+    let ces = ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect();
+    (extrinsics, ces)
+}
+
+#[test]
+fn outcomes_come_from_system_events() {
+    let (mut extrinsics, ces) = block();
+    extrinsics.apply_outcomes(&ces);
+
+    let outcomes: Vec<Option<bool>> = (0..4)
+        .map(|index| extrinsics.get(index).unwrap().success)
+        .collect();
+    assert_eq!(outcomes, [Some(true), Some(true), Some(false), None]);
+    assert!(extrinsics.get(4).is_none());
+}
+
+#[test]
+fn context_resolves_the_extrinsic_of_an_event() {
+    let (mut extrinsics, ces) = block();
+    extrinsics.apply_outcomes(&ces);
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .extrinsics(Arc::new(extrinsics))
+        .build();
+
+    let new_account = &ces[1];
+    assert_eq!(new_account.extrinsic_index(), Some(1));
+    let info = ctx.extrinsic_info(new_account.extrinsic_index()).unwrap();
+    assert_eq!(info.pallet, "SubtensorModule");
+    assert_eq!(info.call, "add_stake");
+    assert_eq!(info.signer, Some(signer()));
+    assert_eq!(info.success, Some(true));
+
+    let inherent = ctx.extrinsic_info(ces[0].extrinsic_index()).unwrap();
+    assert_eq!(inherent.call, "set");
+    assert_eq!(inherent.signer, None);
+
+    assert_eq!(
+        ctx.extrinsic_info(ces[3].extrinsic_index())
+            .unwrap()
+            .success,
+        Some(false)
+    );
+
+    // Finalization events belong to no extrinsic.
+    assert_eq!(ces[4].extrinsic_index(), None);
+    assert!(ctx.extrinsic_info(ces[4].extrinsic_index()).is_none());
+}
+
+#[derive(Encode, Decode, TypeInfo)]
+enum TransactionPaymentEvent {
+    TransactionFeePaid {
+        who: AccountId32,
+        actual_fee: u64,
+        tip: u64,
+    },
+}
+
+/// Fee events for the two `add_stake` calls of [`block`] only, plus one outside
+/// any extrinsic.
+fn fee_events() -> Vec<ChainEvent<SubstrateConfig>> {
+    let paid = |actual_fee, tip| TransactionPaymentEvent::TransactionFeePaid {
+        who: signer(),
+        actual_fee,
+        tip,
+    };
+    let evs = events(
+        pallet_metadata::<TransactionPaymentEvent>("TransactionPayment"),
+        vec![
+            EventRecord::new(Phase::ApplyExtrinsic(1), paid(125_000, 0)),
+            EventRecord::new(Phase::ApplyExtrinsic(2), paid(250_000, 50_000)),
+            EventRecord::new(Phase::Finalization, paid(1, 1)),
+        ],
+    );
+    ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect()
+}
+
+#[test]
+fn fees_come_from_transaction_fee_paid_events() {
+    let (mut extrinsics, ces) = block();
+    extrinsics.apply_outcomes(&ces);
+    extrinsics.apply_fees(&ces);
+    extrinsics.apply_fees(&fee_events());
+
+    let fees: Vec<(Option<u128>, Option<u128>)> = (0..4)
+        .map(|index| {
+            let info = extrinsics.get(index).unwrap();
+            (info.fee, info.tip)
+        })
+        .collect();
+    assert_eq!(
+        fees,
+        [
+            (None, None),
+            (Some(125_000), Some(0)),
+            (Some(250_000), Some(50_000)),
+            (None, None),
+        ]
+    );
+    // Fees do not touch outcomes.
+    assert_eq!(extrinsics.get(2).unwrap().success, Some(false));
+
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .extrinsics(Arc::new(extrinsics))
+        .build();
+    assert_eq!(ctx.extrinsic_fee(Some(2)), Some(250_000));
+    assert_eq!(ctx.extrinsic_fee(Some(3)), None);
+    assert_eq!(ctx.extrinsic_fee(None), None);
+}
+
+#[test]
+fn fees_stay_unset_without_fee_events() {
+    let (mut extrinsics, ces) = block();
+    extrinsics.apply_fees(&ces);
+    assert!((0..4).all(|index| extrinsics.get(index).unwrap().fee.is_none()));
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+    assert_eq!(ctx.extrinsic_fee(Some(1)), None);
+}
+
+#[test]
+fn context_without_extrinsics_resolves_nothing() {
+    let (_, ces) = block();
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+    assert!(ctx.extrinsic_info(ces[1].extrinsic_index()).is_none());
+}
+
+#[test]
+fn signer_is_read_from_account_and_multi_addresses() {
+    let account = [9u8; 32];
+    assert_eq!(signer_from_address(&account), Some(AccountId32(account)));
+
+    let mut multi_id = vec![0u8];
+    multi_id.extend_from_slice(&account);
+    assert_eq!(signer_from_address(&multi_id), Some(AccountId32(account)));
+
+    // MultiAddress::Index and truncated addresses carry no account id.
+    assert_eq!(signer_from_address(&[1, 4]), None);
+    assert_eq!(signer_from_address(&multi_id[..20]), None);
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Encode, TypeInfo)]
+enum SystemCall {
+    remark { remark: Vec<u8> },
+    set_heap_pages { pages: u64 },
+}
+
+#[derive(Debug, PartialEq, DecodeAsType)]
+struct Remark {
+    remark: Vec<u8>,
+}
+
+impl StaticExtrinsic for Remark {
+    const PALLET: &'static str = "System";
+    const CALL: &'static str = "remark";
+}
+
+fn system_call(call: SystemCall) -> Vec<u8> {
+    // Pallet index 0, then the call variant and its arguments.
+    let mut bytes = vec![0];
+    call.encode_to(&mut bytes);
+    bytes
+}
+
+#[test]
+fn extrinsics_decode_signed_and_unsigned() {
+    let decoded = ChainExtrinsic::decode_all::<SubstrateConfig>(
+        vec![
+            unsigned_extrinsic(&system_call(SystemCall::set_heap_pages { pages: 8 })),
+            signed_extrinsic(
+                signer().0,
+                &system_call(SystemCall::remark {
+                    remark: b"hi".to_vec(),
+                }),
+            ),
+        ],
+        call_metadata::<SystemCall>("System", 0),
+    )
+    .unwrap();
+
+    assert_eq!(decoded.len(), 2);
+    assert_eq!(decoded[0].index(), 0);
+    assert_eq!(decoded[0].call_name(), "set_heap_pages");
+    assert!(!decoded[0].is_signed());
+    assert_eq!(decoded[0].signer(), None);
+    assert_eq!(decoded[0].as_call::<Remark>().unwrap(), None);
+
+    assert_eq!(decoded[1].index(), 1);
+    assert_eq!(decoded[1].pallet_name(), "System");
+    assert!(decoded[1].is_signed());
+    assert_eq!(decoded[1].signer(), Some(&signer()));
+    assert_eq!(
+        decoded[1].as_call::<Remark>().unwrap(),
+        Some(Remark {
+            remark: b"hi".to_vec()
+        })
+    );
+
+    let infos = BlockExtrinsics::from_extrinsics(&decoded);
+    assert_eq!(infos.len(), 2);
+    assert_eq!(
+        infos.get(1),
+        Some(&ExtrinsicInfo::new("System", "remark").with_signer(signer()))
+    );
+}
+
+#[test]
+fn correlation_is_opt_in() {
+    assert!(!IndexerConfig::default().correlate_extrinsics);
+    let config = IndexerConfig::builder()
+        .node_url("ws://node")
+        .correlate_extrinsics(true)
+        .build()
+        .unwrap();
+    assert!(config.correlate_extrinsics);
+}
+
+#[test]
+fn fee_tracking_needs_correlation() {
+    assert!(!IndexerConfig::default().track_fees);
+    let err = IndexerConfig::builder()
+        .node_url("ws://node")
+        .track_fees(true)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("track_fees"), "{err}");
+
+    let config = IndexerConfig::builder()
+        .node_url("ws://node")
+        .correlate_extrinsics(true)
+        .track_fees(true)
+        .build()
+        .unwrap();
+    assert!(config.track_fees);
+}
@@ -0,0 +1,179 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::grpc::proto::event_stream_client::EventStreamClient;
+use flamewire_bittensor_indexer::grpc::proto::{EventEnvelope, FilterRequest};
+use flamewire_bittensor_indexer::handler::{Context, Handler};
+use flamewire_bittensor_indexer::{ChainEvent, EventBroadcast, EventStreamService, GrpcServer};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde_json::{json, Value};
+use std::time::Duration;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::H256;
+use tonic::Streaming;
+
+#[derive(Encode, Decode, TypeInfo)]
+enum SubtensorModule {
+    NetworkAdded(u16, u16),
+    PendingEmission { amount: u64, netuid: u16 },
+    WeightsVersionKeySet(u64, u64),
+}
+
+async fn publish(broadcast: &EventBroadcast, block: u64, block_events: Vec<SubtensorModule>) {
+    let records = block_events
+        .into_iter()
+        .map(|event| EventRecord::new(Phase::ApplyExtrinsic(1), event))
+        .collect();
+    let evs = events(
+        pallet_metadata::<SubtensorModule>("SubtensorModule"),
+        records,
+    );
+    let ces: Vec<ChainEvent<SubstrateConfig>> = ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect();
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(block)
+        .block_hash(H256::repeat_byte(block as u8))
+        .build();
+    broadcast.handle_block(&ctx, &ces).await.unwrap();
+}
+
+async fn subscribe(server: &GrpcServer, filter: FilterRequest) -> Streaming<EventEnvelope> {
+    let mut client = EventStreamClient::connect(format!("http://{}", server.local_addr()))
+        .await
+        .unwrap();
+    client.subscribe(filter).await.unwrap().into_inner()
+}
+
+async fn next(stream: &mut Streaming<EventEnvelope>) -> EventEnvelope {
+    tokio::time::timeout(Duration::from_secs(5), stream.message())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap()
+}
+
+fn fields(envelope: &EventEnvelope) -> Value {
+    serde_json::from_str(envelope.fields_json.as_deref().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_subscriber_receives_published_events() {
+    let events = EventBroadcast::new(16);
+    let service = EventStreamService::new(events.clone());
+    let server = GrpcServer::bind("127.0.0.1:0".parse().unwrap(), service)
+        .await
+        .unwrap();
+    let mut stream = subscribe(&server, FilterRequest::default()).await;
+    assert_eq!(events.subscribers(), 1);
+
+    publish(
+        &events,
+        7,
+        vec![
+            SubtensorModule::NetworkAdded(3, 1),
+            SubtensorModule::PendingEmission {
+                amount: 5,
+                netuid: 4,
+            },
+        ],
+    )
+    .await;
+
+    let first = next(&mut stream).await;
+    assert_eq!(first.block_number, 7);
+    assert_eq!(first.block_hash, vec![7; 32]);
+    assert_eq!(first.index, 0);
+    assert_eq!(first.pallet, "SubtensorModule");
+    assert_eq!(first.variant, "NetworkAdded");
+    assert_eq!(first.netuid, Some(3));
+    assert_eq!(fields(&first), json!([3, 1]));
+
+    let second = next(&mut stream).await;
+    assert_eq!(second.variant, "PendingEmission");
+    assert_eq!(second.netuid, Some(4));
+    assert_eq!(fields(&second), json!({"amount": 5, "netuid": 4}));
+    assert_eq!(events.dropped(), 0);
+}
+
+#[tokio::test]
+async fn test_subscribers_filter_by_event_and_netuid() {
+    let events = EventBroadcast::new(16);
+    let service = EventStreamService::new(events.clone());
+    let server = GrpcServer::bind("127.0.0.1:0".parse().unwrap(), service)
+        .await
+        .unwrap();
+    let mut by_event = subscribe(
+        &server,
+        FilterRequest {
+            events: vec!["SubtensorModule.PendingEmission".into()],
+            ..Default::default()
+        },
+    )
+    .await;
+    let mut by_netuid = subscribe(
+        &server,
+        FilterRequest {
+            pallets: vec!["SubtensorModule".into()],
+            netuid: Some(3),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    publish(
+        &events,
+        7,
+        vec![
+            SubtensorModule::NetworkAdded(4, 1),
+            SubtensorModule::PendingEmission {
+                amount: 5,
+                netuid: 3,
+            },
+            // Its first field is too large for a netuid.
+            SubtensorModule::WeightsVersionKeySet(70_000, 1),
+            SubtensorModule::NetworkAdded(3, 1),
+        ],
+    )
+    .await;
+
+    let event = next(&mut by_event).await;
+    assert_eq!(
+        (event.index, event.variant.as_str()),
+        (1, "PendingEmission")
+    );
+
+    let first = next(&mut by_netuid).await;
+    let second = next(&mut by_netuid).await;
+    assert_eq!(
+        [(first.index, first.netuid), (second.index, second.netuid)],
+        [(1, Some(3)), (3, Some(3))]
+    );
+
+    // Nothing else was streamed to either subscriber.
+    publish(&events, 8, vec![SubtensorModule::NetworkAdded(3, 2)]).await;
+    assert_eq!(next(&mut by_netuid).await.block_number, 8);
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), by_event.message())
+            .await
+            .is_err()
+    );
+}
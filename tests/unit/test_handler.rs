@@ -17,8 +17,11 @@
 #[path = "../common/mod.rs"]
 mod common;
 use common::*;
+use flamewire_bittensor_indexer::filters;
 use flamewire_bittensor_indexer::handler::{Context, EventFilter, Handler};
 use flamewire_bittensor_indexer::types::ChainEvent;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
 use subxt::config::substrate::SubstrateConfig;
 use subxt::events::Phase;
 use subxt::utils::H256;
@@ -30,6 +33,9 @@ async fn event_filter_matches() {
     assert!(!EventFilter::pallet("A").matches("B", "C"));
     assert!(EventFilter::event("A", "B").matches("A", "B"));
     assert!(!EventFilter::event("A", "B").matches("A", "C"));
+    assert!(filters::balances::TRANSFER.matches("Balances", "Transfer"));
+    assert!(!filters::balances::TRANSFER.matches("Balances", "Deposit"));
+    assert!(filters::subtensor::pallet().matches("SubtensorModule", "StakeAdded"));
 }
 
 #[tokio::test]
@@ -40,7 +46,9 @@ async fn handler_flow() {
         vec![EventRecord::new(Phase::Initialization, TestEvent::A(1))],
     );
     let handler = MockHandler::new(EventFilter::all());
-    let ctx = Context::<SubstrateConfig>::new(1, H256::zero());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
 
     let ces: Vec<ChainEvent<SubstrateConfig>> = evs
         .iter()
@@ -66,7 +74,9 @@ async fn handler_error_path() {
     );
     let mut handler = MockHandler::new(EventFilter::all());
     handler.fail = true;
-    let ctx = Context::<SubstrateConfig>::new(2, H256::zero());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(2)
+        .build();
 
     for (index, ev) in evs.iter().enumerate() {
         let ev = ev.unwrap();
@@ -80,3 +90,204 @@ async fn handler_error_path() {
 
     assert!(!handler.errors.lock().unwrap().is_empty());
 }
+
+#[test]
+fn event_indices_are_sequential_per_block() {
+    let records = || {
+        vec![
+            EventRecord::new(Phase::Initialization, TestEvent::A(1)),
+            EventRecord::new(Phase::ApplyExtrinsic(0), TestEvent::B(true)),
+            EventRecord::new(Phase::Finalization, TestEvent::A(2)),
+        ]
+    };
+    for _block in 0..2 {
+        let evs = events(test_metadata::<TestEvent>(), records());
+        let indices: Vec<u32> = ChainEvent::<SubstrateConfig>::from_events(&evs)
+            .map(|event| event.unwrap().index())
+            .collect();
+        assert_eq!(indices, [0, 1, 2]);
+    }
+}
+
+#[test]
+fn chain_event_exposes_phase_and_topics() {
+    let topic = H256::repeat_byte(7);
+    let evs = events(
+        test_metadata::<TestEvent>(),
+        vec![
+            EventRecord::new(Phase::Initialization, TestEvent::A(1)),
+            EventRecord::new(Phase::ApplyExtrinsic(3), TestEvent::B(true)).with_topics(vec![topic]),
+        ],
+    );
+    let ces: Vec<ChainEvent<SubstrateConfig>> = ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect();
+
+    assert_eq!(ces[0].phase(), Phase::Initialization);
+    assert!(ces[0].topics().is_empty());
+    assert_eq!(ces[1].phase(), Phase::ApplyExtrinsic(3));
+    assert_eq!(ces[1].topics(), [topic]);
+}
+
+#[test]
+fn chain_event_and_context_debug() {
+    let evs = events(
+        test_metadata::<TestEvent>(),
+        vec![
+            EventRecord::new(Phase::Initialization, TestEvent::A(1)),
+            EventRecord::new(Phase::ApplyExtrinsic(1), TestEvent::B(true)),
+        ],
+    );
+    let ces: Vec<ChainEvent<SubstrateConfig>> = ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect();
+    assert_eq!(
+        format!("{:?}", ces[1]),
+        r#"ChainEvent { pallet: "Test", variant: "B", index: 1, field_bytes: 1 }"#
+    );
+
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(5)
+        .pipeline_data("zeta", 1u32)
+        .pipeline_data("alpha", NotDebug)
+        .build();
+    assert_eq!(
+        format!("{ctx:?}"),
+        format!(
+            r#"Context {{ block_number: 5, block_hash: {:?}, pipeline_keys: ["alpha", "zeta"] }}"#,
+            H256::zero()
+        )
+    );
+}
+
+struct NotDebug;
+
+#[test]
+fn context_builder_sets_fields() {
+    let author = subxt::utils::AccountId32([7; 32]);
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(9)
+        .block_hash(H256::repeat_byte(9))
+        .block_author(author.clone())
+        .timestamp(1_700_000_000_000)
+        .pipeline_data("transfer", 5u128)
+        .build();
+
+    assert_eq!(ctx.block_number, 9);
+    assert_eq!(ctx.block_hash, H256::repeat_byte(9));
+    assert_eq!(ctx.block_author(), Some(&author));
+    assert_eq!(ctx.timestamp(), Some(1_700_000_000_000));
+    assert!(ctx.storage().is_none());
+    assert_eq!(ctx.pipeline_keys(), vec!["transfer"]);
+    assert_eq!(ctx.get_pipeline_data::<u128>("transfer"), Some(5));
+    assert!(ctx.pipeline_keys().is_empty());
+}
+
+#[test]
+fn context_builder_defaults_to_block_zero() {
+    let ctx = Context::<SubstrateConfig>::builder().build();
+    assert_eq!(ctx.block_number, 0);
+    assert_eq!(ctx.block_hash, H256::zero());
+    assert_eq!(ctx.timestamp(), None);
+    assert!(ctx.pipeline_keys().is_empty());
+}
+
+#[test]
+fn owned_summary_outlives_the_event() {
+    let summary = {
+        let evs = events(
+            test_metadata::<TestEvent>(),
+            vec![EventRecord::new(Phase::ApplyExtrinsic(2), TestEvent::A(9))],
+        );
+        let event = ChainEvent::<SubstrateConfig>::from_events(&evs)
+            .next()
+            .unwrap()
+            .unwrap();
+        event.to_owned_summary()
+    };
+    let copy = summary.clone();
+    assert_eq!(copy, summary);
+    assert_eq!(summary.pallet, "Test");
+    assert_eq!(summary.variant, "A");
+    assert_eq!(summary.index, 0);
+    assert_eq!(summary.phase, Phase::ApplyExtrinsic(2));
+    assert_eq!(summary.field_bytes, [9]);
+}
+
+#[test]
+fn chain_event_decodes_as_root_event() {
+    let evs = events(
+        test_metadata::<TestEvent>(),
+        vec![
+            EventRecord::new(Phase::Initialization, TestEvent::A(1)),
+            EventRecord::new(Phase::ApplyExtrinsic(0), TestEvent::B(true)),
+        ],
+    );
+    let decoded: Vec<TestAllEvents<TestEvent>> = ChainEvent::<SubstrateConfig>::from_events(&evs)
+        .map(|event| event.unwrap().as_root_event().unwrap())
+        .collect();
+    assert_eq!(
+        decoded,
+        [
+            TestAllEvents::Test(TestEvent::A(1)),
+            TestAllEvents::Test(TestEvent::B(true)),
+        ]
+    );
+}
+
+#[derive(Encode, Decode, TypeInfo)]
+enum SubnetEvent {
+    WeightsSet { netuid: u16, uid: u16 },
+    Burned { amount: u64 },
+    Positional(u16),
+}
+
+#[test]
+fn field_filter_matches_netuid() {
+    let evs = events(
+        pallet_metadata::<SubnetEvent>("SubtensorModule"),
+        vec![
+            EventRecord::new(
+                Phase::ApplyExtrinsic(0),
+                SubnetEvent::WeightsSet { netuid: 18, uid: 1 },
+            ),
+            EventRecord::new(
+                Phase::ApplyExtrinsic(0),
+                SubnetEvent::WeightsSet { netuid: 3, uid: 1 },
+            ),
+            EventRecord::new(Phase::ApplyExtrinsic(0), SubnetEvent::Burned { amount: 18 }),
+            EventRecord::new(Phase::ApplyExtrinsic(0), SubnetEvent::Positional(18)),
+        ],
+    );
+    let ces: Vec<ChainEvent<SubstrateConfig>> = ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect();
+
+    let subnet_18 = filters::subtensor::pallet().with_field_u16("netuid", 18);
+    let matched: Vec<bool> = ces.iter().map(|ce| subnet_18.matches_event(ce)).collect();
+    assert_eq!(matched, [true, false, false, false]);
+
+    let weights_3 = EventFilter::event("SubtensorModule", "WeightsSet").with_field_u16("netuid", 3);
+    assert!(!weights_3.matches_event(&ces[0]));
+    assert!(weights_3.matches_event(&ces[1]));
+
+    let other_pallet = filters::balances::pallet().with_field_u16("netuid", 18);
+    assert!(ces.iter().all(|ce| !other_pallet.matches_event(ce)));
+}
+
+#[test]
+fn field_filter_skips_decoding_unless_names_match() {
+    let subnet_18 = EventFilter::pallet("SubtensorModule").with_field_u16("netuid", 18);
+    assert!(!subnet_18.matches_with("Balances", "Transfer", |_| panic!("fields decoded")));
+    assert!(EventFilter::all().matches_with("Balances", "Transfer", |_| panic!("fields decoded")));
+
+    let mut lookups = Vec::new();
+    assert!(
+        subnet_18.matches_with("SubtensorModule", "WeightsSet", |name| {
+            lookups.push(name.to_string());
+            Some(18)
+        })
+    );
+    assert!(!subnet_18.matches_with("SubtensorModule", "WeightsSet", |_| None));
+    assert_eq!(lookups, ["netuid"]);
+}
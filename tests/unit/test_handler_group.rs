@@ -20,12 +20,14 @@ use async_trait::async_trait;
 use common::*;
 use flamewire_bittensor_indexer::handler::{Context, EventFilter, Handler};
 use flamewire_bittensor_indexer::handler_group::HandlerGroup;
-use flamewire_bittensor_indexer::{ChainEvent, IndexerError};
+use flamewire_bittensor_indexer::retry::{BreakerState, CircuitBreakerConfig};
+use flamewire_bittensor_indexer::{ChainEvent, ChainExtrinsic, IndexerError};
+use scale_info::TypeInfo;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use subxt::config::substrate::SubstrateConfig;
 use subxt::events::Phase;
-use subxt::utils::H256;
 use tokio::time::sleep;
 
 struct TestHandler {
@@ -34,6 +36,8 @@ struct TestHandler {
     errors: Arc<Mutex<Vec<String>>>,
     delay: Duration,
     fail_event: bool,
+    panic_event: bool,
+    panic_error: bool,
     set_data: Option<(&'static str, u32)>,
     get_data: Option<&'static str>,
 }
@@ -50,6 +54,8 @@ impl TestHandler {
             errors,
             delay: Duration::from_millis(0),
             fail_event: false,
+            panic_event: false,
+            panic_error: false,
             set_data: None,
             get_data: None,
         }
@@ -62,6 +68,14 @@ impl TestHandler {
         self.fail_event = true;
         self
     }
+    fn panic_event(mut self) -> Self {
+        self.panic_event = true;
+        self
+    }
+    fn panic_error(mut self) -> Self {
+        self.panic_error = true;
+        self
+    }
     fn set_data(mut self, key: &'static str, val: u32) -> Self {
         self.set_data = Some((key, val));
         self
@@ -97,11 +111,16 @@ impl Handler<SubstrateConfig> for TestHandler {
                 .unwrap()
                 .push(format!("{}-data:{:?}", self.id, data));
         }
+        if self.panic_event {
+            panic!("boom in {}", self.id);
+        }
         if self.fail_event {
             return Err(IndexerError::HandlerFailed {
                 handler: self.id.into(),
                 block: ctx.block_number,
+                event_index: None,
                 source: Box::new(std::io::Error::other("fail")),
+                retryable: false,
             });
         }
         Ok(())
@@ -119,8 +138,23 @@ impl Handler<SubstrateConfig> for TestHandler {
         Ok(())
     }
 
+    async fn handle_extrinsic(
+        &self,
+        extrinsic: &ChainExtrinsic,
+        _ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("extrinsic-{}-{}", self.id, extrinsic.call_name()));
+        Ok(())
+    }
+
     async fn handle_error(&self, error: &IndexerError, _ctx: &Context<SubstrateConfig>) {
         self.errors.lock().unwrap().push(format!("{error}"));
+        if self.panic_error {
+            panic!("boom handling an error in {}", self.id);
+        }
     }
 }
 
@@ -137,7 +171,9 @@ async fn test_sequential_execution_order() {
         .add(TestHandler::new("1", log.clone(), errs.clone()))
         .add(TestHandler::new("2", log.clone(), errs.clone()))
         .add(TestHandler::new("3", log.clone(), errs.clone()));
-    let ctx = Context::<SubstrateConfig>::new(1, H256::zero());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
     let chain_events: Vec<ChainEvent<SubstrateConfig>> = evs
         .iter()
         .enumerate()
@@ -167,7 +203,9 @@ async fn test_tolerant_mode_continues() {
     let group = HandlerGroup::new()
         .add(TestHandler::new("1", log.clone(), errs.clone()).fail_event())
         .add(TestHandler::new("2", log.clone(), errs.clone()));
-    let ctx = Context::<SubstrateConfig>::new(1, H256::zero());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
     for (index, ev) in evs.iter().enumerate() {
         let ev = ev.unwrap();
         group
@@ -180,6 +218,69 @@ async fn test_tolerant_mode_continues() {
     assert_eq!(errs.lock().unwrap().len(), 1);
 }
 
+#[tokio::test]
+async fn test_tolerant_mode_survives_a_panicking_member() {
+    let metadata = test_metadata::<TestEvent>();
+    let evs = events(
+        metadata,
+        vec![EventRecord::new(Phase::Initialization, TestEvent::A(1))],
+    );
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+    for group in [HandlerGroup::new(), HandlerGroup::parallel()] {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let errs = Arc::new(Mutex::new(Vec::new()));
+        let group = group
+            .add(TestHandler::new("1", log.clone(), errs.clone()).panic_event())
+            .add(TestHandler::new("2", log.clone(), errs.clone()));
+        let ev = evs.iter().next().unwrap().unwrap();
+        group
+            .handle_event(&ChainEvent::new(ev, 0), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(*log.lock().unwrap(), ["event-1", "event-2"]);
+        let errs = errs.lock().unwrap();
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].contains("panicked: boom in 1"), "{}", errs[0]);
+    }
+}
+
+#[tokio::test]
+async fn test_tolerant_mode_survives_a_panicking_error_hook() {
+    let metadata = test_metadata::<TestEvent>();
+    let evs = events(
+        metadata,
+        vec![EventRecord::new(Phase::Initialization, TestEvent::A(1))],
+    );
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+    for group in [HandlerGroup::new(), HandlerGroup::parallel()] {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let errs = Arc::new(Mutex::new(Vec::new()));
+        let group = group
+            .add(
+                TestHandler::new("1", log.clone(), errs.clone())
+                    .fail_event()
+                    .panic_error(),
+            )
+            .add(TestHandler::new("2", log.clone(), errs.clone()));
+        let ev = evs.iter().next().unwrap().unwrap();
+        group
+            .handle_event(&ChainEvent::new(ev, 0), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(*log.lock().unwrap(), ["event-1", "event-2"]);
+        assert_eq!(errs.lock().unwrap().len(), 1);
+
+        // The group's own hook reaches every member past the panicking one.
+        let error = IndexerError::BlockNotFound { block: 1 };
+        group.handle_error(&error, &ctx).await;
+        assert_eq!(errs.lock().unwrap().len(), 3);
+    }
+}
+
 #[tokio::test]
 async fn test_strict_mode_stops_on_error() {
     let metadata = test_metadata::<TestEvent>();
@@ -193,7 +294,9 @@ async fn test_strict_mode_stops_on_error() {
         .strict()
         .add(TestHandler::new("1", log.clone(), errs.clone()).fail_event())
         .add(TestHandler::new("2", log.clone(), errs.clone()));
-    let ctx = Context::<SubstrateConfig>::new(1, H256::zero());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
     let ev = evs.iter().next().unwrap().unwrap();
     let res = group.handle_event(&ChainEvent::new(ev, 0), &ctx).await;
     assert!(res.is_err());
@@ -213,7 +316,9 @@ async fn test_parallel_performance() {
         let ev = evs.iter().next().unwrap().unwrap();
         ChainEvent::new(ev, 0)
     };
-    let ctx = Context::<SubstrateConfig>::new(1, H256::zero());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
     let log_seq = Arc::new(Mutex::new(Vec::new()));
     let err_seq = Arc::new(Mutex::new(Vec::new()));
     let seq_group = HandlerGroup::new()
@@ -260,7 +365,9 @@ async fn test_parallel_error_collection() {
     let group = HandlerGroup::parallel()
         .add(TestHandler::new("1", log.clone(), errs1.clone()).fail_event())
         .add(TestHandler::new("2", log.clone(), errs2.clone()).fail_event());
-    let ctx = Context::<SubstrateConfig>::new(1, H256::zero());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
     let ev = evs.iter().next().unwrap().unwrap();
     group
         .handle_event(&ChainEvent::new(ev, 0), &ctx)
@@ -282,7 +389,9 @@ async fn test_pipeline_data_flow() {
     let h1 = TestHandler::new("1", log.clone(), errs.clone()).set_data("num", 42);
     let h2 = TestHandler::new("2", log.clone(), errs.clone()).get_data("num");
     let group = HandlerGroup::new().add(h1).pipe_to(h2);
-    let ctx = Context::<SubstrateConfig>::new(1, H256::zero());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
     let ev = evs.iter().next().unwrap().unwrap();
     group
         .handle_event(&ChainEvent::new(ev, 0), &ctx)
@@ -315,7 +424,9 @@ async fn test_conditional_handler() {
             e.variant_name() == "A"
         })
         .add(uncond);
-    let ctx = Context::<SubstrateConfig>::new(1, H256::zero());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
     let ev = ev_a.iter().next().unwrap().unwrap();
     group
         .handle_event(&ChainEvent::new(ev, 0), &ctx)
@@ -344,3 +455,209 @@ async fn test_conditional_handler() {
         0
     );
 }
+
+/// Fails the first two of every four saves, like the `error_handling` example.
+struct FlakySaver {
+    attempts: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Handler<SubstrateConfig> for FlakySaver {
+    async fn handle_event(
+        &self,
+        _event: &ChainEvent<SubstrateConfig>,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        let attempt = self.attempts.fetch_add(1, Ordering::SeqCst);
+        if attempt % 4 < 2 {
+            return Err(IndexerError::HandlerFailed {
+                handler: "FlakySaver".into(),
+                block: ctx.block_number,
+                event_index: None,
+                source: Box::new(std::io::Error::other("Database connection timeout")),
+                retryable: false,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn breaker_config(cooldown: Duration) -> CircuitBreakerConfig {
+    CircuitBreakerConfig {
+        threshold: 2,
+        cooldown,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_breaker_skips_events_while_open() {
+    let metadata = test_metadata::<TestEvent>();
+    let evs = events(
+        metadata,
+        vec![EventRecord::new(Phase::Initialization, TestEvent::A(1))],
+    );
+    let ev = ChainEvent::new(evs.iter().next().unwrap().unwrap(), 0);
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let errs = Arc::new(Mutex::new(Vec::new()));
+    let group = HandlerGroup::new()
+        .strict()
+        .add_with_breaker(
+            FlakySaver {
+                attempts: attempts.clone(),
+            },
+            breaker_config(Duration::from_millis(30)),
+        )
+        .add(TestHandler::new("after", log.clone(), errs.clone()));
+    let breaker = group.breakers()[0].clone();
+
+    for block in 0..2 {
+        let ctx = Context::<SubstrateConfig>::builder()
+            .block_number(block)
+            .build();
+        assert!(group.handle_event(&ev, &ctx).await.is_err());
+    }
+    assert!(matches!(breaker.state(), BreakerState::Open { .. }));
+
+    // Open breaker: events are skipped without failing the strict group.
+    for block in 2..7 {
+        let ctx = Context::<SubstrateConfig>::builder()
+            .block_number(block)
+            .build();
+        group.handle_event(&ev, &ctx).await.unwrap();
+    }
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert_eq!(breaker.skipped(), 5);
+    assert_eq!(log.lock().unwrap().len(), 5);
+
+    // After the cooldown the probe succeeds and closes the breaker.
+    sleep(Duration::from_millis(40)).await;
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(7)
+        .build();
+    group.handle_event(&ev, &ctx).await.unwrap();
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(breaker.state(), BreakerState::Closed);
+    assert_eq!(breaker.stats().opens, 1);
+}
+
+#[tokio::test]
+async fn test_breaker_skips_block_handling_while_open() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let errs = Arc::new(Mutex::new(Vec::new()));
+    let group = HandlerGroup::new().add_with_breaker(
+        TestHandler::new("1", log.clone(), errs.clone()).fail_event(),
+        breaker_config(Duration::from_secs(60)),
+    );
+    let metadata = test_metadata::<TestEvent>();
+    let evs = events(
+        metadata,
+        vec![EventRecord::new(Phase::Initialization, TestEvent::A(1))],
+    );
+    let ev = ChainEvent::new(evs.iter().next().unwrap().unwrap(), 0);
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+    for _ in 0..3 {
+        group.handle_event(&ev, &ctx).await.unwrap();
+    }
+    group.handle_block(&ctx, &[]).await.unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec!["event-1", "event-1"]);
+    assert_eq!(errs.lock().unwrap().len(), 2);
+    assert_eq!(group.breakers()[0].skipped(), 1);
+}
+
+#[tokio::test]
+async fn conditional_handler_filters_on_phase() {
+    let evs = events(
+        test_metadata::<TestEvent>(),
+        vec![
+            EventRecord::new(Phase::Initialization, TestEvent::A(1)),
+            EventRecord::new(Phase::ApplyExtrinsic(0), TestEvent::A(2)),
+        ],
+    );
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let errs = Arc::new(Mutex::new(Vec::new()));
+    let group = HandlerGroup::new().add_conditional(
+        TestHandler::new("init", log.clone(), errs.clone()),
+        |e: &ChainEvent<SubstrateConfig>| e.phase() == Phase::Initialization,
+    );
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+    for ev in ChainEvent::from_events(&evs) {
+        group.handle_event(&ev.unwrap(), &ctx).await.unwrap();
+    }
+    assert_eq!(*log.lock().unwrap(), ["event-init"]);
+}
+
+#[allow(non_camel_case_types, dead_code)]
+#[derive(TypeInfo)]
+enum TestCall {
+    noop,
+}
+
+#[tokio::test]
+async fn extrinsics_reach_every_handler() {
+    let extrinsics = ChainExtrinsic::decode_all::<SubstrateConfig>(
+        vec![unsigned_extrinsic(&[0, 0])],
+        call_metadata::<TestCall>("Test", 0),
+    )
+    .unwrap();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let errs = Arc::new(Mutex::new(Vec::new()));
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+
+    let group = HandlerGroup::new()
+        .add(TestHandler::new("1", log.clone(), errs.clone()))
+        .add_conditional(TestHandler::new("2", log.clone(), errs.clone()), |_| false);
+    group.handle_extrinsic(&extrinsics[0], &ctx).await.unwrap();
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["extrinsic-1-noop", "extrinsic-2-noop"]
+    );
+}
+
+/// Counts its shutdowns, failing them with `fail`.
+struct Stopping {
+    fail: bool,
+    stopped: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Handler<SubstrateConfig> for Stopping {
+    async fn on_reorg(&self, _from: u64, _to: u64) -> Result<(), IndexerError> {
+        self.on_shutdown().await
+    }
+
+    async fn on_shutdown(&self) -> Result<(), IndexerError> {
+        self.stopped.fetch_add(1, Ordering::SeqCst);
+        if self.fail {
+            return Err(IndexerError::handler_failed("stopping", 0, "flush failed"));
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn failed_member_does_not_stop_the_others_shutting_down() {
+    let stopped = Arc::new(AtomicUsize::new(0));
+    let group = HandlerGroup::<SubstrateConfig>::new()
+        .add(Stopping {
+            fail: true,
+            stopped: stopped.clone(),
+        })
+        .add(Stopping {
+            fail: false,
+            stopped: stopped.clone(),
+        });
+
+    assert!(group.on_shutdown().await.is_err());
+    assert_eq!(stopped.load(Ordering::SeqCst), 2);
+    assert!(group.on_reorg(3, 4).await.is_err());
+    assert_eq!(stopped.load(Ordering::SeqCst), 4);
+}
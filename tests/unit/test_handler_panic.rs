@@ -0,0 +1,148 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use async_trait::async_trait;
+use common::*;
+use flamewire_bittensor_indexer::testing::{
+    run_handlers, run_handlers_with, HandlerCall, TestChain,
+};
+use flamewire_bittensor_indexer::{
+    ChainEvent, Context, Handler, IndexerBuilder, IndexerConfig, IndexerError,
+};
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+
+fn chain() -> TestChain<TestEvent> {
+    let mut chain = TestChain::new("Test");
+    for n in 1..=3 {
+        chain.add_block(vec![EventRecord::new(
+            Phase::ApplyExtrinsic(0),
+            TestEvent::A(n),
+        )]);
+    }
+    chain
+}
+
+/// Panics on every event, or on every block with `in_block`.
+struct Panicking {
+    in_block: bool,
+}
+
+#[async_trait]
+impl Handler<SubstrateConfig> for Panicking {
+    fn name(&self) -> &str {
+        "panicking"
+    }
+
+    async fn handle_event(
+        &self,
+        _event: &ChainEvent<SubstrateConfig>,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        if !self.in_block {
+            panic!("boom at {}", ctx.block_number);
+        }
+        Ok(())
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<SubstrateConfig>,
+        _events: &[ChainEvent<SubstrateConfig>],
+    ) -> Result<(), IndexerError> {
+        if self.in_block {
+            panic!("boom at {}", ctx.block_number);
+        }
+        Ok(())
+    }
+}
+
+struct Quiet;
+
+#[async_trait]
+impl Handler<SubstrateConfig> for Quiet {}
+
+#[tokio::test]
+async fn panics_become_handler_errors() {
+    let run = run_handlers(
+        &chain(),
+        vec![Box::new(Panicking { in_block: false }), Box::new(Quiet)],
+    )
+    .await;
+
+    run.result.unwrap();
+    assert_eq!(
+        run.handler(0).errors(),
+        vec![
+            "Handler panicking failed at block 1, event 0: panicked: boom at 1",
+            "Handler panicking failed at block 2, event 0: panicked: boom at 2",
+            "Handler panicking failed at block 3, event 0: panicked: boom at 3",
+        ]
+    );
+    assert_eq!(run.handler(1).events().len(), 3);
+}
+
+#[tokio::test]
+async fn panics_count_against_the_error_budget() {
+    let run = run_handlers_with(
+        &chain(),
+        IndexerBuilder::new().handler_error_budget(1),
+        vec![Box::new(Panicking { in_block: true })],
+    )
+    .await;
+
+    assert!(matches!(
+        run.result,
+        Err(IndexerError::ErrorBudgetExhausted {
+            errors: 2,
+            budget: 1
+        })
+    ));
+    assert_eq!(run.handler(0).blocks(), vec![1, 2]);
+}
+
+#[tokio::test]
+async fn failed_run_shuts_down_every_handler() {
+    let run = run_handlers_with(
+        &chain(),
+        IndexerBuilder::new().handler_error_budget(0),
+        vec![Box::new(Panicking { in_block: true }), Box::new(Quiet)],
+    )
+    .await;
+
+    assert!(run.result.is_err());
+    for handler in run.handlers() {
+        assert_eq!(handler.calls.last(), Some(&HandlerCall::Shutdown));
+    }
+}
+
+#[tokio::test]
+#[should_panic(expected = "boom at 1")]
+async fn abort_on_panic_unwinds() {
+    run_handlers_with(
+        &chain(),
+        IndexerBuilder::new().abort_on_panic(true),
+        vec![Box::new(Panicking { in_block: false })],
+    )
+    .await;
+}
+
+#[test]
+fn panics_are_caught_by_default() {
+    assert!(!IndexerConfig::default().abort_on_panic);
+}
@@ -0,0 +1,174 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use async_trait::async_trait;
+use common::*;
+use flamewire_bittensor_indexer::handler::{Context, Handler};
+use flamewire_bittensor_indexer::testing::{
+    run_handlers, run_handlers_with, HandlerCall, TestChain,
+};
+use flamewire_bittensor_indexer::types::ChainEvent;
+use flamewire_bittensor_indexer::{EventFilter, IndexerBuilder, IndexerError, StopReason};
+use std::sync::{Arc, Mutex};
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+
+/// Blocks 1 to 3, each with `A(n)` and, in odd blocks, `B(true)`.
+fn chain() -> TestChain<TestEvent> {
+    let mut chain = TestChain::new("Test");
+    for n in 1..=3u8 {
+        let mut records = vec![EventRecord::new(Phase::Initialization, TestEvent::A(n))];
+        if n % 2 == 1 {
+            records.push(EventRecord::new(
+                Phase::ApplyExtrinsic(1),
+                TestEvent::B(true),
+            ));
+        }
+        chain.add_block(records);
+    }
+    chain
+}
+
+#[tokio::test]
+async fn handlers_see_every_block_and_their_events() {
+    let run = run_handlers(
+        &chain(),
+        vec![
+            Box::new(MockHandler::new(EventFilter::all())),
+            Box::new(MockHandler::new(EventFilter::event("Test", "B"))),
+        ],
+    )
+    .await;
+
+    let summary = run.result.unwrap();
+    assert_eq!(summary.stop_reason, StopReason::EndBlock);
+    assert_eq!(summary.blocks_processed, 3);
+    assert_eq!(run.handler(0).blocks(), vec![1, 2, 3]);
+    assert_eq!(run.handler(1).blocks(), vec![1, 2, 3]);
+    assert_eq!(
+        run.handler(0).events(),
+        vec![
+            (1, "Test.A".to_string()),
+            (1, "Test.B".to_string()),
+            (2, "Test.A".to_string()),
+            (3, "Test.A".to_string()),
+            (3, "Test.B".to_string()),
+        ]
+    );
+    assert_eq!(
+        run.handler(1).events(),
+        vec![(1, "Test.B".to_string()), (3, "Test.B".to_string())]
+    );
+    assert_eq!(run.handler(0).calls.last(), Some(&HandlerCall::Shutdown));
+}
+
+#[tokio::test]
+async fn handler_errors_are_logged() {
+    let mut failing = MockHandler::new(EventFilter::event("Test", "B"));
+    failing.fail = true;
+    let run = run_handlers(&chain(), vec![Box::new(failing)]).await;
+
+    assert!(run.result.is_ok());
+    assert_eq!(run.handler(0).errors().len(), 2);
+    assert!(matches!(
+        run.handler(0).calls[..3],
+        [
+            HandlerCall::Block(1),
+            HandlerCall::Event {
+                block: 1,
+                index: 1,
+                ..
+            },
+            HandlerCall::Error { block: 1, .. },
+        ]
+    ));
+}
+
+#[tokio::test]
+async fn builder_settings_apply() {
+    let mut failing = MockHandler::new(EventFilter::all());
+    failing.fail = true;
+    let run = run_handlers_with(
+        &chain(),
+        IndexerBuilder::new().handler_error_budget(2),
+        vec![Box::new(failing)],
+    )
+    .await;
+
+    assert!(matches!(
+        run.result,
+        Err(IndexerError::ErrorBudgetExhausted {
+            errors: 3,
+            budget: 2
+        })
+    ));
+    assert_eq!(run.handler(0).errors().len(), 3);
+}
+
+struct Extract;
+
+#[async_trait]
+impl Handler<SubstrateConfig> for Extract {
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<SubstrateConfig>,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        ctx.set_pipeline_data("variant", event.variant_name().to_string());
+        Ok(())
+    }
+}
+
+#[derive(Clone, Default)]
+struct Consume(Arc<Mutex<Vec<String>>>);
+
+#[async_trait]
+impl Handler<SubstrateConfig> for Consume {
+    async fn handle_event(
+        &self,
+        _event: &ChainEvent<SubstrateConfig>,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        if let Some(variant) = ctx.get_pipeline_data::<String>("variant") {
+            self.0.lock().unwrap().push(variant);
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn pipeline_data_reaches_later_handlers() {
+    let consume = Consume::default();
+    let run = run_handlers(&chain(), vec![Box::new(Extract), Box::new(consume.clone())]).await;
+
+    run.result.unwrap();
+    assert_eq!(*consume.0.lock().unwrap(), vec!["A", "B", "A", "A", "B"]);
+}
+
+#[tokio::test]
+async fn an_empty_chain_is_rejected() {
+    let run = run_handlers(
+        &TestChain::<TestEvent>::new("Test"),
+        vec![Box::new(MockHandler::new(EventFilter::all()))],
+    )
+    .await;
+    assert!(matches!(
+        run.result,
+        Err(IndexerError::InvalidConfig { .. })
+    ));
+}
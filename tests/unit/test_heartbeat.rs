@@ -0,0 +1,106 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::validated_types::WebSocketUrl;
+use flamewire_bittensor_indexer::{HeartbeatFile, IndexerBuilder, IndexerError};
+use std::fs::File;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use subxt::config::substrate::SubstrateConfig;
+
+fn mtime(path: &Path) -> SystemTime {
+    std::fs::metadata(path).unwrap().modified().unwrap()
+}
+
+/// Backdate the file so a later touch is visible even with coarse timestamps.
+fn backdate(path: &Path) -> SystemTime {
+    let old = SystemTime::now() - Duration::from_secs(3600);
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+    mtime(path)
+}
+
+#[test]
+fn blocks_and_intervals_touch_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("alive");
+    let mut heartbeat = HeartbeatFile::new(&path, Duration::from_secs(10));
+
+    heartbeat.on_interval(true);
+    assert!(path.exists());
+
+    let old = backdate(&path);
+    heartbeat.on_block(1);
+    assert!(mtime(&path) > old);
+
+    let old = backdate(&path);
+    heartbeat.on_interval(true);
+    assert!(mtime(&path) > old);
+}
+
+#[test]
+fn open_breaker_skips_interval_touch() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("alive");
+    let mut heartbeat = HeartbeatFile::new(&path, Duration::from_secs(10));
+
+    heartbeat.on_interval(false);
+    assert!(!path.exists());
+
+    heartbeat.on_block(1);
+    let old = backdate(&path);
+    heartbeat.on_interval(false);
+    assert_eq!(mtime(&path), old);
+}
+
+#[test]
+fn requires_progress_goes_stale_without_new_blocks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("alive");
+    let mut heartbeat = HeartbeatFile::new(&path, Duration::from_secs(10)).requires_progress(true);
+
+    heartbeat.on_interval(true);
+    assert!(!path.exists());
+
+    heartbeat.on_block(5);
+    let old = backdate(&path);
+    heartbeat.on_interval(true);
+    assert_eq!(mtime(&path), old);
+    // A replayed block after a reorg is not progress either.
+    heartbeat.on_block(4);
+    heartbeat.on_block(5);
+    assert_eq!(mtime(&path), old);
+
+    heartbeat.on_block(6);
+    assert!(mtime(&path) > old);
+}
+
+#[test]
+fn zero_heartbeat_interval_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let err = IndexerBuilder::<SubstrateConfig>::new()
+        .connect(WebSocketUrl::parse("ws://node").unwrap())
+        .heartbeat_file(dir.path().join("alive"), Duration::ZERO)
+        .validate()
+        .unwrap_err();
+    assert!(
+        matches!(err, IndexerError::InvalidConfig { field, .. } if field == "heartbeat_interval")
+    );
+}
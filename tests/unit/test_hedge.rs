@@ -0,0 +1,146 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::hedge::HedgedRpc;
+use flamewire_bittensor_indexer::IndexerError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+/// Simulated node answering after a fixed latency.
+struct MockEndpoint {
+    name: &'static str,
+    latency: Duration,
+    fail: bool,
+    started: AtomicUsize,
+    completed: AtomicUsize,
+}
+
+impl MockEndpoint {
+    fn new(name: &'static str, latency_ms: u64) -> Arc<Self> {
+        Self::build(name, latency_ms, false)
+    }
+
+    fn failing(name: &'static str, latency_ms: u64) -> Arc<Self> {
+        Self::build(name, latency_ms, true)
+    }
+
+    fn build(name: &'static str, latency_ms: u64, fail: bool) -> Arc<Self> {
+        Arc::new(Self {
+            name,
+            latency: Duration::from_millis(latency_ms),
+            fail,
+            started: AtomicUsize::new(0),
+            completed: AtomicUsize::new(0),
+        })
+    }
+
+    async fn block_hash(self: Arc<Self>) -> Result<&'static str, IndexerError> {
+        self.started.fetch_add(1, Ordering::SeqCst);
+        sleep(self.latency).await;
+        self.completed.fetch_add(1, Ordering::SeqCst);
+        if self.fail {
+            Err(IndexerError::Subxt(Box::new(subxt::Error::Other(format!(
+                "{} failed",
+                self.name
+            )))))
+        } else {
+            Ok(self.name)
+        }
+    }
+}
+
+fn hedged(
+    primary: &Arc<MockEndpoint>,
+    secondary: &Arc<MockEndpoint>,
+) -> HedgedRpc<Arc<MockEndpoint>> {
+    HedgedRpc::new(primary.clone()).with_secondary(secondary.clone(), Duration::from_millis(300))
+}
+
+#[tokio::test(start_paused = true)]
+async fn fast_primary_is_not_hedged() {
+    let primary = MockEndpoint::new("primary", 100);
+    let secondary = MockEndpoint::new("secondary", 100);
+    let rpc = hedged(&primary, &secondary);
+
+    assert_eq!(rpc.call(MockEndpoint::block_hash).await.unwrap(), "primary");
+    assert_eq!(secondary.started.load(Ordering::SeqCst), 0);
+    assert_eq!(rpc.counters().calls(), 1);
+    assert_eq!(rpc.counters().hedges(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn slow_primary_loses_to_secondary() {
+    let primary = MockEndpoint::new("primary", 4_000);
+    let secondary = MockEndpoint::new("secondary", 100);
+    let rpc = hedged(&primary, &secondary);
+
+    let start = Instant::now();
+    assert_eq!(
+        rpc.call(MockEndpoint::block_hash).await.unwrap(),
+        "secondary"
+    );
+    assert_eq!(start.elapsed(), Duration::from_millis(400));
+    assert_eq!(rpc.counters().hedges(), 1);
+    assert_eq!(rpc.counters().hedge_wins(), 1);
+
+    // The primary request was cancelled rather than left running.
+    sleep(Duration::from_secs(10)).await;
+    assert_eq!(primary.started.load(Ordering::SeqCst), 1);
+    assert_eq!(primary.completed.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn primary_can_still_win_after_hedging() {
+    let primary = MockEndpoint::new("primary", 350);
+    let secondary = MockEndpoint::new("secondary", 4_000);
+    let rpc = hedged(&primary, &secondary);
+
+    assert_eq!(rpc.call(MockEndpoint::block_hash).await.unwrap(), "primary");
+    assert_eq!(rpc.counters().hedges(), 1);
+    assert_eq!(rpc.counters().hedge_wins(), 0);
+    assert_eq!(secondary.completed.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn failed_answer_waits_for_the_other_endpoint() {
+    let primary = MockEndpoint::failing("primary", 350);
+    let secondary = MockEndpoint::new("secondary", 1_000);
+    let rpc = hedged(&primary, &secondary);
+    assert_eq!(
+        rpc.call(MockEndpoint::block_hash).await.unwrap(),
+        "secondary"
+    );
+    assert_eq!(rpc.counters().hedge_wins(), 1);
+
+    let primary = MockEndpoint::failing("primary", 500);
+    let secondary = MockEndpoint::failing("secondary", 100);
+    let rpc = hedged(&primary, &secondary);
+    let err = rpc.call(MockEndpoint::block_hash).await.unwrap_err();
+    assert!(err.to_string().contains("primary failed"));
+    assert_eq!(rpc.counters().hedge_wins(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn without_secondary_calls_only_primary() {
+    let primary = MockEndpoint::new("primary", 4_000);
+    let rpc = HedgedRpc::new(primary.clone());
+
+    assert_eq!(rpc.call(MockEndpoint::block_hash).await.unwrap(), "primary");
+    assert_eq!(rpc.counters().hedges(), 0);
+    assert_eq!(primary.started.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,277 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::http_api::{StatusServer, StatusSource};
+use flamewire_bittensor_indexer::queue::QueueGauge;
+use flamewire_bittensor_indexer::retry::CircuitBreaker;
+use flamewire_bittensor_indexer::storage::Checkpoint;
+use flamewire_bittensor_indexer::EventBroadcast;
+#[cfg(feature = "metrics")]
+use flamewire_bittensor_indexer::IndexerMetrics;
+use futures::{SinkExt, StreamExt};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+struct Fixture {
+    checkpoint: watch::Sender<Option<Checkpoint>>,
+    rpc_breaker: Arc<CircuitBreaker>,
+    source: StatusSource,
+}
+
+fn fixture() -> Fixture {
+    let checkpoint = watch::Sender::new(None);
+    let rpc_breaker = Arc::new(CircuitBreaker::new(1, Duration::from_secs(60)));
+    let source = StatusSource::new(
+        checkpoint.subscribe(),
+        rpc_breaker.clone(),
+        Arc::new(CircuitBreaker::new(1, Duration::from_secs(60))),
+        Arc::new(QueueGauge::new(16)),
+    );
+    Fixture {
+        checkpoint,
+        rpc_breaker,
+        source,
+    }
+}
+
+async fn get(server: &StatusServer, path: &str) -> (StatusCode, String) {
+    let response = reqwest::get(format!("http://{}{path}", server.local_addr()))
+        .await
+        .unwrap();
+    (response.status(), response.text().await.unwrap())
+}
+
+async fn get_json(server: &StatusServer, path: &str) -> (StatusCode, Value) {
+    let (status, body) = get(server, path).await;
+    (status, serde_json::from_str(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_checkpoint_follows_stored_value() {
+    let fixture = fixture();
+    let server = StatusServer::bind("127.0.0.1:0".parse().unwrap(), fixture.source)
+        .await
+        .unwrap();
+
+    let (status, _) = get_json(&server, "/checkpoint").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    fixture.checkpoint.send_replace(Some(Checkpoint {
+        block: 42,
+        updated_at: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_752_116_400)),
+    }));
+    let (status, body) = get_json(&server, "/checkpoint").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["block"], 42);
+    assert_eq!(body["updated_at"], "2025-07-10T03:00:00.000Z");
+}
+
+#[tokio::test]
+async fn test_status_answers_while_breaker_is_open() {
+    let fixture = fixture();
+    fixture.checkpoint.send_replace(Some(Checkpoint::new(7)));
+    fixture.rpc_breaker.record_failure();
+    assert!(fixture.rpc_breaker.is_open());
+    let server = StatusServer::bind("127.0.0.1:0".parse().unwrap(), fixture.source)
+        .await
+        .unwrap();
+
+    let (status, body) = get_json(&server, "/status").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["checkpoint"], 7);
+    assert_eq!(body["stale"], false);
+    assert_eq!(body["rpc_breaker"]["state"], "open");
+    assert_eq!(body["rpc_breaker"]["opens"], 1);
+    assert_eq!(body["storage_breaker"]["state"], "closed");
+    assert_eq!(body["queue_depth"], 0);
+    assert_eq!(body["queue_capacity"], 16);
+}
+
+#[tokio::test]
+async fn test_stale_status_is_unavailable() {
+    let fixture = fixture();
+    fixture.checkpoint.send_replace(Some(Checkpoint {
+        block: 7,
+        updated_at: Some(SystemTime::now() - Duration::from_secs(3_600)),
+    }));
+    let source = fixture.source.stale_after(Some(Duration::from_secs(60)));
+    let server = StatusServer::bind("127.0.0.1:0".parse().unwrap(), source)
+        .await
+        .unwrap();
+
+    let (status, body) = get_json(&server, "/status").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["stale"], true);
+    assert!(body["staleness"].as_str().unwrap().starts_with("1h"));
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint() {
+    let fixture = fixture();
+    #[cfg(feature = "metrics")]
+    let source = fixture
+        .source
+        .metrics(Some(Arc::new(IndexerMetrics::new().unwrap())));
+    #[cfg(not(feature = "metrics"))]
+    let source = fixture.source;
+    let server = StatusServer::bind("127.0.0.1:0".parse().unwrap(), source)
+        .await
+        .unwrap();
+
+    let (status, body) = get(&server, "/metrics").await;
+    if cfg!(feature = "metrics") {
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("indexer_blocks_processed_total 0"));
+    } else {
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_stops_server() {
+    let server = StatusServer::bind("127.0.0.1:0".parse().unwrap(), fixture().source)
+        .await
+        .unwrap();
+    let addr = server.local_addr();
+    assert_eq!(get(&server, "/status").await.0, StatusCode::OK);
+
+    server.shutdown().await;
+
+    assert!(reqwest::get(format!("http://{addr}/status")).await.is_err());
+}
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn ws_server(events: &EventBroadcast) -> StatusServer {
+    let source = fixture().source.events(events.clone());
+    StatusServer::bind("127.0.0.1:0".parse().unwrap(), source)
+        .await
+        .unwrap()
+}
+
+async fn connect(server: &StatusServer) -> Client {
+    let url = format!("ws://{}/ws", server.local_addr());
+    tokio_tungstenite::connect_async(url).await.unwrap().0
+}
+
+async fn receive(client: &mut Client) -> Message {
+    tokio::time::timeout(Duration::from_secs(5), client.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap()
+}
+
+async fn receive_json(client: &mut Client) -> Value {
+    match receive(client).await {
+        Message::Text(text) => serde_json::from_str(&text).unwrap(),
+        other => panic!("expected a text message, got {other:?}"),
+    }
+}
+
+async fn set_filter(client: &mut Client, filter: Value) -> Value {
+    client
+        .send(Message::text(filter.to_string()))
+        .await
+        .unwrap();
+    receive_json(client).await
+}
+
+#[tokio::test]
+async fn test_ws_streams_matching_events() {
+    let events = EventBroadcast::new(16);
+    let server = ws_server(&events).await;
+    let mut client = connect(&server).await;
+
+    let filter = json!({"pallet": "Balances", "event": "Transfer"});
+    assert_eq!(
+        set_filter(&mut client, filter.clone()).await,
+        json!({ "subscribed": filter })
+    );
+    publish(
+        &events,
+        7,
+        vec![Balances::Deposit { who: 3, amount: 1 }, transfer(5)],
+    )
+    .await;
+    write_block(&events, 8, vec![transfer(6)]).await;
+
+    assert_eq!(
+        receive_json(&mut client).await,
+        json!({
+            "block_number": 7,
+            "block_hash": format!("0x{}", "07".repeat(32)),
+            "index": 1,
+            "pallet": "Balances",
+            "variant": "Transfer",
+            "fields_json": {"from": 1, "to": 2, "amount": 5},
+            "netuid": null,
+        })
+    );
+    assert_eq!(receive_json(&mut client).await["block_number"], 8);
+}
+
+#[tokio::test]
+async fn test_ws_replaces_filter_and_rejects_invalid_ones() {
+    let events = EventBroadcast::new(16);
+    let server = ws_server(&events).await;
+    let mut client = connect(&server).await;
+
+    let reply = set_filter(&mut client, json!({"pallet": 5})).await;
+    assert!(reply["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("invalid filter"));
+
+    set_filter(&mut client, json!({"event": "Transfer"})).await;
+    set_filter(&mut client, json!({"event": "Deposit"})).await;
+    publish(
+        &events,
+        7,
+        vec![transfer(5), Balances::Deposit { who: 3, amount: 1 }],
+    )
+    .await;
+
+    assert_eq!(receive_json(&mut client).await["variant"], "Deposit");
+}
+
+#[tokio::test]
+async fn test_ws_disconnects_slow_client() {
+    let events = EventBroadcast::new(1);
+    let server = ws_server(&events).await;
+    let mut client = connect(&server).await;
+    set_filter(&mut client, json!({})).await;
+
+    // The single-threaded test runtime lets the server read the channel only
+    // once all three events are published, by which time it missed two.
+    write_block(&events, 7, vec![transfer(1), transfer(2), transfer(3)]).await;
+
+    match receive(&mut client).await {
+        Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Policy),
+        other => panic!("expected a close frame, got {other:?}"),
+    }
+    assert_eq!(events.dropped(), 2);
+}
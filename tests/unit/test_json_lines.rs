@@ -0,0 +1,179 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::handler::{EventFilter, Handler};
+use flamewire_bittensor_indexer::json_lines::JsonLinesHandler;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use subxt::config::substrate::SubstrateConfig;
+
+/// Shared buffer standing in for stdout.
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Buffer {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+fn lines(path: &Path) -> Vec<Value> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn hash(block: u8) -> String {
+    format!("0x{}", format!("{block:02x}").repeat(32))
+}
+
+#[tokio::test]
+async fn test_envelopes_and_block_records_one_per_line() {
+    let buffer = Buffer::default();
+    let handler = JsonLinesHandler::writer(buffer.clone())
+        .with_filter(EventFilter::event("Balances", "Transfer"))
+        .with_block_records();
+
+    write_block(
+        &handler,
+        7,
+        vec![Balances::Deposit { who: 3, amount: 1 }, transfer(5)],
+    )
+    .await;
+    write_block(&handler, 8, Vec::<Balances>::new()).await;
+
+    let lines: Vec<Value> = buffer
+        .contents()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            json!({
+                "block_number": 7,
+                "block_hash": hash(7),
+                "timestamp": null,
+                "index": 1,
+                "pallet": "Balances",
+                "variant": "Transfer",
+                "fields_json": {"from": 1, "to": 2, "amount": 5},
+            }),
+            json!({"block_number": 7, "block_hash": hash(7), "events": 1}),
+            json!({"block_number": 8, "block_hash": hash(8), "events": 0}),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_pretty_output_is_a_json_stream() {
+    let buffer = Buffer::default();
+    let handler = JsonLinesHandler::writer(buffer.clone()).pretty(true);
+
+    write_block(&handler, 7, vec![transfer(5), transfer(6)]).await;
+
+    let contents = buffer.contents();
+    assert!(contents.lines().count() > 2);
+    let records: Vec<Value> = serde_json::Deserializer::from_str(&contents)
+        .into_iter()
+        .map(|record| record.unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1]["fields_json"]["amount"], 6);
+}
+
+#[tokio::test]
+async fn test_files_rotate_by_block_count() {
+    let dir = tempfile::tempdir().unwrap();
+    let handler = JsonLinesHandler::file(dir.path().join("out/events.ndjson")).rotate_blocks(100);
+
+    write_block(&handler, 99, vec![transfer(5)]).await;
+    write_block(&handler, 100, vec![transfer(6), transfer(7)]).await;
+    write_block(&handler, 250, vec![transfer(8)]).await;
+    Handler::<SubstrateConfig>::on_shutdown(&handler)
+        .await
+        .unwrap();
+
+    let out = dir.path().join("out");
+    let mut names: Vec<_> = fs::read_dir(&out)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            "events_0000000000_0000000100.ndjson",
+            "events_0000000100_0000000200.ndjson",
+            "events_0000000200_0000000300.ndjson",
+        ]
+    );
+    let amounts = |name: &str| -> Vec<Value> {
+        lines(&out.join(name))
+            .iter()
+            .map(|line| line["fields_json"]["amount"].clone())
+            .collect()
+    };
+    assert_eq!(
+        amounts("events_0000000000_0000000100.ndjson"),
+        vec![json!(5)]
+    );
+    assert_eq!(
+        amounts("events_0000000100_0000000200.ndjson"),
+        vec![json!(6), json!(7)]
+    );
+    assert_eq!(
+        amounts("events_0000000200_0000000300.ndjson"),
+        vec![json!(8)]
+    );
+}
+
+#[tokio::test]
+async fn test_file_append_and_truncate() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("events.ndjson");
+    fs::write(&path, "{\"old\":true}\n").unwrap();
+
+    let handler = JsonLinesHandler::file(&path);
+    write_block(&handler, 7, vec![transfer(5)]).await;
+    let appended = lines(&path);
+    assert_eq!(appended.len(), 2);
+    assert_eq!(appended[0], json!({"old": true}));
+
+    let handler = JsonLinesHandler::file(&path).append(false);
+    write_block(&handler, 8, vec![transfer(6)]).await;
+    let truncated = lines(&path);
+    assert_eq!(truncated.len(), 1);
+    assert_eq!(truncated[0]["block_number"], 8);
+}
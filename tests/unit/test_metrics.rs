@@ -0,0 +1,157 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use async_trait::async_trait;
+use flamewire_bittensor_indexer::handler::{Context, Handler};
+use flamewire_bittensor_indexer::handler_group::HandlerGroup;
+use flamewire_bittensor_indexer::retry::{
+    retry_with_backoff_observed, CircuitBreaker, RetryConfig,
+};
+use flamewire_bittensor_indexer::{ChainEvent, IndexerError, IndexerMetrics, MetricsServer};
+use prometheus::Registry;
+use std::sync::Arc;
+use std::time::Duration;
+use subxt::config::substrate::SubstrateConfig;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+struct FailingHandler;
+
+#[async_trait]
+impl Handler<SubstrateConfig> for FailingHandler {
+    async fn handle_block(
+        &self,
+        ctx: &Context<SubstrateConfig>,
+        _events: &[ChainEvent<SubstrateConfig>],
+    ) -> Result<(), IndexerError> {
+        Err(IndexerError::handler_failed(
+            "flaky",
+            ctx.block_number,
+            "boom",
+        ))
+    }
+}
+
+async fn get(addr: std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes())
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[test]
+fn test_block_and_head_series() {
+    let metrics = IndexerMetrics::new().unwrap();
+    metrics.observe_head(120);
+    metrics.observe_block(100, 7, Duration::from_millis(20));
+    metrics.observe_block(101, 3, Duration::from_millis(20));
+    // An older head, e.g. from a lagging node, does not move the gauge back.
+    metrics.observe_head(110);
+
+    let text = metrics.render().unwrap();
+    assert!(text.contains("indexer_blocks_processed_total 2"));
+    assert!(text.contains("indexer_events_processed_total 10"));
+    assert!(text.contains("indexer_chain_head 120"));
+    assert!(text.contains("indexer_last_processed_block 101"));
+    assert!(text.contains("indexer_chain_lag_blocks 19"));
+    assert!(text.contains("indexer_block_processing_seconds_count 2"));
+}
+
+#[tokio::test]
+async fn test_absorbed_handler_errors_are_counted() {
+    let metrics = Arc::new(IndexerMetrics::new().unwrap());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(5)
+        .metrics(metrics.clone())
+        .build();
+    let group = HandlerGroup::new().add(FailingHandler);
+
+    group.handle_block(&ctx, &[]).await.unwrap();
+    group.handle_block(&ctx, &[]).await.unwrap();
+    metrics.record_handler_error(&IndexerError::invalid_config("field", "bad"));
+
+    let text = metrics.render().unwrap();
+    assert!(text.contains(r#"indexer_handler_errors_total{handler="flaky"} 2"#));
+    assert!(text.contains(r#"indexer_handler_errors_total{handler="unknown"} 1"#));
+}
+
+#[tokio::test]
+async fn test_breaker_and_retry_series() {
+    let metrics = IndexerMetrics::new().unwrap();
+    let breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+    metrics.watch_breaker(&breaker);
+    assert!(metrics
+        .render()
+        .unwrap()
+        .contains(r#"indexer_breaker_state{breaker="rpc"} 0"#));
+
+    let cfg = RetryConfig {
+        max_retries: 2,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(1),
+        backoff_multiplier: 1.0,
+        ..Default::default()
+    };
+    let res = retry_with_backoff_observed(
+        || async {
+            Err::<(), _>(IndexerError::ConnectionFailed {
+                url: "ws://node".into(),
+                source: Box::new(subxt::Error::Other("drop".into())),
+            })
+        },
+        &cfg,
+        &breaker,
+        &metrics,
+    )
+    .await;
+    assert!(res.is_err());
+    breaker.record_failure();
+
+    let text = metrics.render().unwrap();
+    assert!(text.contains(r#"indexer_breaker_state{breaker="rpc"} 2"#));
+    assert!(text.contains("indexer_rpc_retries_total 1"));
+    assert!(text.contains("indexer_rpc_retry_give_ups_total 1"));
+}
+
+#[test]
+fn test_shared_registry_rejects_duplicates() {
+    let registry = Registry::new();
+    let _metrics = IndexerMetrics::with_registry(registry.clone()).unwrap();
+    assert!(matches!(
+        IndexerMetrics::with_registry(registry),
+        Err(IndexerError::Metrics(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint() {
+    let metrics = Arc::new(IndexerMetrics::new().unwrap());
+    metrics.observe_block(42, 1, Duration::from_millis(5));
+    let server = MetricsServer::bind("127.0.0.1:0".parse().unwrap(), metrics)
+        .await
+        .unwrap();
+
+    let response = get(server.local_addr(), "/metrics").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("indexer_last_processed_block 42"));
+
+    let response = get(server.local_addr(), "/health").await;
+    assert!(response.starts_with("HTTP/1.1 404"));
+}
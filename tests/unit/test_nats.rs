@@ -0,0 +1,45 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::nats::{sanitize_token, NatsSinkHandler};
+use flamewire_bittensor_indexer::IndexerError;
+
+#[test]
+fn test_sanitize_token() {
+    assert_eq!(sanitize_token("Transfer"), "Transfer");
+    assert_eq!(sanitize_token("Stake.Added"), "Stake_Added");
+    assert_eq!(sanitize_token("a*b>c d\te"), "a_b_c_d_e");
+    assert_eq!(sanitize_token(""), "_");
+}
+
+#[tokio::test]
+async fn test_rejects_invalid_prefixes() {
+    // Validated before connecting, so no server is needed.
+    for prefix in [
+        "",
+        "events.",
+        "events..all",
+        "events.*",
+        "events.>",
+        "my events",
+    ] {
+        let res = NatsSinkHandler::new("nats://127.0.0.1:1", prefix).await;
+        assert!(
+            matches!(res, Err(IndexerError::InvalidConfig { ref field, .. }) if field == "subject_prefix"),
+            "{prefix:?}"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::config::IndexerConfig;
+use flamewire_bittensor_indexer::validated_types::{NodeAuth, WebSocketUrl};
+use flamewire_bittensor_indexer::{IndexerBuilder, IndexerError};
+use subxt::config::substrate::SubstrateConfig;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpListener;
+
+/// Accept one connection and return its handshake request, then hang up.
+async fn capture_handshake(listener: TcpListener) -> String {
+    let (mut socket, _) = listener.accept().await.unwrap();
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let n = socket.read(&mut buf).await.unwrap();
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+    String::from_utf8_lossy(&request).into_owned()
+}
+
+async fn handshake_with(
+    builder: impl FnOnce(u16) -> IndexerBuilder<SubstrateConfig>,
+) -> (String, IndexerError) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = tokio::spawn(capture_handshake(listener));
+    let err = builder(port).build().await.err().unwrap();
+    (server.await.unwrap(), err)
+}
+
+fn authorization(request: &str) -> Option<&str> {
+    request.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("authorization")
+            .then_some(value.trim())
+    })
+}
+
+#[test]
+fn header_values() {
+    let basic = NodeAuth::Basic {
+        username: "user".into(),
+        password: "pass".into(),
+    };
+    assert_eq!(basic.header_value(), "Basic dXNlcjpwYXNz");
+    let bearer = NodeAuth::Bearer {
+        token: "t0k3n".into(),
+    };
+    assert_eq!(bearer.header_value(), "Bearer t0k3n");
+}
+
+#[test]
+fn credentials_are_masked_in_debug_output() {
+    let url = WebSocketUrl::parse("wss://rpc.example.com")
+        .unwrap()
+        .with_basic_auth("indexer", "s3cr3t");
+    let debug = format!("{url:?}");
+    assert!(debug.contains("indexer"), "{debug}");
+    assert!(!debug.contains("s3cr3t"), "{debug}");
+
+    let config = IndexerConfig::builder()
+        .node_url("wss://rpc.example.com")
+        .node_auth(NodeAuth::Bearer {
+            token: "t0k3n".into(),
+        })
+        .build()
+        .unwrap();
+    let debug = format!("{config:?}");
+    assert!(!debug.contains("t0k3n"), "{debug}");
+}
+
+#[tokio::test]
+async fn bearer_token_is_sent_in_the_handshake() {
+    let (request, err) = handshake_with(|port| {
+        IndexerBuilder::new().connect(
+            WebSocketUrl::parse(&format!("ws://127.0.0.1:{port}"))
+                .unwrap()
+                .with_bearer_token("t0k3n"),
+        )
+    })
+    .await;
+    assert_eq!(authorization(&request), Some("Bearer t0k3n"), "{request}");
+    assert!(
+        matches!(err, IndexerError::ConnectionFailed { .. }),
+        "{err}"
+    );
+    assert!(!err.to_string().contains("t0k3n"), "{err}");
+}
+
+#[tokio::test]
+async fn basic_auth_is_sent_in_the_handshake() {
+    let (request, _) = handshake_with(|port| {
+        IndexerBuilder::new().connect(
+            WebSocketUrl::parse(&format!("ws://127.0.0.1:{port}"))
+                .unwrap()
+                .with_basic_auth("user", "pass"),
+        )
+    })
+    .await;
+    assert_eq!(
+        authorization(&request),
+        Some("Basic dXNlcjpwYXNz"),
+        "{request}"
+    );
+}
+
+#[tokio::test]
+async fn no_authorization_header_without_credentials() {
+    let (request, _) = handshake_with(|port| {
+        IndexerBuilder::new()
+            .connect(WebSocketUrl::parse(&format!("ws://127.0.0.1:{port}")).unwrap())
+    })
+    .await;
+    assert!(request.starts_with("GET "), "{request}");
+    assert_eq!(authorization(&request), None, "{request}");
+}
+
+#[tokio::test]
+async fn config_credentials_are_sent_in_the_handshake() {
+    let (request, _) = handshake_with(|port| {
+        let config = IndexerConfig::builder()
+            .node_url(format!("ws://127.0.0.1:{port}"))
+            .node_auth(NodeAuth::Bearer {
+                token: "t0k3n".into(),
+            })
+            .build()
+            .unwrap();
+        IndexerBuilder::from_config(config).unwrap()
+    })
+    .await;
+    assert_eq!(authorization(&request), Some("Bearer t0k3n"), "{request}");
+}
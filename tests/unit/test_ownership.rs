@@ -0,0 +1,211 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::handler::{Context, Handler};
+use flamewire_bittensor_indexer::presets::ownership::{
+    MemoryOwnershipStore, Ownership, OwnershipLookup, OwnershipStore, OwnershipTracker,
+    PIPELINE_KEY,
+};
+use flamewire_bittensor_indexer::{ChainEvent, IndexerError};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use std::sync::Arc;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::AccountId32;
+
+#[derive(Encode, Decode, TypeInfo)]
+enum SubtensorModule {
+    NeuronRegistered(u16, u16, AccountId32),
+    HotkeySwapped {
+        coldkey: AccountId32,
+        old_hotkey: AccountId32,
+        new_hotkey: AccountId32,
+    },
+    ColdkeySwapped {
+        old_coldkey: AccountId32,
+        new_coldkey: AccountId32,
+    },
+}
+
+fn key(byte: u8) -> AccountId32 {
+    AccountId32([byte; 32])
+}
+
+fn owned(coldkey: u8, since_block: u64) -> Option<Ownership> {
+    Some(Ownership {
+        coldkey: key(coldkey),
+        since_block,
+    })
+}
+
+async fn handle(
+    tracker: &OwnershipTracker,
+    block: u64,
+    block_events: Vec<SubtensorModule>,
+) -> Result<Context<SubstrateConfig>, IndexerError> {
+    let records = block_events
+        .into_iter()
+        .map(|event| EventRecord::new(Phase::ApplyExtrinsic(1), event))
+        .collect();
+    let evs = events(
+        pallet_metadata::<SubtensorModule>("SubtensorModule"),
+        records,
+    );
+    let ces: Vec<ChainEvent<SubstrateConfig>> = ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect();
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(block)
+        .build();
+    tracker.handle_block(&ctx, &ces).await?;
+    Ok(ctx)
+}
+
+async fn sorted(lookup: &OwnershipLookup, coldkey: u8, block: u64) -> Vec<AccountId32> {
+    let mut hotkeys = lookup.hotkeys_of(&key(coldkey), block).await.unwrap();
+    hotkeys.sort();
+    hotkeys
+}
+
+// Hotkeys are 1..=3 and coldkeys 10 and 11. Hotkeys 1 and 2 start with
+// coldkey 10 at block 5, 1 is swapped for 3 at block 10 and coldkey 10
+// becomes 11 at block 20.
+#[tokio::test]
+async fn scripted_ownership_changes_keep_history() {
+    let store = Arc::new(MemoryOwnershipStore::new());
+    store.set_owner(&key(1), Some(&key(10)), 5).await.unwrap();
+    store.set_owner(&key(2), Some(&key(10)), 5).await.unwrap();
+    let tracker = OwnershipTracker::new(store.clone());
+    let lookup = tracker.lookup_handle();
+
+    handle(
+        &tracker,
+        10,
+        vec![SubtensorModule::HotkeySwapped {
+            coldkey: key(10),
+            old_hotkey: key(1),
+            new_hotkey: key(3),
+        }],
+    )
+    .await
+    .unwrap();
+    let swap = vec![SubtensorModule::ColdkeySwapped {
+        old_coldkey: key(10),
+        new_coldkey: key(11),
+    }];
+    let ctx = handle(&tracker, 20, swap).await.unwrap();
+    // Registering a known hotkey elsewhere changes nothing, and needs no
+    // storage access.
+    handle(
+        &tracker,
+        25,
+        vec![SubtensorModule::NeuronRegistered(1, 7, key(2))],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(lookup.lookup(&key(1), 9).await.unwrap(), owned(10, 5));
+    assert_eq!(lookup.lookup(&key(1), 10).await.unwrap(), None);
+    assert_eq!(lookup.lookup(&key(3), 9).await.unwrap(), None);
+    assert_eq!(lookup.lookup(&key(3), 10).await.unwrap(), owned(10, 10));
+    assert_eq!(lookup.lookup(&key(3), 20).await.unwrap(), owned(11, 20));
+    assert_eq!(lookup.lookup(&key(2), 19).await.unwrap(), owned(10, 5));
+    assert_eq!(lookup.lookup(&key(2), 30).await.unwrap(), owned(11, 20));
+    assert_eq!(sorted(&lookup, 10, 15).await, vec![key(2), key(3)]);
+    assert!(sorted(&lookup, 10, 20).await.is_empty());
+    assert_eq!(sorted(&lookup, 11, 20).await, vec![key(2), key(3)]);
+
+    let published = ctx
+        .peek_pipeline_data::<OwnershipLookup>(PIPELINE_KEY)
+        .unwrap();
+    assert_eq!(published.lookup(&key(2), 20).await.unwrap(), owned(11, 20));
+}
+
+#[tokio::test]
+async fn reprocessing_and_reorgs_are_safe() {
+    let store = Arc::new(MemoryOwnershipStore::new());
+    store.set_owner(&key(1), Some(&key(10)), 5).await.unwrap();
+    let tracker = OwnershipTracker::new(store.clone());
+    let swap = || {
+        vec![SubtensorModule::ColdkeySwapped {
+            old_coldkey: key(10),
+            new_coldkey: key(11),
+        }]
+    };
+
+    handle(&tracker, 20, swap()).await.unwrap();
+    handle(&tracker, 20, swap()).await.unwrap();
+    assert_eq!(store.owner_at(&key(1), 20).await.unwrap(), owned(11, 20));
+    assert_eq!(store.hotkeys_at(&key(11), 20).await.unwrap(), vec![key(1)]);
+
+    Handler::<SubstrateConfig>::on_reorg(&tracker, 20, 21)
+        .await
+        .unwrap();
+    assert_eq!(store.owner_at(&key(1), 30).await.unwrap(), owned(10, 5));
+
+    // The new chain swaps to another coldkey at the same height.
+    handle(
+        &tracker,
+        20,
+        vec![SubtensorModule::ColdkeySwapped {
+            old_coldkey: key(10),
+            new_coldkey: key(12),
+        }],
+    )
+    .await
+    .unwrap();
+    assert_eq!(store.owner_at(&key(1), 20).await.unwrap(), owned(12, 20));
+    assert!(store.hotkeys_at(&key(11), 20).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn unknown_registration_reads_storage() {
+    let tracker = OwnershipTracker::new(MemoryOwnershipStore::new());
+    let err = handle(
+        &tracker,
+        3,
+        vec![SubtensorModule::NeuronRegistered(1, 0, key(4))],
+    )
+    .await
+    .unwrap_err();
+    match &err {
+        IndexerError::HandlerFailed {
+            handler,
+            block,
+            event_index,
+            ..
+        } => {
+            assert_eq!(handler, "OwnershipTracker");
+            assert_eq!(*block, 3);
+            assert_eq!(*event_index, Some(0));
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+    assert!(err.to_string().contains("Owner"), "{err}");
+}
+
+#[tokio::test]
+async fn seeding_needs_storage_access() {
+    let tracker = OwnershipTracker::new(MemoryOwnershipStore::new()).seed_from_storage(true);
+    let err = handle(&tracker, 3, vec![]).await.unwrap_err();
+    assert!(err.to_string().contains("OwnershipTracker"), "{err}");
+    // A failed seed is retried on the next block.
+    assert!(handle(&tracker, 4, vec![]).await.is_err());
+}
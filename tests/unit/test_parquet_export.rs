@@ -0,0 +1,291 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use async_trait::async_trait;
+use common::*;
+use flamewire_bittensor_indexer::handler::{Context, EventFilter, Handler};
+use flamewire_bittensor_indexer::parquet_export::{
+    EnvelopeRow, ParquetExportHandler, ParquetSchema, ToArrow,
+};
+use flamewire_bittensor_indexer::testing::TestChain;
+use flamewire_bittensor_indexer::{ChainEvent, IndexerBuilder, IndexerError};
+use futures::FutureExt;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::basic::Compression;
+use std::fs::{self, File};
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::Arc;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+
+struct TransferRow {
+    block: u64,
+    to: String,
+    amount: u64,
+}
+
+impl ToArrow for TransferRow {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("block", DataType::UInt64, false),
+            Field::new("to", DataType::Utf8, false),
+            Field::new("amount", DataType::UInt64, false),
+        ]))
+    }
+
+    fn to_record_batch(rows: &[Self]) -> Result<RecordBatch, ArrowError> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(|r| r.block))),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|r| r.to.as_str()),
+            )),
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(|r| r.amount))),
+        ];
+        RecordBatch::try_new(Self::schema(), columns)
+    }
+}
+
+fn transfers_handler(dir: &Path) -> ParquetExportHandler<SubstrateConfig, TransferRow> {
+    let schema = ParquetSchema::typed("transfers", |t: Transfer, ctx| TransferRow {
+        block: ctx.block_number,
+        to: t.to.to_string(),
+        amount: t.amount,
+    });
+    ParquetExportHandler::new(dir, schema).unwrap()
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Row count of each row group, and the file's batches.
+fn read(path: &Path) -> (Vec<i64>, SchemaRef, Vec<RecordBatch>) {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap();
+    let groups = builder
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|group| group.num_rows())
+        .collect();
+    let schema = builder.schema().clone();
+    let batches = builder
+        .build()
+        .unwrap()
+        .map(|batch| batch.unwrap())
+        .collect();
+    (groups, schema, batches)
+}
+
+fn u64_column(batches: &[RecordBatch], name: &str) -> Vec<u64> {
+    batches
+        .iter()
+        .flat_map(|batch| {
+            let column = batch.column_by_name(name).unwrap();
+            let values = column.as_any().downcast_ref::<UInt64Array>().unwrap();
+            values.values().to_vec()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_typed_rows_roll_files_and_row_groups() {
+    let dir = tempfile::tempdir().unwrap();
+    let handler = transfers_handler(dir.path())
+        .row_group_blocks(10)
+        .file_blocks(100);
+
+    write_block(
+        &handler,
+        100,
+        vec![transfer(5), Balances::Deposit { who: 3, amount: 1 }],
+    )
+    .await;
+    write_block(&handler, 105, vec![transfer(6)]).await;
+    write_block(&handler, 110, vec![transfer(7), transfer(8)]).await;
+    write_block(&handler, 199, vec![transfer(9)]).await;
+    write_block(&handler, 200, vec![transfer(10)]).await;
+
+    // Block 200's rows stay buffered until flushed.
+    assert_eq!(
+        file_names(dir.path()),
+        vec!["transfers_0000000100_0000000200.parquet"]
+    );
+    handler.on_shutdown().await.unwrap();
+    assert_eq!(
+        file_names(dir.path()),
+        vec![
+            "transfers_0000000100_0000000200.parquet",
+            "transfers_0000000200_0000000300.parquet",
+        ]
+    );
+
+    let (groups, schema, batches) =
+        read(&dir.path().join("transfers_0000000100_0000000200.parquet"));
+    assert_eq!(groups, vec![2, 2, 1]);
+    assert_eq!(schema.fields(), TransferRow::schema().fields());
+    assert_eq!(u64_column(&batches, "block"), vec![100, 105, 110, 110, 199]);
+    assert_eq!(u64_column(&batches, "amount"), vec![5, 6, 7, 8, 9]);
+
+    let (groups, _, batches) = read(&dir.path().join("transfers_0000000200_0000000300.parquet"));
+    assert_eq!(groups, vec![1]);
+    assert_eq!(u64_column(&batches, "amount"), vec![10]);
+}
+
+#[tokio::test]
+async fn test_envelope_schema_round_trips() {
+    let dir = tempfile::tempdir().unwrap();
+    let handler = ParquetExportHandler::new(
+        dir.path(),
+        ParquetSchema::envelope("balances", EventFilter::pallet("Balances")),
+    )
+    .unwrap()
+    .compression(Compression::UNCOMPRESSED);
+
+    write_block(
+        &handler,
+        10,
+        vec![transfer(5), Balances::Deposit { who: 3, amount: 1 }],
+    )
+    .await;
+    write_block(&handler, 11, vec![transfer(6)]).await;
+    handler.flush().unwrap();
+
+    let (groups, schema, batches) =
+        read(&dir.path().join("balances_0000000000_0000100000.parquet"));
+    assert_eq!(groups, vec![3]);
+    assert_eq!(schema.fields(), EnvelopeRow::schema().fields());
+    assert_eq!(u64_column(&batches, "block_number"), vec![10, 10, 11]);
+    let events = batches[0]
+        .column_by_name("event")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(
+        events.iter().flatten().collect::<Vec<_>>(),
+        vec!["Transfer", "Deposit", "Transfer"]
+    );
+}
+
+#[tokio::test]
+async fn test_rewritten_range_goes_to_new_part() {
+    let dir = tempfile::tempdir().unwrap();
+    let handler = transfers_handler(dir.path());
+    write_block(&handler, 5, vec![transfer(1)]).await;
+    handler.flush().unwrap();
+    write_block(&handler, 5, vec![transfer(1)]).await;
+    handler.flush().unwrap();
+
+    assert_eq!(
+        file_names(dir.path()),
+        vec![
+            "transfers_0000000000_0000100000.parquet",
+            "transfers_0000000000_0000100000_1.parquet",
+        ]
+    );
+    let (groups, _, _) = read(&dir.path().join("transfers_0000000000_0000100000_1.parquet"));
+    assert_eq!(groups, vec![1]);
+}
+
+#[tokio::test]
+async fn test_checkpoint_held_back_to_open_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let handler = transfers_handler(dir.path()).row_group_blocks(1);
+
+    assert_eq!(handler.on_checkpoint(4).await.unwrap(), Some(4));
+    write_block(&handler, 5, vec![transfer(1)]).await;
+    write_block(&handler, 6, vec![transfer(2)]).await;
+    // Written as row groups, but the file has no footer yet.
+    assert_eq!(handler.on_checkpoint(6).await.unwrap(), Some(4));
+    handler.flush().unwrap();
+    assert_eq!(handler.on_checkpoint(6).await.unwrap(), Some(6));
+}
+
+/// Panics while handling the given block.
+struct CrashAt(u64);
+
+#[async_trait]
+impl Handler<SubstrateConfig> for CrashAt {
+    async fn handle_block(
+        &self,
+        ctx: &Context<SubstrateConfig>,
+        _events: &[ChainEvent<SubstrateConfig>],
+    ) -> Result<(), IndexerError> {
+        assert_ne!(ctx.block_number, self.0, "crash");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_crash_mid_file_loses_no_rows() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut chain = TestChain::<Balances>::new("Balances");
+    for amount in 1..=6 {
+        chain.add_block(vec![EventRecord::new(
+            Phase::ApplyExtrinsic(0),
+            transfer(amount),
+        )]);
+    }
+    let store = MockCheckpointStore::new();
+    let checkpoints = store.checkpoints.clone();
+
+    // The first run dies at block 4 without shutting down, leaving blocks 1
+    // to 4 in an incomplete file.
+    let mut crashing = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(chain.source())
+        .with_store(store)
+        .abort_on_panic(true)
+        .add_handler(transfers_handler(dir.path()).row_group_blocks(1))
+        .add_handler(CrashAt(4))
+        .build()
+        .await
+        .unwrap();
+    let crashed = AssertUnwindSafe(crashing.run()).catch_unwind().await;
+    assert!(crashed.is_err());
+    drop(crashing);
+    assert_eq!(checkpoints.lock().unwrap().last(), Some(&0));
+
+    let mut resumed = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(chain.source())
+        .with_store(MockCheckpointStore {
+            checkpoints: checkpoints.clone(),
+            ..MockCheckpointStore::new()
+        })
+        .end_at_block(6)
+        .add_handler(transfers_handler(dir.path()).row_group_blocks(1))
+        .build()
+        .await
+        .unwrap();
+    resumed.run().await.unwrap();
+
+    assert_eq!(
+        file_names(dir.path()),
+        vec!["transfers_0000000000_0000100000.parquet"]
+    );
+    let (_, _, batches) = read(&dir.path().join("transfers_0000000000_0000100000.parquet"));
+    assert_eq!(u64_column(&batches, "amount"), vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(checkpoints.lock().unwrap().last(), Some(&6));
+}
@@ -0,0 +1,126 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::testing::TestChain;
+use flamewire_bittensor_indexer::{
+    EventFilter, Indexer, IndexerBuilder, IndexerError, MockBlockSource,
+};
+use std::sync::{Arc, Mutex};
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+
+struct Fixture {
+    indexer: Indexer<SubstrateConfig>,
+    /// What the handler of all events saw.
+    seen: Arc<Mutex<Vec<String>>>,
+    checkpoints: Arc<Mutex<Vec<u64>>>,
+}
+
+/// An indexer over blocks 1 to 3, each with `A(n)` and `B(true)`, with a
+/// handler of all events and a failing handler of `Test.B`.
+async fn fixture() -> Fixture {
+    let mut chain = TestChain::<TestEvent>::new("Test");
+    for n in 1..=3 {
+        chain.add_block(vec![
+            EventRecord::new(Phase::Initialization, TestEvent::A(n)),
+            EventRecord::new(Phase::ApplyExtrinsic(1), TestEvent::B(true)),
+        ]);
+    }
+    let all = MockHandler::new(EventFilter::all());
+    let seen = all.events.clone();
+    let mut failing = MockHandler::new(EventFilter::event("Test", "B"));
+    failing.fail = true;
+    let store = MockCheckpointStore::new();
+    let checkpoints = store.checkpoints.clone();
+    let indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(chain.source())
+        .with_store(store)
+        .add_handler(all)
+        .add_handler(failing)
+        .build()
+        .await
+        .unwrap();
+    Fixture {
+        indexer,
+        seen,
+        checkpoints,
+    }
+}
+
+#[tokio::test]
+async fn process_block_reports_each_handler() {
+    let Fixture {
+        indexer,
+        seen,
+        checkpoints,
+    } = fixture().await;
+
+    let report = indexer.process_block(2).await.unwrap();
+
+    assert_eq!(report.len(), 2);
+    assert!(report[0].1.is_ok());
+    assert!(matches!(
+        &report[1].1,
+        Err(IndexerError::HandlerFailed {
+            block: 2,
+            event_index: Some(1),
+            ..
+        })
+    ));
+    assert_eq!(*seen.lock().unwrap(), vec!["block:2", "Test.A", "Test.B"]);
+    assert!(checkpoints.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn process_block_at_finds_the_block_by_hash() {
+    let Fixture { indexer, seen, .. } = fixture().await;
+
+    let report = indexer
+        .process_block_at(MockBlockSource::<SubstrateConfig>::hash(3))
+        .await
+        .unwrap();
+
+    assert_eq!(report.len(), 2);
+    assert_eq!(seen.lock().unwrap()[0], "block:3");
+}
+
+#[tokio::test]
+async fn unknown_blocks_fail() {
+    let Fixture { indexer, seen, .. } = fixture().await;
+
+    assert!(matches!(
+        indexer.process_block(9).await,
+        Err(IndexerError::BlockNotFound { block: 9 })
+    ));
+    assert!(seen.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn unknown_hashes_fail() {
+    let Fixture { indexer, seen, .. } = fixture().await;
+    let hash = MockBlockSource::<SubstrateConfig>::hash(9);
+
+    match indexer.process_block_at(hash).await {
+        Err(IndexerError::BlockHashNotFound { hash: missing }) => {
+            assert_eq!(missing, format!("{hash:?}"));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(seen.lock().unwrap().is_empty());
+}
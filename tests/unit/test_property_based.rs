@@ -17,9 +17,14 @@
 #[path = "../common/mod.rs"]
 mod common;
 use common::*;
-use flamewire_bittensor_indexer::{config::IndexerConfig, CheckpointStore, IndexerError};
+use flamewire_bittensor_indexer::{
+    config::IndexerConfig, BlockRange, CheckpointStore, FinalityMode, IndexerError, JitterMode,
+    RetryConfig, StorageConfig, WebSocketUrl,
+};
 use once_cell::sync::Lazy;
 use proptest::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::runtime::Runtime;
 
 static RT: Lazy<Runtime> = Lazy::new(|| Runtime::new().unwrap());
@@ -111,22 +116,44 @@ fn prop_empty_store() {
 // Configuration validation properties
 #[test]
 fn prop_config_validation() {
-    proptest!(|(secure in any::<bool>(), host in "[a-zA-Z0-9.-]{1,20}", port in 1u32..65535, db in "[a-zA-Z0-9_/]{1,20}")| {
+    proptest!(|(secure in any::<bool>(), host in "[a-zA-Z0-9.-]{1,20}", port in 1u32..65535, db in "[a-zA-Z0-9_]{1,20}", rate in proptest::option::of(1u32..10_000), best in any::<bool>(), interval in 1u64..1_000)| {
         let proto = if secure { "wss" } else { "ws" };
         let url = format!("{proto}://{host}:{port}/rpc");
         let db_url = format!("postgres://{db}@localhost/db");
 
-        let cfg = IndexerConfig { node_url: url.clone(), database_url: Some(db_url.clone()), start_block: Some(1), end_block: None };
-        assert!(cfg.validate().is_ok());
+        let finality = if best { FinalityMode::Best } else { FinalityMode::Finalized };
 
-        let built = IndexerConfig::builder()
+        let cfg = IndexerConfig { node_urls: vec![url.clone()], database_url: Some(db_url.clone()), start_from: StartFrom::Block(1), end_block: None, max_blocks_per_minute: rate, finality, checkpoint_interval: interval, ..Default::default() };
+        // Hosts such as `1.2.3.999` or `a..b` are not valid URLs; exactly what the url
+        // crate accepts must pass validation.
+        let parses = url::Url::parse(&url).is_ok();
+        assert_eq!(cfg.validate().is_ok(), parses, "{}", url);
+        if !parses {
+            match cfg.validate().err().unwrap() { IndexerError::InvalidConfig { field, .. } => assert_eq!(field, "node_url"), _ => panic!("wrong error") }
+            return Ok(());
+        }
+
+        let mut builder = IndexerConfig::builder()
             .node_url(url.clone())
             .with_postgres(db_url.clone())
             .start_from_block(1)
-            .build()
-            .unwrap();
-        assert_eq!(built.node_url, cfg.node_url);
+            .finality(finality)
+            .checkpoint_interval(interval);
+        if let Some(rate) = rate {
+            builder = builder.max_blocks_per_minute(rate);
+        }
+        let built = builder.build().unwrap();
+        assert_eq!(built.node_urls, [WebSocketUrl::parse(&url).unwrap().as_str()]);
         assert_eq!(built.database_url, cfg.database_url);
+        assert_eq!(built.max_blocks_per_minute, rate);
+        assert_eq!(built.finality, finality);
+        assert_eq!(built.checkpoint_interval, interval);
+
+        let err = IndexerConfig::builder().node_url(url.clone()).checkpoint_interval(0).build().err().unwrap();
+        match err { IndexerError::InvalidConfig { field, .. } => assert_eq!(field, "checkpoint_interval"), _ => panic!("wrong error") }
+
+        let err = IndexerConfig::builder().node_url(url.clone()).max_blocks_per_minute(0).build().err().unwrap();
+        match err { IndexerError::InvalidConfig { field, .. } => assert_eq!(field, "max_blocks_per_minute"), _ => panic!("wrong error") }
 
         let bad = format!("ftp://{host}:{port}/rpc");
         let err = IndexerConfig::builder().node_url(bad).build().err().unwrap();
@@ -137,6 +164,18 @@ fn prop_config_validation() {
     });
 }
 
+#[test]
+fn prop_unknown_database_scheme_is_typed() {
+    proptest!(|(scheme in "[a-z]{1,12}", rest in "[a-zA-Z0-9/.]{0,20}")| {
+        prop_assume!(!matches!(scheme.as_str(), "postgres" | "postgresql" | "sqlite"));
+        let url = format!("{scheme}://{rest}");
+        match StorageConfig::parse(&url) {
+            Err(IndexerError::UnsupportedStorageScheme { scheme: s }) => assert_eq!(s, scheme),
+            _ => panic!("wrong result for scheme {scheme}"),
+        }
+    });
+}
+
 #[test]
 fn prop_filter_composition_laws() {
     proptest!(|(p in "[a-zA-Z0-9]{0,20}", e1 in "[a-zA-Z0-9]{0,20}", e2 in "[a-zA-Z0-9]{0,20}")| {
@@ -187,3 +226,107 @@ fn prop_config_boundary_values() {
         assert!(empty.is_err());
     });
 }
+
+#[test]
+fn prop_jittered_delay_bounds() {
+    proptest!(|(base_ms in 0u64..60_000, max_ms in 1u64..30_000, fraction in 0.0f64..=1.0,
+                mode in prop_oneof![Just(JitterMode::None), Just(JitterMode::Full), Just(JitterMode::Equal)])| {
+        let cfg = RetryConfig {
+            max_delay: Duration::from_millis(max_ms),
+            jitter: mode,
+            jitter_source: Some(Arc::new(move || fraction)),
+            ..Default::default()
+        };
+        let capped = Duration::from_millis(base_ms.min(max_ms));
+        let delay = cfg.jittered_delay(Duration::from_millis(base_ms));
+
+        assert!(delay <= cfg.max_delay);
+        assert!(delay <= capped);
+        match mode {
+            JitterMode::None => assert_eq!(delay, capped),
+            JitterMode::Full => assert_eq!(delay, capped.mul_f64(fraction)),
+            JitterMode::Equal => assert!(delay >= capped / 2),
+        }
+    });
+}
+
+#[test]
+fn random_jitter_stays_in_range() {
+    let cfg = RetryConfig {
+        jitter: JitterMode::Full,
+        ..Default::default()
+    };
+    for _ in 0..1_000 {
+        assert!(cfg.jittered_delay(Duration::from_secs(1)) <= Duration::from_secs(1));
+    }
+}
+
+#[test]
+fn prop_block_range_invariants() {
+    proptest!(|(start in 0u64..1_000_000, span in 0u64..1_000, below in 1u64..1_000, probe in 0u64..1_002_000)| {
+        let end = start + span;
+        let range = BlockRange::new(start, Some(end)).unwrap();
+        prop_assert_eq!(range.len(), Some(span + 1));
+        prop_assert_eq!(range.iter().count() as u64, span + 1);
+        prop_assert_eq!(range.iter().next(), Some(start));
+        prop_assert_eq!(range.iter().last(), Some(end));
+        prop_assert_eq!(range.contains(probe), (start..=end).contains(&probe));
+
+        let open = BlockRange::from_start(start);
+        prop_assert_eq!(open.len(), None);
+        prop_assert_eq!(open.contains(probe), probe >= start);
+
+        if let Some(end) = start.checked_sub(below) {
+            let is_invalid_end = matches!(
+                BlockRange::new(start, Some(end)),
+                Err(IndexerError::InvalidConfig { ref field, .. }) if field == "end_block"
+            );
+            prop_assert!(is_invalid_end);
+        }
+    });
+}
+
+#[test]
+fn prop_config_range_matches_validation() {
+    proptest!(|(start in 0u64..1_000, end in proptest::option::of(0u64..1_000))| {
+        let result = IndexerConfig::builder()
+            .node_url("wss://node")
+            .start_from(flamewire_bittensor_indexer::StartFrom::Block(start))
+            .build();
+        let cfg = match end {
+            Some(end) => IndexerConfig { end_block: Some(end), ..result.unwrap() },
+            None => result.unwrap(),
+        };
+        prop_assert_eq!(cfg.validate().is_ok(), cfg.range().is_some());
+        prop_assert_eq!(cfg.validate().is_ok(), BlockRange::new(start, end).is_ok());
+    });
+}
+
+#[test]
+fn prop_websocket_url_parse_is_idempotent() {
+    proptest!(|(secure in any::<bool>(), host in "[a-zA-Z][a-zA-Z0-9-]{0,15}(\\.[a-zA-Z]{2,6})?", port in proptest::option::of(1u16..u16::MAX), path in "(/[a-z0-9]{1,8}){0,2}", slash in any::<bool>(), upper in any::<bool>())| {
+        let scheme = if secure { "wss" } else { "ws" };
+        let scheme = if upper { scheme.to_uppercase() } else { scheme.to_string() };
+        let port = port.map(|p| format!(":{p}")).unwrap_or_default();
+        let trailing = if slash && path.is_empty() { "/" } else { "" };
+        let input = format!("{scheme}://{host}{port}{path}{trailing}");
+
+        let url = WebSocketUrl::parse(&input).unwrap();
+        let reparsed = WebSocketUrl::parse(&url.to_string()).unwrap();
+        prop_assert_eq!(&reparsed, &url);
+        prop_assert_eq!(reparsed.as_str(), url.as_str());
+        prop_assert_eq!(url.host(), host.to_lowercase());
+        prop_assert!(!url.as_str().ends_with('/'));
+    });
+}
+
+#[test]
+fn prop_websocket_url_default_ports_are_equivalent() {
+    proptest!(|(host in "[a-z][a-z0-9]{0,15}", secure in any::<bool>())| {
+        let (scheme, default_port) = if secure { ("wss", 443) } else { ("ws", 80) };
+        let plain = WebSocketUrl::parse(&format!("{scheme}://{host}")).unwrap();
+        let explicit = WebSocketUrl::parse(&format!("{}://{}:{default_port}/", scheme.to_uppercase(), host.to_uppercase())).unwrap();
+        prop_assert_eq!(&plain, &explicit);
+        prop_assert_eq!(plain.port(), default_port);
+    });
+}
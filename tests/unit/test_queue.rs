@@ -0,0 +1,105 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use async_trait::async_trait;
+use common::*;
+use flamewire_bittensor_indexer::handler::{Context, EventFilter, Handler};
+use flamewire_bittensor_indexer::queue::{bounded, QueueGauge};
+use flamewire_bittensor_indexer::{ChainEvent, IndexerError};
+use std::sync::Arc;
+use std::time::Duration;
+use subxt::config::substrate::SubstrateConfig;
+use tokio::time::{sleep, timeout};
+
+/// Handler that takes far longer per block than producing one does.
+struct SlowHandler {
+    inner: MockHandler,
+    delay: Duration,
+}
+
+#[async_trait]
+impl Handler<SubstrateConfig> for SlowHandler {
+    fn event_filter(&self) -> EventFilter {
+        EventFilter::all()
+    }
+
+    async fn handle_block(
+        &self,
+        ctx: &Context<SubstrateConfig>,
+        events: &[ChainEvent<SubstrateConfig>],
+    ) -> Result<(), IndexerError> {
+        sleep(self.delay).await;
+        self.inner.handle_block(ctx, events).await
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn slow_handler_bounds_buffered_blocks() {
+    let gauge = Arc::new(QueueGauge::new(3));
+    let (tx, mut rx) = bounded::<u64>(gauge.clone());
+    let handler = SlowHandler {
+        inner: MockHandler::new(EventFilter::all()),
+        delay: Duration::from_millis(50),
+    };
+    let handled = handler.inner.events.clone();
+
+    let producer = tokio::spawn(async move {
+        for block in 0..20u64 {
+            tx.send(block).await.unwrap();
+        }
+    });
+    while let Some(block) = rx.recv().await {
+        assert!(gauge.depth() <= 3);
+        let ctx = Context::builder().block_number(block).build();
+        handler.handle_block(&ctx, &[]).await.unwrap();
+    }
+    producer.await.unwrap();
+
+    assert_eq!(handled.lock().unwrap().len(), 20);
+    assert_eq!(handled.lock().unwrap()[19], "block:19");
+    assert_eq!(gauge.peak(), 3);
+    assert_eq!(gauge.depth(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn full_queue_blocks_sender() {
+    let gauge = Arc::new(QueueGauge::new(2));
+    let (tx, mut rx) = bounded::<u64>(gauge.clone());
+    tx.send(1).await.unwrap();
+    tx.send(2).await.unwrap();
+    assert_eq!(gauge.depth(), 2);
+
+    assert!(timeout(Duration::from_secs(1), tx.send(3)).await.is_err());
+    assert_eq!(gauge.depth(), 2);
+
+    assert_eq!(rx.recv().await, Some(1));
+    assert_eq!(gauge.depth(), 1);
+    tx.send(3).await.unwrap();
+    assert_eq!(gauge.depth(), 2);
+}
+
+#[tokio::test]
+async fn dropped_receiver_resets_depth_and_rejects_sends() {
+    let gauge = Arc::new(QueueGauge::new(4));
+    let (tx, rx) = bounded::<u64>(gauge.clone());
+    tx.send(1).await.unwrap();
+    tx.send(2).await.unwrap();
+    drop(rx);
+    assert_eq!(gauge.depth(), 0);
+    assert_eq!(tx.send(3).await, Err(3));
+}
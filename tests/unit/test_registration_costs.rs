@@ -0,0 +1,175 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use async_trait::async_trait;
+use common::*;
+use flamewire_bittensor_indexer::handler::{Context, Handler};
+use flamewire_bittensor_indexer::presets::registrations::{
+    RegistrationCost, RegistrationCostSource, RegistrationCosts, RegistrationRecord, PIPELINE_KEY,
+};
+use flamewire_bittensor_indexer::units::Rao;
+use flamewire_bittensor_indexer::{ChainEvent, IndexerError};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use std::sync::{Arc, Mutex};
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::AccountId32;
+
+#[derive(Encode, Decode, TypeInfo)]
+enum SubtensorModule {
+    NeuronRegistered(u16, u16, AccountId32),
+    AxonServed(u16, AccountId32),
+}
+
+/// Cost source answering from the netuid, recording every read.
+#[derive(Default)]
+struct StubCosts {
+    reads: Mutex<Vec<(u64, u16)>>,
+}
+
+#[async_trait]
+impl RegistrationCostSource<SubstrateConfig> for StubCosts {
+    async fn cost(
+        &self,
+        ctx: &Context<SubstrateConfig>,
+        netuid: u16,
+    ) -> Result<RegistrationCost, IndexerError> {
+        self.reads.lock().unwrap().push((ctx.block_number, netuid));
+        if netuid == 99 {
+            return Err(IndexerError::invalid_config(
+                "netuid",
+                "subnet 99 does not exist",
+            ));
+        }
+        Ok(RegistrationCost {
+            difficulty: 10_000 * u64::from(netuid),
+            burn: Rao::new(u128::from(netuid) * 1_000_000_000),
+        })
+    }
+}
+
+fn hotkey(byte: u8) -> AccountId32 {
+    AccountId32([byte; 32])
+}
+
+fn block(block_events: Vec<SubtensorModule>) -> Vec<ChainEvent<SubstrateConfig>> {
+    let records = block_events
+        .into_iter()
+        .map(|event| EventRecord::new(Phase::ApplyExtrinsic(1), event))
+        .collect();
+    let evs = events(
+        pallet_metadata::<SubtensorModule>("SubtensorModule"),
+        records,
+    );
+    ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn registrations_are_enriched_once_per_subnet() {
+    let costs = Arc::new(StubCosts::default());
+    let handler = RegistrationCosts::with_source(costs.clone());
+    let ces = block(vec![
+        SubtensorModule::NeuronRegistered(1, 4, hotkey(1)),
+        SubtensorModule::AxonServed(1, hotkey(1)),
+        SubtensorModule::NeuronRegistered(3, 0, hotkey(2)),
+        SubtensorModule::NeuronRegistered(1, 5, hotkey(3)),
+    ]);
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(42)
+        .build();
+    handler.handle_block(&ctx, &ces).await.unwrap();
+
+    let records = ctx
+        .get_pipeline_data::<Vec<RegistrationRecord>>(PIPELINE_KEY)
+        .unwrap();
+    let record = |event_index, netuid: u16, uid, key| RegistrationRecord {
+        block_number: 42,
+        event_index,
+        netuid,
+        uid,
+        hotkey: hotkey(key),
+        difficulty: 10_000 * u64::from(netuid),
+        burn: Rao::from_tao(u64::from(netuid)),
+    };
+    assert_eq!(
+        records,
+        vec![record(0, 1, 4, 1), record(2, 3, 0, 2), record(3, 1, 5, 3)]
+    );
+    assert_eq!(*costs.reads.lock().unwrap(), vec![(42, 1), (42, 3)]);
+}
+
+#[tokio::test]
+async fn blocks_without_registrations_publish_an_empty_list() {
+    let costs = Arc::new(StubCosts::default());
+    let handler = RegistrationCosts::with_source(costs.clone());
+    let ces = block(vec![SubtensorModule::AxonServed(1, hotkey(1))]);
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(7)
+        .build();
+    handler.handle_block(&ctx, &ces).await.unwrap();
+
+    let records = ctx
+        .get_pipeline_data::<Vec<RegistrationRecord>>(PIPELINE_KEY)
+        .unwrap();
+    assert!(records.is_empty());
+    assert!(costs.reads.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn failed_reads_name_the_event() {
+    let handler = RegistrationCosts::with_source(StubCosts::default());
+    let ces = block(vec![
+        SubtensorModule::NeuronRegistered(1, 0, hotkey(1)),
+        SubtensorModule::NeuronRegistered(99, 0, hotkey(2)),
+    ]);
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(8)
+        .build();
+    let err = handler.handle_block(&ctx, &ces).await.unwrap_err();
+    match &err {
+        IndexerError::HandlerFailed {
+            handler,
+            block,
+            event_index,
+            ..
+        } => {
+            assert_eq!(handler, "RegistrationCosts");
+            assert_eq!(*block, 8);
+            assert_eq!(*event_index, Some(1));
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+    assert!(err.to_string().contains("subnet 99"), "{err}");
+}
+
+#[tokio::test]
+async fn chain_storage_needs_storage_access() {
+    let handler = RegistrationCosts::new();
+    let ces = block(vec![SubtensorModule::NeuronRegistered(1, 0, hotkey(1))]);
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(9)
+        .build();
+    let err = handler.handle_block(&ctx, &ces).await.unwrap_err();
+    assert!(
+        err.to_string().contains("SubtensorModule.Difficulty"),
+        "{err}"
+    );
+}
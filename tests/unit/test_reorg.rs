@@ -0,0 +1,184 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use async_trait::async_trait;
+use flamewire_bittensor_indexer::handler::Handler;
+use flamewire_bittensor_indexer::handler_group::HandlerGroup;
+use flamewire_bittensor_indexer::{IndexerConfig, IndexerError, Reorg, ReorgTracker};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use subxt::config::substrate::SubstrateConfig;
+
+/// Block hashes of the node's chain, keyed by height.
+type Chain = HashMap<u64, u64>;
+
+fn chain(blocks: impl IntoIterator<Item = (u64, u64)>) -> Chain {
+    blocks.into_iter().collect()
+}
+
+/// Tracker that indexed blocks `0..=tip` with hash `n` for block `n`.
+fn tracker(max_depth: u32, tip: u64) -> ReorgTracker<u64> {
+    let mut tracker = ReorgTracker::new(max_depth);
+    for n in 0..=tip {
+        tracker.push(n, n);
+    }
+    tracker
+}
+
+async fn check(
+    tracker: &mut ReorgTracker<u64>,
+    chain: &Chain,
+) -> Result<Option<Reorg>, IndexerError> {
+    tracker
+        .check(|n| {
+            let hash = chain.get(&n).copied();
+            async move { Ok(hash) }
+        })
+        .await
+}
+
+#[tokio::test]
+async fn canonical_tip_is_not_a_reorg() {
+    let mut tracker = tracker(4, 10);
+    let node = chain((0..=11).map(|n| (n, n)));
+    assert_eq!(check(&mut tracker, &node).await.unwrap(), None);
+    assert_eq!(tracker.tip(), Some(10));
+}
+
+#[tokio::test]
+async fn reorg_within_limit_reports_unwound_range() {
+    let mut tracker = tracker(4, 10);
+    // Blocks 8..=10 were replaced by a fork.
+    let node = chain(
+        (0..=7)
+            .map(|n| (n, n))
+            .chain((8..=11).map(|n| (n, n + 100))),
+    );
+    let reorg = check(&mut tracker, &node).await.unwrap().unwrap();
+    assert_eq!(reorg, Reorg { from: 8, to: 10 });
+    assert_eq!(reorg.depth(), 3);
+    assert_eq!(tracker.tip(), Some(7));
+    assert_eq!(tracker.hash_of(8), None);
+
+    tracker.push(8, 108);
+    assert_eq!(check(&mut tracker, &node).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn reorg_at_the_limit_is_unwound() {
+    let mut tracker = tracker(3, 10);
+    let node = chain(
+        (0..=7)
+            .map(|n| (n, n))
+            .chain((8..=10).map(|n| (n, n + 100))),
+    );
+    let reorg = check(&mut tracker, &node).await.unwrap().unwrap();
+    assert_eq!(reorg, Reorg { from: 8, to: 10 });
+    assert_eq!(reorg.depth(), 3);
+}
+
+#[tokio::test]
+async fn reorg_beyond_limit_is_an_error() {
+    let mut tracker = tracker(3, 10);
+    let node = chain(
+        (0..=5)
+            .map(|n| (n, n))
+            .chain((6..=10).map(|n| (n, n + 100))),
+    );
+    match check(&mut tracker, &node).await {
+        Err(IndexerError::ReorgTooDeep { depth, max }) => {
+            assert!(depth > max, "{depth} <= {max}");
+            assert_eq!(max, 3);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    // The history is left untouched.
+    assert_eq!(tracker.tip(), Some(10));
+}
+
+#[tokio::test]
+async fn reorg_of_a_short_history_unwinds_all_of_it() {
+    // Only block 10 was indexed, as after starting from the latest block.
+    let mut tracker = ReorgTracker::new(4);
+    tracker.push(10, 10);
+    let node = chain((0..=11).map(|n| (n, n + 100)));
+    let reorg = check(&mut tracker, &node).await.unwrap().unwrap();
+    assert_eq!(reorg, Reorg { from: 10, to: 10 });
+    assert_eq!(tracker.tip(), None);
+
+    tracker.push(10, 110);
+    assert_eq!(check(&mut tracker, &node).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn history_is_bounded_by_max_depth() {
+    let tracker = tracker(2, 10);
+    assert_eq!(tracker.hash_of(8), Some(8));
+    assert_eq!(tracker.hash_of(7), None);
+}
+
+#[test]
+fn reorg_too_deep_names_depth_and_limit() {
+    let err = IndexerError::ReorgTooDeep { depth: 70, max: 64 };
+    assert_eq!(
+        err.to_string(),
+        "Reorg of 70 blocks exceeds max_reorg_depth of 64"
+    );
+}
+
+#[test]
+fn max_reorg_depth_defaults_and_is_validated() {
+    let cfg = IndexerConfig::builder()
+        .node_url("ws://node")
+        .build()
+        .unwrap();
+    assert_eq!(cfg.max_reorg_depth, 64);
+
+    let cfg = IndexerConfig::builder()
+        .node_url("ws://node")
+        .max_reorg_depth(8)
+        .build()
+        .unwrap();
+    assert_eq!(cfg.max_reorg_depth, 8);
+
+    let err = IndexerConfig::builder()
+        .node_url("ws://node")
+        .max_reorg_depth(0)
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(err, IndexerError::InvalidConfig { field, .. } if field == "max_reorg_depth"));
+}
+
+struct ReorgRecorder(Arc<Mutex<Vec<(u64, u64)>>>);
+
+#[async_trait]
+impl Handler<SubstrateConfig> for ReorgRecorder {
+    async fn on_reorg(&self, from: u64, to: u64) -> Result<(), IndexerError> {
+        self.0.lock().unwrap().push((from, to));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn handler_group_forwards_on_reorg() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let group = HandlerGroup::<SubstrateConfig>::new()
+        .add(ReorgRecorder(seen.clone()))
+        .add(ReorgRecorder(seen.clone()));
+    group.on_reorg(8, 10).await.unwrap();
+    assert_eq!(*seen.lock().unwrap(), [(8, 10), (8, 10)]);
+}
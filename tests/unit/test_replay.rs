@@ -0,0 +1,244 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use async_trait::async_trait;
+use common::*;
+use flamewire_bittensor_indexer::handler::{Context, Handler};
+use flamewire_bittensor_indexer::run::StopReason;
+use flamewire_bittensor_indexer::types::ChainEvent;
+use flamewire_bittensor_indexer::{
+    IndexerBuilder, IndexerError, MemoryStore, RecordingWriter, ReplaySource,
+};
+use parity_scale_codec::Encode;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::H256;
+
+/// Everything a handler can observe of an event.
+type Seen = (u64, H256, u32, String, String, String);
+
+#[derive(Clone, Default)]
+struct Collect(Arc<Mutex<Vec<Seen>>>);
+
+impl Collect {
+    fn seen(&self) -> Vec<Seen> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+fn observe(block: u64, hash: H256, event: &ChainEvent<SubstrateConfig>) -> Seen {
+    (
+        block,
+        hash,
+        event.index(),
+        event.pallet_name().to_string(),
+        event.variant_name().to_string(),
+        format!("{:?}", event.field_values().unwrap()),
+    )
+}
+
+#[async_trait]
+impl Handler<SubstrateConfig> for Collect {
+    async fn handle_event(
+        &self,
+        event: &ChainEvent<SubstrateConfig>,
+        ctx: &Context<SubstrateConfig>,
+    ) -> Result<(), IndexerError> {
+        let seen = observe(ctx.block_number, ctx.block_hash, event);
+        self.0.lock().unwrap().push(seen);
+        Ok(())
+    }
+}
+
+fn block_events(number: u64) -> Vec<u8> {
+    event_bytes(vec![
+        EventRecord::new(Phase::Initialization, TestEvent::A(number as u8)),
+        EventRecord::new(Phase::ApplyExtrinsic(1), TestEvent::B(number % 2 == 0)),
+    ])
+}
+
+/// Blocks 1 to 5 of the `Test` pallet, and 6 after an upgrade renaming it to
+/// `Upgraded`.
+fn record(path: &Path) {
+    let writer = RecordingWriter::create(path, &H256::repeat_byte(0xee).encode()).unwrap();
+    writer
+        .runtime(100, 1, pallet_metadata_bytes::<TestEvent>("Test"))
+        .unwrap();
+    for number in 1..=5 {
+        let hash = H256::repeat_byte(number as u8);
+        writer
+            .block(number, &hash.encode(), &[], &block_events(number))
+            .unwrap();
+    }
+    writer
+        .runtime(101, 1, pallet_metadata_bytes::<TestEvent>("Upgraded"))
+        .unwrap();
+    writer
+        .block(6, &H256::repeat_byte(6).encode(), &[], &block_events(6))
+        .unwrap();
+}
+
+/// What the live path decodes from the same bytes.
+fn decoded(number: u64, pallet: &'static str) -> Vec<Seen> {
+    let metadata = pallet_metadata::<TestEvent>(pallet);
+    let events = subxt::events::Events::decode_from(block_events(number), metadata);
+    ChainEvent::from_events(&events)
+        .map(|event| observe(number, H256::repeat_byte(number as u8), &event.unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn replay_feeds_recorded_events_to_handlers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("blocks.rec");
+    record(&path);
+
+    let collect = Collect::default();
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .replay_from(&path)
+        .with_store(MemoryStore::new())
+        .add_handler(collect.clone())
+        .build()
+        .await
+        .unwrap();
+    let summary = indexer.run().await.unwrap();
+
+    assert_eq!(summary.blocks_processed, 6);
+    assert_eq!(summary.last_block, Some(6));
+    assert_eq!(summary.stop_reason, StopReason::EndOfRecording);
+    let mut expected: Vec<_> = (1..=5).flat_map(|n| decoded(n, "Test")).collect();
+    expected.extend(decoded(6, "Upgraded"));
+    assert_eq!(collect.seen(), expected);
+}
+
+#[tokio::test]
+async fn replay_respects_start_and_end_blocks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("blocks.rec");
+    record(&path);
+
+    let collect = Collect::default();
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .replay_from(&path)
+        .with_store(MemoryStore::new())
+        .start_from_block(2)
+        .end_at_block(4)
+        .add_handler(collect.clone())
+        .build()
+        .await
+        .unwrap();
+    let summary = indexer.run().await.unwrap();
+
+    assert_eq!(summary.stop_reason, StopReason::EndBlock);
+    let blocks: Vec<_> = collect.seen().iter().map(|seen| seen.0).collect();
+    assert_eq!(blocks, vec![2, 2, 3, 3, 4, 4]);
+}
+
+#[tokio::test]
+async fn replay_fails_on_an_unrecorded_hash() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("blocks.rec");
+    record(&path);
+
+    let collect = Collect::default();
+    let indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .replay_from(&path)
+        .with_store(MemoryStore::new())
+        .add_handler(collect.clone())
+        .build()
+        .await
+        .unwrap();
+
+    indexer
+        .process_block_at(H256::repeat_byte(2))
+        .await
+        .unwrap();
+    match indexer.process_block_at(H256::repeat_byte(9)).await {
+        Err(IndexerError::BlockHashNotFound { hash }) => {
+            assert_eq!(hash, format!("{:?}", H256::repeat_byte(9)));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+    let blocks: Vec<_> = collect.seen().iter().map(|seen| seen.0).collect();
+    assert_eq!(blocks, vec![2, 2]);
+}
+
+#[test]
+fn truncated_recording_replays_up_to_its_last_block() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("blocks.rec");
+    record(&path);
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() - 3]).unwrap();
+
+    let replay = ReplaySource::open(&path).unwrap();
+    assert_eq!(replay.blocks(), 5);
+    assert_eq!(replay.first_block(), Some(1));
+    assert_eq!(replay.last_block(), Some(5));
+}
+
+#[test]
+fn corrupt_entries_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("blocks.rec");
+    record(&path);
+    let mut bytes = std::fs::read(&path).unwrap();
+    let offset = bytes.len();
+    // No entry has variant index 0xff.
+    bytes.extend_from_slice(&[0xff, 0, 0, 0]);
+    std::fs::write(&path, &bytes).unwrap();
+
+    let Err(IndexerError::Io(e)) = ReplaySource::open(&path) else {
+        panic!("corrupt recording was accepted");
+    };
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+    assert!(e
+        .to_string()
+        .contains(&format!("corrupt entry at offset {offset}")));
+}
+
+#[test]
+fn other_files_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("blocks.rec");
+    std::fs::write(&path, b"{\"block\": 1}").unwrap();
+
+    assert!(matches!(
+        ReplaySource::open(&path),
+        Err(IndexerError::Io(e)) if e.kind() == std::io::ErrorKind::InvalidData
+    ));
+}
+
+#[test]
+fn blocks_need_a_runtime_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let writer = RecordingWriter::create(dir.path().join("blocks.rec"), &[0; 32]).unwrap();
+    assert!(writer.block(1, &[1; 32], &[], &block_events(1)).is_err());
+}
+
+#[test]
+fn replay_cannot_be_recorded() {
+    let err = IndexerBuilder::<SubstrateConfig>::new()
+        .replay_from("in.rec")
+        .record_to("out.rec")
+        .validate()
+        .unwrap_err();
+    assert!(matches!(err, IndexerError::InvalidConfig { field, .. } if field == "record_to"));
+}
@@ -0,0 +1,152 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use async_trait::async_trait;
+use flamewire_bittensor_indexer::config::{IndexerConfig, TlsMode};
+use flamewire_bittensor_indexer::rpc::{connect_with, ClientFactory};
+use flamewire_bittensor_indexer::validated_types::NodeAuth;
+use flamewire_bittensor_indexer::IndexerError;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Constructor {
+    Secure,
+    AcceptInvalidCerts,
+    Plaintext,
+}
+
+/// Factory that reports which constructor was chosen instead of connecting.
+struct Recording;
+
+#[async_trait]
+impl ClientFactory for Recording {
+    type Client = (Constructor, String);
+
+    async fn secure(
+        &self,
+        url: &str,
+        _auth: Option<&NodeAuth>,
+    ) -> Result<Self::Client, subxt::Error> {
+        Ok((Constructor::Secure, url.to_string()))
+    }
+
+    async fn accept_invalid_certs(
+        &self,
+        url: &str,
+        _auth: Option<&NodeAuth>,
+    ) -> Result<Self::Client, subxt::Error> {
+        Ok((Constructor::AcceptInvalidCerts, url.to_string()))
+    }
+
+    async fn plaintext(
+        &self,
+        url: &str,
+        _auth: Option<&NodeAuth>,
+    ) -> Result<Self::Client, subxt::Error> {
+        Ok((Constructor::Plaintext, url.to_string()))
+    }
+}
+
+async fn chosen(url: &str, tls: TlsMode) -> Constructor {
+    let (constructor, connected) = connect_with(&Recording, url, None, tls).await.unwrap();
+    assert_eq!(connected, url);
+    constructor
+}
+
+#[tokio::test]
+async fn wss_verifies_certificates_by_default() {
+    assert_eq!(
+        chosen("wss://user:pw@node.example:443", TlsMode::default()).await,
+        Constructor::Secure
+    );
+}
+
+#[tokio::test]
+async fn insecure_mode_accepts_invalid_certificates() {
+    assert_eq!(
+        chosen("wss://user:pw@node.example", TlsMode::Insecure).await,
+        Constructor::AcceptInvalidCerts
+    );
+}
+
+#[tokio::test]
+async fn ws_connects_without_tls_in_either_mode() {
+    for tls in [TlsMode::Secure, TlsMode::Insecure] {
+        assert_eq!(
+            chosen("ws://127.0.0.1:9944", tls).await,
+            Constructor::Plaintext
+        );
+        assert_eq!(
+            chosen("ws://node.example:9944", tls).await,
+            Constructor::Plaintext
+        );
+    }
+}
+
+#[tokio::test]
+async fn factory_errors_name_the_node() {
+    struct Failing;
+
+    #[async_trait]
+    impl ClientFactory for Failing {
+        type Client = ();
+
+        async fn secure(&self, _: &str, _: Option<&NodeAuth>) -> Result<(), subxt::Error> {
+            Err(subxt::Error::Other("handshake failed".into()))
+        }
+
+        async fn accept_invalid_certs(
+            &self,
+            _: &str,
+            _: Option<&NodeAuth>,
+        ) -> Result<(), subxt::Error> {
+            unreachable!()
+        }
+
+        async fn plaintext(&self, _: &str, _: Option<&NodeAuth>) -> Result<(), subxt::Error> {
+            unreachable!()
+        }
+    }
+
+    let err = connect_with(
+        &Failing,
+        "wss://user:pw@node.example",
+        None,
+        TlsMode::Secure,
+    )
+    .await
+    .unwrap_err();
+    match &err {
+        IndexerError::ConnectionFailed { url, .. } => assert!(url.contains("node.example")),
+        other => panic!("wrong error: {other}"),
+    }
+    assert!(!err.to_string().contains("pw@"), "{err}");
+}
+
+#[test]
+fn tls_mode_defaults_to_secure() {
+    let config = IndexerConfig::builder()
+        .node_url("wss://node.example")
+        .build()
+        .unwrap();
+    assert_eq!(config.tls_mode, TlsMode::Secure);
+
+    let config = IndexerConfig::builder()
+        .node_url("wss://node.example")
+        .tls_mode(TlsMode::Insecure)
+        .build()
+        .unwrap();
+    assert_eq!(config.tls_mode, TlsMode::Insecure);
+}
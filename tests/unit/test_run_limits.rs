@@ -0,0 +1,107 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::config::IndexerConfig;
+use flamewire_bittensor_indexer::{IndexerError, RunLimits, StopReason};
+use std::time::{Duration, SystemTime};
+
+const BLOCK_TIME: Duration = Duration::from_secs(12);
+
+/// Stand-in for the run loop: handles a block every `BLOCK_TIME` from block 0 until the
+/// limits stop it. Returns the number of blocks handled and why it stopped.
+async fn run_blocks(limits: RunLimits) -> (u64, StopReason) {
+    let mut next = 0;
+    loop {
+        if let Some(reason) = limits.check(next) {
+            return (next, reason);
+        }
+        tokio::time::sleep(BLOCK_TIME).await;
+        next += 1;
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn end_after_stops_between_blocks() {
+    let limits = RunLimits::new(None, Some(Duration::from_secs(30 * 60)), None);
+    assert_eq!(run_blocks(limits).await, (150, StopReason::TimeLimit));
+}
+
+#[tokio::test(start_paused = true)]
+async fn time_limit_does_not_interrupt_a_block() {
+    let limits = RunLimits::new(None, Some(Duration::from_secs(30)), None);
+    // Blocks start at 0s, 12s and 24s; the one started at 24s runs past the limit
+    // and still completes.
+    assert_eq!(run_blocks(limits).await, (3, StopReason::TimeLimit));
+}
+
+#[tokio::test(start_paused = true)]
+async fn end_at_time_stops_the_run() {
+    let limits = RunLimits::new(
+        None,
+        None,
+        Some(SystemTime::now() + Duration::from_secs(60)),
+    );
+    assert_eq!(run_blocks(limits).await, (5, StopReason::TimeLimit));
+
+    let past = RunLimits::new(None, None, Some(SystemTime::now() - Duration::from_secs(1)));
+    assert_eq!(run_blocks(past).await, (0, StopReason::TimeLimit));
+}
+
+#[tokio::test(start_paused = true)]
+async fn whichever_limit_comes_first_wins() {
+    let limits = RunLimits::new(Some(9), Some(Duration::from_secs(30 * 60)), None);
+    assert_eq!(run_blocks(limits).await, (10, StopReason::EndBlock));
+
+    let limits = RunLimits::new(Some(1_000), Some(Duration::from_secs(60)), None);
+    assert_eq!(run_blocks(limits).await, (5, StopReason::TimeLimit));
+
+    let start = tokio::time::Instant::now();
+    let limits = RunLimits::new(
+        None,
+        Some(Duration::from_secs(600)),
+        Some(SystemTime::now() + Duration::from_secs(120)),
+    );
+    let deadline = limits.deadline().unwrap();
+    assert!(deadline - start <= Duration::from_secs(120));
+    assert!(deadline - start > Duration::from_secs(110));
+}
+
+#[tokio::test(start_paused = true)]
+async fn no_limits_never_stop() {
+    let limits = RunLimits::new(None, None, None);
+    assert_eq!(limits.deadline(), None);
+    tokio::time::sleep(Duration::from_secs(365 * 24 * 3600)).await;
+    assert_eq!(limits.check(u64::MAX), None);
+}
+
+#[test]
+fn zero_end_after_is_rejected() {
+    let err = IndexerConfig::builder()
+        .node_url("ws://node")
+        .end_after(Duration::ZERO)
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, IndexerError::InvalidConfig { field, .. } if field == "end_after"));
+
+    let cfg = IndexerConfig::builder()
+        .node_url("ws://node")
+        .end_after(Duration::from_secs(1800))
+        .end_at_block(100)
+        .build()
+        .unwrap();
+    assert_eq!(cfg.end_after, Some(Duration::from_secs(1800)));
+    assert_eq!(cfg.end_block, Some(100));
+}
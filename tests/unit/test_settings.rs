@@ -0,0 +1,198 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::config::IndexerConfig;
+use flamewire_bittensor_indexer::{IndexerError, RuntimeSettings, SettingsHandle};
+use std::time::Duration;
+use tokio::time::Instant;
+
+fn settings(max_blocks_per_minute: Option<u32>) -> RuntimeSettings {
+    RuntimeSettings {
+        max_blocks_per_minute,
+        checkpoint_interval: 1,
+        handler_error_budget: None,
+    }
+}
+
+/// Stand-in for the dispatch loop: handles `blocks` instant blocks, pacing them with
+/// the current settings, and returns when each block started.
+async fn paced_blocks(handle: &SettingsHandle, blocks: usize) -> Vec<Duration> {
+    let start = Instant::now();
+    let mut starts = Vec::new();
+    for _ in 0..blocks {
+        let block_start = Instant::now();
+        starts.push(block_start - start);
+        if let Some(wait) = handle.get().throttle_delay(block_start.elapsed()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+    starts
+}
+
+#[tokio::test(start_paused = true)]
+async fn throttle_change_applies_mid_run() {
+    let handle = SettingsHandle::new(settings(Some(60)));
+    let remote = handle.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(4_500)).await;
+        remote.set_max_blocks_per_minute(Some(600)).unwrap();
+    });
+
+    let starts = paced_blocks(&handle, 8).await;
+    let gaps: Vec<Duration> = starts.windows(2).map(|w| w[1] - w[0]).collect();
+    assert_eq!(gaps[..4], [Duration::from_secs(1); 4]);
+    // The block started at 4s was paced before the change; later ones use the new rate.
+    assert_eq!(gaps[4], Duration::from_secs(1));
+    assert_eq!(gaps[5..], [Duration::from_millis(100); 2]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn removing_the_throttle_stops_pacing() {
+    let handle = SettingsHandle::new(settings(Some(6)));
+    handle.set_max_blocks_per_minute(None).unwrap();
+    let starts = paced_blocks(&handle, 3).await;
+    assert_eq!(starts, [Duration::ZERO; 3]);
+}
+
+#[test]
+fn throttle_delay_accounts_for_processing_time() {
+    let settings = settings(Some(60));
+    assert_eq!(
+        settings.throttle_delay(Duration::from_millis(300)),
+        Some(Duration::from_millis(700))
+    );
+    assert_eq!(settings.throttle_delay(Duration::from_secs(2)), None);
+    assert_eq!(
+        RuntimeSettings {
+            max_blocks_per_minute: None,
+            ..settings
+        }
+        .throttle_delay(Duration::ZERO),
+        None
+    );
+}
+
+#[test]
+fn apply_reports_changes_and_rejects_invalid_values() {
+    let handle = SettingsHandle::new(settings(None));
+    let mut rx = handle.subscribe();
+
+    let changed = handle
+        .apply(RuntimeSettings {
+            checkpoint_interval: 50,
+            handler_error_budget: Some(10),
+            ..settings(None)
+        })
+        .unwrap();
+    assert_eq!(changed, ["checkpoint_interval", "handler_error_budget"]);
+    assert!(rx.has_changed().unwrap());
+    assert_eq!(rx.borrow_and_update().checkpoint_interval, 50);
+
+    assert!(handle.apply(handle.get()).unwrap().is_empty());
+    assert!(!rx.has_changed().unwrap());
+
+    let err = handle.set_checkpoint_interval(0).unwrap_err();
+    assert!(
+        matches!(err, IndexerError::InvalidConfig { field, .. } if field == "checkpoint_interval")
+    );
+    let err = handle.set_max_blocks_per_minute(Some(0)).unwrap_err();
+    assert!(
+        matches!(err, IndexerError::InvalidConfig { field, .. } if field == "max_blocks_per_minute")
+    );
+    assert_eq!(handle.get().checkpoint_interval, 50);
+
+    handle.set_handler_error_budget(None).unwrap();
+    assert_eq!(handle.get().handler_error_budget, None);
+}
+
+#[test]
+fn settings_start_from_the_config() {
+    let cfg = IndexerConfig::builder()
+        .node_url("ws://node")
+        .max_blocks_per_minute(120)
+        .checkpoint_interval(25)
+        .handler_error_budget(3)
+        .build()
+        .unwrap();
+    assert_eq!(
+        RuntimeSettings::from(&cfg),
+        RuntimeSettings {
+            max_blocks_per_minute: Some(120),
+            checkpoint_interval: 25,
+            handler_error_budget: Some(3),
+        }
+    );
+}
+
+#[test]
+fn error_budget_error_names_the_budget() {
+    let err = IndexerError::ErrorBudgetExhausted {
+        errors: 6,
+        budget: 5,
+    };
+    assert_eq!(
+        err.to_string(),
+        "Handlers failed 6 times, exceeding the error budget of 5"
+    );
+}
+
+#[cfg(feature = "hot-reload")]
+#[tokio::test]
+async fn watched_file_changes_are_applied() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("indexer.toml");
+    std::fs::write(
+        &path,
+        "node_url = \"wss://node\"\nmax_blocks_per_minute = 60\n",
+    )
+    .unwrap();
+    let handle = SettingsHandle::new(RuntimeSettings::from(
+        &IndexerConfig::from_file(&path).unwrap(),
+    ));
+    let watcher = handle.watch_file(&path, Duration::from_millis(10)).unwrap();
+
+    std::fs::write(
+        &path,
+        "node_url = \"wss://other\"\nmax_blocks_per_minute = 6\ncheckpoint_interval = 10\n",
+    )
+    .unwrap();
+    // Make sure the change is visible even with coarse modification times.
+    std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(std::time::SystemTime::now() + Duration::from_secs(5))
+        .unwrap();
+
+    let mut rx = handle.subscribe();
+    tokio::time::timeout(Duration::from_secs(5), rx.changed())
+        .await
+        .expect("reload timed out")
+        .unwrap();
+    watcher.abort();
+    assert_eq!(handle.get().max_blocks_per_minute, Some(6));
+    assert_eq!(handle.get().checkpoint_interval, 10);
+}
+
+#[cfg(feature = "hot-reload")]
+#[tokio::test]
+async fn zero_poll_interval_is_rejected() {
+    let handle = SettingsHandle::new(settings(None));
+    let err = handle
+        .watch_file("indexer.toml", Duration::ZERO)
+        .unwrap_err();
+    assert!(matches!(err, IndexerError::InvalidConfig { field, .. } if field == "poll_interval"));
+}
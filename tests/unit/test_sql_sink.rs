@@ -0,0 +1,140 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::handler::EventFilter;
+use flamewire_bittensor_indexer::sql_sink::{SqlSinkHandler, SqlValue};
+use flamewire_bittensor_indexer::IndexerError;
+use sqlx::SqlitePool;
+use subxt::config::substrate::SubstrateConfig;
+
+const INSERT: &str = "INSERT INTO transfers (block, idx, sender, recipient, amount, memo)
+     VALUES ($1, $2, $3, $4, $5, $6)";
+
+type Row = (i64, i64, i64, i64, i64, Option<String>);
+
+async fn pool() -> SqlitePool {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::query(
+        "CREATE TABLE transfers (
+            block INTEGER NOT NULL,
+            idx INTEGER NOT NULL,
+            sender INTEGER NOT NULL,
+            recipient INTEGER NOT NULL,
+            amount INTEGER NOT NULL CHECK (amount < 1000),
+            memo TEXT
+        )",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool
+}
+
+/// Maps transfers to rows and fails on transfers to account 0.
+fn handler(pool: &SqlitePool) -> SqlSinkHandler<SubstrateConfig> {
+    SqlSinkHandler::new(pool.clone(), INSERT, |event, ctx| {
+        let Some(t) = event.as_event::<Transfer>().unwrap() else {
+            return Ok(None);
+        };
+        if t.to == 0 {
+            return Err(IndexerError::handler_failed(
+                "test",
+                ctx.block_number,
+                "transfer to account 0",
+            ));
+        }
+        Ok(Some(vec![
+            SqlValue::Int(Some(ctx.block_number as i64)),
+            event.index().into(),
+            t.from.into(),
+            t.to.into(),
+            SqlValue::Int(Some(t.amount as i64)),
+            SqlValue::Text(None),
+        ]))
+    })
+    .with_filter(EventFilter::pallet("Balances"))
+}
+
+async fn rows(pool: &SqlitePool) -> Vec<Row> {
+    sqlx::query_as("SELECT * FROM transfers ORDER BY block, idx")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_rows_are_inserted() {
+    let pool = pool().await;
+    let handler = handler(&pool);
+
+    write_block(
+        &handler,
+        7,
+        vec![
+            transfer_to(2, 5),
+            Balances::Deposit { who: 3, amount: 1 },
+            transfer_to(3, 6),
+        ],
+    )
+    .await;
+    write_block(&handler, 8, Vec::<Balances>::new()).await;
+
+    assert_eq!(
+        rows(&pool).await,
+        vec![(7, 0, 1, 2, 5, None), (7, 2, 1, 3, 6, None)]
+    );
+}
+
+#[tokio::test]
+async fn test_failed_statement_rolls_back_block() {
+    let pool = pool().await;
+    let handler = handler(&pool);
+
+    write_block(&handler, 7, vec![transfer_to(2, 5)]).await;
+    // The second transfer breaks the CHECK constraint.
+    let result = try_write_block(&handler, 8, vec![transfer_to(2, 6), transfer_to(3, 5_000)]).await;
+
+    assert!(matches!(result, Err(IndexerError::Database(_))));
+    assert_eq!(rows(&pool).await, vec![(7, 0, 1, 2, 5, None)]);
+}
+
+#[tokio::test]
+async fn test_mapper_error_fails_block_in_strict_mode() {
+    let pool = pool().await;
+    let handler = handler(&pool).strict();
+
+    let result = try_write_block(&handler, 7, vec![transfer_to(2, 5), transfer_to(0, 6)]).await;
+
+    assert!(matches!(result, Err(IndexerError::HandlerFailed { .. })));
+    assert!(rows(&pool).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_mapper_error_skips_event_by_default() {
+    let pool = pool().await;
+    let handler = handler(&pool);
+
+    write_block(&handler, 7, vec![transfer_to(0, 5), transfer_to(2, 6)]).await;
+
+    assert_eq!(rows(&pool).await, vec![(7, 1, 1, 2, 6, None)]);
+}
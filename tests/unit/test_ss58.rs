@@ -0,0 +1,71 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::prelude::{ss58, AccountId32, DisplayBittensor};
+use flamewire_bittensor_indexer::IndexerError;
+
+const VECTORS: [(&str, &str); 3] = [
+    (
+        "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d",
+        "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+    ),
+    (
+        "8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48",
+        "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty",
+    ),
+    (
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "5C4hrfjw9DjXZTzV3MwzrrAr9P1MJhSrvWGWqi1eSuyUpnhM",
+    ),
+];
+
+fn account(hex: &str) -> AccountId32 {
+    AccountId32(decode_hex(hex).try_into().unwrap())
+}
+
+#[test]
+fn known_addresses_round_trip() {
+    for (hex, address) in VECTORS {
+        let account = account(hex);
+        assert_eq!(ss58::encode_bittensor(&account), address);
+        assert_eq!(DisplayBittensor(&account).to_string(), address);
+        assert_eq!(ss58::parse_ss58(address).unwrap(), account);
+    }
+}
+
+#[test]
+fn other_network_prefix_is_rejected() {
+    // Alice with the Polkadot prefix 0.
+    let err = ss58::parse_ss58("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5").unwrap_err();
+    assert!(matches!(err, IndexerError::InvalidConfig { .. }));
+    assert!(err.to_string().contains("prefix 0, expected 42"));
+}
+
+#[test]
+fn malformed_addresses_are_rejected() {
+    let mut corrupted = VECTORS[0].1.to_string();
+    corrupted.replace_range(20..21, "X");
+    for address in ["", "5GrwvaEF5zXb", "0OIl", corrupted.as_str()] {
+        assert!(ss58::parse_ss58(address).is_err(), "{address}");
+    }
+    assert!(ss58::parse_ss58(&corrupted)
+        .unwrap_err()
+        .to_string()
+        .contains("checksum"));
+}
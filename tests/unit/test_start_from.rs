@@ -0,0 +1,173 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::config::validate_range;
+use flamewire_bittensor_indexer::{IndexerConfig, IndexerError, StartFrom};
+
+const HEAD: u64 = 500;
+
+fn store_with(checkpoints: &[u64]) -> MockCheckpointStore {
+    let store = MockCheckpointStore::new();
+    store
+        .checkpoints
+        .lock()
+        .unwrap()
+        .extend_from_slice(checkpoints);
+    store
+}
+
+#[tokio::test]
+async fn each_start_position_resolves() {
+    let store = store_with(&[10, 42]);
+    for (start, expected) in [
+        (StartFrom::Genesis, 0),
+        (StartFrom::Checkpoint, 42),
+        (StartFrom::Block(7), 7),
+        (StartFrom::Latest, HEAD),
+    ] {
+        assert_eq!(
+            start.resolve(&store, HEAD).await.unwrap(),
+            expected,
+            "{start:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn checkpoint_falls_back_to_genesis() {
+    let store = store_with(&[]);
+    assert_eq!(
+        StartFrom::Checkpoint.resolve(&store, HEAD).await.unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn only_checkpoint_reads_the_store() {
+    let mut store = store_with(&[42]);
+    store.fail_load = true;
+    for start in [StartFrom::Genesis, StartFrom::Block(3), StartFrom::Latest] {
+        start.resolve(&store, HEAD).await.unwrap();
+    }
+    let err = StartFrom::Checkpoint
+        .resolve(&store, HEAD)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err, IndexerError::CheckpointError { .. }));
+}
+
+#[test]
+fn start_from_defaults_to_checkpoint() {
+    let cfg = IndexerConfig::builder()
+        .node_url("ws://node")
+        .build()
+        .unwrap();
+    assert_eq!(cfg.start_from, StartFrom::Checkpoint);
+
+    let cfg = IndexerConfig::builder()
+        .node_url("ws://node")
+        .start_from_block(9)
+        .build()
+        .unwrap();
+    assert_eq!(cfg.start_from, StartFrom::Block(9));
+}
+
+#[test]
+fn end_block_is_checked_against_explicit_start_block_only() {
+    let err = IndexerConfig::builder()
+        .node_url("ws://node")
+        .start_from(StartFrom::Block(10))
+        .end_at_block(5)
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(err, IndexerError::InvalidConfig { field, .. } if field == "end_block"));
+
+    for start in [StartFrom::Genesis, StartFrom::Checkpoint, StartFrom::Latest] {
+        IndexerConfig::builder()
+            .node_url("ws://node")
+            .start_from(start)
+            .end_at_block(5)
+            .build()
+            .unwrap();
+    }
+}
+
+fn range(start: StartFrom, end: Option<u64>, strict: bool) -> IndexerConfig {
+    let builder = IndexerConfig::builder()
+        .node_url("ws://node")
+        .start_from(start)
+        .strict_range(strict)
+        .range_tolerance(10);
+    match end {
+        Some(end) => builder.end_at_block(end),
+        None => builder,
+    }
+    .build()
+    .unwrap()
+}
+
+fn invalid_field(result: Result<(), IndexerError>) -> String {
+    match result {
+        Err(IndexerError::InvalidConfig { field, .. }) => field,
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn start_within_tolerance_of_head_is_accepted() {
+    for start in [0, HEAD, HEAD + 10] {
+        let cfg = range(StartFrom::Block(start), None, true);
+        validate_range(HEAD, None, &cfg).unwrap();
+    }
+}
+
+#[test]
+fn start_far_past_head_fails_only_when_strict() {
+    let start = StartFrom::Block(99_999_999);
+    let err = validate_range(HEAD, None, &range(start, None, true));
+    assert_eq!(invalid_field(err), "start_from");
+    validate_range(HEAD, None, &range(start, None, false)).unwrap();
+
+    let err = validate_range(HEAD, None, &range(StartFrom::Block(HEAD + 11), None, true));
+    assert_eq!(invalid_field(err), "start_from");
+}
+
+#[test]
+fn named_starts_are_not_range_checked() {
+    for start in [StartFrom::Genesis, StartFrom::Latest, StartFrom::Checkpoint] {
+        validate_range(0, None, &range(start, None, true)).unwrap();
+    }
+}
+
+#[test]
+fn end_block_below_checkpoint_fails() {
+    let cfg = range(StartFrom::Checkpoint, Some(100), false);
+    assert_eq!(
+        invalid_field(validate_range(HEAD, Some(101), &cfg)),
+        "end_block"
+    );
+    validate_range(HEAD, Some(100), &cfg).unwrap();
+    validate_range(HEAD, None, &cfg).unwrap();
+
+    // An explicit start block ignores the checkpoint.
+    let cfg = range(StartFrom::Block(50), Some(100), false);
+    validate_range(HEAD, Some(200), &cfg).unwrap();
+}
@@ -0,0 +1,165 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::retry::{CircuitBreaker, RetryObserver};
+#[cfg(feature = "metrics")]
+use flamewire_bittensor_indexer::validated_types::WebSocketUrl;
+#[cfg(feature = "metrics")]
+use flamewire_bittensor_indexer::IndexerBuilder;
+use flamewire_bittensor_indexer::{IndexerError, StatsdMetrics};
+use std::net::UdpSocket;
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(feature = "metrics")]
+use subxt::config::substrate::SubstrateConfig;
+
+fn server() -> UdpSocket {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    socket
+}
+
+fn recv(socket: &UdpSocket) -> Vec<String> {
+    let mut buf = [0u8; 2048];
+    let len = socket.recv(&mut buf).unwrap();
+    std::str::from_utf8(&buf[..len])
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn flush_sends_counters_gauges_and_timings() {
+    let server = server();
+    let statsd = StatsdMetrics::new(server.local_addr().unwrap()).unwrap();
+    statsd.observe_head(12);
+    statsd.observe_block(10, 3, Duration::from_millis(250));
+    statsd.observe_block(11, 2, Duration::from_millis(5));
+    statsd.flush().unwrap();
+
+    let lines = recv(&server);
+    assert!(lines.contains(&"indexer.blocks_processed:2|c".to_string()));
+    assert!(lines.contains(&"indexer.events_processed:5|c".to_string()));
+    assert!(lines.contains(&"indexer.chain_head:12|g".to_string()));
+    assert!(lines.contains(&"indexer.last_processed_block:11|g".to_string()));
+    assert!(lines.contains(&"indexer.chain_lag_blocks:1|g".to_string()));
+    assert!(lines.contains(&"indexer.block_processing_time:250|ms".to_string()));
+    assert!(lines.contains(&"indexer.block_processing_time:5|ms".to_string()));
+}
+
+#[test]
+fn counters_reset_after_flush() {
+    let server = server();
+    let statsd = StatsdMetrics::new(server.local_addr().unwrap()).unwrap();
+    statsd.observe_block(1, 0, Duration::ZERO);
+    statsd.flush().unwrap();
+    recv(&server);
+
+    statsd.observe_block(2, 4, Duration::ZERO);
+    statsd.flush().unwrap();
+    let lines = recv(&server);
+    assert!(lines.contains(&"indexer.blocks_processed:1|c".to_string()));
+    assert!(lines.contains(&"indexer.events_processed:4|c".to_string()));
+}
+
+#[test]
+fn prefix_and_tags_are_applied() {
+    let server = server();
+    let statsd = StatsdMetrics::new(server.local_addr().unwrap())
+        .unwrap()
+        .with_prefix("tao")
+        .with_tag("env", "prod");
+    statsd.record_handler_error(&IndexerError::handler_failed("my::Handler", 1, "boom"));
+    statsd.record_handler_error(&IndexerError::BlockNotFound { block: 1 });
+    statsd.record_sink_delivery("webhook", 3, false);
+    statsd.flush().unwrap();
+
+    let lines = recv(&server);
+    assert!(lines.contains(&"tao.handler_errors:1|c|#env:prod,handler:my__Handler".to_string()));
+    assert!(lines.contains(&"tao.handler_errors:1|c|#env:prod,handler:unknown".to_string()));
+    assert!(
+        lines.contains(&"tao.sink_events:3|c|#env:prod,sink:webhook,outcome:failed".to_string())
+    );
+}
+
+#[test]
+fn retries_and_breaker_state_are_reported() {
+    let server = server();
+    let statsd = Arc::new(StatsdMetrics::new(server.local_addr().unwrap()).unwrap());
+    let error = IndexerError::BlockNotFound { block: 1 };
+    statsd.on_retry(1, Duration::ZERO, &error);
+    statsd.on_retry(2, Duration::ZERO, &error);
+    statsd.on_give_up(3, &error);
+    let breaker = CircuitBreaker::new(1, Duration::from_secs(60)).with_domain("rpc");
+    statsd.watch_breaker(&breaker);
+    breaker.record_failure();
+    statsd.flush().unwrap();
+
+    let lines = recv(&server);
+    assert!(lines.contains(&"indexer.rpc_retries:2|c".to_string()));
+    assert!(lines.contains(&"indexer.rpc_retry_give_ups:1|c".to_string()));
+    assert!(lines.contains(&"indexer.breaker_state:2|g|#breaker:rpc".to_string()));
+}
+
+#[test]
+fn large_flushes_are_split_into_packets() {
+    let server = server();
+    let statsd = StatsdMetrics::new(server.local_addr().unwrap()).unwrap();
+    for i in 0..100 {
+        statsd.record_sink_delivery(&format!("sink-{i}"), 1, true);
+    }
+    statsd.flush().unwrap();
+
+    let mut lines = Vec::new();
+    while lines.len() < 100 {
+        let packet = recv(&server);
+        assert!(packet.iter().map(|l| l.len() + 1).sum::<usize>() <= 1433);
+        lines.extend(packet);
+    }
+    assert_eq!(lines.len(), 100);
+}
+
+#[tokio::test]
+async fn started_metrics_flush_on_a_timer() {
+    let server = server();
+    let statsd = StatsdMetrics::new(server.local_addr().unwrap())
+        .unwrap()
+        .flush_interval(Duration::from_millis(20))
+        .start();
+    statsd.observe_head(7);
+
+    let lines = tokio::task::spawn_blocking(move || recv(&server))
+        .await
+        .unwrap();
+    assert_eq!(lines, vec!["indexer.chain_head:7|g".to_string()]);
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn statsd_and_prometheus_are_exclusive() {
+    let server = server();
+    let statsd = Arc::new(StatsdMetrics::new(server.local_addr().unwrap()).unwrap());
+    let err = IndexerBuilder::<SubstrateConfig>::new()
+        .connect(WebSocketUrl::parse("ws://node").unwrap())
+        .statsd(statsd)
+        .serve_metrics("127.0.0.1:0")
+        .validate()
+        .unwrap_err();
+    assert!(matches!(err, IndexerError::InvalidConfig { field, .. } if field == "statsd"));
+}
@@ -14,16 +14,26 @@
  * limitations under the License.
  */
 
+#[cfg(feature = "sqlite")]
+#[path = "../common/mod.rs"]
+mod common;
+use flamewire_bittensor_indexer::storage::init::init_store;
+#[cfg(feature = "sqlite")]
+use flamewire_bittensor_indexer::storage::init::migrate_checkpoint;
+#[cfg(any(feature = "json-storage", feature = "sqlite"))]
+use flamewire_bittensor_indexer::storage::init::{export_checkpoint, import_checkpoint};
 #[cfg(feature = "json-storage")]
 use flamewire_bittensor_indexer::storage::json::JsonStore;
 #[cfg(feature = "postgres")]
-use flamewire_bittensor_indexer::storage::postgres::PostgreSQLStore;
+use flamewire_bittensor_indexer::storage::postgres::{PostgreSQLStore, PostgresCheckpointListener};
 #[cfg(feature = "sqlite")]
 use flamewire_bittensor_indexer::storage::sqlite::SQLiteStore;
+#[cfg(feature = "json-storage")]
+use flamewire_bittensor_indexer::storage::Checkpoint;
 use flamewire_bittensor_indexer::CheckpointStore;
-#[cfg(feature = "postgres")]
 use flamewire_bittensor_indexer::IndexerError;
-#[cfg(feature = "json-storage")]
+use flamewire_bittensor_indexer::StorageConfig;
+#[cfg(any(feature = "json-storage", feature = "sqlite"))]
 use tempfile::tempdir;
 
 #[cfg(feature = "json-storage")]
@@ -46,6 +56,196 @@ async fn sqlite_store_cycle() {
     assert_eq!(store.load_checkpoint().await.unwrap(), Some(7));
 }
 
+#[cfg(feature = "json-storage")]
+#[tokio::test]
+async fn shared_store_through_arc() {
+    use flamewire_bittensor_indexer::RetryingStore;
+    use std::sync::Arc;
+
+    let dir = tempdir().unwrap();
+    let shared = Arc::new(JsonStore::new(dir.path().join("chk.json")));
+    let for_indexer: Box<dyn CheckpointStore> =
+        Box::new(RetryingStore::with_defaults(shared.clone()));
+
+    for_indexer.store_checkpoint(21).await.unwrap();
+    assert_eq!(shared.load_checkpoint().await.unwrap(), Some(21));
+
+    let borrowed: &dyn CheckpointStore = &&*shared;
+    assert_eq!(borrowed.load_checkpoint().await.unwrap(), Some(21));
+}
+
+#[cfg(feature = "json-storage")]
+#[tokio::test]
+async fn json_storage_creates_its_directory() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("nested").join("checkpoint.json");
+    let store = init_store(StorageConfig::json(path.clone())).await.unwrap();
+    store.store_checkpoint(5).await.unwrap();
+    assert!(path.exists());
+    assert_eq!(
+        JsonStore::new(path).load_checkpoint().await.unwrap(),
+        Some(5)
+    );
+}
+
+#[tokio::test]
+async fn memory_storage_starts_empty() {
+    let store = init_store(StorageConfig::Memory).await.unwrap();
+    assert_eq!(store.load_checkpoint().await.unwrap(), None);
+    store.store_checkpoint(9).await.unwrap();
+    assert_eq!(store.load_checkpoint().await.unwrap(), Some(9));
+    assert!(store
+        .load_checkpoint_info()
+        .await
+        .unwrap()
+        .unwrap()
+        .updated_at
+        .is_some());
+
+    let fresh = init_store(StorageConfig::Memory).await.unwrap();
+    assert_eq!(fresh.load_checkpoint().await.unwrap(), None);
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_memory_urls_round_trip_through_init_store() {
+    for url in ["sqlite://:memory:", "sqlite::memory:"] {
+        let store = init_store(StorageConfig::parse(url).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(store.load_checkpoint().await.unwrap(), None);
+        store.store_checkpoint(11).await.unwrap();
+        assert_eq!(store.load_checkpoint().await.unwrap(), Some(11));
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_memory_url_keeps_the_checkpoint_of_a_built_indexer() {
+    use common::*;
+    use flamewire_bittensor_indexer::{EventFilter, IndexerBuilder, MockBlockSource, SqliteUrl};
+    use subxt::config::substrate::SubstrateConfig;
+    use subxt::events::Phase;
+
+    let source =
+        MockBlockSource::<SubstrateConfig>::new(pallet_metadata_bytes::<TestEvent>("Test"))
+            .with_blocks(1..=5, |n| {
+                event_bytes(vec![EventRecord::new(
+                    Phase::Initialization,
+                    TestEvent::A(n as u8),
+                )])
+            })
+            .finalized(5);
+    let mut indexer = IndexerBuilder::<SubstrateConfig>::new()
+        .block_source(source)
+        .with_sqlite(SqliteUrl::parse("sqlite://:memory:").unwrap())
+        .start_from_block(1)
+        .end_at_block(3)
+        .add_handler(MockHandler::new(EventFilter::all()))
+        .build()
+        .await
+        .unwrap();
+    let summary = indexer.run().await.unwrap();
+
+    assert_eq!(summary.last_block, Some(3));
+    let checkpoint = indexer.health().await.unwrap().checkpoint.unwrap();
+    assert_eq!(checkpoint.block, 3);
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_file_urls_round_trip_through_init_store() {
+    let dir = tempdir().unwrap();
+    for name in ["plain.db", "rwc.db?mode=rwc"] {
+        let url = format!("sqlite://{}", dir.path().join(name).display());
+        let store = init_store(StorageConfig::parse(&url).unwrap())
+            .await
+            .unwrap();
+        store.store_checkpoint(3).await.unwrap();
+        drop(store);
+
+        let reopened = init_store(StorageConfig::parse(&url).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(reopened.load_checkpoint().await.unwrap(), Some(3));
+    }
+}
+
+#[cfg(feature = "json-storage")]
+#[tokio::test]
+async fn json_store_records_updated_at() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("chk.json");
+    std::fs::write(&path, r#"{ "last_block": 4 }"#).unwrap();
+    let store = JsonStore::new(&path);
+
+    let legacy = store.load_checkpoint_info().await.unwrap().unwrap();
+    assert_eq!(legacy, Checkpoint::new(4));
+    assert_eq!(legacy.staleness(), None);
+
+    store.store_checkpoint(5).await.unwrap();
+    let info = store.load_checkpoint_info().await.unwrap().unwrap();
+    assert_eq!(info.block, 5);
+    assert!(info.staleness().unwrap() < std::time::Duration::from_secs(60));
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_store_migrates_legacy_table() {
+    let dir = tempdir().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("legacy.db").display());
+    {
+        use sqlx::sqlite::SqlitePoolOptions;
+        let pool = SqlitePoolOptions::new()
+            .connect(&format!("{url}?mode=rwc"))
+            .await
+            .unwrap();
+        sqlx::query(
+            "CREATE TABLE indexer_checkpoint (id TEXT PRIMARY KEY, last_block BIGINT NOT NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO indexer_checkpoint (id, last_block) VALUES ('bittensor', 8)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+    }
+
+    let store = SQLiteStore::new(&url).await.unwrap();
+    let legacy = store.load_checkpoint_info().await.unwrap().unwrap();
+    assert_eq!((legacy.block, legacy.updated_at), (8, None));
+
+    store.store_checkpoint(9).await.unwrap();
+    let info = store.load_checkpoint_info().await.unwrap().unwrap();
+    assert_eq!(info.block, 9);
+    assert!(info.updated_at.is_some());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_url_normalization() {
+    use flamewire_bittensor_indexer::SqliteUrl;
+
+    let url = SqliteUrl::parse("sqlite://:memory:").unwrap();
+    assert!(url.is_memory());
+    assert_eq!(url.to_connection_string(), "sqlite://:memory:");
+    assert!(SqliteUrl::parse("sqlite::memory:").unwrap().is_memory());
+
+    let url = SqliteUrl::parse("sqlite://data/file.db").unwrap();
+    assert!(!url.is_memory());
+    assert_eq!(url.as_path().to_str(), Some("data/file.db"));
+    assert_eq!(url.to_connection_string(), "sqlite://data/file.db?mode=rwc");
+
+    let url = SqliteUrl::parse("sqlite://file.db?mode=ro").unwrap();
+    assert_eq!(url.query(), Some("mode=ro"));
+    assert_eq!(url.to_connection_string(), "sqlite://file.db?mode=ro");
+    assert_eq!(url.to_string(), "sqlite://file.db?mode=ro");
+
+    assert!(SqliteUrl::parse("file.db").is_err());
+}
+
 #[cfg(feature = "postgres")]
 #[tokio::test]
 async fn postgres_store_failure() {
@@ -55,3 +255,200 @@ async fn postgres_store_failure() {
         _ => panic!("unexpected result"),
     }
 }
+
+#[cfg(all(feature = "json-storage", feature = "sqlite"))]
+#[tokio::test]
+async fn json_to_sqlite_checkpoint_migration() {
+    let dir = tempdir().unwrap();
+    let json = JsonStore::new(dir.path().join("chk.json"));
+    json.store_checkpoint(42).await.unwrap();
+
+    let sqlite = SQLiteStore::new("sqlite::memory:").await.unwrap();
+    let checkpoint = export_checkpoint(&json).await.unwrap().unwrap();
+    assert_eq!(checkpoint.block, 42);
+    assert!(checkpoint.updated_at.is_some());
+    import_checkpoint(&sqlite, &checkpoint, false)
+        .await
+        .unwrap();
+    assert_eq!(sqlite.load_checkpoint().await.unwrap(), Some(42));
+}
+
+#[cfg(feature = "json-storage")]
+#[tokio::test]
+async fn import_refuses_to_rewind_checkpoint() {
+    let dir = tempdir().unwrap();
+    let store = JsonStore::new(dir.path().join("chk.json"));
+    store.store_checkpoint(100).await.unwrap();
+
+    let res = import_checkpoint(&store, &Checkpoint::new(10), false).await;
+    match res {
+        Err(IndexerError::CheckpointConflict { existing, incoming }) => {
+            assert_eq!((existing, incoming), (100, 10))
+        }
+        _ => panic!("unexpected result: {res:?}"),
+    }
+    assert_eq!(store.load_checkpoint().await.unwrap(), Some(100));
+
+    import_checkpoint(&store, &Checkpoint::new(10), true)
+        .await
+        .unwrap();
+    assert_eq!(store.load_checkpoint().await.unwrap(), Some(10));
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn migrate_checkpoint_between_sqlite_files() {
+    let dir = tempdir().unwrap();
+    let from = format!("sqlite://{}", dir.path().join("from.db").display());
+    let to = format!("sqlite://{}", dir.path().join("to.db").display());
+
+    let empty = migrate_checkpoint(Some(from.clone()), Some(to.clone()), false)
+        .await
+        .unwrap();
+    assert_eq!(empty, None);
+
+    let source = SQLiteStore::new(&from).await.unwrap();
+    source.store_checkpoint(9).await.unwrap();
+    drop(source);
+
+    let migrated = migrate_checkpoint(Some(from), Some(to.clone()), false)
+        .await
+        .unwrap();
+    assert_eq!(migrated.map(|checkpoint| checkpoint.block), Some(9));
+
+    let target = SQLiteStore::new(&to).await.unwrap();
+    assert_eq!(target.load_checkpoint().await.unwrap(), Some(9));
+}
+
+#[cfg(not(feature = "postgres"))]
+#[tokio::test]
+async fn postgres_url_without_feature_is_reported() {
+    let spec = StorageConfig::parse("postgres://user@localhost/db").unwrap();
+    let res = init_store(spec).await;
+    match res {
+        Err(flamewire_bittensor_indexer::IndexerError::StorageBackendUnavailable {
+            scheme,
+            feature,
+        }) => {
+            assert_eq!(scheme, "postgres");
+            assert_eq!(feature, "postgres");
+        }
+        _ => panic!("unexpected result"),
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn postgres_checkpoint_notifications() {
+    use futures::StreamExt;
+    use std::time::Duration;
+
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        return;
+    };
+    let mut updates = PostgresCheckpointListener::subscribe(&url, "indexer_progress_test")
+        .await
+        .unwrap();
+    let store = PostgreSQLStore::new(&url)
+        .await
+        .unwrap()
+        .with_notifications("indexer_progress_test");
+    store.store_checkpoint(1234).await.unwrap();
+
+    let block = tokio::time::timeout(Duration::from_secs(5), updates.next())
+        .await
+        .expect("notification not received");
+    assert_eq!(block, Some(1234));
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_read_only_store_rejects_writes() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("indexer.db");
+    let url = format!("sqlite://{}", path.display());
+    let store = init_store(StorageConfig::parse(&url).unwrap())
+        .await
+        .unwrap();
+    store.store_checkpoint(21).await.unwrap();
+    drop(store);
+
+    let read_only = init_store(StorageConfig::parse(&format!("{url}?mode=ro")).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(read_only.load_checkpoint().await.unwrap(), Some(21));
+    match read_only.store_checkpoint(22).await {
+        Err(IndexerError::CheckpointError {
+            operation, backend, ..
+        }) => assert_eq!(
+            (operation.as_str(), backend.as_str()),
+            ("store_checkpoint", "sqlite")
+        ),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_read_only_store_does_not_create_a_database() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("missing/indexer.db");
+    for mode in ["ro", "rw"] {
+        let url = format!("sqlite://{}?mode={mode}", path.display());
+        match init_store(StorageConfig::parse(&url).unwrap()).await {
+            Err(IndexerError::CheckpointError { operation, .. }) => {
+                assert_eq!(operation, "connect")
+            }
+            Err(other) => panic!("unexpected error: {other}"),
+            Ok(_) => panic!("{mode}: opened a missing database"),
+        }
+    }
+    assert!(!path.exists());
+    assert!(!dir.path().join("missing").exists());
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_connection_parameters_are_applied() {
+    let dir = tempdir().unwrap();
+    let url = format!(
+        "sqlite://{}?mode=rwc&cache=shared&busy_timeout=5000&journal_mode=wal&synchronous=normal",
+        dir.path().join("tuned.db").display()
+    );
+    let store = init_store(StorageConfig::parse(&url).unwrap())
+        .await
+        .unwrap();
+    store.store_checkpoint(1).await.unwrap();
+    assert!(dir.path().join("tuned.db-wal").exists());
+}
+
+#[test]
+fn sqlite_unknown_parameters_are_rejected() {
+    use flamewire_bittensor_indexer::SqliteUrl;
+
+    for (input, expected) in [
+        ("sqlite://a.db?foo=1", "unknown SQLite parameter `foo`"),
+        (
+            "sqlite://a.db?busy_timeout=soon",
+            "unsupported busy_timeout `soon`",
+        ),
+        (
+            "sqlite://a.db?journal_mode=fast",
+            "unsupported journal_mode `fast`",
+        ),
+        (
+            "sqlite://a.db?synchronous=always",
+            "unsupported synchronous `always`",
+        ),
+        ("sqlite://a.db?cache=big", "unsupported cache `big`"),
+    ] {
+        match SqliteUrl::parse(input) {
+            Err(IndexerError::InvalidConfig { field, message }) => {
+                assert_eq!(field, "database_url");
+                assert!(message.contains(expected), "{input}: {message}");
+            }
+            other => panic!("{input}: unexpected result: {other:?}"),
+        }
+    }
+    assert!(SqliteUrl::parse("sqlite://a.db?journal_mode=WAL&synchronous=FULL").is_ok());
+}
@@ -0,0 +1,133 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::handler::{Context, Handler};
+use flamewire_bittensor_indexer::presets::subnet_metrics::SubnetMetrics;
+use flamewire_bittensor_indexer::{ChainEvent, IndexerError};
+use parity_scale_codec::{Decode, Encode};
+use prometheus::{Registry, TextEncoder};
+use scale_info::TypeInfo;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::AccountId32;
+
+#[derive(Encode, Decode, TypeInfo)]
+enum SubtensorModule {
+    NeuronRegistered(u16, u16, AccountId32),
+    AxonServed(u16, AccountId32),
+    StakeAdded(AccountId32, AccountId32, u64, u64, u16, u64),
+    StakeRemoved(AccountId32, AccountId32, u64, u64, u16, u64),
+    EmissionValuesSet(u16, u64),
+    PendingEmission { amount: u64, netuid: u16 },
+    NetworkRemoved(u16),
+}
+
+fn hotkey() -> AccountId32 {
+    AccountId32([1; 32])
+}
+
+fn coldkey() -> AccountId32 {
+    AccountId32([2; 32])
+}
+
+async fn handle(metrics: &SubnetMetrics, block: u64, block_events: Vec<SubtensorModule>) {
+    let records = block_events
+        .into_iter()
+        .map(|event| EventRecord::new(Phase::ApplyExtrinsic(1), event))
+        .collect();
+    let evs = events(
+        pallet_metadata::<SubtensorModule>("SubtensorModule"),
+        records,
+    );
+    let ces: Vec<ChainEvent<SubstrateConfig>> = ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect();
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(block)
+        .build();
+    metrics.handle_block(&ctx, &ces).await.unwrap();
+}
+
+fn scrape(registry: &Registry) -> String {
+    TextEncoder::new()
+        .encode_to_string(&registry.gather())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn counters_are_labeled_by_netuid() {
+    let registry = Registry::new();
+    let metrics = SubnetMetrics::register(&registry)
+        .unwrap()
+        .max_netuid(512)
+        .track_event("EmissionValuesSet")
+        .track_event("PendingEmission");
+
+    handle(
+        &metrics,
+        1,
+        vec![
+            SubtensorModule::NeuronRegistered(1, 0, hotkey()),
+            SubtensorModule::NeuronRegistered(3, 0, hotkey()),
+            SubtensorModule::AxonServed(3, hotkey()),
+            SubtensorModule::StakeAdded(coldkey(), hotkey(), 500, 480, 1, 3),
+        ],
+    )
+    .await;
+    handle(
+        &metrics,
+        2,
+        vec![
+            SubtensorModule::NeuronRegistered(1, 1, hotkey()),
+            SubtensorModule::NeuronRegistered(2000, 0, hotkey()),
+            SubtensorModule::StakeRemoved(coldkey(), hotkey(), 200, 190, 1, 3),
+            SubtensorModule::EmissionValuesSet(3, 1_000),
+            SubtensorModule::PendingEmission {
+                amount: 7,
+                netuid: 4,
+            },
+            SubtensorModule::NetworkRemoved(5),
+        ],
+    )
+    .await;
+
+    let text = scrape(&registry);
+    for series in [
+        "bittensor_subnet_registrations_total{netuid=\"1\"} 2",
+        "bittensor_subnet_registrations_total{netuid=\"3\"} 1",
+        "bittensor_subnet_registrations_total{netuid=\"other\"} 1",
+        "bittensor_subnet_axons_served_total{netuid=\"3\"} 1",
+        "bittensor_subnet_stake_added_rao_total{netuid=\"1\"} 500",
+        "bittensor_subnet_stake_removed_rao_total{netuid=\"1\"} 200",
+        "bittensor_subnet_events_total{event=\"EmissionValuesSet\",netuid=\"3\"} 1",
+        "bittensor_subnet_events_total{event=\"PendingEmission\",netuid=\"4\"} 1",
+    ] {
+        assert!(text.contains(series), "missing {series} in\n{text}");
+    }
+    assert!(!text.contains("NetworkRemoved"), "{text}");
+    assert!(!text.contains("netuid=\"2000\""), "{text}");
+}
+
+#[test]
+fn registering_twice_fails() {
+    let registry = Registry::new();
+    SubnetMetrics::register(&registry).unwrap();
+    let err = SubnetMetrics::register(&registry).err().unwrap();
+    assert!(matches!(err, IndexerError::Metrics(_)), "{err}");
+}
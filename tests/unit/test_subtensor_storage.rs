@@ -0,0 +1,148 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::handler::Context;
+use flamewire_bittensor_indexer::subtensor_storage::{
+    self, burn_address, difficulty_address, subnet_n_address, total_hotkey_stake_address,
+};
+use flamewire_bittensor_indexer::IndexerError;
+use frame_metadata::v15::{
+    PalletStorageMetadata, StorageEntryMetadata, StorageEntryModifier, StorageEntryType,
+    StorageHasher,
+};
+use scale_info::{meta_type, TypeInfo};
+use subxt::config::substrate::SubstrateConfig;
+use subxt::ext::subxt_core::storage::get_address_bytes;
+use subxt::metadata::Metadata;
+use subxt::utils::AccountId32;
+
+/// twox128("SubtensorModule")
+const PALLET_PREFIX: &str = "658faa385070e074c85bf6b568cf0555";
+
+#[derive(TypeInfo)]
+enum Event {
+    Dummy,
+}
+
+fn map_entry<K: TypeInfo + 'static, V: TypeInfo + 'static>(
+    name: &'static str,
+) -> StorageEntryMetadata {
+    StorageEntryMetadata {
+        name,
+        modifier: StorageEntryModifier::Default,
+        ty: StorageEntryType::Map {
+            hashers: vec![StorageHasher::Identity],
+            key: meta_type::<K>(),
+            value: meta_type::<V>(),
+        },
+        default: vec![0; std::mem::size_of::<V>()],
+        docs: vec![],
+    }
+}
+
+fn metadata() -> Metadata {
+    pallet_metadata_with_storage::<Event>(
+        "SubtensorModule",
+        Some(PalletStorageMetadata {
+            prefix: "SubtensorModule",
+            entries: vec![
+                map_entry::<[u8; 32], u64>("TotalHotkeyStake"),
+                map_entry::<u16, u16>("SubnetworkN"),
+                map_entry::<u16, u64>("Difficulty"),
+                map_entry::<u16, u64>("Burn"),
+            ],
+        }),
+    )
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[test]
+fn total_hotkey_stake_key_is_prefixed_identity_hotkey() {
+    let hotkey = AccountId32([7u8; 32]);
+    let key = get_address_bytes(&total_hotkey_stake_address(&hotkey), &metadata()).unwrap();
+    let expected = format!(
+        "{PALLET_PREFIX}7b4e834c482cd6f103e108dacad0ab65{}",
+        hex(&hotkey.0)
+    );
+    assert_eq!(hex(&key), expected);
+}
+
+#[test]
+fn subnet_n_key_is_prefixed_identity_netuid() {
+    let key = get_address_bytes(&subnet_n_address(18), &metadata()).unwrap();
+    assert_eq!(
+        hex(&key),
+        format!("{PALLET_PREFIX}a1048e9d244171852dfe8db314dc68ca1200")
+    );
+}
+
+#[test]
+fn registration_cost_keys_are_prefixed_identity_netuid() {
+    let key = get_address_bytes(&difficulty_address(3), &metadata()).unwrap();
+    assert_eq!(
+        hex(&key),
+        format!("{PALLET_PREFIX}7d15dd66fbf0cbda1d3a651b5e606df20300")
+    );
+    let key = get_address_bytes(&burn_address(3), &metadata()).unwrap();
+    assert_eq!(
+        hex(&key),
+        format!("{PALLET_PREFIX}01be1755d08418802946bca51b6863250300")
+    );
+}
+
+#[test]
+fn unknown_storage_item_is_rejected() {
+    let metadata = pallet_metadata_with_storage::<Event>(
+        "SubtensorModule",
+        Some(PalletStorageMetadata {
+            prefix: "SubtensorModule",
+            entries: vec![map_entry::<u16, u16>("SubnetworkN")],
+        }),
+    );
+    let hotkey = AccountId32([7u8; 32]);
+    assert!(get_address_bytes(&total_hotkey_stake_address(&hotkey), &metadata).is_err());
+}
+
+#[tokio::test]
+async fn helpers_name_the_item_without_storage_access() {
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(1)
+        .build();
+    assert!(ctx.storage().is_none());
+
+    let err = subtensor_storage::subnet_n(&ctx, 18).await.unwrap_err();
+    match &err {
+        IndexerError::StorageQueryFailed { pallet, item, .. } => {
+            assert_eq!(pallet, "SubtensorModule");
+            assert_eq!(item, "SubnetworkN");
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+    assert!(err
+        .to_string()
+        .starts_with("Storage query SubtensorModule.SubnetworkN failed"));
+
+    let err = subtensor_storage::total_hotkey_stake(&ctx, &AccountId32([0u8; 32]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("TotalHotkeyStake"));
+}
@@ -0,0 +1,266 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use async_trait::async_trait;
+use flamewire_bittensor_indexer::handler::Handler;
+use flamewire_bittensor_indexer::retry::{
+    retry_span, retry_with_backoff, BreakerDomain, CircuitBreaker, RetryConfig,
+};
+use flamewire_bittensor_indexer::{block_span, handler_span, record_span_error, IndexerError};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::utils::H256;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Instrument, Level, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+#[derive(Clone, Default)]
+struct Fields(HashMap<String, String>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+}
+
+struct CapturedEvent {
+    level: Level,
+    /// Fields of every span the event was emitted in.
+    span_fields: HashMap<String, String>,
+}
+
+/// Layer recording each event together with the fields of its enclosing spans.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<CapturedEvent>>>);
+
+impl<S> Layer<S> for Capture
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        ctx.span(id).unwrap().extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        if let Some(fields) = span.extensions_mut().get_mut::<Fields>() {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut span_fields = HashMap::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(fields) = span.extensions().get::<Fields>() {
+                    span_fields.extend(fields.0.clone());
+                }
+            }
+        }
+        self.0.lock().unwrap().push(CapturedEvent {
+            level: *event.metadata().level(),
+            span_fields,
+        });
+    }
+}
+
+struct CapturedSpan {
+    name: &'static str,
+    parent: Option<&'static str>,
+    fields: HashMap<String, String>,
+}
+
+/// Layer recording the name, parent and final fields of each closed span.
+#[derive(Clone, Default)]
+struct SpanCapture(Arc<Mutex<Vec<CapturedSpan>>>);
+
+impl SpanCapture {
+    fn get(
+        &self,
+        name: &str,
+        field: &str,
+        value: &str,
+    ) -> Option<(Option<&'static str>, HashMap<String, String>)> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|span| {
+                span.name == name && span.fields.get(field).map(String::as_str) == Some(value)
+            })
+            .map(|span| (span.parent, span.fields.clone()))
+    }
+}
+
+impl<S> Layer<S> for SpanCapture
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        ctx.span(id).unwrap().extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        if let Some(fields) = span.extensions_mut().get_mut::<Fields>() {
+            values.record(fields);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = ctx.span(&id).unwrap();
+        let fields = span
+            .extensions()
+            .get::<Fields>()
+            .map(|fields| fields.0.clone())
+            .unwrap_or_default();
+        self.0.lock().unwrap().push(CapturedSpan {
+            name: span.name(),
+            parent: span.parent().map(|parent| parent.name()),
+            fields,
+        });
+    }
+}
+
+struct StakeWriter;
+
+#[async_trait]
+impl Handler<SubstrateConfig> for StakeWriter {}
+
+#[test]
+fn handler_spans_nest_under_the_block_span() {
+    let capture = SpanCapture::default();
+    let hash = H256::repeat_byte(7);
+    let error = IndexerError::handler_failed("stakes", 42, "boom");
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(capture.clone()), || {
+        let block = block_span(42, hash);
+        block.record("events", 3);
+        block.in_scope(|| {
+            handler_span("transfers", 2).in_scope(|| tracing::debug!("handled"));
+            record_span_error(&handler_span("stakes", 1), &error);
+        });
+    });
+
+    let (parent, fields) = capture.get("index_block", "block", "42").unwrap();
+    assert_eq!(parent, None);
+    assert_eq!(fields["hash"], format!("{hash:?}"));
+    assert_eq!(fields["events"], "3");
+
+    let (parent, fields) = capture.get("handler", "handler", "transfers").unwrap();
+    assert_eq!(parent, Some("index_block"));
+    assert_eq!(fields["matched_events"], "2");
+    assert!(!fields.contains_key("error"));
+
+    let (parent, fields) = capture.get("handler", "handler", "stakes").unwrap();
+    assert_eq!(parent, Some("index_block"));
+    assert_eq!(fields["matched_events"], "1");
+    assert_eq!(fields["error"], error.to_string());
+    assert_eq!(fields["otel.status_code"], "ERROR");
+}
+
+#[test]
+fn handler_name_defaults_to_the_type_name() {
+    let name = Handler::<SubstrateConfig>::name(&StakeWriter).to_string();
+    assert!(name.ends_with("::StakeWriter"), "{name}");
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_warnings_carry_operation_block_and_attempt() {
+    let capture = Capture::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let config = RetryConfig {
+        max_retries: 3,
+        initial_delay: Duration::from_millis(10),
+        ..Default::default()
+    };
+    let breaker = CircuitBreaker::new(10, Duration::from_secs(1));
+    let calls = AtomicUsize::new(0);
+    let result = retry_with_backoff(
+        || async {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(IndexerError::Io(std::io::Error::from(
+                    std::io::ErrorKind::TimedOut,
+                )))
+            } else {
+                Ok(())
+            }
+        },
+        &config,
+        &breaker,
+    )
+    .instrument(retry_span("chain_get_block_hash", Some(1_234_567)))
+    .await;
+    assert!(result.is_ok());
+
+    let events = capture.0.lock().unwrap();
+    let warnings: Vec<_> = events.iter().filter(|e| e.level == Level::WARN).collect();
+    assert_eq!(warnings.len(), 2);
+    for (i, warning) in warnings.iter().enumerate() {
+        let fields = &warning.span_fields;
+        assert_eq!(fields["operation"], "chain_get_block_hash");
+        assert_eq!(fields["block"], "1234567");
+        assert_eq!(fields["attempt"], (i + 1).to_string());
+    }
+}
+
+#[test]
+fn retry_span_leaves_block_empty_when_unknown() {
+    let capture = Capture::default();
+    let subscriber = tracing_subscriber::registry().with(capture.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        retry_span("chain_get_finalized_head", None).in_scope(|| tracing::info!("inside"));
+    });
+
+    let events = capture.0.lock().unwrap();
+    assert_eq!(
+        events[0].span_fields["operation"],
+        "chain_get_finalized_head"
+    );
+    assert!(!events[0].span_fields.contains_key("block"));
+}
+
+#[test]
+fn sources_lists_the_error_chain() {
+    let err = IndexerError::CheckpointError {
+        operation: "store".into(),
+        backend: "postgres".into(),
+        source: Box::new(std::io::Error::other("disk full")),
+    };
+    assert_eq!(err.sources(), vec!["disk full".to_string()]);
+    assert!(IndexerError::CircuitOpen {
+        domain: BreakerDomain::Storage
+    }
+    .sources()
+    .is_empty());
+}
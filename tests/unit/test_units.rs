@@ -0,0 +1,148 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use flamewire_bittensor_indexer::units::{Rao, RAO_PER_TAO};
+use flamewire_bittensor_indexer::IndexerError;
+use proptest::prelude::*;
+
+const MAX_TAO: &str = "340282366920938463463374607431.768211455";
+
+#[test]
+fn formats_edge_amounts() {
+    assert_eq!(Rao(0).to_tao_string(0), "0");
+    assert_eq!(Rao(0).to_tao_string(2), "0.00");
+    assert_eq!(Rao(0).to_string(), "τ0");
+
+    assert_eq!(Rao(1).to_tao_string(9), "0.000000001");
+    assert_eq!(Rao(1).to_tao_string(8), "0.00000000");
+    assert_eq!(Rao(1).to_string(), "τ0.000000001");
+
+    assert_eq!(Rao(u128::MAX).to_tao_string(9), MAX_TAO);
+    assert_eq!(
+        Rao(u128::MAX).to_tao_string(2),
+        "340282366920938463463374607431.77"
+    );
+    assert_eq!(
+        Rao(u128::MAX).to_tao_string(0),
+        "340282366920938463463374607432"
+    );
+    assert_eq!(Rao(u128::MAX).to_string(), format!("τ{MAX_TAO}"));
+}
+
+#[test]
+fn rounds_half_up_and_pads() {
+    assert_eq!(Rao(1_500_000_000).to_tao_string(2), "1.50");
+    assert_eq!(Rao(1_500_000_000).to_tao_string(12), "1.500000000000");
+    assert_eq!(Rao(1_500_000_000).to_string(), "τ1.5");
+    assert_eq!(Rao(5).to_tao_string(8), "0.00000001");
+    assert_eq!(Rao(4).to_tao_string(8), "0.00000000");
+    assert_eq!(Rao(999_999_999).to_tao_string(2), "1.00");
+    assert_eq!(Rao(2_499_999_999).to_tao_string(0), "2");
+    assert_eq!(Rao(2_500_000_000).to_tao_string(0), "3");
+}
+
+#[test]
+fn parses_tao_exactly() {
+    assert_eq!(Rao::from_tao_str("0").unwrap(), Rao::ZERO);
+    assert_eq!(Rao::from_tao_str("1").unwrap(), Rao::ONE_TAO);
+    assert_eq!(Rao::from_tao_str("1.5").unwrap(), Rao(1_500_000_000));
+    assert_eq!(Rao::from_tao_str("0.000000001").unwrap(), Rao(1));
+    assert_eq!(
+        Rao::from_tao_str("2.100000000000").unwrap(),
+        Rao(2_100_000_000)
+    );
+    assert_eq!(Rao::from_tao_str(MAX_TAO).unwrap(), Rao(u128::MAX));
+    assert_eq!(Rao::from_tao(3), Rao(3 * RAO_PER_TAO));
+}
+
+#[test]
+fn rejects_malformed_and_lossy_amounts() {
+    for bad in [
+        "",
+        ".",
+        "1.",
+        ".5",
+        "-1",
+        "+1",
+        "1e9",
+        "1,5",
+        "1_000",
+        " 1",
+        "τ1",
+        "0.0000000001",
+        "340282366920938463463374607431.768211456",
+        "340282366920938463463374607432",
+    ] {
+        match Rao::from_tao_str(bad) {
+            Err(IndexerError::InvalidConfig { field, .. }) => assert_eq!(field, "amount", "{bad}"),
+            other => panic!("`{bad}` parsed as {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn arithmetic() {
+    let mut total = Rao(10) + Rao(5) - Rao(3);
+    assert_eq!(total, Rao(12));
+    total += Rao::ONE_TAO;
+    total -= Rao(2);
+    assert_eq!(total, Rao(RAO_PER_TAO + 10));
+    assert_eq!([Rao(1), Rao(2), Rao(3)].iter().sum::<Rao>(), Rao(6));
+    assert_eq!(Rao(u128::MAX).checked_add(Rao(1)), None);
+    assert_eq!(Rao(0).checked_sub(Rao(1)), None);
+    assert_eq!(Rao(0).saturating_sub(Rao(1)), Rao::ZERO);
+    assert_eq!(Rao(u128::MAX).saturating_add(Rao(1)), Rao(u128::MAX));
+    assert_eq!(u128::from(Rao::from(7u64)), 7);
+}
+
+#[cfg(feature = "json-storage")]
+#[test]
+fn serializes_as_a_rao_string() {
+    assert_eq!(
+        serde_json::to_string(&Rao(u128::MAX)).unwrap(),
+        format!("\"{}\"", u128::MAX)
+    );
+    let rao: Rao = serde_json::from_str("\"1500000000\"").unwrap();
+    assert_eq!(rao, Rao(1_500_000_000));
+    assert!(serde_json::from_str::<Rao>("1500000000").is_err());
+    assert!(serde_json::from_str::<Rao>("\"1.5\"").is_err());
+}
+
+#[test]
+fn prop_full_precision_round_trips() {
+    proptest!(|(rao in any::<u128>())| {
+        let tao = Rao(rao).to_tao_string(9);
+        prop_assert_eq!(Rao::from_tao_str(&tao).unwrap(), Rao(rao));
+        let shown = Rao(rao).to_string();
+        prop_assert_eq!(Rao::from_tao_str(shown.trim_start_matches('τ')).unwrap(), Rao(rao));
+    });
+}
+
+#[test]
+fn prop_rounding_is_within_half_a_unit() {
+    proptest!(|(rao in any::<u128>(), places in 0usize..=12)| {
+        let tao = Rao(rao).to_tao_string(places);
+        let decimals = tao.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+        prop_assert_eq!(decimals, places);
+
+        let parsed = Rao::from_tao_str(&tao);
+        // Rounding u128::MAX up to whole TAO no longer fits in a u128.
+        prop_assume!(parsed.is_ok());
+        let parsed = parsed.unwrap().get();
+        let unit = 10u128.pow(9u32.saturating_sub(places as u32));
+        prop_assert!(parsed.abs_diff(rao) <= unit / 2);
+    });
+}
@@ -0,0 +1,308 @@
+/*
+ * Copyright 2025 Flamewire
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[path = "../common/mod.rs"]
+mod common;
+use common::*;
+use flamewire_bittensor_indexer::handler::{Context, EventFilter, Handler};
+use flamewire_bittensor_indexer::retry::RetryConfig;
+use flamewire_bittensor_indexer::{ChainEvent, IndexerError, WebhookHandler};
+use serde_json::{json, Value};
+use std::time::Duration;
+use subxt::config::substrate::SubstrateConfig;
+use subxt::events::Phase;
+use subxt::utils::H256;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn chain_events() -> Vec<ChainEvent<SubstrateConfig>> {
+    let evs = events(
+        test_metadata::<TestEvent>(),
+        vec![
+            EventRecord::new(Phase::ApplyExtrinsic(0), TestEvent::A(1)),
+            EventRecord::new(Phase::ApplyExtrinsic(1), TestEvent::B(true)),
+            EventRecord::new(Phase::ApplyExtrinsic(2), TestEvent::A(2)),
+        ],
+    );
+    ChainEvent::from_events(&evs)
+        .map(|event| event.unwrap())
+        .collect()
+}
+
+fn fast_retry() -> RetryConfig {
+    RetryConfig {
+        max_retries: 3,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(2),
+        backoff_multiplier: 1.0,
+        ..Default::default()
+    }
+}
+
+/// Run `webhook` over the test block like the indexer would.
+async fn handle(webhook: &WebhookHandler, ctx: &Context<SubstrateConfig>) -> Vec<IndexerError> {
+    let events = chain_events();
+    let mut errors = Vec::new();
+    if let Err(e) = webhook.handle_block(ctx, &events).await {
+        errors.push(e);
+    }
+    let filter = Handler::<SubstrateConfig>::event_filter(webhook);
+    for event in events.iter().filter(|e| filter.matches_event(*e)) {
+        if let Err(e) = webhook.handle_event(event, ctx).await {
+            errors.push(e);
+        }
+    }
+    errors
+}
+
+async fn bodies(server: &MockServer) -> Vec<Value> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_rejects_non_http_urls() {
+    assert!(matches!(
+        WebhookHandler::new("ftp://example.com/hook"),
+        Err(IndexerError::InvalidConfig { .. })
+    ));
+    assert!(WebhookHandler::new("not a url").is_err());
+}
+
+#[tokio::test]
+async fn test_posts_each_matching_event_as_envelope() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .and(header("authorization", "Bearer secret"))
+        .and(header("content-type", "application/json"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let webhook = WebhookHandler::new(format!("{}/hook", server.uri()))
+        .unwrap()
+        .with_filter(EventFilter::event("Test", "A"))
+        .with_auth_header("Bearer secret");
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(7)
+        .block_hash(H256::repeat_byte(1))
+        .build();
+    assert!(handle(&webhook, &ctx).await.is_empty());
+
+    let bodies = bodies(&server).await;
+    assert_eq!(
+        bodies[0],
+        json!({
+            "block_number": 7,
+            "block_hash": format!("0x{}", "01".repeat(32)),
+            "timestamp": null,
+            "index": 0,
+            "pallet": "Test",
+            "variant": "A",
+            "fields_json": [1],
+        })
+    );
+    assert_eq!(bodies[1]["index"], 2);
+    assert_eq!(bodies[1]["fields_json"], json!([2]));
+    assert_eq!(webhook.sent(), 2);
+}
+
+#[tokio::test]
+async fn test_retries_server_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let webhook = WebhookHandler::new(server.uri())
+        .unwrap()
+        .with_filter(EventFilter::event("Test", "B"))
+        .with_retry(fast_retry());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(7)
+        .build();
+    assert!(handle(&webhook, &ctx).await.is_empty());
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    assert_eq!(webhook.sent(), 1);
+    assert_eq!(webhook.failed(), 0);
+}
+
+#[tokio::test]
+async fn test_client_errors_fail_without_retry() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(400))
+        .mount(&server)
+        .await;
+
+    let webhook = WebhookHandler::new(server.uri())
+        .unwrap()
+        .with_filter(EventFilter::event("Test", "B"))
+        .with_retry(fast_retry());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(7)
+        .build();
+    let errors = handle(&webhook, &ctx).await;
+
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        &errors[0],
+        IndexerError::HandlerFailed {
+            block: 7,
+            retryable: false,
+            ..
+        }
+    ));
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    assert_eq!(webhook.failed(), 1);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_retries() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let webhook = WebhookHandler::new(server.uri())
+        .unwrap()
+        .with_filter(EventFilter::event("Test", "B"))
+        .with_retry(fast_retry());
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(7)
+        .build();
+    let errors = handle(&webhook, &ctx).await;
+
+    assert!(matches!(
+        &errors[..],
+        [IndexerError::HandlerFailed {
+            retryable: true,
+            ..
+        }]
+    ));
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    assert_eq!(webhook.failed(), 1);
+}
+
+#[tokio::test]
+async fn test_batches_by_size() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let webhook = WebhookHandler::new(server.uri())
+        .unwrap()
+        .with_batching(2, Duration::from_secs(60));
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(7)
+        .build();
+    assert!(handle(&webhook, &ctx).await.is_empty());
+
+    let sent = bodies(&server).await;
+    assert_eq!(sent.len(), 1);
+    let batch = sent[0].as_array().unwrap();
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch[0]["variant"], "A");
+    assert_eq!(batch[1]["variant"], "B");
+    // The third event is only in memory, so block 7 is not durable yet.
+    let checkpoint = Handler::<SubstrateConfig>::on_checkpoint(&webhook, 7).await;
+    assert_eq!(checkpoint.unwrap(), Some(6));
+
+    webhook.flush().await.unwrap();
+    let checkpoint = Handler::<SubstrateConfig>::on_checkpoint(&webhook, 7).await;
+    assert_eq!(checkpoint.unwrap(), Some(7));
+    let sent = bodies(&server).await;
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1].as_array().unwrap().len(), 1);
+    assert_eq!(sent[1][0]["index"], 2);
+    assert_eq!(webhook.sent(), 3);
+}
+
+#[tokio::test]
+async fn test_batches_by_age() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let webhook = WebhookHandler::new(server.uri())
+        .unwrap()
+        .with_filter(EventFilter::event("Test", "B"))
+        .with_batching(100, Duration::from_millis(20));
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(7)
+        .build();
+    assert!(handle(&webhook, &ctx).await.is_empty());
+    assert!(bodies(&server).await.is_empty());
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    let next = Context::<SubstrateConfig>::builder()
+        .block_number(8)
+        .build();
+    webhook.handle_block(&next, &[]).await.unwrap();
+
+    let sent = bodies(&server).await;
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0][0]["block_number"], 7);
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn test_deliveries_feed_metrics() {
+    use flamewire_bittensor_indexer::IndexerMetrics;
+    use std::sync::Arc;
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let metrics = Arc::new(IndexerMetrics::new().unwrap());
+    let webhook = WebhookHandler::new(server.uri()).unwrap();
+    let ctx = Context::<SubstrateConfig>::builder()
+        .block_number(7)
+        .metrics(metrics.clone())
+        .build();
+    assert!(handle(&webhook, &ctx).await.is_empty());
+
+    let text = metrics.render().unwrap();
+    let line = text
+        .lines()
+        .find(|line| line.starts_with("indexer_sink_events_total{") && line.contains("sent"))
+        .unwrap();
+    assert!(line.contains(r#"sink="webhook""#));
+    assert!(line.ends_with(" 3"));
+}